@@ -15,13 +15,18 @@
 
 use got_engine::types::*;
 use got_engine::engine::{Action, MusterAction2};
+use got_engine::replay::{Annotation, AnnotationTarget};
 use got_engine::visibility::PlayerView;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Trait that all AI agents must implement.
 /// Each method corresponds to a pending decision type.
 /// The agent receives a PlayerView and must return an Action.
 pub trait Agent: Send + Sync {
-    /// Human-readable name for this agent (e.g., "Aggressive", "Turtle").
+    /// Name results are recorded under. Agents built from the registry
+    /// report their canonical spec (e.g. "heuristic").
     fn name(&self) -> &str;
 
     /// The house this agent is playing.
@@ -30,73 +35,30 @@ pub trait Agent: Send + Sync {
     /// Make a decision based on the current player view.
     /// This is the universal entry point — dispatches to specific methods.
     fn decide(&mut self, view: &PlayerView) -> Action {
-        match view.pending.as_ref().expect("No pending decision") {
-            PendingDecision::PlaceOrders { .. } => {
-                Action::PlaceOrders(self.place_orders(view))
-            }
-            PendingDecision::ChooseRaid { from_area, valid_targets, .. } => {
-                Action::Raid(self.choose_raid(view, *from_area, valid_targets))
-            }
-            PendingDecision::ChooseMarch { from_area, valid_destinations, .. } => {
-                let (to, units) = self.choose_march(view, *from_area, valid_destinations);
-                Action::March { to, unit_indices: units }
-            }
-            PendingDecision::LeavePowerToken { area_id, .. } => {
-                Action::LeavePowerToken(self.leave_power_token(view, *area_id))
-            }
-            PendingDecision::SupportDeclaration { attacker, defender, .. } => {
-                Action::DeclareSupport(self.declare_support(view, *attacker, *defender))
-            }
-            PendingDecision::SelectHouseCard { available_cards, .. } => {
-                Action::SelectCard(self.select_house_card(view, available_cards))
-            }
-            PendingDecision::UseValyrianBlade { .. } => {
-                Action::UseValyrianBlade(self.use_valyrian_blade(view))
-            }
-            PendingDecision::Bidding { bidding_type, track, .. } => {
-                Action::Bid(self.submit_bid(view, *bidding_type, *track))
-            }
-            PendingDecision::WesterosChoice { options, .. } => {
-                Action::WesterosChoice(self.westeros_choice(view, options))
-            }
-            PendingDecision::Muster { areas, .. } => {
-                Action::Muster(self.choose_muster(view, areas))
-            }
-            PendingDecision::Retreat { possible_areas, .. } => {
-                Action::Retreat(self.choose_retreat(view, possible_areas))
-            }
-            PendingDecision::Reconcile { area_id, .. } => {
-                let (aid, idx) = self.choose_reconcile(view, *area_id);
-                Action::Reconcile(aid, idx)
-            }
-            PendingDecision::MessengerRaven { .. } => {
-                Action::MessengerRaven(self.use_messenger_raven(view))
-            }
-            PendingDecision::AeronSwap { .. } => {
-                Action::AeronSwap(self.use_aeron(view))
-            }
-            PendingDecision::TyrionReplace { .. } => {
-                Action::TyrionReplace(self.tyrion_replacement(view))
-            }
-            PendingDecision::PatchfaceDiscard { visible_cards, .. } => {
-                Action::PatchfaceDiscard(self.patchface_discard(view, visible_cards))
-            }
-            PendingDecision::RobbRetreat { possible_areas, .. } => {
-                Action::RobbRetreat(self.robb_retreat(view, possible_areas))
-            }
-            PendingDecision::WildlingPenaltyChoice { options, .. } => {
-                Action::WildlingPenalty(self.wildling_penalty(view, options))
-            }
-            PendingDecision::CerseiRemoveOrder { .. } => {
-                Action::CerseiRemoveOrder(self.cersei_remove_order(view))
-            }
-            PendingDecision::DoranChooseTrack { .. } => {
-                Action::DoranChooseTrack(self.doran_choose_track(view))
-            }
-            PendingDecision::QueenOfThornsRemoveOrder { .. } => {
-                Action::QueenOfThorns(self.queen_of_thorns(view))
-            }
-        }
+        dispatch(self, view)
+    }
+
+    /// What the agent weighed for its most recent decision: candidate
+    /// actions with scores or visit counts. None for agents that don't
+    /// score their options, or when the last decision wasn't scored.
+    fn last_decision_info(&self) -> Option<DecisionInfo> {
+        None
+    }
+
+    /// Called before the first decision of a game played as part of a
+    /// match. Agents are built afresh for every game, so `ctx.history` is
+    /// all they know of the match's earlier games.
+    fn on_match_start(&mut self, _ctx: &MatchContext) {}
+
+    /// Called when a game of a match is over, with that game last in
+    /// `ctx.history`.
+    fn on_match_end(&mut self, _ctx: &MatchContext) {}
+
+    /// Whether the agent reads more of its view than the board, its own
+    /// decision, hand and tokens. Agents returning false are handed a
+    /// `minimal_view`, which skips the costly per-house summaries.
+    fn needs_view(&self) -> bool {
+        true
     }
 
     // ── Individual decision methods ────────────────────────────────────
@@ -107,11 +69,15 @@ pub trait Agent: Send + Sync {
     /// Place orders on all areas with units. Returns Vec<(area_id, token_index)>.
     fn place_orders(&mut self, view: &PlayerView) -> Vec<(AreaId, u8)>;
 
+    /// Choose which of several eligible orders to resolve this turn.
+    fn choose_order_to_resolve(&mut self, view: &PlayerView, order_type: OrderType, candidates: &[AreaId]) -> AreaId;
+
     /// Choose raid target. None = skip raid.
     fn choose_raid(&mut self, view: &PlayerView, from: AreaId, targets: &[AreaId]) -> Option<AreaId>;
 
-    /// Choose march destination + which unit indices to move.
-    fn choose_march(&mut self, view: &PlayerView, from: AreaId, destinations: &[AreaId]) -> (AreaId, Vec<usize>);
+    /// Choose march destination + which unit indices to move, out of the
+    /// `selectable` ones.
+    fn choose_march(&mut self, view: &PlayerView, from: AreaId, destinations: &[AreaId], selectable: &[usize]) -> (AreaId, Vec<usize>);
 
     /// Whether to leave a power token when vacating an area.
     fn leave_power_token(&mut self, view: &PlayerView, area: AreaId) -> bool;
@@ -143,11 +109,12 @@ pub trait Agent: Send + Sync {
     /// Messenger Raven: swap an order. None = don't swap.
     fn use_messenger_raven(&mut self, view: &PlayerView) -> Option<(AreaId, u8)>;
 
-    /// Aeron Damphair: pay 2 power to swap card. None = decline.
-    fn use_aeron(&mut self, view: &PlayerView) -> Option<HouseCardId>;
+    /// Aeron Damphair: pay to play one of `options` instead. None = decline.
+    fn use_aeron(&mut self, view: &PlayerView, options: &[HouseCardId]) -> Option<HouseCardId>;
 
-    /// Tyrion: choose replacement card.
-    fn tyrion_replacement(&mut self, view: &PlayerView) -> HouseCardId;
+    /// Tyrion: choose a replacement among `options` (the hand but the
+    /// cancelled card).
+    fn tyrion_replacement(&mut self, view: &PlayerView, options: &[HouseCardId]) -> HouseCardId;
 
     /// Patchface: choose card to discard from opponent's hand.
     fn patchface_discard(&mut self, view: &PlayerView, visible: &[HouseCardId]) -> HouseCardId;
@@ -167,3 +134,166 @@ pub trait Agent: Send + Sync {
     /// Queen of Thorns: choose area whose opponent order to remove.
     fn queen_of_thorns(&mut self, view: &PlayerView) -> AreaId;
 }
+
+/// The default `decide`: hands the pending decision to the matching
+/// per-decision method. Agents overriding `decide` to do bookkeeping
+/// around every decision call this for the decision itself.
+pub fn dispatch<A: Agent + ?Sized>(agent: &mut A, view: &PlayerView) -> Action {
+    match view.pending.as_ref().expect("No pending decision") {
+        PendingDecision::PlaceOrders { .. } => {
+            Action::PlaceOrders(agent.place_orders(view))
+        }
+        PendingDecision::ChooseOrderToResolve { order_type, candidates, .. } => {
+            Action::ResolveOrder(agent.choose_order_to_resolve(view, *order_type, candidates))
+        }
+        PendingDecision::ChooseRaid { from_area, valid_targets, .. } => {
+            Action::Raid(agent.choose_raid(view, *from_area, valid_targets))
+        }
+        PendingDecision::ChooseMarch { from_area, valid_destinations, selectable_units, .. } => {
+            let (to, units) = agent.choose_march(view, *from_area, valid_destinations, selectable_units);
+            Action::March { to, unit_indices: units }
+        }
+        PendingDecision::LeavePowerToken { area_id, .. } => {
+            Action::LeavePowerToken(agent.leave_power_token(view, *area_id))
+        }
+        PendingDecision::SupportDeclaration { attacker, defender, .. } => {
+            Action::DeclareSupport(agent.declare_support(view, *attacker, *defender))
+        }
+        PendingDecision::SelectHouseCard { available_cards, .. } => {
+            Action::SelectCard(agent.select_house_card(view, available_cards))
+        }
+        PendingDecision::UseValyrianBlade { .. } => {
+            Action::UseValyrianBlade(agent.use_valyrian_blade(view))
+        }
+        PendingDecision::Bidding { bidding_type, track, .. } => {
+            Action::Bid(agent.submit_bid(view, *bidding_type, *track))
+        }
+        PendingDecision::WesterosChoice { options, .. } => {
+            Action::WesterosChoice(agent.westeros_choice(view, options))
+        }
+        PendingDecision::Muster { areas, .. } => {
+            Action::Muster(agent.choose_muster(view, areas))
+        }
+        PendingDecision::Retreat { possible_areas, .. } => {
+            Action::Retreat(agent.choose_retreat(view, possible_areas))
+        }
+        PendingDecision::Reconcile { area_id, .. } => {
+            let (aid, idx) = agent.choose_reconcile(view, *area_id);
+            Action::Reconcile(aid, idx)
+        }
+        PendingDecision::MessengerRaven { .. } => {
+            Action::MessengerRaven(agent.use_messenger_raven(view))
+        }
+        PendingDecision::AeronSwap { available_cards, .. } => {
+            Action::AeronSwap(agent.use_aeron(view, available_cards))
+        }
+        PendingDecision::TyrionReplace { available_cards, .. } => {
+            Action::TyrionReplace(agent.tyrion_replacement(view, available_cards))
+        }
+        PendingDecision::PatchfaceDiscard { visible_cards, .. } => {
+            Action::PatchfaceDiscard(agent.patchface_discard(view, visible_cards))
+        }
+        PendingDecision::RobbRetreat { possible_areas, .. } => {
+            Action::RobbRetreat(agent.robb_retreat(view, possible_areas))
+        }
+        PendingDecision::WildlingPenaltyChoice { options, .. } => {
+            Action::WildlingPenalty(agent.wildling_penalty(view, options))
+        }
+        PendingDecision::CerseiRemoveOrder { .. } => {
+            Action::CerseiRemoveOrder(agent.cersei_remove_order(view))
+        }
+        PendingDecision::DoranChooseTrack { .. } => {
+            Action::DoranChooseTrack(agent.doran_choose_track(view))
+        }
+        PendingDecision::QueenOfThornsRemoveOrder { .. } => {
+            Action::QueenOfThorns(agent.queen_of_thorns(view))
+        }
+    }
+}
+
+// ── Decision introspection ─────────────────────────────────────────────
+
+/// One option an agent considered.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Candidate {
+    pub action: Action,
+    /// Agent-specific score; higher is better.
+    pub score: f64,
+    /// Search visits, for agents that sample.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visits: Option<u32>,
+}
+
+/// What an agent weighed for one decision.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct DecisionInfo {
+    /// Best first.
+    pub candidates: Vec<Candidate>,
+    /// The agent's estimate of its chance to win from this position.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub win_probability: Option<f64>,
+    /// Search statistics, e.g. iterations, nodes, time_ms.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub stats: BTreeMap<String, f64>,
+}
+
+impl DecisionInfo {
+    /// Info from scored candidates, sorted best first.
+    pub fn scored(candidates: impl IntoIterator<Item = (Action, f64)>) -> Self {
+        let mut candidates: Vec<Candidate> = candidates.into_iter()
+            .map(|(action, score)| Candidate { action, score, visits: None })
+            .collect();
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+        DecisionInfo { candidates, ..Default::default() }
+    }
+
+    pub fn best(&self) -> Option<&Candidate> {
+        self.candidates.first()
+    }
+
+    /// The info as an annotation on `target`, written by `author`.
+    pub fn annotation(&self, target: AnnotationTarget, author: &str) -> Annotation {
+        let mut note = Annotation::new(target, author)
+            .with("candidates", serde_json::to_value(&self.candidates).unwrap());
+        if let Some(p) = self.win_probability {
+            note = note.with("win_probability", p);
+        }
+        for (key, value) in &self.stats {
+            note = note.with(key.clone(), *value);
+        }
+        note
+    }
+}
+
+// ── Matches ────────────────────────────────────────────────────────────
+
+/// Where a game stands within a match: the same seats playing several
+/// games in a row, with the houses rotating between them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct MatchContext {
+    /// Games in the match.
+    pub games: usize,
+    /// The game being played, 0 = first.
+    pub game: usize,
+    /// The agent's seat, the same in every game of the match.
+    pub seat: usize,
+    /// Finished games, oldest first.
+    pub history: Vec<MatchGame>,
+}
+
+/// One finished game of a match, seen by seat.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct MatchGame {
+    pub seed: u64,
+    /// The house each seat played.
+    pub houses: Vec<HouseName>,
+    /// Each seat's finishing place, 1 = winner.
+    pub places: Vec<u8>,
+}
+
+impl MatchContext {
+    /// Games `seat` has won so far.
+    pub fn wins(&self, seat: usize) -> usize {
+        self.history.iter().filter(|g| g.places.get(seat) == Some(&1)).count()
+    }
+}
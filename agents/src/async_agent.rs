@@ -0,0 +1,174 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Async Agents — agents whose answers arrive over the network
+//
+// An `AsyncAgent` returns its action as a future, so an agent waiting on
+// a socket or a child process holds no thread while it waits. The engine
+// and the game loop stay synchronous; the adapters bridge the two:
+//
+//   SyncAdapter    any `Agent` as an `AsyncAgent` (answers at once)
+//   BlockingAgent  an `AsyncAgent` as an `Agent`, blocking on a tokio
+//                  runtime handle for each answer (feature `async`)
+//
+// `got_tournament::async_runner` plays many such games at once on tokio.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::agent::Agent;
+use got_engine::engine::Action;
+use got_engine::visibility::PlayerView;
+use got_engine::types::HouseName;
+use std::future::Future;
+use std::pin::Pin;
+
+/// The answer an `AsyncAgent` is working on.
+pub type DecideFuture<'a> = Pin<Box<dyn Future<Output = Action> + Send + 'a>>;
+
+/// An agent that answers through a future. Like `Agent::decide`, the
+/// action must answer the view's pending decision.
+pub trait AsyncAgent: Send + Sync {
+    fn name(&self) -> &str;
+    fn house(&self) -> HouseName;
+    fn decide<'a>(&'a mut self, view: &'a PlayerView) -> DecideFuture<'a>;
+}
+
+/// A synchronous agent playing where an `AsyncAgent` is expected.
+pub struct SyncAdapter(pub Box<dyn Agent>);
+
+impl AsyncAgent for SyncAdapter {
+    fn name(&self) -> &str { self.0.name() }
+    fn house(&self) -> HouseName { self.0.house() }
+
+    fn decide<'a>(&'a mut self, view: &'a PlayerView) -> DecideFuture<'a> {
+        Box::pin(std::future::ready(self.0.decide(view)))
+    }
+}
+
+#[cfg(feature = "async")]
+pub use blocking::BlockingAgent;
+
+#[cfg(feature = "async")]
+mod blocking {
+    use super::AsyncAgent;
+    use crate::agent::Agent;
+    use crate::heuristic::HeuristicAgent;
+    use got_engine::engine::{Action, MusterAction2};
+    use got_engine::types::*;
+    use got_engine::visibility::PlayerView;
+    use tokio::runtime::Handle;
+
+    /// An `AsyncAgent` behind the synchronous `Agent` trait. Each decision
+    /// blocks the calling thread on `handle` until the future resolves, so
+    /// it must not be called from inside an async task; the tournament's
+    /// async runner plays each game on a blocking thread for this.
+    pub struct BlockingAgent {
+        inner: Box<dyn AsyncAgent>,
+        handle: Handle,
+        fallback: HeuristicAgent,
+    }
+
+    impl BlockingAgent {
+        pub fn new(inner: Box<dyn AsyncAgent>, handle: Handle, seed: u64) -> Self {
+            let fallback = HeuristicAgent::new(inner.house(), seed);
+            BlockingAgent { inner, handle, fallback }
+        }
+    }
+
+    impl Agent for BlockingAgent {
+        fn name(&self) -> &str { self.inner.name() }
+        fn house(&self) -> HouseName { self.inner.house() }
+
+        fn decide(&mut self, view: &PlayerView) -> Action {
+            self.handle.block_on(self.inner.decide(view))
+        }
+
+        // Answers arrive as whole actions through `decide`; the
+        // per-decision methods are only reached by callers that skip it.
+
+        fn place_orders(&mut self, view: &PlayerView) -> Vec<(AreaId, u8)> {
+            self.fallback.place_orders(view)
+        }
+
+        fn choose_order_to_resolve(&mut self, view: &PlayerView, order_type: OrderType, candidates: &[AreaId]) -> AreaId {
+            self.fallback.choose_order_to_resolve(view, order_type, candidates)
+        }
+
+        fn choose_raid(&mut self, view: &PlayerView, from: AreaId, targets: &[AreaId]) -> Option<AreaId> {
+            self.fallback.choose_raid(view, from, targets)
+        }
+
+        fn choose_march(&mut self, view: &PlayerView, from: AreaId, destinations: &[AreaId], selectable: &[usize]) -> (AreaId, Vec<usize>) {
+            self.fallback.choose_march(view, from, destinations, selectable)
+        }
+
+        fn leave_power_token(&mut self, view: &PlayerView, area: AreaId) -> bool {
+            self.fallback.leave_power_token(view, area)
+        }
+
+        fn declare_support(&mut self, view: &PlayerView, attacker: HouseName, defender: HouseName) -> SupportChoice {
+            self.fallback.declare_support(view, attacker, defender)
+        }
+
+        fn select_house_card(&mut self, view: &PlayerView, available: &[HouseCardId]) -> HouseCardId {
+            self.fallback.select_house_card(view, available)
+        }
+
+        fn use_valyrian_blade(&mut self, view: &PlayerView) -> bool {
+            self.fallback.use_valyrian_blade(view)
+        }
+
+        fn submit_bid(&mut self, view: &PlayerView, bid_type: BiddingType, track: Option<Track>) -> u8 {
+            self.fallback.submit_bid(view, bid_type, track)
+        }
+
+        fn westeros_choice(&mut self, view: &PlayerView, options: &[String]) -> usize {
+            self.fallback.westeros_choice(view, options)
+        }
+
+        fn choose_muster(&mut self, view: &PlayerView, areas: &[MusterArea]) -> Vec<(AreaId, MusterAction2)> {
+            self.fallback.choose_muster(view, areas)
+        }
+
+        fn choose_retreat(&mut self, view: &PlayerView, options: &[AreaId]) -> AreaId {
+            self.fallback.choose_retreat(view, options)
+        }
+
+        fn choose_reconcile(&mut self, view: &PlayerView, area: AreaId) -> (AreaId, usize) {
+            self.fallback.choose_reconcile(view, area)
+        }
+
+        fn use_messenger_raven(&mut self, view: &PlayerView) -> Option<(AreaId, u8)> {
+            self.fallback.use_messenger_raven(view)
+        }
+
+        fn use_aeron(&mut self, view: &PlayerView, options: &[HouseCardId]) -> Option<HouseCardId> {
+            self.fallback.use_aeron(view, options)
+        }
+
+        fn tyrion_replacement(&mut self, view: &PlayerView, options: &[HouseCardId]) -> HouseCardId {
+            self.fallback.tyrion_replacement(view, options)
+        }
+
+        fn patchface_discard(&mut self, view: &PlayerView, visible: &[HouseCardId]) -> HouseCardId {
+            self.fallback.patchface_discard(view, visible)
+        }
+
+        fn robb_retreat(&mut self, view: &PlayerView, options: &[AreaId]) -> AreaId {
+            self.fallback.robb_retreat(view, options)
+        }
+
+        fn wildling_penalty(&mut self, view: &PlayerView, options: &[String]) -> usize {
+            self.fallback.wildling_penalty(view, options)
+        }
+
+        fn cersei_remove_order(&mut self, view: &PlayerView) -> AreaId {
+            self.fallback.cersei_remove_order(view)
+        }
+
+        fn doran_choose_track(&mut self, view: &PlayerView) -> Track {
+            self.fallback.doran_choose_track(view)
+        }
+
+        fn queen_of_thorns(&mut self, view: &PlayerView) -> AreaId {
+            self.fallback.queen_of_thorns(view)
+        }
+    }
+}
@@ -0,0 +1,441 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Cloned Agent — imitates recorded play with a learned policy
+//
+// The model is one multinomial logistic regression head per decision
+// kind, over the features from `got_engine::encoding`. It is fitted on an
+// exported dataset (see `got-runner train-clone`) and saved as a small
+// binary file. At play time each decision is answered with the legal
+// option the head scores highest; decisions whose head was never trained,
+// and the ones whose legality the agent does not model (mustering, the
+// Messenger Raven, Aeron), are passed to a HeuristicAgent.
+//
+// Spec: `cloned{model=path/to/model.bin}`
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::agent::Agent;
+use crate::heuristic::HeuristicAgent;
+use got_engine::encoding::{card_index, encode_observation, DecisionKind, OBS_LEN};
+use got_engine::engine::MusterAction2;
+use got_engine::map::NUM_AREAS;
+use got_engine::types::*;
+use got_engine::visibility::PlayerView;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex, OnceLock};
+
+const MAGIC: &[u8; 8] = b"GOTCLONE";
+const VERSION: u32 = 1;
+
+// ── Model ──────────────────────────────────────────────────────────────
+
+/// Softmax regression over one decision kind's action space.
+#[derive(Debug, Clone)]
+pub struct LinearHead {
+    pub classes: usize,
+    /// `classes` rows of OBS_LEN weights followed by a bias.
+    pub weights: Vec<f32>,
+}
+
+impl LinearHead {
+    fn new(classes: usize) -> Self {
+        LinearHead { classes, weights: vec![0.0; classes * (OBS_LEN + 1)] }
+    }
+
+    pub fn logits(&self, obs: &[f32]) -> Vec<f32> {
+        self.weights.chunks_exact(OBS_LEN + 1)
+            .map(|row| row[OBS_LEN] + row[..OBS_LEN].iter().zip(obs).map(|(w, x)| w * x).sum::<f32>())
+            .collect()
+    }
+
+    /// One SGD step on the cross-entropy loss for a single example.
+    fn step(&mut self, obs: &[f32], target: usize, lr: f32, l2: f32) {
+        let logits = self.logits(obs);
+        let max = logits.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        let exp: Vec<f32> = logits.iter().map(|&z| (z - max).exp()).collect();
+        let sum: f32 = exp.iter().sum();
+        for (c, row) in self.weights.chunks_exact_mut(OBS_LEN + 1).enumerate() {
+            let grad = exp[c] / sum - (c == target) as u8 as f32;
+            for (w, &x) in row[..OBS_LEN].iter_mut().zip(obs) {
+                *w -= lr * (grad * x + l2 * *w);
+            }
+            row[OBS_LEN] -= lr * grad;
+        }
+    }
+}
+
+/// One training example: the observation a decision was made from and
+/// the index of the answer given.
+#[derive(Debug, Clone, Copy)]
+pub struct Example<'a> {
+    pub kind: DecisionKind,
+    pub obs: &'a [f32],
+    pub action: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct FitOptions {
+    pub epochs: usize,
+    pub learning_rate: f32,
+    pub l2: f32,
+    /// Seed for the per-epoch shuffle.
+    pub seed: u64,
+}
+
+impl Default for FitOptions {
+    fn default() -> Self {
+        FitOptions { epochs: 5, learning_rate: 0.05, l2: 1e-5, seed: 0 }
+    }
+}
+
+/// A head per decision kind; kinds absent from the training data have none.
+#[derive(Debug, Clone, Default)]
+pub struct PolicyModel {
+    heads: HashMap<DecisionKind, LinearHead>,
+}
+
+impl PolicyModel {
+    /// Fit a head for every decision kind present in `examples`.
+    pub fn fit(examples: &[Example], opts: &FitOptions) -> Self {
+        let mut model = PolicyModel::default();
+        model.train(examples, opts);
+        model
+    }
+
+    /// Continue training from the current weights; kinds without a head
+    /// get a fresh one.
+    pub fn train(&mut self, examples: &[Example], opts: &FitOptions) {
+        let mut rng = ChaCha8Rng::seed_from_u64(opts.seed);
+        let mut order: Vec<usize> = (0..examples.len()).collect();
+        for epoch in 0..opts.epochs {
+            order.shuffle(&mut rng);
+            let lr = opts.learning_rate / (1.0 + epoch as f32);
+            for &i in &order {
+                let ex = &examples[i];
+                let classes = ex.kind.action_space() as usize;
+                if ex.action as usize >= classes || ex.obs.len() != OBS_LEN {
+                    continue;
+                }
+                self.heads.entry(ex.kind)
+                    .or_insert_with(|| LinearHead::new(classes))
+                    .step(ex.obs, ex.action as usize, lr, opts.l2);
+            }
+        }
+    }
+
+    pub fn head(&self, kind: DecisionKind) -> Option<&LinearHead> {
+        self.heads.get(&kind)
+    }
+
+    /// Kinds with a trained head, in `DecisionKind::ALL` order.
+    pub fn kinds(&self) -> Vec<DecisionKind> {
+        DecisionKind::ALL.into_iter().filter(|k| self.heads.contains_key(k)).collect()
+    }
+
+    /// Highest-scoring index among `legal`. None when the kind has no head
+    /// or nothing is legal.
+    pub fn best(&self, kind: DecisionKind, obs: &[f32], legal: &[u32]) -> Option<u32> {
+        let logits = self.head(kind)?.logits(obs);
+        legal.iter()
+            .copied()
+            .filter(|&a| (a as usize) < logits.len())
+            .max_by(|&a, &b| logits[a as usize].total_cmp(&logits[b as usize]))
+    }
+
+    /// Per-kind (correct, total) of the unrestricted argmax on `examples`.
+    pub fn accuracy(&self, examples: &[Example]) -> Vec<(DecisionKind, usize, usize)> {
+        let mut counts: HashMap<DecisionKind, (usize, usize)> = HashMap::new();
+        for ex in examples {
+            let entry = counts.entry(ex.kind).or_default();
+            entry.1 += 1;
+            if let Some(head) = self.head(ex.kind) {
+                let logits = head.logits(ex.obs);
+                let argmax = (0..logits.len()).max_by(|&a, &b| logits[a].total_cmp(&logits[b]));
+                if argmax == Some(ex.action as usize) {
+                    entry.0 += 1;
+                }
+            }
+        }
+        DecisionKind::ALL.into_iter()
+            .filter_map(|k| counts.get(&k).map(|&(c, t)| (k, c, t)))
+            .collect()
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        for v in [VERSION, OBS_LEN as u32, self.heads.len() as u32] {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        for kind in self.kinds() {
+            let head = &self.heads[&kind];
+            out.extend_from_slice(&(kind.index() as u32).to_le_bytes());
+            out.extend_from_slice(&(head.classes as u32).to_le_bytes());
+            for w in &head.weights {
+                out.extend_from_slice(&w.to_le_bytes());
+            }
+        }
+        fs::File::create(path)
+            .and_then(|mut f| f.write_all(&out))
+            .map_err(|e| format!("Cannot write model {}: {}", path, e))
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let mut bytes = Vec::new();
+        fs::File::open(path)
+            .and_then(|mut f| f.read_to_end(&mut bytes))
+            .map_err(|e| format!("Cannot read model {}: {}", path, e))?;
+        let bad = || format!("{} is not a valid cloned-agent model", path);
+        let mut r = ByteReader { bytes: &bytes, pos: 0 };
+        if r.take(8).ok_or_else(bad)? != MAGIC {
+            return Err(bad());
+        }
+        let version = r.u32().ok_or_else(bad)?;
+        if version != VERSION {
+            return Err(format!("{}: unsupported model version {}", path, version));
+        }
+        let obs_len = r.u32().ok_or_else(bad)? as usize;
+        if obs_len != OBS_LEN {
+            return Err(format!("{}: model expects {} features, this build encodes {}", path, obs_len, OBS_LEN));
+        }
+        let mut model = PolicyModel::default();
+        for _ in 0..r.u32().ok_or_else(bad)? {
+            let kind = *DecisionKind::ALL.get(r.u32().ok_or_else(bad)? as usize).ok_or_else(bad)?;
+            let classes = r.u32().ok_or_else(bad)? as usize;
+            if classes != kind.action_space() as usize {
+                return Err(bad());
+            }
+            let weights = r.take(classes * (OBS_LEN + 1) * 4).ok_or_else(bad)?
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+                .collect();
+            model.heads.insert(kind, LinearHead { classes, weights });
+        }
+        Ok(model)
+    }
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let s = self.bytes.get(self.pos..self.pos + n)?;
+        self.pos += n;
+        Some(s)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+    }
+}
+
+/// Models are shared between all seats and games that load the same file.
+fn cached_model(path: &str) -> Result<Arc<PolicyModel>, String> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Arc<PolicyModel>>>> = OnceLock::new();
+    let mut cache = CACHE.get_or_init(Default::default).lock().unwrap();
+    if let Some(m) = cache.get(path) {
+        return Ok(m.clone());
+    }
+    let model = Arc::new(PolicyModel::load(path)?);
+    cache.insert(path.to_string(), model.clone());
+    Ok(model)
+}
+
+// ── Agent ──────────────────────────────────────────────────────────────
+
+pub struct ClonedAgent {
+    house: HouseName,
+    name: String,
+    model: Arc<PolicyModel>,
+    fallback: HeuristicAgent,
+}
+
+impl ClonedAgent {
+    pub fn new(house: HouseName, seed: u64, model: Arc<PolicyModel>, name: String) -> Self {
+        ClonedAgent { house, name, model, fallback: HeuristicAgent::new(house, seed) }
+    }
+
+    /// Load (or reuse) the model at `path`.
+    pub fn from_file(house: HouseName, seed: u64, path: &str, name: String) -> Result<Self, String> {
+        Ok(Self::new(house, seed, cached_model(path)?, name))
+    }
+
+    fn pick(&self, view: &PlayerView, focus: Option<AreaId>, legal: &[u32]) -> Option<u32> {
+        let kind = DecisionKind::of(view.pending.as_ref()?);
+        self.model.head(kind)?;
+        self.model.best(kind, &encode_observation(view, focus), legal)
+    }
+
+    fn pick_area(&self, view: &PlayerView, focus: Option<AreaId>, options: &[AreaId]) -> Option<AreaId> {
+        let legal: Vec<u32> = options.iter().map(|a| a.0 as u32).collect();
+        self.pick(view, focus, &legal).map(|a| AreaId(a as u8))
+    }
+
+    fn pick_card(&self, view: &PlayerView, options: &[HouseCardId]) -> Option<HouseCardId> {
+        let legal: Vec<u32> = options.iter().map(|&c| card_index(c)).collect();
+        let best = self.pick(view, None, &legal)?;
+        options.iter().copied().find(|&c| card_index(c) == best)
+    }
+
+    fn my_power(&self, view: &PlayerView) -> u8 {
+        view.house_info.get(&self.house).map_or(0, |h| h.power)
+    }
+}
+
+impl Agent for ClonedAgent {
+    fn name(&self) -> &str { &self.name }
+    fn house(&self) -> HouseName { self.house }
+
+    fn place_orders(&mut self, view: &PlayerView) -> Vec<(AreaId, u8)> {
+        if self.model.head(DecisionKind::PlaceOrders).is_none() {
+            return self.fallback.place_orders(view);
+        }
+        let mut orders = Vec::new();
+        let mut used: Vec<u8> = Vec::new();
+        for area in view.areas.iter().filter(|a| a.house == Some(self.house) && !a.units.is_empty()) {
+            let legal: Vec<u32> = view.placeable_tokens(&used).into_iter().map(u32::from).collect();
+            if let Some(t) = self.pick(view, Some(area.id), &legal) {
+                orders.push((area.id, t as u8));
+                used.push(t as u8);
+            }
+        }
+        orders
+    }
+
+    fn choose_order_to_resolve(&mut self, view: &PlayerView, order_type: OrderType, candidates: &[AreaId]) -> AreaId {
+        self.pick_area(view, None, candidates)
+            .unwrap_or_else(|| self.fallback.choose_order_to_resolve(view, order_type, candidates))
+    }
+
+    fn choose_raid(&mut self, view: &PlayerView, from: AreaId, targets: &[AreaId]) -> Option<AreaId> {
+        let mut legal: Vec<u32> = targets.iter().map(|a| a.0 as u32).collect();
+        legal.push(NUM_AREAS as u32);
+        match self.pick(view, Some(from), &legal) {
+            Some(a) if a < NUM_AREAS as u32 => Some(AreaId(a as u8)),
+            Some(_) => None,
+            None => self.fallback.choose_raid(view, from, targets),
+        }
+    }
+
+    fn choose_march(&mut self, view: &PlayerView, from: AreaId, destinations: &[AreaId], selectable: &[usize]) -> (AreaId, Vec<usize>) {
+        let mut legal: Vec<u32> = destinations.iter().map(|a| a.0 as u32).collect();
+        legal.push(NUM_AREAS as u32);
+        match self.pick(view, Some(from), &legal) {
+            Some(a) if a < NUM_AREAS as u32 => (AreaId(a as u8), selectable.to_vec()),
+            Some(_) => (from, vec![]),
+            None => self.fallback.choose_march(view, from, destinations, selectable),
+        }
+    }
+
+    fn leave_power_token(&mut self, view: &PlayerView, area: AreaId) -> bool {
+        let legal: &[u32] = if self.my_power(view) > 0 { &[0, 1] } else { &[0] };
+        self.pick(view, Some(area), legal)
+            .map_or_else(|| self.fallback.leave_power_token(view, area), |a| a == 1)
+    }
+
+    fn declare_support(&mut self, view: &PlayerView, attacker: HouseName, defender: HouseName) -> SupportChoice {
+        let focus = match view.pending {
+            Some(PendingDecision::SupportDeclaration { area_id, .. }) => Some(area_id),
+            _ => None,
+        };
+        match self.pick(view, focus, &[0, 1, 2]) {
+            Some(0) => SupportChoice::Attacker,
+            Some(1) => SupportChoice::Defender,
+            Some(_) => SupportChoice::None,
+            None => self.fallback.declare_support(view, attacker, defender),
+        }
+    }
+
+    fn select_house_card(&mut self, view: &PlayerView, available: &[HouseCardId]) -> HouseCardId {
+        self.pick_card(view, available)
+            .unwrap_or_else(|| self.fallback.select_house_card(view, available))
+    }
+
+    fn use_valyrian_blade(&mut self, view: &PlayerView) -> bool {
+        self.pick(view, None, &[0, 1])
+            .map_or_else(|| self.fallback.use_valyrian_blade(view), |a| a == 1)
+    }
+
+    fn submit_bid(&mut self, view: &PlayerView, bid_type: BiddingType, track: Option<Track>) -> u8 {
+        let max = self.my_power(view).min(20) as u32;
+        let legal: Vec<u32> = (0..=max).collect();
+        self.pick(view, None, &legal)
+            .map_or_else(|| self.fallback.submit_bid(view, bid_type, track), |a| a as u8)
+    }
+
+    fn westeros_choice(&mut self, view: &PlayerView, options: &[String]) -> usize {
+        let legal: Vec<u32> = (0..options.len() as u32).collect();
+        self.pick(view, None, &legal)
+            .map_or_else(|| self.fallback.westeros_choice(view, options), |a| a as usize)
+    }
+
+    fn choose_muster(&mut self, view: &PlayerView, areas: &[MusterArea]) -> Vec<(AreaId, MusterAction2)> {
+        self.fallback.choose_muster(view, areas)
+    }
+
+    fn choose_retreat(&mut self, view: &PlayerView, options: &[AreaId]) -> AreaId {
+        self.pick_area(view, None, options)
+            .unwrap_or_else(|| self.fallback.choose_retreat(view, options))
+    }
+
+    fn choose_reconcile(&mut self, view: &PlayerView, area: AreaId) -> (AreaId, usize) {
+        let units = view.areas[area.0 as usize].units.len();
+        let legal: Vec<u32> = (0..units.min(16) as u32).collect();
+        self.pick(view, Some(area), &legal)
+            .map_or_else(|| self.fallback.choose_reconcile(view, area), |i| (area, i as usize))
+    }
+
+    fn use_messenger_raven(&mut self, view: &PlayerView) -> Option<(AreaId, u8)> {
+        self.fallback.use_messenger_raven(view)
+    }
+
+    fn use_aeron(&mut self, view: &PlayerView, options: &[HouseCardId]) -> Option<HouseCardId> {
+        self.fallback.use_aeron(view, options)
+    }
+
+    fn tyrion_replacement(&mut self, view: &PlayerView, options: &[HouseCardId]) -> HouseCardId {
+        self.pick_card(view, options)
+            .unwrap_or_else(|| self.fallback.tyrion_replacement(view, options))
+    }
+
+    fn patchface_discard(&mut self, view: &PlayerView, visible: &[HouseCardId]) -> HouseCardId {
+        self.pick_card(view, visible)
+            .unwrap_or_else(|| self.fallback.patchface_discard(view, visible))
+    }
+
+    fn robb_retreat(&mut self, view: &PlayerView, options: &[AreaId]) -> AreaId {
+        self.pick_area(view, None, options)
+            .unwrap_or_else(|| self.fallback.robb_retreat(view, options))
+    }
+
+    fn wildling_penalty(&mut self, view: &PlayerView, options: &[String]) -> usize {
+        let legal: Vec<u32> = (0..options.len() as u32).collect();
+        self.pick(view, None, &legal)
+            .map_or_else(|| self.fallback.wildling_penalty(view, options), |a| a as usize)
+    }
+
+    fn cersei_remove_order(&mut self, view: &PlayerView) -> AreaId {
+        self.pick_area(view, None, view.removable_orders())
+            .unwrap_or_else(|| self.fallback.cersei_remove_order(view))
+    }
+
+    fn doran_choose_track(&mut self, view: &PlayerView) -> Track {
+        match self.pick(view, None, &[0, 1, 2]) {
+            Some(0) => Track::IronThrone,
+            Some(1) => Track::Fiefdoms,
+            Some(_) => Track::KingsCourt,
+            None => self.fallback.doran_choose_track(view),
+        }
+    }
+
+    fn queen_of_thorns(&mut self, view: &PlayerView) -> AreaId {
+        self.pick_area(view, None, view.removable_orders())
+            .unwrap_or_else(|| self.fallback.queen_of_thorns(view))
+    }
+}
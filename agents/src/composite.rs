@@ -0,0 +1,189 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Composite Agent — one agent per decision type
+//
+// Every decision goes to the base agent unless its type has an override,
+// in which case the override agent answers it. This isolates a single
+// subsystem of an agent: `composite{base=heuristic,select_house_card=random}`
+// is the heuristic agent with random card choice.
+//
+// Keys are `DecisionKind` keys (snake_case), values are agent specs;
+// nested specs keep their braces: `composite{base=cloned{model=m.bin},bidding=heuristic}`.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::agent::{Agent, DecisionInfo, MatchContext};
+use crate::registry::AgentSpec;
+use got_engine::encoding::DecisionKind;
+use got_engine::engine::{Action, MusterAction2};
+use got_engine::types::*;
+use got_engine::visibility::PlayerView;
+use std::collections::HashMap;
+
+pub struct CompositeAgent {
+    house: HouseName,
+    name: String,
+    base: Box<dyn Agent>,
+    overrides: HashMap<DecisionKind, Box<dyn Agent>>,
+    /// Kind of the last decision, to find the agent that answered it.
+    last: Option<DecisionKind>,
+}
+
+impl CompositeAgent {
+    pub fn new(house: HouseName, name: String, base: Box<dyn Agent>) -> Self {
+        CompositeAgent { house, name, base, overrides: HashMap::new(), last: None }
+    }
+
+    /// Route decisions of `kind` to `agent`.
+    pub fn with_override(mut self, kind: DecisionKind, agent: Box<dyn Agent>) -> Self {
+        self.overrides.insert(kind, agent);
+        self
+    }
+
+    /// Build from a `composite{base=...,<decision>=...}` spec. Every
+    /// sub-agent gets the seat's seed.
+    pub fn from_spec(spec: &AgentSpec, house: HouseName, seed: u64) -> Result<Self, String> {
+        let base: AgentSpec = spec.params.get("base")
+            .ok_or("composite needs a base agent, e.g. composite{base=heuristic,select_house_card=random}")?
+            .parse()?;
+        let mut agent = CompositeAgent::new(house, spec.to_string(), base.build(house, seed)?);
+        for (key, value) in spec.params.iter().filter(|(k, _)| *k != "base") {
+            let kind: DecisionKind = key.parse()?;
+            let sub: AgentSpec = value.parse()?;
+            agent = agent.with_override(kind, sub.build(house, seed)?);
+        }
+        Ok(agent)
+    }
+
+    fn agent_for(&mut self, view: &PlayerView) -> &mut dyn Agent {
+        let kind = view.pending.as_ref().map(DecisionKind::of);
+        match kind.and_then(|k| self.overrides.get_mut(&k)) {
+            Some(agent) => agent.as_mut(),
+            None => self.base.as_mut(),
+        }
+    }
+}
+
+impl Agent for CompositeAgent {
+    fn name(&self) -> &str { &self.name }
+    fn house(&self) -> HouseName { self.house }
+
+    fn decide(&mut self, view: &PlayerView) -> Action {
+        self.last = view.pending.as_ref().map(DecisionKind::of);
+        self.agent_for(view).decide(view)
+    }
+
+    fn last_decision_info(&self) -> Option<DecisionInfo> {
+        match self.last.and_then(|k| self.overrides.get(&k)) {
+            Some(agent) => agent.last_decision_info(),
+            None => self.base.last_decision_info(),
+        }
+    }
+
+    fn needs_view(&self) -> bool {
+        self.base.needs_view() || self.overrides.values().any(|a| a.needs_view())
+    }
+
+    fn on_match_start(&mut self, ctx: &MatchContext) {
+        self.base.on_match_start(ctx);
+        for agent in self.overrides.values_mut() {
+            agent.on_match_start(ctx);
+        }
+    }
+
+    fn on_match_end(&mut self, ctx: &MatchContext) {
+        self.base.on_match_end(ctx);
+        for agent in self.overrides.values_mut() {
+            agent.on_match_end(ctx);
+        }
+    }
+
+    // `decide` routes whole decisions, so the per-decision methods are only
+    // reached when a caller invokes them directly; they go to the base.
+
+    fn place_orders(&mut self, view: &PlayerView) -> Vec<(AreaId, u8)> {
+        self.base.place_orders(view)
+    }
+
+    fn choose_order_to_resolve(&mut self, view: &PlayerView, order_type: OrderType, candidates: &[AreaId]) -> AreaId {
+        self.base.choose_order_to_resolve(view, order_type, candidates)
+    }
+
+    fn choose_raid(&mut self, view: &PlayerView, from: AreaId, targets: &[AreaId]) -> Option<AreaId> {
+        self.base.choose_raid(view, from, targets)
+    }
+
+    fn choose_march(&mut self, view: &PlayerView, from: AreaId, destinations: &[AreaId], selectable: &[usize]) -> (AreaId, Vec<usize>) {
+        self.base.choose_march(view, from, destinations, selectable)
+    }
+
+    fn leave_power_token(&mut self, view: &PlayerView, area: AreaId) -> bool {
+        self.base.leave_power_token(view, area)
+    }
+
+    fn declare_support(&mut self, view: &PlayerView, attacker: HouseName, defender: HouseName) -> SupportChoice {
+        self.base.declare_support(view, attacker, defender)
+    }
+
+    fn select_house_card(&mut self, view: &PlayerView, available: &[HouseCardId]) -> HouseCardId {
+        self.base.select_house_card(view, available)
+    }
+
+    fn use_valyrian_blade(&mut self, view: &PlayerView) -> bool {
+        self.base.use_valyrian_blade(view)
+    }
+
+    fn submit_bid(&mut self, view: &PlayerView, bid_type: BiddingType, track: Option<Track>) -> u8 {
+        self.base.submit_bid(view, bid_type, track)
+    }
+
+    fn westeros_choice(&mut self, view: &PlayerView, options: &[String]) -> usize {
+        self.base.westeros_choice(view, options)
+    }
+
+    fn choose_muster(&mut self, view: &PlayerView, areas: &[MusterArea]) -> Vec<(AreaId, MusterAction2)> {
+        self.base.choose_muster(view, areas)
+    }
+
+    fn choose_retreat(&mut self, view: &PlayerView, options: &[AreaId]) -> AreaId {
+        self.base.choose_retreat(view, options)
+    }
+
+    fn choose_reconcile(&mut self, view: &PlayerView, area: AreaId) -> (AreaId, usize) {
+        self.base.choose_reconcile(view, area)
+    }
+
+    fn use_messenger_raven(&mut self, view: &PlayerView) -> Option<(AreaId, u8)> {
+        self.base.use_messenger_raven(view)
+    }
+
+    fn use_aeron(&mut self, view: &PlayerView, options: &[HouseCardId]) -> Option<HouseCardId> {
+        self.base.use_aeron(view, options)
+    }
+
+    fn tyrion_replacement(&mut self, view: &PlayerView, options: &[HouseCardId]) -> HouseCardId {
+        self.base.tyrion_replacement(view, options)
+    }
+
+    fn patchface_discard(&mut self, view: &PlayerView, visible: &[HouseCardId]) -> HouseCardId {
+        self.base.patchface_discard(view, visible)
+    }
+
+    fn robb_retreat(&mut self, view: &PlayerView, options: &[AreaId]) -> AreaId {
+        self.base.robb_retreat(view, options)
+    }
+
+    fn wildling_penalty(&mut self, view: &PlayerView, options: &[String]) -> usize {
+        self.base.wildling_penalty(view, options)
+    }
+
+    fn cersei_remove_order(&mut self, view: &PlayerView) -> AreaId {
+        self.base.cersei_remove_order(view)
+    }
+
+    fn doran_choose_track(&mut self, view: &PlayerView) -> Track {
+        self.base.doran_choose_track(view)
+    }
+
+    fn queen_of_thorns(&mut self, view: &PlayerView) -> AreaId {
+        self.base.queen_of_thorns(view)
+    }
+}
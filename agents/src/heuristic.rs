@@ -3,12 +3,15 @@
 // Significantly stronger than RandomAgent.
 // ═══════════════════════════════════════════════════════════════════════
 
-use crate::agent::Agent;
+use crate::agent::{dispatch, Agent, DecisionInfo};
 use got_engine::types::*;
-use got_engine::engine::MusterAction2;
+use got_engine::engine::{Action, MusterAction2};
 use got_engine::map::AREAS;
+use got_engine::map::analysis::{self, Transport};
+use got_engine::navigation;
 use got_engine::visibility::{PlayerView, AreaView};
 use got_engine::cards;
+use got_engine::eval;
 use rand::Rng;
 use rand::seq::SliceRandom;
 use rand_chacha::ChaCha8Rng;
@@ -17,6 +20,8 @@ use rand::SeedableRng;
 pub struct HeuristicAgent {
     house: HouseName,
     rng: ChaCha8Rng,
+    /// Scores behind the last decision, for the scored decision types.
+    info: Option<DecisionInfo>,
 }
 
 #[allow(dead_code)]
@@ -25,6 +30,7 @@ impl HeuristicAgent {
         HeuristicAgent {
             house,
             rng: ChaCha8Rng::seed_from_u64(seed),
+            info: None,
         }
     }
 
@@ -59,13 +65,24 @@ impl HeuristicAgent {
             .collect()
     }
 
-    /// Check if an area has enemy neighbors.
+    /// Check if an enemy could march into an area next move.
     fn has_enemy_neighbor(&self, view: &PlayerView, area_id: AreaId) -> bool {
-        let def = &AREAS[area_id.0 as usize];
-        def.adjacent.iter().any(|&adj| {
-            let a = &view.areas[adj.0 as usize];
-            a.units.iter().any(|u| u.house != self.house)
-        })
+        view.threats().threatened(area_id)
+    }
+
+    /// Areas an enemy army could reach within `moves` marches.
+    fn enemy_reach(&self, view: &PlayerView, moves: u8) -> Vec<bool> {
+        let mut reached = vec![false; view.areas.len()];
+        for area in &view.areas {
+            let Some(enemy) = area.units.iter().find(|u| u.house != self.house && !u.routed).map(|u| u.house) else { continue };
+            if !AREAS[area.id.0 as usize].is_land() {
+                continue;
+            }
+            for (to, _) in navigation::reachable_within(view, area.id, enemy, moves) {
+                reached[to.0 as usize] = true;
+            }
+        }
+        reached
     }
 
     /// Get combat strength of units in an area.
@@ -83,9 +100,22 @@ impl HeuristicAgent {
 }
 
 impl Agent for HeuristicAgent {
-    fn name(&self) -> &str { "Heuristic" }
+    fn name(&self) -> &str { "heuristic" }
     fn house(&self) -> HouseName { self.house }
 
+    fn decide(&mut self, view: &PlayerView) -> Action {
+        self.info = None;
+        let action = dispatch(self, view);
+        if let Some(info) = &mut self.info {
+            info.win_probability = Some(eval::static_eval(view, self.house) as f64);
+        }
+        action
+    }
+
+    fn last_decision_info(&self) -> Option<DecisionInfo> {
+        self.info.clone()
+    }
+
     fn place_orders(&mut self, view: &PlayerView) -> Vec<(AreaId, u8)> {
         let mut orders = Vec::new();
         let mut used_tokens: Vec<u8> = Vec::new();
@@ -97,6 +127,7 @@ impl Agent for HeuristicAgent {
 
         for area_view in &my_areas {
             let has_threat = self.has_enemy_neighbor(view, area_view.id);
+            let enemy_str = view.threats().area(area_view.id).reachable_strength as i16;
             let is_castle = {
                 let idx = area_view.id.0 as usize;
                 AREAS[idx].has_castle_or_stronghold()
@@ -110,7 +141,7 @@ impl Agent for HeuristicAgent {
                 .sum();
 
             // Strategy:
-            // - Castle with enemies nearby → Defense (star if available)
+            // - Castle with enemies nearby, weak or outmatched → Defense (star if available)
             // - Strong army → March (prefer +1 star)
             // - Weak or no threat → Consolidate Power
             // - Sea areas → Support
@@ -119,7 +150,7 @@ impl Agent for HeuristicAgent {
 
             let preferred_type = if is_sea {
                 OrderType::Support
-            } else if is_castle && has_threat && unit_str <= 2 {
+            } else if is_castle && has_threat && (unit_str <= 2 || enemy_str >= unit_str) {
                 OrderType::Defense
             } else if unit_str >= 2 && !is_castle {
                 OrderType::March
@@ -133,18 +164,15 @@ impl Agent for HeuristicAgent {
             };
 
             // Find the best available token of the preferred type
-            let token = self.find_best_token(&used_tokens, preferred_type, &view.order_restrictions);
+            let free = view.placeable_tokens(&used_tokens);
+            let token = Self::find_best_token(&free, preferred_type);
 
             if let Some(t) = token {
                 orders.push((area_view.id, t));
                 used_tokens.push(t);
             } else {
                 // Fallback: any available token
-                let fallback: Vec<u8> = (0..15u8)
-                    .filter(|t| !used_tokens.contains(t))
-                    .filter(|&t| !view.order_restrictions.contains(&ORDER_TOKENS[t as usize].order_type))
-                    .collect();
-                if let Some(&t) = fallback.choose(&mut self.rng) {
+                if let Some(&t) = free.choose(&mut self.rng) {
                     orders.push((area_view.id, t));
                     used_tokens.push(t);
                 }
@@ -153,6 +181,20 @@ impl Agent for HeuristicAgent {
         orders
     }
 
+    fn choose_order_to_resolve(&mut self, view: &PlayerView, order_type: OrderType, candidates: &[AreaId]) -> AreaId {
+        // Marches: move the biggest army first.
+        // Raids and Consolidate Power: resolve star orders first.
+        let score = |aid: &AreaId| {
+            let area = &view.areas[aid.0 as usize];
+            match order_type {
+                OrderType::March => area.units.len(),
+                _ => area.order.map_or(0, |o| o.star as usize),
+            }
+        };
+        self.info = Some(DecisionInfo::scored(candidates.iter().map(|a| (Action::ResolveOrder(*a), score(a) as f64))));
+        candidates.iter().copied().max_by_key(score).unwrap_or(candidates[0])
+    }
+
     fn choose_raid(&mut self, _view: &PlayerView, _from: AreaId, targets: &[AreaId]) -> Option<AreaId> {
         if targets.is_empty() {
             return None;
@@ -162,15 +204,11 @@ impl Agent for HeuristicAgent {
         targets.choose(&mut self.rng).copied()
     }
 
-    fn choose_march(&mut self, view: &PlayerView, from: AreaId, destinations: &[AreaId]) -> (AreaId, Vec<usize>) {
+    fn choose_march(&mut self, view: &PlayerView, from: AreaId, destinations: &[AreaId], selectable: &[usize]) -> (AreaId, Vec<usize>) {
         if destinations.is_empty() {
             return (from, vec![]);
         }
 
-        let unit_count = view.areas.iter()
-            .find(|a| a.id == from)
-            .map_or(0, |a| a.units.len());
-
         // Prefer:
         // 1. Unoccupied castles/strongholds
         // 2. Weakly defended enemy castles
@@ -179,6 +217,8 @@ impl Agent for HeuristicAgent {
 
         let mut best_dest = *destinations.choose(&mut self.rng).unwrap();
         let mut best_score = -100i32;
+        let unit_indices = selectable.to_vec();
+        let mut scores = Vec::new();
 
         for &dest in destinations {
             let def = &AREAS[dest.0 as usize];
@@ -217,6 +257,7 @@ impl Agent for HeuristicAgent {
 
             // Small random factor
             score += self.rng.gen_range(0..5);
+            scores.push((Action::March { to: dest, unit_indices: unit_indices.clone() }, score as f64));
 
             if score > best_score {
                 best_score = score;
@@ -224,8 +265,8 @@ impl Agent for HeuristicAgent {
             }
         }
 
-        // Move all units
-        let unit_indices: Vec<usize> = (0..unit_count).collect();
+        self.info = Some(DecisionInfo::scored(scores));
+        // Move every unit that may go
         (best_dest, unit_indices)
     }
 
@@ -251,15 +292,20 @@ impl Agent for HeuristicAgent {
         // Score each card based on situation
         let in_combat = view.combat.as_ref();
         let am_attacker = in_combat.is_some_and(|c| c.attacker == self.house);
+        let opponent = in_combat.map(|c| if am_attacker { c.defender } else { c.attacker });
+        let opponent_swords = opponent.map_or(0, |h| view.house_info[&h].remaining_abilities.max_swords);
 
         let mut best_card = available[0];
         let mut best_score = -100i32;
+        let mut scores = Vec::new();
 
         for &card_id in available {
             let card = cards::get_house_card(card_id);
             let mut score = card.strength as i32 * 3;
             score += card.swords as i32 * 2;
             score += card.fortifications as i32 * 2;
+            // Fortifications only help against swords the opponent may still play
+            score += card.fortifications.min(opponent_swords) as i32;
 
             // Prefer high-strength cards when we have few left
             if available.len() <= 3 {
@@ -272,20 +318,19 @@ impl Agent for HeuristicAgent {
             }
 
             // Prefer attacking-bonus cards when attacking
-            if am_attacker {
-                match card_id {
-                    HouseCardId::SerJaimeLannister | HouseCardId::GreatjonUmber => score += 4,
-                    _ => {}
-                }
+            if am_attacker && matches!(card.ability, CardAbility::FlatBonus { side: CombatSide::Attacker, .. }) {
+                score += 4;
             }
 
             score += self.rng.gen_range(0..3);
+            scores.push((Action::SelectCard(card_id), score as f64));
 
             if score > best_score {
                 best_score = score;
                 best_card = card_id;
             }
         }
+        self.info = Some(DecisionInfo::scored(scores));
 
         best_card
     }
@@ -318,29 +363,21 @@ impl Agent for HeuristicAgent {
 
     fn choose_muster(&mut self, view: &PlayerView, areas: &[MusterArea]) -> Vec<(AreaId, MusterAction2)> {
         let mut actions = Vec::new();
-        let avail = view.house_info.get(&self.house)
-            .map_or(UnitPool { footmen: 0, knights: 0, siege_engines: 0, ships: 0 }, |h| h.available_units);
-
-        let mut remaining_knights = avail.knights;
-        let mut remaining_footmen = avail.footmen;
-        let mut remaining_siege = avail.siege_engines;
-
-        for muster_area in areas {
-            let pts = muster_area.points;
-            let is_land = AREAS[muster_area.area_id.0 as usize].is_land();
-
-            if !is_land { continue; } // Skip sea areas
-
-            if pts >= 2 && remaining_knights > 0 {
-                // Build knight (costs 2 points)
-                actions.push((muster_area.area_id, MusterAction2::Build(UnitType::Knight)));
-                remaining_knights -= 1;
-            } else if pts >= 2 && remaining_siege > 0 {
-                actions.push((muster_area.area_id, MusterAction2::Build(UnitType::SiegeEngine)));
-                remaining_siege -= 1;
-            } else if pts >= 1 && remaining_footmen > 0 {
-                actions.push((muster_area.area_id, MusterAction2::Build(UnitType::Footman)));
-                remaining_footmen -= 1;
+
+        // Castles an enemy army could reach this round or next get the
+        // units first
+        let reached = self.enemy_reach(view, 2);
+        let mut areas = areas.to_vec();
+        areas.sort_by_key(|a| !reached[a.area_id.0 as usize]);
+
+        for muster_area in &areas {
+            // The strongest unit the points, pool and supply allow
+            let options = view.muster_options(muster_area.area_id, muster_area.points, &actions);
+            let build = [UnitType::Knight, UnitType::SiegeEngine, UnitType::Footman].into_iter()
+                .map(MusterAction2::Build)
+                .find(|b| options.iter().any(|(c, _)| c == b));
+            if let Some(build) = build {
+                actions.push((muster_area.area_id, build));
             }
         }
         actions
@@ -353,7 +390,11 @@ impl Agent for HeuristicAgent {
                 return opt;
             }
         }
-        *options.choose(&mut self.rng).expect("No retreat options")
+        // Otherwise fall back towards home
+        let home = analysis::home_area(self.house);
+        options.iter().copied()
+            .min_by_key(|&opt| analysis::distance(opt, home, Transport::Ships).unwrap_or(u8::MAX))
+            .expect("No retreat options")
     }
 
     fn choose_reconcile(&mut self, view: &PlayerView, area: AreaId) -> (AreaId, usize) {
@@ -381,17 +422,16 @@ impl Agent for HeuristicAgent {
         None // Don't bother swapping for now
     }
 
-    fn use_aeron(&mut self, _view: &PlayerView) -> Option<HouseCardId> {
+    fn use_aeron(&mut self, _view: &PlayerView, _options: &[HouseCardId]) -> Option<HouseCardId> {
         None // Don't swap — 2 power is expensive
     }
 
-    fn tyrion_replacement(&mut self, view: &PlayerView) -> HouseCardId {
+    fn tyrion_replacement(&mut self, _view: &PlayerView, options: &[HouseCardId]) -> HouseCardId {
         // Pick lowest-strength card (save the good ones)
-        let hand = &view.my_hand;
-        hand.iter()
+        options.iter()
             .min_by_key(|&&c| cards::get_house_card(c).strength)
             .copied()
-            .unwrap_or(hand[0])
+            .unwrap_or(options[0])
     }
 
     fn patchface_discard(&mut self, _view: &PlayerView, visible: &[HouseCardId]) -> HouseCardId {
@@ -420,9 +460,8 @@ impl Agent for HeuristicAgent {
 
     fn cersei_remove_order(&mut self, view: &PlayerView) -> AreaId {
         // Remove march orders first (deny enemy movement), then support
-        let targets: Vec<(AreaId, &Order)> = view.areas.iter()
-            .filter(|a| a.house.is_some() && a.house != Some(self.house) && a.order.is_some())
-            .map(|a| (a.id, a.order.as_ref().unwrap()))
+        let targets: Vec<(AreaId, &Order)> = view.removable_orders().iter()
+            .filter_map(|&a| Some((a, view.areas[a.0 as usize].order.as_ref()?)))
             .collect();
 
         // Priority: March > Support > Defense > Raid > CP
@@ -450,11 +489,9 @@ impl Agent for HeuristicAgent {
 }
 
 impl HeuristicAgent {
-    fn find_best_token(&mut self, used: &[u8], preferred: OrderType, restrictions: &[OrderType]) -> Option<u8> {
+    fn find_best_token(free: &[u8], preferred: OrderType) -> Option<u8> {
         // Find the best available token of the preferred type
-        let mut candidates: Vec<(u8, i32)> = (0..15u8)
-            .filter(|t| !used.contains(t))
-            .filter(|&t| !restrictions.contains(&ORDER_TOKENS[t as usize].order_type))
+        let mut candidates: Vec<(u8, i32)> = free.iter().copied()
             .filter(|&t| ORDER_TOKENS[t as usize].order_type == preferred)
             .map(|t| {
                 let def = &ORDER_TOKENS[t as usize];
@@ -463,7 +500,7 @@ impl HeuristicAgent {
             })
             .collect();
 
-        candidates.sort_by(|a, b| b.1.cmp(&a.1));
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.1));
         candidates.first().map(|(t, _)| *t)
     }
 }
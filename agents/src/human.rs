@@ -0,0 +1,487 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Human Agent — a person at the terminal answers every decision
+//
+// Each prompt lists the legal choices for the pending decision, numbered
+// from 1; Enter takes the first one and `b` reprints the board. The agent
+// sees only its own PlayerView, so hidden information stays hidden.
+//
+// Hot seat: all human seats share one Console. When the deciding human
+// changes, the screen is cleared and the next player is asked to take the
+// keyboard before their view (hand, unrevealed orders) is printed.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::agent::Agent;
+use got_engine::cards::get_house_card;
+use got_engine::engine::{port_of, MusterAction2};
+use got_engine::map::{area_name, AREAS};
+use got_engine::navigation::port_has_room;
+use got_engine::types::*;
+use got_engine::visibility::PlayerView;
+use std::io::{BufRead, Write};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+/// Terminal shared by the human seats of a session.
+pub struct Console {
+    input: Box<dyn BufRead + Send>,
+    output: Box<dyn Write + Send>,
+    /// Houses played by humans at this console.
+    seats: Vec<HouseName>,
+    /// Human whose view is currently on screen.
+    on_screen: Option<HouseName>,
+    board: String,
+}
+
+impl Console {
+    pub fn new(input: Box<dyn BufRead + Send>, output: Box<dyn Write + Send>) -> Self {
+        Console { input, output, seats: Vec::new(), on_screen: None, board: String::new() }
+    }
+
+    /// The process-wide console on stdin/stdout.
+    pub fn stdio() -> Arc<Mutex<Console>> {
+        static STDIO: OnceLock<Arc<Mutex<Console>>> = OnceLock::new();
+        STDIO.get_or_init(|| {
+            let input = Box::new(std::io::BufReader::new(std::io::stdin()));
+            Arc::new(Mutex::new(Console::new(input, Box::new(std::io::stdout()))))
+        }).clone()
+    }
+
+    fn say(&mut self, text: &str) {
+        let _ = writeln!(self.output, "{}", text);
+        let _ = self.output.flush();
+    }
+
+    /// One trimmed line of input; None at end of input.
+    fn read_line(&mut self) -> Option<String> {
+        let mut line = String::new();
+        match self.input.read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line.trim().to_string()),
+        }
+    }
+
+    /// Clear the screen and wait for `house` to take the keyboard, unless
+    /// they are the only human or already looking at it.
+    fn hand_over(&mut self, house: HouseName) -> bool {
+        let changed = self.on_screen != Some(house);
+        if self.seats.len() > 1 && changed {
+            let _ = write!(self.output, "\x1b[2J\x1b[H");
+            self.say(&format!("Pass the keyboard to House {}.", house));
+            self.say("Other players look away. Press Enter when ready.");
+            self.read_line();
+        }
+        self.on_screen = Some(house);
+        changed
+    }
+
+    /// Ask until the answer is a number in 1..=options.len(); Enter or end
+    /// of input picks the first option. Returns a 0-based index.
+    fn choose(&mut self, prompt: &str, options: &[String]) -> usize {
+        if options.len() <= 1 {
+            return 0;
+        }
+        self.say(prompt);
+        for (i, o) in options.iter().enumerate() {
+            self.say(&format!("  {:2}. {}", i + 1, o));
+        }
+        loop {
+            let _ = write!(self.output, "> ");
+            let _ = self.output.flush();
+            let Some(line) = self.read_line() else { return 0 };
+            if line.is_empty() {
+                return 0;
+            }
+            if line.eq_ignore_ascii_case("b") {
+                let board = self.board.clone();
+                self.say(&board);
+                continue;
+            }
+            match line.parse::<usize>() {
+                Ok(n) if (1..=options.len()).contains(&n) => return n - 1,
+                _ => self.say(&format!("Enter a number from 1 to {} (b = board)", options.len())),
+            }
+        }
+    }
+
+    fn confirm(&mut self, prompt: &str) -> bool {
+        self.choose(prompt, &["No".to_string(), "Yes".to_string()]) == 1
+    }
+
+    /// Ask for a number in 0..=max; Enter or end of input gives 0.
+    fn number(&mut self, prompt: &str, max: u8) -> u8 {
+        loop {
+            let _ = write!(self.output, "{} (0-{}) > ", prompt, max);
+            let _ = self.output.flush();
+            let Some(line) = self.read_line() else { return 0 };
+            if line.is_empty() {
+                return 0;
+            }
+            match line.parse::<u8>() {
+                Ok(n) if n <= max => return n,
+                _ => self.say(&format!("Enter a number from 0 to {}", max)),
+            }
+        }
+    }
+}
+
+pub struct HumanAgent {
+    house: HouseName,
+    console: Arc<Mutex<Console>>,
+}
+
+impl HumanAgent {
+    /// A human seat on `console`; every seat sharing it is part of the same
+    /// hot-seat session.
+    pub fn new(house: HouseName, console: Arc<Mutex<Console>>) -> Self {
+        {
+            let mut c = console.lock().unwrap();
+            if !c.seats.contains(&house) {
+                c.seats.push(house);
+            }
+        }
+        HumanAgent { house, console }
+    }
+
+    /// A human seat on the terminal.
+    pub fn stdio(house: HouseName) -> Self {
+        HumanAgent::new(house, Console::stdio())
+    }
+
+    /// Take the console for a decision: hand over if another human had it,
+    /// then print the status line (and the board for a new player).
+    fn turn(&self, view: &PlayerView) -> MutexGuard<'_, Console> {
+        self.take(view, false)
+    }
+
+    fn take(&self, view: &PlayerView, show_board: bool) -> MutexGuard<'_, Console> {
+        let mut c = self.console.lock().unwrap();
+        c.board = render_board(view);
+        if c.hand_over(self.house) || show_board {
+            let board = c.board.clone();
+            c.say(&board);
+        }
+        let me = &view.house_info[&self.house];
+        c.say(&format!(
+            "\n── House {} · round {} · {:?} ── power {} · supply {} · castles to win {} (b = board)",
+            self.house, view.round, view.phase, me.power, me.supply, me.castles_to_win,
+        ));
+        c
+    }
+
+    fn pick_area(&self, view: &PlayerView, prompt: &str, areas: &[AreaId]) -> AreaId {
+        let labels: Vec<String> = areas.iter().map(|&a| area_label(view, a)).collect();
+        areas[self.turn(view).choose(prompt, &labels)]
+    }
+
+    fn pick_card(&self, view: &PlayerView, prompt: &str, cards: &[HouseCardId]) -> HouseCardId {
+        let labels: Vec<String> = cards.iter().map(|&c| card_label(c)).collect();
+        cards[self.turn(view).choose(prompt, &labels)]
+    }
+
+    /// Opponent areas showing an order (Cersei, Queen of Thorns).
+    fn remove_order(&self, view: &PlayerView, prompt: &str) -> AreaId {
+        let targets = view.removable_orders();
+        if targets.is_empty() {
+            return AreaId(0);
+        }
+        self.pick_area(view, prompt, targets)
+    }
+}
+
+// ── Rendering ──────────────────────────────────────────────────────────
+
+fn units_label(units: &[Unit]) -> String {
+    units.iter()
+        .map(|u| {
+            let s = match u.unit_type {
+                UnitType::Footman => "footman",
+                UnitType::Knight => "knight",
+                UnitType::Ship => "ship",
+                UnitType::SiegeEngine => "siege",
+            };
+            if u.routed { format!("{} (routed)", s) } else { s.to_string() }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn token_label(index: usize) -> String {
+    let t = ORDER_TOKENS[index];
+    format!("{:?} {:+}{}", t.order_type, t.strength, if t.star { " ★" } else { "" })
+}
+
+fn order_label(order: &Order) -> String {
+    token_label(order.token_index as usize)
+}
+
+fn card_label(id: HouseCardId) -> String {
+    let c = get_house_card(id);
+    let mut label = format!("{:?} (strength {}, swords {}, forts {}", id, c.strength, c.swords, c.fortifications);
+    if c.ability != CardAbility::None {
+        label.push_str(&format!("; {:?}", c.ability));
+    }
+    label + ")"
+}
+
+fn area_label(view: &PlayerView, id: AreaId) -> String {
+    let area = &view.areas[id.0 as usize];
+    let mut label = area_name(id).to_string();
+    if let Some(h) = area.house {
+        label.push_str(&format!(" [{}]", h));
+    }
+    if !area.units.is_empty() {
+        label.push_str(&format!(" {}", units_label(&area.units)));
+    }
+    label
+}
+
+/// Tracks, hands and every occupied area as the viewer sees them.
+fn render_board(view: &PlayerView) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("Round {} · {:?} · wildlings {}\n", view.round, view.phase, view.wildling_threat));
+    for h in &view.turn_order {
+        let i = &view.house_info[h];
+        out.push_str(&format!(
+            "  {:10} throne {} fiefdoms {} court {} · supply {} · power {:2} · cards {} · needs {} castles\n",
+            h.to_string(), i.iron_throne, i.fiefdoms, i.kings_court, i.supply, i.power, i.cards_in_hand, i.castles_to_win,
+        ));
+    }
+    for area in view.areas.iter().filter(|a| !a.units.is_empty() || a.house.is_some()) {
+        let order = match (&area.order, area.has_hidden_order) {
+            (Some(o), _) => format!(" <{}>", order_label(o)),
+            (None, true) => " <hidden order>".to_string(),
+            _ => String::new(),
+        };
+        out.push_str(&format!("  {}{}\n", area_label(view, area.id), order));
+    }
+    if let Some(c) = &view.combat {
+        out.push_str(&format!(
+            "  Combat in {}: {} ({}) attacks {} ({})\n",
+            area_name(c.area_id), c.attacker, units_label(&c.attacking_units), c.defender, units_label(&c.defending_units),
+        ));
+    }
+    let hand: Vec<String> = view.my_hand.iter().map(|&c| card_label(c)).collect();
+    out.push_str(&format!("  Your hand: {}", hand.join("; ")));
+    out
+}
+
+// ── Decisions ──────────────────────────────────────────────────────────
+
+impl Agent for HumanAgent {
+    fn name(&self) -> &str { "human" }
+    fn house(&self) -> HouseName { self.house }
+
+    fn place_orders(&mut self, view: &PlayerView) -> Vec<(AreaId, u8)> {
+        let mut c = self.take(view, true);
+        let mut orders = Vec::new();
+        let mut used: Vec<u8> = Vec::new();
+        for area in view.areas.iter().filter(|a| a.house == Some(self.house) && !a.units.is_empty()) {
+            let legal = view.placeable_tokens(&used);
+            if legal.is_empty() {
+                continue;
+            }
+            let labels: Vec<String> = legal.iter().map(|&t| token_label(t as usize)).collect();
+            let t = legal[c.choose(&format!("Order for {}:", area_label(view, area.id)), &labels)];
+            orders.push((area.id, t));
+            used.push(t);
+        }
+        orders
+    }
+
+    fn choose_order_to_resolve(&mut self, view: &PlayerView, order_type: OrderType, candidates: &[AreaId]) -> AreaId {
+        self.pick_area(view, &format!("Resolve which {:?} order?", order_type), candidates)
+    }
+
+    fn choose_raid(&mut self, view: &PlayerView, from: AreaId, targets: &[AreaId]) -> Option<AreaId> {
+        let mut labels = vec!["Skip the raid".to_string()];
+        labels.extend(targets.iter().map(|&a| area_label(view, a)));
+        let i = self.turn(view).choose(&format!("Raid from {}:", area_name(from)), &labels);
+        if i == 0 { None } else { Some(targets[i - 1]) }
+    }
+
+    fn choose_march(&mut self, view: &PlayerView, from: AreaId, destinations: &[AreaId], selectable: &[usize]) -> (AreaId, Vec<usize>) {
+        let units: Vec<Unit> = selectable.iter().map(|&i| view.areas[from.0 as usize].units[i]).collect();
+        let mut labels = vec!["Stay (remove the order)".to_string()];
+        labels.extend(destinations.iter().map(|&a| area_label(view, a)));
+        let mut c = self.turn(view);
+        let i = c.choose(&format!("March from {} ({}):", area_name(from), units_label(&units)), &labels);
+        if i == 0 {
+            return (from, vec![]);
+        }
+        let mut moving = Vec::new();
+        for (&u, unit) in selectable.iter().zip(&units) {
+            if units.len() == 1 || c.confirm(&format!("Move {}?", units_label(std::slice::from_ref(unit)))) {
+                moving.push(u);
+            }
+        }
+        (destinations[i - 1], moving)
+    }
+
+    fn leave_power_token(&mut self, view: &PlayerView, area: AreaId) -> bool {
+        view.house_info[&self.house].power > 0
+            && self.turn(view).confirm(&format!("Leave a power token in {}?", area_name(area)))
+    }
+
+    fn declare_support(&mut self, view: &PlayerView, attacker: HouseName, defender: HouseName) -> SupportChoice {
+        let labels = vec![
+            "Support nobody".to_string(),
+            format!("Support the attacker, {}", attacker),
+            format!("Support the defender, {}", defender),
+        ];
+        match self.turn(view).choose("Support in combat:", &labels) {
+            1 => SupportChoice::Attacker,
+            2 => SupportChoice::Defender,
+            _ => SupportChoice::None,
+        }
+    }
+
+    fn select_house_card(&mut self, view: &PlayerView, available: &[HouseCardId]) -> HouseCardId {
+        self.pick_card(view, "Play which house card?", available)
+    }
+
+    fn use_valyrian_blade(&mut self, view: &PlayerView) -> bool {
+        self.turn(view).confirm("Use the Valyrian Steel Blade (+1 strength)?")
+    }
+
+    fn submit_bid(&mut self, view: &PlayerView, bid_type: BiddingType, track: Option<Track>) -> u8 {
+        let power = view.house_info[&self.house].power;
+        let what = match track {
+            Some(t) => format!("{:?} bid for {:?}", bid_type, t),
+            None => format!("{:?} bid", bid_type),
+        };
+        self.turn(view).number(&format!("{}: power tokens", what), power)
+    }
+
+    fn westeros_choice(&mut self, view: &PlayerView, options: &[String]) -> usize {
+        self.turn(view).choose("Westeros card choice:", options)
+    }
+
+    fn choose_muster(&mut self, view: &PlayerView, areas: &[MusterArea]) -> Vec<(AreaId, MusterAction2)> {
+        let mut pool = view.house_info[&self.house].available_units;
+        let mut c = self.turn(view);
+        let mut actions = Vec::new();
+        let mut ships: Vec<AreaId> = Vec::new();
+        for m in areas.iter().filter(|m| AREAS[m.area_id.0 as usize].is_land()) {
+            let mut points = m.points;
+            let mut footmen = view.areas[m.area_id.0 as usize].units.iter()
+                .filter(|u| u.house == self.house && u.unit_type == UnitType::Footman)
+                .count();
+            while points > 0 {
+                let mut choices = vec![("Done".to_string(), None)];
+                if pool.footmen > 0 {
+                    choices.push(("Footman (1 point)".to_string(), Some(MusterAction2::Build(UnitType::Footman))));
+                }
+                if points >= 2 && pool.knights > 0 {
+                    choices.push(("Knight (2 points)".to_string(), Some(MusterAction2::Build(UnitType::Knight))));
+                }
+                if points >= 2 && pool.siege_engines > 0 {
+                    choices.push(("Siege engine (2 points)".to_string(), Some(MusterAction2::Build(UnitType::SiegeEngine))));
+                }
+                if footmen > 0 && pool.knights > 0 {
+                    choices.push(("Upgrade a footman to a knight (1 point)".to_string(), Some(MusterAction2::Upgrade)));
+                }
+                if pool.ships > 0 {
+                    let seas = AREAS[m.area_id.0 as usize].adjacent.iter().copied()
+                        .filter(|&a| AREAS[a.0 as usize].is_sea() && !view.areas[a.0 as usize].blocked)
+                        .filter(|&a| view.areas[a.0 as usize].units.iter().all(|u| u.house == self.house));
+                    let port = port_of(m.area_id).filter(|&p| {
+                        let arriving = ships.iter().filter(|&&s| s == p).count() + 1;
+                        port_has_room(view, p, self.house, arriving)
+                    });
+                    for to in seas.chain(port) {
+                        choices.push((format!("Ship in {} (1 point)", area_name(to)), Some(MusterAction2::BuildShip(to))));
+                    }
+                }
+                let labels: Vec<String> = choices.iter().map(|(l, _)| l.clone()).collect();
+                let prompt = format!("Muster in {} ({} point{} left):", area_name(m.area_id), points, if points == 1 { "" } else { "s" });
+                let Some(action) = choices.swap_remove(c.choose(&prompt, &labels)).1 else { break };
+                match action {
+                    MusterAction2::Build(UnitType::Footman) => { pool.footmen -= 1; footmen += 1; points -= 1 }
+                    MusterAction2::Build(UnitType::Knight) => { pool.knights -= 1; points -= 2 }
+                    MusterAction2::Build(_) => { pool.siege_engines -= 1; points -= 2 }
+                    MusterAction2::BuildShip(to) => { pool.ships -= 1; ships.push(to); points -= 1 }
+                    MusterAction2::Upgrade => { pool.knights -= 1; pool.footmen += 1; footmen -= 1; points -= 1 }
+                }
+                actions.push((m.area_id, action));
+            }
+        }
+        actions
+    }
+
+    fn choose_retreat(&mut self, view: &PlayerView, options: &[AreaId]) -> AreaId {
+        self.pick_area(view, "Retreat to:", options)
+    }
+
+    fn choose_reconcile(&mut self, view: &PlayerView, area: AreaId) -> (AreaId, usize) {
+        let units = &view.areas[area.0 as usize].units;
+        let mine: Vec<usize> = (0..units.len()).filter(|&i| units[i].house == self.house).collect();
+        if mine.is_empty() {
+            return (area, 0);
+        }
+        let labels: Vec<String> = mine.iter().map(|&i| units_label(&units[i..=i])).collect();
+        let i = self.turn(view).choose(&format!("Over supply in {}: disband which unit?", area_name(area)), &labels);
+        (area, mine[i])
+    }
+
+    fn use_messenger_raven(&mut self, view: &PlayerView) -> Option<(AreaId, u8)> {
+        let mine: Vec<AreaId> = view.areas.iter()
+            .filter(|a| a.house == Some(self.house) && a.order.is_some())
+            .map(|a| a.id)
+            .collect();
+        let used: Vec<u8> = view.areas.iter()
+            .filter_map(|a| a.order.filter(|o| o.house == self.house).map(|o| o.token_index))
+            .collect();
+        let spare: Vec<u8> = (0..ORDER_TOKENS.len() as u8).filter(|t| !used.contains(t)).collect();
+        if mine.is_empty() || spare.is_empty() {
+            return None;
+        }
+        let mut c = self.turn(view);
+        if !c.confirm("Use the Messenger Raven to swap an order?") {
+            return None;
+        }
+        let labels: Vec<String> = mine.iter()
+            .map(|&a| format!("{} <{}>", area_name(a), order_label(view.areas[a.0 as usize].order.as_ref().unwrap())))
+            .collect();
+        let area = mine[c.choose("Replace the order in:", &labels)];
+        let tokens: Vec<String> = spare.iter().map(|&t| token_label(t as usize)).collect();
+        Some((area, spare[c.choose("New order:", &tokens)]))
+    }
+
+    fn use_aeron(&mut self, view: &PlayerView, options: &[HouseCardId]) -> Option<HouseCardId> {
+        let Some(PendingDecision::AeronSwap { power_cost, .. }) = view.pending else { return None };
+        let mut labels = vec!["Keep the current card".to_string()];
+        labels.extend(options.iter().map(|&c| card_label(c)));
+        let prompt = format!("Aeron Damphair: pay {} power to play another card?", power_cost);
+        let i = self.turn(view).choose(&prompt, &labels);
+        if i == 0 { None } else { Some(options[i - 1]) }
+    }
+
+    fn tyrion_replacement(&mut self, view: &PlayerView, options: &[HouseCardId]) -> HouseCardId {
+        self.pick_card(view, "Tyrion cancelled your card. Play instead:", options)
+    }
+
+    fn patchface_discard(&mut self, view: &PlayerView, visible: &[HouseCardId]) -> HouseCardId {
+        self.pick_card(view, "Patchface: discard which opponent card?", visible)
+    }
+
+    fn robb_retreat(&mut self, view: &PlayerView, options: &[AreaId]) -> AreaId {
+        self.pick_area(view, "Robb Stark: the defender retreats to:", options)
+    }
+
+    fn wildling_penalty(&mut self, view: &PlayerView, options: &[String]) -> usize {
+        self.turn(view).choose("Wildling penalty:", options)
+    }
+
+    fn cersei_remove_order(&mut self, view: &PlayerView) -> AreaId {
+        self.remove_order(view, "Cersei Lannister: remove which order?")
+    }
+
+    fn doran_choose_track(&mut self, view: &PlayerView) -> Track {
+        let tracks = [Track::IronThrone, Track::Fiefdoms, Track::KingsCourt];
+        let labels: Vec<String> = tracks.iter().map(|t| format!("{:?}", t)).collect();
+        tracks[self.turn(view).choose("Doran Martell: move your opponent to the bottom of:", &labels)]
+    }
+
+    fn queen_of_thorns(&mut self, view: &PlayerView) -> AreaId {
+        self.remove_order(view, "Queen of Thorns: remove which order?")
+    }
+}
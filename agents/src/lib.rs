@@ -1,7 +1,20 @@
 pub mod agent;
 pub mod random;
 pub mod heuristic;
+pub mod registry;
+pub mod cloned;
+pub mod composite;
+pub mod human;
+pub mod search_support;
+pub mod mcts;
+pub mod async_agent;
 
 pub use agent::Agent;
 pub use random::RandomAgent;
 pub use heuristic::HeuristicAgent;
+pub use registry::AgentSpec;
+pub use cloned::ClonedAgent;
+pub use composite::CompositeAgent;
+pub use human::HumanAgent;
+pub use mcts::MctsAgent;
+pub use async_agent::{AsyncAgent, SyncAdapter};
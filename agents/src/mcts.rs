@@ -0,0 +1,446 @@
+// ═══════════════════════════════════════════════════════════════════════
+// MCTS Agent — determinized Monte Carlo search over candidate actions
+//
+// For each decision the agent gathers a handful of candidate actions (the
+// heuristic agent's choice and its best-scored alternatives, plus random
+// legal proposals) and spends its iterations on them with UCB1: every
+// iteration takes a determinization of the view (`engine::determinize`),
+// plays the chosen candidate, and rolls the game out with the rollout
+// agent in every seat, cut short by the rollout limit and scored by the
+// static evaluator. The candidate visited most is played.
+//
+// Root parallelization: with threads > 1 each worker runs its own share
+// of the iterations with its own random stream and rollout agents; the
+// root statistics are summed at the end. Nothing is shared while the
+// workers run except read-only inputs: the view, the candidates and the
+// pooled determinizations.
+//
+// Determinizations come from a pool that is refreshed with every new view
+// rather than rebuilt (`pool=0` samples a fresh one per iteration); each
+// iteration reseeds the engine's random stream of the copy it plays.
+//
+// Search results are kept in a transposition table keyed by the view's
+// Zobrist hash, so a position seen again starts from what was learned
+// about it. Decisions the engine cannot determinize (Westeros phase,
+// bids) go to the heuristic agent.
+//
+//     mcts{iterations=400,threads=4,rounds=2,rollout=heuristic}
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::agent::{Agent, Candidate, DecisionInfo};
+use crate::heuristic::HeuristicAgent;
+use crate::random::RandomAgent;
+use crate::registry::AgentSpec;
+use crate::search_support::{TranspositionTable, ZobristHash};
+use got_engine::determinize::{determinize, supported, DeterminizationPool};
+use got_engine::engine::{self, action_fits, Action, MusterAction2};
+use got_engine::eval;
+use got_engine::rollout::{rollout, RolloutLimit};
+use got_engine::types::*;
+use got_engine::visibility::{player_view, PlayerView};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Rollouts longer than this are abandoned and score as a draw.
+const MAX_ROLLOUT_DECISIONS: usize = 20_000;
+
+#[derive(Debug, Clone)]
+pub struct MctsConfig {
+    /// Iterations per decision, over all threads.
+    pub iterations: usize,
+    pub threads: usize,
+    /// Where rollouts stop and the evaluator takes over.
+    pub limit: RolloutLimit,
+    /// UCB1 exploration constant.
+    pub exploration: f64,
+    /// Most candidate actions searched per decision.
+    pub candidates: usize,
+    /// Agent playing every seat in the rollouts.
+    pub rollout: AgentSpec,
+    /// Transposition table entries; 0 = no table.
+    pub table_size: usize,
+    /// Determinizations kept between decisions; 0 = a fresh one every
+    /// iteration.
+    pub pool_size: usize,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        MctsConfig {
+            iterations: 200,
+            threads: 1,
+            limit: RolloutLimit::rounds(2),
+            exploration: 0.5,
+            candidates: 8,
+            rollout: AgentSpec::new("heuristic"),
+            table_size: 4096,
+            pool_size: 32,
+        }
+    }
+}
+
+impl MctsConfig {
+    /// Read `iterations`, `threads`, `rounds`, `decisions`, `c`,
+    /// `candidates`, `rollout`, `tt` and `pool` from a spec, defaults
+    /// elsewhere.
+    pub fn from_spec(spec: &AgentSpec) -> Result<Self, String> {
+        const KEYS: [&str; 9] = ["iterations", "threads", "rounds", "decisions", "c", "candidates", "rollout", "tt", "pool"];
+        if let Some(key) = spec.params.keys().find(|k| !KEYS.contains(&k.as_str())) {
+            return Err(format!("mcts: unknown parameter {} (known: {})", key, KEYS.join(", ")));
+        }
+        let d = MctsConfig::default();
+        let limit = match (spec.params.get("rounds"), spec.params.get("decisions")) {
+            (None, None) => d.limit,
+            _ => RolloutLimit {
+                rounds: spec.params.get("rounds").map(|v| v.parse()).transpose().map_err(|_| "mcts: invalid value for rounds")?,
+                decisions: spec.params.get("decisions").map(|v| v.parse()).transpose().map_err(|_| "mcts: invalid value for decisions")?,
+            },
+        };
+        let rollout: AgentSpec = match spec.params.get("rollout") {
+            Some(r) => r.parse()?,
+            None => d.rollout,
+        };
+        if rollout.kind == "mcts" || rollout.kind == "human" {
+            return Err(format!("mcts: {} cannot play rollouts", rollout.kind));
+        }
+        Ok(MctsConfig {
+            iterations: spec.param("iterations", d.iterations)?,
+            threads: spec.param("threads", d.threads)?.max(1),
+            limit,
+            exploration: spec.param("c", d.exploration)?,
+            candidates: spec.param("candidates", d.candidates)?.max(1),
+            rollout,
+            table_size: spec.param("tt", d.table_size)?,
+            pool_size: spec.param("pool", d.pool_size)?,
+        })
+    }
+}
+
+/// Search statistics of one candidate.
+#[derive(Debug, Clone, Copy, Default)]
+struct Arm {
+    visits: u32,
+    total: f64,
+}
+
+impl Arm {
+    fn mean(&self) -> f64 {
+        self.total / self.visits.max(1) as f64
+    }
+}
+
+/// What the table remembers about a position: candidate stats by action.
+type StoredArms = Vec<(serde_json::Value, Arm)>;
+
+pub struct MctsAgent {
+    house: HouseName,
+    name: String,
+    seed: u64,
+    config: MctsConfig,
+    decisions: u64,
+    /// Proposes candidates and answers what the search can't.
+    heuristic: HeuristicAgent,
+    table: Option<TranspositionTable<StoredArms>>,
+    pool: Option<DeterminizationPool>,
+    info: Option<DecisionInfo>,
+}
+
+impl MctsAgent {
+    pub fn new(house: HouseName, seed: u64, name: String, config: MctsConfig) -> Self {
+        let table = (config.table_size > 0).then(|| TranspositionTable::new(config.table_size));
+        let pool = (config.pool_size > 0).then(|| DeterminizationPool::new(house, config.pool_size, seed));
+        MctsAgent { house, name, seed, config, decisions: 0, heuristic: HeuristicAgent::new(house, seed), table, pool, info: None }
+    }
+
+    pub fn from_spec(spec: &AgentSpec, house: HouseName, seed: u64) -> Result<Self, String> {
+        let config = MctsConfig::from_spec(spec)?;
+        config.rollout.build(house, seed)?;
+        Ok(MctsAgent::new(house, seed, spec.to_string(), config))
+    }
+
+    /// The heuristic's choice and alternatives, then random proposals,
+    /// without duplicates.
+    fn candidates(&mut self, view: &PlayerView, seed: u64) -> Vec<Action> {
+        let pending = view.pending.as_ref().expect("No pending decision");
+        let choice = self.heuristic.decide(view);
+        let alternatives: Vec<Action> = self.heuristic.last_decision_info()
+            .map(|info| info.candidates.into_iter().map(|c| c.action).collect())
+            .unwrap_or_default();
+        let limit = self.config.candidates;
+        let mut keys = Vec::new();
+        let mut out = Vec::new();
+        let mut add = |action: Action| {
+            let key = serde_json::to_value(&action).unwrap();
+            if out.len() < limit && !keys.contains(&key) && action_fits(pending, &action) {
+                keys.push(key);
+                out.push(action);
+            }
+        };
+        add(choice);
+        alternatives.into_iter().for_each(&mut add);
+        for i in 0..limit as u64 * 2 {
+            add(RandomAgent::new(self.house, seed.wrapping_add(i)).decide(view));
+        }
+        out
+    }
+
+    fn search(&mut self, view: &PlayerView) -> Action {
+        let start = Instant::now();
+        let seed = self.seed ^ self.decisions.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        let candidates = self.candidates(view, seed);
+        if candidates.len() < 2 {
+            self.info = None;
+            return candidates.into_iter().next().unwrap_or_else(|| self.heuristic.decide(view));
+        }
+        let keys: Vec<serde_json::Value> = candidates.iter().map(|a| serde_json::to_value(a).unwrap()).collect();
+
+        // Warm start from an earlier search of the same position
+        let hash = view.zobrist();
+        let mut prior = vec![Arm::default(); candidates.len()];
+        if let Some(table) = &self.table {
+            table.reset_stats();
+            if let Some((stored, _)) = table.probe(hash) {
+                for (key, arm) in stored {
+                    if let Some(i) = keys.iter().position(|k| *k == key) {
+                        prior[i] = arm;
+                    }
+                }
+            }
+        }
+
+        let pool_before = self.pool.as_ref().map(|p| p.stats()).unwrap_or_default();
+        let pooled: &[GameState] = match &mut self.pool {
+            Some(pool) => pool.sync(view).unwrap_or(&[]),
+            None => &[],
+        };
+        let threads = self.config.threads.min(self.config.iterations.max(1));
+        let config = &self.config;
+        let worker = Worker { view, house: self.house, candidates: &candidates, prior: &prior, pooled, config, seed, threads };
+        let share = |t: usize| config.iterations / threads + usize::from(t < config.iterations % threads);
+        let results: Vec<Vec<Arm>> = if threads == 1 {
+            vec![worker.run(0, share(0))]
+        } else {
+            std::thread::scope(|scope| {
+                let worker = &worker;
+                let workers: Vec<_> = (0..threads)
+                    .map(|t| scope.spawn(move || worker.run(t, share(t))))
+                    .collect();
+                workers.into_iter().map(|w| w.join().expect("search worker panicked")).collect()
+            })
+        };
+        let mut arms = prior;
+        for local in &results {
+            for (arm, l) in arms.iter_mut().zip(local) {
+                arm.visits += l.visits;
+                arm.total += l.total;
+            }
+        }
+        let total_visits: u32 = arms.iter().map(|a| a.visits).sum();
+        if let Some(table) = &self.table {
+            table.store(hash, total_visits, keys.into_iter().zip(arms.iter().copied()).collect());
+        }
+
+        let mut order: Vec<usize> = (0..candidates.len()).collect();
+        order.sort_by(|&a, &b| arms[b].visits.cmp(&arms[a].visits).then(arms[b].mean().total_cmp(&arms[a].mean())));
+        let mut info = DecisionInfo {
+            candidates: order.iter()
+                .map(|&i| Candidate { action: candidates[i].clone(), score: arms[i].mean(), visits: Some(arms[i].visits) })
+                .collect(),
+            win_probability: Some(arms[order[0]].mean()),
+            ..Default::default()
+        };
+        info.stats.insert("iterations".into(), self.config.iterations as f64);
+        info.stats.insert("threads".into(), threads as f64);
+        info.stats.insert("time_ms".into(), start.elapsed().as_secs_f64() * 1000.0);
+        if let Some(table) = &self.table {
+            table.stats().record(&mut info);
+        }
+        if let Some(pool) = &self.pool {
+            let stats = pool.stats();
+            info.stats.insert("pool_size".into(), pool.states().len() as f64);
+            info.stats.insert("pool_built".into(), (stats.built - pool_before.built) as f64);
+            info.stats.insert("pool_redrawn".into(), (stats.redrawn - pool_before.redrawn) as f64);
+        }
+        self.info = Some(info);
+        candidates[order[0]].clone()
+    }
+}
+
+/// What every search worker reads; nothing in it changes while they run.
+struct Worker<'a> {
+    view: &'a PlayerView,
+    house: HouseName,
+    candidates: &'a [Action],
+    /// Statistics from an earlier search, which only steer the selection.
+    prior: &'a [Arm],
+    /// Pooled determinizations; empty = sample one per iteration.
+    pooled: &'a [GameState],
+    config: &'a MctsConfig,
+    seed: u64,
+    threads: usize,
+}
+
+impl Worker<'_> {
+    /// One worker's share of the iterations, on its own random stream:
+    /// the statistics it gathered on top of `prior`.
+    fn run(&self, stream: usize, iterations: usize) -> Vec<Arm> {
+        let (candidates, prior) = (self.candidates, self.prior);
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        rng.set_stream(stream as u64);
+        let mut agents: HashMap<HouseName, Box<dyn Agent>> = HashMap::new();
+        for &h in &self.view.playing_houses {
+            match self.config.rollout.build(h, rng.next_u64()) {
+                Ok(agent) => { agents.insert(h, agent); }
+                Err(_) => return vec![Arm::default(); candidates.len()],
+            }
+        }
+        let draw = 1.0 / self.view.playing_houses.len().max(1) as f64;
+        let mut local = vec![Arm::default(); candidates.len()];
+        for k in 0..iterations {
+            let mut state = if self.pooled.is_empty() {
+                let Ok(state) = determinize(self.view, &mut rng) else { break };
+                state
+            } else {
+                let mut state = self.pooled[(k * self.threads + stream) % self.pooled.len()].clone();
+                state.seed ^= rng.next_u64();
+                state
+            };
+            let n: u32 = prior.iter().zip(&local).map(|(p, l)| p.visits + l.visits).sum();
+            let ucb = |i: usize| {
+                let visits = prior[i].visits + local[i].visits;
+                if visits == 0 {
+                    return f64::INFINITY;
+                }
+                let mean = (prior[i].total + local[i].total) / visits as f64;
+                mean + self.config.exploration * ((n.max(1) as f64).ln() / visits as f64).sqrt()
+            };
+            let arm = (0..candidates.len()).max_by(|&a, &b| ucb(a).total_cmp(&ucb(b))).unwrap();
+
+            // A candidate the sampled world refuses scores like a failed rollout
+            let played = engine::apply_action(&mut state, candidates[arm].clone()).map_err(String::from).and_then(|()| {
+                rollout(&mut state, self.config.limit, MAX_ROLLOUT_DECISIONS, |s, h| {
+                    agents.get_mut(&h).expect("every playing house has a rollout agent").decide(&player_view(s, h))
+                })
+            });
+            local[arm].visits += 1;
+            local[arm].total += match played {
+                Ok(_) => eval::static_eval(&state, self.house) as f64,
+                Err(_) => draw,
+            };
+        }
+        local
+    }
+}
+
+impl Agent for MctsAgent {
+    fn name(&self) -> &str { &self.name }
+    fn house(&self) -> HouseName { self.house }
+
+    fn decide(&mut self, view: &PlayerView) -> Action {
+        self.decisions += 1;
+        if supported(view).is_err() {
+            let action = self.heuristic.decide(view);
+            self.info = self.heuristic.last_decision_info();
+            return action;
+        }
+        self.search(view)
+    }
+
+    fn last_decision_info(&self) -> Option<DecisionInfo> {
+        self.info.clone()
+    }
+
+    // `decide` searches whole decisions, so the per-decision methods are
+    // only reached when a caller invokes them directly; the heuristic
+    // answers them.
+
+    fn place_orders(&mut self, view: &PlayerView) -> Vec<(AreaId, u8)> {
+        self.heuristic.place_orders(view)
+    }
+
+    fn choose_order_to_resolve(&mut self, view: &PlayerView, order_type: OrderType, candidates: &[AreaId]) -> AreaId {
+        self.heuristic.choose_order_to_resolve(view, order_type, candidates)
+    }
+
+    fn choose_raid(&mut self, view: &PlayerView, from: AreaId, targets: &[AreaId]) -> Option<AreaId> {
+        self.heuristic.choose_raid(view, from, targets)
+    }
+
+    fn choose_march(&mut self, view: &PlayerView, from: AreaId, destinations: &[AreaId], selectable: &[usize]) -> (AreaId, Vec<usize>) {
+        self.heuristic.choose_march(view, from, destinations, selectable)
+    }
+
+    fn leave_power_token(&mut self, view: &PlayerView, area: AreaId) -> bool {
+        self.heuristic.leave_power_token(view, area)
+    }
+
+    fn declare_support(&mut self, view: &PlayerView, attacker: HouseName, defender: HouseName) -> SupportChoice {
+        self.heuristic.declare_support(view, attacker, defender)
+    }
+
+    fn select_house_card(&mut self, view: &PlayerView, available: &[HouseCardId]) -> HouseCardId {
+        self.heuristic.select_house_card(view, available)
+    }
+
+    fn use_valyrian_blade(&mut self, view: &PlayerView) -> bool {
+        self.heuristic.use_valyrian_blade(view)
+    }
+
+    fn submit_bid(&mut self, view: &PlayerView, bid_type: BiddingType, track: Option<Track>) -> u8 {
+        self.heuristic.submit_bid(view, bid_type, track)
+    }
+
+    fn westeros_choice(&mut self, view: &PlayerView, options: &[String]) -> usize {
+        self.heuristic.westeros_choice(view, options)
+    }
+
+    fn choose_muster(&mut self, view: &PlayerView, areas: &[MusterArea]) -> Vec<(AreaId, MusterAction2)> {
+        self.heuristic.choose_muster(view, areas)
+    }
+
+    fn choose_retreat(&mut self, view: &PlayerView, options: &[AreaId]) -> AreaId {
+        self.heuristic.choose_retreat(view, options)
+    }
+
+    fn choose_reconcile(&mut self, view: &PlayerView, area: AreaId) -> (AreaId, usize) {
+        self.heuristic.choose_reconcile(view, area)
+    }
+
+    fn use_messenger_raven(&mut self, view: &PlayerView) -> Option<(AreaId, u8)> {
+        self.heuristic.use_messenger_raven(view)
+    }
+
+    fn use_aeron(&mut self, view: &PlayerView, options: &[HouseCardId]) -> Option<HouseCardId> {
+        self.heuristic.use_aeron(view, options)
+    }
+
+    fn tyrion_replacement(&mut self, view: &PlayerView, options: &[HouseCardId]) -> HouseCardId {
+        self.heuristic.tyrion_replacement(view, options)
+    }
+
+    fn patchface_discard(&mut self, view: &PlayerView, visible: &[HouseCardId]) -> HouseCardId {
+        self.heuristic.patchface_discard(view, visible)
+    }
+
+    fn robb_retreat(&mut self, view: &PlayerView, options: &[AreaId]) -> AreaId {
+        self.heuristic.robb_retreat(view, options)
+    }
+
+    fn wildling_penalty(&mut self, view: &PlayerView, options: &[String]) -> usize {
+        self.heuristic.wildling_penalty(view, options)
+    }
+
+    fn cersei_remove_order(&mut self, view: &PlayerView) -> AreaId {
+        self.heuristic.cersei_remove_order(view)
+    }
+
+    fn doran_choose_track(&mut self, view: &PlayerView) -> Track {
+        self.heuristic.doran_choose_track(view)
+    }
+
+    fn queen_of_thorns(&mut self, view: &PlayerView) -> AreaId {
+        self.heuristic.queen_of_thorns(view)
+    }
+}
@@ -27,24 +27,20 @@ impl RandomAgent {
 }
 
 impl Agent for RandomAgent {
-    fn name(&self) -> &str { "Random" }
+    fn name(&self) -> &str { "random" }
     fn house(&self) -> HouseName { self.house }
 
+    // Reads only the board, its own power and tokens and the decision.
+    fn needs_view(&self) -> bool { false }
+
     fn place_orders(&mut self, view: &PlayerView) -> Vec<(AreaId, u8)> {
         let mut orders = Vec::new();
         let mut used_tokens: Vec<u8> = Vec::new();
 
         for area_view in &view.areas {
             if area_view.house == Some(self.house) && !area_view.units.is_empty() {
-                // Pick a random unused order token
-                let available: Vec<u8> = (0..15u8)
-                    .filter(|t| !used_tokens.contains(t))
-                    .filter(|&t| {
-                        let def = got_engine::types::ORDER_TOKENS[t as usize];
-                        // Respect order restrictions
-                        !view.order_restrictions.contains(&def.order_type)
-                    })
-                    .collect();
+                // Pick a random order token still free
+                let available = view.placeable_tokens(&used_tokens);
 
                 if let Some(&token) = available.choose(&mut self.rng) {
                     orders.push((area_view.id, token));
@@ -55,6 +51,10 @@ impl Agent for RandomAgent {
         orders
     }
 
+    fn choose_order_to_resolve(&mut self, _view: &PlayerView, _order_type: OrderType, candidates: &[AreaId]) -> AreaId {
+        *candidates.choose(&mut self.rng).unwrap()
+    }
+
     fn choose_raid(&mut self, _view: &PlayerView, _from: AreaId, targets: &[AreaId]) -> Option<AreaId> {
         if targets.is_empty() {
             None
@@ -63,18 +63,14 @@ impl Agent for RandomAgent {
         }
     }
 
-    fn choose_march(&mut self, view: &PlayerView, from: AreaId, destinations: &[AreaId]) -> (AreaId, Vec<usize>) {
+    fn choose_march(&mut self, _view: &PlayerView, from: AreaId, destinations: &[AreaId], selectable: &[usize]) -> (AreaId, Vec<usize>) {
         if destinations.is_empty() {
             // Can't move anywhere — stay (will trigger MarchSkip path)
             return (from, vec![]);
         }
         let &to = destinations.choose(&mut self.rng).unwrap();
-        let unit_count = view.areas.iter()
-            .find(|a| a.id == from)
-            .map_or(0, |a| a.units.len());
-        // Move all units
-        let unit_indices: Vec<usize> = (0..unit_count).collect();
-        (to, unit_indices)
+        // Move every unit that may go
+        (to, selectable.to_vec())
     }
 
     fn leave_power_token(&mut self, view: &PlayerView, _area: AreaId) -> bool {
@@ -108,12 +104,13 @@ impl Agent for RandomAgent {
         self.rng.gen_range(0..options.len())
     }
 
-    fn choose_muster(&mut self, _view: &PlayerView, areas: &[MusterArea]) -> Vec<(AreaId, MusterAction2)> {
-        // Simple: try to build a footman in each area
+    fn choose_muster(&mut self, view: &PlayerView, areas: &[MusterArea]) -> Vec<(AreaId, MusterAction2)> {
+        // Simple: build a footman in each area the pool and supply allow
         let mut actions = Vec::new();
         for muster_area in areas {
-            if muster_area.points >= 1 {
-                actions.push((muster_area.area_id, MusterAction2::Build(UnitType::Footman)));
+            let footman = MusterAction2::Build(UnitType::Footman);
+            if view.muster_options(muster_area.area_id, muster_area.points, &actions).iter().any(|(c, _)| *c == footman) {
+                actions.push((muster_area.area_id, footman));
             }
         }
         actions
@@ -132,13 +129,13 @@ impl Agent for RandomAgent {
         None // Random agent doesn't bother swapping
     }
 
-    fn use_aeron(&mut self, _view: &PlayerView) -> Option<HouseCardId> {
+    fn use_aeron(&mut self, _view: &PlayerView, _options: &[HouseCardId]) -> Option<HouseCardId> {
         None // Don't swap
     }
 
-    fn tyrion_replacement(&mut self, view: &PlayerView) -> HouseCardId {
+    fn tyrion_replacement(&mut self, _view: &PlayerView, options: &[HouseCardId]) -> HouseCardId {
         // Pick another random card from hand
-        *view.my_hand.choose(&mut self.rng).expect("No cards in hand")
+        *options.choose(&mut self.rng).expect("No cards in hand")
     }
 
     fn patchface_discard(&mut self, _view: &PlayerView, visible: &[HouseCardId]) -> HouseCardId {
@@ -154,12 +151,8 @@ impl Agent for RandomAgent {
     }
 
     fn cersei_remove_order(&mut self, view: &PlayerView) -> AreaId {
-        // Pick a random opponent area with an order
-        let targets: Vec<AreaId> = view.areas.iter()
-            .filter(|a| a.house.is_some() && a.house != Some(self.house) && a.order.is_some())
-            .map(|a| a.id)
-            .collect();
-        *targets.choose(&mut self.rng).unwrap_or(&AreaId(0))
+        // Pick a random order the decision offers
+        *view.removable_orders().choose(&mut self.rng).unwrap_or(&AreaId(0))
     }
 
     fn doran_choose_track(&mut self, _view: &PlayerView) -> Track {
@@ -171,6 +164,6 @@ impl Agent for RandomAgent {
     }
 
     fn queen_of_thorns(&mut self, view: &PlayerView) -> AreaId {
-        self.cersei_remove_order(view) // Same logic: random opponent order
+        self.cersei_remove_order(view) // Same logic: random offered order
     }
 }
@@ -0,0 +1,176 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Agent Registry — build agents from text specs
+//
+// A spec is an agent kind with optional parameters:
+//     random
+//     heuristic
+//     human
+//     kind{key=value,key2=value2}
+//     cloned{model=models/clone.bin}
+//     composite{base=heuristic,select_house_card=random}
+//     mcts{iterations=400,threads=4}
+//
+// The canonical form of a spec (parameters sorted by key) is the agent's
+// name everywhere results are recorded, so two configurations of the same
+// kind are rated separately.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::agent::Agent;
+use crate::cloned::ClonedAgent;
+use crate::composite::CompositeAgent;
+use crate::heuristic::HeuristicAgent;
+use crate::human::HumanAgent;
+use crate::mcts::MctsAgent;
+use crate::random::RandomAgent;
+use got_engine::types::HouseName;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Agent kinds the registry can build.
+pub const AGENT_KINDS: [&str; 6] = ["random", "heuristic", "human", "cloned", "composite", "mcts"];
+
+/// A parsed agent spec: `kind` or `kind{key=value,...}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentSpec {
+    pub kind: String,
+    pub params: BTreeMap<String, String>,
+}
+
+impl AgentSpec {
+    pub fn new(kind: &str) -> Self {
+        AgentSpec { kind: kind.to_string(), params: BTreeMap::new() }
+    }
+
+    /// Look up a parameter and parse it, falling back to `default` when absent.
+    pub fn param<T: FromStr>(&self, key: &str, default: T) -> Result<T, String> {
+        match self.params.get(key) {
+            Some(v) => v.parse().map_err(|_| format!("{}: invalid value for {}: {}", self.kind, key, v)),
+            None => Ok(default),
+        }
+    }
+
+    /// Construct the agent for a house.
+    pub fn build(&self, house: HouseName, seed: u64) -> Result<Box<dyn Agent>, String> {
+        match self.kind.as_str() {
+            "random" | "heuristic" | "human" if !self.params.is_empty() => {
+                Err(format!("{} takes no parameters, got {}", self.kind, self))
+            }
+            "random" => Ok(Box::new(RandomAgent::new(house, seed))),
+            "heuristic" => Ok(Box::new(HeuristicAgent::new(house, seed))),
+            "human" => Ok(Box::new(HumanAgent::stdio(house))),
+            "cloned" => {
+                let path = self.params.get("model").ok_or("cloned needs a model, e.g. cloned{model=clone.bin}")?;
+                Ok(Box::new(ClonedAgent::from_file(house, seed, path, self.to_string())?))
+            }
+            "composite" => Ok(Box::new(CompositeAgent::from_spec(self, house, seed)?)),
+            "mcts" => Ok(Box::new(MctsAgent::from_spec(self, house, seed)?)),
+            other => Err(format!("Unknown agent kind: {} (known: {})", other, AGENT_KINDS.join(", "))),
+        }
+    }
+}
+
+impl FromStr for AgentSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (kind, rest) = match s.find('{') {
+            Some(i) => (&s[..i], Some(&s[i + 1..])),
+            None => (s, None),
+        };
+        let kind = kind.trim().to_ascii_lowercase();
+        if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            return Err(format!("Invalid agent spec: {}", s));
+        }
+        let mut spec = AgentSpec::new(&kind);
+        if let Some(rest) = rest {
+            let body = rest.strip_suffix('}').ok_or_else(|| format!("Unclosed '{{' in agent spec: {}", s))?;
+            for pair in split_outside_braces(body, ',').into_iter().map(str::trim).filter(|p| !p.is_empty()) {
+                let (k, v) = pair.split_once('=').ok_or_else(|| format!("Expected key=value in agent spec: {}", pair))?;
+                if spec.params.insert(k.trim().to_string(), v.trim().to_string()).is_some() {
+                    return Err(format!("Duplicate parameter {} in agent spec: {}", k.trim(), s));
+                }
+            }
+        }
+        Ok(spec)
+    }
+}
+
+// Specs travel as their canonical text, e.g. "cloned{model=clone.bin}".
+impl Serialize for AgentSpec {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for AgentSpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl JsonSchema for AgentSpec {
+    fn schema_name() -> String {
+        "AgentSpec".to_string()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        String::json_schema(generator)
+    }
+}
+
+impl fmt::Display for AgentSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if !self.params.is_empty() {
+            let params: Vec<String> = self.params.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+            write!(f, "{{{}}}", params.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a lineup: one spec for every seat, specs separated by `/` (one per
+/// seat, cycled), or the shorthand `mixed` (heuristic on even seats, random
+/// on odd).
+pub fn parse_lineup(s: &str, seats: usize) -> Result<Vec<AgentSpec>, String> {
+    if s.trim().eq_ignore_ascii_case("mixed") {
+        return Ok((0..seats)
+            .map(|i| AgentSpec::new(if i % 2 == 0 { "heuristic" } else { "random" }))
+            .collect());
+    }
+    let specs = split_outside_braces(s, '/')
+        .into_iter()
+        .map(str::parse)
+        .collect::<Result<Vec<AgentSpec>, String>>()?;
+    if specs.is_empty() {
+        return Err("Empty agent lineup".to_string());
+    }
+    Ok((0..seats).map(|i| specs[i % specs.len()].clone()).collect())
+}
+
+/// Split on `sep`, leaving separators inside `{...}` (nested specs,
+/// file paths in parameters) alone.
+fn split_outside_braces(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            c if c == sep && depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + sep.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
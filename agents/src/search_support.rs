@@ -0,0 +1,405 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Search support — position hashing and a shared transposition table
+//
+// Building blocks for agents that search (MCTS, expectimax):
+//
+//   Zobrist hashing: every piece of a position (who controls an area,
+//   which units stand there, orders, garrisons, track places, supply,
+//   power, cards, round and phase) has a fixed random 64-bit key, and a
+//   position's hash is the XOR of the keys of everything in it. What
+//   isn't a board piece — the decision asked, the battle or auction under
+//   way, deck order, order restrictions — is folded in whole as an FNV-1a
+//   hash of its JSON. Keys and folds are the same in every run and
+//   process. A `PlayerView` hashes only what the viewer sees, so views
+//   that differ in hidden information share a hash — one information set,
+//   one entry.
+//
+//   TranspositionTable: a fixed number of slots shared between search
+//   threads, each behind its own lock. A slot keeps one entry; a new
+//   entry replaces it when the slot is free, holds the same position, or
+//   the new entry was searched at least as deep. Probe and store counts
+//   give the hit rate, which agents report in their `DecisionInfo`.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::agent::DecisionInfo;
+use got_engine::map::NUM_AREAS;
+use got_engine::types::*;
+use got_engine::visibility::PlayerView;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+// ── Zobrist keys ───────────────────────────────────────────────────────
+
+const HOUSES: usize = 6;
+const UNIT_TYPES: usize = 4;
+/// Units of one kind in an area beyond this many share the last key.
+const UNIT_SLOTS: usize = 8;
+const ORDER_TOKENS: usize = 15;
+const CARDS: usize = 42;
+
+/// One thing a position can contain; each has its own key.
+#[derive(Debug, Clone, Copy)]
+enum Feature {
+    Control { area: usize, house: usize },
+    Unit { area: usize, house: usize, unit_type: usize, routed: bool, nth: usize },
+    Order { area: usize, house: usize, token: usize },
+    HiddenOrder { area: usize },
+    Garrison { area: usize, house: Option<usize>, strength: usize },
+    Track { house: usize, track: usize, place: usize },
+    Supply { house: usize, level: usize },
+    Power { house: usize, tokens: usize },
+    Discarded { card: usize },
+    InHand { card: usize },
+    Round(usize),
+    Phase { phase: usize, sub_phase: usize },
+    Wildlings(usize),
+    BladeUsed,
+    RavenUsed,
+}
+
+const CONTROL: usize = 0;
+const UNIT: usize = CONTROL + NUM_AREAS * HOUSES;
+const ORDER: usize = UNIT + NUM_AREAS * HOUSES * UNIT_TYPES * 2 * UNIT_SLOTS;
+const HIDDEN_ORDER: usize = ORDER + NUM_AREAS * HOUSES * ORDER_TOKENS;
+const GARRISON: usize = HIDDEN_ORDER + NUM_AREAS;
+const TRACK: usize = GARRISON + NUM_AREAS * (HOUSES + 1) * 8;
+const SUPPLY: usize = TRACK + HOUSES * 3 * 8;
+const POWER: usize = SUPPLY + HOUSES * 8;
+const DISCARDED: usize = POWER + HOUSES * 32;
+const IN_HAND: usize = DISCARDED + CARDS;
+const ROUND: usize = IN_HAND + CARDS;
+const PHASE: usize = ROUND + 16;
+const WILDLINGS: usize = PHASE + 4 * 4;
+const DOMINANCE: usize = WILDLINGS + 16;
+const NUM_KEYS: usize = DOMINANCE + 2;
+
+impl Feature {
+    fn index(self) -> usize {
+        match self {
+            Feature::Control { area, house } => CONTROL + area * HOUSES + house,
+            Feature::Unit { area, house, unit_type, routed, nth } => {
+                UNIT + (((area * HOUSES + house) * UNIT_TYPES + unit_type) * 2 + routed as usize) * UNIT_SLOTS + nth.min(UNIT_SLOTS - 1)
+            }
+            Feature::Order { area, house, token } => ORDER + (area * HOUSES + house) * ORDER_TOKENS + token.min(ORDER_TOKENS - 1),
+            Feature::HiddenOrder { area } => HIDDEN_ORDER + area,
+            Feature::Garrison { area, house, strength } => {
+                GARRISON + (area * (HOUSES + 1) + house.unwrap_or(HOUSES)) * 8 + strength.min(7)
+            }
+            Feature::Track { house, track, place } => TRACK + (house * 3 + track) * 8 + place.min(7),
+            Feature::Supply { house, level } => SUPPLY + house * 8 + level.min(7),
+            Feature::Power { house, tokens } => POWER + house * 32 + tokens.min(31),
+            Feature::Discarded { card } => DISCARDED + card,
+            Feature::InHand { card } => IN_HAND + card,
+            Feature::Round(round) => ROUND + round.min(15),
+            Feature::Phase { phase, sub_phase } => PHASE + phase * 4 + sub_phase,
+            Feature::Wildlings(threat) => WILDLINGS + threat.min(15),
+            Feature::BladeUsed => DOMINANCE,
+            Feature::RavenUsed => DOMINANCE + 1,
+        }
+    }
+
+    fn key(self) -> u64 {
+        static KEYS: OnceLock<Vec<u64>> = OnceLock::new();
+        let keys = KEYS.get_or_init(|| {
+            // Fixed seed: hashes are stable across runs and processes
+            let mut rng = ChaCha8Rng::seed_from_u64(0x2a5b_0b1e_5eed_0001);
+            (0..NUM_KEYS).map(|_| rng.next_u64()).collect()
+        });
+        keys[self.index()]
+    }
+}
+
+/// The parts of a position folded in whole (`ZobristBuilder::fold`).
+#[derive(Debug, Clone, Copy)]
+enum Part {
+    Pending = 1,
+    /// The phase's progress: the battle, the auction and its sealed bids,
+    /// the Westeros cards drawn.
+    Phase,
+    Combat,
+    BidsIn,
+    WesterosDecks,
+    WildlingDeck,
+    Restrictions,
+}
+
+/// 64-bit FNV-1a of `bytes`, after the eight bytes of `seed`.
+fn fnv1a(seed: u64, bytes: &[u8]) -> u64 {
+    seed.to_le_bytes().iter().chain(bytes)
+        .fold(0xcbf2_9ce4_8422_2325, |h, &b| (h ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3))
+}
+
+fn house_index(house: HouseName) -> usize {
+    house as usize
+}
+
+/// XOR of feature keys, built up piece by piece.
+struct ZobristBuilder(u64);
+
+impl ZobristBuilder {
+    fn add(&mut self, feature: Feature) {
+        self.0 ^= feature.key();
+    }
+
+    fn units(&mut self, area: usize, units: &[Unit]) {
+        let mut seen = [[[0usize; 2]; UNIT_TYPES]; HOUSES];
+        for u in units {
+            let (house, unit_type) = (house_index(u.house), u.unit_type as usize);
+            let nth = &mut seen[house][unit_type][u.routed as usize];
+            self.add(Feature::Unit { area, house, unit_type, routed: u.routed, nth: *nth });
+            *nth += 1;
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn common(
+        &mut self,
+        round: u8,
+        phase: Phase,
+        sub_phase: ActionSubPhase,
+        wildling_threat: u8,
+        garrisons: &std::collections::HashMap<AreaId, Garrison>,
+        blade_used: bool,
+        raven_used: bool,
+        pending: Option<&PendingDecision>,
+    ) {
+        self.add(Feature::Round(round as usize));
+        self.add(Feature::Phase { phase: phase as usize, sub_phase: sub_phase as usize });
+        self.add(Feature::Wildlings(wildling_threat as usize));
+        for (area, g) in garrisons {
+            self.add(Feature::Garrison { area: area.0 as usize, house: g.house.map(house_index), strength: g.strength as usize });
+        }
+        if blade_used {
+            self.add(Feature::BladeUsed);
+        }
+        if raven_used {
+            self.add(Feature::RavenUsed);
+        }
+        if let Some(p) = pending {
+            self.fold(Part::Pending, p);
+        }
+    }
+
+    /// Fold in a part of the position that isn't a board piece: FNV-1a of
+    /// its JSON, started from the part so equal JSON in two parts differs.
+    /// It goes through `Value`, whose maps are sorted, so a `HashMap`'s
+    /// iteration order doesn't leak into the hash.
+    fn fold(&mut self, part: Part, value: &impl Serialize) {
+        let json = serde_json::to_value(value).map(|v| v.to_string()).unwrap_or_default();
+        self.0 ^= fnv1a(part as u64, json.as_bytes());
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn house(&mut self, house: HouseName, iron_throne: u8, fiefdoms: u8, kings_court: u8, supply: u8, power: u8, discards: &[HouseCardId]) {
+        let h = house_index(house);
+        for (track, place) in [iron_throne, fiefdoms, kings_court].into_iter().enumerate() {
+            self.add(Feature::Track { house: h, track, place: place as usize });
+        }
+        self.add(Feature::Supply { house: h, level: supply as usize });
+        self.add(Feature::Power { house: h, tokens: power as usize });
+        for &card in discards {
+            self.add(Feature::Discarded { card: card as usize });
+        }
+    }
+}
+
+/// Zobrist hash of a position, for transposition tables.
+pub trait ZobristHash {
+    fn zobrist(&self) -> u64;
+}
+
+/// Everything in the state, hidden information included: hands, sealed
+/// bids and deck order.
+impl ZobristHash for GameState {
+    fn zobrist(&self) -> u64 {
+        let mut z = ZobristBuilder(0);
+        for (area, a) in self.areas.iter().enumerate() {
+            if let Some(h) = a.house {
+                z.add(Feature::Control { area, house: house_index(h) });
+            }
+            z.units(area, &a.units);
+            if let Some(o) = &a.order {
+                z.add(Feature::Order { area, house: house_index(o.house), token: o.token_index as usize });
+            }
+        }
+        for (&house, p) in &self.houses {
+            z.house(house, p.iron_throne, p.fiefdoms, p.kings_court, p.supply, p.power, &p.discards);
+            for &card in &p.hand {
+                z.add(Feature::InHand { card: card as usize });
+            }
+        }
+        z.common(self.round, self.phase(), self.action_sub_phase(), self.wildling_threat, &self.garrisons,
+            self.valyrian_steel_blade_used, self.messenger_raven_used, self.peek_pending());
+        z.fold(Part::Phase, &self.engine_phase);
+        z.fold(Part::WesterosDecks, &[&self.westeros_deck_1, &self.westeros_deck_2, &self.westeros_deck_3]);
+        z.fold(Part::WildlingDeck, &self.wildling_deck);
+        z.fold(Part::Restrictions, &(&self.order_restrictions, &self.star_order_restrictions));
+        z.0
+    }
+}
+
+/// What the viewer sees: own hand and orders, opponents' orders only once
+/// revealed, the battle and who has bid but not the bids.
+impl ZobristHash for PlayerView {
+    fn zobrist(&self) -> u64 {
+        let mut z = ZobristBuilder(0);
+        for (area, a) in self.areas.iter().enumerate() {
+            if let Some(h) = a.house {
+                z.add(Feature::Control { area, house: house_index(h) });
+            }
+            z.units(area, &a.units);
+            if let Some(o) = &a.order {
+                z.add(Feature::Order { area, house: house_index(o.house), token: o.token_index as usize });
+            } else if a.has_hidden_order {
+                z.add(Feature::HiddenOrder { area });
+            }
+        }
+        for (&house, p) in &self.house_info {
+            z.house(house, p.iron_throne, p.fiefdoms, p.kings_court, p.supply, p.power, &p.discards);
+        }
+        for &card in &self.my_hand {
+            z.add(Feature::InHand { card: card as usize });
+        }
+        z.common(self.round, self.phase, self.action_sub_phase, self.wildling_threat, &self.garrisons,
+            self.valyrian_steel_blade_used, self.messenger_raven_used, self.pending.as_ref());
+        z.fold(Part::Combat, &self.combat);
+        z.fold(Part::BidsIn, &self.bids_in);
+        z.fold(Part::Restrictions, &(&self.order_restrictions, &self.star_order_restrictions));
+        z.0
+    }
+}
+
+// ── Transposition table ────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+struct Entry<T> {
+    key: u64,
+    depth: u32,
+    value: T,
+}
+
+/// Counters of table use since creation or the last `reset_stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TableStats {
+    pub probes: u64,
+    pub hits: u64,
+    pub stores: u64,
+    /// Stores that evicted a different position.
+    pub replacements: u64,
+    /// Stores turned away by a deeper entry for a different position.
+    pub rejected: u64,
+}
+
+impl TableStats {
+    pub fn hit_rate(&self) -> f64 {
+        self.hits as f64 / self.probes.max(1) as f64
+    }
+
+    /// Add the counters to a decision's search statistics.
+    pub fn record(&self, info: &mut DecisionInfo) {
+        info.stats.insert("tt_probes".into(), self.probes as f64);
+        info.stats.insert("tt_hit_rate".into(), self.hit_rate());
+        info.stats.insert("tt_stores".into(), self.stores as f64);
+        info.stats.insert("tt_replacements".into(), self.replacements as f64);
+    }
+}
+
+/// Fixed-size, thread-safe table from position hashes to search results,
+/// replacing by depth.
+pub struct TranspositionTable<T> {
+    slots: Box<[Mutex<Option<Entry<T>>>]>,
+    probes: AtomicU64,
+    hits: AtomicU64,
+    stores: AtomicU64,
+    replacements: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl<T: Clone> TranspositionTable<T> {
+    /// A table with room for `capacity` entries, rounded up to a power of two.
+    pub fn new(capacity: usize) -> Self {
+        let size = capacity.max(1).next_power_of_two();
+        TranspositionTable {
+            slots: (0..size).map(|_| Mutex::new(None)).collect(),
+            probes: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            stores: AtomicU64::new(0),
+            replacements: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    fn slot(&self, key: u64) -> &Mutex<Option<Entry<T>>> {
+        &self.slots[key as usize & (self.slots.len() - 1)]
+    }
+
+    /// The stored value for `key` and the depth it was searched to.
+    pub fn probe(&self, key: u64) -> Option<(T, u32)> {
+        self.probes.fetch_add(1, Ordering::Relaxed);
+        let slot = self.slot(key).lock().unwrap_or_else(|e| e.into_inner());
+        let found = slot.as_ref().filter(|e| e.key == key).map(|e| (e.value.clone(), e.depth));
+        if found.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        }
+        found
+    }
+
+    /// Store `value` for `key` unless a deeper entry for another position
+    /// holds the slot. Returns whether it was stored.
+    pub fn store(&self, key: u64, depth: u32, value: T) -> bool {
+        let mut slot = self.slot(key).lock().unwrap_or_else(|e| e.into_inner());
+        match slot.as_ref() {
+            Some(e) if e.key != key && e.depth > depth => {
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                return false;
+            }
+            Some(e) if e.key != key => {
+                self.replacements.fetch_add(1, Ordering::Relaxed);
+            }
+            _ => {}
+        }
+        self.stores.fetch_add(1, Ordering::Relaxed);
+        *slot = Some(Entry { key, depth, value });
+        true
+    }
+
+    /// Entries currently held.
+    pub fn len(&self) -> usize {
+        self.slots.iter().filter(|s| s.lock().unwrap_or_else(|e| e.into_inner()).is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop every entry; the counters are kept.
+    pub fn clear(&self) {
+        for s in self.slots.iter() {
+            *s.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        }
+    }
+
+    pub fn stats(&self) -> TableStats {
+        TableStats {
+            probes: self.probes.load(Ordering::Relaxed),
+            hits: self.hits.load(Ordering::Relaxed),
+            stores: self.stores.load(Ordering::Relaxed),
+            replacements: self.replacements.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Start counting afresh, e.g. at every decision.
+    pub fn reset_stats(&self) {
+        for c in [&self.probes, &self.hits, &self.stores, &self.replacements, &self.rejected] {
+            c.store(0, Ordering::Relaxed);
+        }
+    }
+}
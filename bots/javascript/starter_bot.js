@@ -0,0 +1,121 @@
+#!/usr/bin/env node
+// Starter bot for the Game of Thrones Strategy Lab.
+//
+// Speaks the lobby protocol, one JSON message per line:
+//
+//   node starter_bot.js                       # on stdin/stdout (protocol-tests)
+//   node starter_bot.js --connect host:7878   # join a lobby over TCP
+//
+// Every decision gets the simplest legal answer. Replace the functions in
+// DECIDE with your own; `view` is the PlayerView as JSON and the map from
+// the welcome message is in `bot.map`.
+
+"use strict";
+
+const net = require("net");
+const readline = require("readline");
+
+// Order token indices by type, plain tokens before the star token
+const TOKENS = {
+  March: [0, 1, 2],
+  Defense: [3, 4, 5],
+  Support: [6, 7, 8],
+  Raid: [9, 10, 11],
+  ConsolidatePower: [12, 13, 14],
+};
+const STAR_TOKENS = new Set([2, 5, 8, 11, 14]);
+
+const bot = { house: null, map: null };
+
+function ownAreas(view) {
+  return view.areas.filter((a) => a.house === bot.house && a.units.length > 0);
+}
+
+function placeOrders(view) {
+  // Plain tokens only, preferring the quiet orders; restricted types skipped
+  const preference = ["ConsolidatePower", "Defense", "Support", "Raid", "March"];
+  const tokens = preference
+    .filter((kind) => !view.order_restrictions.includes(kind))
+    .flatMap((kind) => TOKENS[kind])
+    .filter((t) => !STAR_TOKENS.has(t));
+  const orders = ownAreas(view).slice(0, tokens.length).map((area, i) => [area.id, tokens[i]]);
+  return { type: "place_orders", value: orders };
+}
+
+function reconcile(view, p) {
+  const index = view.areas[p.area_id].units.findIndex((u) => u.house === bot.house);
+  return { type: "reconcile", value: [p.area_id, index] };
+}
+
+const DECIDE = {
+  place_orders: (view) => placeOrders(view),
+  choose_order_to_resolve: (view, p) => ({ type: "resolve_order", value: p.candidates[0] }),
+  choose_raid: () => ({ type: "raid", value: null }),
+  choose_march: () => ({ type: "march_skip" }),
+  leave_power_token: () => ({ type: "leave_power_token", value: false }),
+  support_declaration: () => ({ type: "declare_support", value: "None" }),
+  select_house_card: (view, p) => ({ type: "select_card", value: p.available_cards[0] }),
+  use_valyrian_blade: () => ({ type: "use_valyrian_blade", value: false }),
+  bidding: () => ({ type: "bid", value: 0 }),
+  westeros_choice: () => ({ type: "westeros_choice", value: 0 }),
+  muster: () => ({ type: "muster", value: [] }),
+  retreat: (view, p) => ({ type: "retreat", value: p.possible_areas[0] }),
+  reconcile,
+  messenger_raven: () => ({ type: "messenger_raven", value: null }),
+  aeron_swap: () => ({ type: "aeron_swap", value: null }),
+  tyrion_replace: (view) => ({ type: "tyrion_replace", value: view.my_hand[0] }),
+  patchface_discard: (view, p) => ({ type: "patchface_discard", value: p.visible_cards[0] }),
+  robb_retreat: (view, p) => ({ type: "robb_retreat", value: p.possible_areas[0] }),
+  wildling_penalty_choice: () => ({ type: "wildling_penalty", value: 0 }),
+  cersei_remove_order: (view, p) => ({ type: "cersei_remove_order", value: p.valid_targets[0] }),
+  doran_choose_track: () => ({ type: "doran_choose_track", value: "IronThrone" }),
+  queen_of_thorns_remove_order: (view, p) => ({ type: "queen_of_thorns", value: p.valid_targets[0] }),
+};
+
+// The reply to one server message, or null.
+function handle(msg) {
+  switch (msg.type) {
+    case "welcome":
+      bot.house = msg.house;
+      bot.map = msg.map;
+      break;
+    case "decide": {
+      const pending = msg.view.pending;
+      return { type: "action", action: DECIDE[pending.type](msg.view, pending) };
+    }
+    case "rejected":
+      console.error("rejected: " + msg.reason);
+      break;
+    case "error":
+      console.error("error: " + msg.message);
+      break;
+  }
+  return null;
+}
+
+function play(input, send, done) {
+  const lines = readline.createInterface({ input });
+  lines.on("line", (line) => {
+    if (!line.trim()) return;
+    const msg = JSON.parse(line);
+    const reply = handle(msg);
+    if (reply !== null) send(reply);
+    if (msg.type === "game_over" || msg.type === "error") {
+      lines.close();
+      done();
+    }
+  });
+}
+
+const args = process.argv.slice(2);
+if (args.length === 2 && args[0] === "--connect") {
+  const [host, port] = [args[1].slice(0, args[1].lastIndexOf(":")), Number(args[1].slice(args[1].lastIndexOf(":") + 1))];
+  const socket = net.connect(port, host, () => {
+    const send = (msg) => socket.write(JSON.stringify(msg) + "\n");
+    send({ type: "join", house: null, name: "starter-bot-js" });
+    play(socket, send, () => socket.end());
+  });
+} else {
+  const send = (msg) => process.stdout.write(JSON.stringify(msg) + "\n");
+  play(process.stdin, send, () => process.exit(0));
+}
@@ -0,0 +1,129 @@
+#!/usr/bin/env python3
+"""Starter bot for the Game of Thrones Strategy Lab.
+
+Speaks the lobby protocol, one JSON message per line:
+
+    python3 starter_bot.py                      # on stdin/stdout (protocol-tests)
+    python3 starter_bot.py --connect host:7878  # join a lobby over TCP
+
+Every decision gets the simplest legal answer. Replace the functions in
+DECIDE with your own; `view` is the PlayerView as JSON and the map from
+the welcome message is in `bot.map`.
+"""
+
+import json
+import socket
+import sys
+
+# Order token indices by type, plain tokens before the star token
+TOKENS = {
+    "March": [0, 1, 2],
+    "Defense": [3, 4, 5],
+    "Support": [6, 7, 8],
+    "Raid": [9, 10, 11],
+    "ConsolidatePower": [12, 13, 14],
+}
+STAR_TOKENS = {2, 5, 8, 11, 14}
+
+
+class Bot:
+    def __init__(self):
+        self.house = None
+        self.map = None
+
+    def own_areas(self, view):
+        return [a for a in view["areas"] if a["house"] == self.house and a["units"]]
+
+
+def place_orders(bot, view, pending):
+    # Plain tokens only, preferring the quiet orders; restricted types skipped
+    preference = ["ConsolidatePower", "Defense", "Support", "Raid", "March"]
+    tokens = [t for kind in preference if kind not in view["order_restrictions"]
+              for t in TOKENS[kind] if t not in STAR_TOKENS]
+    orders = [[area["id"], token] for area, token in zip(bot.own_areas(view), tokens)]
+    return {"type": "place_orders", "value": orders}
+
+
+def reconcile(bot, view, pending):
+    units = view["areas"][pending["area_id"]]["units"]
+    index = next(i for i, u in enumerate(units) if u["house"] == bot.house)
+    return {"type": "reconcile", "value": [pending["area_id"], index]}
+
+
+DECIDE = {
+    "place_orders": place_orders,
+    "choose_order_to_resolve": lambda bot, view, p: {"type": "resolve_order", "value": p["candidates"][0]},
+    "choose_raid": lambda bot, view, p: {"type": "raid", "value": None},
+    "choose_march": lambda bot, view, p: {"type": "march_skip"},
+    "leave_power_token": lambda bot, view, p: {"type": "leave_power_token", "value": False},
+    "support_declaration": lambda bot, view, p: {"type": "declare_support", "value": "None"},
+    "select_house_card": lambda bot, view, p: {"type": "select_card", "value": p["available_cards"][0]},
+    "use_valyrian_blade": lambda bot, view, p: {"type": "use_valyrian_blade", "value": False},
+    "bidding": lambda bot, view, p: {"type": "bid", "value": 0},
+    "westeros_choice": lambda bot, view, p: {"type": "westeros_choice", "value": 0},
+    "muster": lambda bot, view, p: {"type": "muster", "value": []},
+    "retreat": lambda bot, view, p: {"type": "retreat", "value": p["possible_areas"][0]},
+    "reconcile": reconcile,
+    "messenger_raven": lambda bot, view, p: {"type": "messenger_raven", "value": None},
+    "aeron_swap": lambda bot, view, p: {"type": "aeron_swap", "value": None},
+    "tyrion_replace": lambda bot, view, p: {"type": "tyrion_replace", "value": view["my_hand"][0]},
+    "patchface_discard": lambda bot, view, p: {"type": "patchface_discard", "value": p["visible_cards"][0]},
+    "robb_retreat": lambda bot, view, p: {"type": "robb_retreat", "value": p["possible_areas"][0]},
+    "wildling_penalty_choice": lambda bot, view, p: {"type": "wildling_penalty", "value": 0},
+    "cersei_remove_order": lambda bot, view, p: {"type": "cersei_remove_order", "value": p["valid_targets"][0]},
+    "doran_choose_track": lambda bot, view, p: {"type": "doran_choose_track", "value": "IronThrone"},
+    "queen_of_thorns_remove_order": lambda bot, view, p: {"type": "queen_of_thorns", "value": p["valid_targets"][0]},
+}
+
+
+def handle(bot, msg):
+    """The reply to one server message, or None."""
+    kind = msg["type"]
+    if kind == "welcome":
+        bot.house, bot.map = msg["house"], msg.get("map")
+    elif kind == "decide":
+        view = msg["view"]
+        pending = view["pending"]
+        return {"type": "action", "action": DECIDE[pending["type"]](bot, view, pending)}
+    elif kind == "rejected":
+        print("rejected: " + msg["reason"], file=sys.stderr)
+    elif kind == "error":
+        print("error: " + msg["message"], file=sys.stderr)
+    return None
+
+
+def play(lines, send):
+    bot = Bot()
+    for line in lines:
+        if not line.strip():
+            continue
+        msg = json.loads(line)
+        reply = handle(bot, msg)
+        if reply is not None:
+            send(reply)
+        if msg["type"] in ("game_over", "error"):
+            return
+
+
+def main():
+    if len(sys.argv) == 3 and sys.argv[1] == "--connect":
+        host, port = sys.argv[2].rsplit(":", 1)
+        conn = socket.create_connection((host, int(port)))
+        stream = conn.makefile("rw", encoding="utf-8", newline="\n")
+
+        def send(msg):
+            stream.write(json.dumps(msg) + "\n")
+            stream.flush()
+
+        send({"type": "join", "house": None, "name": "starter-bot-py"})
+        play(stream, send)
+    else:
+        def send(msg):
+            sys.stdout.write(json.dumps(msg) + "\n")
+            sys.stdout.flush()
+
+        play(sys.stdin, send)
+
+
+if __name__ == "__main__":
+    main()
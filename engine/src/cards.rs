@@ -7,60 +7,61 @@ use crate::types::*;
 // ── House Cards ────────────────────────────────────────────────────────
 
 pub fn house_cards(house: HouseName) -> Vec<HouseCard> {
+    use CardAbility::*;
     match house {
         HouseName::Stark => vec![
-            HouseCard { id: HouseCardId::EddardStark,     house, strength: 4, swords: 2, fortifications: 0 },
-            HouseCard { id: HouseCardId::RobbStark,        house, strength: 3, swords: 0, fortifications: 0 },
-            HouseCard { id: HouseCardId::GreatjonUmber,    house, strength: 2, swords: 2, fortifications: 0 },
-            HouseCard { id: HouseCardId::RooseBolton,      house, strength: 2, swords: 0, fortifications: 0 },
-            HouseCard { id: HouseCardId::TheBlackfish,     house, strength: 1, swords: 0, fortifications: 0 },
-            HouseCard { id: HouseCardId::SerRodrikCassel,  house, strength: 1, swords: 0, fortifications: 2 },
-            HouseCard { id: HouseCardId::CatelynStark,     house, strength: 0, swords: 0, fortifications: 0 },
+            HouseCard { id: HouseCardId::EddardStark,     house, strength: 4, swords: 2, fortifications: 0, ability: None },
+            HouseCard { id: HouseCardId::RobbStark,        house, strength: 3, swords: 0, fortifications: 0, ability: ChooseOpponentRetreat },
+            HouseCard { id: HouseCardId::GreatjonUmber,    house, strength: 2, swords: 2, fortifications: 0, ability: FlatBonus { bonus: 1, side: CombatSide::Attacker } },
+            HouseCard { id: HouseCardId::RooseBolton,      house, strength: 2, swords: 0, fortifications: 0, ability: ReturnToHand },
+            HouseCard { id: HouseCardId::TheBlackfish,     house, strength: 1, swords: 0, fortifications: 0, ability: BonusPerAdjacentLand },
+            HouseCard { id: HouseCardId::SerRodrikCassel,  house, strength: 1, swords: 0, fortifications: 2, ability: None },
+            HouseCard { id: HouseCardId::CatelynStark,     house, strength: 0, swords: 0, fortifications: 0, ability: BonusPerDiscard },
         ],
         HouseName::Lannister => vec![
-            HouseCard { id: HouseCardId::TywinLannister,      house, strength: 4, swords: 0, fortifications: 0 },
-            HouseCard { id: HouseCardId::SerGregorClegane,     house, strength: 3, swords: 3, fortifications: 0 },
-            HouseCard { id: HouseCardId::SerJaimeLannister,    house, strength: 2, swords: 1, fortifications: 0 },
-            HouseCard { id: HouseCardId::TheHound,             house, strength: 2, swords: 0, fortifications: 2 },
-            HouseCard { id: HouseCardId::TyrionLannister,      house, strength: 1, swords: 0, fortifications: 0 },
-            HouseCard { id: HouseCardId::SerKevanLannister,    house, strength: 1, swords: 0, fortifications: 0 },
-            HouseCard { id: HouseCardId::CerseiLannister,      house, strength: 0, swords: 0, fortifications: 0 },
+            HouseCard { id: HouseCardId::TywinLannister,      house, strength: 4, swords: 0, fortifications: 0, ability: StealPower { amount: 2, min_margin: 0 } },
+            HouseCard { id: HouseCardId::SerGregorClegane,     house, strength: 3, swords: 3, fortifications: 0, ability: None },
+            HouseCard { id: HouseCardId::SerJaimeLannister,    house, strength: 2, swords: 1, fortifications: 0, ability: FlatBonus { bonus: 1, side: CombatSide::Attacker } },
+            HouseCard { id: HouseCardId::TheHound,             house, strength: 2, swords: 0, fortifications: 2, ability: None },
+            HouseCard { id: HouseCardId::TyrionLannister,      house, strength: 1, swords: 0, fortifications: 0, ability: CancelOpponentCard },
+            HouseCard { id: HouseCardId::SerKevanLannister,    house, strength: 1, swords: 0, fortifications: 0, ability: StealPowerByOpponentStrength },
+            HouseCard { id: HouseCardId::CerseiLannister,      house, strength: 0, swords: 0, fortifications: 0, ability: RemoveEnemyOrder { chosen: true } },
         ],
         HouseName::Baratheon => vec![
-            HouseCard { id: HouseCardId::StannisBaratheon,   house, strength: 4, swords: 0, fortifications: 0 },
-            HouseCard { id: HouseCardId::RenlyBaratheon,     house, strength: 3, swords: 0, fortifications: 0 },
-            HouseCard { id: HouseCardId::BrienneOfTarth,     house, strength: 2, swords: 1, fortifications: 1 },
-            HouseCard { id: HouseCardId::SerDavosSeaworth,   house, strength: 2, swords: 0, fortifications: 0 },
-            HouseCard { id: HouseCardId::Melisandre,         house, strength: 1, swords: 1, fortifications: 0 },
-            HouseCard { id: HouseCardId::SalladhorSaan,      house, strength: 1, swords: 0, fortifications: 0 },
-            HouseCard { id: HouseCardId::Patchface,          house, strength: 0, swords: 0, fortifications: 0 },
+            HouseCard { id: HouseCardId::StannisBaratheon,   house, strength: 4, swords: 0, fortifications: 0, ability: BonusPerAdjacentShip { ports: false } },
+            HouseCard { id: HouseCardId::RenlyBaratheon,     house, strength: 3, swords: 0, fortifications: 0, ability: NoCasualtiesOnWin },
+            HouseCard { id: HouseCardId::BrienneOfTarth,     house, strength: 2, swords: 1, fortifications: 1, ability: None },
+            HouseCard { id: HouseCardId::SerDavosSeaworth,   house, strength: 2, swords: 0, fortifications: 0, ability: UpgradeFootman },
+            HouseCard { id: HouseCardId::Melisandre,         house, strength: 1, swords: 1, fortifications: 0, ability: DiscardStrongestFromOpponentHand },
+            HouseCard { id: HouseCardId::SalladhorSaan,      house, strength: 1, swords: 0, fortifications: 0, ability: None },
+            HouseCard { id: HouseCardId::Patchface,          house, strength: 0, swords: 0, fortifications: 0, ability: DiscardFromOpponentHand },
         ],
         HouseName::Greyjoy => vec![
-            HouseCard { id: HouseCardId::EuronCrowsEye,     house, strength: 4, swords: 1, fortifications: 0 },
-            HouseCard { id: HouseCardId::VictarionGreyjoy,   house, strength: 3, swords: 0, fortifications: 0 },
-            HouseCard { id: HouseCardId::BalonGreyjoy,       house, strength: 2, swords: 0, fortifications: 0 },
-            HouseCard { id: HouseCardId::TheonGreyjoy,       house, strength: 2, swords: 0, fortifications: 0 },
-            HouseCard { id: HouseCardId::AshaGreyjoy,        house, strength: 1, swords: 0, fortifications: 0 },
-            HouseCard { id: HouseCardId::DagmerCleftjaw,     house, strength: 1, swords: 1, fortifications: 1 },
-            HouseCard { id: HouseCardId::AeronDamphair,      house, strength: 0, swords: 0, fortifications: 0 },
+            HouseCard { id: HouseCardId::EuronCrowsEye,     house, strength: 4, swords: 1, fortifications: 0, ability: None },
+            HouseCard { id: HouseCardId::VictarionGreyjoy,   house, strength: 3, swords: 0, fortifications: 0, ability: BonusPerAdjacentShip { ports: true } },
+            HouseCard { id: HouseCardId::BalonGreyjoy,       house, strength: 2, swords: 0, fortifications: 0, ability: CancelEnemySupport },
+            HouseCard { id: HouseCardId::TheonGreyjoy,       house, strength: 2, swords: 0, fortifications: 0, ability: StealPower { amount: 1, min_margin: 2 } },
+            HouseCard { id: HouseCardId::AshaGreyjoy,        house, strength: 1, swords: 0, fortifications: 0, ability: RetreatHome },
+            HouseCard { id: HouseCardId::DagmerCleftjaw,     house, strength: 1, swords: 1, fortifications: 1, ability: None },
+            HouseCard { id: HouseCardId::AeronDamphair,      house, strength: 0, swords: 0, fortifications: 0, ability: SwapCard { power_cost: 2 } },
         ],
         HouseName::Tyrell => vec![
-            HouseCard { id: HouseCardId::MaceTyrell,         house, strength: 4, swords: 0, fortifications: 0 },
-            HouseCard { id: HouseCardId::SerLorasTyrell,     house, strength: 3, swords: 0, fortifications: 0 },
-            HouseCard { id: HouseCardId::SerGarlanTyrell,    house, strength: 2, swords: 2, fortifications: 0 },
-            HouseCard { id: HouseCardId::RandyllTarly,       house, strength: 2, swords: 2, fortifications: 0 },
-            HouseCard { id: HouseCardId::MargaeryTyrell,     house, strength: 1, swords: 0, fortifications: 1 },
-            HouseCard { id: HouseCardId::AlesterFlorent,     house, strength: 1, swords: 0, fortifications: 1 },
-            HouseCard { id: HouseCardId::QueenOfThorns,      house, strength: 0, swords: 0, fortifications: 0 },
+            HouseCard { id: HouseCardId::MaceTyrell,         house, strength: 4, swords: 0, fortifications: 0, ability: BonusPerOwnSupport },
+            HouseCard { id: HouseCardId::SerLorasTyrell,     house, strength: 3, swords: 0, fortifications: 0, ability: MarchAgain },
+            HouseCard { id: HouseCardId::SerGarlanTyrell,    house, strength: 2, swords: 2, fortifications: 0, ability: None },
+            HouseCard { id: HouseCardId::RandyllTarly,       house, strength: 2, swords: 2, fortifications: 0, ability: None },
+            HouseCard { id: HouseCardId::MargaeryTyrell,     house, strength: 1, swords: 0, fortifications: 1, ability: None },
+            HouseCard { id: HouseCardId::AlesterFlorent,     house, strength: 1, swords: 0, fortifications: 1, ability: None },
+            HouseCard { id: HouseCardId::QueenOfThorns,      house, strength: 0, swords: 0, fortifications: 0, ability: RemoveAdjacentOrder },
         ],
         HouseName::Martell => vec![
-            HouseCard { id: HouseCardId::TheRedViper,        house, strength: 4, swords: 2, fortifications: 1 },
-            HouseCard { id: HouseCardId::AreoHotah,          house, strength: 3, swords: 0, fortifications: 1 },
-            HouseCard { id: HouseCardId::ObaraSand,          house, strength: 2, swords: 1, fortifications: 0 },
-            HouseCard { id: HouseCardId::Darkstar,           house, strength: 2, swords: 1, fortifications: 0 },
-            HouseCard { id: HouseCardId::NymeriaSand,        house, strength: 1, swords: 0, fortifications: 0 },
-            HouseCard { id: HouseCardId::ArianneMartell,     house, strength: 1, swords: 0, fortifications: 0 },
-            HouseCard { id: HouseCardId::DoranMartell,       house, strength: 0, swords: 0, fortifications: 0 },
+            HouseCard { id: HouseCardId::TheRedViper,        house, strength: 4, swords: 2, fortifications: 1, ability: None },
+            HouseCard { id: HouseCardId::AreoHotah,          house, strength: 3, swords: 0, fortifications: 1, ability: None },
+            HouseCard { id: HouseCardId::ObaraSand,          house, strength: 2, swords: 1, fortifications: 0, ability: FlatBonus { bonus: 1, side: CombatSide::Defender } },
+            HouseCard { id: HouseCardId::Darkstar,           house, strength: 2, swords: 1, fortifications: 0, ability: None },
+            HouseCard { id: HouseCardId::NymeriaSand,        house, strength: 1, swords: 0, fortifications: 0, ability: RemoveEnemyOrder { chosen: false } },
+            HouseCard { id: HouseCardId::ArianneMartell,     house, strength: 1, swords: 0, fortifications: 0, ability: NoCasualtiesNoConquest },
+            HouseCard { id: HouseCardId::DoranMartell,       house, strength: 0, swords: 0, fortifications: 0, ability: MoveOpponentToTrackBottom },
         ],
     }
 }
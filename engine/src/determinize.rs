@@ -0,0 +1,303 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Determinization — a full game state consistent with a player's view
+//
+// Search agents only get a `PlayerView`, but simulating needs a
+// `GameState`. A determinization fills in what the viewer cannot know
+// with one guess, drawn at random:
+//
+//   • opponents' hands: a random subset, of the public hand size, of the
+//     cards that are neither discarded nor on the table in a combat (a
+//     battle card back in hand, Roose Bolton's, is always in it)
+//   • opponents' face-down orders: distinct unused tokens, respecting the
+//     round's order restrictions and the owner's star limit
+//   • the Westeros and wildling decks: freshly shuffled
+//   • the engine's random stream: a new seed
+//
+// Everything public is copied from the view. The engine's bookkeeping is
+// rebuilt from it too, which only works where the view says enough about
+// where the game stands: planning (orders and the messenger raven), the
+// action phase and combat. Westeros-phase decisions and bids — card draw
+// progress, mustering order, bids already placed — are not covered, and
+// `determinize` refuses them.
+//
+// Sampling from scratch for every search iteration is costly. A
+// `DeterminizationPool` keeps a set of determinizations for one player
+// and brings them up to date with each new view (`redeterminize`):
+// public parts are copied again, guesses the new view still allows are
+// kept, and only the contradicted ones are drawn afresh.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::cards::all_house_card_ids;
+use crate::setup::{create_game, SetupConfig};
+use crate::tracks;
+use crate::types::*;
+use crate::visibility::PlayerView;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// Whether `determinize` can rebuild the position the view is asked about.
+pub fn supported(view: &PlayerView) -> Result<(), String> {
+    if view.winner.is_some() {
+        return Err("the game is over".to_string());
+    }
+    let pending = view.pending.as_ref().ok_or("the view has no pending decision")?;
+    if pending.house() != view.viewer {
+        return Err(format!("the pending decision belongs to {:?}, not the viewer", pending.house()));
+    }
+    match view.phase {
+        Phase::Planning | Phase::Action | Phase::Combat => {}
+        Phase::Westeros => return Err("Westeros-phase decisions are not determinized".to_string()),
+    }
+    if matches!(pending, PendingDecision::Bidding { .. }) {
+        return Err("bids are not determinized".to_string());
+    }
+    Ok(())
+}
+
+/// A game state that agrees with everything `view` shows, with the hidden
+/// parts drawn from `rng`. The state waits on the view's pending decision.
+pub fn determinize(view: &PlayerView, rng: &mut impl Rng) -> Result<GameState, String> {
+    supported(view)?;
+    let handicaps = view.playing_houses.iter()
+        .filter_map(|&h| view.house_info.get(&h).map(|info| (h, info.castles_to_win)))
+        .filter(|&(_, to_win)| to_win != CASTLES_TO_WIN)
+        .map(|(house, to_win)| Handicap { castle_adjust: (to_win as i16 - CASTLES_TO_WIN as i16) as i8, ..Handicap::none(house) })
+        .collect();
+    let config = SetupConfig {
+        houses: view.playing_houses.clone(),
+        seed: rng.next_u64(),
+        randomize_seats: false,
+        options: GameOptions { handicaps, ..GameOptions::default() },
+    };
+    // A fresh setup brings shuffled decks and everything the view doesn't
+    // describe in its starting form; the rest is filled in from the view.
+    let mut state = create_game(&config)?;
+    for profile in state.houses.values_mut() {
+        profile.hand.clear();
+    }
+    sync_with_view(&mut state, view, rng);
+    Ok(state)
+}
+
+/// Bring an earlier determinization of the same viewer up to date with a
+/// newer `view`. Public parts are copied again; the hidden guesses are kept
+/// where the view still allows them and redrawn where it doesn't, so the
+/// decks stay as they were shuffled. Returns how many hidden guesses
+/// (opponent cards and face-down orders) had to be redrawn.
+pub fn redeterminize(state: &mut GameState, view: &PlayerView, rng: &mut impl Rng) -> Result<usize, String> {
+    supported(view)?;
+    if state.playing_houses != view.playing_houses {
+        return Err("the view is of a different game".to_string());
+    }
+    Ok(sync_with_view(state, view, rng))
+}
+
+/// Copy the public parts of `view` into `state` and fill in the hidden
+/// parts, keeping what `state` already guessed where it still fits.
+/// Returns the number of guesses drawn afresh.
+fn sync_with_view(state: &mut GameState, view: &PlayerView, rng: &mut impl Rng) -> usize {
+    let mut drawn = 0;
+    state.round = view.round;
+    state.turn_order = view.turn_order.clone();
+    state.wildling_threat = view.wildling_threat;
+    state.garrisons = view.garrisons.clone();
+    state.valyrian_steel_blade_used = view.valyrian_steel_blade_used;
+    state.messenger_raven_used = view.messenger_raven_used;
+    state.order_restrictions = view.order_restrictions.clone();
+    state.star_order_restrictions = view.star_order_restrictions.clone();
+    state.winner = None;
+    state.events = Vec::new();
+
+    // Face-down orders guessed before, kept while the area is held by the
+    // same house and still shows a hidden order
+    let guessed: Vec<Option<Order>> = state.areas.iter().zip(&view.areas)
+        .map(|(area, a)| area.order.filter(|o| a.has_hidden_order && a.house == Some(o.house)))
+        .collect();
+    for (area, a) in state.areas.iter_mut().zip(&view.areas) {
+        area.units = a.units.clone();
+        area.house = a.house;
+        area.order = a.order;
+        area.blocked = a.blocked;
+    }
+    for (&house, info) in &view.house_info {
+        let hand = if house == view.viewer {
+            view.my_hand.clone()
+        } else {
+            let (hand, fresh) = sample_hand(view, house, &state.houses[&house].hand, rng);
+            drawn += fresh;
+            hand
+        };
+        let profile = state.house_mut(house);
+        profile.iron_throne = info.iron_throne;
+        profile.fiefdoms = info.fiefdoms;
+        profile.kings_court = info.kings_court;
+        profile.supply = info.supply;
+        profile.power = info.power;
+        profile.available_units = info.available_units;
+        profile.discards = info.discards.clone();
+        profile.card_stats = info.card_stats.clone();
+        profile.hand = hand;
+        profile.used_order_tokens = Vec::new();
+    }
+    for (i, a) in view.areas.iter().enumerate() {
+        if !a.has_hidden_order {
+            continue;
+        }
+        let Some(house) = a.house else { continue };
+        let kept = guessed[i].filter(|g| {
+            !state.areas.iter().any(|area| area.order.is_some_and(|o| o.house == house && o.token_index == g.token_index))
+        });
+        state.areas[i].order = match kept {
+            Some(order) => Some(order),
+            None => {
+                drawn += 1;
+                sample_order(state, house, rng)
+            }
+        };
+    }
+    // Before the reveal, placed tokens count as used by their owner
+    if view.phase == Phase::Planning {
+        for i in 0..state.areas.len() {
+            if let Some(o) = state.areas[i].order {
+                state.house_mut(o.house).used_order_tokens.push(o.token_index);
+            }
+        }
+    }
+
+    // Whose turn it is in the action cycle: the attacker during combat,
+    // otherwise the viewer, who is being asked.
+    let active = view.combat.as_ref().map_or(view.viewer, |c| c.attacker);
+    let player_index = state.turn_order.iter().position(|&h| h == active).unwrap_or(0) as u8;
+    let turn = ActionTurn { sub_phase: view.action_sub_phase, player_index };
+    state.engine_phase = match (view.phase, &view.combat) {
+        // Only the Messenger Raven is asked after the orders are flipped
+        (Phase::Planning, _) => EnginePhase::Planning {
+            orders_revealed: matches!(view.pending, Some(PendingDecision::MessengerRaven { .. })),
+        },
+        (Phase::Combat, Some(combat)) => EnginePhase::Combat { turn, combat: Box::new(combat.clone()) },
+        (Phase::Action | Phase::Combat, _) => EnginePhase::Action(turn),
+        (Phase::Westeros, _) => EnginePhase::westeros(),
+    };
+    state.pending = view.pending.iter().cloned().collect();
+    drawn
+}
+
+/// The cards `house` may be holding, as far as `view` can tell: the
+/// guessed cards in `previous` that are still possible, topped up at
+/// random to the public hand size. Also returns how many were drawn.
+fn sample_hand(view: &PlayerView, house: HouseName, previous: &[HouseCardId], rng: &mut impl Rng) -> (Vec<HouseCardId>, usize) {
+    let info = &view.house_info[&house];
+    let count = info.cards_in_hand as usize;
+    let in_combat = view.combat.as_ref()
+        .map(|c| {
+            let mut cards = Vec::new();
+            if c.attacker == house {
+                cards.extend(c.attacker_card);
+            }
+            if c.defender == house {
+                cards.extend(c.defender_card);
+            }
+            cards
+        })
+        .unwrap_or_default();
+    let possible = |c: &HouseCardId| !info.discards.contains(c) && !in_combat.contains(c);
+    // A card in the battle but not discarded went back to hand (Roose
+    // Bolton), so the house is known to hold it
+    let mut hand: Vec<HouseCardId> = in_combat.iter().copied().filter(|c| !info.discards.contains(c)).collect();
+    hand.extend(previous.iter().copied().filter(possible));
+    hand.truncate(count);
+    let mut rest: Vec<HouseCardId> = all_house_card_ids(house).into_iter()
+        .filter(|c| possible(c) && !hand.contains(c))
+        .collect();
+    rest.shuffle(rng);
+    let drawn = count.saturating_sub(hand.len()).min(rest.len());
+    hand.extend(rest.into_iter().take(drawn));
+    (hand, drawn)
+}
+
+/// A token `house` could have placed face down: unused, not restricted,
+/// and a star only while under its star limit.
+fn sample_order(state: &GameState, house: HouseName, rng: &mut impl Rng) -> Option<Order> {
+    let placed: Vec<&Order> = state.areas.iter()
+        .filter_map(|a| a.order.as_ref())
+        .filter(|o| o.house == house)
+        .collect();
+    let stars = placed.iter().filter(|o| o.star).count() as u8;
+    let star_room = stars < tracks::star_limit(state, house);
+    let allowed: Vec<u8> = (0..ORDER_TOKENS.len() as u8)
+        .filter(|&i| !placed.iter().any(|o| o.token_index == i))
+        .filter(|&i| {
+            let token = ORDER_TOKENS[i as usize];
+            !state.order_restrictions.contains(&token.order_type)
+                && (!token.star || (star_room && !state.star_order_restrictions.contains(&token.order_type)))
+        })
+        .collect();
+    let &token_index = allowed.choose(rng)?;
+    let token = ORDER_TOKENS[token_index as usize];
+    Some(Order { order_type: token.order_type, strength: token.strength, star: token.star, house, token_index })
+}
+
+// ── Pool ───────────────────────────────────────────────────────────────
+
+/// Upkeep counters of a pool since it was created.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Determinizations sampled from scratch.
+    pub built: u64,
+    /// Determinizations brought up to date with a newer view.
+    pub refreshed: u64,
+    /// Hidden guesses drawn afresh while refreshing.
+    pub redrawn: u64,
+}
+
+/// Determinizations of one player's information set, kept current from
+/// view to view.
+pub struct DeterminizationPool {
+    viewer: HouseName,
+    size: usize,
+    rng: ChaCha8Rng,
+    states: Vec<GameState>,
+    stats: PoolStats,
+}
+
+impl DeterminizationPool {
+    pub fn new(viewer: HouseName, size: usize, seed: u64) -> Self {
+        DeterminizationPool { viewer, size, rng: ChaCha8Rng::seed_from_u64(seed), states: Vec::new(), stats: PoolStats::default() }
+    }
+
+    /// Bring every determinization in line with `view` and fill the pool
+    /// up to its size.
+    pub fn sync(&mut self, view: &PlayerView) -> Result<&[GameState], String> {
+        if view.viewer != self.viewer {
+            return Err(format!("the pool belongs to {:?}, not {:?}", self.viewer, view.viewer));
+        }
+        supported(view)?;
+        if self.states.first().is_some_and(|s| s.playing_houses != view.playing_houses) {
+            self.states.clear();
+        }
+        for state in &mut self.states {
+            self.stats.redrawn += redeterminize(state, view, &mut self.rng)? as u64;
+            self.stats.refreshed += 1;
+        }
+        while self.states.len() < self.size {
+            self.states.push(determinize(view, &mut self.rng)?);
+            self.stats.built += 1;
+        }
+        Ok(&self.states)
+    }
+
+    /// The determinizations as of the last `sync`.
+    pub fn states(&self) -> &[GameState] {
+        &self.states
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        self.stats
+    }
+
+    /// Forget every determinization, e.g. when a new game starts.
+    pub fn clear(&mut self) {
+        self.states.clear();
+    }
+}
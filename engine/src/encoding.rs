@@ -0,0 +1,439 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Encoding — fixed-size numeric features for learning agents
+//
+// A PlayerView becomes a flat f32 vector of length OBS_LEN, and every
+// (decision, answer) pair becomes one or more (kind, focus, action index)
+// targets. Houses are encoded relative to the viewer: slot 0 is always the
+// viewer, the others follow in HouseName::ALL order. Only the PlayerView
+// is read, so features never contain hidden information.
+//
+// Decisions that set several things at once (PlaceOrders, Muster) are
+// split into one target per area; the area is marked as the "focus" in
+// the observation so a model can score it independently.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::types::*;
+use crate::engine::{Action, MusterAction2};
+use crate::map::{AREAS, NUM_AREAS};
+use crate::visibility::PlayerView;
+use crate::cards;
+
+// ── Decision kinds ─────────────────────────────────────────────────────
+
+/// One variant per `PendingDecision` type, in declaration order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DecisionKind {
+    WesterosChoice,
+    SupportDeclaration,
+    TyrionReplace,
+    AeronSwap,
+    PatchfaceDiscard,
+    RobbRetreat,
+    Retreat,
+    Reconcile,
+    Muster,
+    Bidding,
+    LeavePowerToken,
+    UseValyrianBlade,
+    PlaceOrders,
+    ChooseOrderToResolve,
+    ChooseRaid,
+    ChooseMarch,
+    SelectHouseCard,
+    MessengerRaven,
+    WildlingPenaltyChoice,
+    CerseiRemoveOrder,
+    DoranChooseTrack,
+    QueenOfThornsRemoveOrder,
+}
+
+const NUM_CARDS: u32 = 42;
+/// Bids above this are clamped into the last action index.
+const MAX_BID: u32 = 20;
+/// Muster choices per area: nothing, four unit types, upgrade.
+const MUSTER_CHOICES: u32 = 6;
+
+impl DecisionKind {
+    pub const ALL: [DecisionKind; 22] = [
+        DecisionKind::WesterosChoice,
+        DecisionKind::SupportDeclaration,
+        DecisionKind::TyrionReplace,
+        DecisionKind::AeronSwap,
+        DecisionKind::PatchfaceDiscard,
+        DecisionKind::RobbRetreat,
+        DecisionKind::Retreat,
+        DecisionKind::Reconcile,
+        DecisionKind::Muster,
+        DecisionKind::Bidding,
+        DecisionKind::LeavePowerToken,
+        DecisionKind::UseValyrianBlade,
+        DecisionKind::PlaceOrders,
+        DecisionKind::ChooseOrderToResolve,
+        DecisionKind::ChooseRaid,
+        DecisionKind::ChooseMarch,
+        DecisionKind::SelectHouseCard,
+        DecisionKind::MessengerRaven,
+        DecisionKind::WildlingPenaltyChoice,
+        DecisionKind::CerseiRemoveOrder,
+        DecisionKind::DoranChooseTrack,
+        DecisionKind::QueenOfThornsRemoveOrder,
+    ];
+
+    pub fn of(pending: &PendingDecision) -> Self {
+        match pending {
+            PendingDecision::WesterosChoice { .. } => DecisionKind::WesterosChoice,
+            PendingDecision::SupportDeclaration { .. } => DecisionKind::SupportDeclaration,
+            PendingDecision::TyrionReplace { .. } => DecisionKind::TyrionReplace,
+            PendingDecision::AeronSwap { .. } => DecisionKind::AeronSwap,
+            PendingDecision::PatchfaceDiscard { .. } => DecisionKind::PatchfaceDiscard,
+            PendingDecision::RobbRetreat { .. } => DecisionKind::RobbRetreat,
+            PendingDecision::Retreat { .. } => DecisionKind::Retreat,
+            PendingDecision::Reconcile { .. } => DecisionKind::Reconcile,
+            PendingDecision::Muster { .. } => DecisionKind::Muster,
+            PendingDecision::Bidding { .. } => DecisionKind::Bidding,
+            PendingDecision::LeavePowerToken { .. } => DecisionKind::LeavePowerToken,
+            PendingDecision::UseValyrianBlade { .. } => DecisionKind::UseValyrianBlade,
+            PendingDecision::PlaceOrders { .. } => DecisionKind::PlaceOrders,
+            PendingDecision::ChooseOrderToResolve { .. } => DecisionKind::ChooseOrderToResolve,
+            PendingDecision::ChooseRaid { .. } => DecisionKind::ChooseRaid,
+            PendingDecision::ChooseMarch { .. } => DecisionKind::ChooseMarch,
+            PendingDecision::SelectHouseCard { .. } => DecisionKind::SelectHouseCard,
+            PendingDecision::MessengerRaven { .. } => DecisionKind::MessengerRaven,
+            PendingDecision::WildlingPenaltyChoice { .. } => DecisionKind::WildlingPenaltyChoice,
+            PendingDecision::CerseiRemoveOrder { .. } => DecisionKind::CerseiRemoveOrder,
+            PendingDecision::DoranChooseTrack { .. } => DecisionKind::DoranChooseTrack,
+            PendingDecision::QueenOfThornsRemoveOrder { .. } => DecisionKind::QueenOfThornsRemoveOrder,
+        }
+    }
+
+    /// The kind of decision `action` answers; None for `Resign` and
+    /// `OfferDraw`, which may come at any decision.
+    pub fn answered_by(action: &Action) -> Option<Self> {
+        Some(match action {
+            Action::PlaceOrders(_) => DecisionKind::PlaceOrders,
+            Action::ResolveOrder(_) => DecisionKind::ChooseOrderToResolve,
+            Action::Raid(_) => DecisionKind::ChooseRaid,
+            Action::March { .. } | Action::MarchSkip => DecisionKind::ChooseMarch,
+            Action::LeavePowerToken(_) => DecisionKind::LeavePowerToken,
+            Action::DeclareSupport(_) => DecisionKind::SupportDeclaration,
+            Action::SelectCard(_) => DecisionKind::SelectHouseCard,
+            Action::UseValyrianBlade(_) => DecisionKind::UseValyrianBlade,
+            Action::Bid(_) => DecisionKind::Bidding,
+            Action::WesterosChoice(_) => DecisionKind::WesterosChoice,
+            Action::Muster(_) => DecisionKind::Muster,
+            Action::Retreat(_) => DecisionKind::Retreat,
+            Action::Reconcile(..) => DecisionKind::Reconcile,
+            Action::MessengerRaven(_) => DecisionKind::MessengerRaven,
+            Action::AeronSwap(_) => DecisionKind::AeronSwap,
+            Action::TyrionReplace(_) => DecisionKind::TyrionReplace,
+            Action::PatchfaceDiscard(_) => DecisionKind::PatchfaceDiscard,
+            Action::RobbRetreat(_) => DecisionKind::RobbRetreat,
+            Action::CerseiRemoveOrder(_) => DecisionKind::CerseiRemoveOrder,
+            Action::DoranChooseTrack(_) => DecisionKind::DoranChooseTrack,
+            Action::QueenOfThorns(_) => DecisionKind::QueenOfThornsRemoveOrder,
+            Action::WildlingPenalty(_) => DecisionKind::WildlingPenaltyChoice,
+            Action::Resign | Action::OfferDraw => return None,
+        })
+    }
+
+    /// Stable numeric id (position in `ALL`).
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn name(self) -> String {
+        format!("{:?}", self)
+    }
+
+    /// snake_case name, e.g. `select_house_card`.
+    pub fn key(self) -> String {
+        let mut key = String::new();
+        for (i, c) in self.name().chars().enumerate() {
+            if c.is_ascii_uppercase() && i > 0 {
+                key.push('_');
+            }
+            key.push(c.to_ascii_lowercase());
+        }
+        key
+    }
+
+    /// Number of distinct action indices this kind can produce.
+    pub fn action_space(self) -> u32 {
+        let areas = NUM_AREAS as u32;
+        match self {
+            DecisionKind::WesterosChoice | DecisionKind::WildlingPenaltyChoice => 5,
+            DecisionKind::SupportDeclaration => 3,
+            DecisionKind::TyrionReplace | DecisionKind::PatchfaceDiscard | DecisionKind::SelectHouseCard => NUM_CARDS,
+            // Last index = decline
+            DecisionKind::AeronSwap => NUM_CARDS + 1,
+            DecisionKind::ChooseRaid | DecisionKind::ChooseMarch => areas + 1,
+            DecisionKind::RobbRetreat | DecisionKind::Retreat | DecisionKind::ChooseOrderToResolve
+            | DecisionKind::CerseiRemoveOrder | DecisionKind::QueenOfThornsRemoveOrder => areas,
+            // Unit index within the focus area
+            DecisionKind::Reconcile => 16,
+            DecisionKind::Muster => MUSTER_CHOICES,
+            DecisionKind::Bidding => MAX_BID + 1,
+            DecisionKind::LeavePowerToken | DecisionKind::UseValyrianBlade => 2,
+            DecisionKind::PlaceOrders => ORDER_TOKENS.len() as u32,
+            // area × token, last index = no swap
+            DecisionKind::MessengerRaven => areas * ORDER_TOKENS.len() as u32 + 1,
+            DecisionKind::DoranChooseTrack => 3,
+        }
+    }
+}
+
+impl std::str::FromStr for DecisionKind {
+    type Err = String;
+
+    /// Accepts the snake_case key or the variant name, in any case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let wanted: String = s.chars().filter(|&c| c != '_').collect::<String>().to_ascii_lowercase();
+        DecisionKind::ALL.into_iter()
+            .find(|k| k.name().to_ascii_lowercase() == wanted)
+            .ok_or_else(|| format!("Unknown decision type: {}", s))
+    }
+}
+
+// ── Action targets ─────────────────────────────────────────────────────
+
+/// One supervised target: which decision, which area it is about (if
+/// split per area) and the index of the chosen answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecisionTarget {
+    pub kind: DecisionKind,
+    pub focus: Option<AreaId>,
+    pub action: u32,
+}
+
+/// Global index of a house card (declaration order of `HouseCardId`).
+pub fn card_index(card: HouseCardId) -> u32 {
+    card as u32
+}
+
+fn track_index(track: Track) -> u32 {
+    match track {
+        Track::IronThrone => 0,
+        Track::Fiefdoms => 1,
+        Track::KingsCourt => 2,
+    }
+}
+
+fn muster_index(action: &MusterAction2) -> u32 {
+    match action {
+        MusterAction2::Build(UnitType::Footman) => 1,
+        MusterAction2::Build(UnitType::Knight) => 2,
+        MusterAction2::Build(UnitType::Ship) | MusterAction2::BuildShip(_) => 3,
+        MusterAction2::Build(UnitType::SiegeEngine) => 4,
+        MusterAction2::Upgrade => 5,
+    }
+}
+
+/// Targets for the answer `action` to `pending`. Empty when the action
+/// does not answer that decision.
+pub fn decision_targets(pending: &PendingDecision, action: &Action) -> Vec<DecisionTarget> {
+    let kind = DecisionKind::of(pending);
+    let one = |action: u32| vec![DecisionTarget { kind, focus: None, action }];
+    let none = NUM_AREAS as u32;
+
+    match (pending, action) {
+        (PendingDecision::PlaceOrders { .. }, Action::PlaceOrders(orders)) => orders.iter()
+            .map(|&(area, token)| DecisionTarget { kind, focus: Some(area), action: token as u32 })
+            .collect(),
+        (PendingDecision::Muster { areas, .. }, Action::Muster(actions)) => areas.iter()
+            .map(|m| {
+                let chosen = actions.iter().find(|(a, _)| *a == m.area_id).map_or(0, |(_, act)| muster_index(act));
+                DecisionTarget { kind, focus: Some(m.area_id), action: chosen }
+            })
+            .collect(),
+        (PendingDecision::Reconcile { area_id, .. }, Action::Reconcile(_, idx)) => {
+            vec![DecisionTarget { kind, focus: Some(*area_id), action: (*idx as u32).min(15) }]
+        }
+        (PendingDecision::ChooseRaid { from_area, .. }, Action::Raid(target)) => {
+            vec![DecisionTarget { kind, focus: Some(*from_area), action: target.map_or(none, |a| a.0 as u32) }]
+        }
+        (PendingDecision::ChooseMarch { from_area, .. }, Action::March { to, unit_indices }) => {
+            let dest = if unit_indices.is_empty() || to == from_area { none } else { to.0 as u32 };
+            vec![DecisionTarget { kind, focus: Some(*from_area), action: dest }]
+        }
+        (PendingDecision::ChooseMarch { from_area, .. }, Action::MarchSkip) => {
+            vec![DecisionTarget { kind, focus: Some(*from_area), action: none }]
+        }
+        (PendingDecision::LeavePowerToken { area_id, .. }, Action::LeavePowerToken(leave)) => {
+            vec![DecisionTarget { kind, focus: Some(*area_id), action: *leave as u32 }]
+        }
+        (PendingDecision::SupportDeclaration { area_id, .. }, Action::DeclareSupport(choice)) => {
+            let idx = match choice {
+                SupportChoice::Attacker => 0,
+                SupportChoice::Defender => 1,
+                SupportChoice::None => 2,
+            };
+            vec![DecisionTarget { kind, focus: Some(*area_id), action: idx }]
+        }
+        (PendingDecision::ChooseOrderToResolve { .. }, Action::ResolveOrder(area)) => one(area.0 as u32),
+        (PendingDecision::SelectHouseCard { .. }, Action::SelectCard(card))
+        | (PendingDecision::TyrionReplace { .. }, Action::TyrionReplace(card))
+        | (PendingDecision::PatchfaceDiscard { .. }, Action::PatchfaceDiscard(card)) => one(card_index(*card)),
+        (PendingDecision::AeronSwap { .. }, Action::AeronSwap(card)) => one(card.map_or(NUM_CARDS, card_index)),
+        (PendingDecision::UseValyrianBlade { .. }, Action::UseValyrianBlade(used)) => one(*used as u32),
+        (PendingDecision::Bidding { .. }, Action::Bid(amount)) => one((*amount as u32).min(MAX_BID)),
+        (PendingDecision::WesterosChoice { .. }, Action::WesterosChoice(idx))
+        | (PendingDecision::WildlingPenaltyChoice { .. }, Action::WildlingPenalty(idx)) => one(*idx as u32),
+        (PendingDecision::Retreat { .. }, Action::Retreat(area))
+        | (PendingDecision::RobbRetreat { .. }, Action::RobbRetreat(area))
+        | (PendingDecision::CerseiRemoveOrder { .. }, Action::CerseiRemoveOrder(area))
+        | (PendingDecision::QueenOfThornsRemoveOrder { .. }, Action::QueenOfThorns(area)) => one(area.0 as u32),
+        (PendingDecision::MessengerRaven { .. }, Action::MessengerRaven(swap)) => {
+            let tokens = ORDER_TOKENS.len() as u32;
+            one(swap.map_or(none * tokens, |(area, token)| area.0 as u32 * tokens + token as u32))
+        }
+        (PendingDecision::DoranChooseTrack { .. }, Action::DoranChooseTrack(track)) => one(track_index(*track)),
+        _ => Vec::new(),
+    }
+}
+
+// ── Observation ────────────────────────────────────────────────────────
+
+const NUM_SLOTS: usize = 6;
+const NUM_ORDER_TYPES: usize = 5;
+const GLOBAL_FEATURES: usize = 2 + 4 + 4 + 2 + 2 * NUM_ORDER_TYPES;
+const HOUSE_FEATURES: usize = 9;
+const HAND_FEATURES: usize = 7;
+const COMBAT_FEATURES: usize = 5;
+/// Controller slot, own/enemy units by type, routed, order (type, star,
+/// strength, own, hidden), garrison (strength, neutral), blocked, combat, focus.
+const AREA_FEATURES: usize = NUM_SLOTS + 8 + 1 + (NUM_ORDER_TYPES + 4) + 2 + 3;
+
+/// Length of every vector produced by `encode_observation`.
+pub const OBS_LEN: usize = GLOBAL_FEATURES
+    + DecisionKind::ALL.len()
+    + NUM_SLOTS * HOUSE_FEATURES
+    + HAND_FEATURES
+    + COMBAT_FEATURES
+    + NUM_AREAS * AREA_FEATURES;
+
+/// Houses in slot order: the viewer first, then the rest of HouseName::ALL.
+pub fn house_slots(viewer: HouseName) -> [HouseName; NUM_SLOTS] {
+    let mut slots = [viewer; NUM_SLOTS];
+    for (slot, h) in slots.iter_mut().skip(1).zip(HouseName::ALL.iter().filter(|&&h| h != viewer)) {
+        *slot = *h;
+    }
+    slots
+}
+
+fn one_hot(out: &mut Vec<f32>, len: usize, hot: Option<usize>) {
+    out.extend((0..len).map(|i| if Some(i) == hot { 1.0 } else { 0.0 }));
+}
+
+fn order_type_index(t: OrderType) -> usize {
+    match t {
+        OrderType::March => 0,
+        OrderType::Raid => 1,
+        OrderType::Support => 2,
+        OrderType::Defense => 3,
+        OrderType::ConsolidatePower => 4,
+    }
+}
+
+fn flags(out: &mut Vec<f32>, types: &[OrderType]) {
+    let mut f = [0.0; NUM_ORDER_TYPES];
+    for &t in types {
+        f[order_type_index(t)] = 1.0;
+    }
+    out.extend(f);
+}
+
+/// Encode what `view.viewer` can see. `focus` marks the area a per-area
+/// decision is about (an order slot, a muster area, a marching army).
+pub fn encode_observation(view: &PlayerView, focus: Option<AreaId>) -> Vec<f32> {
+    let mut out = Vec::with_capacity(OBS_LEN);
+    let slots = house_slots(view.viewer);
+    let slot_of = |h: HouseName| slots.iter().position(|&s| s == h);
+
+    // Global
+    out.push(view.round as f32 / 10.0);
+    out.push(view.wildling_threat as f32 / 12.0);
+    one_hot(&mut out, 4, Some(view.phase as usize));
+    one_hot(&mut out, 4, Some(view.action_sub_phase as usize));
+    out.push(view.valyrian_steel_blade_used as u8 as f32);
+    out.push(view.messenger_raven_used as u8 as f32);
+    flags(&mut out, &view.order_restrictions);
+    flags(&mut out, &view.star_order_restrictions);
+
+    one_hot(&mut out, DecisionKind::ALL.len(), view.pending.as_ref().map(|p| DecisionKind::of(p).index()));
+
+    // Houses
+    for &h in &slots {
+        match view.house_info.get(&h) {
+            Some(info) => {
+                let castles = view.areas.iter()
+                    .filter(|a| a.house == Some(h) && AREAS[a.id.0 as usize].has_castle_or_stronghold())
+                    .count();
+                let units = view.areas.iter().flat_map(|a| &a.units).filter(|u| u.house == h).count();
+                out.extend([
+                    1.0,
+                    info.iron_throne as f32 / 6.0,
+                    info.fiefdoms as f32 / 6.0,
+                    info.kings_court as f32 / 6.0,
+                    info.supply as f32 / 6.0,
+                    info.power as f32 / 20.0,
+                    info.cards_in_hand as f32 / 7.0,
+                    castles as f32 / 7.0,
+                    units as f32 / 20.0,
+                ]);
+            }
+            None => out.extend([0.0; HOUSE_FEATURES]),
+        }
+    }
+
+    // Own hand, in the house's card order
+    for card in cards::all_house_card_ids(view.viewer) {
+        out.push(view.my_hand.contains(&card) as u8 as f32);
+    }
+
+    // Combat
+    let combat_area = view.combat.as_ref().map(|c| c.area_id);
+    match &view.combat {
+        Some(c) => {
+            let strength = |units: &[Unit]| units.iter().map(|u| u.unit_type.combat_strength() as f32).sum::<f32>();
+            out.extend([
+                1.0,
+                (c.attacker == view.viewer) as u8 as f32,
+                (c.defender == view.viewer) as u8 as f32,
+                strength(&c.attacking_units) / 10.0,
+                strength(&c.defending_units) / 10.0,
+            ]);
+        }
+        None => out.extend([0.0; COMBAT_FEATURES]),
+    }
+
+    // Areas
+    for area in &view.areas {
+        one_hot(&mut out, NUM_SLOTS, area.house.and_then(slot_of));
+        let mut own = [0.0f32; 4];
+        let mut enemy = [0.0f32; 4];
+        for u in &area.units {
+            let counts = if u.house == view.viewer { &mut own } else { &mut enemy };
+            counts[u.unit_type as usize] += 1.0 / 3.0;
+        }
+        out.extend(own);
+        out.extend(enemy);
+        out.push(area.units.iter().any(|u| u.routed) as u8 as f32);
+
+        one_hot(&mut out, NUM_ORDER_TYPES, area.order.map(|o| order_type_index(o.order_type)));
+        out.push(area.order.is_some_and(|o| o.star) as u8 as f32);
+        out.push(area.order.map_or(0.0, |o| o.strength as f32 / 2.0));
+        out.push(area.order.is_some_and(|o| o.house == view.viewer) as u8 as f32);
+        out.push(area.has_hidden_order as u8 as f32);
+
+        let garrison = view.garrisons.get(&area.id);
+        out.push(garrison.map_or(0.0, |g| g.strength as f32 / 6.0));
+        out.push(garrison.is_some_and(|g| g.house.is_none()) as u8 as f32);
+
+        out.push(area.blocked as u8 as f32);
+        out.push((combat_area == Some(area.id)) as u8 as f32);
+        out.push((focus == Some(area.id)) as u8 as f32);
+    }
+
+    debug_assert_eq!(out.len(), OBS_LEN);
+    out
+}
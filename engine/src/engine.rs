@@ -3,7 +3,8 @@
 //
 // Architecture:
 //   Pure state machine. Never does I/O or calls agents.
-//   Sets `state.pending` to describe what decision is needed,
+//   Queues pending decisions on `state.pending` (FIFO; the head is
+//   the decision needed now),
 //   and the runner feeds answers back via `apply_action()`.
 //
 // Flow: advance() → pending set → agent decide → apply_action() → repeat
@@ -12,18 +13,25 @@
 use crate::types::*;
 use crate::map::*;
 use crate::supply;
-use crate::navigation;
+use crate::navigation::{self, Board};
 use crate::cards;
-use std::collections::HashMap;
+use crate::tracks;
+use crate::encoding::DecisionKind;
+use std::collections::{HashMap, VecDeque};
+use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
 use rand::seq::SliceRandom;
 use rand::SeedableRng;
 use rand_chacha::ChaCha8Rng;
 
 // ── Action enum ────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone)]
+/// Wire form: `{"type": "bid", "value": 2}`; see `wire` for the policy.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
 pub enum Action {
     PlaceOrders(Vec<(AreaId, u8)>),
+    ResolveOrder(AreaId),
     Raid(Option<AreaId>),
     March { to: AreaId, unit_indices: Vec<usize> },
     MarchSkip,
@@ -45,30 +53,365 @@ pub enum Action {
     DoranChooseTrack(Track),
     QueenOfThorns(AreaId),
     WildlingPenalty(usize),
+    /// Leave the game, instead of answering. Allowed at any decision
+    /// outside a battle.
+    Resign,
+    /// Offer a draw, then answer the decision as usual. The game ends
+    /// drawn once every house still playing has offered in the same round.
+    OfferDraw,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
 pub enum MusterAction2 {
     Build(UnitType),
+    /// A ship into an adjacent sea or the castle's port
+    BuildShip(AreaId),
     Upgrade, // Footman → Knight
 }
 
-// ── Helpers ────────────────────────────────────────────────────────────
+impl MusterAction2 {
+    /// Muster points it takes.
+    pub fn cost(&self) -> u8 {
+        match self {
+            MusterAction2::Build(unit_type) => unit_type.muster_cost(),
+            MusterAction2::BuildShip(_) => UnitType::Ship.muster_cost(),
+            MusterAction2::Upgrade => 1,
+        }
+    }
+}
 
-fn next_rng(state: &mut GameState) -> ChaCha8Rng {
-    state.rng_counter += 1;
-    ChaCha8Rng::seed_from_u64(state.seed.wrapping_add(state.rng_counter.wrapping_mul(6364136223846793005)))
+// ── Action errors ──────────────────────────────────────────────────────
+
+/// Why `apply_action` refused an action. The decision it was meant for
+/// stays open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionError {
+    /// The game already has a winner.
+    GameOver,
+    /// No decision is pending.
+    NoDecision,
+    /// The action answers another kind of decision.
+    WrongDecisionType { pending: DecisionKind, action: DecisionKind },
+    /// The decision is another house's (`apply_action_as`).
+    NotYourTurn { house: HouseName, to_move: HouseName },
+    /// An area, card or option the decision doesn't offer.
+    IllegalTarget(String),
+    /// Order tokens the house may not place.
+    IllegalOrders(String),
+    /// A bid or an ability costing more power than the house has.
+    InsufficientPower { house: HouseName, needed: u8, available: u8 },
+    /// Resigning mid-battle, offering a draw twice in a round.
+    NotAllowed(String),
 }
 
-fn find_track_holder(state: &GameState, track: Track) -> HouseName {
-    state.playing_houses.iter()
-        .find(|&&h| match track {
-            Track::IronThrone => state.house(h).iron_throne == 1,
-            Track::Fiefdoms => state.house(h).fiefdoms == 1,
-            Track::KingsCourt => state.house(h).kings_court == 1,
-        })
-        .copied()
-        .unwrap_or(state.turn_order[0])
+impl std::fmt::Display for ActionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActionError::GameOver => write!(f, "the game is over"),
+            ActionError::NoDecision => write!(f, "no decision is pending"),
+            ActionError::WrongDecisionType { pending, action } =>
+                write!(f, "a {} answer to a pending {} decision", action.key(), pending.key()),
+            ActionError::NotYourTurn { house, to_move } =>
+                write!(f, "{} answered a decision of {}", house, to_move),
+            ActionError::IllegalTarget(reason) | ActionError::IllegalOrders(reason) | ActionError::NotAllowed(reason) =>
+                write!(f, "{}", reason),
+            ActionError::InsufficientPower { house, needed, available } =>
+                write!(f, "{} needs {} power and has {}", house, needed, available),
+        }
+    }
+}
+
+impl std::error::Error for ActionError {}
+
+impl From<ActionError> for String {
+    fn from(e: ActionError) -> String {
+        e.to_string()
+    }
+}
+
+// Every decision and every action answering one (all but `Resign` and
+// `OfferDraw`), as patterns. The mismatch arms of `action_fits` and
+// `apply_action` pair these instead of `_`, so a new decision or action
+// doesn't compile until its contract is written.
+macro_rules! any_decision {
+    () => {
+        PendingDecision::WesterosChoice { .. }
+            | PendingDecision::SupportDeclaration { .. }
+            | PendingDecision::TyrionReplace { .. }
+            | PendingDecision::AeronSwap { .. }
+            | PendingDecision::PatchfaceDiscard { .. }
+            | PendingDecision::RobbRetreat { .. }
+            | PendingDecision::Retreat { .. }
+            | PendingDecision::Reconcile { .. }
+            | PendingDecision::Muster { .. }
+            | PendingDecision::Bidding { .. }
+            | PendingDecision::LeavePowerToken { .. }
+            | PendingDecision::UseValyrianBlade { .. }
+            | PendingDecision::PlaceOrders { .. }
+            | PendingDecision::ChooseOrderToResolve { .. }
+            | PendingDecision::ChooseRaid { .. }
+            | PendingDecision::ChooseMarch { .. }
+            | PendingDecision::SelectHouseCard { .. }
+            | PendingDecision::MessengerRaven { .. }
+            | PendingDecision::WildlingPenaltyChoice { .. }
+            | PendingDecision::CerseiRemoveOrder { .. }
+            | PendingDecision::DoranChooseTrack { .. }
+            | PendingDecision::QueenOfThornsRemoveOrder { .. }
+    };
+}
+
+macro_rules! any_answer {
+    () => {
+        Action::PlaceOrders(_)
+            | Action::ResolveOrder(_)
+            | Action::Raid(_)
+            | Action::March { .. }
+            | Action::MarchSkip
+            | Action::LeavePowerToken(_)
+            | Action::DeclareSupport(_)
+            | Action::SelectCard(_)
+            | Action::UseValyrianBlade(_)
+            | Action::Bid(_)
+            | Action::WesterosChoice(_)
+            | Action::Muster(_)
+            | Action::Retreat(_)
+            | Action::Reconcile(..)
+            | Action::MessengerRaven(_)
+            | Action::AeronSwap(_)
+            | Action::TyrionReplace(_)
+            | Action::PatchfaceDiscard(_)
+            | Action::RobbRetreat(_)
+            | Action::CerseiRemoveOrder(_)
+            | Action::DoranChooseTrack(_)
+            | Action::QueenOfThorns(_)
+            | Action::WildlingPenalty(_)
+    };
+}
+
+/// The decision→action contract: every decision has one `DecisionKind`
+/// (`DecisionKind::of`) and every action names the kind it answers
+/// (`DecisionKind::answered_by`); `action_fits` pairs each decision with
+/// its answers and every other pair with none. All three are exhaustive
+/// matches without a catch-all, so a new decision or action doesn't
+/// compile until it is paired. An answer of another kind is a
+/// `WrongDecisionType`, one of the right kind choosing something the
+/// decision doesn't offer an `IllegalTarget`. `Resign` and `OfferDraw`
+/// fit every decision.
+pub fn check_answer(pending: &PendingDecision, action: &Action) -> Result<(), ActionError> {
+    let kind = DecisionKind::of(pending);
+    if let Some(answers) = DecisionKind::answered_by(action).filter(|&answers| answers != kind) {
+        return Err(ActionError::WrongDecisionType { pending: kind, action: answers });
+    }
+    if action_fits(pending, action) {
+        Ok(())
+    } else {
+        Err(ActionError::IllegalTarget(format!("{:?} is not offered by the pending {} decision", action, kind.key())))
+    }
+}
+
+/// Whether `action` answers `pending`: the variant matches and any choice
+/// from a list the decision offers (card, area, option) is on that list.
+/// `check_answer` says which of the two failed; `validate_action` checks
+/// the rest.
+pub fn action_fits(pending: &PendingDecision, action: &Action) -> bool {
+    use PendingDecision as P;
+    match (pending, action) {
+        (any_decision!(), Action::Resign | Action::OfferDraw) => true,
+        (P::PlaceOrders { .. }, Action::PlaceOrders(orders)) => {
+            orders.iter().all(|&(_, t)| (t as usize) < ORDER_TOKENS.len())
+        }
+        (P::ChooseOrderToResolve { candidates, .. }, Action::ResolveOrder(a)) => candidates.contains(a),
+        (P::ChooseRaid { valid_targets, .. }, Action::Raid(t)) => t.is_none_or(|t| valid_targets.contains(&t)),
+        (P::ChooseMarch { valid_destinations, selectable_units, .. }, Action::March { to, unit_indices }) => {
+            unit_indices.is_empty()
+                || (valid_destinations.contains(to) && unit_indices.iter().all(|i| selectable_units.contains(i)))
+        }
+        (P::ChooseMarch { .. }, Action::MarchSkip) => true,
+        (P::LeavePowerToken { .. }, Action::LeavePowerToken(_)) => true,
+        (P::SupportDeclaration { .. }, Action::DeclareSupport(_)) => true,
+        (P::SelectHouseCard { available_cards, .. }, Action::SelectCard(c)) => available_cards.contains(c),
+        (P::UseValyrianBlade { .. }, Action::UseValyrianBlade(_)) => true,
+        (P::Bidding { .. }, Action::Bid(_)) => true,
+        (P::WesterosChoice { options, .. }, Action::WesterosChoice(i)) => *i < options.len(),
+        (P::Muster { areas, .. }, Action::Muster(actions)) => {
+            actions.iter().all(|(a, _)| areas.iter().any(|m| m.area_id == *a))
+        }
+        (P::Retreat { possible_areas, .. }, Action::Retreat(a)) => possible_areas.contains(a),
+        (P::Reconcile { area_id, .. }, Action::Reconcile(a, _)) => a == area_id,
+        (P::MessengerRaven { .. }, Action::MessengerRaven(swap)) => {
+            swap.is_none_or(|(_, t)| (t as usize) < ORDER_TOKENS.len())
+        }
+        (P::AeronSwap { available_cards, .. }, Action::AeronSwap(swap)) => swap.is_none_or(|c| available_cards.contains(&c)),
+        (P::TyrionReplace { available_cards, .. }, Action::TyrionReplace(card)) => available_cards.contains(card),
+        (P::PatchfaceDiscard { visible_cards, .. }, Action::PatchfaceDiscard(c)) => visible_cards.contains(c),
+        (P::RobbRetreat { possible_areas, .. }, Action::RobbRetreat(a)) => possible_areas.contains(a),
+        (P::WildlingPenaltyChoice { options, .. }, Action::WildlingPenalty(i)) => *i < options.len(),
+        (P::CerseiRemoveOrder { valid_targets, .. }, Action::CerseiRemoveOrder(a)) => valid_targets.contains(a),
+        (P::DoranChooseTrack { .. }, Action::DoranChooseTrack(_)) => true,
+        (P::QueenOfThornsRemoveOrder { valid_targets, .. }, Action::QueenOfThorns(a)) => valid_targets.contains(a),
+        // An answer to another kind of decision
+        (any_decision!(), any_answer!()) => false,
+    }
+}
+
+/// Check `action` against the decision `state` is waiting on. Beyond
+/// `action_fits`, orders must go on the house's own occupied areas, one
+/// each with tokens the Westeros restrictions allow and the house hasn't
+/// used (`order_tokens_error`) and no more stars than the King's Court
+/// allows (`legal_star_count`), the Messenger Raven swaps one of the
+/// house's own orders for a token it could place, a reconciled unit is
+/// one of the house's units in the area, a bid or Aeron's swap can't cost
+/// more power than the house has, Tyrion's replacement comes from the
+/// hand, no one resigns mid-battle and a draw is offered at most once a
+/// round.
+/// `apply_action` refuses whatever this refuses.
+pub fn validate_action(state: &GameState, action: &Action) -> Result<(), ActionError> {
+    if state.winner.is_some() {
+        return Err(ActionError::GameOver);
+    }
+    let pending = state.peek_pending().ok_or(ActionError::NoDecision)?;
+    check_answer(pending, action)?;
+    let house = pending.house();
+    let power = state.houses[&house].power;
+    let in_hand = |card: &HouseCardId| state.house(house).hand.contains(card);
+    match action {
+        Action::PlaceOrders(orders) => {
+            for (i, &(area_id, _)) in orders.iter().enumerate() {
+                let area = state.areas.get(area_id.0 as usize)
+                    .ok_or_else(|| ActionError::IllegalTarget(format!("no area {}", area_id.0)))?;
+                if area.house != Some(house) || area.units.is_empty() {
+                    return Err(ActionError::IllegalTarget(format!("{:?} has no units in {}", house, area_name(area_id))));
+                }
+                if orders[..i].iter().any(|&(a, _)| a == area_id) {
+                    return Err(ActionError::IllegalTarget(format!("two orders for {}", area_name(area_id))));
+                }
+            }
+            if let Some(reason) = order_tokens_error(state, house, orders) {
+                return Err(ActionError::IllegalOrders(reason));
+            }
+            let stars = orders.iter().filter(|&&(_, t)| ORDER_TOKENS[t as usize].star).count();
+            let budget = star_budget(state, house);
+            if stars > budget as usize {
+                return Err(ActionError::IllegalOrders(format!("{} star orders with a King's Court limit of {}", stars, budget)));
+            }
+        }
+        Action::Bid(amount) if *amount > power => {
+            return Err(ActionError::InsufficientPower { house, needed: *amount, available: power });
+        }
+        Action::Muster(actions) => {
+            let mut ships = Vec::new();
+            for (area_id, choice) in actions {
+                match *choice {
+                    MusterAction2::Build(UnitType::Ship) => {
+                        return Err(ActionError::IllegalTarget(format!("ships are mustered into a sea or port, not {}", area_name(*area_id))));
+                    }
+                    MusterAction2::Build(unit_type) if !navigation::can_enter(unit_type, *area_id) => {
+                        return Err(ActionError::IllegalTarget(format!("{:?} is mustered on land, not in {}", unit_type, area_name(*area_id))));
+                    }
+                    MusterAction2::BuildShip(to) if !ship_muster_areas(state, house, *area_id, &ships).contains(&to) => {
+                        return Err(ActionError::IllegalTarget(format!("no ship from {} can go to {}", area_name(*area_id), area_name(to))));
+                    }
+                    MusterAction2::BuildShip(to) => ships.push(to),
+                    _ => {}
+                }
+            }
+            // Each build within its area's points, the supply limits and
+            // the units left, as `legal_actions` offers them
+            let PendingDecision::Muster { areas, .. } = pending else { unreachable!("action_fits matched the decision") };
+            let mut left: HashMap<AreaId, u8> = areas.iter().map(|m| (m.area_id, m.points)).collect();
+            for (i, (area_id, choice)) in actions.iter().enumerate() {
+                let points = left.get_mut(area_id).expect("action_fits checked the area is offered");
+                if choice.cost() > *points {
+                    return Err(ActionError::IllegalTarget(format!("{} has {} muster points left, {:?} costs {}", area_name(*area_id), points, choice, choice.cost())));
+                }
+                if !muster_options(state, house, *area_id, *points, &actions[..i]).iter().any(|(c, _)| c == choice) {
+                    return Err(ActionError::IllegalTarget(format!("{:?} in {} is beyond {:?}'s supply or units", choice, area_name(*area_id), house)));
+                }
+                *points -= choice.cost();
+            }
+        }
+        Action::AeronSwap(Some(card)) => {
+            let PendingDecision::AeronSwap { power_cost, .. } = *pending else { unreachable!("action_fits matched the decision") };
+            if power < power_cost {
+                return Err(ActionError::InsufficientPower { house, needed: power_cost, available: power });
+            }
+            if !in_hand(card) {
+                return Err(ActionError::IllegalTarget(format!("{:?} is not in {:?}'s hand", card, house)));
+            }
+        }
+        Action::MessengerRaven(Some((area_id, t))) => {
+            // Only one of the house's own orders, for a token it could
+            // place there, as `legal_actions` offers them
+            let area = state.areas.get(area_id.0 as usize)
+                .ok_or_else(|| ActionError::IllegalTarget(format!("no area {}", area_id.0)))?;
+            let order = area.order.filter(|o| o.house == house)
+                .ok_or_else(|| ActionError::IllegalTarget(format!("{:?} has no order to swap in {}", house, area_name(*area_id))))?;
+            if !allowed_tokens(state, house, &[], Some(order.token_index)).contains(t) {
+                return Err(ActionError::IllegalOrders(format!(
+                    "order token {} is restricted, in use or over the King's Court limit ({})", t, area_name(*area_id),
+                )));
+            }
+        }
+        Action::Reconcile(area_id, i) if state.area(*area_id).units.get(*i).is_none_or(|u| u.house != house) => {
+            return Err(ActionError::IllegalTarget(format!("{:?} has no unit {} in {}", house, i, area_name(*area_id))));
+        }
+        Action::TyrionReplace(card) if !in_hand(card) => {
+            return Err(ActionError::IllegalTarget(format!("{:?} is not in {:?}'s hand", card, house)));
+        }
+        Action::Resign if state.combat().is_some() => {
+            return Err(ActionError::NotAllowed("cannot resign during a battle".to_string()));
+        }
+        Action::OfferDraw if state.draw_offers.contains(&house) => {
+            return Err(ActionError::NotAllowed(format!("{:?} already offered a draw this round", house)));
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Why `house` can't place these order tokens, if it can't: a token
+/// that doesn't exist, an order type the Westeros cards restricted (or
+/// its star token, under a star restriction), or a token used twice in
+/// the round, within the set or already on the board.
+fn order_tokens_error(state: &GameState, house: HouseName, orders: &[(AreaId, u8)]) -> Option<String> {
+    let on_board: Vec<u8> = state.areas.iter()
+        .filter_map(|a| a.order.filter(|o| o.house == house))
+        .map(|o| o.token_index)
+        .collect();
+    orders.iter().enumerate().find_map(|(i, &(area_id, t))| {
+        let Some(token) = ORDER_TOKENS.get(t as usize) else {
+            return Some(format!("no order token {}", t));
+        };
+        let place = area_name(area_id);
+        if state.order_restrictions.contains(&token.order_type) {
+            Some(format!("{:?} orders are restricted this round ({})", token.order_type, place))
+        } else if token.star && state.star_order_restrictions.contains(&token.order_type) {
+            Some(format!("{:?} star orders are restricted this round ({})", token.order_type, place))
+        } else if on_board.contains(&t) || orders[..i].iter().any(|&(_, u)| u == t) {
+            Some(format!("order token {} is used twice this round ({})", t, place))
+        } else {
+            None
+        }
+    })
+}
+
+// ── Helpers ────────────────────────────────────────────────────────────
+
+// Determinism contract: a game is a function of its seed, its options and
+// the actions applied to it. The setup shuffles draw from the seed itself;
+// every later draw comes from `next_rng` and is keyed by (seed, purpose,
+// n), n counting that purpose's draws only. Adding a draw for one purpose,
+// or a new purpose with a new stream number, leaves every other stream —
+// and so recorded games that never reach the new draw — unchanged.
+
+/// A fresh RNG for the next draw of `purpose`.
+pub(crate) fn next_rng(state: &mut GameState, purpose: RngPurpose) -> ChaCha8Rng {
+    let counter = state.rng_counters.get_mut(purpose);
+    *counter += 1;
+    let mut rng = ChaCha8Rng::seed_from_u64(state.seed.wrapping_add(counter.wrapping_mul(6364136223846793005)));
+    rng.set_stream(purpose.stream());
+    rng
 }
 
 fn get_muster_areas(state: &GameState, house: HouseName) -> Vec<MusterArea> {
@@ -90,23 +433,41 @@ fn get_muster_areas(state: &GameState, house: HouseName) -> Vec<MusterArea> {
 pub fn advance(state: &mut GameState) {
     // Loop instead of recursion to avoid stack overflow
     loop {
-        if state.pending.is_some() || state.winner.is_some() {
+        if state.winner.is_some() {
             return;
         }
-        let phase_before = state.phase;
+        if let Some(pending) = state.peek_pending() {
+            if !state.resigned.contains(&pending.house()) {
+                // Decisions with one answer are answered for, if the options say so
+                let Some(action) = state.options.auto_forced.then(|| forced_action(state)).flatten() else { return };
+                let decision = pending.clone();
+                state.log_event(GameEventKind::DecisionForced { decision });
+                apply_action(state, action).expect("a forced answer is legal");
+                continue;
+            }
+            // Resigned houses are answered for
+            match resigned_answer(pending) {
+                Some(action) => apply_action(state, action).expect("a resigned house's answer is legal"),
+                None => { state.pop_pending(); }
+            }
+            continue;
+        }
+        let phase_before = state.phase();
         let round_before = state.round;
-        let step_before = state.westeros_step;
-        match state.phase {
+        let step_before = state.westeros().map(|w| w.step);
+        let _phase = tracing::debug_span!("phase", round = round_before, phase = ?phase_before).entered();
+        match state.phase() {
             Phase::Westeros => advance_westeros(state),
             Phase::Planning => advance_planning(state),
             Phase::Action   => advance_action_phase(state),
             Phase::Combat   => advance_combat(state),
         }
+        sync_sea_control(state);
         // If nothing changed (no progress), stop to prevent infinite loop
-        if state.pending.is_some() || state.winner.is_some() {
-            return;
+        if state.has_pending() || state.winner.is_some() {
+            continue;
         }
-        if state.phase == phase_before && state.round == round_before && state.westeros_step == step_before {
+        if state.phase() == phase_before && state.round == round_before && state.westeros().map(|w| w.step) == step_before {
             return; // No progress made
         }
     }
@@ -118,104 +479,118 @@ pub fn advance(state: &mut GameState) {
 
 fn advance_westeros(state: &mut GameState) {
     // Handle ongoing bidding
-    if state.bidding.is_some() {
+    if state.bidding().is_some() {
         advance_bidding(state);
-        if state.pending.is_some() || state.bidding.is_some() {
+        if state.has_pending() || state.bidding().is_some() {
             return;
         }
         // Bidding fully complete → fall through to continue cards
     }
 
     // Handle ongoing mustering
-    if state.muster_house_idx > 0 {
+    if mustering_house_idx(state).is_some() {
         advance_mustering_step(state);
-        if state.pending.is_some() || state.muster_house_idx > 0 {
+        if state.has_pending() || mustering_house_idx(state).is_some() {
             return;
         }
         // Mustering fully complete → fall through
     }
 
     // Step 0: Draw cards
-    if state.westeros_step == 0 {
+    if state.westeros().is_some_and(|w| w.step == 0) {
         draw_westeros_cards(state);
-        state.westeros_step = 1;
     }
 
     // Steps 1–3: Resolve each deck's card
     // Increment step BEFORE resolving so re-entry skips processed cards
-    while state.westeros_step >= 1 && state.westeros_step <= 3 {
-        let idx = (state.westeros_step - 1) as usize;
-        if idx >= state.westeros_cards_drawn.len() {
-            state.westeros_step += 1;
-            continue;
+    loop {
+        let Some(progress) = state.westeros_mut() else { return };
+        if !(1..=3).contains(&progress.step) {
+            break;
         }
-        let card = state.westeros_cards_drawn[idx];
-        state.westeros_step += 1; // advance BEFORE resolving
+        let idx = (progress.step - 1) as usize;
+        progress.step += 1; // advance BEFORE resolving
+        let Some(&card) = progress.cards.get(idx) else { continue };
 
         resolve_westeros_card(state, card);
 
-        if state.pending.is_some() {
+        if state.has_pending() {
             return;
         }
-        if state.bidding.is_some() {
+        if state.bidding().is_some() {
             advance_bidding(state);
-            if state.pending.is_some() || state.bidding.is_some() {
+            if state.has_pending() || state.bidding().is_some() {
                 return;
             }
         }
-        if state.muster_house_idx > 0 {
+        if mustering_house_idx(state).is_some() {
             advance_mustering_step(state);
-            if state.pending.is_some() || state.muster_house_idx > 0 {
+            if state.has_pending() || mustering_house_idx(state).is_some() {
                 return;
             }
         }
     }
 
     // All cards resolved → Planning phase
-    state.westeros_cards_drawn.clear();
-    state.westeros_step = 0;
-    state.phase = Phase::Planning;
+    state.engine_phase = EnginePhase::planning();
     // advance loop in public advance() will re-enter
 }
 
+/// The house (index into `playing_houses`) mustering next, while a
+/// Mustering card is being resolved.
+fn mustering_house_idx(state: &GameState) -> Option<u8> {
+    match state.westeros().map(|w| &w.substate) {
+        Some(WesterosSubstate::Mustering { house_idx }) => Some(*house_idx),
+        _ => None,
+    }
+}
+
+fn set_westeros_substate(state: &mut GameState, substate: WesterosSubstate) {
+    if let Some(progress) = state.westeros_mut() {
+        progress.substate = substate;
+    }
+}
+
 /// Advance mustering: ask next house that has muster areas, or finish.
 fn advance_mustering_step(state: &mut GameState) {
     let houses = state.playing_houses.clone();
-    loop {
-        let idx = (state.muster_house_idx - 1) as usize;
-        if idx >= houses.len() {
-            state.muster_house_idx = 0;
+    while let Some(idx) = mustering_house_idx(state) {
+        let Some(&house) = houses.get(idx as usize) else {
+            set_westeros_substate(state, WesterosSubstate::Cards);
             return;
-        }
-        let house = houses[idx];
+        };
         let muster_areas = get_muster_areas(state, house);
         if muster_areas.is_empty() {
-            state.muster_house_idx += 1;
+            set_westeros_substate(state, WesterosSubstate::Mustering { house_idx: idx + 1 });
             continue;
         }
-        state.pending = Some(PendingDecision::Muster { house, areas: muster_areas });
+        state.enqueue_pending(PendingDecision::Muster { house, areas: muster_areas });
         return;
     }
 }
 
 fn draw_westeros_cards(state: &mut GameState) {
-    state.westeros_cards_drawn.clear();
+    let mut cards = Vec::with_capacity(3);
     let mut wildling_icons = 0u8;
 
     if let Some(c) = state.westeros_deck_1.pop() {
         if c.wildling_icon { wildling_icons += 1; }
-        state.westeros_cards_drawn.push(c);
+        cards.push(c);
     }
     if let Some(c) = state.westeros_deck_2.pop() {
         if c.wildling_icon { wildling_icons += 1; }
-        state.westeros_cards_drawn.push(c);
+        cards.push(c);
     }
     if let Some(c) = state.westeros_deck_3.pop() {
         if c.wildling_icon { wildling_icons += 1; }
-        state.westeros_cards_drawn.push(c);
+        cards.push(c);
     }
 
     state.wildling_threat = (state.wildling_threat + wildling_icons * 2).min(12);
+    if let Some(progress) = state.westeros_mut() {
+        progress.cards = cards;
+        progress.step = 1;
+    }
 }
 
 fn resolve_westeros_card(state: &mut GameState, card: WesterosCard) {
@@ -224,12 +599,13 @@ fn resolve_westeros_card(state: &mut GameState, card: WesterosCard) {
         Supply => resolve_supply_update(state),
 
         Mustering => {
-            state.muster_house_idx = 1; // triggers mustering flow in advance_westeros
+            // triggers mustering flow in advance_westeros
+            set_westeros_substate(state, WesterosSubstate::Mustering { house_idx: 0 });
         }
 
         AThroneOfBlades => {
-            let holder = find_track_holder(state, Track::IronThrone);
-            state.pending = Some(PendingDecision::WesterosChoice {
+            let holder = tracks::holder(state, Track::IronThrone);
+            state.enqueue_pending(PendingDecision::WesterosChoice {
                 card_name: "A Throne of Blades".into(),
                 chooser: holder,
                 options: vec!["Mustering".into(), "Supply".into()],
@@ -241,8 +617,8 @@ fn resolve_westeros_card(state: &mut GameState, card: WesterosCard) {
         GameOfThrones => resolve_game_of_thrones(state),
 
         DarkWingsDarkWords => {
-            let holder = find_track_holder(state, Track::KingsCourt);
-            state.pending = Some(PendingDecision::WesterosChoice {
+            let holder = tracks::holder(state, Track::KingsCourt);
+            state.enqueue_pending(PendingDecision::WesterosChoice {
                 card_name: "Dark Wings, Dark Words".into(),
                 chooser: holder,
                 options: vec!["Clash of Kings".into(), "Game of Thrones".into()],
@@ -252,8 +628,8 @@ fn resolve_westeros_card(state: &mut GameState, card: WesterosCard) {
         WildlingAttack => begin_wildling_attack(state),
 
         PutToTheSword => {
-            let holder = find_track_holder(state, Track::Fiefdoms);
-            state.pending = Some(PendingDecision::WesterosChoice {
+            let holder = tracks::holder(state, Track::Fiefdoms);
+            state.enqueue_pending(PendingDecision::WesterosChoice {
                 card_name: "Put to the Sword".into(),
                 chooser: holder,
                 options: vec![
@@ -273,7 +649,7 @@ fn resolve_westeros_card(state: &mut GameState, card: WesterosCard) {
         RainsOfAutumn  => { state.star_order_restrictions.push(OrderType::March); }
 
         WinterIsComing => {
-            let mut rng = next_rng(state);
+            let mut rng = next_rng(state, RngPurpose::WesterosReshuffle);
             let new_card = match card.deck {
                 1 => { state.westeros_deck_1.shuffle(&mut rng); state.westeros_deck_1.pop() }
                 2 => { state.westeros_deck_2.shuffle(&mut rng); state.westeros_deck_2.pop() }
@@ -281,10 +657,12 @@ fn resolve_westeros_card(state: &mut GameState, card: WesterosCard) {
                 _ => None,
             };
             if let Some(nc) = new_card {
-                // westeros_step was already incremented before resolving, so current index = step - 2
-                let s = (state.westeros_step.saturating_sub(2)) as usize;
-                if s < state.westeros_cards_drawn.len() {
-                    state.westeros_cards_drawn[s] = nc;
+                // The step was already incremented before resolving, so current index = step - 2
+                if let Some(progress) = state.westeros_mut() {
+                    let s = progress.step.saturating_sub(2) as usize;
+                    if let Some(slot) = progress.cards.get_mut(s) {
+                        *slot = nc;
+                    }
                 }
                 if nc.wildling_icon {
                     state.wildling_threat = (state.wildling_threat + 2).min(12);
@@ -303,12 +681,16 @@ fn resolve_supply_update(state: &mut GameState) {
         let new_supply = supply::calculate_supply(state, h);
         state.house_mut(h).supply = new_supply;
     }
+    check_victory(state);
+    if state.winner.is_some() {
+        return;
+    }
     // Check for supply violations → reconcile
     for &h in &houses {
         if supply::check_supply_violation(state, h) {
             let violations = supply::find_violations(state, h);
             if let Some(&(vid, curr, max)) = violations.first() {
-                state.pending = Some(PendingDecision::Reconcile {
+                state.enqueue_pending(PendingDecision::Reconcile {
                     house: h, area_id: vid, current_size: curr, max_allowed: max,
                 });
                 return;
@@ -326,7 +708,7 @@ fn resolve_game_of_thrones(state: &mut GameState) {
                 power_gain += AREAS[i].power_icons;
             }
         }
-        state.house_mut(h).power += power_gain;
+        gain_power(state, h, power_gain);
     }
 }
 
@@ -335,14 +717,13 @@ fn resolve_game_of_thrones(state: &mut GameState) {
 // ═══════════════════════════════════════════════════════════════════════
 
 fn begin_clash_of_kings(state: &mut GameState) {
-    state.bidding = Some(BiddingState {
-        bidding_type: BiddingType::IronThrone,
-        bids: HashMap::new(),
-        current_track: Some(Track::IronThrone),
-        remaining_tracks: vec![Track::Fiefdoms, Track::KingsCourt],
-        bid_order: state.turn_order.clone(),
-        next_bidder_idx: 0,
-    });
+    let order = state.turn_order.clone();
+    set_westeros_substate(state, WesterosSubstate::Bidding(BiddingState::new(
+        BiddingType::IronThrone,
+        Some(Track::IronThrone),
+        vec![Track::Fiefdoms, Track::KingsCourt],
+        order,
+    )));
 }
 
 fn begin_wildling_attack(state: &mut GameState) {
@@ -350,28 +731,27 @@ fn begin_wildling_attack(state: &mut GameState) {
     if state.wildling_threat == 0 {
         return;
     }
-    state.bidding = Some(BiddingState {
-        bidding_type: BiddingType::Wildling,
-        bids: HashMap::new(),
-        current_track: None,
-        remaining_tracks: Vec::new(),
-        bid_order: state.turn_order.clone(),
-        next_bidder_idx: 0,
-    });
+    let order = state.turn_order.clone();
+    set_westeros_substate(state, WesterosSubstate::Bidding(BiddingState::new(BiddingType::Wildling, None, Vec::new(), order)));
 }
 
 fn advance_bidding(state: &mut GameState) {
-    let bidding = state.bidding.as_ref().unwrap();
+    // Every house still to bid is asked at once, in bid order; houses with
+    // nothing to bid bid nothing, if the options say so
+    let bidding = state.bidding().unwrap();
     let bt = bidding.bidding_type;
     let track = bidding.current_track;
-
-    if bidding.next_bidder_idx < bidding.bid_order.len() {
-        let house = bidding.bid_order[bidding.next_bidder_idx];
-        state.pending = Some(PendingDecision::Bidding {
-            house,
-            bidding_type: bt,
-            track,
-        });
+    let open: Vec<HouseName> = bidding.bid_order.iter().copied().filter(|&h| !bidding.has_bid(h)).collect();
+    for house in open {
+        let decision = PendingDecision::Bidding { house, bidding_type: bt, track };
+        if bids_automatically(state, house) {
+            state.bidding_mut().unwrap().sealed_bids.insert(house, 0);
+            state.log_event(GameEventKind::DecisionForced { decision });
+        } else {
+            state.enqueue_pending(decision);
+        }
+    }
+    if state.has_pending() {
         return;
     }
 
@@ -386,49 +766,62 @@ fn advance_bidding(state: &mut GameState) {
     }
 
     // If resolve created new bidding (next track), immediately set pending
-    if state.bidding.is_some() {
+    if state.bidding().is_some() {
         advance_bidding(state);
     }
 }
 
+/// Whether the engine bids 0 for `house` without asking it.
+fn bids_automatically(state: &GameState, house: HouseName) -> bool {
+    (state.options.auto_zero_bids || state.options.auto_forced) && state.house(house).power == 0
+}
+
+/// Every bid still to come in the bidding under way, in bid order: the
+/// decisions queued for it. Bids are sealed until all are in, so they may
+/// be answered in any order (`apply_action_as` takes a bid from any house
+/// still to bid) and each house sees the same view whatever the order.
+pub fn open_bids(state: &GameState) -> Vec<PendingDecision> {
+    open_decisions(state).into_iter().filter(|p| matches!(p, PendingDecision::Bidding { .. })).collect()
+}
+
+/// The sealed decisions open at once: every bid, or every house's orders,
+/// still to come when one is at the head of the queue, in the order they
+/// were asked. Empty at any other decision.
+pub fn open_decisions(state: &GameState) -> Vec<PendingDecision> {
+    let Some(head) = state.peek_pending() else { return Vec::new() };
+    if !matches!(head, PendingDecision::Bidding { .. } | PendingDecision::PlaceOrders { .. }) {
+        return Vec::new();
+    }
+    let kind = DecisionKind::of(head);
+    state.pending.iter().filter(|p| DecisionKind::of(p) == kind).cloned().collect()
+}
+
 fn resolve_track_bidding(state: &mut GameState) {
-    let bidding = state.bidding.take().unwrap();
+    let bidding = take_bidding(state).unwrap();
     let track = bidding.current_track.unwrap();
 
     // Sort houses by bid (descending), tiebreak by current position (ascending = better)
     let mut sorted: Vec<(HouseName, u8, u8)> = bidding.bid_order.iter()
         .map(|&h| {
-            let bid = *bidding.bids.get(&h).unwrap_or(&0);
-            let current_pos = match track {
-                Track::IronThrone => state.house(h).iron_throne,
-                Track::Fiefdoms => state.house(h).fiefdoms,
-                Track::KingsCourt => state.house(h).kings_court,
-            };
-            (h, bid, current_pos)
+            let bid = bidding.bid_of(h);
+            (h, bid, tracks::position(state, h, track))
         })
         .collect();
 
+    state.log_event(GameEventKind::BidsRevealed {
+        bidding_type: bidding.bidding_type,
+        bids: sorted.iter().map(|&(h, bid, _)| (h, bid)).collect(),
+    });
     sorted.sort_by(|a, b| b.1.cmp(&a.1).then(a.2.cmp(&b.2)));
 
-    // Assign new positions (1-based)
-    let _houses_clone = state.playing_houses.clone();
-    for (pos, &(h, bid, _)) in sorted.iter().enumerate() {
-        let position = (pos + 1) as u8;
-        match track {
-            Track::IronThrone => state.house_mut(h).iron_throne = position,
-            Track::Fiefdoms => state.house_mut(h).fiefdoms = position,
-            Track::KingsCourt => state.house_mut(h).kings_court = position,
-        }
-        // Deduct bid tokens
-        state.house_mut(h).power = state.house(h).power.saturating_sub(bid);
+    // Deduct bid tokens
+    for &(h, bid, _) in &sorted {
+        spend_power(state, h, bid);
     }
 
-    // Update turn order if Iron Throne changed
-    if track == Track::IronThrone {
-        let mut new_order = state.playing_houses.clone();
-        new_order.sort_by_key(|&h| state.house(h).iron_throne);
-        state.turn_order = new_order;
-    }
+    // Assign new positions (turn order follows automatically)
+    let new_order: Vec<HouseName> = sorted.iter().map(|&(h, _, _)| h).collect();
+    tracks::set_order(state, track, &new_order);
 
     // Continue to next track or finish
     if !bidding.remaining_tracks.is_empty() {
@@ -439,31 +832,41 @@ fn resolve_track_bidding(state: &mut GameState) {
             Track::Fiefdoms => BiddingType::Fiefdoms,
             Track::KingsCourt => BiddingType::KingsCourt,
         };
-        state.bidding = Some(BiddingState {
-            bidding_type: bt,
-            bids: HashMap::new(),
-            current_track: Some(next_track),
-            remaining_tracks: remaining,
-            bid_order: state.turn_order.clone(),
-            next_bidder_idx: 0,
-        });
+        let order = state.turn_order.clone();
+        set_westeros_substate(state, WesterosSubstate::Bidding(BiddingState::new(bt, Some(next_track), remaining, order)));
+    }
+    // else: bidding is done (already ended by take_bidding())
+}
+
+/// End the bidding under way, returning it.
+fn take_bidding(state: &mut GameState) -> Option<BiddingState> {
+    let progress = state.westeros_mut()?;
+    match std::mem::replace(&mut progress.substate, WesterosSubstate::Cards) {
+        WesterosSubstate::Bidding(bidding) => Some(bidding),
+        other => {
+            progress.substate = other;
+            None
+        }
     }
-    // else: bidding is done (already set to None by take())
 }
 
 fn resolve_wildling_bidding(state: &mut GameState) {
-    let bidding = state.bidding.take().unwrap();
-    let total_bid: u8 = bidding.bids.values().sum();
+    let bidding = take_bidding(state).unwrap();
+    let total_bid: u8 = bidding.sealed_bids.values().sum();
     let threat = state.wildling_threat;
 
     // Sort by bid (descending for highest, ascending for lowest)
     let mut sorted: Vec<(HouseName, u8)> = bidding.bid_order.iter()
-        .map(|&h| (h, *bidding.bids.get(&h).unwrap_or(&0)))
+        .map(|&h| (h, bidding.bid_of(h)))
         .collect();
+    state.log_event(GameEventKind::BidsRevealed { bidding_type: bidding.bidding_type, bids: sorted.clone() });
 
     // Deduct all bids
+    let mut bids = Vec::with_capacity(sorted.len());
     for &(h, bid) in &sorted {
-        state.house_mut(h).power = state.house(h).power.saturating_sub(bid);
+        let spent = spend_power(state, h, bid);
+        state.log_event(GameEventKind::WildlingBid { house: h, amount: spent });
+        bids.push((h, spent));
     }
 
     // Draw wildling card
@@ -471,7 +874,7 @@ fn resolve_wildling_bidding(state: &mut GameState) {
     if state.wildling_deck.is_empty() {
         // Reshuffle
         state.wildling_deck = cards::wildling_deck();
-        let mut rng = next_rng(state);
+        let mut rng = next_rng(state, RngPurpose::WildlingReshuffle);
         state.wildling_deck.shuffle(&mut rng);
     }
 
@@ -480,27 +883,17 @@ fn resolve_wildling_bidding(state: &mut GameState) {
 
     if total_bid >= threat {
         // ═══ Night's Watch wins! ═══
-        sorted.sort_by(|a, b| b.1.cmp(&a.1));
+        sorted.sort_by_key(|&(_, bid)| std::cmp::Reverse(bid));
         let highest = sorted[0].0;
+        state.log_event(GameEventKind::WildlingAttack {
+            threat, total_bid, bids, card: card_type, nights_watch_won: true, target: highest,
+        });
 
         match card_type {
             Some(AKingBeyondTheWall) => {
                 // Highest bidder: move to position 1 on any one influence track
                 // Auto: move to top of Iron Throne (most impactful)
-                let pc = state.playing_houses.len() as u8;
-                let old_pos = state.house(highest).iron_throne;
-                let houses = state.playing_houses.clone();
-                for &h in &houses {
-                    if state.house(h).iron_throne < old_pos {
-                        state.house_mut(h).iron_throne += 1;
-                    }
-                }
-                state.house_mut(highest).iron_throne = 1;
-                // Update turn order
-                let mut new_order = state.playing_houses.clone();
-                new_order.sort_by_key(|&h| state.house(h).iron_throne);
-                state.turn_order = new_order;
-                let _ = pc;
+                tracks::move_to_top(state, highest, Track::IronThrone);
             }
             Some(CrowKillers) => {
                 // Highest: replace up to 2 footmen on board with available knights
@@ -517,16 +910,16 @@ fn resolve_wildling_bidding(state: &mut GameState) {
                         if let Some(pos) = state.area(aid).units.iter().position(|u| {
                             u.house == highest && u.unit_type == UnitType::Footman
                         }) {
-                            state.area_mut(aid).units[pos].unit_type = UnitType::Knight;
-                            state.house_mut(highest).available_units.knights -= 1;
-                            state.house_mut(highest).available_units.footmen += 1;
+                            if state.house_mut(highest).swap_unit(UnitType::Footman, UnitType::Knight).is_ok() {
+                                state.area_mut(aid).units[pos].unit_type = UnitType::Knight;
+                            }
                         }
                     }
                 }
             }
             Some(MammothRiders) => {
                 // Highest: retrieve all power tokens bid (+5 bonus on top)
-                state.house_mut(highest).power += 5;
+                gain_power(state, highest, 5);
             }
             Some(MassingOnTheMilkwater) => {
                 // Highest: return all discarded house cards to hand
@@ -538,18 +931,18 @@ fn resolve_wildling_bidding(state: &mut GameState) {
             }
             Some(PreemptiveRaid) => {
                 // Simplified reward: +2 power (reduce wildling track)
-                state.house_mut(highest).power += 2;
+                gain_power(state, highest, 2);
             }
             Some(RattleshirtsRaiders) => {
                 // Highest: +2 power
-                state.house_mut(highest).power += 2;
+                gain_power(state, highest, 2);
             }
             Some(SilenceAtTheWall) => {
                 // Nothing happens
             }
             Some(SkinchangerScout) => {
                 // Simplified: highest bidder gains +2 power (in real game, peek at decks)
-                state.house_mut(highest).power += 2;
+                gain_power(state, highest, 2);
             }
             Some(TheHordeDescends) => {
                 // Highest: muster 2 points in any one castle/stronghold
@@ -560,16 +953,15 @@ fn resolve_wildling_bidding(state: &mut GameState) {
                     })
                     .map(|(i, _)| AreaId(i as u8));
                 if let Some(aid) = muster_area {
-                    if state.house(highest).available_units.footmen > 0 {
+                    if state.house_mut(highest).take_unit(UnitType::Footman).is_ok() {
                         state.area_mut(aid).units.push(Unit {
                             unit_type: UnitType::Footman, house: highest, routed: false,
                         });
-                        state.house_mut(highest).available_units.footmen -= 1;
                     }
                 }
             }
             None => {
-                state.house_mut(highest).power += 2;
+                gain_power(state, highest, 2);
             }
         }
 
@@ -581,40 +973,16 @@ fn resolve_wildling_bidding(state: &mut GameState) {
         ));
         let lowest = sorted[0].0;
         let others: Vec<HouseName> = sorted[1..].iter().map(|&(h, _)| h).collect();
+        state.log_event(GameEventKind::WildlingAttack {
+            threat, total_bid, bids, card: card_type, nights_watch_won: false, target: lowest,
+        });
 
         match card_type {
             Some(AKingBeyondTheWall) => {
                 // Lowest: move to bottom of ALL 3 influence tracks
-                let pc = state.playing_houses.len() as u8;
-                // Iron Throne
-                let old_it = state.house(lowest).iron_throne;
-                let houses = state.playing_houses.clone();
-                for &h in &houses {
-                    if state.house(h).iron_throne > old_it {
-                        state.house_mut(h).iron_throne -= 1;
-                    }
-                }
-                state.house_mut(lowest).iron_throne = pc;
-                // Fiefdoms
-                let old_f = state.house(lowest).fiefdoms;
-                for &h in &houses {
-                    if state.house(h).fiefdoms > old_f {
-                        state.house_mut(h).fiefdoms -= 1;
-                    }
-                }
-                state.house_mut(lowest).fiefdoms = pc;
-                // Kings Court
-                let old_kc = state.house(lowest).kings_court;
-                for &h in &houses {
-                    if state.house(h).kings_court > old_kc {
-                        state.house_mut(h).kings_court -= 1;
-                    }
+                for track in tracks::ALL_TRACKS {
+                    tracks::move_to_bottom(state, lowest, track);
                 }
-                state.house_mut(lowest).kings_court = pc;
-                // Update turn order
-                let mut new_order = state.playing_houses.clone();
-                new_order.sort_by_key(|&h| state.house(h).iron_throne);
-                state.turn_order = new_order;
             }
             Some(CrowKillers) => {
                 // Lowest: all knights become footmen (if footmen available in pool)
@@ -629,16 +997,14 @@ fn resolve_wildling_bidding(state: &mut GameState) {
                     .collect();
                 for (aid, positions) in knight_areas {
                     for pos in positions.into_iter().rev() {
-                        if state.house(lowest).available_units.footmen > 0 {
+                        if state.house_mut(lowest).swap_unit(UnitType::Knight, UnitType::Footman).is_ok() {
                             state.area_mut(aid).units[pos].unit_type = UnitType::Footman;
-                            state.house_mut(lowest).available_units.footmen -= 1;
-                            state.house_mut(lowest).available_units.knights += 1;
                         }
                     }
                 }
                 // Others: lose 1 power each
                 for &h in &others {
-                    state.house_mut(h).power = state.house(h).power.saturating_sub(1);
+                    spend_power(state, h, 1);
                 }
             }
             Some(MammothRiders) => {
@@ -653,7 +1019,7 @@ fn resolve_wildling_bidding(state: &mut GameState) {
                     if let Some(idx) = area_idx {
                         if let Some(pos) = state.areas[idx].units.iter().position(|u| u.house == lowest) {
                             let unit = state.areas[idx].units.remove(pos);
-                            *state.house_mut(lowest).available_units.get_mut(unit.unit_type) += 1;
+                            return_to_pool(state, &unit);
                             destroyed += 1;
                         } else { break; }
                     } else { break; }
@@ -666,18 +1032,26 @@ fn resolve_wildling_bidding(state: &mut GameState) {
                     if let Some(idx) = area_idx {
                         if let Some(pos) = state.areas[idx].units.iter().position(|u| u.house == h) {
                             let unit = state.areas[idx].units.remove(pos);
-                            *state.house_mut(h).available_units.get_mut(unit.unit_type) += 1;
+                            return_to_pool(state, &unit);
                         }
                     }
                 }
             }
             Some(MassingOnTheMilkwater) => {
-                // Lowest: discard entire hand
+                // Lowest: with more than one card in hand, discard those of
+                // the highest strength (a hand is never emptied this way)
                 let hand = state.house(lowest).hand.clone();
-                for card_id in hand {
-                    state.house_mut(lowest).discards.push(card_id);
+                if hand.len() > 1 {
+                    let strength = |c: HouseCardId| cards::get_house_card(c).strength;
+                    let top = hand.iter().map(|&c| strength(c)).max().unwrap_or(0);
+                    let profile = state.house_mut(lowest);
+                    profile.hand.retain(|&c| strength(c) != top);
+                    profile.discards.extend(hand.into_iter().filter(|&c| strength(c) == top));
+                    if profile.hand.is_empty() {
+                        // Every card shared the top strength: keep one
+                        profile.hand.push(profile.discards.pop().unwrap());
+                    }
                 }
-                state.house_mut(lowest).hand.clear();
             }
             Some(PreemptiveRaid) => {
                 // Lowest: destroy 2 units (simplified, auto-pick)
@@ -690,7 +1064,7 @@ fn resolve_wildling_bidding(state: &mut GameState) {
                     if let Some(idx) = area_idx {
                         if let Some(pos) = state.areas[idx].units.iter().position(|u| u.house == lowest) {
                             let unit = state.areas[idx].units.remove(pos);
-                            *state.house_mut(lowest).available_units.get_mut(unit.unit_type) += 1;
+                            return_to_pool(state, &unit);
                             destroyed += 1;
                         } else { break; }
                     } else { break; }
@@ -698,10 +1072,10 @@ fn resolve_wildling_bidding(state: &mut GameState) {
             }
             Some(RattleshirtsRaiders) => {
                 // Lowest: lose ALL remaining power tokens
-                state.house_mut(lowest).power = 0;
+                spend_power(state, lowest, u8::MAX);
                 // Others: lose 1 power each
                 for &h in &others {
-                    state.house_mut(h).power = state.house(h).power.saturating_sub(1);
+                    spend_power(state, h, 1);
                 }
             }
             Some(SilenceAtTheWall) => {
@@ -709,7 +1083,7 @@ fn resolve_wildling_bidding(state: &mut GameState) {
             }
             Some(SkinchangerScout) => {
                 // Lowest: discard all power tokens
-                state.house_mut(lowest).power = 0;
+                spend_power(state, lowest, u8::MAX);
             }
             Some(TheHordeDescends) => {
                 // Lowest: destroy 2 of their strongest units (knight>siege>footman>ship)
@@ -732,7 +1106,7 @@ fn resolve_wildling_bidding(state: &mut GameState) {
                         // Re-find position (indices may have shifted)
                         if let Some(pos) = state.area(aid).units.iter().position(|u| u.house == lowest) {
                             let unit = state.area_mut(aid).units.remove(pos);
-                            *state.house_mut(lowest).available_units.get_mut(unit.unit_type) += 1;
+                            return_to_pool(state, &unit);
                             destroyed += 1;
                         } else { break; }
                     } else { break; }
@@ -745,16 +1119,16 @@ fn resolve_wildling_bidding(state: &mut GameState) {
                     if let Some(idx) = area_idx {
                         if let Some(pos) = state.areas[idx].units.iter().position(|u| u.house == h) {
                             let unit = state.areas[idx].units.remove(pos);
-                            *state.house_mut(h).available_units.get_mut(unit.unit_type) += 1;
+                            return_to_pool(state, &unit);
                         }
                     }
                 }
             }
             None => {
                 // Generic fallback
-                state.house_mut(lowest).power = state.house(lowest).power.saturating_sub(2);
+                spend_power(state, lowest, 2);
                 for &h in &others {
-                    state.house_mut(h).power = state.house(h).power.saturating_sub(1);
+                    spend_power(state, h, 1);
                 }
             }
         }
@@ -763,20 +1137,16 @@ fn resolve_wildling_bidding(state: &mut GameState) {
     }
 
     // Check for empty areas that lost their last unit
-    let houses = state.playing_houses.clone();
-    for (i, area) in state.areas.iter_mut().enumerate() {
-        let def = &AREAS[i];
-        if def.area_type == AreaType::Land && area.units.is_empty() {
-            if let Some(h) = area.house {
-                if !houses.contains(&h) || !area.units.iter().any(|u| u.house == h) {
-                    // Don't clear control if house has garrison or power token
-                    // Simplified: just clear if no units
-                    if area.units.is_empty() {
-                        area.house = None;
-                    }
-                }
-            }
-        }
+    // Don't clear control if house has garrison or power token
+    // Simplified: just clear if no units
+    let emptied: Vec<AreaId> = state.areas.iter().enumerate()
+        .filter(|(i, a)| {
+            AREAS[*i].area_type == AreaType::Land && a.units.is_empty() && a.house.is_some()
+        })
+        .map(|(i, _)| AreaId(i as u8))
+        .collect();
+    for area_id in emptied {
+        set_area_control(state, area_id, None);
     }
 }
 
@@ -785,33 +1155,44 @@ fn resolve_wildling_bidding(state: &mut GameState) {
 // ═══════════════════════════════════════════════════════════════════════
 
 fn advance_planning(state: &mut GameState) {
-    if state.pending.is_some() || state.winner.is_some() {
+    if state.has_pending() || state.winner.is_some() {
         return;
     }
 
-    // Find next house that needs to place orders (in turn order)
-    for &h in &state.turn_order.clone() {
-        let needs_orders = state.areas.iter().any(|area| {
-            area.house == Some(h) && !area.units.is_empty() && area.order.is_none()
-        });
-        if needs_orders {
-            state.pending = Some(PendingDecision::PlaceOrders { house: h });
+    if matches!(state.engine_phase, EnginePhase::Planning { orders_revealed: false }) {
+        // Every house with orders to place is asked at once, in turn order;
+        // the orders go on the board face down as they come in. A house out
+        // of tokens leaves its other areas without orders
+        for &h in &state.turn_order.clone() {
+            let needs_orders = state.areas.iter().any(|area| {
+                area.house == Some(h) && !area.units.is_empty() && area.order.is_none()
+            }) && !available_tokens(state, h).is_empty();
+            if needs_orders {
+                state.enqueue_pending(PendingDecision::PlaceOrders { house: h });
+            }
+        }
+        if state.has_pending() {
             return;
         }
+
+        // All orders placed → flip them together
+        state.engine_phase = EnginePhase::Planning { orders_revealed: true };
+        let orders = state.areas.iter().enumerate()
+            .filter_map(|(i, a)| a.order.map(|o| (AreaId(i as u8), o)))
+            .collect();
+        state.log_event(GameEventKind::OrdersRevealed { orders });
     }
 
-    // All orders placed → messenger raven
+    // Orders revealed → messenger raven
     if !state.messenger_raven_used {
-        let raven_holder = find_track_holder(state, Track::KingsCourt);
+        let raven_holder = tracks::holder(state, Track::KingsCourt);
         state.messenger_raven_used = true;
-        state.pending = Some(PendingDecision::MessengerRaven { house: raven_holder });
+        state.enqueue_pending(PendingDecision::MessengerRaven { house: raven_holder });
         return;
     }
 
     // Transition to Action phase
-    state.phase = Phase::Action;
-    state.action_sub_phase = ActionSubPhase::Raid;
-    state.action_player_index = 0;
+    state.engine_phase = EnginePhase::action();
     // advance loop will re-enter
 }
 
@@ -820,82 +1201,66 @@ fn advance_planning(state: &mut GameState) {
 // ═══════════════════════════════════════════════════════════════════════
 
 fn advance_action_phase(state: &mut GameState) {
-    if state.pending.is_some() || state.winner.is_some() {
+    if state.has_pending() || state.winner.is_some() {
         return;
     }
 
     let player_count = state.playing_houses.len();
 
     loop {
-        if state.action_sub_phase == ActionSubPhase::Done {
-            cleanup_round(state);
-            // advance loop will re-enter
-            return;
-        }
+        let Some(turn) = state.action_turn() else { return };
+        let order_type = match turn.sub_phase {
+            ActionSubPhase::Raid => OrderType::Raid,
+            ActionSubPhase::March => OrderType::March,
+            ActionSubPhase::ConsolidatePower => OrderType::ConsolidatePower,
+            ActionSubPhase::Done => {
+                cleanup_round(state);
+                // advance loop will re-enter
+                return;
+            }
+        };
 
-        // Cycle through players looking for one with orders of current type
-        let start_idx = state.action_player_index;
+        // Cycle through players looking for one with orders of current type.
+        // Each player resolves exactly one order per turn, of their choosing.
+        let start_idx = turn.player_index;
         let mut found = false;
-        let mut checked = 0;
 
-        loop {
-            if checked >= player_count {
-                break; // Full cycle, nobody has orders
-            }
+        for checked in 0..player_count {
             let idx = ((start_idx as usize + checked) % player_count) as u8;
             let house = state.turn_order[idx as usize];
-            checked += 1;
-
-            match state.action_sub_phase {
-                ActionSubPhase::Raid => {
-                    let raid_area = find_first_order_area(state, house, OrderType::Raid);
-                    if let Some(from) = raid_area {
-                        state.action_player_index = idx;
-                        let valid_targets = find_raid_targets(state, from, house);
-                        state.pending = Some(PendingDecision::ChooseRaid {
-                            house,
-                            from_area: from,
-                            valid_targets,
-                        });
-                        return;
-                    }
-                }
-                ActionSubPhase::March => {
-                    let march_area = find_first_order_area(state, house, OrderType::March);
-                    if let Some(from) = march_area {
-                        state.action_player_index = idx;
-                        let valid_dests = navigation::valid_destinations(state, from, house);
-                        state.pending = Some(PendingDecision::ChooseMarch {
-                            house,
-                            from_area: from,
-                            valid_destinations: valid_dests,
-                        });
-                        return;
-                    }
-                }
-                ActionSubPhase::ConsolidatePower => {
-                    let cp_area = find_first_order_area(state, house, OrderType::ConsolidatePower);
-                    if let Some(area_id) = cp_area {
-                        state.action_player_index = idx;
-                        resolve_single_consolidate_power(state, house, area_id);
-                        // After resolving, advance to next player
-                        state.action_player_index = (idx + 1) % player_count as u8;
-                        found = true;
-                        break;
-                    }
-                }
-                ActionSubPhase::Done => unreachable!(),
+            let candidates = find_order_areas(state, house, order_type);
+            if candidates.is_empty() {
+                continue;
+            }
+
+            if let Some(turn) = state.action_turn_mut() {
+                turn.player_index = idx;
+            }
+            if candidates.len() > 1 {
+                state.enqueue_pending(PendingDecision::ChooseOrderToResolve {
+                    house,
+                    order_type,
+                    candidates,
+                });
+                return;
+            }
+            begin_order_resolution(state, house, candidates[0]);
+            if state.has_pending() {
+                return;
             }
+            found = true;
+            break;
         }
 
-        // If ConsolidatePower just resolved one, loop again to find next
-        if found && state.action_sub_phase == ActionSubPhase::ConsolidatePower {
+        // Consolidate Power resolves without a decision — loop again to find next
+        if found {
             continue;
         }
 
         // No one had orders → advance sub-phase
-        state.action_player_index = 0;
-        state.action_sub_phase = match state.action_sub_phase {
+        let Some(turn) = state.action_turn_mut() else { return };
+        turn.player_index = 0;
+        turn.sub_phase = match turn.sub_phase {
             ActionSubPhase::Raid => ActionSubPhase::March,
             ActionSubPhase::March => ActionSubPhase::ConsolidatePower,
             ActionSubPhase::ConsolidatePower => ActionSubPhase::Done,
@@ -904,13 +1269,57 @@ fn advance_action_phase(state: &mut GameState) {
     }
 }
 
-fn find_first_order_area(state: &GameState, house: HouseName, order_type: OrderType) -> Option<AreaId> {
+/// Hand the action phase to the next house in turn order.
+fn pass_turn(state: &mut GameState) {
+    let pc = state.playing_houses.len() as u8;
+    if let Some(turn) = state.action_turn_mut() {
+        turn.player_index = (turn.player_index + 1) % pc;
+    }
+}
+
+/// All areas where a house has an unresolved order of the given type.
+fn find_order_areas(state: &GameState, house: HouseName, order_type: OrderType) -> Vec<AreaId> {
     state.areas.iter().enumerate()
-        .find(|(_, a)| {
+        .filter(|(_, a)| {
             a.house == Some(house) &&
-            a.order.is_some_and(|o| o.order_type == order_type)
+            a.order.is_some_and(|o| o.house == house && o.order_type == order_type)
         })
         .map(|(i, _)| AreaId(i as u8))
+        .collect()
+}
+
+/// Start resolving the order the current player chose for this turn.
+/// Raid and March prompt for a target; Consolidate Power resolves immediately.
+fn begin_order_resolution(state: &mut GameState, house: HouseName, area_id: AreaId) {
+    let order_type = match state.area(area_id).order {
+        Some(o) => o.order_type,
+        None => return,
+    };
+    match order_type {
+        OrderType::Raid => {
+            let valid_targets = find_raid_targets(state, area_id, house);
+            state.enqueue_pending(PendingDecision::ChooseRaid {
+                house,
+                from_area: area_id,
+                valid_targets,
+            });
+        }
+        OrderType::March => {
+            let valid_dests = navigation::valid_destinations(state, area_id, house);
+            state.enqueue_pending(PendingDecision::ChooseMarch {
+                house,
+                from_area: area_id,
+                valid_destinations: valid_dests,
+                selectable_units: navigation::selectable_units(state, area_id, house),
+            });
+        }
+        OrderType::ConsolidatePower => {
+            resolve_single_consolidate_power(state, house, area_id);
+            // After resolving, advance to next player
+            pass_turn(state);
+        }
+        OrderType::Support | OrderType::Defense => {}
+    }
 }
 
 fn resolve_single_consolidate_power(state: &mut GameState, house: HouseName, area_id: AreaId) {
@@ -924,14 +1333,16 @@ fn resolve_single_consolidate_power(state: &mut GameState, house: HouseName, are
             points: area_def.muster_points(),
         };
         state.area_mut(area_id).order = None;
-        state.pending = Some(PendingDecision::Muster {
+        state.enqueue_pending(PendingDecision::Muster {
             house,
             areas: vec![muster_area],
         });
     } else {
-        // Regular CP: gain 1 power + power icons
-        let power_gain = 1 + area_def.power_icons;
-        state.house_mut(house).power += power_gain;
+        // Regular CP: gain 1 power + power icons; a blockaded port
+        // gains nothing
+        let blockaded = area_def.is_port() && is_blockaded(state, area_id, house);
+        let power_gain = if blockaded { 0 } else { 1 + area_def.power_icons };
+        gain_power(state, house, power_gain);
         state.area_mut(area_id).order = None;
     }
 }
@@ -941,25 +1352,25 @@ fn resolve_single_consolidate_power(state: &mut GameState, house: HouseName, are
 // ═══════════════════════════════════════════════════════════════════════
 
 fn advance_combat(state: &mut GameState) {
+    let _combat = state.combat().map(|c| tracing::debug_span!(
+        "combat", attacker = %c.attacker, defender = %c.defender, area = area_name(c.area_id),
+    ).entered());
     loop {
-        if state.pending.is_some() || state.winner.is_some() {
+        if state.has_pending() || state.winner.is_some() {
             return;
         }
 
-        let combat_data = match &state.combat {
+        let combat_data = match state.combat() {
             Some(c) => (c.phase, c.attacker, c.defender, c.area_id),
-            None => {
-                state.phase = Phase::Action;
-                return;
-            }
+            None => return,
         };
         let (combat_phase, attacker, defender, _area_id) = combat_data;
 
         match combat_phase {
             CombatPhase::Support => {
-                let pending_support = state.combat.as_ref().unwrap().pending_support_houses.clone();
+                let pending_support = state.combat().unwrap().pending_support_houses.clone();
                 if let Some(&(sup_area, sup_house)) = pending_support.first() {
-                    state.pending = Some(PendingDecision::SupportDeclaration {
+                    state.enqueue_pending(PendingDecision::SupportDeclaration {
                         house: sup_house,
                         area_id: sup_area,
                         attacker,
@@ -967,130 +1378,161 @@ fn advance_combat(state: &mut GameState) {
                     });
                     return;
                 }
-                if let Some(c) = &mut state.combat {
+                if let Some(c) = state.combat_mut() {
                     c.phase = CombatPhase::Cards;
                 }
                 continue; // re-enter loop for Cards phase
             }
 
             CombatPhase::Cards => {
-                let atk_card = state.combat.as_ref().unwrap().attacker_card;
-                let def_card = state.combat.as_ref().unwrap().defender_card;
+                let atk_card = state.combat().unwrap().attacker_card;
+                let def_card = state.combat().unwrap().defender_card;
 
+                // A house always has a card to play: its hand is refreshed
+                // as soon as the last one leaves it
                 if atk_card.is_none() {
-                    let available = state.house(attacker).hand.clone();
-                    if available.is_empty() {
-                        // No cards → skip to defender
-                    } else {
-                        state.pending = Some(PendingDecision::SelectHouseCard {
-                            house: attacker,
-                            available_cards: available,
-                        });
-                        return;
-                    }
+                    refresh_hand(state, attacker, None);
+                    state.enqueue_pending(PendingDecision::SelectHouseCard {
+                        house: attacker,
+                        available_cards: state.house(attacker).hand.clone(),
+                    });
+                    return;
                 }
 
                 if def_card.is_none() {
-                    let available = state.house(defender).hand.clone();
-                    if available.is_empty() {
-                        // No cards → skip to PreCombat
-                    } else {
-                        state.pending = Some(PendingDecision::SelectHouseCard {
-                            house: defender,
-                            available_cards: available,
-                        });
-                        return;
-                    }
+                    refresh_hand(state, defender, None);
+                    state.enqueue_pending(PendingDecision::SelectHouseCard {
+                        house: defender,
+                        available_cards: state.house(defender).hand.clone(),
+                    });
+                    return;
                 }
 
                 // Both cards selected → PreCombat
-                if let Some(c) = &mut state.combat {
+                if let Some(c) = state.combat_mut() {
                     c.phase = CombatPhase::PreCombat;
                 }
                 continue;
             }
 
             CombatPhase::PreCombat => {
-                let combat = state.combat.as_ref().unwrap();
+                // Tyrion cancels once per battle, the attacker's first: with
+                // Tyrion on both sides only the attacker's acts, and the
+                // replacement card never brings him back
+                let combat = state.combat().unwrap();
                 if !combat.tyrion_resolved {
-                    let atk_card = combat.attacker_card;
-                    let def_card = combat.defender_card;
-
-                    if atk_card == Some(HouseCardId::TyrionLannister) {
-                      if let Some(old_card) = def_card {
-                        if let Some(c) = &mut state.combat { c.tyrion_resolved = true; }
-                        state.house_mut(defender).hand.push(old_card);
-                        if let Some(pos) = state.house(defender).discards.iter().position(|&c| c == old_card) {
-                            state.house_mut(defender).discards.remove(pos);
+                    let sides = [
+                        (attacker, combat.attacker_card, defender, combat.defender_card),
+                        (defender, combat.defender_card, attacker, combat.attacker_card),
+                    ];
+                    if let Some(c) = state.combat_mut() { c.tyrion_resolved = true; }
+                    for (owner, card, decider, cancelled) in sides {
+                        if card_ability(card) != CardAbility::CancelOpponentCard {
+                            continue;
                         }
-                        if let Some(c) = &mut state.combat { c.defender_card = None; }
-                        state.pending = Some(PendingDecision::TyrionReplace { opponent: defender });
-                        return;
-                      }
-                    }
-
-                    if def_card == Some(HouseCardId::TyrionLannister) {
-                      if let Some(old_card) = atk_card {
-                        if let Some(c) = &mut state.combat { c.tyrion_resolved = true; }
-                        state.house_mut(attacker).hand.push(old_card);
-                        if let Some(pos) = state.house(attacker).discards.iter().position(|&c| c == old_card) {
-                            state.house_mut(attacker).discards.remove(pos);
+                        let Some(cancelled) = cancelled else { continue };
+                        // The cancelled card goes back to hand but can't be
+                        // played again this battle
+                        let profile = state.house_mut(decider);
+                        profile.hand.push(cancelled);
+                        if let Some(pos) = profile.discards.iter().position(|&c| c == cancelled) {
+                            profile.discards.remove(pos);
                         }
-                        if let Some(c) = &mut state.combat { c.attacker_card = None; }
-                        state.pending = Some(PendingDecision::TyrionReplace { opponent: attacker });
-                        return;
-                      }
+                        let available_cards: Vec<HouseCardId> = profile.hand.iter().copied().filter(|&c| c != cancelled).collect();
+                        if let Some(c) = state.combat_mut() {
+                            if decider == c.attacker { c.attacker_card = None } else { c.defender_card = None }
+                        }
+                        record_ability(state, owner);
+                        // With nothing else in hand it fights without a card
+                        if !available_cards.is_empty() {
+                            state.enqueue_pending(PendingDecision::TyrionReplace { decider, opponent: owner, available_cards });
+                            return;
+                        }
+                        break;
                     }
-
-                    if let Some(c) = &mut state.combat { c.tyrion_resolved = true; }
                 }
 
-                let combat = state.combat.as_ref().unwrap();
+                // Aeron is offered once, after Tyrion (a cancelled Aeron is
+                // never asked) and only to a house that can pay and has a
+                // card to swap in. The card swapped in doesn't bring the
+                // pre-combat step back to Tyrion or Aeron
+                let combat = state.combat().unwrap();
                 if !combat.aeron_resolved {
-                    let atk_card = combat.attacker_card;
-                    let def_card = combat.defender_card;
+                    let sides = [(attacker, combat.attacker_card), (defender, combat.defender_card)];
+                    if let Some(c) = state.combat_mut() { c.aeron_resolved = true; }
+                    for (house, card) in sides {
+                        let CardAbility::SwapCard { power_cost } = card_ability(card) else { continue };
+                        let available_cards = state.house(house).hand.clone();
+                        if state.house(house).power >= power_cost && !available_cards.is_empty() {
+                            state.enqueue_pending(PendingDecision::AeronSwap { house, available_cards, power_cost });
+                            return;
+                        }
+                    }
+                }
 
-                    if atk_card == Some(HouseCardId::AeronDamphair) && state.house(attacker).power >= 2 {
-                        if let Some(c) = &mut state.combat { c.aeron_resolved = true; }
-                        state.pending = Some(PendingDecision::AeronSwap { house: attacker });
-                        return;
+                // Doran acts on reveal, win or lose, against the other
+                // combatant; the track change is in place before the blade
+                // and the tiebreak are settled
+                let combat = state.combat().unwrap();
+                if !combat.doran_resolved {
+                    let sides = [(attacker, combat.attacker_card, defender), (defender, combat.defender_card, attacker)];
+                    if let Some(c) = state.combat_mut() { c.doran_resolved = true; }
+                    for (decider, card, opponent) in sides {
+                        if card_ability(card) == CardAbility::MoveOpponentToTrackBottom {
+                            state.enqueue_pending(PendingDecision::DoranChooseTrack { decider, opponent });
+                            record_ability(state, decider);
+                        }
                     }
-                    if def_card == Some(HouseCardId::AeronDamphair) && state.house(defender).power >= 2 {
-                        if let Some(c) = &mut state.combat { c.aeron_resolved = true; }
-                        state.pending = Some(PendingDecision::AeronSwap { house: defender });
+                    if state.has_pending() {
                         return;
                     }
+                }
 
-                    if let Some(c) = &mut state.combat { c.aeron_resolved = true; }
+                let combat = state.combat().unwrap();
+                if !combat.queen_of_thorns_resolved {
+                    let sides = [(attacker, combat.attacker_card, defender), (defender, combat.defender_card, attacker)];
+                    if let Some(c) = state.combat_mut() { c.queen_of_thorns_resolved = true; }
+                    for (decider, card, opponent) in sides {
+                        if card_ability(card) != CardAbility::RemoveAdjacentOrder {
+                            continue;
+                        }
+                        // Nothing to remove: the ability passes without asking
+                        let valid_targets = queen_of_thorns_targets(state, opponent);
+                        if !valid_targets.is_empty() {
+                            state.enqueue_pending(PendingDecision::QueenOfThornsRemoveOrder { decider, opponent, valid_targets });
+                            record_ability(state, decider);
+                            return;
+                        }
+                    }
                 }
 
-                if let Some(c) = &mut state.combat {
+                if let Some(c) = state.combat_mut() {
                     c.phase = CombatPhase::Resolution;
                 }
                 continue;
             }
 
             CombatPhase::Resolution => {
-                let blade_holder = find_track_holder(state, Track::Fiefdoms);
+                let blade_holder = tracks::holder(state, Track::Fiefdoms);
                 if !state.valyrian_steel_blade_used {
                     let in_combat = blade_holder == attacker || blade_holder == defender;
                     if in_combat {
-                        state.pending = Some(PendingDecision::UseValyrianBlade { house: blade_holder });
-                        if let Some(c) = &mut state.combat {
+                        state.enqueue_pending(PendingDecision::UseValyrianBlade { house: blade_holder });
+                        if let Some(c) = state.combat_mut() {
                             c.phase = CombatPhase::PostCombat;
                         }
                         return;
                     }
                 }
 
-                if let Some(c) = &mut state.combat {
+                if let Some(c) = state.combat_mut() {
                     c.phase = CombatPhase::PostCombat;
                 }
                 continue;
             }
 
             CombatPhase::PostCombat => {
-                resolve_combat_final(state);
+                run_post_combat(state);
                 return;
             }
         }
@@ -1100,11 +1542,13 @@ fn advance_combat(state: &mut GameState) {
 fn begin_combat(state: &mut GameState, attacker: HouseName, defender: HouseName,
                 area_id: AreaId, attacking_units: Vec<Unit>, march_from: AreaId) {
     let defending_units = state.area(area_id).units.clone();
+    tracing::debug!(attacker = %attacker, defender = %defender, area = area_name(area_id), from = area_name(march_from),
+        attacking = attacking_units.len(), defending = defending_units.len(), "combat begins");
 
     // Find adjacent support areas (non-combatant houses with Support orders)
     let mut support_houses: Vec<(AreaId, HouseName)> = Vec::new();
-    let area_def = &AREAS[area_id.0 as usize];
-    for &adj in area_def.adjacent {
+    let supporters = support_areas(area_id);
+    for &adj in &supporters {
         let adj_area = state.area(adj);
         if let Some(order) = adj_area.order {
             if order.order_type == OrderType::Support {
@@ -1117,7 +1561,8 @@ fn begin_combat(state: &mut GameState, attacker: HouseName, defender: HouseName,
         }
     }
 
-    state.combat = Some(CombatState {
+    let turn = state.action_turn().unwrap_or(ActionTurn { sub_phase: ActionSubPhase::March, player_index: 0 });
+    let combat = CombatState {
         attacker,
         defender,
         area_id,
@@ -1134,31 +1579,55 @@ fn begin_combat(state: &mut GameState, attacker: HouseName, defender: HouseName,
         phase: if support_houses.is_empty() { CombatPhase::Cards } else { CombatPhase::Support },
         aeron_resolved: false,
         tyrion_resolved: false,
+        queen_of_thorns_resolved: false,
+        doran_resolved: false,
         pending_support_houses: support_houses,
-    });
+        outcome: None,
+        post_combat_steps: VecDeque::new(),
+    };
+    state.engine_phase = EnginePhase::Combat { turn, combat: Box::new(combat) };
 
     // Auto-add combatants' own support (adjacent support orders from attacker/defender)
-    for &adj in area_def.adjacent {
+    for &adj in &supporters {
         let adj_area = state.area(adj);
         if let Some(order) = adj_area.order {
             if order.order_type == OrderType::Support {
                 if adj_area.house == Some(attacker) {
-                    state.combat.as_mut().unwrap().support_decisions.insert(adj, SupportChoice::Attacker);
+                    state.combat_mut().unwrap().support_decisions.insert(adj, SupportChoice::Attacker);
                 } else if adj_area.house == Some(defender) {
-                    state.combat.as_mut().unwrap().support_decisions.insert(adj, SupportChoice::Defender);
+                    state.combat_mut().unwrap().support_decisions.insert(adj, SupportChoice::Defender);
                 }
             }
         }
     }
+}
 
-    state.phase = Phase::Combat;
+/// Count one of `house`'s card abilities taking effect.
+fn record_ability(state: &mut GameState, house: HouseName) {
+    let stats = &mut state.house_mut(house).card_stats;
+    stats.abilities_triggered = stats.abilities_triggered.saturating_add(1);
 }
 
-fn resolve_combat_final(state: &mut GameState) {
+/// Count `card` played by `house` in a combat it `won` or lost.
+fn record_card_play(state: &mut GameState, house: HouseName, card: HouseCardId, won: bool) {
+    let swords = if won { cards::get_house_card(card).swords as u16 } else { 0 };
+    let stats = &mut state.house_mut(house).card_stats;
+    stats.cards_played = stats.cards_played.saturating_add(1);
+    *stats.plays.entry(card).or_insert(0) += 1;
+    stats.swords_dealt = stats.swords_dealt.saturating_add(swords);
+}
+
+/// The ability of a played card, `None` when no card was played.
+fn card_ability(card: Option<HouseCardId>) -> CardAbility {
+    card.map_or(CardAbility::None, |c| cards::get_house_card(c).ability)
+}
+
+/// Compare strengths, record the outcome and queue the post-combat steps.
+fn determine_combat_outcome(state: &mut GameState) {
     // Extract all combat data
-    let combat = match &state.combat {
+    let combat = match state.combat() {
         Some(c) => c,
-        None => { state.phase = Phase::Action; return; }
+        None => return,
     };
 
     let attacker = combat.attacker;
@@ -1187,7 +1656,9 @@ fn resolve_combat_final(state: &mut GameState) {
             }
         })
         .sum();
+    // The defender's whole stack fights, but routed units add nothing
     let def_unit_str: i16 = defending_units.iter()
+        .filter(|u| !u.routed)
         .map(|u| u.unit_type.combat_strength() as i16)
         .sum();
 
@@ -1205,8 +1676,10 @@ fn resolve_combat_final(state: &mut GameState) {
         .filter(|o| o.order_type == OrderType::Defense)
         .map_or(0, |o| o.strength as i16);
 
-    // Garrison defense (helps whoever is defending the area)
+    // Garrison defense (helps whoever is defending the area); fleets
+    // fight on ships alone
     let garrison_str: i16 = state.garrisons.get(&area_id)
+        .filter(|_| area_def.is_land())
         .map_or(0, |g| g.strength as i16);
 
     // Support strength
@@ -1217,6 +1690,7 @@ fn resolve_combat_final(state: &mut GameState) {
         let sup = state.area(sup_area);
         let sup_order = sup.order.map_or(0i16, |o| o.strength as i16);
         let sup_units: i16 = sup.units.iter()
+            .filter(|u| !u.routed)
             .map(|u| u.unit_type.combat_strength() as i16)
             .sum();
         let total = sup_order + sup_units;
@@ -1228,125 +1702,26 @@ fn resolve_combat_final(state: &mut GameState) {
     }
 
     // Valyrian Steel Blade
-    let atk_blade: i16 = if state.combat.as_ref().unwrap().attacker_used_blade { 1 } else { 0 };
-    let def_blade: i16 = if state.combat.as_ref().unwrap().defender_used_blade { 1 } else { 0 };
+    let atk_blade: i16 = if state.combat().unwrap().attacker_used_blade { 1 } else { 0 };
+    let def_blade: i16 = if state.combat().unwrap().defender_used_blade { 1 } else { 0 };
 
     // Card abilities: combat modifiers
-    let mut atk_ability_bonus: i16 = 0;
-    let mut def_ability_bonus: i16 = 0;
-
-    // Catelyn Stark: +1 per own discard pile size
-    if atk_card_id == Some(HouseCardId::CatelynStark) {
-        atk_ability_bonus += state.house(attacker).discards.len() as i16;
-    }
-    if def_card_id == Some(HouseCardId::CatelynStark) {
-        def_ability_bonus += state.house(defender).discards.len() as i16;
-    }
-
-    // Stannis Baratheon: +1 per own ship in adjacent sea zones
-    if atk_card_id == Some(HouseCardId::StannisBaratheon) {
-        let adjacent_ships: i16 = area_def.adjacent.iter()
-            .filter(|&&adj| AREAS[adj.0 as usize].area_type == AreaType::Sea)
-            .flat_map(|&adj| state.area(adj).units.iter())
-            .filter(|u| u.house == attacker && u.unit_type == UnitType::Ship)
-            .count() as i16;
-        atk_ability_bonus += adjacent_ships;
-    }
-    if def_card_id == Some(HouseCardId::StannisBaratheon) {
-        let adjacent_ships: i16 = area_def.adjacent.iter()
-            .filter(|&&adj| AREAS[adj.0 as usize].area_type == AreaType::Sea)
-            .flat_map(|&adj| state.area(adj).units.iter())
-            .filter(|u| u.house == defender && u.unit_type == UnitType::Ship)
-            .count() as i16;
-        def_ability_bonus += adjacent_ships;
-    }
-
-    // Victarion Greyjoy: +1 per own ship in adjacent port/sea
-    if atk_card_id == Some(HouseCardId::VictarionGreyjoy) {
-        let adjacent_ships: i16 = area_def.adjacent.iter()
-            .filter(|&&adj| {
-                let at = AREAS[adj.0 as usize].area_type;
-                at == AreaType::Sea || at == AreaType::Port
-            })
-            .flat_map(|&adj| state.area(adj).units.iter())
-            .filter(|u| u.house == attacker && u.unit_type == UnitType::Ship)
-            .count() as i16;
-        atk_ability_bonus += adjacent_ships;
-    }
-    if def_card_id == Some(HouseCardId::VictarionGreyjoy) {
-        let adjacent_ships: i16 = area_def.adjacent.iter()
-            .filter(|&&adj| {
-                let at = AREAS[adj.0 as usize].area_type;
-                at == AreaType::Sea || at == AreaType::Port
-            })
-            .flat_map(|&adj| state.area(adj).units.iter())
-            .filter(|u| u.house == defender && u.unit_type == UnitType::Ship)
-            .count() as i16;
-        def_ability_bonus += adjacent_ships;
-    }
-
-    // Mace Tyrell: +1 for each own supporting area
-    if atk_card_id == Some(HouseCardId::MaceTyrell) {
-        let own_support_count: i16 = support_decisions.iter()
-            .filter(|(_, &choice)| choice == SupportChoice::Attacker)
-            .filter(|(&sup_area, _)| state.area(sup_area).house == Some(attacker))
-            .count() as i16;
-        atk_ability_bonus += own_support_count;
-    }
-    if def_card_id == Some(HouseCardId::MaceTyrell) {
-        let own_support_count: i16 = support_decisions.iter()
-            .filter(|(_, &choice)| choice == SupportChoice::Defender)
-            .filter(|(&sup_area, _)| state.area(sup_area).house == Some(defender))
-            .count() as i16;
-        def_ability_bonus += own_support_count;
-    }
-
-    // The Blackfish: +1 per own land area adjacent to combat area
-    if atk_card_id == Some(HouseCardId::TheBlackfish) {
-        let adj_land_count: i16 = area_def.adjacent.iter()
-            .filter(|&&adj| AREAS[adj.0 as usize].area_type == AreaType::Land && state.area(adj).house == Some(attacker))
-            .count() as i16;
-        atk_ability_bonus += adj_land_count;
-    }
-    if def_card_id == Some(HouseCardId::TheBlackfish) {
-        let adj_land_count: i16 = area_def.adjacent.iter()
-            .filter(|&&adj| AREAS[adj.0 as usize].area_type == AreaType::Land && state.area(adj).house == Some(defender))
-            .count() as i16;
-        def_ability_bonus += adj_land_count;
-    }
-
-    // Ser Jaime Lannister: +1 if attacking (only for attacker)
-    if atk_card_id == Some(HouseCardId::SerJaimeLannister) {
-        atk_ability_bonus += 1;
-    }
-
-    // Greatjon Umber: +1 if attacking
-    if atk_card_id == Some(HouseCardId::GreatjonUmber) {
-        atk_ability_bonus += 1;
-    }
-
-    // Obara Sand: +1 if defending
-    if def_card_id == Some(HouseCardId::ObaraSand) {
-        def_ability_bonus += 1;
-    }
+    let atk_ability = atk_card.map_or(CardAbility::None, |c| c.ability);
+    let def_ability = def_card.map_or(CardAbility::None, |c| c.ability);
+    let atk_ability_bonus = ability_bonus(state, atk_ability, attacker, CombatSide::Attacker, area_id, &support_decisions);
+    let def_ability_bonus = ability_bonus(state, def_ability, defender, CombatSide::Defender, area_id, &support_decisions);
 
     // Balon Greyjoy: enemies may not support
-    let final_atk_support = if def_card_id == Some(HouseCardId::BalonGreyjoy) {
-        0 // Defender played Balon → attacker gets no support
-    } else {
-        atk_support
-    };
-    let final_def_support = if atk_card_id == Some(HouseCardId::BalonGreyjoy) {
-        0 // Attacker played Balon → defender gets no support
-    } else {
-        def_support
-    };
+    let final_atk_support = if def_ability == CardAbility::CancelEnemySupport { 0 } else { atk_support };
+    let final_def_support = if atk_ability == CardAbility::CancelEnemySupport { 0 } else { def_support };
+    let atk_triggered = atk_ability_bonus > 0 || (atk_ability == CardAbility::CancelEnemySupport && def_support > 0);
+    let def_triggered = def_ability_bonus > 0 || (def_ability == CardAbility::CancelEnemySupport && atk_support > 0);
 
     let atk_total = atk_unit_str + atk_card_str + march_bonus + final_atk_support + atk_blade + atk_ability_bonus;
     let def_total = def_unit_str + def_card_str + defense_bonus + garrison_str + final_def_support + def_blade + def_ability_bonus;
 
     // Write back strengths
-    if let Some(c) = &mut state.combat {
+    if let Some(c) = state.combat_mut() {
         c.attacker_strength = atk_total;
         c.defender_strength = def_total;
     }
@@ -1372,13 +1747,13 @@ fn resolve_combat_final(state: &mut GameState) {
 
     // ── Special casualty modifiers ──
 
-    // Renly Baratheon: defender takes no casualties this combat
-    let renly_no_casualties = (def_card_id == Some(HouseCardId::RenlyBaratheon) && !attacker_wins)
-        || (atk_card_id == Some(HouseCardId::RenlyBaratheon) && attacker_wins);
+    // Renly Baratheon: no casualties when its house wins
+    let winner_ability = if attacker_wins { atk_ability } else { def_ability };
+    let renly_no_casualties = winner_ability == CardAbility::NoCasualtiesOnWin;
 
     // Arianne Martell: no casualties, all units return (both sides)
-    let arianne_played = atk_card_id == Some(HouseCardId::ArianneMartell)
-        || def_card_id == Some(HouseCardId::ArianneMartell);
+    let arianne_played = atk_ability == CardAbility::NoCasualtiesNoConquest
+        || def_ability == CardAbility::NoCasualtiesNoConquest;
 
     let effective_casualties = if arianne_played || renly_no_casualties {
         0
@@ -1386,356 +1761,429 @@ fn resolve_combat_final(state: &mut GameState) {
         casualties
     };
 
-    // ── Apply result ──
-
-    if attacker_wins {
-        // Kill defender casualties
-        let mut killed = 0;
-        let mut remaining_defenders: Vec<Unit> = defending_units.clone();
-        if !arianne_played {
-            while killed < effective_casualties && !remaining_defenders.is_empty() {
-                let unit = remaining_defenders.remove(0);
-                *state.house_mut(defender).available_units.get_mut(unit.unit_type) += 1;
-                killed += 1;
-            }
+    let winner = if attacker_wins { attacker } else { defender };
+    let (atk_triggered, def_triggered) = (
+        atk_triggered || atk_ability == CardAbility::NoCasualtiesNoConquest,
+        def_triggered || def_ability == CardAbility::NoCasualtiesNoConquest,
+    );
+    for (house, triggered) in [(attacker, atk_triggered), (defender, def_triggered)] {
+        if triggered || (house == winner && renly_no_casualties && casualties > 0) {
+            record_ability(state, house);
         }
+    }
 
-        if arianne_played {
-            // Arianne: both sides return to their areas, no one conquers
-            // Attacker returns to origin
-            // Defender stays
-            // No territory change
-        } else {
-            // Place attacking units in conquered area
-            state.area_mut(area_id).units.retain(|u| u.house != defender);
-            for unit in &attacking_units {
-                state.area_mut(area_id).units.push(*unit);
-            }
-            state.area_mut(area_id).house = Some(attacker);
-            // Remove garrison when area changes hands
-            state.garrisons.remove(&area_id);
-        }
-
-        // Roose Bolton: returns to hand instead of discard
-        if def_card_id == Some(HouseCardId::RooseBolton) {
-            if let Some(pos) = state.house(defender).discards.iter().position(|&c| c == HouseCardId::RooseBolton) {
-                state.house_mut(defender).discards.remove(pos);
-                state.house_mut(defender).hand.push(HouseCardId::RooseBolton);
-            }
-        }
+    if let Some(c) = state.combat_mut() {
+        c.outcome = Some(CombatOutcome {
+            attacker_wins,
+            casualties: effective_casualties as u8,
+            no_conquest: arianne_played,
+            attacker_support: final_atk_support,
+            defender_support: final_def_support,
+        });
+        c.post_combat_steps = PostCombatStep::ORDER.into_iter().collect();
+    }
+}
 
-        // Post-combat: Tywin Lannister (winner takes 2 power from loser)
-        if atk_card_id == Some(HouseCardId::TywinLannister) {
-            let steal = state.house(defender).power.min(2);
-            state.house_mut(defender).power -= steal;
-            state.house_mut(attacker).power += steal;
-        }
+/// Strength `ability` adds for `house`, fighting on `side` over `area_id`.
+fn ability_bonus(
+    state: &GameState,
+    ability: CardAbility,
+    house: HouseName,
+    side: CombatSide,
+    area_id: AreaId,
+    support_decisions: &HashMap<AreaId, SupportChoice>,
+) -> i16 {
+    let area_def = &AREAS[area_id.0 as usize];
+    match ability {
+        // Ser Jaime, the Greatjon (attacking), Obara Sand (defending)
+        CardAbility::FlatBonus { bonus, side: when } if when == side => bonus as i16,
+        // Catelyn Stark: +1 per own discard pile size
+        CardAbility::BonusPerDiscard => state.house(house).discards.len() as i16,
+        // Stannis Baratheon (seas), Victarion Greyjoy (seas and ports)
+        CardAbility::BonusPerAdjacentShip { ports } => area_def.adjacent.iter()
+            .filter(|&&adj| {
+                let at = AREAS[adj.0 as usize].area_type;
+                at == AreaType::Sea || (ports && at == AreaType::Port)
+            })
+            .flat_map(|&adj| state.area(adj).units.iter())
+            .filter(|u| u.house == house && u.unit_type == UnitType::Ship)
+            .count() as i16,
+        // Mace Tyrell: +1 for each own supporting area
+        CardAbility::BonusPerOwnSupport => {
+            let supports = match side {
+                CombatSide::Attacker => SupportChoice::Attacker,
+                CombatSide::Defender => SupportChoice::Defender,
+            };
+            support_decisions.iter()
+                .filter(|(&sup_area, &choice)| choice == supports && state.area(sup_area).house == Some(house))
+                .count() as i16
+        }
+        // The Blackfish: +1 per own land area adjacent to combat area
+        CardAbility::BonusPerAdjacentLand => area_def.adjacent.iter()
+            .filter(|&&adj| AREAS[adj.0 as usize].area_type == AreaType::Land && state.area(adj).house == Some(house))
+            .count() as i16,
+        _ => 0,
+    }
+}
 
-        // Ser Davos Seaworth: winner upgrades 1 footman to knight
-        if atk_card_id == Some(HouseCardId::SerDavosSeaworth)
-            && state.house(attacker).available_units.knights > 0 {
-                if let Some(pos) = state.area(area_id).units.iter().position(|u| {
-                    u.house == attacker && u.unit_type == UnitType::Footman
-                }) {
-                    state.area_mut(area_id).units[pos].unit_type = UnitType::Knight;
-                    state.house_mut(attacker).available_units.knights -= 1;
-                    state.house_mut(attacker).available_units.footmen += 1;
-                }
-            }
+// ── Post-combat pipeline ──
+// casualties → loser card abilities → winner card abilities → retreat → cleanup
 
-        // Theon Greyjoy: if won by 2+ strength, steal 1 power from loser
-        if atk_card_id == Some(HouseCardId::TheonGreyjoy) && (atk_total - def_total) >= 2 {
-            let steal = state.house(defender).power.min(1);
-            state.house_mut(defender).power -= steal;
-            state.house_mut(attacker).power += steal;
-        }
+fn run_post_combat(state: &mut GameState) {
+    if state.combat().is_some_and(|c| c.outcome.is_none()) {
+        determine_combat_outcome(state);
+    }
 
-        // Ser Kevan Lannister: loser steals power tokens = opponent's card strength
-        if def_card_id == Some(HouseCardId::SerKevanLannister) {
-            let card_str = atk_card.map_or(0, |c| c.strength);
-            let steal = state.house(attacker).power.min(card_str);
-            state.house_mut(attacker).power -= steal;
-            state.house_mut(defender).power += steal;
+    loop {
+        if state.has_pending() || state.winner.is_some() {
+            return;
         }
-
-        // Melisandre: opponent must discard their highest strength house card from hand
-        if atk_card_id == Some(HouseCardId::Melisandre) {
-            let hand = state.house(defender).hand.clone();
-            if !hand.is_empty() {
-                let best = hand.iter()
-                    .max_by_key(|&&c| cards::get_house_card(c).strength)
-                    .copied()
-                    .unwrap();
-                if let Some(pos) = state.house(defender).hand.iter().position(|&c| c == best) {
-                    state.house_mut(defender).hand.remove(pos);
-                    state.house_mut(defender).discards.push(best);
-                }
+        let step = match state.combat_mut() {
+            Some(c) => c.post_combat_steps.pop_front(),
+            None => return,
+        };
+        match step {
+            Some(PostCombatStep::Casualties) => apply_combat_casualties(state),
+            Some(PostCombatStep::LoserAbilities) => resolve_loser_abilities(state),
+            Some(PostCombatStep::WinnerAbilities) => resolve_winner_abilities(state),
+            Some(PostCombatStep::Retreat) => resolve_combat_retreat(state),
+            Some(PostCombatStep::Cleanup) | None => {
+                combat_cleanup(state);
+                return;
             }
         }
+    }
+}
 
-        // Post-combat: Cersei Lannister (remove one enemy order)
-        if atk_card_id == Some(HouseCardId::CerseiLannister) {
-            state.pending = Some(PendingDecision::CerseiRemoveOrder { opponent: defender });
-            check_victory(state);
-            // Don't clear combat yet — will be cleared after Cersei decision
-            return;
+/// Loser removes casualties. Attackers are held in the combat state,
+/// defenders are still on the board.
+fn apply_combat_casualties(state: &mut GameState) {
+    let combat = state.combat().unwrap();
+    let outcome = combat.outcome.unwrap();
+    let loser = combat.loser().unwrap();
+    let area_id = combat.area_id;
+
+    for _ in 0..outcome.casualties {
+        let unit = if outcome.attacker_wins {
+            let pos = state.area(area_id).units.iter().position(|u| u.house == loser);
+            pos.map(|p| state.area_mut(area_id).units.remove(p))
+        } else {
+            let attackers = &mut state.combat_mut().unwrap().attacking_units;
+            if attackers.is_empty() { None } else { Some(attackers.remove(0)) }
+        };
+        match unit {
+            Some(u) => return_to_pool(state, &u),
+            None => break,
         }
+    }
+}
 
-        // Asha Greyjoy: if defender (loser) played Asha, retreat to home area
-        if def_card_id == Some(HouseCardId::AshaGreyjoy) && !remaining_defenders.is_empty() {
-            let home = find_home_area(state, defender);
-            if let Some(home_area) = home {
-                for mut unit in remaining_defenders.drain(..) {
-                    unit.routed = true;
-                    state.area_mut(home_area).units.push(unit);
-                }
+fn resolve_loser_abilities(state: &mut GameState) {
+    let combat = state.combat().unwrap();
+    let winner = combat.winner().unwrap();
+    let loser = combat.loser().unwrap();
+    let winner_card = combat.winner_card();
+    let loser_card = combat.loser_card();
+
+    let triggered = match card_ability(loser_card) {
+        // Roose Bolton: returns to hand instead of discard
+        CardAbility::ReturnToHand => {
+            let card = loser_card.unwrap();
+            if let Some(pos) = state.house(loser).discards.iter().position(|&c| c == card) {
+                state.house_mut(loser).discards.remove(pos);
+                state.house_mut(loser).hand.push(card);
             }
-            // If no home area fallback, fall through to normal retreat below
+            true
         }
+        // Ser Kevan Lannister: loser steals power tokens = opponent's card strength
+        CardAbility::StealPowerByOpponentStrength => {
+            let card_str = winner_card.map_or(0, |c| cards::get_house_card(c).strength);
+            let steal = state.house(winner).power.min(card_str);
+            spend_power(state, winner, steal);
+            gain_power(state, loser, steal);
+            true
+        }
+        _ => false,
+    };
+    if triggered {
+        record_ability(state, loser);
+    }
+}
 
-        // Robb Stark: attacker chooses retreat area for defender
-        if atk_card_id == Some(HouseCardId::RobbStark) {
-            let retreat_options = find_retreat_areas(state, area_id, defender);
-            if !retreat_options.is_empty() && !remaining_defenders.is_empty() {
-                state.pending = Some(PendingDecision::RobbRetreat {
-                    house: attacker,
-                    possible_areas: retreat_options,
-                });
-                check_victory(state);
-                return;
+fn resolve_winner_abilities(state: &mut GameState) {
+    let combat = state.combat().unwrap();
+    let winner = combat.winner().unwrap();
+    let loser = combat.loser().unwrap();
+    let winner_card = combat.winner_card();
+    let margin = (combat.attacker_strength - combat.defender_strength).abs();
+
+    let triggered = match card_ability(winner_card) {
+        // Tywin Lannister (2 power), Theon Greyjoy (1, winning by 2+)
+        CardAbility::StealPower { amount, min_margin } if margin >= min_margin as i16 => {
+            let steal = state.house(loser).power.min(amount);
+            spend_power(state, loser, steal);
+            gain_power(state, winner, steal);
+            true
+        }
+        // Ser Davos Seaworth: winner upgrades 1 footman to knight
+        CardAbility::UpgradeFootman if state.house(winner).available_units.knights > 0 => {
+            let combat = state.combat_mut().unwrap();
+            let area_id = combat.area_id;
+            let upgraded = if winner == combat.attacker {
+                combat.attacking_units.iter_mut().find(|u| u.unit_type == UnitType::Footman)
+            } else {
+                state.areas[area_id.0 as usize].units.iter_mut()
+                    .find(|u| u.house == winner && u.unit_type == UnitType::Footman)
+            };
+            if let Some(unit) = upgraded {
+                unit.unit_type = UnitType::Knight;
+                let _ = state.house_mut(winner).swap_unit(UnitType::Footman, UnitType::Knight);
+                true
+            } else {
+                false
             }
         }
-
-        // Defender must retreat surviving units
-        if !remaining_defenders.is_empty() {
-            let retreat_options = find_retreat_areas(state, area_id, defender);
-            if retreat_options.is_empty() {
-                // Units destroyed
-                for unit in remaining_defenders {
-                    *state.house_mut(defender).available_units.get_mut(unit.unit_type) += 1;
+        // Melisandre: opponent must discard their highest strength house card from hand
+        CardAbility::DiscardStrongestFromOpponentHand => {
+            let best = state.house(loser).hand.iter()
+                .max_by_key(|&&c| cards::get_house_card(c).strength)
+                .copied();
+            if let Some(best) = best {
+                if let Some(pos) = state.house(loser).hand.iter().position(|&c| c == best) {
+                    state.house_mut(loser).hand.remove(pos);
+                    state.house_mut(loser).discards.push(best);
                 }
-            } else {
-                state.pending = Some(PendingDecision::Retreat {
-                    house: defender,
-                    units: remaining_defenders,
-                    from_area: area_id,
-                    possible_areas: retreat_options,
-                });
-                check_victory(state);
-                return;
             }
+            best.is_some()
         }
-
-        // Nymeria Sand: attacker wins, remove one defender order
-        if atk_card_id == Some(HouseCardId::NymeriaSand) {
+        // Cersei Lannister: remove one enemy order, if the loser has any
+        CardAbility::RemoveEnemyOrder { chosen: true } => {
+            let valid_targets = orders_of(state, loser);
+            let any = !valid_targets.is_empty();
+            if any {
+                state.enqueue_pending(PendingDecision::CerseiRemoveOrder { decider: winner, opponent: loser, valid_targets });
+            }
+            any
+        }
+        // Nymeria Sand: remove one enemy order (auto: first found)
+        CardAbility::RemoveEnemyOrder { chosen: false } => {
             let enemy_order_area = state.areas.iter().enumerate()
-                .find(|(_, a)| a.house == Some(defender) && a.order.is_some())
+                .find(|(_, a)| a.house == Some(loser) && a.order.is_some())
                 .map(|(i, _)| AreaId(i as u8));
             if let Some(eid) = enemy_order_area {
                 state.area_mut(eid).order = None;
             }
+            enemy_order_area.is_some()
         }
-
         // Patchface: look at opponent's hand, discard one
-        if atk_card_id == Some(HouseCardId::Patchface) && !state.house(defender).hand.is_empty() {
-            let visible = state.house(defender).hand.clone();
-            state.pending = Some(PendingDecision::PatchfaceDiscard {
-                opponent: defender,
+        CardAbility::DiscardFromOpponentHand if !state.house(loser).hand.is_empty() => {
+            let visible = state.house(loser).hand.clone();
+            state.enqueue_pending(PendingDecision::PatchfaceDiscard {
+                decider: winner,
+                opponent: loser,
                 visible_cards: visible,
             });
-            check_victory(state);
-            return;
-        }
-
-        // Doran Martell: if defender played Doran and lost, move attacker down one track
-        if def_card_id == Some(HouseCardId::DoranMartell) {
-            state.pending = Some(PendingDecision::DoranChooseTrack { opponent: attacker });
-            check_victory(state);
-            return;
+            true
         }
-    } else {
-        // Defender wins — attacker retreats
+        _ => false,
+    };
+    if triggered {
+        record_ability(state, winner);
+    }
+}
 
-        // Kill attacker casualties
-        let mut killed = 0;
-        let mut remaining_attackers: Vec<Unit> = attacking_units.clone();
-        if !arianne_played {
-            while killed < effective_casualties && !remaining_attackers.is_empty() {
-                let unit = remaining_attackers.remove(0);
-                *state.house_mut(attacker).available_units.get_mut(unit.unit_type) += 1;
-                killed += 1;
-            }
+/// Surviving losing units leave the embattled area.
+fn resolve_combat_retreat(state: &mut GameState) {
+    let combat = state.combat().unwrap();
+    let outcome = combat.outcome.unwrap();
+    let (attacker, defender, area_id) = (combat.attacker, combat.defender, combat.area_id);
+    let winner_card = combat.winner_card();
+    let loser_card = combat.loser_card();
+    let origin = combat.march_from_area.unwrap_or(area_id);
+
+    if !outcome.attacker_wins {
+        // Attackers go back where they marched from (home with Asha)
+        let survivors = std::mem::take(&mut state.combat_mut().unwrap().attacking_units);
+        let home = if card_ability(loser_card) == CardAbility::RetreatHome && AREAS[origin.0 as usize].is_land() {
+            find_home_area(state, attacker)
+        } else {
+            None
+        };
+        if home.is_some() {
+            record_ability(state, attacker);
         }
-
-        // Roose Bolton for attacker
-        if atk_card_id == Some(HouseCardId::RooseBolton) {
-            if let Some(pos) = state.house(attacker).discards.iter().position(|&c| c == HouseCardId::RooseBolton) {
-                state.house_mut(attacker).discards.remove(pos);
-                state.house_mut(attacker).hand.push(HouseCardId::RooseBolton);
-            }
+        let to = home.unwrap_or(origin);
+        for mut unit in survivors {
+            unit.routed = !outcome.no_conquest;
+            state.area_mut(to).units.push(unit);
         }
+        return;
+    }
 
-        // Tywin for defender
-        if def_card_id == Some(HouseCardId::TywinLannister) {
-            let steal = state.house(attacker).power.min(2);
-            state.house_mut(attacker).power -= steal;
-            state.house_mut(defender).power += steal;
-        }
+    if outcome.no_conquest {
+        return; // Arianne: defenders hold the area
+    }
 
-        // Ser Davos for defender
-        if def_card_id == Some(HouseCardId::SerDavosSeaworth)
-            && state.house(defender).available_units.knights > 0 {
-                if let Some(pos) = state.area(area_id).units.iter().position(|u| {
-                    u.house == defender && u.unit_type == UnitType::Footman
-                }) {
-                    state.area_mut(area_id).units[pos].unit_type = UnitType::Knight;
-                    state.house_mut(defender).available_units.knights -= 1;
-                    state.house_mut(defender).available_units.footmen += 1;
-                }
-            }
+    let survivors: Vec<Unit> = state.area(area_id).units.iter()
+        .filter(|u| u.house == defender)
+        .copied()
+        .collect();
+    if survivors.is_empty() {
+        return;
+    }
 
-        // Theon for defender
-        if def_card_id == Some(HouseCardId::TheonGreyjoy) && (def_total - atk_total) >= 2 {
-            let steal = state.house(attacker).power.min(1);
-            state.house_mut(attacker).power -= steal;
-            state.house_mut(defender).power += steal;
-        }
-
-        // Ser Kevan for attacker (loser)
-        if atk_card_id == Some(HouseCardId::SerKevanLannister) {
-            let card_str = def_card.map_or(0, |c| c.strength);
-            let steal = state.house(defender).power.min(card_str);
-            state.house_mut(defender).power -= steal;
-            state.house_mut(attacker).power += steal;
-        }
-
-        // Melisandre for defender
-        if def_card_id == Some(HouseCardId::Melisandre) {
-            let hand = state.house(attacker).hand.clone();
-            if !hand.is_empty() {
-                let best = hand.iter()
-                    .max_by_key(|&&c| cards::get_house_card(c).strength)
-                    .copied()
-                    .unwrap();
-                if let Some(pos) = state.house(attacker).hand.iter().position(|&c| c == best) {
-                    state.house_mut(attacker).hand.remove(pos);
-                    state.house_mut(attacker).discards.push(best);
-                }
+    // Asha Greyjoy: losing defender retreats to home area (not a fleet)
+    if card_ability(loser_card) == CardAbility::RetreatHome && AREAS[area_id.0 as usize].is_land() {
+        if let Some(home_area) = find_home_area(state, defender) {
+            record_ability(state, defender);
+            state.area_mut(area_id).units.retain(|u| u.house != defender);
+            for mut unit in survivors {
+                unit.routed = true;
+                state.area_mut(home_area).units.push(unit);
             }
-        }
-
-        // Cersei for defender
-        if def_card_id == Some(HouseCardId::CerseiLannister) {
-            state.pending = Some(PendingDecision::CerseiRemoveOrder { opponent: attacker });
-            check_victory(state);
             return;
         }
+        // No home area: fall through to normal retreat
+    }
 
-        // Asha Greyjoy: if loser, retreat to home area
-        if atk_card_id == Some(HouseCardId::AshaGreyjoy) && !remaining_attackers.is_empty() {
-            // Find home area for attacker
-            let home = find_home_area(state, attacker);
-            if let Some(home_area) = home {
-                for mut unit in remaining_attackers {
-                    unit.routed = true;
-                    state.area_mut(home_area).units.push(unit);
-                }
-            } else {
-                // Fallback: normal retreat to origin
-                let from = march_from_area.unwrap_or(area_id);
-                for mut unit in remaining_attackers {
-                    unit.routed = true;
-                    state.area_mut(from).units.push(unit);
-                }
-            }
-        } else if arianne_played {
-            // Arianne: both sides return, attacker goes back to origin
-            let from = march_from_area.unwrap_or(area_id);
-            for unit in remaining_attackers {
-                state.area_mut(from).units.push(unit);
-            }
-        } else {
-            // Return surviving attackers to origin (routed)
-            let from = march_from_area.unwrap_or(area_id);
-            for mut unit in remaining_attackers {
-                unit.routed = true;
-                state.area_mut(from).units.push(unit);
-            }
-        }
+    let retreat_options = find_retreat_areas(state, area_id, defender, origin);
+    if retreat_options.is_empty() {
+        // Nowhere to go: units destroyed, whoever would have chosen
+        state.area_mut(area_id).units.retain(|u| u.house != defender);
+        for unit in survivors {
+            return_to_pool(state, &unit);
+        }
+    } else if card_ability(winner_card) == CardAbility::ChooseOpponentRetreat {
+        // Robb Stark: attacker chooses among the defender's own retreats
+        record_ability(state, attacker);
+        state.enqueue_pending(PendingDecision::RobbRetreat {
+            house: attacker,
+            defender,
+            units: survivors,
+            from_area: area_id,
+            possible_areas: retreat_options,
+        });
+    } else {
+        state.enqueue_pending(PendingDecision::Retreat {
+            house: defender,
+            units: survivors,
+            from_area: area_id,
+            possible_areas: retreat_options,
+        });
+    }
+}
 
-        // Nymeria Sand: winner chooses enemy order to remove
-        if def_card_id == Some(HouseCardId::NymeriaSand) {
-            // Auto: find and remove first enemy order
-            let enemy_order_area = state.areas.iter().enumerate()
-                .find(|(_, a)| a.house == Some(attacker) && a.order.is_some())
-                .map(|(i, _)| AreaId(i as u8));
-            if let Some(eid) = enemy_order_area {
-                state.area_mut(eid).order = None;
-            }
-        }
+/// Once `house` has no house cards in hand, its discards return to it,
+/// all but `kept` (the card it just played), which stays discarded.
+fn refresh_hand(state: &mut GameState, house: HouseName, kept: Option<HouseCardId>) {
+    let profile = state.house_mut(house);
+    if !profile.hand.is_empty() {
+        return;
+    }
+    let discards = std::mem::take(&mut profile.discards);
+    let (stay, back): (Vec<HouseCardId>, Vec<HouseCardId>) = discards.into_iter().partition(|&c| Some(c) == kept);
+    profile.hand = back;
+    profile.discards = stay;
+}
 
-        // Margaery Tyrell: winner removes opponent's order in attacked area (irrelevant if defender wins, but check)
-        // Margaery is only relevant when winning — skip if it doesn't match
+/// Victorious attackers move in, Ser Loras keeps marching, combat ends.
+fn combat_cleanup(state: &mut GameState) {
+    let combat = state.combat().unwrap();
+    let outcome = combat.outcome.unwrap();
+    let (attacker, area_id) = (combat.attacker, combat.area_id);
+    let march_from_area = combat.march_from_area;
+    let conquers = outcome.attacker_wins;
+    let played = [
+        (attacker, combat.attacker_card, outcome.attacker_wins),
+        (combat.defender, combat.defender_card, !outcome.attacker_wins),
+    ];
 
-        // Patchface for defender
-        if def_card_id == Some(HouseCardId::Patchface) && !state.house(attacker).hand.is_empty() {
-            let visible = state.house(attacker).hand.clone();
-            state.pending = Some(PendingDecision::PatchfaceDiscard {
-                opponent: attacker,
-                visible_cards: visible,
-            });
-            check_victory(state);
-            return;
+    state.log_event(GameEventKind::CombatResolved {
+        area_id,
+        attacker,
+        defender: combat.defender,
+        winner: if outcome.attacker_wins { attacker } else { combat.defender },
+        attacker_card: combat.attacker_card,
+        defender_card: combat.defender_card,
+        attacker_strength: combat.attacker_strength,
+        defender_strength: combat.defender_strength,
+        attacker_support: outcome.attacker_support,
+        defender_support: outcome.defender_support,
+        casualties: outcome.casualties,
+    });
+    for (house, card, won) in played {
+        if let Some(card) = card {
+            record_card_play(state, house, card, won);
         }
+        refresh_hand(state, house, card);
+    }
 
-        // Doran for attacker losing
-        if atk_card_id == Some(HouseCardId::DoranMartell) {
-            state.pending = Some(PendingDecision::DoranChooseTrack { opponent: defender });
-            check_victory(state);
-            return;
+    if conquers {
+        let units = std::mem::take(&mut state.combat_mut().unwrap().attacking_units);
+        if outcome.no_conquest {
+            // Arianne: attackers return to where they marched from
+            let from = march_from_area.unwrap_or(area_id);
+            state.area_mut(from).units.extend(units);
+        } else {
+            state.area_mut(area_id).units.extend(units);
+            // The defeated house's order goes with its units
+            state.area_mut(area_id).order = None;
+            set_area_control(state, area_id, Some(attacker));
+            // Remove garrison when area changes hands
+            state.garrisons.remove(&area_id);
         }
     }
 
-    // ── Ser Loras Tyrell: winner can march again from the conquered area ──
+    // ── Ser Loras Tyrell: attacker can march again from the conquered area ──
     // Save march order before finalize_combat clears it
-    let loras_march_order = if (attacker_wins && atk_card_id == Some(HouseCardId::SerLorasTyrell))
-        || (!attacker_wins && def_card_id == Some(HouseCardId::SerLorasTyrell))
+    let loras_march_order = if conquers && !outcome.no_conquest
+        && card_ability(state.combat().unwrap().attacker_card) == CardAbility::MarchAgain
     {
         march_from_area.and_then(|from| state.area(from).order)
     } else {
         None
     };
-    let loras_target_area = if loras_march_order.is_some() {
-        Some(area_id) // Loras: march order goes on the combat area
-    } else {
-        None
-    };
 
     // Combat done
     finalize_combat(state);
 
     // Apply Ser Loras: place march order on area and give extra turn
-    if let (Some(order), Some(target)) = (loras_march_order, loras_target_area) {
-        state.area_mut(target).order = Some(order);
-        // Roll back action_player_index so the same player goes again
-        let pc = state.playing_houses.len() as u8;
-        state.action_player_index = (state.action_player_index + pc - 1) % pc;
+    if let Some(order) = loras_march_order {
+        if state.winner.is_none() {
+            state.area_mut(area_id).order = Some(order);
+            record_ability(state, attacker);
+            // Roll back the turn so the same player goes again
+            let pc = state.playing_houses.len() as u8;
+            if let Some(turn) = state.action_turn_mut() {
+                turn.player_index = (turn.player_index + pc - 1) % pc;
+            }
+        }
     }
 }
 
 fn finalize_combat(state: &mut GameState) {
     // Remove march order from origin
-    if let Some(combat) = &state.combat {
-        if let Some(from) = combat.march_from_area {
-            state.area_mut(from).order = None;
-        }
+    let origin = state.combat().and_then(|c| c.march_from_area.map(|from| (from, c.attacker)));
+    if let Some((from, _)) = origin {
+        state.area_mut(from).order = None;
+    }
+
+    if let EnginePhase::Combat { turn, .. } = state.engine_phase {
+        state.engine_phase = EnginePhase::Action(turn);
     }
 
-    state.combat = None;
-    state.phase = Phase::Action;
+    // A land area the march left empty is given up or held with a power
+    // token, as after a march without combat; the decision moves play on
+    if let Some((from, attacker)) = origin {
+        let area = state.area(from);
+        if area.units.is_empty() && area.house == Some(attacker) && AREAS[from.0 as usize].is_land() {
+            state.enqueue_pending(PendingDecision::LeavePowerToken { house: attacker, area_id: from });
+            check_victory(state);
+            return;
+        }
+    }
     // Advance to next player in action cycle
-    let pc = state.playing_houses.len() as u8;
-    state.action_player_index = (state.action_player_index + 1) % pc;
+    pass_turn(state);
     check_victory(state);
 }
 
@@ -1743,11 +2191,29 @@ fn finalize_combat(state: &mut GameState) {
 // APPLY ACTION — resolve player decisions
 // ═══════════════════════════════════════════════════════════════════════
 
-pub fn apply_action(state: &mut GameState, action: Action) {
-    let pending = state.pending.take();
-    if pending.is_none() { return; }
+/// Answer the pending decision with `action` and advance. An action
+/// `validate_action` refuses changes nothing and comes back as the error;
+/// the decision stays open.
+pub fn apply_action(state: &mut GameState, action: Action) -> Result<(), ActionError> {
+    if let Err(e) = validate_action(state, &action) {
+        // The game is over the moment a winner is declared
+        if e == ActionError::GameOver {
+            state.clear_pending();
+        }
+        return Err(e);
+    }
+    // Neither answers the decision: it stays open
+    if let Action::Resign | Action::OfferDraw = action {
+        let Some(house) = state.peek_pending().map(PendingDecision::house) else { return Err(ActionError::NoDecision) };
+        match action {
+            Action::Resign => resign(state, house),
+            _ => offer_draw(state, house),
+        }
+        return Ok(());
+    }
+    let Some(pending) = state.pop_pending() else { return Err(ActionError::NoDecision) };
 
-    match (pending.unwrap(), action) {
+    match (pending, action) {
         // ── Planning ──
         (PendingDecision::PlaceOrders { house }, Action::PlaceOrders(orders)) => {
             for (area_id, token_idx) in orders {
@@ -1781,7 +2247,7 @@ pub fn apply_action(state: &mut GameState, action: Action) {
             match card_name.as_str() {
                 "A Throne of Blades" => {
                     if choice == "Mustering" {
-                        state.muster_house_idx = 1;
+                        set_westeros_substate(state, WesterosSubstate::Mustering { house_idx: 0 });
                     } else {
                         resolve_supply_update(state);
                     }
@@ -1810,10 +2276,8 @@ pub fn apply_action(state: &mut GameState, action: Action) {
 
         // ── Bidding ──
         (PendingDecision::Bidding { house, .. }, Action::Bid(amount)) => {
-            let clamped = amount.min(state.house(house).power);
-            if let Some(bidding) = &mut state.bidding {
-                bidding.bids.insert(house, clamped);
-                bidding.next_bidder_idx += 1;
+            if let Some(bidding) = state.bidding_mut() {
+                bidding.sealed_bids.insert(house, amount);
             }
         }
 
@@ -1821,10 +2285,10 @@ pub fn apply_action(state: &mut GameState, action: Action) {
         (PendingDecision::Muster { house, areas: _ }, Action::Muster(actions)) => {
             for (area_id, muster_action) in actions {
                 match muster_action {
+                    // Land units on land, ships on water; `validate_action`
+                    // has refused anything else
                     MusterAction2::Build(unit_type) => {
-                        let pool = state.house(house).available_units.get(unit_type);
-                        if pool > 0 {
-                            *state.house_mut(house).available_units.get_mut(unit_type) -= 1;
+                        if navigation::can_enter(unit_type, area_id) && state.house_mut(house).take_unit(unit_type).is_ok() {
                             state.area_mut(area_id).units.push(Unit {
                                 unit_type,
                                 house,
@@ -1832,64 +2296,83 @@ pub fn apply_action(state: &mut GameState, action: Action) {
                             });
                         }
                     }
+                    MusterAction2::BuildShip(to) => {
+                        if navigation::can_enter(UnitType::Ship, to) && state.house_mut(house).take_unit(UnitType::Ship).is_ok() {
+                            state.area_mut(to).units.push(Unit {
+                                unit_type: UnitType::Ship,
+                                house,
+                                routed: false,
+                            });
+                            set_area_control(state, to, Some(house));
+                        }
+                    }
                     MusterAction2::Upgrade => {
                         // Find a footman and upgrade to knight
-                        let has_knight = state.house(house).available_units.knights > 0;
-                        let area = &mut state.areas[area_id.0 as usize];
-                        if let Some(pos) = area.units.iter().position(|u| {
+                        if let Some(pos) = state.area(area_id).units.iter().position(|u| {
                             u.house == house && u.unit_type == UnitType::Footman
                         }) {
-                            if has_knight {
-                                area.units[pos].unit_type = UnitType::Knight;
-                                state.house_mut(house).available_units.knights -= 1;
-                                state.house_mut(house).available_units.footmen += 1;
+                            if state.house_mut(house).swap_unit(UnitType::Footman, UnitType::Knight).is_ok() {
+                                state.area_mut(area_id).units[pos].unit_type = UnitType::Knight;
                             }
                         }
                     }
                 }
             }
-            // Advance muster_house_idx (for Westeros mustering)
-            if state.muster_house_idx > 0 {
-                state.muster_house_idx += 1;
+            // Move on to the next house (for Westeros mustering)
+            if let Some(idx) = mustering_house_idx(state) {
+                set_westeros_substate(state, WesterosSubstate::Mustering { house_idx: idx + 1 });
             }
         }
 
+        // ── Action phase: which order to resolve this turn ──
+        (PendingDecision::ChooseOrderToResolve { house, candidates, .. }, Action::ResolveOrder(area_id)) => {
+            let area_id = if candidates.contains(&area_id) { area_id } else { candidates[0] };
+            begin_order_resolution(state, house, area_id);
+        }
+
         // ── Raids ──
-        (PendingDecision::ChooseRaid { house: _, from_area, .. }, Action::Raid(target)) => {
-            if let Some(target_id) = target {
-                // If raiding a CP order, steal a power token
-                let target_order = state.area(target_id).order;
-                if let Some(order) = target_order {
-                    if order.order_type == OrderType::ConsolidatePower {
-                        if let Some(target_house) = state.area(target_id).house {
-                            if state.house(target_house).power > 0 {
-                                let raider = state.area(from_area).house.unwrap_or(HouseName::Stark);
-                                state.house_mut(target_house).power -= 1;
-                                state.house_mut(raider).power += 1;
-                            }
-                        }
+        (PendingDecision::ChooseRaid { house, from_area, valid_targets }, Action::Raid(target)) => {
+            if let Some(target_id) = target.filter(|t| valid_targets.contains(t)) {
+                // The raided order is removed. Only a Consolidate Power order
+                // pays out: one token moves from its owner to the raider, if
+                // the owner has one. Support, raid and defense orders give
+                // nothing.
+                if let Some(order) = state.area_mut(target_id).order.take() {
+                    if order.order_type == OrderType::ConsolidatePower && order.house != house {
+                        let stolen = spend_power(state, order.house, 1);
+                        gain_power(state, house, stolen);
                     }
                 }
-                // Remove target's order
-                state.area_mut(target_id).order = None;
             }
             // Remove own raid order
             state.area_mut(from_area).order = None;
-            let pc = state.playing_houses.len() as u8;
-            state.action_player_index = (state.action_player_index + 1) % pc;
+            pass_turn(state);
         }
 
         // ── Marches ──
+        // Units that aren't the house's to move, terrain they can't enter,
+        // or out of reach: the march is refused and the order spent, as if
+        // skipped
+        (PendingDecision::ChooseMarch { house, from_area, .. }, Action::March { to, unit_indices })
+            if !march_is_legal(state, house, from_area, to, &unit_indices) => {
+            state.area_mut(from_area).order = None;
+            pass_turn(state);
+        }
         (PendingDecision::ChooseMarch { house, from_area, .. }, Action::March { to, unit_indices }) => {
-            // Collect units to move
-            let moving_units: Vec<Unit> = unit_indices.iter()
+            // Collect units to move; a repeated index must not copy a unit
+            let mut indices: Vec<usize> = Vec::with_capacity(unit_indices.len());
+            for &i in &unit_indices {
+                if !indices.contains(&i) {
+                    indices.push(i);
+                }
+            }
+            let moving_units: Vec<Unit> = indices.iter()
                 .filter_map(|&i| state.area(from_area).units.get(i).copied())
                 .collect();
 
             // Remove from source (reverse order)
-            let mut sorted_indices = unit_indices.clone();
-            sorted_indices.sort_unstable_by(|a, b| b.cmp(a));
-            for &i in &sorted_indices {
+            indices.sort_unstable();
+            for &i in indices.iter().rev() {
                 if i < state.area(from_area).units.len() {
                     state.area_mut(from_area).units.remove(i);
                 }
@@ -1905,61 +2388,73 @@ pub fn apply_action(state: &mut GameState, action: Action) {
             let has_garrison = state.garrisons.get(&to)
                 .is_some_and(|g| g.house.is_some_and(|h| h != house));
 
-            if has_enemy_units || (target_house.is_some() && target_house != Some(house) && has_garrison) {
+            // Neutral force token: no cards, the attack must match its strength
+            let neutral_strength = state.garrisons.get(&to)
+                .filter(|g| g.house.is_none() && target_house.is_none())
+                .map(|g| g.strength as i16);
+            let repelled = neutral_strength
+                .is_some_and(|req| neutral_attack_strength(state, house, from_area, to, &moving_units) < req);
+
+            if repelled {
+                // Too weak to remove the token: the units stay where they were
+                state.area_mut(from_area).units.extend(moving_units);
+                state.area_mut(from_area).order = None;
+                pass_turn(state);
+            } else if has_enemy_units || (target_house.is_some() && target_house != Some(house) && has_garrison) {
                 // Combat!
                 begin_combat(state, house, target_house.unwrap(), to, moving_units, from_area);
             } else {
+                if neutral_strength.is_some() {
+                    // Neutral force defeated
+                    state.garrisons.remove(&to);
+                }
                 // No combat — place units
                 for unit in moving_units {
                     state.area_mut(to).units.push(unit);
                 }
-                if state.area(to).house.is_none() || state.area(to).house == Some(house) {
-                    state.area_mut(to).house = Some(house);
-                }
+                // An enemy power token alone does not fight: it is removed
+                set_area_control(state, to, Some(house));
 
                 // Update source area
                 if state.area(from_area).units.is_empty() {
                     if AREAS[from_area.0 as usize].is_land() {
-                        state.pending = Some(PendingDecision::LeavePowerToken {
+                        state.enqueue_pending(PendingDecision::LeavePowerToken {
                             house,
                             area_id: from_area,
                         });
                         // Don't advance yet — wait for leave token decision
-                        return;
+                        return Ok(());
                     }
-                    state.area_mut(from_area).house = None;
+                    set_area_control(state, from_area, None);
                 }
 
                 state.area_mut(from_area).order = None;
-                let pc = state.playing_houses.len() as u8;
-                state.action_player_index = (state.action_player_index + 1) % pc;
+                pass_turn(state);
                 check_victory(state);
             }
         }
 
         (PendingDecision::ChooseMarch { from_area, .. }, Action::MarchSkip) => {
             state.area_mut(from_area).order = None;
-            let pc = state.playing_houses.len() as u8;
-            state.action_player_index = (state.action_player_index + 1) % pc;
+            pass_turn(state);
         }
 
         // ── Leave Power Token ──
         (PendingDecision::LeavePowerToken { house, area_id }, Action::LeavePowerToken(leave)) => {
             if leave && state.house(house).power > 0 {
-                state.house_mut(house).power -= 1;
+                spend_power(state, house, 1);
                 // Keep control via power token
             } else {
-                state.area_mut(area_id).house = None;
+                set_area_control(state, area_id, None);
             }
             // Remove march order and advance
             state.area_mut(area_id).order = None;
-            let pc = state.playing_houses.len() as u8;
-            state.action_player_index = (state.action_player_index + 1) % pc;
+            pass_turn(state);
         }
 
         // ── Combat: Card Selection ──
         (PendingDecision::SelectHouseCard { house, .. }, Action::SelectCard(card_id)) => {
-            if let Some(combat) = &mut state.combat {
+            if let Some(combat) = state.combat_mut() {
                 if house == combat.attacker {
                     combat.attacker_card = Some(card_id);
                 } else {
@@ -1976,67 +2471,54 @@ pub fn apply_action(state: &mut GameState, action: Action) {
 
         // ── Combat: Support ──
         (PendingDecision::SupportDeclaration { area_id, .. }, Action::DeclareSupport(choice)) => {
-            if let Some(combat) = &mut state.combat {
+            if let Some(combat) = state.combat_mut() {
                 combat.support_decisions.insert(area_id, choice);
                 combat.pending_support_houses.retain(|&(a, _)| a != area_id);
             }
         }
 
         // ── Combat: Valyrian Steel Blade ──
-        (PendingDecision::UseValyrianBlade { house }, Action::UseValyrianBlade(use_it)) => {
-            if use_it {
-                state.valyrian_steel_blade_used = true;
-                if let Some(combat) = &mut state.combat {
-                    if house == combat.attacker {
-                        combat.attacker_used_blade = true;
-                    } else {
-                        combat.defender_used_blade = true;
-                    }
+        (PendingDecision::UseValyrianBlade { house }, Action::UseValyrianBlade(true)) => {
+            state.valyrian_steel_blade_used = true;
+            if let Some(combat) = state.combat_mut() {
+                if house == combat.attacker {
+                    combat.attacker_used_blade = true;
+                } else {
+                    combat.defender_used_blade = true;
                 }
             }
         }
+        (PendingDecision::UseValyrianBlade { .. }, Action::UseValyrianBlade(false)) => {}
 
         // ── Combat: Retreat ──
         (PendingDecision::Retreat { house, from_area, .. }, Action::Retreat(to)) => {
-            let units: Vec<Unit> = state.area(from_area).units.iter()
-                .filter(|u| u.house == house)
-                .cloned()
-                .collect();
-            state.area_mut(from_area).units.retain(|u| u.house != house);
-            for mut unit in units {
-                unit.routed = true;
-                state.area_mut(to).units.push(unit);
-            }
-            if state.area(to).house.is_none() {
-                state.area_mut(to).house = Some(house);
-            }
-            finalize_combat(state);
+            retreat(state, house, from_area, to);
         }
 
         // ── Combat: Tyrion replacement ──
-        (PendingDecision::TyrionReplace { opponent }, Action::TyrionReplace(new_card)) => {
-            // Opponent picks a new card
-            if let Some(combat) = &mut state.combat {
-                if opponent == combat.attacker {
+        (PendingDecision::TyrionReplace { decider, .. }, Action::TyrionReplace(new_card)) => {
+            // The cancelled side picks a new card
+            if let Some(combat) = state.combat_mut() {
+                if decider == combat.attacker {
                     combat.attacker_card = Some(new_card);
                 } else {
                     combat.defender_card = Some(new_card);
                 }
             }
-            let hand = &mut state.house_mut(opponent).hand;
+            let hand = &mut state.house_mut(decider).hand;
             if let Some(pos) = hand.iter().position(|&c| c == new_card) {
                 hand.remove(pos);
             }
-            state.house_mut(opponent).discards.push(new_card);
+            state.house_mut(decider).discards.push(new_card);
         }
 
         // ── Combat: Aeron swap ──
         (PendingDecision::AeronSwap { .. }, Action::AeronSwap(None)) => {}
-        (PendingDecision::AeronSwap { house }, Action::AeronSwap(Some(new_id))) => {
-                // Pay 2 power
-                state.house_mut(house).power = state.house(house).power.saturating_sub(2);
+        (PendingDecision::AeronSwap { house, power_cost, .. }, Action::AeronSwap(Some(new_id))) => {
+                spend_power(state, house, power_cost);
+                record_ability(state, house);
                 // Return old card to hand, play new one
-                let old_card = if let Some(combat) = &state.combat {
+                let old_card = if let Some(combat) = state.combat() {
                     if house == combat.attacker { combat.attacker_card } else { combat.defender_card }
                 } else { None };
 
@@ -2049,7 +2531,7 @@ pub fn apply_action(state: &mut GameState, action: Action) {
                 }
 
                 // Play new card
-                if let Some(combat) = &mut state.combat {
+                if let Some(combat) = state.combat_mut() {
                     if house == combat.attacker {
                         combat.attacker_card = Some(new_id);
                     } else {
@@ -2064,31 +2546,13 @@ pub fn apply_action(state: &mut GameState, action: Action) {
         }
 
         // ── Combat: Robb retreat choice ──
-        (PendingDecision::RobbRetreat { .. }, Action::RobbRetreat(to)) => {
-            // Move defender's units to chosen area
-            if let Some(combat) = &state.combat {
-                let defender = combat.defender;
-                let area_id = combat.area_id;
-                let units: Vec<Unit> = state.area(area_id).units.iter()
-                    .filter(|u| u.house == defender)
-                    .cloned()
-                    .collect();
-                state.area_mut(area_id).units.retain(|u| u.house != defender);
-                for mut unit in units {
-                    unit.routed = true;
-                    state.area_mut(to).units.push(unit);
-                }
-                if state.area(to).house.is_none() {
-                    state.area_mut(to).house = Some(defender);
-                }
-            }
-            finalize_combat(state);
+        (PendingDecision::RobbRetreat { defender, from_area, .. }, Action::RobbRetreat(to)) => {
+            retreat(state, defender, from_area, to);
         }
 
-        // ── Post-combat: Cersei ──
+        // ── Order removal (Cersei, Queen of Thorns) ──
         (PendingDecision::CerseiRemoveOrder { .. }, Action::CerseiRemoveOrder(area_id)) => {
             state.area_mut(area_id).order = None;
-            finalize_combat(state);
         }
 
         // ── Post-combat: Patchface ──
@@ -2098,71 +2562,32 @@ pub fn apply_action(state: &mut GameState, action: Action) {
                 hand.remove(pos);
             }
             state.house_mut(opponent).discards.push(card_id);
-            finalize_combat(state);
         }
 
-        // ── Post-combat: Doran ──
-        (PendingDecision::DoranChooseTrack { opponent }, Action::DoranChooseTrack(track)) => {
+        // ── Pre-combat: Doran ──
+        (PendingDecision::DoranChooseTrack { opponent, .. }, Action::DoranChooseTrack(track)) => {
             // Move opponent to last position on chosen track
-            let pc = state.playing_houses.len() as u8;
-            match track {
-                Track::IronThrone => {
-                    let old_pos = state.house(opponent).iron_throne;
-                    // Everyone below moves up
-                    let houses = state.playing_houses.clone();
-                    for &h in &houses {
-                        if state.house(h).iron_throne > old_pos {
-                            state.house_mut(h).iron_throne -= 1;
-                        }
-                    }
-                    state.house_mut(opponent).iron_throne = pc;
-                    // Update turn order
-                    let mut new_order = state.playing_houses.clone();
-                    new_order.sort_by_key(|&h| state.house(h).iron_throne);
-                    state.turn_order = new_order;
-                }
-                Track::Fiefdoms => {
-                    let old_pos = state.house(opponent).fiefdoms;
-                    let houses = state.playing_houses.clone();
-                    for &h in &houses {
-                        if state.house(h).fiefdoms > old_pos {
-                            state.house_mut(h).fiefdoms -= 1;
-                        }
-                    }
-                    state.house_mut(opponent).fiefdoms = pc;
-                }
-                Track::KingsCourt => {
-                    let old_pos = state.house(opponent).kings_court;
-                    let houses = state.playing_houses.clone();
-                    for &h in &houses {
-                        if state.house(h).kings_court > old_pos {
-                            state.house_mut(h).kings_court -= 1;
-                        }
-                    }
-                    state.house_mut(opponent).kings_court = pc;
-                }
-            }
-            finalize_combat(state);
+            tracks::move_to_bottom(state, opponent, track);
         }
 
-        // ── Queen of Thorns ──
+        // A removed support order no longer supports the battle
         (PendingDecision::QueenOfThornsRemoveOrder { .. }, Action::QueenOfThorns(area_id)) => {
             state.area_mut(area_id).order = None;
-            finalize_combat(state);
+            if let Some(combat) = state.combat_mut() {
+                combat.support_decisions.remove(&area_id);
+            }
         }
 
         // ── Reconcile ──
-        (PendingDecision::Reconcile { house, .. }, Action::Reconcile(aid, unit_idx)) => {
-            if unit_idx < state.area(aid).units.len() {
-                let unit = state.area_mut(aid).units.remove(unit_idx);
-                *state.house_mut(house).available_units.get_mut(unit.unit_type) += 1;
-            }
+        (PendingDecision::Reconcile { .. }, Action::Reconcile(aid, unit_idx)) => {
+            let unit = state.area_mut(aid).units.remove(unit_idx);
+            return_to_pool(state, &unit);
             // Check ALL houses for remaining violations (current first, then others)
             for &h in &state.playing_houses.clone() {
                 if supply::check_supply_violation(state, h) {
                     let violations = supply::find_violations(state, h);
                     if let Some(&(vid, curr, max)) = violations.first() {
-                        state.pending = Some(PendingDecision::Reconcile {
+                        state.enqueue_pending(PendingDecision::Reconcile {
                             house: h, area_id: vid, current_size: curr, max_allowed: max,
                         });
                         break;
@@ -2176,71 +2601,238 @@ pub fn apply_action(state: &mut GameState, action: Action) {
             // Simplified: penalty already applied in resolve_wildling_bidding
         }
 
-        _ => {
-            // Unhandled — clear pending and continue
+        // `check_answer` lets no mismatch through; should one slip by, the
+        // decision stays open rather than being dropped half-resolved
+        (decision @ (any_decision!()), action @ (any_answer!() | Action::Resign | Action::OfferDraw)) => {
+            let pending = DecisionKind::of(&decision);
+            state.restore_pending(decision);
+            let action = DecisionKind::answered_by(&action).unwrap_or(pending);
+            return Err(ActionError::WrongDecisionType { pending, action });
         }
     }
 
+    sync_sea_control(state);
+
     // After applying action, try to advance
-    if state.pending.is_none() {
+    if !state.has_pending() {
         advance(state);
     }
+    Ok(())
+}
+
+/// `apply_action` on behalf of `house`, refused unless the pending
+/// decision is `house`'s.
+pub fn apply_action_as(state: &mut GameState, house: HouseName, action: Action) -> Result<(), ActionError> {
+    // Sealed bids and orders are all open at once and may come in any order
+    let kind = DecisionKind::answered_by(&action);
+    let answers = |p: &PendingDecision| p.house() == house && Some(DecisionKind::of(p)) == kind;
+    if open_decisions(state).iter().any(answers) {
+        state.promote_pending(answers);
+    }
+    if let Some(to_move) = state.peek_pending().map(PendingDecision::house) {
+        if to_move != house && state.winner.is_none() {
+            return Err(ActionError::NotYourTurn { house, to_move });
+        }
+    }
+    apply_action(state, action)
 }
 
 // ═══════════════════════════════════════════════════════════════════════
 // HELPER FUNCTIONS
 // ═══════════════════════════════════════════════════════════════════════
 
-fn find_raid_targets(state: &GameState, from: AreaId, house: HouseName) -> Vec<AreaId> {
-    let from_def = &AREAS[from.0 as usize];
-    let is_star = state.area(from).order.is_some_and(|o| o.star);
-
-    from_def.adjacent.iter()
-        .filter(|&&adj| {
-            let area = state.area(adj);
-            if area.house == Some(house) || area.house.is_none() {
-                return false;
-            }
-            if let Some(order) = &area.order {
-                match order.order_type {
-                    OrderType::Raid | OrderType::Support | OrderType::ConsolidatePower => true,
-                    OrderType::Defense if is_star => true,
-                    _ => false,
-                }
+/// Strength of a march against a neutral force token: units, march order
+/// and the marching house's own adjacent support. House cards are not used.
+fn neutral_attack_strength(state: &GameState, house: HouseName, from: AreaId,
+                           to: AreaId, units: &[Unit]) -> i16 {
+    let to_def = &AREAS[to.0 as usize];
+    let unit_str: i16 = units.iter()
+        .map(|u| {
+            if u.unit_type == UnitType::SiegeEngine && to_def.has_castle_or_stronghold() {
+                4
             } else {
-                false
+                u.unit_type.combat_strength() as i16
             }
         })
+        .sum();
+    let march_bonus = state.area(from).order.map_or(0, |o| o.strength as i16);
+    let support: i16 = to_def.adjacent.iter()
+        .map(|&adj| state.area(adj))
+        .filter(|a| a.house == Some(house))
+        .filter_map(|a| a.order.filter(|o| o.order_type == OrderType::Support).map(|o| (a, o)))
+        .map(|(a, o)| {
+            o.strength as i16 + a.units.iter().map(|u| u.unit_type.combat_strength() as i16).sum::<i16>()
+        })
+        .sum();
+    unit_str + march_bonus + support
+}
+
+/// Areas the raid order in `from` may remove an order from: adjacent
+/// areas holding another house's order of a kind `raid_removes` allows,
+/// within reach of where the raid stands:
+///
+///   raid on land  → land
+///   raid at sea   → land, sea, port
+///   raid in port  → sea
+///
+/// Nothing when `from` holds no raid order of `house`.
+pub fn find_raid_targets(state: &GameState, from: AreaId, house: HouseName) -> Vec<AreaId> {
+    let Some(raid) = state.area(from).order.filter(|o| o.order_type == OrderType::Raid && o.house == house) else {
+        return Vec::new();
+    };
+    let from_type = AREAS[from.0 as usize].area_type;
+    AREAS[from.0 as usize].adjacent.iter()
+        .filter(|&&adj| raid_reaches(from_type, AREAS[adj.0 as usize].area_type))
+        .filter(|&&adj| state.area(adj).order.is_some_and(|o| o.house != house && raid_removes(raid.star, o.order_type)))
         .copied()
         .collect()
 }
 
-fn find_home_area(_state: &GameState, house: HouseName) -> Option<AreaId> {
-    match house {
-        HouseName::Stark     => Some(WINTERFELL),
-        HouseName::Lannister => Some(LANNISPORT),
-        HouseName::Baratheon => Some(DRAGONSTONE),
-        HouseName::Greyjoy   => Some(PYKE),
-        HouseName::Tyrell    => Some(HIGHGARDEN),
-        HouseName::Martell   => Some(SUNSPEAR),
+/// Whether a raid in a `from` area may target an adjacent `to` area.
+pub fn raid_reaches(from: AreaType, to: AreaType) -> bool {
+    match from {
+        AreaType::Land => to == AreaType::Land,
+        AreaType::Sea => true,
+        AreaType::Port => to == AreaType::Sea,
+    }
+}
+
+/// Whether a raid (starred or not) may remove an order of `target` type:
+/// support, raid and consolidate power always, defense only for a starred
+/// raid, march never.
+pub fn raid_removes(star: bool, target: OrderType) -> bool {
+    match target {
+        OrderType::Raid | OrderType::Support | OrderType::ConsolidatePower => true,
+        OrderType::Defense => star,
+        OrderType::March => false,
     }
 }
 
-fn find_retreat_areas(state: &GameState, from: AreaId, house: HouseName) -> Vec<AreaId> {
+/// Whether the units at `unit_indices` in `from` may march to `to`: each
+/// one is the house's own and not routed, and all of them can make the
+/// move. An empty selection moves nothing and is always allowed.
+fn march_is_legal(state: &GameState, house: HouseName, from: AreaId, to: AreaId, unit_indices: &[usize]) -> bool {
+    if unit_indices.is_empty() {
+        return true;
+    }
+    let units = &state.area(from).units;
+    let selected: Option<Vec<UnitType>> = unit_indices.iter()
+        .map(|&i| units.get(i).filter(|u| u.house == house && !u.routed).map(|u| u.unit_type))
+        .collect();
+    selected.is_some_and(|types| navigation::is_march_valid(state, from, to, house, &types))
+}
+
+/// Areas that may support a battle in `area_id`. Anything adjacent can
+/// support a land battle; a sea battle is fought by ships alone, so only
+/// the adjacent seas and the sea's own ports can join it.
+fn support_areas(area_id: AreaId) -> Vec<AreaId> {
+    let area_def = &AREAS[area_id.0 as usize];
+    if area_def.is_land() {
+        return area_def.adjacent.to_vec();
+    }
+    area_def.adjacent.iter().copied()
+        .filter(|&adj| AREAS[adj.0 as usize].is_sea())
+        .chain(ports_of(area_id))
+        .collect()
+}
+
+/// The ports that open onto `sea`.
+fn ports_of(sea: AreaId) -> impl Iterator<Item = AreaId> {
+    AREAS.iter().filter(move |d| d.is_port() && d.connected_sea == Some(sea)).map(|d| d.id)
+}
+
+/// The port of the castle in `land`, if it has one.
+pub fn port_of(land: AreaId) -> Option<AreaId> {
+    AREAS.iter().find(|d| d.is_port() && d.connected_land == Some(land)).map(|d| d.id)
+}
+
+/// Whether enemy ships hold the sea `port` opens onto.
+pub fn is_blockaded(state: &GameState, port: AreaId, house: HouseName) -> bool {
+    AREAS[port.0 as usize].connected_sea.is_some_and(|sea| state.area(sea).units.iter().any(|u| u.house != house))
+}
+
+/// Where a ship mustered at the castle in `castle` may go: an adjacent
+/// sea free of enemy ships, or the castle's own port while it has room.
+/// `chosen` are ships already mustered this turn, by destination.
+pub fn ship_muster_areas(board: &impl Board, house: HouseName, castle: AreaId, chosen: &[AreaId]) -> Vec<AreaId> {
+    let pending = |to: AreaId| chosen.iter().filter(|&&a| a == to).count();
+    AREAS[castle.0 as usize].adjacent.iter().copied()
+        .filter(|&sea| AREAS[sea.0 as usize].is_sea() && !board.blocked(sea))
+        .filter(|&sea| board.units(sea).iter().all(|u| u.house == house))
+        .chain(port_of(castle).filter(|&port| navigation::port_has_room(board, port, house, pending(port) + 1)))
+        .collect()
+}
+
+/// A house gaining control of a castle takes the enemy ships in its port:
+/// each is replaced by one of the house's own while its pool and supply
+/// allow, and the rest are destroyed.
+fn seize_port(state: &mut GameState, land: AreaId, house: HouseName) {
+    let Some(port) = port_of(land) else { return };
+    let enemy: Vec<Unit> = state.area(port).units.iter().filter(|u| u.house != house).cloned().collect();
+    let Some(from) = enemy.first().map(|u| u.house) else { return };
+    state.area_mut(port).units.retain(|u| u.house == house);
+    for unit in &enemy {
+        return_to_pool(state, unit);
+    }
+    let mut captured = 0;
+    for _ in &enemy {
+        if state.house(house).available_units.ships == 0 || !supply::allows_units(state, house, &[port]) {
+            break;
+        }
+        state.house_mut(house).take_unit(UnitType::Ship).expect("the pool has a ship");
+        state.area_mut(port).units.push(Unit { unit_type: UnitType::Ship, house, routed: false });
+        captured += 1;
+    }
+    let owner = state.area(port).units.first().map(|u| u.house);
+    set_area_control(state, port, owner);
+    state.log_event(GameEventKind::PortSeized { port, from, to: house, captured, destroyed: enemy.len() as u8 - captured });
+}
+
+fn find_home_area(_state: &GameState, house: HouseName) -> Option<AreaId> {
+    Some(crate::map::analysis::home_area(house))
+}
+
+/// Where `house`'s beaten units in `from` may retreat; never into
+/// `barred`, the area the attack came from.
+fn find_retreat_areas(state: &GameState, from: AreaId, house: HouseName, barred: AreaId) -> Vec<AreaId> {
     let from_def = &AREAS[from.0 as usize];
-    from_def.adjacent.iter()
-        .filter(|&&adj| {
-            let def = &AREAS[adj.0 as usize];
-            let area = state.area(adj);
-            def.is_land()
-                && !area.blocked
-                && (area.house.is_none() || area.house == Some(house))
-                && area.units.iter().all(|u| u.house == house)
-        })
-        .copied()
+    let open = |adj: AreaId| {
+        let area = state.area(adj);
+        adj != barred
+            && !area.blocked
+            && (area.house.is_none() || area.house == Some(house))
+            && area.units.iter().all(|u| u.house == house)
+    };
+    if from_def.is_land() {
+        return from_def.adjacent.iter().copied()
+            .filter(|&adj| AREAS[adj.0 as usize].is_land() && open(adj))
+            .collect();
+    }
+    // A beaten fleet retreats over water: to an adjacent sea, or into a
+    // port of this sea whose castle the house holds, if the port has room
+    let fleet = state.area(from).units.iter().filter(|u| u.house == house).count();
+    from_def.adjacent.iter().copied()
+        .filter(|&adj| AREAS[adj.0 as usize].is_sea() && open(adj))
+        .chain(ports_of(from).filter(|&p| open(p) && navigation::port_has_room(state, p, house, fleet)))
         .collect()
 }
 
+/// `house`'s units in `from` retreat, routed, to `to`, taking it if empty.
+fn retreat(state: &mut GameState, house: HouseName, from: AreaId, to: AreaId) {
+    let units: Vec<Unit> = state.area(from).units.iter()
+        .filter(|u| u.house == house)
+        .copied()
+        .collect();
+    state.area_mut(from).units.retain(|u| u.house != house);
+    for mut unit in units {
+        unit.routed = true;
+        state.area_mut(to).units.push(unit);
+    }
+    if state.area(to).house.is_none() {
+        set_area_control(state, to, Some(house));
+    }
+}
+
 fn cleanup_round(state: &mut GameState) {
     // Remove all remaining orders
     for area in &mut state.areas {
@@ -2261,59 +2853,571 @@ fn cleanup_round(state: &mut GameState) {
     }
     state.valyrian_steel_blade_used = false;
     state.messenger_raven_used = false;
+    state.draw_offers.clear();
     state.order_restrictions.clear();
     state.star_order_restrictions.clear();
 
+    check_hill(state);
+    if state.winner.is_some() {
+        return;
+    }
+
     // Advance round
     state.round += 1;
 
-    if state.round > 10 {
+    if state.round > state.options.victory.last_round() {
         resolve_tiebreaker(state);
         return;
     }
 
     // Rounds 2+: Westeros phase
-    state.phase = Phase::Westeros;
-    state.westeros_step = 0;
+    state.engine_phase = EnginePhase::westeros();
 }
 
-fn check_victory(state: &mut GameState) {
-    for &h in &state.playing_houses {
-        let castles = state.areas.iter().enumerate()
-            .filter(|(i, a)| {
-                a.house == Some(h) && AREAS[*i].has_castle_or_stronghold()
-            })
-            .count();
-        if castles >= 7 {
-            state.winner = Some(h);
+/// Add power tokens to a house's pool, recording the gain.
+pub(crate) fn gain_power(state: &mut GameState, house: HouseName, amount: u8) {
+    if amount == 0 {
+        return;
+    }
+    state.house_mut(house).power += amount;
+    state.log_event(GameEventKind::PowerGained { house, amount });
+}
+
+/// Remove up to `amount` power tokens from a house's pool, recording what
+/// was actually spent. Returns the amount removed.
+pub(crate) fn spend_power(state: &mut GameState, house: HouseName, amount: u8) -> u8 {
+    let spent = amount.min(state.house(house).power);
+    if spent > 0 {
+        state.house_mut(house).power -= spent;
+        state.log_event(GameEventKind::PowerSpent { house, amount: spent });
+    }
+    spent
+}
+
+/// Put a unit that left the board back in its owner's pool. A refused
+/// return leaves the house a unit short, which `check_unit_pools` reports.
+fn return_to_pool(state: &mut GameState, unit: &Unit) {
+    let _ = state.house_mut(unit.house).return_unit(unit.unit_type);
+}
+
+/// Seas and ports take no control tokens: a house holds one exactly while
+/// its ships are there. Hand every one to the owner of its ships, or to no
+/// one once they have left or been destroyed.
+pub(crate) fn sync_sea_control(state: &mut GameState) {
+    for (i, def) in AREAS.iter().enumerate() {
+        if def.is_land() {
+            continue;
+        }
+        let owner = state.areas[i].units.first().map(|u| u.house);
+        if state.areas[i].house != owner {
+            set_area_control(state, AreaId(i as u8), owner);
+        }
+    }
+}
+
+/// Change who controls an area, recording the change in the event log.
+pub(crate) fn set_area_control(state: &mut GameState, area_id: AreaId, house: Option<HouseName>) {
+    let from = state.area(area_id).house;
+    if from == house {
+        return;
+    }
+    state.area_mut(area_id).house = house;
+    let castles_after = house.map_or(0, |h| state.castle_count(h));
+    state.log_event(GameEventKind::ControlChanged { area_id, from, to: house, castles_after });
+    if let Some(h) = house.filter(|_| AREAS[area_id.0 as usize].is_land()) {
+        seize_port(state, area_id, h);
+    }
+}
+
+/// End the game as soon as a house holds 7 castles/strongholds.
+///
+/// If several houses are at 7+ when this runs (e.g. a conquest and a
+/// retreat resolved in the same step), the house whose control change took
+/// it to 7 earliest in the event log wins. Declaring a winner terminates the
+/// game immediately: any outstanding decision is dropped.
+pub(crate) fn check_victory(state: &mut GameState) {
+    if state.winner.is_some() {
+        state.clear_pending();
+        return;
+    }
+    match state.options.victory {
+        VictoryCondition::Castles => {}
+        VictoryCondition::SupplyDomination { supply } => {
+            let reached = final_ranking(state).into_iter()
+                .find(|&h| !state.resigned.contains(&h) && state.house(h).supply >= supply);
+            if let Some(winner) = reached {
+                declare_winner(state, winner);
+            }
             return;
         }
+        VictoryCondition::MostCastlesAfter { .. } | VictoryCondition::KingOfTheHill { .. } => return,
+    }
+    let reached: Vec<HouseName> = state.playing_houses.iter()
+        .copied()
+        .filter(|&h| state.castle_count(h) >= state.options.castles_to_win(h))
+        .collect();
+    if reached.is_empty() {
+        return;
+    }
+
+    let reached_at = |h: HouseName| {
+        state.events.iter()
+            .rev()
+            .find(|e| matches!(e.kind,
+                GameEventKind::ControlChanged { to: Some(t), castles_after, .. }
+                    if t == h && castles_after >= state.options.castles_to_win(h)))
+            .map_or(u32::MAX, |e| e.seq)
+    };
+    let winner = reached.iter().copied().min_by_key(|&h| reached_at(h)).unwrap();
+    declare_winner(state, winner);
+}
+
+fn declare_winner(state: &mut GameState, house: HouseName) {
+    let castles = state.castle_count(house);
+    state.winner = Some(house);
+    state.clear_pending();
+    state.log_event(GameEventKind::GameWon { house, castles });
+}
+
+/// Under `VictoryCondition::KingOfTheHill`, count another round end for
+/// whoever holds the hill and declare them the winner once they have
+/// held it long enough.
+pub(crate) fn check_hill(state: &mut GameState) {
+    let VictoryCondition::KingOfTheHill { area, rounds } = state.options.victory else { return };
+    if state.winner.is_some() {
+        return;
+    }
+    state.hill_holder = match (state.area(area).house, state.hill_holder) {
+        (Some(h), Some((held_by, n))) if h == held_by => Some((h, n + 1)),
+        (Some(h), _) => Some((h, 1)),
+        (None, _) => None,
+    };
+    if let Some((house, held)) = state.hill_holder {
+        if held >= rounds {
+            declare_winner(state, house);
+        }
     }
 }
 
 fn resolve_tiebreaker(state: &mut GameState) {
-    // Strongholds (2 pts) > Castles (1 pt), then supply, then power, then Iron Throne
-    let mut rankings: Vec<(HouseName, usize, u8, u8, u8)> = state.playing_houses.iter()
-        .map(|&h| {
-            let score: usize = state.areas.iter().enumerate()
-                .filter(|(_, a)| a.house == Some(h))
-                .map(|(i, _)| {
-                    if AREAS[i].stronghold { 2 }
-                    else if AREAS[i].castle { 1 }
-                    else { 0 }
-                })
-                .sum();
-            let profile = state.house(h);
-            (h, score, profile.supply, profile.power, profile.iron_throne)
-        })
-        .collect();
+    let ranking = final_ranking(state);
+    declare_winner(state, ranking[0]);
+}
+
+/// Tiebreaker score of a house: areas with a castle or stronghold (each
+/// counts once), supply, power, Iron Throne position. Compare with `tiebreak_cmp`.
+pub fn final_score(state: &GameState, house: HouseName) -> (usize, u8, u8, u8) {
+    let profile = state.house(house);
+    (state.castle_count(house) as usize, profile.supply, profile.power, profile.iron_throne)
+}
 
+fn tiebreak_cmp(a: &(usize, u8, u8, u8), b: &(usize, u8, u8, u8)) -> std::cmp::Ordering {
+    b.0.cmp(&a.0)                       // Most castle/stronghold areas
+        .then(b.1.cmp(&a.1))             // Highest supply
+        .then(b.2.cmp(&a.2))             // Most power
+        .then(a.3.cmp(&b.3))             // Best Iron Throne (lower = better)
+}
+
+/// Full finishing order, best first. A declared winner is always first
+/// and resigned houses last, the first to resign at the bottom; everyone
+/// else is ordered by the official tiebreaker.
+pub fn final_ranking(state: &GameState) -> Vec<HouseName> {
+    let mut rankings: Vec<(HouseName, (usize, u8, u8, u8))> = state.playing_houses.iter()
+        .map(|&h| (h, final_score(state, h)))
+        .collect();
+    let resigned_at = |h: HouseName| state.resigned.iter().position(|&r| r == h).map_or(0, |i| state.resigned.len() - i);
     rankings.sort_by(|a, b| {
-        b.1.cmp(&a.1)                       // Most castle/stronghold points
-            .then(b.2.cmp(&a.2))             // Highest supply
-            .then(b.3.cmp(&a.3))             // Most power
-            .then(a.4.cmp(&b.4))             // Best Iron Throne (lower = better)
+        let winner_first = (Some(b.0) == state.winner).cmp(&(Some(a.0) == state.winner));
+        winner_first
+            .then_with(|| resigned_at(a.0).cmp(&resigned_at(b.0)))
+            .then_with(|| tiebreak_cmp(&a.1, &b.1))
     });
+    rankings.into_iter().map(|(h, _)| h).collect()
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// RESIGNATION AND DRAWS
+// ═══════════════════════════════════════════════════════════════════════
+
+/// Take `house` out of the game. Its orders, units, garrisons and power
+/// tokens leave the board, leaving neutral force tokens behind under
+/// `ResignPolicy::Neutral`; the others play on, and the last one left wins.
+fn resign(state: &mut GameState, house: HouseName) {
+    if state.resigned.contains(&house) {
+        return;
+    }
+    state.resigned.push(house);
+    state.draw_offers.retain(|&h| h != house);
+    state.log_event(GameEventKind::Resigned { house });
+    let neutral = state.options.resign_policy == ResignPolicy::Neutral;
+    for (i, def) in AREAS.iter().enumerate() {
+        let area_id = AreaId(i as u8);
+        let area = state.area_mut(area_id);
+        if area.order.is_some_and(|o| o.house == house) {
+            area.order = None;
+        }
+        let units: Vec<Unit> = area.units.iter().filter(|u| u.house == house).copied().collect();
+        area.units.retain(|u| u.house != house);
+        for unit in &units {
+            return_to_pool(state, unit);
+        }
+        let garrison = state.garrisons.get(&area_id).filter(|g| g.house == Some(house)).map(|g| g.strength);
+        if garrison.is_some() {
+            state.garrisons.remove(&area_id);
+        }
+        if state.area(area_id).house == Some(house) {
+            set_area_control(state, area_id, None);
+        }
+        let strength = garrison.unwrap_or(0) + units.iter().map(|u| u.unit_type.combat_strength()).sum::<u8>();
+        if neutral && def.is_land() && strength > 0 {
+            state.garrisons.insert(area_id, Garrison { house: None, strength });
+        }
+    }
+    match state.remaining_houses()[..] {
+        [last] => declare_winner(state, last),
+        _ => check_draw(state),
+    }
+}
 
-    state.winner = Some(rankings[0].0);
+/// Record `house`'s draw offer and end the game if it was the last one
+/// missing.
+fn offer_draw(state: &mut GameState, house: HouseName) {
+    if state.draw_offers.contains(&house) || state.resigned.contains(&house) {
+        return;
+    }
+    state.draw_offers.push(house);
+    state.log_event(GameEventKind::DrawOffered { house });
+    check_draw(state);
 }
+
+fn check_draw(state: &mut GameState) {
+    let houses = state.remaining_houses();
+    if state.draw_offers.is_empty() || !houses.iter().all(|h| state.draw_offers.contains(h)) {
+        return;
+    }
+    state.drawn = true;
+    state.winner = Some(final_ranking(state)[0]);
+    state.clear_pending();
+    state.log_event(GameEventKind::DrawAgreed { houses });
+}
+
+/// The engine's answer for a resigned house: pass, bid nothing, take the
+/// first option. None for decisions only a house with units on the board
+/// is asked, which are dropped.
+fn resigned_answer(pending: &PendingDecision) -> Option<Action> {
+    use PendingDecision as P;
+    Some(match pending {
+        P::PlaceOrders { .. } => Action::PlaceOrders(Vec::new()),
+        P::ChooseOrderToResolve { candidates, .. } => Action::ResolveOrder(*candidates.first()?),
+        P::ChooseRaid { .. } => Action::Raid(None),
+        P::ChooseMarch { .. } => Action::MarchSkip,
+        P::LeavePowerToken { .. } => Action::LeavePowerToken(false),
+        P::SupportDeclaration { .. } => Action::DeclareSupport(SupportChoice::None),
+        P::UseValyrianBlade { .. } => Action::UseValyrianBlade(false),
+        P::Bidding { .. } => Action::Bid(0),
+        P::WesterosChoice { .. } => Action::WesterosChoice(0),
+        P::WildlingPenaltyChoice { .. } => Action::WildlingPenalty(0),
+        P::Muster { .. } => Action::Muster(Vec::new()),
+        P::MessengerRaven { .. } => Action::MessengerRaven(None),
+        P::AeronSwap { .. } => Action::AeronSwap(None),
+        P::Reconcile { area_id, .. } => Action::Reconcile(*area_id, 0),
+        P::DoranChooseTrack { .. } => Action::DoranChooseTrack(Track::IronThrone),
+        P::SelectHouseCard { available_cards, .. } => Action::SelectCard(*available_cards.first()?),
+        P::TyrionReplace { available_cards, .. } => Action::TyrionReplace(*available_cards.first()?),
+        P::PatchfaceDiscard { visible_cards, .. } => Action::PatchfaceDiscard(*visible_cards.first()?),
+        P::Retreat { possible_areas, .. } => Action::Retreat(*possible_areas.first()?),
+        P::RobbRetreat { possible_areas, .. } => Action::RobbRetreat(*possible_areas.first()?),
+        P::CerseiRemoveOrder { valid_targets, .. } => Action::CerseiRemoveOrder(*valid_targets.first()?),
+        P::QueenOfThornsRemoveOrder { valid_targets, .. } => Action::QueenOfThorns(*valid_targets.first()?),
+    })
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+// LEGAL ACTIONS — answers that pass validation and the rules behind it
+// ═══════════════════════════════════════════════════════════════════════
+
+/// The only legal answer to the decision `state` waits on, if it has
+/// exactly one. Orders are never forced: a house may leave an area
+/// without one.
+pub fn forced_action(state: &GameState) -> Option<Action> {
+    if matches!(state.peek_pending()?, PendingDecision::PlaceOrders { .. }) {
+        return None;
+    }
+    let mut actions = legal_actions(state);
+    if actions.len() == 1 { actions.pop() } else { None }
+}
+
+/// Every legal answer to the decision `state` waits on, empty if none is
+/// pending. Resigning and offering a draw are left out. Orders and musters
+/// combine a choice per area, too many to list: for those only the
+/// answers touching a single area are given (and the empty muster);
+/// `random_legal_action` draws whole combinations.
+pub fn legal_actions(state: &GameState) -> Vec<Action> {
+    use PendingDecision as P;
+    let Some(pending) = state.peek_pending() else { return Vec::new() };
+    let house = pending.house();
+    match pending {
+        P::PlaceOrders { .. } => {
+            let tokens = allowed_tokens(state, house, &[], None);
+            own_occupied_areas(state, house).into_iter()
+                .flat_map(|a| tokens.iter().map(move |&t| Action::PlaceOrders(vec![(a, t)])))
+                .collect()
+        }
+        P::Muster { areas, .. } => {
+            let mut actions = vec![Action::Muster(Vec::new())];
+            for m in areas {
+                actions.extend(muster_options(state, house, m.area_id, m.points, &[])
+                    .into_iter()
+                    .map(|(choice, _)| Action::Muster(vec![(m.area_id, choice)])));
+            }
+            actions
+        }
+        P::ChooseOrderToResolve { candidates, .. } => candidates.iter().map(|&a| Action::ResolveOrder(a)).collect(),
+        P::ChooseRaid { valid_targets, .. } => std::iter::once(None).chain(valid_targets.iter().copied().map(Some))
+            .map(Action::Raid)
+            .collect(),
+        P::ChooseMarch { from_area, valid_destinations, selectable_units, .. } => {
+            let mut actions = vec![Action::MarchSkip];
+            // Every non-empty selection of the units that may march
+            for mask in 1..1u32 << selectable_units.len().min(10) {
+                let unit_indices: Vec<usize> = selectable_units.iter().enumerate()
+                    .filter(|(i, _)| mask & (1 << i) != 0)
+                    .map(|(_, &u)| u)
+                    .collect();
+                for &to in valid_destinations {
+                    if march_is_legal(state, house, *from_area, to, &unit_indices) {
+                        actions.push(Action::March { to, unit_indices: unit_indices.clone() });
+                    }
+                }
+            }
+            actions
+        }
+        P::LeavePowerToken { .. } => {
+            let mut actions = vec![Action::LeavePowerToken(false)];
+            if state.house(house).power > 0 {
+                actions.push(Action::LeavePowerToken(true));
+            }
+            actions
+        }
+        P::SupportDeclaration { .. } => [SupportChoice::Attacker, SupportChoice::Defender, SupportChoice::None]
+            .into_iter().map(Action::DeclareSupport).collect(),
+        P::SelectHouseCard { available_cards, .. } => available_cards.iter().map(|&c| Action::SelectCard(c)).collect(),
+        P::UseValyrianBlade { .. } => vec![Action::UseValyrianBlade(false), Action::UseValyrianBlade(true)],
+        P::Bidding { .. } => (0..=state.house(house).power).map(Action::Bid).collect(),
+        P::WesterosChoice { options, .. } => (0..options.len()).map(Action::WesterosChoice).collect(),
+        P::WildlingPenaltyChoice { options, .. } => (0..options.len()).map(Action::WildlingPenalty).collect(),
+        P::Retreat { possible_areas, .. } => possible_areas.iter().map(|&a| Action::Retreat(a)).collect(),
+        P::RobbRetreat { possible_areas, .. } => possible_areas.iter().map(|&a| Action::RobbRetreat(a)).collect(),
+        P::Reconcile { area_id, .. } => state.area(*area_id).units.iter().enumerate()
+            .filter(|(_, u)| u.house == house)
+            .map(|(i, _)| Action::Reconcile(*area_id, i))
+            .collect(),
+        P::MessengerRaven { .. } => {
+            let mut actions = vec![Action::MessengerRaven(None)];
+            for (i, area) in state.areas.iter().enumerate() {
+                let Some(order) = area.order.filter(|o| o.house == house) else { continue };
+                // The swapped token goes back and frees its star
+                for t in allowed_tokens(state, house, &[], Some(order.token_index)) {
+                    actions.push(Action::MessengerRaven(Some((AreaId(i as u8), t))));
+                }
+            }
+            actions
+        }
+        P::AeronSwap { available_cards, power_cost, .. } => {
+            let mut actions = vec![Action::AeronSwap(None)];
+            if state.house(house).power >= *power_cost {
+                actions.extend(available_cards.iter().filter(|c| state.house(house).hand.contains(c)).map(|&c| Action::AeronSwap(Some(c))));
+            }
+            actions
+        }
+        P::TyrionReplace { available_cards, .. } => available_cards.iter().map(|&c| Action::TyrionReplace(c)).collect(),
+        P::PatchfaceDiscard { visible_cards, .. } => visible_cards.iter().map(|&c| Action::PatchfaceDiscard(c)).collect(),
+        P::DoranChooseTrack { .. } => [Track::IronThrone, Track::Fiefdoms, Track::KingsCourt]
+            .into_iter().map(Action::DoranChooseTrack).collect(),
+        P::CerseiRemoveOrder { valid_targets, .. } => valid_targets.iter().copied().map(Action::CerseiRemoveOrder).collect(),
+        P::QueenOfThornsRemoveOrder { valid_targets, .. } => valid_targets.iter().copied().map(Action::QueenOfThorns).collect(),
+    }
+}
+
+/// A legal answer to the pending decision drawn at random, None if no
+/// decision is pending. Orders go on every occupied area while tokens
+/// last, and musters spend each area's points on units the pool and
+/// supply allow. The fallback wherever a runner needs a move nobody gave.
+pub fn random_legal_action(state: &GameState, rng: &mut impl rand::Rng) -> Option<Action> {
+    let pending = state.peek_pending()?;
+    let house = pending.house();
+    match pending {
+        PendingDecision::PlaceOrders { .. } => {
+            let mut orders: Vec<(AreaId, u8)> = Vec::new();
+            for area in own_occupied_areas(state, house) {
+                let placed: Vec<u8> = orders.iter().map(|&(_, t)| t).collect();
+                let Some(&token) = allowed_tokens(state, house, &placed, None).choose(rng) else { break };
+                orders.push((area, token));
+            }
+            Some(Action::PlaceOrders(orders))
+        }
+        PendingDecision::Muster { areas, .. } => {
+            let mut actions = Vec::new();
+            for m in areas {
+                let mut points = m.points;
+                loop {
+                    let options = muster_options(state, house, m.area_id, points, &actions);
+                    // Stopping early is a choice too
+                    if rng.gen_range(0..=options.len()) == 0 {
+                        break;
+                    }
+                    let (choice, cost) = options.choose(rng)?.clone();
+                    actions.push((m.area_id, choice));
+                    points -= cost;
+                }
+            }
+            Some(Action::Muster(actions))
+        }
+        _ => legal_actions(state).choose(rng).cloned(),
+    }
+}
+
+/// Areas where `house` has units, the ones that take orders.
+fn own_occupied_areas(state: &GameState, house: HouseName) -> Vec<AreaId> {
+    state.areas.iter().enumerate()
+        .filter(|(_, a)| a.house == Some(house) && !a.units.is_empty())
+        .map(|(i, _)| AreaId(i as u8))
+        .collect()
+}
+
+/// Order tokens `house` could place on top of the orders it has on the
+/// board, as `allowed_tokens` with nothing pending.
+pub fn available_tokens(state: &GameState, house: HouseName) -> Vec<u8> {
+    allowed_tokens(state, house, &[], None)
+}
+
+/// Star orders `house` may have on the board at once, from its King's
+/// Court position.
+pub fn legal_star_count(state: &GameState, house: HouseName) -> u8 {
+    tracks::star_limit(state, house)
+}
+
+/// Star orders `house` may still place: its King's Court limit less the
+/// stars it has on the board.
+pub fn star_budget(state: &GameState, house: HouseName) -> u8 {
+    let stars = state.areas.iter()
+        .filter_map(|a| a.order.filter(|o| o.house == house && o.star))
+        .count() as u8;
+    legal_star_count(state, house).saturating_sub(stars)
+}
+
+/// Order tokens `house` may still place: not on the board (bar the
+/// `freed` one) or among `placed`, not restricted by the Westeros cards,
+/// and a star only while under its King's Court limit.
+fn allowed_tokens(state: &GameState, house: HouseName, placed: &[u8], freed: Option<u8>) -> Vec<u8> {
+    let used: Vec<u8> = state.areas.iter()
+        .filter_map(|a| a.order.filter(|o| o.house == house))
+        .map(|o| o.token_index)
+        .filter(|&t| Some(t) != freed)
+        .chain(placed.iter().copied())
+        .collect();
+    let stars = used.iter().filter(|&&t| ORDER_TOKENS[t as usize].star).count() as u8;
+    let star_room = stars < tracks::star_limit(state, house);
+    (0..ORDER_TOKENS.len() as u8)
+        .filter(|t| !used.contains(t))
+        .filter(|&t| {
+            let token = ORDER_TOKENS[t as usize];
+            !state.order_restrictions.contains(&token.order_type)
+                && (!token.star || (star_room && !state.star_order_restrictions.contains(&token.order_type)))
+        })
+        .collect()
+}
+
+/// What `house` can still muster in `area_id` with `points` left after
+/// `chosen`, and the cost of each: land units, ships (one per place in
+/// `ship_muster_areas`) and upgrades the pool has room for, within supply.
+fn muster_options(
+    state: &GameState,
+    house: HouseName,
+    area_id: AreaId,
+    points: u8,
+    chosen: &[(AreaId, MusterAction2)],
+) -> Vec<(MusterAction2, u8)> {
+    let profile = state.house(house);
+    board_muster_options(state, house, profile.available_units, profile.supply, area_id, points, chosen)
+}
+
+/// `muster_options` on any board, a `PlayerView` included, for a house
+/// with `pool` units left at `supply`.
+pub fn board_muster_options(
+    board: &impl Board,
+    house: HouseName,
+    mut pool: UnitPool,
+    supply: u8,
+    area_id: AreaId,
+    points: u8,
+    chosen: &[(AreaId, MusterAction2)],
+) -> Vec<(MusterAction2, u8)> {
+    if !AREAS[area_id.0 as usize].is_land() {
+        return Vec::new();
+    }
+    let mut built = Vec::new();
+    let mut ships = Vec::new();
+    let mut footmen = board.units(area_id).iter().filter(|u| u.house == house && u.unit_type == UnitType::Footman).count();
+    for (area, choice) in chosen {
+        match choice {
+            MusterAction2::Build(unit_type) => {
+                *pool.get_mut(*unit_type) -= 1;
+                built.push(*area);
+                if *area == area_id && *unit_type == UnitType::Footman {
+                    footmen += 1;
+                }
+            }
+            MusterAction2::BuildShip(to) => {
+                pool.ships -= 1;
+                ships.push(*to);
+            }
+            MusterAction2::Upgrade => {
+                *pool.get_mut(UnitType::Knight) -= 1;
+                *pool.get_mut(UnitType::Footman) += 1;
+                if *area == area_id {
+                    footmen -= 1;
+                }
+            }
+        }
+    }
+    let with = |to: AreaId| {
+        let mut added: Vec<AreaId> = built.iter().chain(&ships).copied().collect();
+        added.push(to);
+        supply::board_allows_units(board, supply, house, &added)
+    };
+    let room = with(area_id);
+
+    let mut options: Vec<(MusterAction2, u8)> = [UnitType::Footman, UnitType::Knight, UnitType::SiegeEngine].into_iter()
+        .filter(|&u| room && u.muster_cost() <= points && pool.get(u) > 0)
+        .map(|u| (MusterAction2::Build(u), u.muster_cost()))
+        .collect();
+    if points >= UnitType::Ship.muster_cost() && pool.ships > 0 {
+        options.extend(ship_muster_areas(board, house, area_id, &ships).into_iter()
+            .filter(|&to| with(to))
+            .map(|to| (MusterAction2::BuildShip(to), UnitType::Ship.muster_cost())));
+    }
+    if footmen > 0 && points >= 1 && pool.get(UnitType::Knight) > 0 {
+        options.push((MusterAction2::Upgrade, 1));
+    }
+    options
+}
+
+/// Areas holding one of `house`'s orders.
+fn orders_of(state: &GameState, house: HouseName) -> Vec<AreaId> {
+    state.areas.iter().enumerate()
+        .filter(|(_, a)| a.order.is_some_and(|o| o.house == house))
+        .map(|(i, _)| AreaId(i as u8))
+        .collect()
+}
+
+/// `opponent`'s orders the Queen of Thorns may remove: next to the
+/// embattled area, bar the march that started the battle.
+fn queen_of_thorns_targets(state: &GameState, opponent: HouseName) -> Vec<AreaId> {
+    let Some(combat) = state.combat() else { return Vec::new() };
+    let near = AREAS[combat.area_id.0 as usize].adjacent;
+    orders_of(state, opponent).into_iter()
+        .filter(|a| near.contains(a) && Some(*a) != combat.march_from_area)
+        .collect()
+}
+
@@ -0,0 +1,242 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Static evaluation — how likely each house is to win from a position
+//
+// Every house gets a handful of public features (castles, supply, power,
+// track standing, army strength, board tension, wildling exposure) and a
+// score, the weighted sum of its features. Win probabilities are the
+// softmax of the scores over the playing houses, so they always sum to 1
+// and a house's estimate depends on how it compares to the others.
+//
+// The weights are a conditional-logit fit on positions sampled from
+// heuristic self-play (`got-runner eval-calibrate --fit`), which keeps
+// the estimates calibrated: among positions rated 30%, about 30% are won.
+// Only public information is read, so a `PlayerView` gets the same value
+// as the full `GameState`.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::map::AREAS;
+use crate::types::*;
+use crate::visibility::PlayerView;
+
+// ── Features ───────────────────────────────────────────────────────────
+
+pub const NUM_FEATURES: usize = 10;
+
+pub const FEATURE_NAMES: [&str; NUM_FEATURES] = [
+    "castles",
+    "late_castles",
+    "lead",
+    "supply",
+    "power",
+    "tracks",
+    "strength",
+    "exposed",
+    "pressure",
+    "wildling",
+];
+
+/// Fitted weights, in `FEATURE_NAMES` order.
+pub const WEIGHTS: [f32; NUM_FEATURES] = [4.0, 4.0, 2.0, 0.6, 0.8, 0.5, 0.5, -1.0, 0.8, -1.0];
+
+/// One house's evaluation inputs, each scaled to about 0..1.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Features {
+    /// Castles and strongholds held, over the number needed to win.
+    pub castles: f32,
+    /// `castles` weighted by how far the game is (round / 10): a castle
+    /// lead counts for more the fewer rounds are left to overturn it.
+    pub late_castles: f32,
+    /// Castles ahead of the best rival (0 when not leading), over castles
+    /// to win: only the leader is paid for the gap.
+    pub lead: f32,
+    pub supply: f32,
+    pub power: f32,
+    /// Mean standing on the three influence tracks, 1 = top, 0 = bottom.
+    pub tracks: f32,
+    /// Combat strength of the house's units on the board.
+    pub strength: f32,
+    /// Held castles with enemy units next to them, over castles to win.
+    pub exposed: f32,
+    /// Enemy castles next to the house's units, over castles to win.
+    pub pressure: f32,
+    /// Wildling threat times the house's power shortfall against the
+    /// average, the risk of taking the losing bidder's penalty.
+    pub wildling: f32,
+}
+
+impl Features {
+    pub fn to_array(&self) -> [f32; NUM_FEATURES] {
+        [
+            self.castles,
+            self.late_castles,
+            self.lead,
+            self.supply,
+            self.power,
+            self.tracks,
+            self.strength,
+            self.exposed,
+            self.pressure,
+            self.wildling,
+        ]
+    }
+
+    pub fn score(&self, weights: &[f32; NUM_FEATURES]) -> f32 {
+        self.to_array().iter().zip(weights).map(|(f, w)| f * w).sum()
+    }
+}
+
+// ── Positions ──────────────────────────────────────────────────────────
+
+/// A house's public standing off the board.
+#[derive(Debug, Clone, Copy)]
+pub struct Standing {
+    pub iron_throne: u8,
+    pub fiefdoms: u8,
+    pub kings_court: u8,
+    pub supply: u8,
+    pub power: u8,
+    pub castles_to_win: u8,
+}
+
+/// Anything that can be evaluated: the full state or a player's view.
+pub trait Evaluable {
+    fn round(&self) -> u8;
+    fn winner(&self) -> Option<HouseName>;
+    fn wildling_threat(&self) -> u8;
+    fn houses(&self) -> &[HouseName];
+    fn standing(&self, house: HouseName) -> Option<Standing>;
+    /// Controller and units of the area with index `i`.
+    fn area(&self, i: usize) -> (Option<HouseName>, &[Unit]);
+}
+
+impl Evaluable for GameState {
+    fn round(&self) -> u8 { self.round }
+    fn winner(&self) -> Option<HouseName> { self.winner }
+    fn wildling_threat(&self) -> u8 { self.wildling_threat }
+    fn houses(&self) -> &[HouseName] { &self.playing_houses }
+
+    fn standing(&self, house: HouseName) -> Option<Standing> {
+        self.houses.get(&house).map(|p| Standing {
+            iron_throne: p.iron_throne,
+            fiefdoms: p.fiefdoms,
+            kings_court: p.kings_court,
+            supply: p.supply,
+            power: p.power,
+            castles_to_win: self.options.castles_to_win(house),
+        })
+    }
+
+    fn area(&self, i: usize) -> (Option<HouseName>, &[Unit]) {
+        (self.areas[i].house, &self.areas[i].units)
+    }
+}
+
+impl Evaluable for PlayerView {
+    fn round(&self) -> u8 { self.round }
+    fn winner(&self) -> Option<HouseName> { self.winner }
+    fn wildling_threat(&self) -> u8 { self.wildling_threat }
+    fn houses(&self) -> &[HouseName] { &self.playing_houses }
+
+    fn standing(&self, house: HouseName) -> Option<Standing> {
+        self.house_info.get(&house).map(|p| Standing {
+            iron_throne: p.iron_throne,
+            fiefdoms: p.fiefdoms,
+            kings_court: p.kings_court,
+            supply: p.supply,
+            power: p.power,
+            castles_to_win: p.castles_to_win,
+        })
+    }
+
+    fn area(&self, i: usize) -> (Option<HouseName>, &[Unit]) {
+        (self.areas[i].house, &self.areas[i].units)
+    }
+}
+
+/// Evaluation features of `house`, or None when it is not playing.
+pub fn features(position: &impl Evaluable, house: HouseName) -> Option<Features> {
+    let standing = position.standing(house)?;
+    let houses = position.houses();
+    let n = houses.len().max(2) as f32;
+    let to_win = standing.castles_to_win.max(1) as f32;
+    let track = |pos: u8| (n - pos as f32) / (n - 1.0);
+
+    let mut castles = 0usize;
+    let mut strength = 0;
+    let mut exposed = 0;
+    let mut pressure = 0;
+    for (i, def) in AREAS.iter().enumerate() {
+        let (owner, units) = position.area(i);
+        strength += units.iter().filter(|u| u.house == house).map(|u| u.unit_type.combat_strength() as u32).sum::<u32>();
+        if !def.has_castle_or_stronghold() {
+            continue;
+        }
+        let neighbour_units = |own: bool| def.adjacent.iter()
+            .any(|a| position.area(a.0 as usize).1.iter().any(|u| (u.house == house) == own));
+        match owner {
+            Some(h) if h == house => {
+                castles += 1;
+                if neighbour_units(false) {
+                    exposed += 1;
+                }
+            }
+            Some(_) if neighbour_units(true) => pressure += 1,
+            _ => {}
+        }
+    }
+
+    let best_rival = houses.iter()
+        .filter(|&&h| h != house)
+        .map(|&h| (0..AREAS.len()).filter(|&i| position.area(i).0 == Some(h) && AREAS[i].has_castle_or_stronghold()).count())
+        .max()
+        .unwrap_or(0);
+    let lead = castles.saturating_sub(best_rival) as f32 / to_win;
+    let mean_power = houses.iter()
+        .filter_map(|&h| position.standing(h))
+        .map(|s| s.power as f32)
+        .sum::<f32>() / houses.len().max(1) as f32;
+    let castles = castles as f32 / to_win;
+    Some(Features {
+        castles,
+        late_castles: castles * position.round().min(10) as f32 / 10.0,
+        lead,
+        supply: standing.supply as f32 / 6.0,
+        power: standing.power as f32 / 20.0,
+        tracks: (track(standing.iron_throne) + track(standing.fiefdoms) + track(standing.kings_court)) / 3.0,
+        strength: strength as f32 / 20.0,
+        exposed: exposed as f32 / to_win,
+        pressure: pressure as f32 / to_win,
+        wildling: position.wildling_threat() as f32 / 12.0 * ((mean_power - standing.power as f32) / 20.0).max(0.0),
+    })
+}
+
+// ── Evaluation ─────────────────────────────────────────────────────────
+
+/// Win probability of every playing house under `weights`, in
+/// `playing_houses` order. A decided game gives the winner 1.
+pub fn win_probabilities_with(position: &impl Evaluable, weights: &[f32; NUM_FEATURES]) -> Vec<(HouseName, f32)> {
+    let houses = position.houses();
+    if let Some(winner) = position.winner() {
+        return houses.iter().map(|&h| (h, if h == winner { 1.0 } else { 0.0 })).collect();
+    }
+    let scores: Vec<f32> = houses.iter()
+        .map(|&h| features(position, h).map_or(f32::NEG_INFINITY, |f| f.score(weights)))
+        .collect();
+    let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let exp: Vec<f32> = scores.iter().map(|s| (s - max).exp()).collect();
+    let total: f32 = exp.iter().sum();
+    houses.iter().zip(exp).map(|(&h, e)| (h, e / total)).collect()
+}
+
+/// Win probability of every playing house, in `playing_houses` order.
+pub fn win_probabilities(position: &impl Evaluable) -> Vec<(HouseName, f32)> {
+    win_probabilities_with(position, &WEIGHTS)
+}
+
+/// Estimated probability that `house` wins from `position`; 0 for a
+/// house that is not playing.
+pub fn static_eval(position: &impl Evaluable, house: HouseName) -> f32 {
+    win_probabilities(position).into_iter()
+        .find(|&(h, _)| h == house)
+        .map_or(0.0, |(_, p)| p)
+}
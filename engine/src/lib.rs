@@ -4,8 +4,17 @@ pub mod cards;
 pub mod supply;
 pub mod navigation;
 pub mod setup;
+pub mod tracks;
 pub mod engine;
 pub mod visibility;
+pub mod threats;
+pub mod encoding;
+pub mod eval;
+pub mod rollout;
+pub mod determinize;
+pub mod wire;
+pub mod replay;
+pub mod snapshot;
 
 #[cfg(test)]
 mod tests;
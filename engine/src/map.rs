@@ -4,6 +4,10 @@
 // ═══════════════════════════════════════════════════════════════════════
 
 use crate::types::{AreaId, AreaType};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub mod analysis;
 
 /// Static description of a map area (compile-time constant).
 #[derive(Debug, Clone)]
@@ -108,11 +112,58 @@ pub const SUNSPEAR_PORT: AreaId       = AreaId(58);
 
 pub const NUM_AREAS: usize = 59;
 
+/// Ships a port can hold.
+pub const MAX_SHIPS_IN_PORT: usize = 3;
+
 /// Lookup area name by AreaId.
 pub fn area_name(id: AreaId) -> &'static str {
     AREAS[id.0 as usize].name
 }
 
+// ── Serializable map ───────────────────────────────────────────────────
+
+/// The map as data, for clients that can't link `AREAS`: what `AreaDef`
+/// holds for every area, with views' area indexes as ids.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct StaticMap {
+    /// By area id.
+    pub areas: Vec<MapArea>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct MapArea {
+    pub id: AreaId,
+    pub name: String,
+    pub area_type: AreaType,
+    pub castle: bool,
+    pub stronghold: bool,
+    pub supply_icons: u8,
+    pub power_icons: u8,
+    pub adjacent: Vec<AreaId>,
+    /// For ports: the land area owning the port and the sea it opens onto.
+    pub connected_land: Option<AreaId>,
+    pub connected_sea: Option<AreaId>,
+}
+
+/// Every area of the map, in id order.
+pub fn static_map() -> StaticMap {
+    let areas = AREAS.iter()
+        .map(|d| MapArea {
+            id: d.id,
+            name: d.name.to_string(),
+            area_type: d.area_type,
+            castle: d.castle,
+            stronghold: d.stronghold,
+            supply_icons: d.supply_icons,
+            power_icons: d.power_icons,
+            adjacent: d.adjacent.to_vec(),
+            connected_land: d.connected_land,
+            connected_sea: d.connected_sea,
+        })
+        .collect();
+    StaticMap { areas }
+}
+
 // ── Static area definitions ────────────────────────────────────────────
 
 macro_rules! land {
@@ -0,0 +1,223 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Map analysis — distances, chokepoints and home regions
+//
+// Facts about the board alone, computed once on first use:
+//
+//   • distances: the fewest marches between two land areas, either by
+//     land only or with ship chains assumed everywhere (`Transport::Ships`
+//     — any two coastal areas are then one march apart, as a transport
+//     chain has no length limit)
+//   • chokepoints: land areas whose loss splits the land they connect
+//     (articulation points of the land adjacency graph), with the areas
+//     cut off behind them
+//   • home regions: every land area is given to the house whose home
+//     stronghold is strictly nearest, among the houses in the game,
+//     counting the seas crossed on the way; ties leave it contested
+//
+// Units, control and blocked areas play no part; see `navigation` for
+// movement on an actual board.
+// ═══════════════════════════════════════════════════════════════════════
+
+use super::*;
+use crate::types::HouseName;
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+
+/// How armies may travel between land areas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    /// Land adjacency only.
+    Land,
+    /// Land adjacency, or a ship chain through any seas.
+    Ships,
+}
+
+/// Home stronghold of a house.
+pub fn home_area(house: HouseName) -> AreaId {
+    match house {
+        HouseName::Stark     => WINTERFELL,
+        HouseName::Lannister => LANNISPORT,
+        HouseName::Baratheon => DRAGONSTONE,
+        HouseName::Greyjoy   => PYKE,
+        HouseName::Tyrell    => HIGHGARDEN,
+        HouseName::Martell   => SUNSPEAR,
+    }
+}
+
+// ── Distances ──────────────────────────────────────────────────────────
+
+type DistanceTable = Vec<[Option<u8>; NUM_AREAS]>;
+
+fn is_land(id: AreaId) -> bool {
+    AREAS[id.0 as usize].is_land()
+}
+
+fn land_areas() -> impl Iterator<Item = AreaId> {
+    AREAS.iter().filter(|d| d.is_land()).map(|d| d.id)
+}
+
+/// Land areas one march from `from`.
+fn neighbours(from: AreaId, transport: Transport) -> Vec<AreaId> {
+    let def = &AREAS[from.0 as usize];
+    let mut next: Vec<AreaId> = def.adjacent.iter().copied().filter(|&a| is_land(a)).collect();
+    if transport == Transport::Ships && def.adjacent.iter().any(|&a| AREAS[a.0 as usize].is_sea()) {
+        // The seas are all connected, so every coast is one chain away
+        for coast in land_areas().filter(|&l| l != from && coastal(l)) {
+            if !next.contains(&coast) {
+                next.push(coast);
+            }
+        }
+    }
+    next
+}
+
+fn coastal(id: AreaId) -> bool {
+    AREAS[id.0 as usize].adjacent.iter().any(|&a| AREAS[a.0 as usize].is_sea())
+}
+
+fn bfs(from: AreaId, transport: Transport, removed: Option<AreaId>) -> [Option<u8>; NUM_AREAS] {
+    let mut dist = [None; NUM_AREAS];
+    dist[from.0 as usize] = Some(0);
+    let mut queue = VecDeque::from([from]);
+    while let Some(at) = queue.pop_front() {
+        let d = dist[at.0 as usize].unwrap_or(0);
+        for next in neighbours(at, transport) {
+            if Some(next) != removed && dist[next.0 as usize].is_none() {
+                dist[next.0 as usize] = Some(d + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    dist
+}
+
+fn table(transport: Transport) -> &'static DistanceTable {
+    static LAND: OnceLock<DistanceTable> = OnceLock::new();
+    static SHIPS: OnceLock<DistanceTable> = OnceLock::new();
+    let cell = match transport {
+        Transport::Land => &LAND,
+        Transport::Ships => &SHIPS,
+    };
+    cell.get_or_init(|| {
+        (0..NUM_AREAS)
+            .map(|i| {
+                let id = AreaId(i as u8);
+                if is_land(id) { bfs(id, transport, None) } else { [None; NUM_AREAS] }
+            })
+            .collect()
+    })
+}
+
+/// Fewest marches from one land area to another; `None` when either is
+/// not land or there is no way.
+pub fn distance(from: AreaId, to: AreaId, transport: Transport) -> Option<u8> {
+    table(transport)[from.0 as usize][to.0 as usize]
+}
+
+/// Fewest marches from `from` to every area, by area id.
+pub fn distances_from(from: AreaId, transport: Transport) -> &'static [Option<u8>; NUM_AREAS] {
+    &table(transport)[from.0 as usize]
+}
+
+// ── Chokepoints ────────────────────────────────────────────────────────
+
+/// A land area whose loss splits the land around it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chokepoint {
+    pub area: AreaId,
+    /// Land areas no longer joined to the larger side without it.
+    pub cut_off: Vec<AreaId>,
+}
+
+/// Articulation points of the land adjacency graph, by area id.
+pub fn chokepoints() -> &'static [Chokepoint] {
+    static CHOKEPOINTS: OnceLock<Vec<Chokepoint>> = OnceLock::new();
+    CHOKEPOINTS.get_or_init(|| {
+        land_areas()
+            .filter_map(|area| {
+                // The pieces its neighbours fall into without it
+                let mut parts: Vec<Vec<AreaId>> = Vec::new();
+                let mut seen = [false; NUM_AREAS];
+                for n in neighbours(area, Transport::Land) {
+                    if seen[n.0 as usize] {
+                        continue;
+                    }
+                    let reach = bfs(n, Transport::Land, Some(area));
+                    let part: Vec<AreaId> = land_areas().filter(|&l| l != area && reach[l.0 as usize].is_some()).collect();
+                    for l in &part {
+                        seen[l.0 as usize] = true;
+                    }
+                    parts.push(part);
+                }
+                if parts.len() < 2 {
+                    return None;
+                }
+                parts.sort_by_key(|p| std::cmp::Reverse(p.len()));
+                let mut cut_off: Vec<AreaId> = parts[1..].iter().flatten().copied().collect();
+                cut_off.sort_by_key(|a| a.0);
+                Some(Chokepoint { area, cut_off })
+            })
+            .collect()
+    })
+}
+
+// ── Home regions ───────────────────────────────────────────────────────
+
+/// Areas crossed from `from` to every land area, seas counted like land,
+/// by area id. Ship chains make every coast one march apart, which says
+/// nothing about whose coast it is; counting the seas does.
+fn crossings_from(from: AreaId) -> [Option<u8>; NUM_AREAS] {
+    let mut dist = [None; NUM_AREAS];
+    dist[from.0 as usize] = Some(0);
+    let mut queue = VecDeque::from([from]);
+    while let Some(at) = queue.pop_front() {
+        let d = dist[at.0 as usize].unwrap_or(0);
+        for &next in AREAS[at.0 as usize].adjacent {
+            if !AREAS[next.0 as usize].is_port() && dist[next.0 as usize].is_none() {
+                dist[next.0 as usize] = Some(d + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    for (i, d) in dist.iter_mut().enumerate() {
+        if !AREAS[i].is_land() {
+            *d = None;
+        }
+    }
+    dist
+}
+
+/// The house each land area belongs to among `houses`: the one whose home
+/// stronghold is fewest areas away, seas included. By area id; seas,
+/// ports and ties are `None`.
+pub fn home_regions(houses: &[HouseName]) -> Vec<Option<HouseName>> {
+    let crossings: Vec<(HouseName, [Option<u8>; NUM_AREAS])> = houses.iter()
+        .map(|&h| (h, crossings_from(home_area(h))))
+        .collect();
+    (0..NUM_AREAS)
+        .map(|i| {
+            let mut best: Option<(u8, HouseName)> = None;
+            let mut tied = false;
+            for (house, dist) in &crossings {
+                let Some(d) = dist[i] else { continue };
+                match best {
+                    Some((b, _)) if d > b => {}
+                    Some((b, _)) if d == b => tied = true,
+                    _ => {
+                        best = Some((d, *house));
+                        tied = false;
+                    }
+                }
+            }
+            best.filter(|_| !tied).map(|(_, h)| h)
+        })
+        .collect()
+}
+
+/// Land areas of `house`'s home region among `houses`.
+pub fn home_region(houses: &[HouseName], house: HouseName) -> Vec<AreaId> {
+    home_regions(houses).iter().enumerate()
+        .filter(|&(_, &h)| h == Some(house))
+        .map(|(i, _)| AreaId(i as u8))
+        .collect()
+}
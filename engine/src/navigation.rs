@@ -4,86 +4,177 @@
 // ═══════════════════════════════════════════════════════════════════════
 
 use crate::types::*;
-use crate::map::AREAS;
+use crate::map::{AREAS, MAX_SHIPS_IN_PORT};
+use crate::visibility::PlayerView;
 use std::collections::VecDeque;
 
+/// What movement needs to know about the board: the units in an area,
+/// who controls it and whether it is blocked. Both the full state and a
+/// player's view have it.
+pub trait Board {
+    fn units(&self, area: AreaId) -> &[Unit];
+    fn controller(&self, area: AreaId) -> Option<HouseName>;
+    fn blocked(&self, area: AreaId) -> bool;
+}
+
+impl Board for GameState {
+    fn units(&self, area: AreaId) -> &[Unit] { &self.areas[area.0 as usize].units }
+    fn controller(&self, area: AreaId) -> Option<HouseName> { self.areas[area.0 as usize].house }
+    fn blocked(&self, area: AreaId) -> bool { self.areas[area.0 as usize].blocked }
+}
+
+impl Board for PlayerView {
+    fn units(&self, area: AreaId) -> &[Unit] { &self.areas[area.0 as usize].units }
+    fn controller(&self, area: AreaId) -> Option<HouseName> { self.areas[area.0 as usize].house }
+    fn blocked(&self, area: AreaId) -> bool { self.areas[area.0 as usize].blocked }
+}
+
 /// Check if a move from one area to another is valid for a given house.
 /// Considers direct adjacency and ship transport chains.
-pub fn is_move_valid(state: &GameState, from: AreaId, to: AreaId, house: HouseName) -> bool {
-    let from_def = &AREAS[from.0 as usize];
-    let to_def = &AREAS[to.0 as usize];
-    let to_state = &state.areas[to.0 as usize];
+pub fn is_move_valid(state: &impl Board, from: AreaId, to: AreaId, house: HouseName) -> bool {
+    move_destinations(state, from, house).contains(&to)
+}
 
-    // Blocked regions are impassable (3-player game)
-    if to_state.blocked {
-        return false;
+/// Every area a move from `from` may end in for `house`, whatever the
+/// units, in one pass: the areas next to it (a sea also opens into its
+/// ports) and, from land, the shores of every chain of seas holding the
+/// house's ships. Blocked regions (3-player game) are impassable.
+pub fn move_destinations(state: &impl Board, from: AreaId, house: HouseName) -> Vec<AreaId> {
+    let from_def = &AREAS[from.0 as usize];
+    let mut reached = vec![false; AREAS.len()];
+    for &adj in from_def.adjacent {
+        reached[adj.0 as usize] = true;
     }
-
-    // Direct adjacency
-    if from_def.adjacent.contains(&to) {
-        return true;
+    for port in AREAS.iter().filter(|a| a.connected_sea == Some(from)) {
+        reached[port.id.0 as usize] = true;
     }
 
     // Ship Transport: Land → (chain of friendly-ship seas) → Land
     // Units starting in a Port or Sea cannot use ship transport
-    if !from_def.is_land() {
-        return false;
-    }
-    if to_def.is_sea() {
-        return false;
-    }
-
-    // BFS through seas with friendly ships
-    let mut queue: VecDeque<AreaId> = VecDeque::new();
-    let mut visited = vec![false; AREAS.len()];
-    
-    queue.push_back(from);
-    visited[from.0 as usize] = true;
-
-    while let Some(current) = queue.pop_front() {
-        let current_def = &AREAS[current.0 as usize];
-
-        for &adj_id in current_def.adjacent {
-            if visited[adj_id.0 as usize] {
-                continue;
+    if from_def.is_land() {
+        let mut visited = vec![false; AREAS.len()];
+        let mut queue: VecDeque<AreaId> = VecDeque::new();
+        for &adj in from_def.adjacent {
+            if AREAS[adj.0 as usize].is_sea() && has_friendly_ship(state, adj, house) {
+                visited[adj.0 as usize] = true;
+                queue.push_back(adj);
             }
-
-            let adj_def = &AREAS[adj_id.0 as usize];
-
-            // Did we reach the destination?
-            if adj_id == to {
-                // Only valid if we came through a sea with friendly ship
-                if current_def.is_sea() && has_friendly_ship(state, current, house) {
-                    return true;
+        }
+        while let Some(sea) = queue.pop_front() {
+            for &adj in AREAS[sea.0 as usize].adjacent {
+                if !AREAS[adj.0 as usize].is_sea() {
+                    reached[adj.0 as usize] = true;
+                } else if !visited[adj.0 as usize] && has_friendly_ship(state, adj, house) {
+                    visited[adj.0 as usize] = true;
+                    queue.push_back(adj);
                 }
-                continue;
-            }
-
-            // Can we traverse through this area?
-            if adj_def.is_sea() && has_friendly_ship(state, adj_id, house) {
-                visited[adj_id.0 as usize] = true;
-                queue.push_back(adj_id);
             }
         }
     }
 
-    false
+    reached[from.0 as usize] = false;
+    (0..AREAS.len())
+        .map(|i| AreaId(i as u8))
+        .filter(|&to| reached[to.0 as usize] && !state.blocked(to))
+        .collect()
 }
 
 /// Check if an area has at least one friendly ship.
-fn has_friendly_ship(state: &GameState, area: AreaId, house: HouseName) -> bool {
-    state.areas[area.0 as usize].units.iter()
+fn has_friendly_ship(state: &impl Board, area: AreaId, house: HouseName) -> bool {
+    state.units(area).iter()
         .any(|u| u.unit_type == UnitType::Ship && u.house == house)
 }
 
-/// Get all valid move destinations for a house from a given area.
+/// Whether a unit of this type may end its move in `to`: ships sail the
+/// seas and ports, everything else stays on land.
+pub fn can_enter(unit_type: UnitType, to: AreaId) -> bool {
+    let to_def = &AREAS[to.0 as usize];
+    match unit_type {
+        UnitType::Ship => !to_def.is_land(),
+        _ => to_def.is_land(),
+    }
+}
+
+/// Whether `units` may march together from `from` to `to`: the move is
+/// valid, every one of them may enter `to` and, into a port, the port
+/// takes them.
+pub fn is_march_valid(state: &impl Board, from: AreaId, to: AreaId, house: HouseName, units: &[UnitType]) -> bool {
+    to != from && can_end_in(state, to, house, units) && is_move_valid(state, from, to, house)
+}
+
+/// Whether `units` may end a march in `to`, once it is in reach.
+fn can_end_in(state: &impl Board, to: AreaId, house: HouseName, units: &[UnitType]) -> bool {
+    units.iter().all(|&ut| can_enter(ut, to))
+        && (!AREAS[to.0 as usize].is_port() || port_has_room(state, to, house, units.len()))
+}
+
+/// Whether `arriving` more of `house`'s ships may enter `port`: only the
+/// house holding the port's castle uses it, and it holds at most
+/// `MAX_SHIPS_IN_PORT` ships.
+pub fn port_has_room(state: &impl Board, port: AreaId, house: HouseName, arriving: usize) -> bool {
+    AREAS[port.0 as usize].connected_land.is_some_and(|land| state.controller(land) == Some(house))
+        && state.units(port).iter().all(|u| u.house == house)
+        && state.units(port).len() + arriving <= MAX_SHIPS_IN_PORT
+}
+
+/// Indices of the units in `from` that `house` may march: its own, and
+/// not routed.
+pub fn selectable_units(state: &GameState, from: AreaId, house: HouseName) -> Vec<usize> {
+    state.areas[from.0 as usize].units.iter().enumerate()
+        .filter(|(_, u)| u.house == house && !u.routed)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Get all valid move destinations for a house from a given area: those
+/// its selectable units there can reach and enter.
 pub fn valid_destinations(state: &GameState, from: AreaId, house: HouseName) -> Vec<AreaId> {
-    let mut destinations = Vec::new();
-    for i in 0..AREAS.len() {
-        let to = AreaId(i as u8);
-        if to != from && is_move_valid(state, from, to, house) {
-            destinations.push(to);
+    let units = &state.areas[from.0 as usize].units;
+    let types: Vec<UnitType> = selectable_units(state, from, house).into_iter()
+        .map(|i| units[i].unit_type)
+        .collect();
+    if types.is_empty() {
+        return Vec::new();
+    }
+    valid_destinations_for(state, from, house, &types)
+}
+
+/// Valid destinations for a particular selection of units. A selection
+/// mixing ships with land units has none.
+pub fn valid_destinations_for(state: &GameState, from: AreaId, house: HouseName, units: &[UnitType]) -> Vec<AreaId> {
+    move_destinations(state, from, house).into_iter()
+        .filter(|&to| can_end_in(state, to, house, units))
+        .collect()
+}
+
+/// Areas `house` could get an army from `from` to within `n_moves`
+/// marches, each with the fewest marches it takes. Every march may use
+/// adjacency or a transport chain along the house's ships as they stand
+/// now; blocked areas are never entered. Armies in the way are ignored:
+/// this is how far the house could get, not how far it would fight its
+/// way. An army from a land area stays on land, ships from a sea or port
+/// stay at sea. `from` itself is not included.
+pub fn reachable_within(state: &impl Board, from: AreaId, house: HouseName, n_moves: u8) -> Vec<(AreaId, u8)> {
+    let mover = if AREAS[from.0 as usize].is_land() { UnitType::Footman } else { UnitType::Ship };
+    let mut moves: Vec<Option<u8>> = vec![None; AREAS.len()];
+    moves[from.0 as usize] = Some(0);
+    let mut frontier = vec![from];
+    for step in 1..=n_moves {
+        let mut next = Vec::new();
+        for &at in &frontier {
+            for to in move_destinations(state, at, house) {
+                if moves[to.0 as usize].is_none() && can_end_in(state, to, house, &[mover]) {
+                    moves[to.0 as usize] = Some(step);
+                    next.push(to);
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
         }
+        frontier = next;
     }
-    destinations
+    moves.iter().enumerate()
+        .filter_map(|(i, m)| m.filter(|&m| m > 0).map(|m| (AreaId(i as u8), m)))
+        .collect()
 }
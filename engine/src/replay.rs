@@ -0,0 +1,260 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Replays — recorded games with annotations
+//
+// A replay is the setup plus every action in order; the engine is
+// deterministic, so that is enough to rebuild any position of the game.
+// Annotations attach free text and structured values (an agent's win
+// estimate, search statistics, a reviewer's comment) to an action, an
+// event or the whole game, so analysis tools can show them alongside.
+//
+// On disk a replay is JSON Lines, one tagged record per line:
+//
+//   {"type":"header","version":3,"setup":{...},"players":[...]}
+//   {"type":"action","index":0,"round":1,"house":"Stark","action":{...}}
+//   {"type":"annotation","target":{"type":"action","index":0},"author":"mcts","data":{"win_probability":0.72}}
+//   {"type":"event","seq":0,"round":1,"phase":"Action","kind":{...}}
+//   {"type":"result","winner":"Stark","ranking":[...]}
+//
+// Annotation lines may appear anywhere after the header, so tools add
+// notes by appending to the file without rewriting it.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::engine::{self, action_fits, Action};
+use crate::setup::{create_game, SetupConfig};
+use crate::types::*;
+use crate::wire::{self, WIRE_VERSION};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+// ── Annotations ────────────────────────────────────────────────────────
+
+/// What an annotation is attached to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnnotationTarget {
+    /// The whole game.
+    Game,
+    /// The action with this index in the replay.
+    Action { index: usize },
+    /// The event with this sequence number.
+    Event { seq: u32 },
+}
+
+/// A note on a recorded game: free text, structured values, or both.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Annotation {
+    pub target: AnnotationTarget,
+    /// Who wrote it: an agent name, a tool or a person.
+    pub author: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Named values, e.g. `{"win_probability": 0.72, "visits": 800}`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub data: BTreeMap<String, Value>,
+}
+
+impl Annotation {
+    pub fn new(target: AnnotationTarget, author: impl Into<String>) -> Self {
+        Annotation { target, author: author.into(), text: None, data: BTreeMap::new() }
+    }
+
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    pub fn with(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.data.insert(key.into(), value.into());
+        self
+    }
+
+    /// One-line rendering for reports: author, text, then the values.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("[{}]", self.author)];
+        parts.extend(self.text.clone());
+        parts.extend(self.data.iter().map(|(k, v)| format!("{}={}", k, v)));
+        parts.join(" ")
+    }
+}
+
+// ── Replay ─────────────────────────────────────────────────────────────
+
+/// Who played a house, for display.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ReplayPlayer {
+    pub house: HouseName,
+    pub agent: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RecordedAction {
+    pub index: usize,
+    pub round: u8,
+    pub house: HouseName,
+    pub action: Action,
+}
+
+/// A recorded game. `events`, `winner` and `ranking` are filled by
+/// `finish`; an unfinished replay still plays back as far as it goes.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Replay {
+    pub version: u32,
+    pub setup: SetupConfig,
+    pub players: Vec<ReplayPlayer>,
+    pub actions: Vec<RecordedAction>,
+    pub events: Vec<GameEvent>,
+    pub annotations: Vec<Annotation>,
+    pub winner: Option<HouseName>,
+    pub ranking: Vec<HouseName>,
+}
+
+/// One line of the JSON Lines form.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Line {
+    Header { version: u32, setup: SetupConfig, players: Vec<ReplayPlayer> },
+    Action(RecordedAction),
+    Event(GameEvent),
+    Annotation(Annotation),
+    Result { winner: HouseName, ranking: Vec<HouseName> },
+}
+
+impl Replay {
+    pub fn new(setup: SetupConfig, players: Vec<ReplayPlayer>) -> Self {
+        Replay {
+            version: WIRE_VERSION,
+            setup,
+            players,
+            actions: Vec::new(),
+            events: Vec::new(),
+            annotations: Vec::new(),
+            winner: None,
+            ranking: Vec::new(),
+        }
+    }
+
+    /// Append an action; returns its index for annotations.
+    pub fn record(&mut self, round: u8, house: HouseName, action: Action) -> usize {
+        let index = self.actions.len();
+        self.actions.push(RecordedAction { index, round, house, action });
+        index
+    }
+
+    pub fn annotate(&mut self, annotation: Annotation) {
+        self.annotations.push(annotation);
+    }
+
+    /// Store the outcome of the finished game.
+    pub fn finish(&mut self, events: &[GameEvent], winner: HouseName, ranking: Vec<HouseName>) {
+        self.events = events.to_vec();
+        self.winner = Some(winner);
+        self.ranking = ranking;
+    }
+
+    pub fn annotations_on<'a>(&'a self, target: &'a AnnotationTarget) -> impl Iterator<Item = &'a Annotation> + 'a {
+        self.annotations.iter().filter(move |a| &a.target == target)
+    }
+
+    /// Re-apply every recorded action to a fresh game. Fails when an action
+    /// does not answer the decision the engine is waiting for (or comes from
+    /// the wrong house), or when the resulting event log differs from the
+    /// recorded one.
+    pub fn play(&self) -> Result<GameState, String> {
+        let mut state = create_game(&self.setup)?;
+        for recorded in &self.actions {
+            engine::advance(&mut state);
+            let pending = state.peek_pending()
+                .ok_or_else(|| format!("action {}: the game is not waiting for a decision", recorded.index))?;
+            if pending.house() != recorded.house {
+                return Err(format!("action {}: recorded for {}, but {} has to decide", recorded.index, recorded.house, pending.house()));
+            }
+            if !action_fits(pending, &recorded.action) {
+                return Err(format!("action {}: {:?} does not answer {:?}", recorded.index, recorded.action, pending));
+            }
+            engine::apply_action(&mut state, recorded.action.clone())
+                .map_err(|e| format!("action {}: {}", recorded.index, e))?;
+        }
+        engine::advance(&mut state);
+        if !self.events.is_empty() {
+            if let Some(seq) = self.events.iter().zip(&state.events).position(|(a, b)| a != b) {
+                return Err(format!("replay diverges from the recorded events at event {}", seq));
+            }
+            if self.events.len() != state.events.len() {
+                return Err(format!("replay produced {} events, {} were recorded", state.events.len(), self.events.len()));
+            }
+        }
+        Ok(state)
+    }
+
+    // ── JSON Lines ─────────────────────────────────────────────────────
+
+    pub fn to_jsonl(&self) -> String {
+        let header = Line::Header { version: self.version, setup: self.setup.clone(), players: self.players.clone() };
+        let mut lines = vec![header];
+        lines.extend(self.actions.iter().cloned().map(Line::Action));
+        lines.extend(self.events.iter().cloned().map(Line::Event));
+        lines.extend(self.annotations.iter().cloned().map(Line::Annotation));
+        if let Some(winner) = self.winner {
+            lines.push(Line::Result { winner, ranking: self.ranking.clone() });
+        }
+        lines.iter().map(|l| serde_json::to_string(l).unwrap() + "\n").collect()
+    }
+
+    /// Parse the JSON Lines form, upgrading lines written with an older
+    /// wire format version.
+    pub fn from_jsonl(text: &str) -> Result<Replay, String> {
+        let mut lines = text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+        let (_, first) = lines.next().ok_or("empty replay")?;
+        let mut replay = match serde_json::from_str(first).map_err(|e| format!("line 1: {}", e))? {
+            Line::Header { version, setup, players } => Replay { version, ..Replay::new(setup, players) },
+            _ => return Err("line 1: replay must start with a header".into()),
+        };
+        if replay.version > WIRE_VERSION {
+            return Err(format!("replay has wire format {}, this build reads up to {}", replay.version, WIRE_VERSION));
+        }
+        for (n, text) in lines {
+            let mut value: Value = serde_json::from_str(text).map_err(|e| format!("line {}: {}", n + 1, e))?;
+            if replay.version < WIRE_VERSION {
+                match value["type"].as_str() {
+                    Some("action") => value["action"] = wire::upgrade_action(value["action"].take()),
+                    Some("event") => value = wire::upgrade_event(value).map_err(|e| format!("line {}: {}", n + 1, e))?,
+                    _ => {}
+                }
+            }
+            match serde_json::from_value(value).map_err(|e| format!("line {}: {}", n + 1, e))? {
+                Line::Header { .. } => return Err(format!("line {}: second header", n + 1)),
+                Line::Action(a) => replay.actions.push(a),
+                Line::Event(e) => replay.events.push(e),
+                Line::Annotation(a) => replay.annotations.push(a),
+                Line::Result { winner, ranking } => {
+                    replay.winner = Some(winner);
+                    replay.ranking = ranking;
+                }
+            }
+        }
+        replay.version = WIRE_VERSION;
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.to_jsonl()).map_err(|e| format!("Cannot write {}: {}", path.display(), e))
+    }
+
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        Replay::from_jsonl(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+/// Add an annotation to a saved replay by appending one line.
+pub fn append_annotation(path: &Path, annotation: &Annotation) -> Result<(), String> {
+    let line = serde_json::to_string(&Line::Annotation(annotation.clone())).unwrap();
+    let mut file = OpenOptions::new().append(true).open(path)
+        .map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Cannot write {}: {}", path.display(), e))
+}
@@ -0,0 +1,142 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Rollouts — playing a position forward with some policy
+//
+// Playing every simulation to the end of round 10 is slow, and the
+// further a random or heuristic playout runs the noisier its result. A
+// `RolloutLimit` cuts the simulation short after a number of decisions or
+// rounds; the position it stops in is then scored by the static
+// evaluator (`eval::static_eval`) instead of by the finished game.
+//
+// `static_eval` of the final position is the rollout's value either way:
+// a finished game gives its winner 1 and everyone else 0, a truncated one
+// the evaluator's win probability.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::engine::{self, Action};
+use crate::types::*;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::fmt;
+use std::str::FromStr;
+
+/// Where a rollout stops short of the end of the game. Both limits count
+/// from the position the rollout starts in; the first one reached wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RolloutLimit {
+    /// Stop after this many decisions.
+    pub decisions: Option<usize>,
+    /// Stop once this many rounds have started after the first.
+    pub rounds: Option<u8>,
+}
+
+impl RolloutLimit {
+    /// Play to the end of the game.
+    pub const FULL: RolloutLimit = RolloutLimit { decisions: None, rounds: None };
+
+    pub fn decisions(n: usize) -> Self {
+        RolloutLimit { decisions: Some(n), rounds: None }
+    }
+
+    pub fn rounds(n: u8) -> Self {
+        RolloutLimit { decisions: None, rounds: Some(n) }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.decisions.is_none() && self.rounds.is_none()
+    }
+
+    fn reached(&self, start_round: u8, round: u8, decisions: usize) -> bool {
+        self.decisions.is_some_and(|n| decisions >= n)
+            || self.rounds.is_some_and(|n| round >= start_round.saturating_add(n))
+    }
+}
+
+impl FromStr for RolloutLimit {
+    type Err = String;
+
+    /// `full`, or `decisions=N` and/or `rounds=N` separated by commas.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("full") {
+            return Ok(RolloutLimit::FULL);
+        }
+        let mut limit = RolloutLimit::FULL;
+        for part in s.split(',').map(str::trim) {
+            let (key, value) = part.split_once('=')
+                .ok_or_else(|| format!("Expected full, decisions=N or rounds=N, got {}", part))?;
+            let bad = || format!("Invalid rollout limit: {}", part);
+            match key.trim() {
+                "decisions" => limit.decisions = Some(value.trim().parse().map_err(|_| bad())?),
+                "rounds" => limit.rounds = Some(value.trim().parse().map_err(|_| bad())?),
+                other => return Err(format!("Unknown rollout limit: {} (known: decisions, rounds)", other)),
+            }
+        }
+        Ok(limit)
+    }
+}
+
+impl fmt::Display for RolloutLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(n) = self.decisions {
+            parts.push(format!("decisions={}", n));
+        }
+        if let Some(n) = self.rounds {
+            parts.push(format!("rounds={}", n));
+        }
+        if parts.is_empty() {
+            write!(f, "full")
+        } else {
+            write!(f, "{}", parts.join(","))
+        }
+    }
+}
+
+/// How a rollout ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rollout {
+    /// Decisions played.
+    pub decisions: usize,
+    /// Stopped by the limit before the game was decided.
+    pub truncated: bool,
+}
+
+/// Continue `state` with `policy` answering every decision until the game
+/// is won or `limit` is reached. `max_decisions` guards against games that
+/// never finish and is an error, not a truncation.
+pub fn rollout(
+    state: &mut GameState,
+    limit: RolloutLimit,
+    max_decisions: usize,
+    mut policy: impl FnMut(&GameState, HouseName) -> Action,
+) -> Result<Rollout, String> {
+    let start_round = state.round;
+    for decisions in 0..max_decisions {
+        engine::advance(state);
+        if state.winner.is_some() {
+            return Ok(Rollout { decisions, truncated: false });
+        }
+        if limit.reached(start_round, state.round, decisions) {
+            return Ok(Rollout { decisions, truncated: true });
+        }
+        let house = match state.peek_pending() {
+            Some(p) => p.house(),
+            None => return Err(format!("Game stuck: phase={:?}, round={}", state.phase(), state.round)),
+        };
+        let action = policy(state, house);
+        engine::apply_action(state, action).map_err(|e| format!("{}'s rollout answer refused: {}", house, e))?;
+    }
+    Err(format!("Game exceeded {} decisions without finishing (round {})", max_decisions, state.round))
+}
+
+/// Shuffle the undrawn decks and move the engine's random stream, so a
+/// simulation doesn't follow the future the game was set up with.
+pub fn reshuffle_hidden(state: &mut GameState, seed: u64) {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    state.westeros_deck_1.shuffle(&mut rng);
+    state.westeros_deck_2.shuffle(&mut rng);
+    state.westeros_deck_3.shuffle(&mut rng);
+    state.wildling_deck.shuffle(&mut rng);
+    state.seed ^= seed;
+}
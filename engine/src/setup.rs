@@ -6,10 +6,13 @@
 use crate::types::*;
 use crate::map::*;
 use crate::cards;
+use crate::tracks;
 use rand::seq::SliceRandom;
 use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use rand_chacha::ChaCha8Rng;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
 /// Starting configuration for a house.
 struct HouseSetup {
@@ -94,17 +97,206 @@ fn house_setups() -> Vec<(HouseName, HouseSetup)> {
     ]
 }
 
+/// One line of the neutral force chart: the token's strength in `area`
+/// for 3, 4, 5 and 6 players (0 = no token). Areas in a house's starting
+/// zone name it as `absent`; they only get a token when it is not playing.
+struct NeutralForceRow {
+    area: AreaId,
+    absent: Option<HouseName>,
+    strengths: [u8; 4],
+}
+
+const fn row(area: AreaId, absent: Option<HouseName>, strengths: [u8; 4]) -> NeutralForceRow {
+    NeutralForceRow { area, absent, strengths }
+}
+
+/// Neutral force tokens by player count. In the 4-player game the Reach and
+/// Dorne are held by neutral forces throughout, the passes and Three Towers
+/// included; the Dornish garrisons grow in the 5-player game, where Martell
+/// is the only house left out.
+const NEUTRAL_FORCE_CHART: &[NeutralForceRow] = &[
+    row(KINGS_LANDING, None, [5, 5, 5, 5]),
+    row(THE_EYRIE, None, [6, 6, 6, 6]),
+    row(PYKE, Some(HouseName::Greyjoy), [5, 5, 5, 5]),
+    row(GREYWATER_WATCH, Some(HouseName::Greyjoy), [3, 3, 3, 3]),
+    row(HIGHGARDEN, Some(HouseName::Tyrell), [5, 5, 5, 5]),
+    row(OLDTOWN, Some(HouseName::Tyrell), [3, 3, 3, 3]),
+    row(DORNISH_MARCHES, Some(HouseName::Tyrell), [3, 3, 3, 3]),
+    row(THREE_TOWERS, Some(HouseName::Tyrell), [0, 3, 0, 0]),
+    row(SUNSPEAR, Some(HouseName::Martell), [5, 5, 5, 5]),
+    row(SALT_SHORE, Some(HouseName::Martell), [3, 3, 4, 4]),
+    row(YRONWOOD, Some(HouseName::Martell), [3, 3, 4, 4]),
+    row(STARFALL, Some(HouseName::Martell), [3, 3, 4, 4]),
+    row(PRINCES_PASS, Some(HouseName::Martell), [0, 3, 0, 0]),
+    row(THE_BONEWAY, Some(HouseName::Martell), [0, 3, 0, 0]),
+];
+
+/// Neutral force tokens for a house selection: the chart's tokens for its
+/// player count, with `GameOptions::neutral_forces` applied on top. Areas
+/// that are closed or start under a house's control are left to the caller.
+pub fn neutral_forces(houses: &[HouseName], options: &GameOptions) -> Vec<(AreaId, u8)> {
+    let player_count = houses.len() as u8;
+    let column = (player_count.clamp(3, 6) - 3) as usize;
+    let mut forces: Vec<(AreaId, u8)> = NEUTRAL_FORCE_CHART.iter()
+        .filter(|r| r.absent.is_none_or(|h| !houses.contains(&h)))
+        .map(|r| (r.area, r.strengths[column]))
+        .collect();
+    for n in options.neutral_forces.iter().filter(|n| n.player_count == player_count) {
+        match forces.iter_mut().find(|(area, _)| *area == n.area) {
+            Some(force) => force.1 = n.strength,
+            None => forces.push((n.area, n.strength)),
+        }
+    }
+    forces.retain(|&(_, strength)| strength > 0);
+    forces
+}
+
+// ── Setup configuration ────────────────────────────────────────────────
+
+/// Which houses play and how seats are assigned.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SetupConfig {
+    /// Houses taking part (3–6, no duplicates).
+    pub houses: Vec<HouseName>,
+    pub seed: u64,
+    /// Shuffle which seat (player slot) plays which house.
+    pub randomize_seats: bool,
+    /// Handicaps and other rule variations.
+    #[serde(default)]
+    pub options: GameOptions,
+}
+
+impl SetupConfig {
+    /// Official house selection for a player count.
+    pub fn new(player_count: u8, seed: u64) -> Self {
+        let houses = house_setups().into_iter()
+            .filter(|(_, s)| s.minimum_players <= player_count)
+            .map(|(h, _)| h)
+            .collect();
+        SetupConfig { houses, seed, randomize_seats: false, options: GameOptions::default() }
+    }
+
+    pub fn with_houses(houses: Vec<HouseName>, seed: u64) -> Self {
+        SetupConfig { houses, seed, randomize_seats: false, options: GameOptions::default() }
+    }
+
+    pub fn player_count(&self) -> u8 {
+        self.houses.len() as u8
+    }
+
+    /// Check the house selection is a legal combination.
+    pub fn validate(&self) -> Result<(), String> {
+        let pc = self.houses.len();
+        if !(3..=6).contains(&pc) {
+            return Err(format!("Player count must be 3–6, got {}", pc));
+        }
+        for (i, h) in self.houses.iter().enumerate() {
+            if self.houses[..i].contains(h) {
+                return Err(format!("House {} selected more than once", h));
+            }
+        }
+        for (i, h) in self.options.handicaps.iter().enumerate() {
+            if !self.houses.contains(&h.house) {
+                return Err(format!("Handicap for {}, which is not playing", h.house));
+            }
+            if self.options.handicaps[..i].iter().any(|o| o.house == h.house) {
+                return Err(format!("House {} has more than one handicap", h.house));
+            }
+            if let Some(ut) = h.removed_unit {
+                let starts_with = house_setups().into_iter()
+                    .any(|(name, setup)| name == h.house && setup.starting_units.iter().any(|(_, units)| units.contains(&ut)));
+                if !starts_with {
+                    return Err(format!("Handicap removes a {:?} from {}, which starts without one", ut, h.house));
+                }
+            }
+        }
+        for (i, n) in self.options.neutral_forces.iter().enumerate() {
+            if !(3..=6).contains(&n.player_count) {
+                return Err(format!("Neutral force for a {}-player game", n.player_count));
+            }
+            if !AREAS.get(n.area.0 as usize).is_some_and(|a| a.is_land()) {
+                return Err(format!("Neutral force in area {}, which is not a land area", n.area.0));
+            }
+            if self.options.neutral_forces[..i].iter().any(|o| o.player_count == n.player_count && o.area == n.area) {
+                return Err(format!("Neutral force in {} set more than once for {} players", area_name(n.area), n.player_count));
+            }
+        }
+        match self.options.victory {
+            VictoryCondition::Castles => {}
+            VictoryCondition::MostCastlesAfter { rounds } if !(1..=10).contains(&rounds) => {
+                return Err(format!("Most castles after {} rounds: must be 1–10", rounds));
+            }
+            VictoryCondition::KingOfTheHill { area, rounds } => {
+                if !AREAS.get(area.0 as usize).is_some_and(|a| a.is_land()) {
+                    return Err(format!("King of the hill on area {}, which is not a land area", area.0));
+                }
+                if !(1..=10).contains(&rounds) {
+                    return Err(format!("King of the hill for {} rounds: must be 1–10", rounds));
+                }
+            }
+            VictoryCondition::SupplyDomination { supply } if !(1..=6).contains(&supply) => {
+                return Err(format!("Supply domination at {}: the track runs 0–6", supply));
+            }
+            _ => {}
+        }
+        let blocked = blocked_areas(pc as u8);
+        for (h, setup) in house_setups() {
+            if self.houses.contains(&h) && blocked.contains(&setup.home_area) {
+                return Err(format!("House {} cannot play in a {}-player game: its home area is closed", h, pc));
+            }
+        }
+        Ok(())
+    }
+
+    /// Houses in seat order: seat i is played by `seats()[i]`.
+    /// Shuffled deterministically from the seed when `randomize_seats` is set.
+    pub fn seats(&self) -> Vec<HouseName> {
+        let mut seats = self.houses.clone();
+        if self.randomize_seats {
+            let mut rng = ChaCha8Rng::seed_from_u64(self.seed ^ SEAT_SEED_SALT);
+            seats.shuffle(&mut rng);
+        }
+        seats
+    }
+}
+
+/// Keeps seat shuffling independent of the deck shuffles for the same seed.
+const SEAT_SEED_SALT: u64 = 0x5EA7_5EA7_5EA7_5EA7;
+
+/// Areas closed to play for a player count (3-player: the southern map).
+fn blocked_areas(player_count: u8) -> &'static [AreaId] {
+    if player_count == 3 {
+        &[
+            SUNSPEAR, SALT_SHORE, STARFALL, YRONWOOD, PRINCES_PASS,
+            THE_BONEWAY, THREE_TOWERS, DORNISH_MARCHES,
+            HIGHGARDEN, OLDTOWN, THE_ARBOR,
+            SEA_OF_DORNE, EAST_SUMMER_SEA, WEST_SUMMER_SEA, REDWYNE_STRAITS,
+            SUNSPEAR_PORT, HIGHGARDEN_PORT, OLDTOWN_PORT,
+        ]
+    } else {
+        &[]
+    }
+}
+
 /// Create the initial game state for a given number of players (3–6).
 /// Seed controls deck shuffling for reproducibility.
 pub fn create_initial_state(player_count: u8, seed: u64) -> GameState {
     assert!((3..=6).contains(&player_count), "Player count must be 3–6");
+    create_game(&SetupConfig::new(player_count, seed)).expect("official house selection is legal")
+}
+
+/// Create the initial state for an explicit house selection.
+pub fn create_game(config: &SetupConfig) -> Result<GameState, String> {
+    config.validate()?;
+    let player_count = config.player_count();
+    let seed = config.seed;
 
     let mut rng = ChaCha8Rng::seed_from_u64(seed);
 
     let setups = house_setups();
     let playing: Vec<(HouseName, HouseSetup)> = setups
         .into_iter()
-        .filter(|(_, s)| s.minimum_players <= player_count)
+        .filter(|(h, _)| config.houses.contains(h))
         .collect();
 
     let playing_houses: Vec<HouseName> = playing.iter().map(|(h, _)| *h).collect();
@@ -124,16 +316,19 @@ pub fn create_initial_state(player_count: u8, seed: u64) -> GameState {
     let mut houses = HashMap::new();
     for (house_name, setup) in &playing {
         // Starting unit pool
-        let mut pool = UnitPool {
-            footmen: 10,
-            knights: 5,
-            ships: 6,
-            siege_engines: 2,
-        };
-
-        // Place starting units
-        for (area_id, unit_types) in &setup.starting_units {
-            for &ut in unit_types {
+        let mut pool = UnitPool::CAPACITY;
+
+        // Place starting units, less the one a handicap removes (the
+        // last of its type in the chart)
+        let handicap = config.options.handicap(*house_name);
+        let removed = handicap.and_then(|h| h.removed_unit)
+            .and_then(|ut| setup.starting_units.iter().enumerate().rev()
+                .find_map(|(i, (_, units))| units.iter().rposition(|&u| u == ut).map(|j| (i, j))));
+        for (i, (area_id, unit_types)) in setup.starting_units.iter().enumerate() {
+            for (j, &ut) in unit_types.iter().enumerate() {
+                if removed == Some((i, j)) {
+                    continue;
+                }
                 let unit = Unit {
                     unit_type: ut,
                     house: *house_name,
@@ -153,12 +348,13 @@ pub fn create_initial_state(player_count: u8, seed: u64) -> GameState {
             iron_throne: setup.iron_throne,
             fiefdoms: setup.fiefdoms,
             kings_court: setup.kings_court,
-            supply: setup.initial_supply,
-            power: 5,
+            supply: config.options.supply_level(*house_name, setup.initial_supply),
+            power: config.options.starting_power(*house_name),
             available_units: pool,
             hand: cards::all_house_card_ids(*house_name),
             discards: Vec::new(),
             used_order_tokens: Vec::new(),
+            card_stats: CardStats::default(),
         });
     }
 
@@ -173,50 +369,21 @@ pub fn create_initial_state(player_count: u8, seed: u64) -> GameState {
         }
     }
 
-    // Neutral garrisons for absent houses (5-player: no Martell; 4-player: no Greyjoy+Martell)
-    let absent_garrison_areas: Vec<(AreaId, u8)> = if player_count == 5 {
-        // Martell absent — garrison their home and adjacent areas
-        vec![(SUNSPEAR, 5), (SALT_SHORE, 3), (YRONWOOD, 3), (STARFALL, 3)]
-    } else if player_count == 4 {
-        // Greyjoy + Martell absent
-        vec![
-            (SUNSPEAR, 5), (SALT_SHORE, 3), (YRONWOOD, 3), (STARFALL, 3),
-            (PYKE, 5), (GREYWATER_WATCH, 3),
-        ]
-    } else {
-        Vec::new()
-    };
-    for (area, strength) in absent_garrison_areas {
-        garrisons.entry(area).or_insert(Garrison {
-            house: None, // neutral
-            strength,
-        });
+    // Block areas closed at this player count (3-player: southern regions)
+    for &area in blocked_areas(player_count) {
+        areas[area.0 as usize].blocked = true;
     }
 
-    // Block areas for 3-player game (southern regions)
-    if player_count == 3 {
-        let blocked_areas = [
-            SUNSPEAR, SALT_SHORE, STARFALL, YRONWOOD, PRINCES_PASS,
-            THE_BONEWAY, THREE_TOWERS, DORNISH_MARCHES,
-            HIGHGARDEN, OLDTOWN, THE_ARBOR,
-            SEA_OF_DORNE, EAST_SUMMER_SEA, WEST_SUMMER_SEA, REDWYNE_STRAITS,
-            SUNSPEAR_PORT, HIGHGARDEN_PORT, OLDTOWN_PORT,
-        ];
-        for &area in &blocked_areas {
-            areas[area.0 as usize].blocked = true;
+    // Neutral force tokens from the chart (skipping blocked areas)
+    for (area, strength) in neutral_forces(&playing_houses, &config.options) {
+        if !areas[area.0 as usize].blocked && areas[area.0 as usize].house.is_none() {
+            garrisons.entry(area).or_insert(Garrison {
+                house: None, // neutral
+                strength,
+            });
         }
     }
 
-    // Neutral garrison at King's Landing and The Eyrie (always)
-    garrisons.entry(KINGS_LANDING).or_insert(Garrison {
-        house: None, // neutral
-        strength: 5,
-    });
-    garrisons.entry(THE_EYRIE).or_insert(Garrison {
-        house: None, // neutral
-        strength: 6,
-    });
-
     // Shuffle decks
     let mut deck1 = cards::westeros_deck_1();
     let mut deck2 = cards::westeros_deck_2();
@@ -227,11 +394,9 @@ pub fn create_initial_state(player_count: u8, seed: u64) -> GameState {
     deck3.shuffle(&mut rng);
     wildling.shuffle(&mut rng);
 
-    GameState {
+    let mut state = GameState {
         round: 1,
-        phase: Phase::Planning, // Westeros skipped on round 1
-        action_sub_phase: ActionSubPhase::Raid,
-        action_player_index: 0,
+        engine_phase: EnginePhase::planning(), // Westeros skipped on round 1
         houses,
         areas,
         turn_order,
@@ -245,17 +410,22 @@ pub fn create_initial_state(player_count: u8, seed: u64) -> GameState {
         wildling_deck: wildling,
         order_restrictions: Vec::new(),
         star_order_restrictions: Vec::new(),
-        combat: None,
-        bidding: None,
-        westeros_cards_drawn: Vec::new(),
-        westeros_step: 0,
-        muster_house_idx: 0,
         seed,
-        rng_counter: 0,
-        pending: None,
+        rng_counters: RngCounters::default(),
+        pending: VecDeque::new(),
         winner: None,
         playing_houses,
-    }
+        events: Vec::new(),
+        options: config.options.canonical(),
+        resigned: Vec::new(),
+        draw_offers: Vec::new(),
+        drawn: false,
+        hill_holder: None,
+    };
+
+    // Starting positions are for the 6-player board; close the gaps
+    tracks::normalize(&mut state);
+    Ok(state)
 }
 
 #[cfg(test)]
@@ -267,7 +437,7 @@ mod tests {
         let state = create_initial_state(6, 42);
         assert_eq!(state.playing_houses.len(), 6);
         assert_eq!(state.round, 1);
-        assert_eq!(state.phase, Phase::Planning);
+        assert_eq!(state.phase(), Phase::Planning);
         assert_eq!(state.wildling_threat, 2);
 
         // Baratheon should be first in turn order (iron throne = 1)
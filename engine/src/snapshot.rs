@@ -0,0 +1,96 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Snapshots — canonical games pinned against rule regressions
+//
+// A fixed list of (player count, seed) games is played to the end with
+// `random_legal_action`, each decision drawing from an RNG keyed by the
+// seed and the decision number. What a game ends in is summarized by a
+// hash of the final state (its JSON with object keys sorted, FNV-1a), the
+// number of events and rounds, and the winner.
+//
+// The stored summaries live in `engine/snapshots/canonical.json`. A rule
+// change that alters any of these games fails the engine tests; when the
+// change is deliberate, `got-runner snapshots --bless` rewrites the file.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::engine::{advance, apply_action, random_legal_action};
+use crate::setup::create_initial_state;
+use crate::types::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+/// The games in the suite: (player count, seed).
+pub const CANONICAL_GAMES: &[(u8, u64)] = &[
+    (3, 1), (4, 2), (5, 3), (6, 4), (6, 5), (6, 6),
+];
+
+/// Decisions after which a canonical game is cut off, should one never end.
+const MAX_DECISIONS: u64 = 100_000;
+
+/// How a canonical game ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub players: u8,
+    pub seed: u64,
+    /// `state_hash` of the final state, as 16 hex digits.
+    pub state_hash: String,
+    pub events: usize,
+    pub rounds: u8,
+    pub winner: Option<HouseName>,
+}
+
+/// FNV-1a of the state's JSON. Going through `serde_json::Value` sorts
+/// object keys, so the hash does not depend on `HashMap` iteration order.
+pub fn state_hash(state: &GameState) -> u64 {
+    let json = serde_json::to_value(state).expect("game state serializes").to_string();
+    json.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3))
+}
+
+/// Play one canonical game to the end.
+pub fn play_canonical(players: u8, seed: u64) -> Snapshot {
+    let mut state = create_initial_state(players, seed);
+    advance(&mut state);
+    let mut step = 0u64;
+    while state.winner.is_none() && step < MAX_DECISIONS {
+        step += 1;
+        let mut rng = ChaCha8Rng::seed_from_u64(seed.wrapping_add(step.wrapping_mul(999_961)));
+        let Some(action) = random_legal_action(&state, &mut rng) else { break };
+        apply_action(&mut state, action).expect("a random legal action is accepted");
+        advance(&mut state);
+    }
+    Snapshot {
+        players,
+        seed,
+        state_hash: format!("{:016x}", state_hash(&state)),
+        events: state.events.len(),
+        rounds: state.round,
+        winner: state.winner,
+    }
+}
+
+/// Every game in `CANONICAL_GAMES`, in order.
+pub fn canonical_snapshots() -> Vec<Snapshot> {
+    CANONICAL_GAMES.iter().map(|&(players, seed)| play_canonical(players, seed)).collect()
+}
+
+/// One line per game whose snapshot differs from the stored one, or that
+/// is missing on either side. Empty when they all match.
+pub fn diff(stored: &[Snapshot], actual: &[Snapshot]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for a in actual {
+        match stored.iter().find(|s| s.players == a.players && s.seed == a.seed) {
+            None => lines.push(format!("{}p seed {}: no stored snapshot", a.players, a.seed)),
+            Some(s) if s != a => lines.push(format!(
+                "{}p seed {}: stored {} ({} events, round {}, winner {:?}), now {} ({} events, round {}, winner {:?})",
+                a.players, a.seed, s.state_hash, s.events, s.rounds, s.winner, a.state_hash, a.events, a.rounds, a.winner,
+            )),
+            Some(_) => {}
+        }
+    }
+    for s in stored {
+        if !actual.iter().any(|a| a.players == s.players && a.seed == s.seed) {
+            lines.push(format!("{}p seed {}: stored but no longer in the suite", s.players, s.seed));
+        }
+    }
+    lines
+}
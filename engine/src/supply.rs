@@ -4,18 +4,33 @@
 
 use crate::types::*;
 use crate::map::AREAS;
+use crate::navigation::Board;
 
 /// Check if a house violates supply limits.
 /// Returns true if supply is violated.
 pub fn check_supply_violation(state: &GameState, house: HouseName) -> bool {
-    let supply = state.house(house).supply.min(6);
-    let limits = supply_limits(supply);
+    !allows_units(state, house, &[])
+}
+
+/// Whether `house` keeps within its supply limits with one more of its
+/// units in each of `added` (an area may repeat).
+pub fn allows_units(state: &GameState, house: HouseName, added: &[AreaId]) -> bool {
+    board_allows_units(state, state.house(house).supply, house, added)
+}
+
+/// `allows_units` on any board, a `PlayerView` included, for a house at
+/// `supply`.
+pub fn board_allows_units(board: &impl Board, supply: u8, house: HouseName, added: &[AreaId]) -> bool {
+    let limits = supply_limits(supply.min(6));
 
     // Collect armies: groups of 2+ units in same area
     let mut armies: Vec<u8> = Vec::new();
-    for area_state in state.areas.iter() {
-        if area_state.house == Some(house) && area_state.units.len() >= 2 {
-            armies.push(area_state.units.len() as u8);
+    for i in 0..AREAS.len() {
+        let area = AreaId(i as u8);
+        let arriving = added.iter().filter(|a| a.0 as usize == i).count();
+        let size = board.units(area).len() + arriving;
+        if (board.controller(area) == Some(house) || arriving > 0) && size >= 2 {
+            armies.push(size as u8);
         }
     }
 
@@ -24,17 +39,11 @@ pub fn check_supply_violation(state: &GameState, house: HouseName) -> bool {
 
     // More armies than slots? Violation.
     if armies.len() > limits.len() {
-        return true;
+        return false;
     }
 
     // Each army must fit in its corresponding slot
-    for (i, &army_size) in armies.iter().enumerate() {
-        if army_size > limits[i] {
-            return true;
-        }
-    }
-
-    false
+    armies.iter().zip(limits).all(|(&army_size, &limit)| army_size <= limit)
 }
 
 /// Check supply limits for all playing houses.
@@ -46,14 +55,16 @@ pub fn check_all_supply_violations(state: &GameState) -> Vec<(HouseName, bool)>
 }
 
 /// Calculate supply level for a house based on controlled supply icons.
+/// Only land counts; seas and ports never carry barrels. A handicap's
+/// supply adjustment is added to the count.
 pub fn calculate_supply(state: &GameState, house: HouseName) -> u8 {
     let mut total: u8 = 0;
     for (i, area_state) in state.areas.iter().enumerate() {
-        if area_state.house == Some(house) {
+        if area_state.house == Some(house) && AREAS[i].is_land() {
             total += AREAS[i].supply_icons;
         }
     }
-    total.min(6)
+    state.options.supply_level(house, total)
 }
 
 /// Find which armies violate supply limits and by how much.
@@ -69,7 +80,7 @@ pub fn find_violations(state: &GameState, house: HouseName) -> Vec<(AreaId, u8,
             armies.push((AreaId(i as u8), area_state.units.len() as u8));
         }
     }
-    armies.sort_unstable_by(|a, b| b.1.cmp(&a.1));
+    armies.sort_unstable_by_key(|&(_, size)| std::cmp::Reverse(size));
 
     let mut violations = Vec::new();
     for (idx, &(area_id, size)) in armies.iter().enumerate() {
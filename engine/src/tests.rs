@@ -3,14 +3,17 @@
 // ═══════════════════════════════════════════════════════════════════════
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use crate::types::*;
     use crate::map::*;
     use crate::cards;
     use crate::supply;
-    use crate::engine::{advance, apply_action, Action, MusterAction2};
+    use crate::engine::{advance, apply_action, check_victory, final_ranking, final_score, random_legal_action, set_area_control, Action, ActionError, MusterAction2};
     use crate::navigation;
-    use crate::setup::create_initial_state;
+    use crate::setup::{create_game, create_initial_state, SetupConfig};
+    use crate::tracks;
+    use std::collections::{HashMap, VecDeque};
 
     // ── Helper: create a minimal state for unit testing ──────────────────
 
@@ -29,142 +32,145 @@ mod tests {
         let mut step = 0u64;
         while state.winner.is_none() && step < 100_000 {
             step += 1;
-            let pending = match &state.pending {
-                Some(p) => p.clone(),
-                None => break,
-            };
             let mut rng = ChaCha8Rng::seed_from_u64(seed.wrapping_add(step.wrapping_mul(999961)));
-            let action = random_action(&pending, &state, &mut rng);
-            apply_action(&mut state, action);
+            let Some(action) = random_legal_action(&state, &mut rng) else { break };
+            apply_action(&mut state, action).unwrap();
             advance(&mut state);
         }
         state
     }
 
-    /// Produce a random action for a given pending decision.
-    fn random_action(
-        pending: &PendingDecision,
-        state: &GameState,
-        rng: &mut impl rand::Rng,
-    ) -> Action {
-        use rand::seq::SliceRandom;
+    /// An action of the wrong kind for `pending`.
+    fn stray_action(pending: &PendingDecision) -> Action {
         match pending {
-            PendingDecision::PlaceOrders { house } => {
-                let my_areas: Vec<AreaId> = state.areas.iter().enumerate()
-                    .filter(|(_, a)| a.house == Some(*house) && !a.units.is_empty())
-                    .map(|(i, _)| AreaId(i as u8))
-                    .collect();
-                let mut orders = Vec::new();
-                let mut used: Vec<u8> = Vec::new();
-                for area in &my_areas {
-                    let available: Vec<u8> = (0..15u8)
-                        .filter(|t| !used.contains(t))
-                        .collect();
-                    if let Some(&t) = available.choose(rng) {
-                        orders.push((*area, t));
-                        used.push(t);
-                    }
+            PendingDecision::Bidding { .. } => Action::MarchSkip,
+            _ => Action::Bid(0),
+        }
+    }
+
+    proptest::proptest! {
+        #![proptest_config(proptest::prelude::ProptestConfig::with_cases(40))]
+
+        /// From every state random play reaches, the engine waits on a
+        /// decision or has a winner, is in the combat phase exactly while a
+        /// combat is on, and never asks the decision it was just given an
+        /// answer to again within the same phase. An action of the wrong
+        /// kind is refused and leaves the decision open.
+        #[test]
+        fn prop_state_machine_never_strands(seed in 0u64..1_000_000, players in 3u8..=6, stray_every in 2u64..50) {
+            use rand::SeedableRng;
+            let mut state = create_initial_state(players, seed);
+            advance(&mut state);
+            let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(seed);
+            for step in 0..20_000u64 {
+                if state.winner.is_some() {
+                    break;
                 }
-                Action::PlaceOrders(orders)
-            }
-            PendingDecision::ChooseRaid { valid_targets, .. } => {
-                if valid_targets.is_empty() || rng.gen_bool(0.3) {
-                    Action::Raid(None)
-                } else {
-                    Action::Raid(Some(*valid_targets.choose(rng).unwrap()))
+                let pending = state.peek_pending().cloned();
+                proptest::prop_assert!(pending.is_some(), "stranded in {:?} at step {}", state.phase(), step);
+                let pending = pending.unwrap();
+                if step % stray_every == 0 {
+                    let refused = apply_action(&mut state, stray_action(&pending));
+                    proptest::prop_assert!(matches!(refused, Err(ActionError::WrongDecisionType { .. })), "{:?}", refused);
+                    proptest::prop_assert_eq!(format!("{:?}", state.peek_pending()), format!("{:?}", Some(&pending)));
                 }
-            }
-            PendingDecision::ChooseMarch { from_area, valid_destinations, .. } => {
-                if valid_destinations.is_empty() {
-                    Action::MarchSkip
-                } else {
-                    let to = *valid_destinations.choose(rng).unwrap();
-                    let unit_count = state.area(*from_area).units.len();
-                    let indices: Vec<usize> = (0..unit_count).collect();
-                    Action::March { to, unit_indices: indices }
+
+                let (phase, round) = (state.phase(), state.round);
+                let action = random_legal_action(&state, &mut rng).unwrap();
+                apply_action(&mut state, action).unwrap();
+                proptest::prop_assert_eq!(state.check_combat(), Ok(()));
+                if state.phase() == phase && state.round == round {
+                    if let Some(next) = state.peek_pending() {
+                        proptest::prop_assert_ne!(format!("{:?}", next), format!("{:?}", pending), "asked again at step {}", step);
+                    }
                 }
             }
-            PendingDecision::LeavePowerToken { .. } => {
-                Action::LeavePowerToken(rng.gen_bool(0.5))
-            }
-            PendingDecision::SupportDeclaration { .. } => {
-                let choices = [SupportChoice::Attacker, SupportChoice::Defender, SupportChoice::None];
-                Action::DeclareSupport(*choices.choose(rng).unwrap())
-            }
-            PendingDecision::SelectHouseCard { available_cards, .. } => {
-                Action::SelectCard(*available_cards.choose(rng).unwrap())
-            }
-            PendingDecision::UseValyrianBlade { .. } => {
-                Action::UseValyrianBlade(rng.gen_bool(0.5))
-            }
-            PendingDecision::Bidding { house, .. } => {
-                let max = state.house(*house).power;
-                Action::Bid(rng.gen_range(0..=max))
-            }
-            PendingDecision::WesterosChoice { options, .. } => {
-                Action::WesterosChoice(rng.gen_range(0..options.len()))
+        }
+    }
+
+    proptest::proptest! {
+        #![proptest_config(proptest::prelude::ProptestConfig::with_cases(64))]
+
+        /// On a board strewn with random orders, every raid target is
+        /// adjacent, within the raid's land/sea reach, and holds another
+        /// house's order of a kind the raid may remove; and every such
+        /// area is offered.
+        #[test]
+        fn prop_raid_targets_follow_the_legal_matrix(seed in 0u64..1_000_000, players in 3u8..=6) {
+            use crate::engine::{find_raid_targets, raid_reaches, raid_removes};
+            use rand::{Rng, SeedableRng};
+            let mut state = create_initial_state(players, seed);
+            let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(seed);
+            for area in state.areas.iter_mut() {
+                area.order = None;
+                if rng.gen_bool(0.6) {
+                    let token_index = rng.gen_range(0..ORDER_TOKENS.len());
+                    let token = ORDER_TOKENS[token_index];
+                    let house = state.playing_houses[rng.gen_range(0..state.playing_houses.len())];
+                    area.order = Some(Order {
+                        order_type: token.order_type, strength: token.strength, star: token.star, house, token_index: token_index as u8,
+                    });
+                }
             }
-            PendingDecision::Muster { areas, .. } => {
-                // Simple: build one footman per area if possible
-                let mut actions = Vec::new();
-                for area in areas {
-                    if area.points >= 1 {
-                        actions.push((area.area_id, MusterAction2::Build(UnitType::Footman)));
+            for from in (0..NUM_AREAS).map(|i| AreaId(i as u8)) {
+                for &house in &state.playing_houses {
+                    let targets = find_raid_targets(&state, from, house);
+                    let raid = state.area(from).order.filter(|o| o.order_type == OrderType::Raid && o.house == house);
+                    let Some(raid) = raid else {
+                        proptest::prop_assert!(targets.is_empty(), "{:?} raids from {:?} without a raid order", house, from);
+                        continue;
+                    };
+                    let from_def = &AREAS[from.0 as usize];
+                    for adj in from_def.adjacent.iter().copied() {
+                        let order = state.area(adj).order;
+                        let legal = raid_reaches(from_def.area_type, AREAS[adj.0 as usize].area_type)
+                            && order.is_some_and(|o| o.house != house && raid_removes(raid.star, o.order_type));
+                        proptest::prop_assert_eq!(targets.contains(&adj), legal, "{:?} raid from {:?} on {:?}", house, from, adj);
+                    }
+                    for t in &targets {
+                        proptest::prop_assert!(from_def.adjacent.contains(t));
+                        let order = state.area(*t).order.unwrap();
+                        proptest::prop_assert!(order.order_type != OrderType::March);
+                        proptest::prop_assert!(raid.star || order.order_type != OrderType::Defense);
+                        proptest::prop_assert!(!from_def.is_land() || AREAS[t.0 as usize].is_land());
                     }
                 }
-                Action::Muster(actions)
-            }
-            PendingDecision::Retreat { possible_areas, .. } => {
-                if possible_areas.is_empty() {
-                    // Units are destroyed
-                    Action::Retreat(AreaId(255))
-                } else {
-                    Action::Retreat(*possible_areas.choose(rng).unwrap())
-                }
-            }
-            PendingDecision::Reconcile { area_id, .. } => {
-                Action::Reconcile(*area_id, 0)
-            }
-            PendingDecision::MessengerRaven { .. } => {
-                Action::MessengerRaven(None)
-            }
-            PendingDecision::AeronSwap { .. } => {
-                Action::AeronSwap(None)
-            }
-            PendingDecision::TyrionReplace { opponent } => {
-                let hand = &state.house(*opponent).hand;
-                Action::TyrionReplace(*hand.first().unwrap_or(&HouseCardId::EddardStark))
-            }
-            PendingDecision::PatchfaceDiscard { visible_cards, .. } => {
-                Action::PatchfaceDiscard(*visible_cards.first().unwrap())
-            }
-            PendingDecision::RobbRetreat { possible_areas, .. } => {
-                Action::RobbRetreat(*possible_areas.first().unwrap())
-            }
-            PendingDecision::WildlingPenaltyChoice { options, .. } => {
-                Action::WildlingPenalty(rng.gen_range(0..options.len()))
-            }
-            PendingDecision::CerseiRemoveOrder { .. } => {
-                // find any area with an order
-                let area = state.areas.iter().enumerate()
-                    .find(|(_, a)| a.order.is_some())
-                    .map(|(i, _)| AreaId(i as u8))
-                    .unwrap_or(AreaId(0));
-                Action::CerseiRemoveOrder(area)
-            }
-            PendingDecision::DoranChooseTrack { .. } => {
-                Action::DoranChooseTrack(Track::IronThrone)
-            }
-            PendingDecision::QueenOfThornsRemoveOrder { .. } => {
-                let area = state.areas.iter().enumerate()
-                    .find(|(_, a)| a.order.is_some())
-                    .map(|(i, _)| AreaId(i as u8))
-                    .unwrap_or(AreaId(0));
-                Action::QueenOfThorns(area)
             }
         }
     }
 
+    #[test]
+    fn test_raid_reach_and_order_owner() {
+        use crate::engine::{find_raid_targets, raid_reaches};
+        assert!(raid_reaches(AreaType::Land, AreaType::Land));
+        assert!(!raid_reaches(AreaType::Land, AreaType::Sea));
+        assert!(!raid_reaches(AreaType::Land, AreaType::Port));
+        assert!(raid_reaches(AreaType::Sea, AreaType::Land));
+        assert!(raid_reaches(AreaType::Sea, AreaType::Port));
+        assert!(raid_reaches(AreaType::Port, AreaType::Sea));
+        assert!(!raid_reaches(AreaType::Port, AreaType::Land));
+
+        let token = |order_type: OrderType| ORDER_TOKENS.iter().position(|t| t.order_type == order_type && !t.star).unwrap() as u8;
+        let order = |house, order_type| Some(Order { order_type, strength: 0, star: false, house, token_index: token(order_type) });
+        let mut state = make_6p_state(42);
+        for area in state.areas.iter_mut() {
+            area.order = None;
+        }
+        state.area_mut(WINTERFELL).order = order(HouseName::Stark, OrderType::Raid);
+        // Moat Cailin is Lannister's, but the order on it is Stark's own
+        state.area_mut(MOAT_CAILIN).house = Some(HouseName::Lannister);
+        state.area_mut(MOAT_CAILIN).order = order(HouseName::Stark, OrderType::Support);
+        // An enemy order counts even where no house holds the area
+        state.area_mut(KARHOLD).house = None;
+        state.area_mut(KARHOLD).order = order(HouseName::Greyjoy, OrderType::ConsolidatePower);
+        // The adjacent sea is out of a land raid's reach
+        let sea = AREAS[WINTERFELL.0 as usize].adjacent.iter().copied().find(|a| AREAS[a.0 as usize].is_sea()).unwrap();
+        state.area_mut(sea).order = order(HouseName::Greyjoy, OrderType::Support);
+        assert_eq!(find_raid_targets(&state, WINTERFELL, HouseName::Stark), vec![KARHOLD]);
+        // Someone else's raid order in Winterfell is not Stark's to resolve
+        assert!(find_raid_targets(&state, WINTERFELL, HouseName::Greyjoy).is_empty());
+    }
+
     // ═════════════════════════════════════════════════════════════════════
     // SETUP TESTS
     // ═════════════════════════════════════════════════════════════════════
@@ -174,10 +180,10 @@ mod tests {
         let state = make_6p_state(42);
         assert_eq!(state.playing_houses.len(), 6);
         assert_eq!(state.round, 1);
-        assert_eq!(state.phase, Phase::Planning); // Round 1 skips Westeros
+        assert_eq!(state.phase(), Phase::Planning); // Round 1 skips Westeros
         assert_eq!(state.wildling_threat, 2);
         assert!(state.winner.is_none());
-        assert!(state.combat.is_none());
+        assert!(state.combat().is_none());
     }
 
     #[test]
@@ -259,7 +265,7 @@ mod tests {
     fn test_neutral_garrisons_5p() {
         let state = create_initial_state(5, 42);
         // 5-player: Martell excluded, Dornish areas get neutral garrisons
-        assert!(state.garrisons.get(&SUNSPEAR).is_some());
+        assert!(state.garrisons.contains_key(&SUNSPEAR));
         assert_eq!(state.garrisons.get(&SUNSPEAR).unwrap().strength, 5);
         assert_eq!(state.garrisons.get(&SUNSPEAR).unwrap().house, None);
     }
@@ -469,6 +475,363 @@ mod tests {
         assert!(!dests.is_empty(), "Ships should be able to move from Shivering Sea");
     }
 
+    #[test]
+    fn test_destinations_respect_terrain() {
+        let state = make_6p_state(42);
+        let land = navigation::valid_destinations(&state, WINTERFELL, HouseName::Stark);
+        assert!(land.iter().all(|&a| AREAS[a.0 as usize].is_land()), "{:?}", land);
+        assert!(!land.contains(&BAY_OF_ICE) && !land.contains(&WINTERFELL_PORT));
+        let sea = navigation::valid_destinations(&state, THE_SHIVERING_SEA, HouseName::Stark);
+        assert!(sea.iter().all(|&a| !AREAS[a.0 as usize].is_land()), "{:?}", sea);
+
+        // A port's ships may only put out to sea
+        assert_eq!(navigation::valid_destinations_for(&state, WINTERFELL_PORT, HouseName::Stark, &[UnitType::Ship]), vec![BAY_OF_ICE]);
+        // Ships and footmen can't travel together
+        let mixed = [UnitType::Ship, UnitType::Footman];
+        assert!(navigation::valid_destinations_for(&state, WINTERFELL, HouseName::Stark, &mixed).is_empty());
+        assert!(navigation::can_enter(UnitType::SiegeEngine, WINTERFELL));
+        assert!(!navigation::can_enter(UnitType::Knight, THE_SHIVERING_SEA));
+    }
+
+    /// The search `navigation::is_move_valid` used to run for every
+    /// destination on its own, kept to check the single pass against.
+    fn is_move_valid_per_destination(state: &GameState, from: AreaId, to: AreaId, house: HouseName) -> bool {
+        let from_def = &AREAS[from.0 as usize];
+        let to_def = &AREAS[to.0 as usize];
+        if state.area(to).blocked {
+            return false;
+        }
+        if from_def.adjacent.contains(&to) || to_def.connected_sea == Some(from) {
+            return true;
+        }
+        if !from_def.is_land() || to_def.is_sea() {
+            return false;
+        }
+        let friendly = |a: AreaId| state.area(a).units.iter().any(|u| u.unit_type == UnitType::Ship && u.house == house);
+        let mut queue = VecDeque::from([from]);
+        let mut visited = [false; NUM_AREAS];
+        visited[from.0 as usize] = true;
+        while let Some(current) = queue.pop_front() {
+            let current_def = &AREAS[current.0 as usize];
+            for &adj in current_def.adjacent {
+                if visited[adj.0 as usize] {
+                    continue;
+                }
+                if adj == to {
+                    if current_def.is_sea() && friendly(current) {
+                        return true;
+                    }
+                    continue;
+                }
+                if AREAS[adj.0 as usize].is_sea() && friendly(adj) {
+                    visited[adj.0 as usize] = true;
+                    queue.push_back(adj);
+                }
+            }
+        }
+        false
+    }
+
+    #[test]
+    fn test_move_destinations_match_the_per_destination_search() {
+        use rand::{Rng, SeedableRng};
+        use rand_chacha::ChaCha8Rng;
+
+        let mut checked = 0;
+        for seed in 1..=3u64 {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            let mut state = create_initial_state(6, seed);
+            advance(&mut state);
+            for step in 0..600 {
+                if state.winner.is_some() {
+                    break;
+                }
+                if step % 100 == 0 {
+                    // Scatter extra ships for longer transport chains
+                    let mut board = state.clone();
+                    let houses = board.playing_houses.clone();
+                    for (i, area) in board.areas.iter_mut().enumerate() {
+                        if AREAS[i].is_sea() && rng.gen_bool(0.3) {
+                            let house = houses[rng.gen_range(0..houses.len())];
+                            area.units.push(Unit { unit_type: UnitType::Ship, house, routed: false });
+                        }
+                    }
+                    if rng.gen_bool(0.5) {
+                        board.areas[MOAT_CAILIN.0 as usize].blocked = true;
+                    }
+                    for board in [&state, &board] {
+                        for &house in &board.playing_houses {
+                            for from in (0..NUM_AREAS).map(|i| AreaId(i as u8)) {
+                                let per_destination: Vec<AreaId> = (0..NUM_AREAS).map(|i| AreaId(i as u8))
+                                    .filter(|&to| is_move_valid_per_destination(board, from, to, house))
+                                    .collect();
+                                assert_eq!(navigation::move_destinations(board, from, house), per_destination,
+                                           "{:?} from {}", house, area_name(from));
+                                checked += 1;
+                            }
+                        }
+                    }
+                }
+                let Some(action) = random_legal_action(&state, &mut rng) else { break };
+                apply_action(&mut state, action).unwrap();
+                advance(&mut state);
+            }
+        }
+        assert!(checked > 0);
+    }
+
+    #[test]
+    fn test_reachable_within() {
+        let mut state = make_6p_state(42);
+        let one = navigation::reachable_within(&state, WINTERFELL, HouseName::Stark, 1);
+        let mut direct: Vec<AreaId> = one.iter().map(|&(a, m)| { assert_eq!(m, 1); a }).collect();
+        direct.sort_by_key(|a| a.0);
+        assert_eq!(direct, navigation::valid_destinations_for(&state, WINTERFELL, HouseName::Stark, &[UnitType::Footman]));
+
+        let two = navigation::reachable_within(&state, WINTERFELL, HouseName::Stark, 2);
+        assert!(one.iter().all(|r| two.contains(r)));
+        assert!(two.len() > one.len());
+        assert!(two.iter().all(|&(a, m)| AREAS[a.0 as usize].is_land() && a != WINTERFELL && (m == 1 || m == 2)));
+        // Moat Cailin is next door; The Twins only one march further
+        assert!(two.contains(&(MOAT_CAILIN, 1)));
+        assert!(two.contains(&(THE_TWINS, 2)));
+
+        // Blocked areas are never entered
+        state.areas[MOAT_CAILIN.0 as usize].blocked = true;
+        let around = navigation::reachable_within(&state, WINTERFELL, HouseName::Stark, 2);
+        assert!(!around.iter().any(|&(a, _)| a == MOAT_CAILIN));
+
+        // Ships stay at sea
+        let fleet = navigation::reachable_within(&state, THE_SHIVERING_SEA, HouseName::Stark, 2);
+        assert!(fleet.iter().all(|&(a, _)| !AREAS[a.0 as usize].is_land()));
+    }
+
+    #[test]
+    fn test_static_map_round_trip() {
+        let map = static_map();
+        assert_eq!(map.areas.len(), NUM_AREAS);
+        for (i, area) in map.areas.iter().enumerate() {
+            let def = &AREAS[i];
+            assert_eq!(area.id, AreaId(i as u8));
+            assert_eq!(area.name, def.name);
+            assert_eq!(area.adjacent, def.adjacent);
+            assert_eq!((area.supply_icons, area.power_icons), (def.supply_icons, def.power_icons));
+        }
+        let port = &map.areas[WINTERFELL_PORT.0 as usize];
+        assert_eq!((port.area_type, port.connected_land), (AreaType::Port, Some(WINTERFELL)));
+
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(serde_json::from_str::<StaticMap>(&json).unwrap(), map);
+    }
+
+    #[test]
+    fn test_map_analysis() {
+        use crate::map::analysis::{self, Transport};
+
+        assert_eq!(analysis::distance(WINTERFELL, WINTERFELL, Transport::Land), Some(0));
+        assert_eq!(analysis::distance(WINTERFELL, THE_TWINS, Transport::Land), Some(2));
+        assert_eq!(analysis::distance(WINTERFELL, THE_SHIVERING_SEA, Transport::Land), None);
+        // Islands have no land route; a ship chain reaches any coast in one march
+        assert_eq!(analysis::distance(WINTERFELL, PYKE, Transport::Land), None);
+        assert_eq!(analysis::distance(WINTERFELL, SUNSPEAR, Transport::Ships), Some(1));
+        for a in 0..NUM_AREAS as u8 {
+            for b in 0..NUM_AREAS as u8 {
+                let (a, b) = (AreaId(a), AreaId(b));
+                let (land, ships) = (analysis::distance(a, b, Transport::Land), analysis::distance(a, b, Transport::Ships));
+                assert!(ships.is_some() || !AREAS[a.0 as usize].is_land() || !AREAS[b.0 as usize].is_land());
+                if let (Some(l), Some(s)) = (land, ships) {
+                    assert!(s <= l);
+                }
+            }
+        }
+
+        // Moat Cailin holds the North off from the rest of Westeros
+        let chokepoints = analysis::chokepoints();
+        let moat = chokepoints.iter().find(|c| c.area == MOAT_CAILIN).expect("Moat Cailin is a chokepoint");
+        assert!(moat.cut_off.contains(&WINTERFELL) && !moat.cut_off.contains(&THE_TWINS));
+        assert!(!chokepoints.iter().any(|c| c.area == KINGS_LANDING));
+
+        let houses = [HouseName::Stark, HouseName::Lannister, HouseName::Baratheon, HouseName::Greyjoy, HouseName::Tyrell, HouseName::Martell];
+        let regions = analysis::home_regions(&houses);
+        for &h in &houses {
+            assert_eq!(regions[analysis::home_area(h).0 as usize], Some(h));
+        }
+        assert_eq!(regions[CASTLE_BLACK.0 as usize], Some(HouseName::Stark));
+        assert!(regions.iter().enumerate().all(|(i, r)| r.is_none() || AREAS[i].is_land()));
+        // Fewer houses, larger regions
+        let three = analysis::home_region(&houses[..3], HouseName::Lannister);
+        assert!(three.len() > analysis::home_region(&houses, HouseName::Lannister).len());
+    }
+
+    #[test]
+    fn test_threat_map_matches_navigation() {
+        use crate::threats::ThreatMap;
+        use crate::visibility::player_view;
+
+        let mut state = make_6p_state(42);
+        state.areas[BLACKWATER.0 as usize].units = vec![
+            Unit { unit_type: UnitType::Knight, house: HouseName::Lannister, routed: false },
+            Unit { unit_type: UnitType::Knight, house: HouseName::Lannister, routed: false },
+        ];
+        state.areas[BLACKWATER.0 as usize].house = Some(HouseName::Lannister);
+
+        let view = player_view(&state, HouseName::Baratheon);
+        let map = view.threats();
+        assert!(map.area(KINGS_LANDING).reachable_by.contains(&HouseName::Lannister));
+        assert!(map.area(KINGS_LANDING).adjacent_strength >= 4);
+        assert!(map.area(KINGS_LANDING).reachable_strength >= map.area(KINGS_LANDING).adjacent_strength);
+        // The viewer's own units are no threat to it
+        let lannister = player_view(&state, HouseName::Lannister);
+        assert!(!lannister.threats().area(KINGS_LANDING).reachable_by.contains(&HouseName::Lannister));
+
+        // Every house other than the viewer reaches exactly where navigation lets it march
+        for (i, area) in state.areas.iter().enumerate() {
+            let from = AreaId(i as u8);
+            let Some(house) = area.units.first().map(|u| u.house).filter(|&h| h != HouseName::Baratheon) else { continue };
+            for to in navigation::valid_destinations(&state, from, house) {
+                assert!(map.area(to).reachable_by.contains(&house), "{:?} from {} to {}", house, area_name(from), area_name(to));
+            }
+        }
+        for (i, threat) in map.areas.iter().enumerate() {
+            let to = AreaId(i as u8);
+            for &house in &threat.reachable_by {
+                let from_anywhere = (0..NUM_AREAS).map(|f| AreaId(f as u8))
+                    .any(|f| navigation::valid_destinations(&state, f, house).contains(&to));
+                assert!(from_anywhere, "{:?} can't march to {}", house, area_name(to));
+            }
+        }
+
+        // Built once, kept with the view, and never serialized
+        assert_eq!(view.threats(), &ThreatMap::of(&view));
+        assert!(!serde_json::to_string(&view).unwrap().contains("threat_map"));
+    }
+
+    #[test]
+    fn test_march_into_illegal_terrain_is_refused() {
+        let mut state = stark_march_state(&[WINTERFELL]);
+        advance(&mut state);
+        let before = state.area(WINTERFELL).units.clone();
+        assert!(!before.is_empty());
+        let refused = apply_action(&mut state, Action::March { to: BAY_OF_ICE, unit_indices: vec![0] });
+        assert!(matches!(refused, Err(ActionError::IllegalTarget(_))), "{:?}", refused);
+        assert_eq!(state.area(WINTERFELL).units, before, "footmen can't march into the sea");
+        assert!(state.area(BAY_OF_ICE).units.iter().all(|u| u.unit_type == UnitType::Ship));
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::ChooseMarch { .. })), "the march is still to be made");
+    }
+
+    #[test]
+    fn test_march_selection_only_own_ready_units() {
+        let mut state = stark_march_state(&[WINTERFELL]);
+        let stark = |routed| Unit { unit_type: UnitType::Footman, house: HouseName::Stark, routed };
+        let lannister = Unit { unit_type: UnitType::Knight, house: HouseName::Lannister, routed: false };
+        state.area_mut(WINTERFELL).units = vec![stark(false), stark(true), lannister, stark(false)];
+        advance(&mut state);
+        match state.peek_pending() {
+            Some(PendingDecision::ChooseMarch { selectable_units, valid_destinations, .. }) => {
+                assert_eq!(selectable_units, &vec![0, 3]);
+                assert!(valid_destinations.contains(&KARHOLD));
+            }
+            other => panic!("Expected ChooseMarch, got {:?}", other),
+        }
+
+        // Taking the routed footman or the Lannister knight along is refused
+        for indices in [vec![0, 1], vec![2], vec![0, 9]] {
+            let mut s = state.clone();
+            assert!(apply_action(&mut s, Action::March { to: KARHOLD, unit_indices: indices.clone() }).is_err());
+            assert_eq!(s.area(WINTERFELL).units.len(), 4, "{:?}", indices);
+            assert!(s.area(KARHOLD).units.is_empty());
+        }
+        apply_action(&mut state, Action::March { to: KARHOLD, unit_indices: vec![3, 0] }).unwrap();
+        assert_eq!(state.area(KARHOLD).units, vec![stark(false), stark(false)]);
+        let left: Vec<(HouseName, UnitType)> = state.area(WINTERFELL).units.iter().map(|u| (u.house, u.unit_type)).collect();
+        assert_eq!(left, vec![(HouseName::Stark, UnitType::Footman), (HouseName::Lannister, UnitType::Knight)]);
+
+        // Nothing left that may march: no destinations to offer
+        let mut state = stark_march_state(&[WINTERFELL]);
+        state.area_mut(WINTERFELL).units = vec![stark(true)];
+        assert!(navigation::valid_destinations(&state, WINTERFELL, HouseName::Stark).is_empty());
+    }
+
+    #[test]
+    fn test_split_march_fights_one_combat_against_whole_stack() {
+        let unit = |unit_type, house, routed| Unit { unit_type, house, routed };
+        let fight = |state: &mut GameState, unit_indices: Vec<usize>| {
+            advance(state);
+            apply_action(state, Action::March { to: KARHOLD, unit_indices }).unwrap();
+            assert_eq!(state.check_combat(), Ok(()));
+            while let Some(pending) = state.peek_pending().cloned() {
+                let action = match pending {
+                    PendingDecision::SelectHouseCard { house: HouseName::Stark, .. } => Action::SelectCard(HouseCardId::EddardStark),
+                    PendingDecision::SelectHouseCard { .. } => Action::SelectCard(HouseCardId::SerJaimeLannister),
+                    PendingDecision::UseValyrianBlade { .. } => Action::UseValyrianBlade(false),
+                    _ => break,
+                };
+                apply_action(state, action).unwrap();
+            }
+        };
+        let setup = |stark_units: Vec<Unit>| {
+            let mut state = stark_march_state(&[WINTERFELL]);
+            state.area_mut(WINTERFELL).units = stark_units;
+            state.area_mut(KARHOLD).units = vec![
+                unit(UnitType::Knight, HouseName::Lannister, false),
+                unit(UnitType::Footman, HouseName::Lannister, true),
+            ];
+            state.area_mut(KARHOLD).house = Some(HouseName::Lannister);
+            state.area_mut(KARHOLD).order = Some(Order {
+                order_type: OrderType::ConsolidatePower,
+                strength: 0,
+                star: false,
+                house: HouseName::Lannister,
+                token_index: 0,
+            });
+            state.garrisons.remove(&KARHOLD);
+            state
+        };
+
+        // Only the marching knight attacks; the footmen left behind hold
+        // Winterfell. The routed Lannister footman defends but adds nothing.
+        let mut state = setup(vec![
+            unit(UnitType::Footman, HouseName::Stark, false),
+            unit(UnitType::Knight, HouseName::Stark, false),
+            unit(UnitType::Footman, HouseName::Stark, false),
+        ]);
+        let mut probe = state.clone();
+        advance(&mut probe);
+        apply_action(&mut probe, Action::March { to: KARHOLD, unit_indices: vec![1] }).unwrap();
+        let combat = probe.combat().unwrap();
+        assert_eq!(combat.attacking_units, vec![unit(UnitType::Knight, HouseName::Stark, false)]);
+        assert_eq!(combat.defending_units, probe.area(KARHOLD).units);
+        assert_eq!(probe.area(WINTERFELL).units.len(), 2);
+
+        fight(&mut state, vec![1]);
+        let strengths = state.events.iter().find_map(|e| match e.kind {
+            GameEventKind::CombatResolved { attacker_strength, defender_strength, .. } => Some((attacker_strength, defender_strength)),
+            _ => None,
+        });
+        assert_eq!(strengths, Some((2 + 4, 2 + 2)));
+        assert_eq!(state.area(WINTERFELL).house, Some(HouseName::Stark));
+        assert!(!matches!(state.peek_pending(), Some(PendingDecision::LeavePowerToken { .. })));
+
+        // Marching everything out leaves Winterfell to a power token decision
+        let mut state = setup(vec![unit(UnitType::Knight, HouseName::Stark, false)]);
+        fight(&mut state, vec![0]);
+        assert!(state.combat().is_none());
+        assert_eq!(state.area(KARHOLD).house, Some(HouseName::Stark));
+        assert!(state.area(KARHOLD).order.is_none(), "the defeated house's order is removed");
+        assert!(matches!(state.peek_pending(),
+            Some(&PendingDecision::LeavePowerToken { house: HouseName::Stark, area_id: WINTERFELL })));
+        apply_action(&mut state, Action::LeavePowerToken(false)).unwrap();
+        assert_eq!(state.area(WINTERFELL).house, None);
+
+        // A combat no march order accounts for is flagged
+        let mut state = setup(vec![unit(UnitType::Knight, HouseName::Stark, false)]);
+        advance(&mut state);
+        apply_action(&mut state, Action::March { to: KARHOLD, unit_indices: vec![0] }).unwrap();
+        state.area_mut(WINTERFELL).order = None;
+        assert!(matches!(state.check_combat(), Err(GameError::CombatMismatch(_))));
+        assert!(tracks::check_invariants(&state).is_err());
+    }
+
     // ═════════════════════════════════════════════════════════════════════
     // GAME STATE METHODS TESTS
     // ═════════════════════════════════════════════════════════════════════
@@ -512,14 +875,14 @@ mod tests {
     fn test_advance_creates_pending() {
         let mut state = make_6p_state(42);
         advance(&mut state);
-        assert!(state.pending.is_some(), "Should have a pending decision after advance");
+        assert!(state.has_pending(), "Should have a pending decision after advance");
     }
 
     #[test]
     fn test_first_pending_is_place_orders() {
         let mut state = make_6p_state(42);
         advance(&mut state);
-        match &state.pending {
+        match state.peek_pending() {
             Some(PendingDecision::PlaceOrders { house }) => {
                 // First player to place orders should be the first in turn_order
                 assert_eq!(*house, state.turn_order[0]);
@@ -533,7 +896,7 @@ mod tests {
         let mut state = make_6p_state(42);
         advance(&mut state);
 
-        let house = match &state.pending {
+        let house = match state.peek_pending() {
             Some(PendingDecision::PlaceOrders { house }) => *house,
             _ => panic!("Expected PlaceOrders"),
         };
@@ -550,7 +913,7 @@ mod tests {
             .map(|(i, &a)| (a, i as u8))
             .collect();
 
-        apply_action(&mut state, Action::PlaceOrders(orders.clone()));
+        apply_action(&mut state, Action::PlaceOrders(orders.clone())).unwrap();
         advance(&mut state);
 
         // Orders should be placed
@@ -562,6 +925,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_orders_are_sealed_until_all_are_in() {
+        use crate::engine::{apply_action_as, open_decisions};
+        use crate::visibility::player_view;
+
+        let mut state = make_6p_state(42);
+        advance(&mut state);
+        let order = state.turn_order.clone();
+        assert_eq!(open_decisions(&state).iter().map(|p| p.house()).collect::<Vec<_>>(), order);
+        let plain: Vec<u8> = (0..ORDER_TOKENS.len() as u8).filter(|&t| !ORDER_TOKENS[t as usize].star).collect();
+        let orders_for = |state: &GameState, h: HouseName| -> Vec<(AreaId, u8)> {
+            state.areas.iter().enumerate()
+                .filter(|(_, a)| a.house == Some(h) && !a.units.is_empty())
+                .zip(&plain)
+                .map(|((i, _), &t)| (AreaId(i as u8), t))
+                .collect()
+        };
+
+        // The last house in turn order places first; the others see only
+        // that its orders are down
+        let (&last, rest) = order.split_last().unwrap();
+        let placed = orders_for(&state, last);
+        apply_action_as(&mut state, last, Action::PlaceOrders(placed.clone())).unwrap();
+        advance(&mut state);
+        let view = player_view(&state, order[0]);
+        assert!(matches!(view.pending, Some(PendingDecision::PlaceOrders { house }) if house == order[0]));
+        for &(a, _) in &placed {
+            assert!(view.areas[a.0 as usize].order.is_none() && view.areas[a.0 as usize].has_hidden_order);
+            assert!(player_view(&state, last).areas[a.0 as usize].order.is_some());
+        }
+        assert!(player_view(&state, last).pending.is_none());
+
+        for &h in rest {
+            assert!(!state.events.iter().any(|e| matches!(e.kind, GameEventKind::OrdersRevealed { .. })));
+            let placed = orders_for(&state, h);
+            apply_action_as(&mut state, h, Action::PlaceOrders(placed)).unwrap();
+            advance(&mut state);
+        }
+
+        // All flipped at once, then the raven holder sees every order
+        let revealed = state.events.iter().find_map(|e| match &e.kind {
+            GameEventKind::OrdersRevealed { orders } => Some(orders.len()),
+            _ => None,
+        }).expect("orders are revealed together");
+        assert_eq!(revealed, state.areas.iter().filter(|a| a.order.is_some()).count());
+        let Some(&PendingDecision::MessengerRaven { house: raven }) = state.peek_pending() else { panic!("the raven follows the reveal") };
+        let view = player_view(&state, raven);
+        assert!(view.areas.iter().all(|a| !a.has_hidden_order));
+        assert!(view.areas.iter().zip(&state.areas).all(|(v, a)| v.order == a.order));
+    }
+
     // ═════════════════════════════════════════════════════════════════════
     // DETERMINISM TESTS
     // ═════════════════════════════════════════════════════════════════════
@@ -591,6 +1005,30 @@ mod tests {
         assert!(winners.len() >= 2, "Random games should produce different winners");
     }
 
+    #[test]
+    fn test_rng_purposes_draw_from_independent_streams() {
+        use rand::RngCore;
+        let draw = |state: &mut GameState, purpose| crate::engine::next_rng(state, purpose).next_u64();
+        let mut plain = make_6p_state(7);
+        let mut extra = plain.clone();
+        draw(&mut extra, RngPurpose::WildlingReshuffle);
+        assert_eq!(draw(&mut plain, RngPurpose::WesterosReshuffle), draw(&mut extra, RngPurpose::WesterosReshuffle),
+            "a wildling reshuffle does not shift the Westeros stream");
+        assert_eq!(plain.rng_counters.westeros_reshuffle, 1);
+        assert_eq!(extra.rng_counters.wildling_reshuffle, 1);
+
+        let mut other = make_6p_state(7);
+        assert_ne!(draw(&mut other, RngPurpose::WildlingReshuffle), draw(&mut make_6p_state(7), RngPurpose::WesterosReshuffle));
+    }
+
+    #[test]
+    fn test_canonical_games_match_snapshots() {
+        let stored: Vec<crate::snapshot::Snapshot> =
+            serde_json::from_str(include_str!("../snapshots/canonical.json")).unwrap();
+        let diffs = crate::snapshot::diff(&stored, &crate::snapshot::canonical_snapshots());
+        assert!(diffs.is_empty(), "canonical games changed (`got-runner snapshots --bless` if intended):\n{}", diffs.join("\n"));
+    }
+
     // ═════════════════════════════════════════════════════════════════════
     // FULL GAME SIMULATION TESTS
     // ═════════════════════════════════════════════════════════════════════
@@ -651,7 +1089,7 @@ mod tests {
     #[test]
     fn test_combat_state_default() {
         let state = make_6p_state(42);
-        assert!(state.combat.is_none());
+        assert!(state.combat().is_none());
     }
 
     #[test]
@@ -663,6 +1101,500 @@ mod tests {
         assert_eq!(g.strength, 5);
     }
 
+    #[test]
+    fn test_neutral_garrisons_4p_and_3p() {
+        // 4-player: Greyjoy plays, Tyrell and Martell zones are neutral
+        let state = create_initial_state(4, 42);
+        assert_eq!(state.garrisons[&PYKE].house, Some(HouseName::Greyjoy));
+        assert!(!state.garrisons.contains_key(&GREYWATER_WATCH), "Greyjoy's own units start there");
+        assert_eq!(state.garrisons[&HIGHGARDEN], Garrison { house: None, strength: 5 });
+        assert_eq!(state.garrisons[&SUNSPEAR], Garrison { house: None, strength: 5 });
+
+        // 3-player: Pyke is neutral, blocked areas get no tokens
+        let state = create_initial_state(3, 42);
+        assert_eq!(state.garrisons[&PYKE], Garrison { house: None, strength: 5 });
+        assert!(!state.garrisons.contains_key(&SUNSPEAR));
+        assert!(!state.garrisons.contains_key(&HIGHGARDEN));
+        for g in state.garrisons.values() {
+            assert!(g.house.is_none() || state.playing_houses.contains(&g.house.unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_neutral_force_strengths_by_player_count() {
+        let strength = |state: &GameState, area: AreaId| state.garrisons.get(&area).map(|g| g.strength);
+        let three = create_initial_state(3, 42);
+        assert_eq!(strength(&three, GREYWATER_WATCH), Some(3));
+        assert_eq!(strength(&three, SALT_SHORE), None, "closed in a 3-player game");
+
+        let four = create_initial_state(4, 42);
+        assert_eq!(strength(&four, OLDTOWN), Some(3));
+        assert_eq!(strength(&four, SALT_SHORE), Some(3));
+        assert_eq!(strength(&four, STARFALL), Some(3));
+
+        let five = create_initial_state(5, 42);
+        assert_eq!(strength(&five, OLDTOWN), None, "Tyrell plays");
+        assert_eq!(strength(&five, SUNSPEAR), Some(5));
+        assert_eq!(strength(&five, SALT_SHORE), Some(4));
+        assert_eq!(strength(&five, YRONWOOD), Some(4));
+        assert_eq!(strength(&five, STARFALL), Some(4));
+        assert_eq!(strength(&five, KINGS_LANDING), Some(5));
+    }
+
+    #[test]
+    fn test_official_4p_setup() {
+        let state = create_initial_state(4, 42);
+        assert_eq!(state.playing_houses.len(), 4);
+        for area in [
+            HIGHGARDEN, OLDTOWN, DORNISH_MARCHES, THREE_TOWERS,
+            SUNSPEAR, SALT_SHORE, YRONWOOD, STARFALL, PRINCES_PASS, THE_BONEWAY,
+        ] {
+            assert!(!state.area(area).blocked, "{} is open", area_name(area));
+            assert_eq!(state.garrisons.get(&area).map(|g| g.house), Some(None), "{} is neutral", area_name(area));
+        }
+        assert!(state.areas.iter().all(|a| !a.blocked));
+
+        let (stark, lannister, baratheon, greyjoy) =
+            (HouseName::Stark, HouseName::Lannister, HouseName::Baratheon, HouseName::Greyjoy);
+        assert_eq!(tracks::order(&state, Track::IronThrone), vec![baratheon, lannister, stark, greyjoy]);
+        assert_eq!(tracks::order(&state, Track::Fiefdoms), vec![greyjoy, stark, baratheon, lannister]);
+        assert_eq!(tracks::order(&state, Track::KingsCourt), vec![lannister, stark, baratheon, greyjoy]);
+        assert_eq!(state.turn_order, vec![baratheon, lannister, stark, greyjoy]);
+
+        let five = create_initial_state(5, 42);
+        assert!(!five.garrisons.contains_key(&PRINCES_PASS), "only the 4-player chart holds the passes");
+    }
+
+    #[test]
+    fn test_neutral_force_options_override_chart() {
+        let mut config = SetupConfig::new(4, 42);
+        config.options.neutral_forces = vec![
+            NeutralForce { player_count: 4, area: KINGS_LANDING, strength: 3 },
+            NeutralForce { player_count: 4, area: OLDTOWN, strength: 0 },
+            NeutralForce { player_count: 4, area: HARRENHAL, strength: 2 },
+            NeutralForce { player_count: 5, area: THE_EYRIE, strength: 1 },
+        ];
+        let state = create_game(&config).unwrap();
+        assert_eq!(state.garrisons[&KINGS_LANDING].strength, 3);
+        assert!(!state.garrisons.contains_key(&OLDTOWN));
+        assert_eq!(state.garrisons[&HARRENHAL], Garrison { house: None, strength: 2 });
+        assert_eq!(state.garrisons[&THE_EYRIE].strength, 6, "5-player entries don't apply");
+        assert!(!state.options.is_standard());
+
+        config.options.neutral_forces.push(NeutralForce { player_count: 4, area: BLACKWATER_BAY, strength: 2 });
+        assert!(config.validate().is_err(), "neutral forces only go on land");
+        config.options.neutral_forces.pop();
+        config.options.neutral_forces.push(NeutralForce { player_count: 4, area: KINGS_LANDING, strength: 4 });
+        assert!(config.validate().is_err(), "one entry per area and player count");
+    }
+
+    /// Baratheon army in Kingswood with a march order, next to neutral King's Landing.
+    fn march_on_kings_landing(units: &[UnitType]) -> GameState {
+        let mut state = stark_march_state(&[]);
+        state.area_mut(KINGSWOOD).units = units.iter()
+            .map(|&unit_type| Unit { unit_type, house: HouseName::Baratheon, routed: false })
+            .collect();
+        state.area_mut(KINGSWOOD).house = Some(HouseName::Baratheon);
+        state.area_mut(KINGSWOOD).order = Some(Order {
+            order_type: OrderType::March,
+            strength: 0,
+            star: false,
+            house: HouseName::Baratheon,
+            token_index: 1,
+        });
+        advance(&mut state);
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::ChooseMarch { from_area: KINGSWOOD, .. })));
+        state
+    }
+
+    #[test]
+    fn test_weak_march_repelled_by_neutral_force() {
+        let mut state = march_on_kings_landing(&[UnitType::Footman, UnitType::Footman]);
+        apply_action(&mut state, Action::March { to: KINGS_LANDING, unit_indices: vec![0, 1] }).unwrap();
+        assert_eq!(state.area(KINGSWOOD).units.len(), 2, "units stay home");
+        assert!(state.area(KINGS_LANDING).units.is_empty());
+        assert_eq!(state.area(KINGS_LANDING).house, None);
+        assert!(state.garrisons.contains_key(&KINGS_LANDING));
+        assert!(state.area(KINGSWOOD).order.is_none(), "the march order is spent");
+    }
+
+    #[test]
+    fn test_strong_march_removes_neutral_force() {
+        let mut state = march_on_kings_landing(&[UnitType::Knight, UnitType::Knight, UnitType::Footman]);
+        apply_action(&mut state, Action::March { to: KINGS_LANDING, unit_indices: vec![0, 1] }).unwrap();
+        // Two knights (4) fall short of 5
+        assert!(state.garrisons.contains_key(&KINGS_LANDING));
+
+        let mut state = march_on_kings_landing(&[UnitType::Knight, UnitType::Knight, UnitType::Footman]);
+        apply_action(&mut state, Action::March { to: KINGS_LANDING, unit_indices: vec![0, 1, 2] }).unwrap();
+        assert!(!state.garrisons.contains_key(&KINGS_LANDING));
+        assert!(state.combat().is_none(), "neutral forces do not fight with cards");
+        assert_eq!(state.area(KINGS_LANDING).house, Some(HouseName::Baratheon));
+        assert_eq!(state.area(KINGS_LANDING).units.len(), 3);
+    }
+
+    /// A fleet of `ships` ships for each side: the attacker's, with a march
+    /// order, and the defender's in the sea next to it. Each side holds
+    /// only `card`; the attacker is waiting to choose its march.
+    fn fleet_battle_state(attacker: (HouseName, AreaId, usize, HouseCardId), defender: (HouseName, AreaId, usize, HouseCardId)) -> GameState {
+        let mut state = stark_march_state(&[]);
+        for (house, area, ships, card) in [attacker, defender] {
+            set_ships(&mut state, area, house, ships);
+            state.house_mut(house).hand = vec![card];
+        }
+        state.area_mut(attacker.1).order = Some(Order {
+            order_type: OrderType::March,
+            strength: 0,
+            star: false,
+            house: attacker.0,
+            token_index: 0,
+        });
+        advance(&mut state);
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::ChooseMarch { from_area, .. }) if *from_area == attacker.1));
+        state
+    }
+
+    /// Replace whatever is in `area` with `ships` of `house`'s ships, drawn
+    /// from its pool.
+    fn set_ships(state: &mut GameState, area: AreaId, house: HouseName, ships: usize) {
+        for unit in std::mem::take(&mut state.area_mut(area).units) {
+            state.house_mut(unit.house).return_unit(unit.unit_type).unwrap();
+        }
+        for _ in 0..ships {
+            state.house_mut(house).take_unit(UnitType::Ship).unwrap();
+            state.area_mut(area).units.push(Unit { unit_type: UnitType::Ship, house, routed: false });
+        }
+        state.area_mut(area).house = (ships > 0).then_some(house);
+    }
+
+    /// `stark` Stark ships with a march order in the Bay of Ice and
+    /// `greyjoy` Greyjoy ships in the Sunset Sea; each side holds one card.
+    fn sea_battle_state(stark: usize, greyjoy: usize, stark_card: HouseCardId, greyjoy_card: HouseCardId) -> GameState {
+        fleet_battle_state((HouseName::Stark, BAY_OF_ICE, stark, stark_card), (HouseName::Greyjoy, SUNSET_SEA, greyjoy, greyjoy_card))
+    }
+
+    /// Answer whatever the battle asks until it is over or the loser must
+    /// choose where to retreat.
+    fn finish_combat(state: &mut GameState) {
+        use rand::SeedableRng;
+        let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(7);
+        while state.combat().is_some() {
+            let pending = state.peek_pending().cloned().expect("combat waits on a decision");
+            if matches!(pending, PendingDecision::Retreat { .. }) {
+                return;
+            }
+            let action = random_legal_action(state, &mut rng).unwrap();
+            apply_action(state, action).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_vacated_sea_loses_control() {
+        let mut state = sea_battle_state(2, 0, HouseCardId::EddardStark, HouseCardId::TheonGreyjoy);
+        state.area_mut(SUNSET_SEA).house = None;
+        apply_action(&mut state, Action::March { to: SUNSET_SEA, unit_indices: vec![0, 1] }).unwrap();
+        assert_eq!(state.area(SUNSET_SEA).house, Some(HouseName::Stark));
+        assert_eq!(state.area(BAY_OF_ICE).house, None, "no power token can hold a sea");
+        assert!(!matches!(state.peek_pending(), Some(PendingDecision::LeavePowerToken { .. })));
+
+        // Ships lost at sea take control with them
+        let mut state = sea_battle_state(2, 0, HouseCardId::EddardStark, HouseCardId::TheonGreyjoy);
+        for (size, held) in [(2, Some(HouseName::Stark)), (1, None)] {
+            state.clear_pending();
+            state.enqueue_pending(PendingDecision::Reconcile { house: HouseName::Stark, area_id: BAY_OF_ICE, current_size: size, max_allowed: size - 1 });
+            apply_action(&mut state, Action::Reconcile(BAY_OF_ICE, 0)).unwrap();
+            assert_eq!(state.area(BAY_OF_ICE).units.len(), size as usize - 1);
+            assert_eq!(state.area(BAY_OF_ICE).house, held);
+        }
+    }
+
+    #[test]
+    fn test_contested_sea_control() {
+        // The whole fleet sails into battle: its home sea lapses at once
+        let mut state = sea_battle_state(3, 1, HouseCardId::EddardStark, HouseCardId::TheonGreyjoy);
+        apply_action(&mut state, Action::March { to: SUNSET_SEA, unit_indices: vec![0, 1, 2] }).unwrap();
+        assert!(state.combat().is_some());
+        assert_eq!(state.area(BAY_OF_ICE).house, None);
+        assert_eq!(state.area(SUNSET_SEA).house, Some(HouseName::Greyjoy));
+        finish_combat(&mut state);
+        assert_eq!(state.area(SUNSET_SEA).house, Some(HouseName::Stark));
+        assert!(state.area(SUNSET_SEA).units.iter().all(|u| u.house == HouseName::Stark));
+        assert_eq!(state.area(BAY_OF_ICE).house, None);
+
+        // Beaten back, the fleet regains the sea it sailed from
+        let mut state = sea_battle_state(1, 2, HouseCardId::CatelynStark, HouseCardId::VictarionGreyjoy);
+        apply_action(&mut state, Action::March { to: SUNSET_SEA, unit_indices: vec![0] }).unwrap();
+        assert_eq!(state.area(BAY_OF_ICE).house, None);
+        finish_combat(&mut state);
+        assert_eq!(state.area(SUNSET_SEA).house, Some(HouseName::Greyjoy));
+        assert_eq!(state.area(BAY_OF_ICE).house, Some(HouseName::Stark));
+        assert_eq!(state.area(BAY_OF_ICE).units.len(), 1);
+        assert_eq!(state.check_unit_pools(), Ok(()));
+    }
+
+    /// Greyjoy's three ships from the Sunset Sea beat one Stark ship in the
+    /// Bay of Ice; Stark's port holds `in_port` ships beforehand.
+    fn stark_fleet_beaten(in_port: usize) -> GameState {
+        let mut state = fleet_battle_state(
+            (HouseName::Greyjoy, SUNSET_SEA, 3, HouseCardId::VictarionGreyjoy),
+            (HouseName::Stark, BAY_OF_ICE, 1, HouseCardId::RooseBolton),
+        );
+        set_ships(&mut state, WINTERFELL_PORT, HouseName::Stark, in_port);
+        apply_action(&mut state, Action::March { to: BAY_OF_ICE, unit_indices: vec![0, 1, 2] }).unwrap();
+        finish_combat(&mut state);
+        state
+    }
+
+    #[test]
+    fn test_sea_battle_is_fought_on_ships_and_cards() {
+        let strengths = |garrison: bool| {
+            let mut state = fleet_battle_state(
+                (HouseName::Greyjoy, SUNSET_SEA, 3, HouseCardId::DagmerCleftjaw),
+                (HouseName::Stark, BAY_OF_ICE, 1, HouseCardId::RooseBolton),
+            );
+            if garrison {
+                state.garrisons.insert(BAY_OF_ICE, Garrison { house: Some(HouseName::Stark), strength: 2 });
+            }
+            apply_action(&mut state, Action::March { to: BAY_OF_ICE, unit_indices: vec![0, 1, 2] }).unwrap();
+            finish_combat(&mut state);
+            state.events.iter().find_map(|e| match e.kind {
+                GameEventKind::CombatResolved { attacker_strength, defender_strength, .. } => Some((attacker_strength, defender_strength)),
+                _ => None,
+            })
+        };
+        let card = |c| cards::get_house_card(c).strength as i16;
+        assert_eq!(strengths(false), Some((3 + card(HouseCardId::DagmerCleftjaw), 1 + card(HouseCardId::RooseBolton))));
+        assert_eq!(strengths(true), strengths(false), "no garrison holds a sea");
+    }
+
+    #[test]
+    fn test_sea_battle_retreats_over_water() {
+        let mut state = stark_fleet_beaten(0);
+        let possible = match state.peek_pending() {
+            Some(PendingDecision::Retreat { house: HouseName::Stark, from_area: BAY_OF_ICE, possible_areas, .. }) => possible_areas.clone(),
+            other => panic!("Expected Stark to retreat, got {:?}", other),
+        };
+        // Never onto the shore, nor into the sea the attackers sailed from
+        assert_eq!(possible, vec![WINTERFELL_PORT]);
+        apply_action(&mut state, Action::Retreat(WINTERFELL_PORT)).unwrap();
+        assert_eq!(state.area(WINTERFELL_PORT).units.len(), 1);
+        assert_eq!(state.area(WINTERFELL_PORT).house, Some(HouseName::Stark));
+        assert_eq!(state.area(BAY_OF_ICE).house, Some(HouseName::Greyjoy));
+        assert!(state.area(BAY_OF_ICE).units.iter().all(|u| u.unit_type == UnitType::Ship && u.house == HouseName::Greyjoy));
+
+        // A full port leaves the fleet nowhere to go
+        let state = stark_fleet_beaten(MAX_SHIPS_IN_PORT);
+        assert!(!matches!(state.peek_pending(), Some(PendingDecision::Retreat { .. })));
+        assert!(state.area(BAY_OF_ICE).units.iter().all(|u| u.house == HouseName::Greyjoy));
+        assert_eq!(state.area(WINTERFELL_PORT).units.len(), MAX_SHIPS_IN_PORT);
+    }
+
+    #[test]
+    fn test_ships_are_mustered_into_seas_and_ports() {
+        use crate::engine::{legal_actions, validate_action};
+
+        let mut state = make_6p_state(42);
+        set_ships(&mut state, THE_SHIVERING_SEA, HouseName::Stark, 0);
+        set_ships(&mut state, BAY_OF_ICE, HouseName::Greyjoy, 1);
+        state.pending = VecDeque::from([PendingDecision::Muster {
+            house: HouseName::Stark,
+            areas: vec![MusterArea { area_id: WINTERFELL, points: 2 }],
+        }]);
+        let ship_into = |state: &GameState| -> Vec<AreaId> {
+            legal_actions(state).iter().filter_map(|a| match a {
+                Action::Muster(m) => match m[..] {
+                    [(WINTERFELL, MusterAction2::BuildShip(to))] => Some(to),
+                    _ => None,
+                },
+                _ => None,
+            }).collect()
+        };
+        // Not into the Bay of Ice, where Greyjoy's ship is
+        assert_eq!(ship_into(&state), vec![THE_SHIVERING_SEA, WINTERFELL_PORT]);
+        let muster = |to: AreaId| Action::Muster(vec![(WINTERFELL, MusterAction2::BuildShip(to))]);
+        assert!(validate_action(&state, &muster(BAY_OF_ICE)).is_err());
+        assert!(validate_action(&state, &Action::Muster(vec![(WINTERFELL, MusterAction2::Build(UnitType::Ship))])).is_err());
+
+        // A full port takes no more
+        let mut full = state.clone();
+        set_ships(&mut full, WINTERFELL_PORT, HouseName::Stark, MAX_SHIPS_IN_PORT);
+        assert_eq!(ship_into(&full), vec![THE_SHIVERING_SEA]);
+        set_ships(&mut full, WINTERFELL_PORT, HouseName::Stark, MAX_SHIPS_IN_PORT - 1);
+        let two = Action::Muster(vec![(WINTERFELL, MusterAction2::BuildShip(WINTERFELL_PORT)), (WINTERFELL, MusterAction2::BuildShip(WINTERFELL_PORT))]);
+        assert!(validate_action(&full, &two).is_err());
+
+        let ships = state.house(HouseName::Stark).available_units.ships;
+        apply_action(&mut state, muster(WINTERFELL_PORT)).unwrap();
+        assert_eq!(state.area(WINTERFELL_PORT).units, vec![Unit { unit_type: UnitType::Ship, house: HouseName::Stark, routed: false }]);
+        assert_eq!(state.area(WINTERFELL_PORT).house, Some(HouseName::Stark));
+        assert_eq!(state.house(HouseName::Stark).available_units.ships, ships - 1);
+        assert_eq!(state.check_unit_pools(), Ok(()));
+    }
+
+    #[test]
+    fn test_muster_stays_within_points_supply_and_pool() {
+        use crate::engine::validate_action;
+
+        let mut state = make_6p_state(42);
+        state.pending = VecDeque::from([PendingDecision::Muster {
+            house: HouseName::Stark,
+            areas: vec![MusterArea { area_id: WINTERFELL, points: 2 }],
+        }]);
+        let build = |units: &[UnitType]| Action::Muster(units.iter().map(|&u| (WINTERFELL, MusterAction2::Build(u))).collect());
+        assert_eq!(validate_action(&state, &build(&[UnitType::Footman])), Ok(()));
+
+        // Four knights cost 8 of Winterfell's 2 points
+        let before = state.area(WINTERFELL).units.clone();
+        let knights = build(&[UnitType::Knight; 4]);
+        let refused = validate_action(&state, &knights);
+        assert!(matches!(refused, Err(ActionError::IllegalTarget(_))), "{:?}", refused);
+        assert!(apply_action(&mut state, knights).is_err());
+        assert_eq!(state.area(WINTERFELL).units, before);
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::Muster { .. })), "the muster is still to be made");
+
+        // Two footmen fit the points but not Stark's supply
+        assert!(validate_action(&state, &build(&[UnitType::Footman, UnitType::Footman])).is_err());
+        let mut supplied = state.clone();
+        supplied.house_mut(HouseName::Stark).supply = 6;
+        assert_eq!(validate_action(&supplied, &build(&[UnitType::Footman, UnitType::Footman])), Ok(()));
+
+        // Nor past the units left in the pool
+        supplied.house_mut(HouseName::Stark).available_units.footmen = 1;
+        assert!(validate_action(&supplied, &build(&[UnitType::Footman, UnitType::Footman])).is_err());
+        assert_eq!(state.check_unit_pools(), Ok(()));
+    }
+
+    #[test]
+    fn test_land_units_are_mustered_on_land() {
+        use crate::engine::validate_action;
+
+        // Even a decision that listed a sea doesn't put footmen in it
+        let mut state = make_6p_state(42);
+        state.pending = VecDeque::from([PendingDecision::Muster {
+            house: HouseName::Stark,
+            areas: vec![MusterArea { area_id: THE_SHIVERING_SEA, points: 2 }],
+        }]);
+        let footman = Action::Muster(vec![(THE_SHIVERING_SEA, MusterAction2::Build(UnitType::Footman))]);
+        let refused = validate_action(&state, &footman);
+        assert!(matches!(refused, Err(ActionError::IllegalTarget(_))), "{:?}", refused);
+        let before = state.area(THE_SHIVERING_SEA).units.clone();
+        assert!(apply_action(&mut state, footman).is_err());
+        assert_eq!(state.area(THE_SHIVERING_SEA).units, before);
+        assert_eq!(state.check_unit_pools(), Ok(()));
+    }
+
+    #[test]
+    fn test_only_the_castle_holder_sails_into_its_port() {
+        let mut state = make_6p_state(42);
+        set_ships(&mut state, BAY_OF_ICE, HouseName::Stark, 1);
+        let stark_ship = [UnitType::Ship];
+        assert!(navigation::is_march_valid(&state, BAY_OF_ICE, WINTERFELL_PORT, HouseName::Stark, &stark_ship));
+        // Only from the port's own sea, and never onto land
+        assert!(!navigation::is_march_valid(&state, THE_SHIVERING_SEA, WINTERFELL_PORT, HouseName::Stark, &stark_ship));
+        assert!(!navigation::is_march_valid(&state, WINTERFELL_PORT, WINTERFELL, HouseName::Stark, &stark_ship));
+        assert!(!navigation::is_march_valid(&state, BAY_OF_ICE, WINTERFELL_PORT, HouseName::Greyjoy, &stark_ship));
+        set_ships(&mut state, WINTERFELL_PORT, HouseName::Stark, MAX_SHIPS_IN_PORT);
+        assert!(!navigation::is_march_valid(&state, BAY_OF_ICE, WINTERFELL_PORT, HouseName::Stark, &stark_ship));
+    }
+
+    #[test]
+    fn test_taking_a_castle_seizes_the_ships_in_its_port() {
+        let mut state = make_6p_state(42);
+        set_ships(&mut state, WINTERFELL_PORT, HouseName::Stark, 3);
+        let stark_ships = state.house(HouseName::Stark).available_units.ships;
+        // Greyjoy has two ships left in its pool
+        let spare = state.house(HouseName::Greyjoy).available_units.ships;
+        for _ in 0..spare - 2 {
+            state.house_mut(HouseName::Greyjoy).take_unit(UnitType::Ship).unwrap();
+        }
+        set_area_control(&mut state, WINTERFELL, Some(HouseName::Greyjoy));
+        let port = state.area(WINTERFELL_PORT);
+        assert_eq!(port.units.iter().filter(|u| u.house == HouseName::Greyjoy).count(), 2);
+        assert_eq!(port.units.len(), 2);
+        assert_eq!(port.house, Some(HouseName::Greyjoy));
+        assert_eq!(state.house(HouseName::Stark).available_units.ships, stark_ships + 3);
+        assert_eq!(state.house(HouseName::Greyjoy).available_units.ships, 0);
+        assert!(state.events.iter().any(|e| e.kind == GameEventKind::PortSeized {
+            port: WINTERFELL_PORT, from: HouseName::Stark, to: HouseName::Greyjoy, captured: 2, destroyed: 1,
+        }));
+    }
+
+    #[test]
+    fn test_a_blockaded_port_consolidates_nothing() {
+        let consolidate = |blockade: bool| {
+            let mut state = make_6p_state(42);
+            set_ships(&mut state, WINTERFELL_PORT, HouseName::Stark, 1);
+            set_ships(&mut state, BAY_OF_ICE, HouseName::Greyjoy, blockade as usize);
+            state.area_mut(WINTERFELL_PORT).order = Some(Order {
+                order_type: OrderType::ConsolidatePower,
+                strength: 0,
+                star: false,
+                house: HouseName::Stark,
+                token_index: 13,
+            });
+            state.pending = VecDeque::from([PendingDecision::ChooseOrderToResolve {
+                house: HouseName::Stark,
+                order_type: OrderType::ConsolidatePower,
+                candidates: vec![WINTERFELL_PORT],
+            }]);
+            let power = state.house(HouseName::Stark).power;
+            apply_action(&mut state, Action::ResolveOrder(WINTERFELL_PORT)).unwrap();
+            state.house(HouseName::Stark).power - power
+        };
+        assert_eq!(consolidate(false), 1);
+        assert_eq!(consolidate(true), 0);
+    }
+
+    #[test]
+    fn test_cornered_fleet_is_destroyed() {
+        let mut state = fleet_battle_state(
+            (HouseName::Greyjoy, SUNSET_SEA, 3, HouseCardId::VictarionGreyjoy),
+            (HouseName::Stark, BAY_OF_ICE, 1, HouseCardId::RooseBolton),
+        );
+        set_ships(&mut state, WINTERFELL_PORT, HouseName::Stark, MAX_SHIPS_IN_PORT);
+        let pool = state.house(HouseName::Stark).available_units.ships;
+        // Two Greyjoy ships stay behind and hold the only open sea
+        apply_action(&mut state, Action::March { to: BAY_OF_ICE, unit_indices: vec![0] }).unwrap();
+        state.house_mut(HouseName::Greyjoy).hand = vec![HouseCardId::EuronCrowsEye];
+        finish_combat(&mut state);
+        assert!(state.combat().is_none(), "nowhere to retreat: no decision");
+        assert_eq!(state.area(BAY_OF_ICE).house, Some(HouseName::Greyjoy));
+        assert_eq!(state.area(SUNSET_SEA).house, Some(HouseName::Greyjoy));
+        assert_eq!(state.house(HouseName::Stark).available_units.ships, pool + 1);
+        assert_eq!(state.check_unit_pools(), Ok(()));
+    }
+
+    #[test]
+    fn test_only_ships_support_sea_battles() {
+        let support = |house| Some(Order { order_type: OrderType::Support, strength: 0, star: false, house, token_index: 6 });
+        let mut state = fleet_battle_state(
+            (HouseName::Greyjoy, SUNSET_SEA, 2, HouseCardId::VictarionGreyjoy),
+            (HouseName::Stark, BAY_OF_ICE, 1, HouseCardId::RooseBolton),
+        );
+        set_ships(&mut state, WINTERFELL_PORT, HouseName::Stark, 1);
+        state.area_mut(WINTERFELL_PORT).order = support(HouseName::Stark);
+        state.area_mut(WINTERFELL).order = support(HouseName::Stark);
+        apply_action(&mut state, Action::March { to: BAY_OF_ICE, unit_indices: vec![0, 1] }).unwrap();
+        let combat = state.combat().unwrap();
+        assert_eq!(combat.support_decisions.get(&WINTERFELL_PORT), Some(&SupportChoice::Defender));
+        assert!(!combat.support_decisions.contains_key(&WINTERFELL), "land can't support at sea");
+
+        // The same Winterfell support order backs a land battle next door
+        let mut state = stark_march_state(&[]);
+        state.area_mut(WINTERFELL).order = support(HouseName::Stark);
+        state.area_mut(CASTLE_BLACK).house = Some(HouseName::Stark);
+        state.area_mut(CASTLE_BLACK).units = vec![Unit { unit_type: UnitType::Footman, house: HouseName::Stark, routed: false }];
+        state.house_mut(HouseName::Stark).available_units.footmen -= 1;
+        state.area_mut(KARHOLD).house = Some(HouseName::Greyjoy);
+        state.area_mut(KARHOLD).units = vec![Unit { unit_type: UnitType::Knight, house: HouseName::Greyjoy, routed: false }];
+        state.house_mut(HouseName::Greyjoy).available_units.knights -= 1;
+        state.area_mut(KARHOLD).order = Some(Order { order_type: OrderType::March, strength: 0, star: false, house: HouseName::Greyjoy, token_index: 0 });
+        advance(&mut state);
+        apply_action(&mut state, Action::March { to: CASTLE_BLACK, unit_indices: vec![0] }).unwrap();
+        let combat = state.combat().unwrap();
+        assert_eq!(combat.support_decisions.get(&WINTERFELL), Some(&SupportChoice::Defender));
+    }
+
     // ═════════════════════════════════════════════════════════════════════
     // HOUSE CARD ABILITY TESTS (structural)
     // ═════════════════════════════════════════════════════════════════════
@@ -699,6 +1631,26 @@ mod tests {
         assert_eq!(euron.swords, 1);
     }
 
+    #[test]
+    fn test_card_ability_descriptors() {
+        let ability = |id| cards::get_house_card(id).ability;
+        assert_eq!(ability(HouseCardId::SerJaimeLannister), CardAbility::FlatBonus { bonus: 1, side: CombatSide::Attacker });
+        assert_eq!(ability(HouseCardId::ObaraSand), CardAbility::FlatBonus { bonus: 1, side: CombatSide::Defender });
+        assert_eq!(ability(HouseCardId::TheonGreyjoy), CardAbility::StealPower { amount: 1, min_margin: 2 });
+        assert_eq!(ability(HouseCardId::TyrionLannister).timing(), AbilityTiming::PreCombat);
+        assert_eq!(ability(HouseCardId::RooseBolton).timing(), AbilityTiming::OnLoss);
+        assert_eq!(ability(HouseCardId::SerLorasTyrell).timing(), AbilityTiming::OnWin);
+        assert_eq!(ability(HouseCardId::EddardStark).timing(), AbilityTiming::Never);
+
+        // Every house has cards with abilities
+        for h in HouseName::ALL {
+            let timings: Vec<AbilityTiming> = cards::house_cards(h).iter().map(|c| c.ability.timing()).collect();
+            assert!(timings.iter().any(|&t| t != AbilityTiming::Never), "{:?} has abilities", h);
+        }
+        let json = serde_json::to_value(ability(HouseCardId::AeronDamphair)).unwrap();
+        assert_eq!(json, serde_json::json!({ "type": "swap_card", "power_cost": 2 }));
+    }
+
     // ═════════════════════════════════════════════════════════════════════
     // ROUND / PHASE PROGRESSION TESTS
     // ═════════════════════════════════════════════════════════════════════
@@ -706,10 +1658,10 @@ mod tests {
     #[test]
     fn test_round1_skips_westeros() {
         let mut state = make_6p_state(42);
-        assert_eq!(state.phase, Phase::Planning); // Round 1 starts in Planning
+        assert_eq!(state.phase(), Phase::Planning); // Round 1 starts in Planning
         advance(&mut state);
         // Should get a PlaceOrders pending, not a Westeros decision
-        match &state.pending {
+        match state.peek_pending() {
             Some(PendingDecision::PlaceOrders { .. }) => {}
             other => panic!("Round 1 should go straight to Planning, got {:?}", other),
         }
@@ -790,4 +1742,2308 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_player_view_summarizes_remaining_opponent_abilities() {
+        use crate::visibility::player_view;
+        let mut state = make_6p_state(42);
+        advance(&mut state);
+
+        let lannister = player_view(&state, HouseName::Stark).house_info[&HouseName::Lannister].remaining_abilities;
+        assert!(lannister.cancels_card && lannister.steals_power && lannister.removes_order);
+        assert!(!lannister.drops_track && !lannister.cancels_support);
+        assert_eq!((lannister.max_strength, lannister.max_swords, lannister.max_fortifications), (4, 3, 2));
+
+        // Played cards are public, so the summary drops them
+        let profile = state.house_mut(HouseName::Lannister);
+        for card in [HouseCardId::TyrionLannister, HouseCardId::SerGregorClegane] {
+            profile.hand.retain(|&c| c != card);
+            profile.discards.push(card);
+        }
+        let lannister = player_view(&state, HouseName::Stark).house_info[&HouseName::Lannister].remaining_abilities;
+        assert!(!lannister.cancels_card);
+        assert_eq!(lannister.max_swords, 1);
+        let martell = player_view(&state, HouseName::Stark).house_info[&HouseName::Martell].remaining_abilities;
+        assert!(martell.drops_track);
+    }
+
+    #[test]
+    fn test_player_view_tracks_own_tokens_and_star_budget() {
+        use crate::visibility::player_view;
+        let mut state = make_6p_state(42);
+        let stark = HouseName::Stark;
+        let limit = tracks::star_limit(&state, stark);
+        assert!(limit >= 1);
+        let view = player_view(&state, stark);
+        assert_eq!(view.my_available_tokens.len(), ORDER_TOKENS.len());
+        assert_eq!(view.my_star_budget, limit);
+
+        let token = |order_type: OrderType, star: bool| {
+            ORDER_TOKENS.iter().position(|t| t.order_type == order_type && t.star == star).unwrap() as u8
+        };
+        let place = |state: &mut GameState, area: AreaId, t: u8| {
+            let def = ORDER_TOKENS[t as usize];
+            state.area_mut(area).order = Some(Order {
+                order_type: def.order_type, strength: def.strength, star: def.star, house: stark, token_index: t,
+            });
+        };
+        let star_march = token(OrderType::March, true);
+        place(&mut state, WINTERFELL, star_march);
+        let view = player_view(&state, stark);
+        assert!(!view.my_available_tokens.contains(&star_march));
+        assert_eq!(view.my_star_budget, limit - 1);
+        assert_eq!(player_view(&state, HouseName::Lannister).my_star_budget, tracks::star_limit(&state, HouseName::Lannister));
+
+        // Tokens left for more orders: one fewer star each time
+        let free = view.placeable_tokens(&[]);
+        let stars = |ts: &[u8]| ts.iter().filter(|&&t| ORDER_TOKENS[t as usize].star).count();
+        assert_eq!(stars(&free) > 0, limit > 1);
+        let used_up: Vec<u8> = free.iter().copied().filter(|&t| ORDER_TOKENS[t as usize].star).take(limit as usize - 1).collect();
+        assert_eq!(stars(&view.placeable_tokens(&used_up)), 0);
+
+        // Restrictions remove their tokens
+        state.order_restrictions.push(OrderType::Support);
+        let view = player_view(&state, stark);
+        assert!(view.my_available_tokens.iter().all(|&t| ORDER_TOKENS[t as usize].order_type != OrderType::Support));
+
+        // A raven swap hands the star back
+        state.engine_phase = EnginePhase::action();
+        state.enqueue_pending(PendingDecision::MessengerRaven { house: stark });
+        apply_action(&mut state, Action::MessengerRaven(Some((WINTERFELL, token(OrderType::Defense, false))))).unwrap();
+        let view = player_view(&state, stark);
+        assert!(view.my_available_tokens.contains(&star_march));
+        assert_eq!(view.my_star_budget, limit);
+    }
+
+    // ═════════════════════════════════════════════════════════════════════
+    // ACTION PHASE TURN STRUCTURE TESTS
+    // ═════════════════════════════════════════════════════════════════════
+
+    /// 6p state in the March sub-phase with the given Stark march orders
+    /// as the only orders on the board.
+    fn stark_march_state(areas: &[AreaId]) -> GameState {
+        let mut state = make_6p_state(42);
+        for a in state.areas.iter_mut() {
+            a.order = None;
+        }
+        for (i, &aid) in areas.iter().enumerate() {
+            state.area_mut(aid).order = Some(Order {
+                order_type: OrderType::March,
+                strength: 0,
+                star: false,
+                house: HouseName::Stark,
+                token_index: i as u8,
+            });
+        }
+        state.engine_phase = EnginePhase::Action(ActionTurn { sub_phase: ActionSubPhase::March, player_index: 0 });
+        state
+    }
+
+    #[test]
+    fn test_player_chooses_which_order_to_resolve() {
+        let mut state = stark_march_state(&[WINTERFELL, WHITE_HARBOR]);
+        advance(&mut state);
+        match state.peek_pending() {
+            Some(PendingDecision::ChooseOrderToResolve { house, order_type, candidates }) => {
+                assert_eq!(*house, HouseName::Stark);
+                assert_eq!(*order_type, OrderType::March);
+                assert_eq!(candidates, &vec![WINTERFELL, WHITE_HARBOR]);
+            }
+            other => panic!("Expected ChooseOrderToResolve, got {:?}", other),
+        }
+
+        // Choosing the second order resolves it, not the first on the board
+        apply_action(&mut state, Action::ResolveOrder(WHITE_HARBOR)).unwrap();
+        match state.peek_pending() {
+            Some(PendingDecision::ChooseMarch { from_area, .. }) => assert_eq!(*from_area, WHITE_HARBOR),
+            other => panic!("Expected ChooseMarch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_single_eligible_order_skips_choice() {
+        let mut state = stark_march_state(&[WINTERFELL]);
+        advance(&mut state);
+        match state.peek_pending() {
+            Some(PendingDecision::ChooseMarch { house, from_area, .. }) => {
+                assert_eq!(*house, HouseName::Stark);
+                assert_eq!(*from_area, WINTERFELL);
+            }
+            other => panic!("Expected ChooseMarch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_march_onto_enemy_power_token_takes_control() {
+        let mut state = stark_march_state(&[WINTERFELL]);
+        state.area_mut(MOAT_CAILIN).units.clear();
+        state.area_mut(MOAT_CAILIN).house = Some(HouseName::Lannister);
+        advance(&mut state);
+        apply_action(&mut state, Action::March { to: MOAT_CAILIN, unit_indices: vec![0] }).unwrap();
+
+        assert!(state.combat().is_none(), "a power token alone does not fight");
+        assert_eq!(state.area(MOAT_CAILIN).house, Some(HouseName::Stark));
+        assert!(state.events.iter().any(|e| matches!(e.kind,
+            GameEventKind::ControlChanged { area_id: MOAT_CAILIN, from: Some(HouseName::Lannister), to: Some(HouseName::Stark), .. })));
+    }
+
+    #[test]
+    fn test_one_order_per_turn() {
+        // Stark and Lannister each hold a march order; Stark has two.
+        let mut state = stark_march_state(&[WINTERFELL, WHITE_HARBOR]);
+        state.area_mut(LANNISPORT).order = Some(Order {
+            order_type: OrderType::March,
+            strength: 0,
+            star: false,
+            house: HouseName::Lannister,
+            token_index: 0,
+        });
+        let stark_idx = state.turn_order.iter().position(|&h| h == HouseName::Stark).unwrap();
+        let lannister_idx = state.turn_order.iter().position(|&h| h == HouseName::Lannister).unwrap();
+        state.action_turn_mut().unwrap().player_index = stark_idx as u8;
+        advance(&mut state);
+        apply_action(&mut state, Action::ResolveOrder(WINTERFELL)).unwrap();
+        apply_action(&mut state, Action::MarchSkip).unwrap();
+        advance(&mut state);
+
+        // Stark's other order waits; Lannister takes the next turn
+        assert_eq!(state.action_turn().unwrap().player_index as usize, lannister_idx);
+        match state.peek_pending() {
+            Some(PendingDecision::ChooseMarch { house, .. }) => assert_eq!(*house, HouseName::Lannister),
+            other => panic!("Expected Lannister march, got {:?}", other),
+        }
+        assert!(state.area(WHITE_HARBOR).order.is_some());
+    }
+
+    /// Stark raids from Winterfell (a star raid if `star_raid`) into Moat
+    /// Cailin, held by Lannister with `target` (order type, star) on it and
+    /// `power` tokens to spare; with `pick` Stark names Moat Cailin even if
+    /// it isn't a valid target. Returns the state after the raid and
+    /// whether Moat Cailin was offered.
+    fn resolve_raid(star_raid: bool, target: (OrderType, bool), power: u8, pick: bool) -> (GameState, bool) {
+        let token = |order_type: OrderType, star: bool| {
+            ORDER_TOKENS.iter().position(|t| t.order_type == order_type && t.star == star).unwrap() as u8
+        };
+        let order = |house: HouseName, (order_type, star): (OrderType, bool)| {
+            let token_index = token(order_type, star);
+            let strength = ORDER_TOKENS[token_index as usize].strength;
+            Some(Order { order_type, strength, star, house, token_index })
+        };
+        let mut state = stark_march_state(&[]);
+        state.action_turn_mut().unwrap().sub_phase = ActionSubPhase::Raid;
+        state.area_mut(WINTERFELL).order = order(HouseName::Stark, (OrderType::Raid, star_raid));
+        state.area_mut(MOAT_CAILIN).house = Some(HouseName::Lannister);
+        state.area_mut(MOAT_CAILIN).order = order(HouseName::Lannister, target);
+        state.house_mut(HouseName::Lannister).power = power;
+        state.house_mut(HouseName::Stark).power = 5;
+        state.action_turn_mut().unwrap().player_index = state.turn_order.iter().position(|&h| h == HouseName::Stark).unwrap() as u8;
+        advance(&mut state);
+        let offered = match state.peek_pending() {
+            Some(PendingDecision::ChooseRaid { house, valid_targets, .. }) => {
+                assert_eq!(*house, HouseName::Stark);
+                valid_targets.contains(&MOAT_CAILIN)
+            }
+            other => panic!("Expected ChooseRaid, got {:?}", other),
+        };
+        if pick && !offered {
+            // A target not offered is refused; the raid is then declined
+            let refused = apply_action(&mut state, Action::Raid(Some(MOAT_CAILIN)));
+            assert!(matches!(refused, Err(ActionError::IllegalTarget(_))), "{:?}", refused);
+            apply_action(&mut state, Action::Raid(None)).unwrap();
+        } else {
+            apply_action(&mut state, Action::Raid(pick.then_some(MOAT_CAILIN))).unwrap();
+        }
+        assert!(state.area(WINTERFELL).order.is_none());
+        (state, offered)
+    }
+
+    #[test]
+    fn test_raid_reward_matrix() {
+        use OrderType::*;
+        // (raid star, target order, target star, Lannister power) → (offered, Stark power, Lannister power)
+        let cases = [
+            (false, ConsolidatePower, false, 3, (true, 6, 2)),
+            (false, ConsolidatePower, true, 3, (true, 6, 2)),
+            (false, ConsolidatePower, false, 0, (true, 5, 0)),
+            (true, ConsolidatePower, false, 1, (true, 6, 0)),
+            (false, Support, false, 3, (true, 5, 3)),
+            (false, Raid, true, 3, (true, 5, 3)),
+            (false, Defense, false, 3, (false, 5, 3)),
+            (true, Defense, true, 3, (true, 5, 3)),
+            (true, March, false, 3, (false, 5, 3)),
+        ];
+        for (star_raid, order_type, star, power, expected) in cases {
+            let (state, offered) = resolve_raid(star_raid, (order_type, star), power, true);
+            let got = (offered, state.house(HouseName::Stark).power, state.house(HouseName::Lannister).power);
+            assert_eq!(got, expected, "raid★={} on {:?}★={} with {} power", star_raid, order_type, star, power);
+        }
+
+        // Declining the raid takes nothing, and an illegal target is refused
+        let (state, _) = resolve_raid(false, (ConsolidatePower, false), 3, false);
+        assert_eq!(state.house(HouseName::Stark).power, 5);
+        let (state, offered) = resolve_raid(false, (Defense, true), 3, true);
+        assert!(!offered);
+        assert_eq!(state.house(HouseName::Stark).power, 5);
+    }
+
+    // ═════════════════════════════════════════════════════════════════════
+    // VICTORY TIMING TESTS
+    // ═════════════════════════════════════════════════════════════════════
+
+    /// Hand `n` unclaimed castle/stronghold areas to a house directly
+    /// (bypassing the event log, like starting positions).
+    fn give_castles(state: &mut GameState, house: HouseName, n: usize, skip: &[AreaId]) {
+        let free: Vec<AreaId> = (0..NUM_AREAS)
+            .map(|i| AreaId(i as u8))
+            .filter(|a| AREAS[a.0 as usize].has_castle_or_stronghold())
+            .filter(|a| state.area(*a).house.is_none() && !skip.contains(a))
+            .take(n)
+            .collect();
+        assert_eq!(free.len(), n);
+        for a in free {
+            state.area_mut(a).house = Some(house);
+        }
+    }
+
+    fn unclaimed_castle(state: &GameState) -> AreaId {
+        (0..NUM_AREAS)
+            .map(|i| AreaId(i as u8))
+            .find(|a| AREAS[a.0 as usize].has_castle_or_stronghold() && state.area(*a).house.is_none())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_first_to_seven_wins_simultaneous_victory() {
+        for first in [HouseName::Stark, HouseName::Lannister] {
+            let second = if first == HouseName::Stark { HouseName::Lannister } else { HouseName::Stark };
+            let mut state = make_6p_state(42);
+            let stark_now = state.castle_count(HouseName::Stark) as usize;
+            let lannister_now = state.castle_count(HouseName::Lannister) as usize;
+            give_castles(&mut state, HouseName::Stark, 6 - stark_now, &[]);
+            give_castles(&mut state, HouseName::Lannister, 6 - lannister_now, &[]);
+
+            let a = unclaimed_castle(&state);
+            set_area_control(&mut state, a, Some(first));
+            let b = unclaimed_castle(&state);
+            set_area_control(&mut state, b, Some(second));
+            assert_eq!(state.castle_count(first), 7);
+            assert_eq!(state.castle_count(second), 7);
+
+            check_victory(&mut state);
+            assert_eq!(state.winner, Some(first), "{:?} reached seven first", first);
+            assert!(matches!(
+                state.events.last().map(|e| &e.kind),
+                Some(GameEventKind::GameWon { house, castles: 7 }) if *house == first
+            ));
+        }
+    }
+
+    #[test]
+    fn test_handicaps_change_starting_power_and_castles_to_win() {
+        let mut config = SetupConfig::new(6, 42);
+        config.options = GameOptions {
+            handicaps: vec![
+                Handicap { castle_adjust: 1, ..Handicap::none(HouseName::Stark) },
+                Handicap { power_bonus: -2, ..Handicap::none(HouseName::Lannister) },
+                Handicap { power_bonus: 30, castle_adjust: -1, ..Handicap::none(HouseName::Tyrell) },
+            ],
+            ..GameOptions::default()
+        };
+        let mut state = create_game(&config).unwrap();
+        assert_eq!(state.house(HouseName::Lannister).power, 3);
+        assert_eq!(state.house(HouseName::Tyrell).power, 20, "power is capped at 20");
+        assert_eq!(state.house(HouseName::Baratheon).power, 5);
+
+        let stark_now = state.castle_count(HouseName::Stark) as usize;
+        give_castles(&mut state, HouseName::Stark, 7 - stark_now, &[]);
+        check_victory(&mut state);
+        assert_eq!(state.winner, None, "Stark needs eight castles");
+        let a = unclaimed_castle(&state);
+        set_area_control(&mut state, a, Some(HouseName::Stark));
+        check_victory(&mut state);
+        assert_eq!(state.winner, Some(HouseName::Stark));
+
+        config.options.handicaps.push(Handicap { power_bonus: 1, ..Handicap::none(HouseName::Stark) });
+        assert!(config.validate().is_err(), "one handicap per house");
+    }
+
+    #[test]
+    fn test_handicaps_shift_supply_and_remove_starting_units() {
+        let mut config = SetupConfig::new(6, 42);
+        config.options.handicaps = vec![
+            Handicap { supply_adjust: 2, removed_unit: Some(UnitType::Footman), ..Handicap::none(HouseName::Stark) },
+            Handicap { supply_adjust: -5, removed_unit: Some(UnitType::Ship), ..Handicap::none(HouseName::Greyjoy) },
+        ];
+        assert!(!config.options.is_standard());
+        let state = create_game(&config).unwrap();
+        assert_eq!(state.house(HouseName::Stark).supply, 3);
+        assert_eq!(state.house(HouseName::Greyjoy).supply, 0, "supply is floored at 0");
+        assert_eq!(supply::calculate_supply(&state, HouseName::Stark), 3, "the shift applies at every supply update");
+
+        // The last footman in Stark's chart (White Harbor) stays in the pool
+        assert!(state.areas[WHITE_HARBOR.0 as usize].units.is_empty());
+        assert_eq!(state.areas[WHITE_HARBOR.0 as usize].house, None);
+        assert_eq!(state.areas[WINTERFELL.0 as usize].units.len(), 2);
+        let standard = create_game(&SetupConfig::new(6, 42)).unwrap();
+        for house in [HouseName::Stark, HouseName::Greyjoy] {
+            let ut = config.options.handicap(house).unwrap().removed_unit.unwrap();
+            assert_eq!(state.house(house).available_units.get(ut), standard.house(house).available_units.get(ut) + 1);
+        }
+
+        config.options.handicaps = vec![Handicap { removed_unit: Some(UnitType::SiegeEngine), ..Handicap::none(HouseName::Stark) }];
+        assert!(config.validate().is_err(), "Stark starts without a siege engine");
+        config.options.handicaps = vec![Handicap::none(HouseName::Stark)];
+        assert!(config.options.is_standard() && config.options.canonical().handicaps.is_empty());
+    }
+
+    #[test]
+    fn test_alternative_victory_conditions() {
+        use crate::engine::check_hill;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+        let with_victory = |victory| {
+            let mut config = SetupConfig::new(6, 42);
+            config.options.victory = victory;
+            create_game(&config).unwrap()
+        };
+
+        // Most castles after N rounds: seven castles win nothing early,
+        // and the game stops after round N
+        let mut state = with_victory(VictoryCondition::MostCastlesAfter { rounds: 3 });
+        let stark_now = state.castle_count(HouseName::Stark) as usize;
+        give_castles(&mut state, HouseName::Stark, 7 - stark_now, &[]);
+        check_victory(&mut state);
+        assert_eq!(state.winner, None);
+        let mut state = with_victory(VictoryCondition::MostCastlesAfter { rounds: 3 });
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        advance(&mut state);
+        while state.winner.is_none() {
+            let action = random_legal_action(&state, &mut rng).unwrap();
+            apply_action(&mut state, action).unwrap();
+            advance(&mut state);
+        }
+        assert_eq!(state.round, 4);
+        assert_eq!(state.winner, Some(final_ranking(&state)[0]));
+
+        // King of the hill: held at the end of two rounds in a row
+        let mut state = with_victory(VictoryCondition::KingOfTheHill { area: KINGS_LANDING, rounds: 2 });
+        state.area_mut(KINGS_LANDING).house = Some(HouseName::Baratheon);
+        check_hill(&mut state);
+        state.area_mut(KINGS_LANDING).house = Some(HouseName::Lannister);
+        check_hill(&mut state);
+        assert_eq!(state.hill_holder, Some((HouseName::Lannister, 1)), "losing the hill restarts the count");
+        check_hill(&mut state);
+        assert_eq!(state.winner, Some(HouseName::Lannister));
+
+        // Supply domination: the first to the mark, the tiebreaker among several
+        let mut state = with_victory(VictoryCondition::SupplyDomination { supply: 5 });
+        state.house_mut(HouseName::Stark).supply = 4;
+        check_victory(&mut state);
+        assert_eq!(state.winner, None);
+        state.house_mut(HouseName::Stark).supply = 5;
+        state.house_mut(HouseName::Tyrell).supply = 6;
+        let best = final_ranking(&state).into_iter().find(|h| [HouseName::Stark, HouseName::Tyrell].contains(h));
+        check_victory(&mut state);
+        assert_eq!(state.winner, best);
+
+        for victory in [
+            VictoryCondition::MostCastlesAfter { rounds: 0 },
+            VictoryCondition::KingOfTheHill { area: AreaId(40), rounds: 2 },
+            VictoryCondition::SupplyDomination { supply: 7 },
+        ] {
+            let mut config = SetupConfig::new(6, 42);
+            config.options.victory = victory;
+            assert!(config.validate().is_err(), "{:?}", victory);
+        }
+        assert!(GameOptions::default().is_standard());
+        let json = serde_json::to_value(GameOptions { victory: VictoryCondition::SupplyDomination { supply: 6 }, ..Default::default() }).unwrap();
+        assert_eq!(json["victory"], serde_json::json!({ "type": "supply_domination", "supply": 6 }));
+    }
+
+    #[test]
+    fn test_capture_events_are_sequenced() {
+        let mut state = make_6p_state(42);
+        let a = unclaimed_castle(&state);
+        set_area_control(&mut state, a, Some(HouseName::Stark));
+        set_area_control(&mut state, a, Some(HouseName::Stark)); // no-op
+        set_area_control(&mut state, a, Some(HouseName::Lannister));
+        assert_eq!(state.events.len(), 2);
+        assert!(state.events.windows(2).all(|w| w[0].seq < w[1].seq));
+        match &state.events[1].kind {
+            GameEventKind::ControlChanged { area_id, from, to, .. } => {
+                assert_eq!(*area_id, a);
+                assert_eq!(*from, Some(HouseName::Stark));
+                assert_eq!(*to, Some(HouseName::Lannister));
+            }
+            other => panic!("Expected ControlChanged, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_mid_round_victory_ends_game_immediately() {
+        let mut state = stark_march_state(&[WINTERFELL]);
+        // Other houses still have orders to resolve this round
+        state.area_mut(LANNISPORT).order = Some(Order {
+            order_type: OrderType::March,
+            strength: 0,
+            star: false,
+            house: HouseName::Lannister,
+            token_index: 0,
+        });
+        let stark_now = state.castle_count(HouseName::Stark) as usize;
+        give_castles(&mut state, HouseName::Stark, 6 - stark_now, &[MOAT_CAILIN]);
+        let stark_idx = state.turn_order.iter().position(|&h| h == HouseName::Stark).unwrap();
+        state.action_turn_mut().unwrap().player_index = stark_idx as u8;
+        advance(&mut state);
+
+        // Keep one unit behind so Winterfell stays occupied
+        apply_action(&mut state, Action::March { to: MOAT_CAILIN, unit_indices: vec![0] }).unwrap();
+        assert_eq!(state.winner, Some(HouseName::Stark));
+        assert!(!state.has_pending(), "no decisions after the game is won");
+
+        let round = state.round;
+        advance(&mut state);
+        assert_eq!(apply_action(&mut state, Action::MarchSkip), Err(ActionError::GameOver));
+        assert_eq!(state.round, round);
+        assert!(state.area(LANNISPORT).order.is_some());
+    }
+
+    // ═════════════════════════════════════════════════════════════════════
+    // POST-COMBAT PIPELINE TESTS
+    // ═════════════════════════════════════════════════════════════════════
+
+    /// A combat about to enter post-combat resolution: five attacking
+    /// knights against whatever already stands in `area`.
+    fn post_combat_state(attacker: HouseName, atk_card: HouseCardId,
+                         defender: HouseName, def_card: HouseCardId,
+                         area_id: AreaId, from: AreaId) -> GameState {
+        let mut state = make_6p_state(42);
+        let knight = Unit { unit_type: UnitType::Knight, house: attacker, routed: false };
+        // All five of the attacker's knights are on the march
+        for area in &mut state.areas {
+            area.units.retain(|u| u.house != attacker || u.unit_type != UnitType::Knight);
+        }
+        state.house_mut(attacker).available_units.knights = 0;
+        // An uninvolved march keeps the action phase open after combat
+        state.area_mut(HIGHGARDEN).order = Some(Order {
+            order_type: OrderType::March,
+            strength: 0,
+            star: false,
+            house: HouseName::Tyrell,
+            token_index: 0,
+        });
+        // The march order being resolved
+        state.area_mut(from).order = Some(Order {
+            order_type: OrderType::March,
+            strength: 0,
+            star: false,
+            house: attacker,
+            token_index: 1,
+        });
+        let combat = CombatState {
+            attacker,
+            defender,
+            area_id,
+            attacking_units: vec![knight; 5],
+            defending_units: state.area(area_id).units.clone(),
+            attacker_card: Some(atk_card),
+            defender_card: Some(def_card),
+            attacker_strength: 0,
+            defender_strength: 0,
+            march_from_area: Some(from),
+            attacker_used_blade: false,
+            defender_used_blade: false,
+            support_decisions: HashMap::new(),
+            phase: CombatPhase::PostCombat,
+            aeron_resolved: true,
+            tyrion_resolved: true,
+            queen_of_thorns_resolved: true,
+            doran_resolved: true,
+            pending_support_houses: vec![],
+            outcome: None,
+            post_combat_steps: VecDeque::new(),
+        };
+        state.engine_phase = EnginePhase::Combat {
+            turn: ActionTurn { sub_phase: ActionSubPhase::March, player_index: 0 },
+            combat: Box::new(combat),
+        };
+        state
+    }
+
+    /// A Stark defense order in White Harbor, for Cersei to remove.
+    fn stark_defends_white_harbor(state: &mut GameState) {
+        state.area_mut(WHITE_HARBOR).order = Some(Order {
+            order_type: OrderType::Defense,
+            strength: 1,
+            star: false,
+            house: HouseName::Stark,
+            token_index: 3,
+        });
+    }
+
+    #[test]
+    fn test_last_house_card_returns_the_discards() {
+        let mut state = post_combat_state(
+            HouseName::Lannister, HouseCardId::SerGregorClegane,
+            HouseName::Stark, HouseCardId::EddardStark,
+            WINTERFELL, LANNISPORT,
+        );
+        // Gregor was Lannister's last card; Stark still holds one
+        let lannister = state.house_mut(HouseName::Lannister);
+        lannister.discards = std::mem::take(&mut lannister.hand);
+        let stark = state.house_mut(HouseName::Stark);
+        stark.hand = vec![HouseCardId::SerRodrikCassel];
+        stark.discards = cards::all_house_card_ids(HouseName::Stark).into_iter()
+            .filter(|&c| c != HouseCardId::SerRodrikCassel)
+            .collect();
+
+        advance(&mut state);
+        while let Some(PendingDecision::Retreat { possible_areas, .. }) = state.peek_pending() {
+            let to = possible_areas[0];
+            apply_action(&mut state, Action::Retreat(to)).unwrap();
+        }
+        assert!(state.combat().is_none());
+        let lannister = state.house(HouseName::Lannister);
+        assert_eq!(lannister.discards, vec![HouseCardId::SerGregorClegane], "the card just played stays discarded");
+        assert_eq!(lannister.hand.len(), 6);
+        assert!(!lannister.hand.contains(&HouseCardId::SerGregorClegane));
+        let stark = state.house(HouseName::Stark);
+        assert_eq!((stark.hand.len(), stark.discards.len()), (1, 6), "a house with a card left keeps its discards");
+
+    }
+
+    #[test]
+    fn test_houses_always_have_a_card_to_play() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+        let mut refreshes = 0;
+        for seed in 0..40 {
+            let mut state = create_initial_state(6, seed);
+            advance(&mut state);
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            while state.winner.is_none() {
+                if let Some(PendingDecision::SelectHouseCard { house, available_cards }) = state.peek_pending() {
+                    assert!(!available_cards.is_empty(), "{} has no card to play", house);
+                }
+                for &h in &state.playing_houses {
+                    let profile = state.house(h);
+                    assert_eq!(profile.hand.len() + profile.discards.len(), 7, "{}'s cards are all in hand or discarded", h);
+                }
+                let discarded: HashMap<HouseName, usize> = state.houses.iter().map(|(&h, p)| (h, p.discards.len())).collect();
+                let Some(action) = random_legal_action(&state, &mut rng) else { break };
+                apply_action(&mut state, action).unwrap();
+                advance(&mut state);
+                refreshes += state.houses.iter()
+                    .filter(|(h, p)| discarded[h] >= 6 && p.discards.len() <= 1)
+                    .count();
+            }
+        }
+        assert!(refreshes > 0, "some house played its last card");
+    }
+
+    #[test]
+    fn test_robb_picks_among_the_defenders_own_retreats() {
+        let mut state = post_combat_state(
+            HouseName::Stark, HouseCardId::RobbStark,
+            HouseName::Lannister, HouseCardId::TyrionLannister,
+            LANNISPORT, RIVERRUN,
+        );
+        // The march left Riverrun open, but the defenders may not flee into it
+        state.area_mut(RIVERRUN).house = None;
+        let defenders = state.area(LANNISPORT).units.len();
+        let in_sept = state.area(STONEY_SEPT).units.len();
+        advance(&mut state);
+        let (units, possible_areas) = match state.peek_pending() {
+            Some(PendingDecision::RobbRetreat { house: HouseName::Stark, defender: HouseName::Lannister, units, from_area: LANNISPORT, possible_areas }) => {
+                (units.len(), possible_areas.clone())
+            }
+            other => panic!("Expected RobbRetreat, got {:?}", other),
+        };
+        assert_eq!(units, defenders);
+        assert!(possible_areas.contains(&STONEY_SEPT));
+        assert!(!possible_areas.contains(&RIVERRUN), "not back along the attacker's march");
+        assert!(apply_action(&mut state, Action::RobbRetreat(RIVERRUN)).is_err());
+
+        apply_action(&mut state, Action::RobbRetreat(STONEY_SEPT)).unwrap();
+        assert!(state.combat().is_none());
+        let sept = &state.area(STONEY_SEPT).units;
+        assert_eq!(sept.len(), in_sept + defenders);
+        assert_eq!(sept.iter().filter(|u| u.routed).count(), defenders);
+        assert_eq!(state.area(LANNISPORT).house, Some(HouseName::Stark));
+    }
+
+    #[test]
+    fn test_robb_with_nowhere_to_send_the_defenders_destroys_them() {
+        let mut state = post_combat_state(
+            HouseName::Stark, HouseCardId::RobbStark,
+            HouseName::Lannister, HouseCardId::TyrionLannister,
+            LANNISPORT, RIVERRUN,
+        );
+        state.area_mut(RIVERRUN).house = None;
+        for area in [STONEY_SEPT, SEAROAD_MARCHES] {
+            state.area_mut(area).units.clear();
+            state.area_mut(area).house = Some(HouseName::Greyjoy);
+        }
+        let pool = state.house(HouseName::Lannister).available_units;
+        let defenders = state.area(LANNISPORT).units.clone();
+        advance(&mut state);
+        assert!(!matches!(state.peek_pending(), Some(PendingDecision::RobbRetreat { .. })));
+        assert!(state.combat().is_none());
+        assert!(state.area(LANNISPORT).units.iter().all(|u| u.house == HouseName::Stark));
+        let after = &state.house(HouseName::Lannister).available_units;
+        for ut in [UnitType::Footman, UnitType::Knight] {
+            let lost = defenders.iter().filter(|u| u.unit_type == ut).count() as u8;
+            assert_eq!(after.get(ut), pool.get(ut) + lost, "{:?} back in the pool", ut);
+        }
+    }
+
+    #[test]
+    fn test_cersei_resolves_before_retreat() {
+        let mut state = post_combat_state(
+            HouseName::Lannister, HouseCardId::CerseiLannister,
+            HouseName::Stark, HouseCardId::CatelynStark,
+            WINTERFELL, LANNISPORT,
+        );
+        let stark_units = state.area(WINTERFELL).units.len();
+        stark_defends_white_harbor(&mut state);
+        advance(&mut state);
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::CerseiRemoveOrder { opponent: HouseName::Stark, .. })));
+        assert_eq!(state.combat().unwrap().post_combat_steps,
+                   VecDeque::from([PostCombatStep::Retreat, PostCombatStep::Cleanup]));
+
+        apply_action(&mut state, Action::CerseiRemoveOrder(WHITE_HARBOR)).unwrap();
+        let to = match state.peek_pending() {
+            Some(PendingDecision::Retreat { house, possible_areas, .. }) => {
+                assert_eq!(*house, HouseName::Stark);
+                possible_areas[0]
+            }
+            other => panic!("Expected Retreat after Cersei, got {:?}", other),
+        };
+        assert!(state.combat().is_some(), "combat stays open until the retreat is resolved");
+
+        apply_action(&mut state, Action::Retreat(to)).unwrap();
+        assert!(state.combat().is_none());
+        assert_eq!(state.phase(), Phase::Action);
+        assert_eq!(state.area(WINTERFELL).house, Some(HouseName::Lannister));
+        assert_eq!(state.area(WINTERFELL).units.len(), 5);
+        let retreated = state.area(to).units.iter()
+            .filter(|u| u.house == HouseName::Stark && u.routed)
+            .count();
+        assert_eq!(retreated, stark_units, "defenders survive the retreat");
+    }
+
+    #[test]
+    fn test_order_removal_offers_only_the_opponents_orders() {
+        use crate::engine::{legal_actions, validate_action};
+
+        // Cersei with nothing to remove passes without asking
+        let mut state = post_combat_state(
+            HouseName::Lannister, HouseCardId::CerseiLannister,
+            HouseName::Stark, HouseCardId::CatelynStark,
+            WINTERFELL, LANNISPORT,
+        );
+        advance(&mut state);
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::Retreat { .. })));
+
+        let mut state = post_combat_state(
+            HouseName::Lannister, HouseCardId::CerseiLannister,
+            HouseName::Stark, HouseCardId::CatelynStark,
+            WINTERFELL, LANNISPORT,
+        );
+        stark_defends_white_harbor(&mut state);
+        advance(&mut state);
+        assert_eq!(state.peek_pending().unwrap().removal_targets(), [WHITE_HARBOR]);
+        assert_eq!(legal_actions(&state).len(), 1);
+        // Lannister's own march is not a target
+        let refused = validate_action(&state, &Action::CerseiRemoveOrder(LANNISPORT));
+        assert!(matches!(refused, Err(ActionError::IllegalTarget(_))), "{:?}", refused);
+        assert_eq!(apply_action(&mut state, Action::CerseiRemoveOrder(LANNISPORT)), refused);
+        assert!(state.area(LANNISPORT).order.is_some());
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::CerseiRemoveOrder { .. })), "the decision stays open");
+
+        // Queen of Thorns defending Winterfell: Stark's orders next to it,
+        // bar the march from White Harbor that started the battle
+        let mut state = post_combat_state(
+            HouseName::Stark, HouseCardId::EddardStark,
+            HouseName::Tyrell, HouseCardId::QueenOfThorns,
+            WINTERFELL, WHITE_HARBOR,
+        );
+        let stark_order = |token_index| Some(Order {
+            order_type: OrderType::Support, strength: 0, star: false, house: HouseName::Stark, token_index,
+        });
+        state.area_mut(THE_SHIVERING_SEA).order = stark_order(6);
+        state.area_mut(LANNISPORT).order = stark_order(7);
+        let combat = state.combat_mut().unwrap();
+        combat.phase = CombatPhase::PreCombat;
+        combat.queen_of_thorns_resolved = false;
+        combat.support_decisions.insert(THE_SHIVERING_SEA, SupportChoice::Attacker);
+        advance(&mut state);
+        match state.peek_pending() {
+            Some(PendingDecision::QueenOfThornsRemoveOrder { decider, opponent, valid_targets }) => {
+                assert_eq!((*decider, *opponent), (HouseName::Tyrell, HouseName::Stark));
+                assert_eq!(valid_targets, &[THE_SHIVERING_SEA]);
+            }
+            other => panic!("Expected the Queen of Thorns, got {:?}", other),
+        }
+        apply_action(&mut state, Action::QueenOfThorns(THE_SHIVERING_SEA)).unwrap();
+        assert!(state.area(THE_SHIVERING_SEA).order.is_none());
+        let support = state.events.iter().find_map(|e| match e.kind {
+            GameEventKind::CombatResolved { attacker_support, .. } => Some(attacker_support),
+            _ => None,
+        });
+        assert_eq!(support, Some(0), "the removed support no longer counts");
+        assert_eq!(state.house(HouseName::Tyrell).card_stats.abilities_triggered, 1);
+    }
+
+    /// Rewind the battle to the cards' reveal, for Doran to act.
+    fn doran_revealed(state: &mut GameState) {
+        let combat = state.combat_mut().unwrap();
+        combat.phase = CombatPhase::PreCombat;
+        combat.doran_resolved = false;
+    }
+
+    #[test]
+    fn test_loser_abilities_precede_winner_abilities() {
+        // Roose Bolton is back in hand by the time Patchface looks at it
+        let mut state = post_combat_state(
+            HouseName::Baratheon, HouseCardId::Patchface,
+            HouseName::Stark, HouseCardId::RooseBolton,
+            WINTERFELL, DRAGONSTONE,
+        );
+        advance(&mut state);
+        match state.peek_pending() {
+            Some(PendingDecision::PatchfaceDiscard { opponent, visible_cards, .. }) => {
+                assert_eq!(*opponent, HouseName::Stark);
+                assert!(visible_cards.contains(&HouseCardId::RooseBolton));
+            }
+            other => panic!("Expected Patchface after Roose, got {:?}", other),
+        }
+
+        apply_action(&mut state, Action::PatchfaceDiscard(HouseCardId::RooseBolton)).unwrap();
+        assert!(state.house(HouseName::Stark).discards.contains(&HouseCardId::RooseBolton));
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::Retreat { house: HouseName::Stark, .. })));
+    }
+
+    #[test]
+    fn test_doran_acts_when_revealed_win_or_lose() {
+        // Losing: Doran still moves the attacker, before the battle is decided
+        let mut state = post_combat_state(
+            HouseName::Baratheon, HouseCardId::Patchface,
+            HouseName::Martell, HouseCardId::DoranMartell,
+            SUNSPEAR, DRAGONSTONE,
+        );
+        doran_revealed(&mut state);
+        advance(&mut state);
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::DoranChooseTrack { decider: HouseName::Martell, opponent: HouseName::Baratheon })));
+        assert!(state.combat().unwrap().outcome.is_none());
+        apply_action(&mut state, Action::DoranChooseTrack(Track::KingsCourt)).unwrap();
+        assert_eq!(state.house(HouseName::Baratheon).kings_court, 6);
+        assert_eq!(tracks::star_limit(&state, HouseName::Baratheon), 0);
+        assert_eq!(state.house(HouseName::Martell).card_stats.abilities_triggered, 1);
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::PatchfaceDiscard { .. })));
+
+        // Winning: the defender goes to the bottom of the Fiefdoms track
+        // and with it loses the blade it would have used in this battle
+        let mut state = post_combat_state(
+            HouseName::Martell, HouseCardId::DoranMartell,
+            HouseName::Greyjoy, HouseCardId::DagmerCleftjaw,
+            PYKE, SUNSPEAR,
+        );
+        tracks::move_to_top(&mut state, HouseName::Greyjoy, Track::Fiefdoms);
+        doran_revealed(&mut state);
+        advance(&mut state);
+        apply_action(&mut state, Action::DoranChooseTrack(Track::Fiefdoms)).unwrap();
+        assert_eq!(state.house(HouseName::Greyjoy).fiefdoms, 6);
+        assert_ne!(tracks::holder(&state, Track::Fiefdoms), HouseName::Greyjoy);
+        assert!(!matches!(state.peek_pending(), Some(PendingDecision::UseValyrianBlade { .. })));
+        assert!(state.combat().is_none_or(|c| c.outcome.is_some_and(|o| o.attacker_wins)));
+        assert_eq!(state.area(PYKE).house, Some(HouseName::Martell));
+    }
+
+    /// Greyjoy attacks Winterfell with Aeron Damphair, holding `hand` and
+    /// `power`, against `def_card`; the battle is about to enter
+    /// pre-combat abilities.
+    fn aeron_attacks(hand: &[HouseCardId], power: u8, def_card: HouseCardId) -> GameState {
+        let mut state = post_combat_state(
+            HouseName::Greyjoy, HouseCardId::AeronDamphair,
+            HouseName::Stark, def_card,
+            WINTERFELL, WHITE_HARBOR,
+        );
+        let greyjoy = state.house_mut(HouseName::Greyjoy);
+        greyjoy.hand = hand.to_vec();
+        greyjoy.discards = vec![HouseCardId::AeronDamphair];
+        greyjoy.power = power;
+        let combat = state.combat_mut().unwrap();
+        combat.phase = CombatPhase::PreCombat;
+        combat.aeron_resolved = false;
+        combat.tyrion_resolved = false;
+        combat.queen_of_thorns_resolved = false;
+        state
+    }
+
+    #[test]
+    fn test_aeron_is_offered_his_hand_once_and_only_when_able() {
+        let hand = [HouseCardId::EuronCrowsEye, HouseCardId::BalonGreyjoy];
+        let mut state = aeron_attacks(&hand, 3, HouseCardId::CatelynStark);
+        advance(&mut state);
+        assert_eq!(state.peek_pending(), Some(&PendingDecision::AeronSwap {
+            house: HouseName::Greyjoy,
+            available_cards: hand.to_vec(),
+            power_cost: 2,
+        }));
+        let declined = {
+            let mut s = state.clone();
+            apply_action(&mut s, Action::AeronSwap(None)).unwrap();
+            s
+        };
+        assert_eq!(declined.house(HouseName::Greyjoy).power, 3, "declining costs nothing");
+        assert!(!matches!(declined.peek_pending(), Some(PendingDecision::AeronSwap { .. })));
+
+        // Swapping pays, takes Aeron back and plays the new card without
+        // asking again
+        let refused = apply_action(&mut state, Action::AeronSwap(Some(HouseCardId::VictarionGreyjoy)));
+        assert!(matches!(refused, Err(ActionError::IllegalTarget(_))), "{:?}", refused);
+        apply_action(&mut state, Action::AeronSwap(Some(HouseCardId::EuronCrowsEye))).unwrap();
+        let greyjoy = state.house(HouseName::Greyjoy);
+        assert_eq!(greyjoy.power, 1);
+        assert!(greyjoy.hand.contains(&HouseCardId::AeronDamphair));
+        assert!(greyjoy.discards.contains(&HouseCardId::EuronCrowsEye));
+        assert!(!matches!(state.peek_pending(), Some(PendingDecision::AeronSwap { .. })));
+        assert_eq!(state.combat().unwrap().attacker_card, Some(HouseCardId::EuronCrowsEye));
+
+        // Not asked without the power to pay or a card to swap in
+        for (hand, power) in [(&hand[..], 1), (&[][..], 5)] {
+            let mut state = aeron_attacks(hand, power, HouseCardId::CatelynStark);
+            advance(&mut state);
+            assert!(!matches!(state.peek_pending(), Some(PendingDecision::AeronSwap { .. })), "{:?} {}", hand, power);
+        }
+    }
+
+    #[test]
+    fn test_tyrion_cancels_aeron_before_he_can_swap() {
+        // Stark defending with Lannister's Tyrion stands in for any Tyrion
+        let mut state = aeron_attacks(&[HouseCardId::EuronCrowsEye], 5, HouseCardId::TyrionLannister);
+        advance(&mut state);
+        match state.peek_pending() {
+            Some(PendingDecision::TyrionReplace { decider, .. }) => assert_eq!(*decider, HouseName::Greyjoy),
+            other => panic!("Expected Tyrion's replacement, got {:?}", other),
+        }
+        apply_action(&mut state, Action::TyrionReplace(HouseCardId::EuronCrowsEye)).unwrap();
+        assert!(!matches!(state.peek_pending(), Some(PendingDecision::AeronSwap { .. })), "a cancelled Aeron is not offered");
+        assert_eq!(state.house(HouseName::Greyjoy).power, 5);
+    }
+
+    #[test]
+    fn test_tyrion_replacement_is_another_card_or_none() {
+        // Lannister attacks with Tyrion; Stark's card is cancelled
+        let tyrion_attacks = |def_card: HouseCardId, def_hand: &[HouseCardId]| {
+            let mut state = post_combat_state(
+                HouseName::Lannister, HouseCardId::TyrionLannister,
+                HouseName::Stark, def_card,
+                WINTERFELL, LANNISPORT,
+            );
+            let stark = state.house_mut(HouseName::Stark);
+            stark.hand = def_hand.to_vec();
+            stark.discards = vec![def_card];
+            let combat = state.combat_mut().unwrap();
+            combat.phase = CombatPhase::PreCombat;
+            combat.tyrion_resolved = false;
+            combat.aeron_resolved = false;
+            combat.queen_of_thorns_resolved = false;
+            advance(&mut state);
+            state
+        };
+
+        // The cancelled card returns to hand but is not offered again
+        let mut state = tyrion_attacks(HouseCardId::CatelynStark, &[HouseCardId::RooseBolton]);
+        assert_eq!(state.peek_pending(), Some(&PendingDecision::TyrionReplace {
+            decider: HouseName::Stark,
+            opponent: HouseName::Lannister,
+            available_cards: vec![HouseCardId::RooseBolton],
+        }));
+        assert!(state.house(HouseName::Stark).hand.contains(&HouseCardId::CatelynStark));
+        assert!(!state.house(HouseName::Stark).discards.contains(&HouseCardId::CatelynStark));
+        let refused = apply_action(&mut state, Action::TyrionReplace(HouseCardId::CatelynStark));
+        assert!(matches!(refused, Err(ActionError::IllegalTarget(_))), "{:?}", refused);
+        apply_action(&mut state, Action::TyrionReplace(HouseCardId::RooseBolton)).unwrap();
+        assert_eq!(state.combat().unwrap().defender_card, Some(HouseCardId::RooseBolton));
+
+        // With nothing else in hand Stark fights without a card
+        let state = tyrion_attacks(HouseCardId::CatelynStark, &[]);
+        assert!(!matches!(state.peek_pending(), Some(PendingDecision::TyrionReplace { .. })));
+        assert_eq!(state.house(HouseName::Stark).hand, vec![HouseCardId::CatelynStark]);
+        assert_eq!(state.combat().unwrap().defender_card, None);
+
+        // Tyrion on both sides: the attacker's cancels the defender's, which
+        // never acts, and a Tyrion played as the replacement doesn't either
+        let mut state = tyrion_attacks(HouseCardId::TyrionLannister, &[HouseCardId::RooseBolton]);
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::TyrionReplace { decider: HouseName::Stark, available_cards, .. })
+            if *available_cards == vec![HouseCardId::RooseBolton]));
+        apply_action(&mut state, Action::TyrionReplace(HouseCardId::RooseBolton)).unwrap();
+        advance(&mut state);
+        assert!(!matches!(state.peek_pending(), Some(PendingDecision::TyrionReplace { .. })));
+        assert_eq!(state.combat().unwrap().attacker_card, Some(HouseCardId::TyrionLannister));
+
+        let mut state = tyrion_attacks(HouseCardId::CatelynStark, &[HouseCardId::TyrionLannister]);
+        apply_action(&mut state, Action::TyrionReplace(HouseCardId::TyrionLannister)).unwrap();
+        advance(&mut state);
+        assert!(!matches!(state.peek_pending(), Some(PendingDecision::TyrionReplace { .. })));
+        assert_eq!(state.combat().unwrap().attacker_card, Some(HouseCardId::TyrionLannister));
+    }
+
+    #[test]
+    fn test_card_ability_decisions_go_to_the_card_owner() {
+        use crate::visibility::player_view;
+
+        // Only the decider is shown the decision, never the house it is about
+        let routed = |state: &GameState, decider: HouseName, opponent: HouseName| {
+            let pending = state.peek_pending().unwrap();
+            assert_eq!(pending.house(), decider, "{:?}", pending);
+            assert!(player_view(state, decider).pending.is_some());
+            assert!(player_view(state, opponent).pending.is_none(), "{:?}", pending);
+        };
+
+        // Doran (acting on reveal) and Patchface (the winner's) are decided by
+        // their owners
+        let mut state = post_combat_state(
+            HouseName::Baratheon, HouseCardId::Patchface,
+            HouseName::Martell, HouseCardId::DoranMartell,
+            SUNSPEAR, DRAGONSTONE,
+        );
+        doran_revealed(&mut state);
+        advance(&mut state);
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::DoranChooseTrack { .. })));
+        routed(&state, HouseName::Martell, HouseName::Baratheon);
+        apply_action(&mut state, Action::DoranChooseTrack(Track::Fiefdoms)).unwrap();
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::PatchfaceDiscard { .. })));
+        routed(&state, HouseName::Baratheon, HouseName::Martell);
+
+        // Cersei's owner picks which of the loser's orders goes
+        let mut state = post_combat_state(
+            HouseName::Lannister, HouseCardId::CerseiLannister,
+            HouseName::Stark, HouseCardId::CatelynStark,
+            WINTERFELL, LANNISPORT,
+        );
+        stark_defends_white_harbor(&mut state);
+        advance(&mut state);
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::CerseiRemoveOrder { .. })));
+        routed(&state, HouseName::Lannister, HouseName::Stark);
+
+        // Tyrion cancels the defender's card: the defender picks another
+        let mut state = post_combat_state(
+            HouseName::Lannister, HouseCardId::TyrionLannister,
+            HouseName::Stark, HouseCardId::CatelynStark,
+            WINTERFELL, LANNISPORT,
+        );
+        let combat = state.combat_mut().unwrap();
+        combat.phase = CombatPhase::PreCombat;
+        combat.tyrion_resolved = false;
+        advance(&mut state);
+        match state.peek_pending() {
+            Some(PendingDecision::TyrionReplace { decider, opponent, .. }) => {
+                assert_eq!((*decider, *opponent), (HouseName::Stark, HouseName::Lannister));
+            }
+            other => panic!("Expected Tyrion's replacement, got {:?}", other),
+        }
+        routed(&state, HouseName::Stark, HouseName::Lannister);
+        let replacement = state.house(HouseName::Stark).hand[0];
+        apply_action(&mut state, Action::TyrionReplace(replacement)).unwrap();
+        assert!(!state.house(HouseName::Stark).hand.contains(&replacement));
+    }
+
+    #[test]
+    fn test_turn_order_change_mid_action_phase() {
+        let mut state = post_combat_state(
+            HouseName::Baratheon, HouseCardId::Patchface,
+            HouseName::Martell, HouseCardId::DoranMartell,
+            SUNSPEAR, DRAGONSTONE,
+        );
+        for (i, (house, area)) in [(HouseName::Lannister, LANNISPORT), (HouseName::Stark, WINTERFELL)].into_iter().enumerate() {
+            state.area_mut(area).order = Some(Order {
+                order_type: OrderType::March,
+                strength: 0,
+                star: false,
+                house,
+                token_index: i as u8,
+            });
+        }
+        assert_eq!(state.turn_order[0], HouseName::Baratheon);
+        state.action_turn_mut().unwrap().player_index = 0;
+        doran_revealed(&mut state);
+        advance(&mut state);
+
+        // Doran sends the attacker to the bottom of the Iron Throne track
+        // while it is still Baratheon's turn
+        apply_action(&mut state, Action::DoranChooseTrack(Track::IronThrone)).unwrap();
+        assert_eq!(state.turn_order, vec![
+            HouseName::Lannister, HouseName::Stark, HouseName::Martell,
+            HouseName::Greyjoy, HouseName::Tyrell, HouseName::Baratheon,
+        ]);
+        assert_eq!(state.current_action_player(), HouseName::Baratheon);
+
+        let mut steps = 0;
+        while state.combat().is_some() && steps < 10 {
+            let action = match state.peek_pending() {
+                Some(PendingDecision::PatchfaceDiscard { visible_cards, .. }) => Action::PatchfaceDiscard(visible_cards[0]),
+                Some(PendingDecision::Retreat { possible_areas, .. }) => Action::Retreat(possible_areas[0]),
+                other => panic!("Unexpected decision {:?}", other),
+            };
+            apply_action(&mut state, action).unwrap();
+            steps += 1;
+        }
+        // Play passes to the house after Baratheon in the new order, which
+        // wraps around to the new Iron Throne holder
+        match state.peek_pending() {
+            Some(PendingDecision::ChooseMarch { house, .. }) => assert_eq!(*house, HouseName::Lannister),
+            other => panic!("Expected Lannister's march, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_losing_attacker_returns_to_origin() {
+        let mut state = post_combat_state(
+            HouseName::Lannister, HouseCardId::CerseiLannister,
+            HouseName::Stark, HouseCardId::CatelynStark,
+            WINTERFELL, LANNISPORT,
+        );
+        // One footman cannot beat Winterfell's defenders and garrison
+        state.combat_mut().unwrap().attacking_units =
+            vec![Unit { unit_type: UnitType::Footman, house: HouseName::Lannister, routed: false }];
+        let before = state.area(LANNISPORT).units.len();
+        advance(&mut state);
+
+        assert!(state.combat().is_none());
+        assert_eq!(state.area(WINTERFELL).house, Some(HouseName::Stark));
+        assert_eq!(state.area(LANNISPORT).units.len(), before + 1);
+        assert!(state.area(LANNISPORT).units.last().unwrap().routed);
+    }
+
+    // ═════════════════════════════════════════════════════════════════════
+    // DECISION QUEUE TESTS
+    // ═════════════════════════════════════════════════════════════════════
+
+    #[test]
+    fn test_decision_queue_is_fifo() {
+        let mut state = make_6p_state(42);
+        assert!(!state.has_pending());
+        state.enqueue_pending(PendingDecision::MessengerRaven { house: HouseName::Stark });
+        state.enqueue_pending(PendingDecision::MessengerRaven { house: HouseName::Lannister });
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::MessengerRaven { house: HouseName::Stark })));
+        assert!(matches!(state.pop_pending(), Some(PendingDecision::MessengerRaven { house: HouseName::Stark })));
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::MessengerRaven { house: HouseName::Lannister })));
+        state.clear_pending();
+        assert!(state.peek_pending().is_none());
+    }
+
+    #[test]
+    fn test_apply_action_services_head_of_queue() {
+        use crate::visibility::player_view;
+        let mut state = make_6p_state(42);
+        state.engine_phase = EnginePhase::action();
+        state.enqueue_pending(PendingDecision::MessengerRaven { house: HouseName::Stark });
+        state.enqueue_pending(PendingDecision::MessengerRaven { house: HouseName::Lannister });
+
+        // Only the head of the queue is shown, and only to the house it involves
+        assert!(player_view(&state, HouseName::Stark).pending.is_some());
+        assert!(player_view(&state, HouseName::Lannister).pending.is_none());
+
+        // Answering the head leaves the rest queued; the engine does not advance
+        apply_action(&mut state, Action::MessengerRaven(None)).unwrap();
+        assert_eq!(state.pending.len(), 1);
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::MessengerRaven { house: HouseName::Lannister })));
+        assert!(player_view(&state, HouseName::Lannister).pending.is_some());
+    }
+
+    // ═════════════════════════════════════════════════════════════════════
+    // INFLUENCE TRACK TESTS
+    // ═════════════════════════════════════════════════════════════════════
+
+    #[test]
+    fn test_tracks_are_permutations_at_setup() {
+        for pc in 3..=6 {
+            let state = create_initial_state(pc, 42);
+            assert_eq!(tracks::check_invariants(&state), Ok(()), "{}p setup", pc);
+            for track in tracks::ALL_TRACKS {
+                let holder = tracks::holder(&state, track);
+                assert_eq!(tracks::position(&state, holder, track), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_move_to_bottom_shifts_houses_up() {
+        let mut state = make_6p_state(42);
+        let before = tracks::order(&state, Track::IronThrone);
+        tracks::move_to_bottom(&mut state, before[0], Track::IronThrone);
+        let after = tracks::order(&state, Track::IronThrone);
+        assert_eq!(&after[..5], &before[1..]);
+        assert_eq!(after[5], before[0]);
+        assert_eq!(state.turn_order, after, "turn order follows the Iron Throne");
+        assert_eq!(tracks::check_invariants(&state), Ok(()));
+    }
+
+    #[test]
+    fn test_set_position_and_swap() {
+        let mut state = make_6p_state(42);
+        let before = tracks::order(&state, Track::KingsCourt);
+        tracks::set_position(&mut state, before[4], Track::KingsCourt, 2);
+        let after = tracks::order(&state, Track::KingsCourt);
+        assert_eq!(after, vec![before[0], before[4], before[1], before[2], before[3], before[5]]);
+
+        tracks::swap(&mut state, Track::KingsCourt, after[0], after[5]);
+        assert_eq!(tracks::holder(&state, Track::KingsCourt), after[5]);
+        assert_eq!(tracks::star_limit(&state, after[5]), 3);
+        assert_eq!(tracks::star_limit(&state, after[0]), 0);
+        assert_eq!(tracks::check_invariants(&state), Ok(()));
+    }
+
+    #[test]
+    fn test_tracks_stay_consistent_through_games() {
+        for pc in 3..=6 {
+            for seed in 0..5 {
+                let state = play_full_game_random(seed * 31 + pc as u64, pc);
+                assert_eq!(tracks::check_invariants(&state), Ok(()), "{}p seed {}", pc, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unit_pools_conserved_through_games() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        for pc in 3..=6 {
+            for seed in 0..4 {
+                let seed = seed * 17 + pc as u64;
+                let mut state = create_initial_state(pc, seed);
+                advance(&mut state);
+                let mut step = 0u64;
+                while state.winner.is_none() && step < 100_000 {
+                    step += 1;
+                    let mut rng = ChaCha8Rng::seed_from_u64(seed.wrapping_add(step.wrapping_mul(999961)));
+                    let Some(action) = random_legal_action(&state, &mut rng) else { break };
+                    apply_action(&mut state, action).unwrap();
+                    advance(&mut state);
+                    assert_eq!(state.check_unit_pools(), Ok(()), "{}p seed {} step {}", pc, seed, step);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_unit_pool_capacity_enforced() {
+        let mut state = create_initial_state(3, 1);
+        let house = state.playing_houses[0];
+        assert_eq!(state.check_unit_pools(), Ok(()));
+
+        // A full pool refuses a unit that never left it
+        let profile = state.house_mut(house);
+        profile.available_units = UnitPool::CAPACITY;
+        assert_eq!(profile.return_unit(UnitType::Knight), Err(GameError::PoolOverflow { house, unit_type: UnitType::Knight }));
+        assert_eq!(profile.available_units.knights, 5);
+
+        // An empty pool can't supply one, and a failed swap changes nothing
+        profile.available_units.siege_engines = 0;
+        assert_eq!(profile.take_unit(UnitType::SiegeEngine), Err(GameError::PoolExhausted { house, unit_type: UnitType::SiegeEngine }));
+        assert!(profile.swap_unit(UnitType::Footman, UnitType::Knight).is_err());
+        assert_eq!((profile.available_units.footmen, profile.available_units.knights), (10, 5));
+
+        // A knight minted on the board is caught by the invariant checker
+        let mut state = create_initial_state(3, 1);
+        let area = state.areas.iter().position(|a| a.units.iter().any(|u| u.house == house)).unwrap();
+        state.areas[area].units.push(Unit { unit_type: UnitType::Knight, house, routed: false });
+        let err = state.check_unit_pools().unwrap_err();
+        assert!(matches!(err, GameError::UnitsNotConserved { unit_type: UnitType::Knight, .. }), "{}", err);
+        assert!(tracks::check_invariants(&state).is_err());
+    }
+
+    #[test]
+    fn test_march_ignores_repeated_unit_indices() {
+        let mut state = create_initial_state(3, 1);
+        let house = state.playing_houses[0];
+        let from = state.areas.iter().position(|a| a.house == Some(house) && a.units.len() >= 2).map(|i| AreaId(i as u8)).unwrap();
+        let to = crate::map::AREAS[from.0 as usize].adjacent.iter().copied()
+            .find(|&a| state.area(a).units.is_empty() && !state.garrisons.contains_key(&a)
+                && crate::map::AREAS[a.0 as usize].area_type == crate::map::AREAS[from.0 as usize].area_type)
+            .unwrap();
+        let before = state.area(from).units.clone();
+        state.clear_pending();
+        state.enqueue_pending(PendingDecision::ChooseMarch { house, from_area: from, valid_destinations: vec![to], selectable_units: vec![0, 1] });
+        apply_action(&mut state, Action::March { to, unit_indices: vec![0, 0] }).unwrap();
+        assert_eq!(state.area(to).units, vec![before[0]]);
+        assert_eq!(state.area(from).units.len() + 1, before.len());
+        assert_eq!(state.check_unit_pools(), Ok(()));
+    }
+
+    // ════════════════════════════════════════════════════════════════════
+    // Setup configuration
+    // ════════════════════════════════════════════════════════════════════
+
+    #[test]
+    fn test_custom_house_selection() {
+        use HouseName::*;
+        let config = SetupConfig::with_houses(vec![Stark, Lannister, Baratheon, Tyrell], 7);
+        let state = create_game(&config).unwrap();
+        assert_eq!(state.playing_houses.len(), 4);
+        assert!(state.playing_houses.contains(&Tyrell));
+        assert!(!state.playing_houses.contains(&Greyjoy));
+        // Greyjoy's home is held by neutral forces; nothing in the south is closed
+        assert_eq!(state.garrisons.get(&PYKE).map(|g| (g.house, g.strength)), Some((None, 5)));
+        assert!(state.areas.iter().all(|a| !a.blocked));
+        assert_eq!(tracks::check_invariants(&state), Ok(()));
+    }
+
+    #[test]
+    fn test_illegal_house_selections_rejected() {
+        use HouseName::*;
+        // Southern houses cannot play when the south is closed
+        assert!(SetupConfig::with_houses(vec![Stark, Lannister, Tyrell], 1).validate().is_err());
+        assert!(SetupConfig::with_houses(vec![Stark, Lannister, Baratheon, Stark], 1).validate().is_err());
+        assert!(SetupConfig::with_houses(vec![Stark, Lannister], 1).validate().is_err());
+        assert!(create_game(&SetupConfig::with_houses(vec![Greyjoy, Lannister, Martell], 1)).is_err());
+        for pc in 3..=6 {
+            assert_eq!(SetupConfig::new(pc, 1).validate(), Ok(()));
+        }
+        assert_eq!("tyReLL".parse::<HouseName>(), Ok(Tyrell));
+        assert!("targaryen".parse::<HouseName>().is_err());
+    }
+
+    #[test]
+    fn test_seat_randomization_is_deterministic_permutation() {
+        let mut config = SetupConfig::new(6, 99);
+        assert_eq!(config.seats(), config.houses, "seats follow house order by default");
+        config.randomize_seats = true;
+        let seats = config.seats();
+        assert_eq!(seats, config.seats());
+        assert_eq!(seats.len(), config.houses.len());
+        assert!(config.houses.iter().all(|h| seats.contains(h)));
+        // Different seeds eventually give a different seating
+        assert!((0..20).any(|s| SetupConfig { seed: s, ..config.clone() }.seats() != seats));
+        // Seating does not change the board itself
+        assert_eq!(format!("{:?}", create_game(&config).unwrap().areas), format!("{:?}", create_initial_state(6, 99).areas));
+    }
+
+    // ════════════════════════════════════════════════════════════════════
+    // Final ranking
+    // ════════════════════════════════════════════════════════════════════
+
+    #[test]
+    fn test_final_ranking_puts_winner_first_then_tiebreaker() {
+        for pc in 3..=6 {
+            let state = play_full_game_random(pc as u64 * 17, pc);
+            let ranking = final_ranking(&state);
+            assert_eq!(ranking.len(), pc as usize);
+            assert_eq!(Some(ranking[0]), state.winner);
+            for pair in ranking[1..].windows(2) {
+                let (a, b) = (final_score(&state, pair[0]), final_score(&state, pair[1]));
+                assert!(a.0 > b.0 || (a.0 == b.0 && (a.1, a.2, std::cmp::Reverse(a.3)) >= (b.1, b.2, std::cmp::Reverse(b.3))),
+                    "{:?} ranked above {:?}", pair[0], pair[1]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_final_ranking_orders_by_supply_on_equal_castles() {
+        let mut state = make_6p_state(42);
+        for area in state.areas.iter_mut() {
+            area.house = None;
+        }
+        state.house_mut(HouseName::Greyjoy).supply = 6;
+        state.house_mut(HouseName::Stark).supply = 5;
+        let ranking = final_ranking(&state);
+        assert_eq!(&ranking[..2], &[HouseName::Greyjoy, HouseName::Stark]);
+        // A declared winner beats any score
+        state.winner = Some(HouseName::Martell);
+        assert_eq!(final_ranking(&state)[0], HouseName::Martell);
+    }
+
+    // ════════════════════════════════════════════════════════════════════
+    // Event log statistics
+    // ════════════════════════════════════════════════════════════════════
+
+    #[test]
+    fn test_power_events_account_for_final_power() {
+        for pc in 3..=6 {
+            let seed = pc as u64 * 101;
+            let initial = create_initial_state(pc, seed);
+            let state = play_full_game_random(seed, pc);
+            for &h in &state.playing_houses {
+                let net: i32 = state.events.iter()
+                    .map(|e| match e.kind {
+                        GameEventKind::PowerGained { house, amount } if house == h => amount as i32,
+                        GameEventKind::PowerSpent { house, amount } if house == h => -(amount as i32),
+                        _ => 0,
+                    })
+                    .sum();
+                assert_eq!(initial.house(h).power as i32 + net, state.house(h).power as i32,
+                    "{}p {:?}: power events must explain the final pool", pc, h);
+            }
+        }
+    }
+
+    #[test]
+    fn test_combat_resolution_is_logged() {
+        let mut state = post_combat_state(
+            HouseName::Lannister, HouseCardId::CerseiLannister,
+            HouseName::Stark, HouseCardId::CatelynStark,
+            WINTERFELL, LANNISPORT,
+        );
+        stark_defends_white_harbor(&mut state);
+        advance(&mut state);
+        apply_action(&mut state, Action::CerseiRemoveOrder(WHITE_HARBOR)).unwrap();
+        let to = match state.peek_pending() {
+            Some(PendingDecision::Retreat { possible_areas, .. }) => possible_areas[0],
+            other => panic!("Expected Retreat, got {:?}", other),
+        };
+        apply_action(&mut state, Action::Retreat(to)).unwrap();
+        let battles: Vec<_> = state.events.iter()
+            .filter_map(|e| match &e.kind {
+                GameEventKind::CombatResolved { area_id, attacker, winner, attacker_card, .. } =>
+                    Some((*area_id, *attacker, *winner, *attacker_card)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(battles, vec![(WINTERFELL, HouseName::Lannister, HouseName::Lannister, Some(HouseCardId::CerseiLannister))]);
+        let strengths = state.events.iter().find_map(|e| match e.kind {
+            GameEventKind::CombatResolved { attacker_strength, defender_strength, casualties, .. } =>
+                Some((attacker_strength, defender_strength, casualties)),
+            _ => None,
+        });
+        let (atk, def, casualties) = strengths.unwrap();
+        assert!(atk >= def, "the recorded strengths must agree with the winner");
+        assert_eq!(casualties, 0, "Cersei has no swords");
+    }
+
+    #[test]
+    fn test_card_stats_track_plays_swords_and_abilities() {
+        let mut state = post_combat_state(
+            HouseName::Lannister, HouseCardId::CerseiLannister,
+            HouseName::Stark, HouseCardId::CatelynStark,
+            WINTERFELL, LANNISPORT,
+        );
+        stark_defends_white_harbor(&mut state);
+        advance(&mut state);
+        apply_action(&mut state, Action::CerseiRemoveOrder(WHITE_HARBOR)).unwrap();
+        let to = match state.peek_pending() {
+            Some(PendingDecision::Retreat { possible_areas, .. }) => possible_areas[0],
+            other => panic!("Expected Retreat, got {:?}", other),
+        };
+        apply_action(&mut state, Action::Retreat(to)).unwrap();
+
+        let lannister = &state.house(HouseName::Lannister).card_stats;
+        assert_eq!(lannister.cards_played, 1);
+        assert_eq!(lannister.plays.get(&HouseCardId::CerseiLannister), Some(&1));
+        assert_eq!(lannister.swords_dealt, 0, "Cersei has no swords");
+        assert_eq!(lannister.abilities_triggered, 1, "Cersei removed an order");
+        let stark = &state.house(HouseName::Stark).card_stats;
+        assert_eq!((stark.cards_played, stark.swords_dealt), (1, 0));
+
+        // Public: every house sees them
+        let view = crate::visibility::player_view(&state, HouseName::Baratheon);
+        assert_eq!(&view.house_info[&HouseName::Lannister].card_stats, lannister);
+    }
+
+    #[test]
+    fn test_wildling_attacks_are_logged() {
+        let mut attacks = 0;
+        for seed in 0..6 {
+            let state = play_full_game_random(seed * 7 + 3, 6);
+            for e in &state.events {
+                if let GameEventKind::WildlingAttack { threat, total_bid, bids, nights_watch_won, target, .. } = &e.kind {
+                    attacks += 1;
+                    assert_eq!(bids.len(), 6, "every house bids");
+                    assert!(bids.iter().map(|&(_, b)| b).sum::<u8>() <= *total_bid);
+                    assert_eq!(*nights_watch_won, total_bid >= threat);
+                    let target_bid = bids.iter().find(|(h, _)| h == target).unwrap().1;
+                    let extreme = if *nights_watch_won {
+                        bids.iter().map(|&(_, b)| b).max()
+                    } else {
+                        bids.iter().map(|&(_, b)| b).min()
+                    };
+                    assert_eq!(Some(target_bid), extreme);
+                }
+            }
+        }
+        assert!(attacks > 0, "random games should reach at least one wildling attack");
+    }
+
+    #[test]
+    fn test_observation_encoding_has_fixed_length() {
+        use crate::encoding::{encode_observation, OBS_LEN};
+        use crate::visibility::player_view;
+
+        let mut state = create_initial_state(6, 42);
+        advance(&mut state);
+        for &h in &state.playing_houses.clone() {
+            let view = player_view(&state, h);
+            assert_eq!(encode_observation(&view, None).len(), OBS_LEN);
+            assert_eq!(encode_observation(&view, Some(WINTERFELL)).len(), OBS_LEN);
+        }
+        let state = play_full_game_random(5, 4);
+        let view = player_view(&state, state.playing_houses[0]);
+        assert_eq!(encode_observation(&view, None).len(), OBS_LEN);
+    }
+
+    #[test]
+    fn test_order_placement_targets_split_per_area() {
+        use crate::encoding::{decision_targets, DecisionKind};
+
+        let pending = PendingDecision::PlaceOrders { house: HouseName::Stark };
+        let action = Action::PlaceOrders(vec![(WINTERFELL, 0), (AreaId(1), 5)]);
+        let targets = decision_targets(&pending, &action);
+        assert_eq!(targets.len(), 2);
+        assert!(targets.iter().all(|t| t.kind == DecisionKind::PlaceOrders));
+        assert_eq!(targets[0].focus, Some(WINTERFELL));
+        for t in &targets {
+            assert!(t.action < DecisionKind::PlaceOrders.action_space());
+        }
+    }
+
+    #[test]
+    fn test_decision_kind_keys_round_trip() {
+        use crate::encoding::DecisionKind;
+
+        assert_eq!(DecisionKind::SelectHouseCard.key(), "select_house_card");
+        for kind in DecisionKind::ALL {
+            assert_eq!(kind.key().parse::<DecisionKind>(), Ok(kind));
+            assert_eq!(kind.name().parse::<DecisionKind>(), Ok(kind));
+        }
+        assert!("select_card".parse::<DecisionKind>().is_err());
+    }
+
+    #[test]
+    fn test_player_view_json_round_trip() {
+        use crate::visibility::{player_view, PlayerView};
+
+        let mut state = create_initial_state(6, 42);
+        advance(&mut state);
+        assert!(!state.garrisons.is_empty(), "area-keyed maps must be exercised");
+        let view = player_view(&state, HouseName::Stark);
+        let json = serde_json::to_value(&view).unwrap();
+        let back: PlayerView = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&back).unwrap(), json);
+
+        let action = Action::March { to: WINTERFELL, unit_indices: vec![0, 1] };
+        let back: Action = serde_json::from_str(&serde_json::to_string(&action).unwrap()).unwrap();
+        assert!(matches!(back, Action::March { to, unit_indices } if to == WINTERFELL && unit_indices == [0, 1]));
+    }
+
+    #[test]
+    fn test_action_fits_pending_decision() {
+        use crate::engine::action_fits;
+
+        let pending = PendingDecision::SelectHouseCard {
+            house: HouseName::Stark,
+            available_cards: vec![HouseCardId::EddardStark, HouseCardId::RobbStark],
+        };
+        assert!(action_fits(&pending, &Action::SelectCard(HouseCardId::RobbStark)));
+        assert!(!action_fits(&pending, &Action::SelectCard(HouseCardId::TywinLannister)));
+        assert!(!action_fits(&pending, &Action::Bid(3)));
+
+        let march = PendingDecision::ChooseMarch {
+            house: HouseName::Stark,
+            from_area: WINTERFELL,
+            valid_destinations: vec![AreaId(1)],
+            selectable_units: vec![0, 2],
+        };
+        assert!(action_fits(&march, &Action::March { to: AreaId(1), unit_indices: vec![0] }));
+        assert!(!action_fits(&march, &Action::March { to: AreaId(1), unit_indices: vec![0, 1] }));
+        assert!(action_fits(&march, &Action::March { to: WINTERFELL, unit_indices: vec![] }));
+        assert!(!action_fits(&march, &Action::March { to: AreaId(2), unit_indices: vec![0] }));
+        assert!(action_fits(&march, &Action::MarchSkip));
+    }
+
+    #[test]
+    fn test_validate_action_checks_orders_and_bids() {
+        use crate::engine::validate_action;
+
+        let mut state = make_6p_state(42);
+        state.pending = VecDeque::from([PendingDecision::PlaceOrders { house: HouseName::Stark }]);
+        assert!(validate_action(&state, &Action::PlaceOrders(vec![(WINTERFELL, 0)])).is_ok());
+        assert!(validate_action(&state, &Action::PlaceOrders(vec![(LANNISPORT, 0)])).is_err(), "not Stark's area");
+        assert!(validate_action(&state, &Action::PlaceOrders(vec![(WINTERFELL, 0), (WINTERFELL, 1)])).is_err());
+        assert!(validate_action(&state, &Action::Bid(1)).is_err(), "not an answer to PlaceOrders");
+
+        let power = state.houses[&HouseName::Stark].power;
+        state.pending = VecDeque::from([PendingDecision::Bidding {
+            house: HouseName::Stark,
+            bidding_type: BiddingType::Wildling,
+            track: None,
+        }]);
+        assert!(validate_action(&state, &Action::Bid(power)).is_ok());
+        assert!(validate_action(&state, &Action::Bid(power + 1)).is_err());
+    }
+
+    #[test]
+    fn test_every_decision_is_answered_by_its_own_actions() {
+        use crate::encoding::DecisionKind;
+        use crate::engine::check_answer;
+
+        // One action of every variant: between them they answer every kind
+        let actions = [
+            Action::PlaceOrders(vec![]), Action::ResolveOrder(WINTERFELL), Action::Raid(None),
+            Action::March { to: WINTERFELL, unit_indices: vec![] }, Action::MarchSkip, Action::LeavePowerToken(true),
+            Action::DeclareSupport(SupportChoice::None), Action::SelectCard(HouseCardId::RobbStark),
+            Action::UseValyrianBlade(false), Action::Bid(0), Action::WesterosChoice(0), Action::Muster(vec![]),
+            Action::Retreat(WINTERFELL), Action::Reconcile(WINTERFELL, 0), Action::MessengerRaven(None),
+            Action::AeronSwap(None), Action::TyrionReplace(HouseCardId::RobbStark),
+            Action::PatchfaceDiscard(HouseCardId::RobbStark), Action::RobbRetreat(WINTERFELL),
+            Action::CerseiRemoveOrder(WINTERFELL), Action::DoranChooseTrack(Track::IronThrone),
+            Action::QueenOfThorns(WINTERFELL), Action::WildlingPenalty(0), Action::Resign, Action::OfferDraw,
+        ];
+        for kind in DecisionKind::ALL {
+            assert!(actions.iter().any(|a| DecisionKind::answered_by(a) == Some(kind)), "nothing answers {}", kind.key());
+        }
+
+        let pending = PendingDecision::SelectHouseCard { house: HouseName::Stark, available_cards: vec![HouseCardId::RobbStark] };
+        for action in &actions {
+            let checked = check_answer(&pending, action);
+            match DecisionKind::answered_by(action) {
+                Some(DecisionKind::SelectHouseCard) | None => assert_eq!(checked, Ok(())),
+                Some(other) => assert_eq!(checked, Err(ActionError::WrongDecisionType { pending: DecisionKind::SelectHouseCard, action: other })),
+            }
+        }
+        assert!(matches!(check_answer(&pending, &Action::SelectCard(HouseCardId::EddardStark)), Err(ActionError::IllegalTarget(_))));
+    }
+
+    #[test]
+    fn test_refused_actions_say_why_and_change_nothing() {
+        use crate::encoding::DecisionKind;
+        use crate::engine::apply_action_as;
+
+        let mut state = make_6p_state(42);
+        state.pending = VecDeque::from([PendingDecision::PlaceOrders { house: HouseName::Stark }]);
+        let before = serde_json::to_string(&state).unwrap();
+        let refused = apply_action(&mut state, Action::Bid(1)).unwrap_err();
+        assert_eq!(refused, ActionError::WrongDecisionType { pending: DecisionKind::PlaceOrders, action: DecisionKind::Bidding });
+        assert_eq!(refused.to_string(), "a bidding answer to a pending place_orders decision");
+        assert_eq!(
+            apply_action(&mut state, Action::PlaceOrders(vec![(LANNISPORT, 0)])),
+            Err(ActionError::IllegalTarget("Stark has no units in Lannisport".to_string())),
+        );
+        assert_eq!(
+            apply_action_as(&mut state, HouseName::Lannister, Action::PlaceOrders(vec![(WINTERFELL, 0)])),
+            Err(ActionError::NotYourTurn { house: HouseName::Lannister, to_move: HouseName::Stark }),
+        );
+        assert_eq!(serde_json::to_string(&state).unwrap(), before, "a refused action changes nothing");
+
+        apply_action_as(&mut state, HouseName::Stark, Action::PlaceOrders(vec![(WINTERFELL, 0)])).unwrap();
+        assert!(state.area(WINTERFELL).order.is_some());
+        state.pending.clear();
+        assert_eq!(apply_action(&mut state, Action::MarchSkip), Err(ActionError::NoDecision));
+    }
+
+    #[test]
+    fn test_star_orders_respect_the_kings_court_limit() {
+        use crate::engine::{legal_star_count, validate_action};
+
+        let mut state = make_6p_state(42);
+        tracks::set_position(&mut state, HouseName::Stark, Track::KingsCourt, 4);
+        assert_eq!(legal_star_count(&state, HouseName::Stark), 1);
+        state.pending = VecDeque::from([PendingDecision::PlaceOrders { house: HouseName::Stark }]);
+        // March +1★ and Defense +2★
+        let two_stars = Action::PlaceOrders(vec![(WINTERFELL, 2), (WHITE_HARBOR, 5), (THE_SHIVERING_SEA, 0)]);
+        let refused = validate_action(&state, &two_stars);
+        assert!(matches!(refused, Err(ActionError::IllegalOrders(_))), "{:?}", refused);
+        assert!(validate_action(&state, &Action::PlaceOrders(vec![(WINTERFELL, 2), (WHITE_HARBOR, 4)])).is_ok());
+
+        // Applied anyway, none of the orders is placed
+        assert_eq!(apply_action(&mut state, two_stars), refused);
+        assert!(state.areas.iter().all(|a| a.order.is_none()));
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::PlaceOrders { .. })));
+
+        tracks::set_position(&mut state, HouseName::Stark, Track::KingsCourt, 6);
+        assert_eq!(legal_star_count(&state, HouseName::Stark), 0);
+    }
+
+    #[test]
+    fn test_restricted_and_reused_order_tokens_are_refused() {
+        use crate::engine::validate_action;
+
+        let mut state = make_6p_state(42);
+        state.order_restrictions.push(OrderType::Support);
+        state.star_order_restrictions.push(OrderType::March);
+        state.pending = VecDeque::from([PendingDecision::PlaceOrders { house: HouseName::Stark }]);
+        let refused = [
+            (vec![(WINTERFELL, 6)], "Support orders are restricted"),
+            (vec![(WINTERFELL, 2)], "March star orders are restricted"),
+            (vec![(WINTERFELL, 0), (WHITE_HARBOR, 0)], "used twice"),
+        ];
+        for (orders, reason) in refused {
+            let action = Action::PlaceOrders(orders);
+            let err = validate_action(&state, &action).unwrap_err();
+            assert!(matches!(&err, ActionError::IllegalOrders(r) if r.contains(reason)), "{}", err);
+            assert_eq!(apply_action(&mut state, action), Err(err));
+            assert!(matches!(state.peek_pending(), Some(PendingDecision::PlaceOrders { .. })), "the decision stays open");
+            assert!(state.areas.iter().all(|a| a.order.is_none()));
+        }
+        assert!(validate_action(&state, &Action::PlaceOrders(vec![(WINTERFELL, 0), (WHITE_HARBOR, 1)])).is_ok());
+
+        // A token already on the board can't be placed again
+        state.area_mut(WINTERFELL).order = Some(Order {
+            order_type: OrderType::March, strength: -1, star: false, house: HouseName::Stark, token_index: 0,
+        });
+        assert!(validate_action(&state, &Action::PlaceOrders(vec![(WHITE_HARBOR, 0)])).unwrap_err().to_string().contains("used twice"));
+    }
+
+    #[test]
+    fn test_raven_swaps_only_own_orders_for_placeable_tokens() {
+        use crate::engine::validate_action;
+
+        let order = |house, token_index: u8| {
+            let token = ORDER_TOKENS[token_index as usize];
+            Some(Order { order_type: token.order_type, strength: token.strength, star: token.star, house, token_index })
+        };
+        let mut state = make_6p_state(42);
+        state.engine_phase = EnginePhase::action();
+        state.area_mut(WINTERFELL).order = order(HouseName::Stark, 0);
+        state.area_mut(THE_SHIVERING_SEA).order = order(HouseName::Stark, 3);
+        state.area_mut(LANNISPORT).order = order(HouseName::Lannister, 0);
+        state.order_restrictions.push(OrderType::Support);
+        tracks::set_position(&mut state, HouseName::Stark, Track::KingsCourt, 6);
+        state.pending = VecDeque::from([PendingDecision::MessengerRaven { house: HouseName::Stark }]);
+
+        let refused = [
+            // Another house's order, and an area with no order
+            (Action::MessengerRaven(Some((LANNISPORT, 1))), "no order to swap"),
+            (Action::MessengerRaven(Some((WHITE_HARBOR, 1))), "no order to swap"),
+            // A restricted token, one already on the board, a star over the limit
+            (Action::MessengerRaven(Some((WINTERFELL, 6))), "restricted, in use"),
+            (Action::MessengerRaven(Some((WINTERFELL, 3))), "restricted, in use"),
+            (Action::MessengerRaven(Some((WINTERFELL, 2))), "restricted, in use"),
+        ];
+        for (action, reason) in refused {
+            let err = validate_action(&state, &action).unwrap_err();
+            assert!(matches!(&err, ActionError::IllegalTarget(r) | ActionError::IllegalOrders(r) if r.contains(reason)), "{}", err);
+            assert_eq!(apply_action(&mut state, action), Err(err));
+            assert!(matches!(state.peek_pending(), Some(PendingDecision::MessengerRaven { .. })), "the decision stays open");
+        }
+        assert!(matches!(
+            validate_action(&state, &Action::MessengerRaven(Some((LANNISPORT, 1)))),
+            Err(ActionError::IllegalTarget(_)),
+        ));
+        assert_eq!(state.area(LANNISPORT).order, order(HouseName::Lannister, 0));
+        assert_eq!(state.area(WHITE_HARBOR).order, None);
+        assert_eq!(state.area(WINTERFELL).order, order(HouseName::Stark, 0));
+
+        apply_action(&mut state, Action::MessengerRaven(Some((WINTERFELL, 4)))).unwrap();
+        assert_eq!(state.area(WINTERFELL).order, order(HouseName::Stark, 4));
+    }
+
+    #[test]
+    fn test_reconcile_refuses_units_not_in_the_area() {
+        use crate::engine::validate_action;
+
+        let mut state = make_6p_state(42);
+        let units = state.area(WINTERFELL).units.len();
+        state.pending = VecDeque::from([PendingDecision::Reconcile {
+            house: HouseName::Stark, area_id: WINTERFELL, current_size: units as u8, max_allowed: units as u8 - 1,
+        }]);
+        let action = Action::Reconcile(WINTERFELL, units);
+        let err = validate_action(&state, &action).unwrap_err();
+        assert!(matches!(err, ActionError::IllegalTarget(_)), "{}", err);
+        assert_eq!(apply_action(&mut state, action), Err(err));
+        assert_eq!(state.area(WINTERFELL).units.len(), units);
+        assert!(matches!(state.peek_pending(), Some(PendingDecision::Reconcile { .. })), "the decision stays open");
+
+        apply_action(&mut state, Action::Reconcile(WINTERFELL, 0)).unwrap();
+        assert_eq!(state.area(WINTERFELL).units.len(), units - 1);
+    }
+
+    /// A 4-player game in a wildling bidding, nobody asked yet, with Stark
+    /// out of power.
+    fn wildling_bidding(auto_zero_bids: bool) -> GameState {
+        let mut config = SetupConfig::new(4, 9);
+        config.options.auto_zero_bids = auto_zero_bids;
+        let mut state = create_game(&config).unwrap();
+        state.clear_pending();
+        state.house_mut(HouseName::Stark).power = 0;
+        state.engine_phase = EnginePhase::westeros();
+        let bid_order = state.turn_order.clone();
+        state.westeros_mut().unwrap().substate = WesterosSubstate::Bidding(BiddingState::new(BiddingType::Wildling, None, Vec::new(), bid_order));
+        advance(&mut state);
+        state
+    }
+
+    #[test]
+    fn test_bids_over_power_are_refused_and_zero_power_bids_are_automatic() {
+        use crate::engine::open_bids;
+
+        // Asked like everyone else by default
+        let state = wildling_bidding(false);
+        assert!(open_bids(&state).iter().any(|p| p.house() == HouseName::Stark));
+
+        let mut state = wildling_bidding(true);
+        assert!(open_bids(&state).iter().all(|p| p.house() != HouseName::Stark));
+        let order = state.turn_order.clone();
+        let mut asked = Vec::new();
+        while let Some(PendingDecision::Bidding { house, .. }) = state.peek_pending().cloned() {
+            asked.push(house);
+            let power = state.house(house).power;
+            // Too much is refused and the decision stays open
+            assert_eq!(apply_action(&mut state, Action::Bid(power + 1)),
+                Err(ActionError::InsufficientPower { house, needed: power + 1, available: power }));
+            assert!(matches!(state.peek_pending(), Some(PendingDecision::Bidding { house: h, .. }) if *h == house));
+            apply_action(&mut state, Action::Bid(1)).unwrap();
+            advance(&mut state);
+        }
+        let expected: Vec<HouseName> = order.iter().copied().filter(|&h| h != HouseName::Stark).collect();
+        assert_eq!(asked, expected);
+
+        let revealed = state.events.iter().find_map(|e| match &e.kind {
+            GameEventKind::BidsRevealed { bidding_type: BiddingType::Wildling, bids } => Some(bids.clone()),
+            _ => None,
+        }).expect("bids are revealed");
+        assert_eq!(revealed, order.iter().map(|&h| (h, if h == HouseName::Stark { 0 } else { 1 })).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_bids_are_sealed_until_all_are_in() {
+        use crate::engine::{apply_action_as, open_bids};
+        use crate::visibility::player_view;
+
+        let mut state = wildling_bidding(false);
+        let order = state.turn_order.clone();
+        // Everyone is asked at once, each sees only their own decision
+        assert_eq!(open_bids(&state).iter().map(|p| p.house()).collect::<Vec<_>>(), order);
+        for &h in &order {
+            assert!(matches!(player_view(&state, h).pending, Some(PendingDecision::Bidding { house, .. }) if house == h));
+        }
+
+        // Bids come in last to first; nobody learns an amount until the last
+        let mut bidders = order.clone();
+        bidders.reverse();
+        let last = bidders.pop().unwrap();
+        for (i, &h) in bidders.iter().enumerate() {
+            let amount = state.house(h).power.min(1);
+            apply_action_as(&mut state, h, Action::Bid(amount)).unwrap();
+            advance(&mut state);
+            assert_eq!(apply_action_as(&mut state, h, Action::Bid(0)), Err(ActionError::NotYourTurn { house: h, to_move: order[0] }));
+            let view = player_view(&state, last);
+            assert_eq!(view.bids_in, order.iter().rev().take(i + 1).rev().copied().collect::<Vec<_>>());
+            assert!(player_view(&state, h).pending.is_none());
+            assert!(!state.events.iter().any(|e| matches!(e.kind, GameEventKind::BidsRevealed { .. })));
+        }
+
+        apply_action_as(&mut state, last, Action::Bid(0)).unwrap();
+        advance(&mut state);
+        assert!(state.bidding().is_none());
+        let revealed = state.events.iter().find_map(|e| match &e.kind {
+            GameEventKind::BidsRevealed { bids, .. } => Some(bids.clone()),
+            _ => None,
+        }).expect("bids are revealed together");
+        assert_eq!(revealed.len(), order.len());
+    }
+
+    #[test]
+    fn test_auto_forced_answers_single_option_decisions() {
+        use crate::engine::{forced_action, legal_actions};
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        let play = |auto_forced: bool| {
+            let mut config = SetupConfig::new(6, 5);
+            config.options.auto_forced = auto_forced;
+            let mut state = create_game(&config).unwrap();
+            advance(&mut state);
+            let mut rng = ChaCha8Rng::seed_from_u64(5);
+            let (mut asked, mut single) = (0, 0);
+            while state.winner.is_none() {
+                if forced_action(&state).is_some() {
+                    single += 1;
+                }
+                let Some(action) = random_legal_action(&state, &mut rng) else { break };
+                asked += 1;
+                apply_action(&mut state, action).unwrap();
+                advance(&mut state);
+            }
+            (state, asked, single)
+        };
+
+        let (state, asked, single) = play(true);
+        assert_eq!(single, 0, "no decision with one answer reaches a player");
+        let forced = state.events.iter()
+            .filter(|e| matches!(e.kind, GameEventKind::DecisionForced { .. }))
+            .count();
+        assert!(forced > 0 && asked > 0);
+
+        let (state, _, single) = play(false);
+        assert!(single > 0, "without the option they are asked");
+        assert!(state.events.iter().all(|e| !matches!(e.kind, GameEventKind::DecisionForced { .. })));
+
+        // Orders are never forced
+        let mut state = make_6p_state(42);
+        state.pending = VecDeque::from([PendingDecision::PlaceOrders { house: HouseName::Stark }]);
+        assert!(forced_action(&state).is_none());
+        state.pending = VecDeque::from([PendingDecision::SelectHouseCard {
+            house: HouseName::Stark,
+            available_cards: vec![HouseCardId::RobbStark],
+        }]);
+        assert!(matches!(forced_action(&state), Some(Action::SelectCard(HouseCardId::RobbStark))));
+        assert_eq!(legal_actions(&state).len(), 1);
+    }
+
+    #[test]
+    fn test_massing_on_the_milkwater_discards_the_strongest_cards() {
+        let mut state = wildling_bidding(true);
+        state.wildling_threat = 12;
+        let milkwater = cards::wildling_deck().into_iter()
+            .find(|c| c.card_type == WildlingCardType::MassingOnTheMilkwater)
+            .unwrap();
+        state.wildling_deck.push(milkwater);
+        while state.peek_pending().is_some_and(|p| matches!(p, PendingDecision::Bidding { .. })) {
+            apply_action(&mut state, Action::Bid(0)).unwrap();
+            advance(&mut state);
+        }
+        let target = state.events.iter().find_map(|e| match e.kind {
+            GameEventKind::WildlingAttack { nights_watch_won: false, target, .. } => Some(target),
+            _ => None,
+        }).expect("the wildlings win");
+        let profile = state.house(target);
+        let strength = |c: &HouseCardId| cards::get_house_card(*c).strength;
+        assert_eq!(profile.discards.iter().map(strength).collect::<Vec<_>>(), vec![4]);
+        assert_eq!(profile.hand.len(), 6, "the rest of the hand stays");
+    }
+
+    #[test]
+    fn test_static_eval_probabilities() {
+        use crate::eval::{static_eval, win_probabilities};
+        use crate::visibility::player_view;
+
+        let mut state = create_initial_state(6, 42);
+        advance(&mut state);
+        let probs = win_probabilities(&state);
+        assert_eq!(probs.len(), 6);
+        let total: f32 = probs.iter().map(|(_, p)| p).sum();
+        assert!((total - 1.0).abs() < 1e-4);
+        for &(house, p) in &probs {
+            assert!(p > 0.05 && p < 0.5, "{:?} starts at {}", house, p);
+            assert_eq!(static_eval(&player_view(&state, house), house), p, "a view sees the same public position");
+        }
+
+        // Taking a rival's castle raises the taker's estimate
+        let before = static_eval(&state, HouseName::Stark);
+        let lannister_castle = (0..AREAS.len())
+            .find(|&i| state.areas[i].house == Some(HouseName::Lannister) && AREAS[i].has_castle_or_stronghold())
+            .unwrap();
+        set_area_control(&mut state, AreaId(lannister_castle as u8), Some(HouseName::Stark));
+        assert!(static_eval(&state, HouseName::Stark) > before);
+
+        state.winner = Some(HouseName::Greyjoy);
+        assert_eq!(static_eval(&state, HouseName::Greyjoy), 1.0);
+        assert_eq!(static_eval(&state, HouseName::Stark), 0.0);
+    }
+
+    #[test]
+    fn test_rollout_limit_truncates() {
+        use crate::rollout::{rollout, RolloutLimit};
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        assert_eq!("full".parse::<RolloutLimit>(), Ok(RolloutLimit::FULL));
+        let limit: RolloutLimit = "rounds=2, decisions=300".parse().unwrap();
+        assert_eq!(limit, RolloutLimit { decisions: Some(300), rounds: Some(2) });
+        assert_eq!(limit.to_string().parse::<RolloutLimit>(), Ok(limit));
+        assert!("turns=3".parse::<RolloutLimit>().is_err());
+
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+        let mut policy = |state: &GameState, _: HouseName| random_legal_action(state, &mut rng).unwrap();
+        let mut state = create_initial_state(6, 5);
+        let r = rollout(&mut state, RolloutLimit::decisions(40), 100_000, &mut policy).unwrap();
+        assert_eq!(r.decisions, 40);
+        assert!(r.truncated);
+        let round = state.round;
+        let r = rollout(&mut state, RolloutLimit::rounds(1), 100_000, &mut policy).unwrap();
+        assert!(r.truncated);
+        assert_eq!(state.round, round + 1);
+        let r = rollout(&mut state, RolloutLimit::FULL, 100_000, &mut policy).unwrap();
+        assert!(!r.truncated);
+        assert!(state.winner.is_some());
+    }
+
+    #[test]
+    fn test_determinization_matches_view() {
+        use crate::determinize::{determinize, supported, DeterminizationPool};
+        use crate::visibility::player_view;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        let mut rng = ChaCha8Rng::seed_from_u64(8);
+        let mut state = create_initial_state(6, 8);
+        let mut checked = 0;
+        let mut pool = DeterminizationPool::new(HouseName::Stark, 4, 8);
+        advance(&mut state);
+        while state.winner.is_none() && checked < 200 {
+            let pending = state.peek_pending().cloned().expect("game waits for a decision");
+            let view = player_view(&state, pending.house());
+            if supported(&view).is_ok() {
+                let guess = determinize(&view, &mut rng).unwrap();
+                assert_eq!(
+                    serde_json::to_value(player_view(&guess, view.viewer)).unwrap(),
+                    serde_json::to_value(&view).unwrap(),
+                    "a determinization looks exactly like the view it came from",
+                );
+                for &h in &state.playing_houses {
+                    assert_eq!(guess.houses[&h].hand.len(), state.houses[&h].hand.len());
+                }
+                if view.viewer == HouseName::Stark {
+                    // Refreshed pool members look like the newest view too
+                    for member in pool.sync(&view).unwrap() {
+                        assert_eq!(serde_json::to_value(player_view(member, view.viewer)).unwrap(), serde_json::to_value(&view).unwrap());
+                    }
+                }
+                checked += 1;
+            }
+            let action = random_legal_action(&state, &mut rng).unwrap();
+            apply_action(&mut state, action).unwrap();
+            advance(&mut state);
+        }
+        assert!(checked >= 50, "only {} positions determinized", checked);
+        let stats = pool.stats();
+        assert_eq!(stats.built, 4, "the pool is built once and refreshed after that");
+        assert!(stats.refreshed > 0);
+    }
+
+    // ── Frozen wire format ───────────────────────────────────────────────
+    //
+    // Stored logs and bot clients depend on these exact shapes. A failure
+    // here means the JSON changed: add a `#[serde(rename)]` or bump
+    // `wire::WIRE_VERSION` with an upgrade path, don't edit the expectation.
+
+    #[test]
+    fn test_frozen_wire_format() {
+        use serde_json::json;
+        fn wire<T: serde::Serialize>(value: &T) -> serde_json::Value {
+            serde_json::to_value(value).unwrap()
+        }
+
+        let actions = [
+            (Action::PlaceOrders(vec![(WINTERFELL, 3)]), json!({"type": "place_orders", "value": [[WINTERFELL.0, 3]]})),
+            (Action::ResolveOrder(AreaId(4)), json!({"type": "resolve_order", "value": 4})),
+            (Action::Raid(None), json!({"type": "raid", "value": null})),
+            (Action::March { to: AreaId(1), unit_indices: vec![0, 2] },
+                json!({"type": "march", "value": {"to": 1, "unit_indices": [0, 2]}})),
+            (Action::MarchSkip, json!({"type": "march_skip"})),
+            (Action::DeclareSupport(SupportChoice::Attacker), json!({"type": "declare_support", "value": "Attacker"})),
+            (Action::SelectCard(HouseCardId::EddardStark), json!({"type": "select_card", "value": "EddardStark"})),
+            (Action::Bid(2), json!({"type": "bid", "value": 2})),
+            (Action::Muster(vec![(AreaId(7), MusterAction2::Build(UnitType::Footman)), (AreaId(7), MusterAction2::Upgrade)]),
+                json!({"type": "muster", "value": [[7, {"type": "build", "value": "Footman"}], [7, {"type": "upgrade"}]]})),
+            (Action::Reconcile(AreaId(5), 1), json!({"type": "reconcile", "value": [5, 1]})),
+            (Action::MessengerRaven(Some((AreaId(3), 0))), json!({"type": "messenger_raven", "value": [3, 0]})),
+            (Action::DoranChooseTrack(Track::KingsCourt), json!({"type": "doran_choose_track", "value": "KingsCourt"})),
+            (Action::WildlingPenalty(1), json!({"type": "wildling_penalty", "value": 1})),
+        ];
+        for (action, expected) in actions {
+            assert_eq!(wire(&action), expected, "{:?}", action);
+            let back: Action = serde_json::from_value(expected.clone()).unwrap();
+            assert_eq!(wire(&back), expected);
+        }
+
+        let pending = PendingDecision::ChooseMarch { house: HouseName::Stark, from_area: AreaId(2), valid_destinations: vec![AreaId(1)], selectable_units: vec![] };
+        assert_eq!(wire(&pending), json!({"type": "choose_march", "house": "Stark", "from_area": 2, "valid_destinations": [1]}));
+        let pending = PendingDecision::Bidding { house: HouseName::Martell, bidding_type: BiddingType::Wildling, track: None };
+        assert_eq!(wire(&pending), json!({"type": "bidding", "house": "Martell", "bidding_type": "Wildling", "track": null}));
+        // Adding, renaming or repurposing a field is a new wire version,
+        // with an upgrade for the older shape
+        let pending = PendingDecision::TyrionReplace { decider: HouseName::Stark, opponent: HouseName::Lannister, available_cards: vec![] };
+        assert_eq!(wire(&pending), json!({"type": "tyrion_replace", "decider": "Stark", "opponent": "Lannister", "available_cards": []}));
+        let pending = PendingDecision::QueenOfThornsRemoveOrder { decider: HouseName::Tyrell, opponent: HouseName::Martell, valid_targets: vec![AreaId(4)] };
+        assert_eq!(wire(&pending), json!({"type": "queen_of_thorns_remove_order", "decider": "Tyrell", "opponent": "Martell", "valid_targets": [4]}));
+        let pending = PendingDecision::RobbRetreat {
+            house: HouseName::Stark, defender: HouseName::Lannister, units: vec![], from_area: AreaId(9), possible_areas: vec![AreaId(12)],
+        };
+        assert_eq!(wire(&pending), json!({"type": "robb_retreat", "house": "Stark", "defender": "Lannister", "units": [], "from_area": 9, "possible_areas": [12]}));
+
+        let event = GameEvent {
+            seq: 3,
+            round: 2,
+            phase: Phase::Action,
+            kind: GameEventKind::PowerGained { house: HouseName::Tyrell, amount: 2 },
+        };
+        assert_eq!(wire(&event), json!({"seq": 3, "round": 2, "phase": "Action",
+            "kind": {"type": "power_gained", "house": "Tyrell", "amount": 2}}));
+
+        assert_eq!(wire(&HouseName::Greyjoy), json!("Greyjoy"));
+        assert_eq!(wire(&UnitType::SiegeEngine), json!("SiegeEngine"));
+        assert_eq!(wire(&OrderType::ConsolidatePower), json!("ConsolidatePower"));
+        assert_eq!(wire(&Phase::Westeros), json!("Westeros"));
+    }
+
+    #[test]
+    fn test_wire_version_upgrades() {
+        use crate::wire::{read_action, read_event, read_pending, WIRE_VERSION};
+        use serde_json::json;
+
+        let action = read_action(json!({"March": {"to": 1, "unit_indices": [0]}}), 1).unwrap();
+        assert!(matches!(action, Action::March { to: AreaId(1), ref unit_indices } if unit_indices == &[0]));
+        assert!(matches!(read_action(json!("MarchSkip"), 1).unwrap(), Action::MarchSkip));
+        assert!(matches!(read_action(json!({"Bid": 2}), 1).unwrap(), Action::Bid(2)));
+        let muster = read_action(json!({"Muster": [[7, {"Build": "Knight"}], [7, "Upgrade"]]}), 1).unwrap();
+        match muster {
+            Action::Muster(builds) => {
+                assert!(matches!(builds[0], (AreaId(7), MusterAction2::Build(UnitType::Knight))));
+                assert!(matches!(builds[1], (AreaId(7), MusterAction2::Upgrade)));
+            }
+            other => panic!("expected muster, got {:?}", other),
+        }
+
+        let pending = read_pending(json!({"PlaceOrders": {"house": "Stark"}}), 1).unwrap();
+        assert!(matches!(pending, PendingDecision::PlaceOrders { house: HouseName::Stark }));
+        let event = read_event(json!({"seq": 0, "round": 1, "phase": "Westeros",
+            "kind": {"GameWon": {"house": "Lannister", "castles": 7}}}), 1).unwrap();
+        assert_eq!(event.kind, GameEventKind::GameWon { house: HouseName::Lannister, castles: 7 });
+
+        // Version 2 asked card-ability decisions of `opponent` (Queen of
+        // Thorns of `house`, once it had one); that house is the decider,
+        // and the other side, never recorded, is left as the decider too
+        let patchface = json!({"type": "patchface_discard", "opponent": "Stark", "visible_cards": ["EddardStark"]});
+        assert!(read_pending(patchface.clone(), 3).is_err());
+        let pending = read_pending(patchface, 2).unwrap();
+        assert_eq!(pending, PendingDecision::PatchfaceDiscard {
+            decider: HouseName::Stark, opponent: HouseName::Stark, visible_cards: vec![HouseCardId::EddardStark],
+        });
+        let pending = read_pending(json!({"type": "doran_choose_track", "opponent": "Tyrell"}), 2).unwrap();
+        assert_eq!(pending.house(), HouseName::Tyrell);
+        let pending = read_pending(json!({"type": "queen_of_thorns_remove_order", "house": "Tyrell", "opponent": "Martell", "valid_targets": [4]}), 2).unwrap();
+        assert_eq!(pending, PendingDecision::QueenOfThornsRemoveOrder {
+            decider: HouseName::Tyrell, opponent: HouseName::Martell, valid_targets: vec![AreaId(4)],
+        });
+        let queen = PendingDecision::QueenOfThornsRemoveOrder { decider: HouseName::Stark, opponent: HouseName::Stark, valid_targets: vec![] };
+        assert_eq!(read_pending(json!({"type": "queen_of_thorns_remove_order", "opponent": "Stark"}), 2).unwrap(), queen);
+        assert_eq!(read_pending(json!({"QueenOfThornsRemoveOrder": {"opponent": "Stark"}}), 1).unwrap(), queen);
+        let event = read_event(json!({"seq": 0, "round": 1, "phase": "Action",
+            "kind": {"type": "decision_forced", "decision": {"type": "tyrion_replace", "opponent": "Baratheon"}}}), 2).unwrap();
+        assert!(matches!(event.kind, GameEventKind::DecisionForced { decision: PendingDecision::TyrionReplace { decider: HouseName::Baratheon, .. } }));
+        // Its Robb retreat lacked the defender's side, which nothing records
+        let robb = json!({"type": "robb_retreat", "house": "Stark", "possible_areas": [9, 12]});
+        assert!(read_pending(robb.clone(), 3).is_err());
+        let refused = read_pending(robb.clone(), 2).unwrap_err();
+        assert!(refused.starts_with("cannot upgrade a robb_retreat"), "{}", refused);
+        let event = json!({"seq": 0, "round": 1, "phase": "Action", "kind": {"type": "decision_forced", "decision": robb}});
+        assert_eq!(read_event(event, 2).unwrap_err(), refused);
+        let robb = json!({"type": "robb_retreat", "house": "Stark", "defender": "Lannister", "units": [], "from_area": 9, "possible_areas": [12]});
+        assert_eq!(read_pending(robb, 2).unwrap(), PendingDecision::RobbRetreat {
+            house: HouseName::Stark, defender: HouseName::Lannister, units: Vec::new(), from_area: AreaId(9), possible_areas: vec![AreaId(12)],
+        });
+
+        assert!(read_action(json!({"type": "bid", "value": 1}), WIRE_VERSION).is_ok());
+        assert!(read_action(json!({"Bid": 1}), WIRE_VERSION).is_err());
+        assert!(read_action(json!({"type": "bid", "value": 1}), WIRE_VERSION + 1).is_err());
+    }
+
+    #[test]
+    fn test_replay_round_trip_keeps_annotations() {
+        use crate::replay::{Annotation, AnnotationTarget, Replay, ReplayPlayer};
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        let config = SetupConfig::new(4, 11);
+        let players = config.houses.iter().map(|&h| ReplayPlayer { house: h, agent: "random".into() }).collect();
+        let mut replay = Replay::new(config.clone(), players);
+        let mut state = create_game(&config).unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(11);
+        advance(&mut state);
+        while state.winner.is_none() && replay.actions.len() < 100_000 {
+            let pending = state.peek_pending().cloned().expect("game waits for a decision");
+            let action = random_legal_action(&state, &mut rng).unwrap();
+            let index = replay.record(state.round, pending.house(), action.clone());
+            if index == 3 {
+                replay.annotate(Annotation::new(AnnotationTarget::Action { index }, "mcts").with("win_probability", 0.72));
+            }
+            apply_action(&mut state, action).unwrap();
+            advance(&mut state);
+        }
+        let winner = state.winner.expect("random game finishes");
+        replay.finish(&state.events, winner, final_ranking(&state));
+        replay.annotate(Annotation::new(AnnotationTarget::Game, "reviewer").with_text("close game"));
+
+        // A note appended to the saved file after the fact
+        let extra = Annotation::new(AnnotationTarget::Event { seq: 0 }, "reviewer").with_text("first event");
+        let text = replay.to_jsonl() + &serde_json::to_string(&serde_json::json!({
+            "type": "annotation", "target": {"type": "event", "seq": 0}, "author": "reviewer", "text": "first event",
+        })).unwrap() + "\n";
+        let loaded = Replay::from_jsonl(&text).unwrap();
+        assert_eq!(loaded.actions.len(), replay.actions.len());
+        assert_eq!(loaded.annotations.len(), 3);
+        let on_action: Vec<_> = loaded.annotations_on(&AnnotationTarget::Action { index: 3 }).collect();
+        assert_eq!(on_action[0].data["win_probability"], 0.72);
+        assert_eq!(loaded.annotations[2], extra);
+
+        let replayed = loaded.play().unwrap();
+        assert_eq!(replayed.winner, Some(winner));
+        assert_eq!(replayed.events, state.events);
+
+        let mut broken = loaded.clone();
+        broken.actions.truncate(broken.actions.len() / 2);
+        assert!(broken.play().is_err(), "a shortened replay no longer matches the recorded events");
+    }
+
+    #[test]
+    fn test_random_legal_actions_pass_validation_and_rules() {
+        use crate::engine::{legal_actions, validate_action};
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        for seed in 0..8u64 {
+            let mut state = create_initial_state(3 + (seed % 4) as u8, seed);
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            advance(&mut state);
+            while state.winner.is_none() {
+                let house = state.peek_pending().expect("game waits for a decision").house();
+                for action in legal_actions(&state) {
+                    assert_eq!(validate_action(&state, &action), Ok(()), "seed {}", seed);
+                }
+                let action = random_legal_action(&state, &mut rng).unwrap();
+                assert_eq!(validate_action(&state, &action), Ok(()), "seed {}", seed);
+                if let Action::PlaceOrders(orders) = &action {
+                    let stars = orders.iter().filter(|&&(_, t)| ORDER_TOKENS[t as usize].star).count() as u8;
+                    assert!(stars <= tracks::star_limit(&state, house));
+                    assert!(orders.iter().all(|&(_, t)| !state.order_restrictions.contains(&ORDER_TOKENS[t as usize].order_type)));
+                }
+                // apply_action advances, and a Supply card drawn after the
+                // muster may lower the limits; only the muster is checked
+                let supply = state.house(house).supply;
+                let mustering = matches!(action, Action::Muster(_)) && !supply::check_supply_violation(&state, house);
+                apply_action(&mut state, action).unwrap();
+                if mustering && state.house(house).supply == supply {
+                    assert!(!supply::check_supply_violation(&state, house), "seed {}: muster broke supply", seed);
+                }
+                assert_eq!(state.check_unit_pools(), Ok(()));
+                advance(&mut state);
+            }
+        }
+    }
+
+    // ═════════════════════════════════════════════════════════════════════
+    // RESIGNATION AND DRAW TESTS
+    // ═════════════════════════════════════════════════════════════════════
+
+    /// Play random moves, resigning `quitter` the first time it is asked
+    /// from `round` on.
+    fn play_with_resignation(config: &SetupConfig, quitter: HouseName, round: u8) -> GameState {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        let mut state = create_game(config).unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(config.seed);
+        advance(&mut state);
+        let mut step = 0;
+        while state.winner.is_none() && step < 100_000 {
+            step += 1;
+            let pending = state.peek_pending().cloned().expect("game waits for a decision");
+            let action = if pending.house() == quitter && state.round >= round && !state.resigned.contains(&quitter)
+                && crate::engine::validate_action(&state, &Action::Resign).is_ok()
+            {
+                Action::Resign
+            } else {
+                random_legal_action(&state, &mut rng).unwrap()
+            };
+            apply_action(&mut state, action).unwrap();
+            advance(&mut state);
+        }
+        state
+    }
+
+    #[test]
+    fn test_resigned_house_leaves_the_board_and_ranks_last() {
+        for policy in [ResignPolicy::Remove, ResignPolicy::Neutral] {
+            let mut config = SetupConfig::new(4, 21);
+            config.options.resign_policy = policy;
+            let state = play_with_resignation(&config, HouseName::Stark, 3);
+
+            assert_eq!(state.resigned, vec![HouseName::Stark]);
+            assert!(state.winner.is_some_and(|w| w != HouseName::Stark), "the others play the game out");
+            assert!(state.events.iter().any(|e| e.kind == GameEventKind::Resigned { house: HouseName::Stark }));
+            assert!(state.areas.iter().all(|a| a.house != Some(HouseName::Stark)
+                && a.units.iter().all(|u| u.house != HouseName::Stark)));
+            assert!(state.garrisons.values().all(|g| g.house != Some(HouseName::Stark)));
+            assert_eq!(*final_ranking(&state).last().unwrap(), HouseName::Stark);
+            // Winterfell keeps a neutral force only under the Neutral policy,
+            // unless someone has taken it since
+            if state.area(WINTERFELL).house.is_none() {
+                let neutral = state.garrisons.get(&WINTERFELL).is_some_and(|g| g.house.is_none());
+                assert_eq!(neutral, policy == ResignPolicy::Neutral, "{:?}", policy);
+            }
+        }
+    }
+
+    #[test]
+    fn test_games_finish_after_resignations() {
+        for seed in 0..12 {
+            let config = SetupConfig::new(3 + (seed % 4) as u8, seed);
+            let quitter = config.houses[seed as usize % config.houses.len()];
+            let state = play_with_resignation(&config, quitter, 1 + (seed % 9) as u8);
+            assert!(state.winner.is_some(), "seed {} did not finish", seed);
+            assert_eq!(state.resigned.len(), usize::from(state.round > (seed % 9) as u8), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_last_house_standing_wins() {
+        let mut state = create_game(&SetupConfig::new(3, 4)).unwrap();
+        advance(&mut state);
+        for _ in 0..2 {
+            apply_action(&mut state, Action::Resign).unwrap();
+            advance(&mut state);
+        }
+        let winner = state.winner.expect("one house left");
+        assert_eq!(state.remaining_houses(), vec![winner]);
+        assert_eq!(final_ranking(&state)[0], winner);
+        assert_eq!(final_ranking(&state)[2], state.resigned[0]);
+    }
+
+    #[test]
+    fn test_draw_needs_every_remaining_house() {
+        use crate::engine::validate_action;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        let mut state = create_game(&SetupConfig::new(3, 8)).unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(8);
+        advance(&mut state);
+        let first = state.peek_pending().unwrap().house();
+        apply_action(&mut state, Action::OfferDraw).unwrap();
+        advance(&mut state);
+        // The offer leaves the decision open
+        assert_eq!(state.peek_pending().unwrap().house(), first);
+        assert!(validate_action(&state, &Action::OfferDraw).is_err(), "one offer per round");
+
+        // The others offer the first time they are asked
+        while state.winner.is_none() && state.round == 1 {
+            let pending = state.peek_pending().cloned().unwrap();
+            let house = pending.house();
+            let action = if state.draw_offers.contains(&house) {
+                random_legal_action(&state, &mut rng).unwrap()
+            } else {
+                Action::OfferDraw
+            };
+            apply_action(&mut state, action).unwrap();
+            advance(&mut state);
+        }
+        assert!(state.drawn, "every house offered in round 1");
+        assert_eq!(state.winner, Some(final_ranking(&state)[0]));
+        assert!(state.peek_pending().is_none());
+        assert!(state.events.iter().any(|e| matches!(&e.kind, GameEventKind::DrawAgreed { houses } if houses.len() == 3)));
+    }
+
+    #[test]
+    fn test_draw_offers_lapse_at_round_end() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        let mut state = create_game(&SetupConfig::new(4, 3)).unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        advance(&mut state);
+        apply_action(&mut state, Action::OfferDraw).unwrap();
+        advance(&mut state);
+        assert_eq!(state.draw_offers.len(), 1);
+        while state.winner.is_none() && state.round == 1 {
+            let action = random_legal_action(&state, &mut rng).unwrap();
+            apply_action(&mut state, action).unwrap();
+            advance(&mut state);
+        }
+        assert!(state.draw_offers.is_empty());
+        assert!(!state.drawn);
+    }
 }
@@ -0,0 +1,147 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Threat map — what the viewer's enemies could bring to each area
+//
+// Derived from a `PlayerView` alone, for every area:
+//
+//   • adjacent strength: enemy units in neighbouring areas that could
+//     enter it (land units next to land, ships next to seas and ports)
+//   • reachable strength: enemy units that could march into it in one
+//     move, adjacent or by ship transport along their own ships
+//   • the houses those units belong to
+//
+// Orders are not considered: every enemy army is assumed able to march,
+// as at planning time. Routed units stay put. Siege engines count 4
+// against castles and strongholds and nothing elsewhere, as in combat.
+//
+// `PlayerView::threats` builds the map on first use and keeps it with
+// the view, so agents asking about many areas pay for it once.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::map::{AREAS, NUM_AREAS};
+use crate::navigation::{can_enter, port_has_room};
+use crate::types::*;
+use crate::visibility::PlayerView;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AreaThreat {
+    /// Enemy strength in neighbouring areas able to enter this one.
+    pub adjacent_strength: u8,
+    /// Enemy strength able to march in with one move, transport included.
+    pub reachable_strength: u8,
+    /// Houses with units able to march in, in turn order.
+    pub reachable_by: Vec<HouseName>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreatMap {
+    pub viewer: HouseName,
+    /// By area id.
+    pub areas: Vec<AreaThreat>,
+}
+
+impl ThreatMap {
+    pub fn of(view: &PlayerView) -> ThreatMap {
+        let mut areas = vec![AreaThreat::default(); NUM_AREAS];
+        for (i, area) in view.areas.iter().enumerate() {
+            let from = AreaId(i as u8);
+            for &house in &view.turn_order {
+                if house == view.viewer {
+                    continue;
+                }
+                let units: Vec<UnitType> = area.units.iter()
+                    .filter(|u| u.house == house && !u.routed)
+                    .map(|u| u.unit_type)
+                    .collect();
+                if units.is_empty() {
+                    continue;
+                }
+                for (to, transported) in reach(view, from, house) {
+                    let strength = strength_into(&units, to);
+                    if strength == 0 && !units.iter().any(|&u| can_enter(u, to)) {
+                        continue;
+                    }
+                    let threat = &mut areas[to.0 as usize];
+                    if !transported {
+                        threat.adjacent_strength = threat.adjacent_strength.saturating_add(strength);
+                    }
+                    threat.reachable_strength = threat.reachable_strength.saturating_add(strength);
+                    if !threat.reachable_by.contains(&house) {
+                        threat.reachable_by.push(house);
+                    }
+                }
+            }
+        }
+        ThreatMap { viewer: view.viewer, areas }
+    }
+
+    pub fn area(&self, id: AreaId) -> &AreaThreat {
+        &self.areas[id.0 as usize]
+    }
+
+    /// Whether any enemy could march into the area next move.
+    pub fn threatened(&self, id: AreaId) -> bool {
+        !self.area(id).reachable_by.is_empty()
+    }
+}
+
+/// Strength the units that may enter `to` would bring.
+fn strength_into(units: &[UnitType], to: AreaId) -> u8 {
+    let fortified = AREAS[to.0 as usize].has_castle_or_stronghold();
+    units.iter()
+        .filter(|&&u| can_enter(u, to))
+        .map(|&u| match u {
+            UnitType::SiegeEngine if fortified => 4,
+            u => u.combat_strength(),
+        })
+        .sum()
+}
+
+/// Areas `house` could move into from `from` in one move, each with
+/// whether it takes ship transport. Blocked areas are left out.
+fn reach(view: &PlayerView, from: AreaId, house: HouseName) -> Vec<(AreaId, bool)> {
+    let open = |id: AreaId| !view.areas[id.0 as usize].blocked;
+    let mut found: Vec<(AreaId, bool)> = AREAS[from.0 as usize].adjacent.iter()
+        .filter(|&&a| open(a))
+        .map(|&a| (a, false))
+        .collect();
+    if !AREAS[from.0 as usize].is_land() {
+        // A sea opens into the ports of the castles the house holds
+        found.extend(AREAS.iter()
+            .filter(|d| d.connected_sea == Some(from) && port_has_room(view, d.id, house, 1))
+            .map(|d| (d.id, false)));
+        return found;
+    }
+    // Land → seas holding the house's ships → land
+    let has_ship = |id: AreaId| view.areas[id.0 as usize].units.iter().any(|u| u.unit_type == UnitType::Ship && u.house == house);
+    let mut visited = [false; NUM_AREAS];
+    let mut queue: VecDeque<AreaId> = VecDeque::new();
+    visited[from.0 as usize] = true;
+    for &a in AREAS[from.0 as usize].adjacent {
+        if AREAS[a.0 as usize].is_sea() && has_ship(a) {
+            visited[a.0 as usize] = true;
+            queue.push_back(a);
+        }
+    }
+    while let Some(sea) = queue.pop_front() {
+        for &a in AREAS[sea.0 as usize].adjacent {
+            if visited[a.0 as usize] {
+                continue;
+            }
+            let def = &AREAS[a.0 as usize];
+            if def.is_sea() {
+                if has_ship(a) {
+                    visited[a.0 as usize] = true;
+                    queue.push_back(a);
+                }
+            } else if def.is_land() && open(a) {
+                visited[a.0 as usize] = true;
+                if !found.iter().any(|&(f, _)| f == a) {
+                    found.push((a, true));
+                }
+            }
+        }
+    }
+    found
+}
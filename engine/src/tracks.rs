@@ -0,0 +1,133 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Influence Tracks — the single place that mutates track positions
+//
+// Positions are 1-based (1 = top). For every track, the positions of the
+// playing houses must always be a permutation of 1..=player_count.
+// Every mutation goes through `set_order`, which re-derives turn order
+// from the Iron Throne track and checks that invariant.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::types::*;
+
+pub const ALL_TRACKS: [Track; 3] = [Track::IronThrone, Track::Fiefdoms, Track::KingsCourt];
+
+/// Position of a house on a track (1 = top).
+pub fn position(state: &GameState, house: HouseName, track: Track) -> u8 {
+    let profile = state.house(house);
+    match track {
+        Track::IronThrone => profile.iron_throne,
+        Track::Fiefdoms => profile.fiefdoms,
+        Track::KingsCourt => profile.kings_court,
+    }
+}
+
+fn position_mut(state: &mut GameState, house: HouseName, track: Track) -> &mut u8 {
+    let profile = state.house_mut(house);
+    match track {
+        Track::IronThrone => &mut profile.iron_throne,
+        Track::Fiefdoms => &mut profile.fiefdoms,
+        Track::KingsCourt => &mut profile.kings_court,
+    }
+}
+
+/// Houses on a track, top to bottom.
+pub fn order(state: &GameState, track: Track) -> Vec<HouseName> {
+    let mut houses = state.playing_houses.clone();
+    houses.sort_by_key(|&h| position(state, h, track));
+    houses
+}
+
+/// The house at the top of a track (holder of its dominance token).
+pub fn holder(state: &GameState, track: Track) -> HouseName {
+    order(state, track)[0]
+}
+
+/// Rewrite a whole track from a top-to-bottom list of every playing house.
+pub fn set_order(state: &mut GameState, track: Track, new_order: &[HouseName]) {
+    assert_eq!(new_order.len(), state.playing_houses.len(), "track order must list every playing house");
+    for (i, &h) in new_order.iter().enumerate() {
+        *position_mut(state, h, track) = (i + 1) as u8;
+    }
+    sync(state);
+}
+
+/// Move a house to `pos` (1-based), shifting the houses in between by one.
+pub fn set_position(state: &mut GameState, house: HouseName, track: Track, pos: u8) {
+    let mut houses = order(state, track);
+    houses.retain(|&h| h != house);
+    let idx = (pos.max(1) as usize - 1).min(houses.len());
+    houses.insert(idx, house);
+    set_order(state, track, &houses);
+}
+
+pub fn move_to_top(state: &mut GameState, house: HouseName, track: Track) {
+    set_position(state, house, track, 1);
+}
+
+pub fn move_to_bottom(state: &mut GameState, house: HouseName, track: Track) {
+    let bottom = state.playing_houses.len() as u8;
+    set_position(state, house, track, bottom);
+}
+
+/// Exchange the positions of two houses on a track.
+pub fn swap(state: &mut GameState, track: Track, a: HouseName, b: HouseName) {
+    let mut houses = order(state, track);
+    let ia = houses.iter().position(|&h| h == a).unwrap();
+    let ib = houses.iter().position(|&h| h == b).unwrap();
+    houses.swap(ia, ib);
+    set_order(state, track, &houses);
+}
+
+/// Compact arbitrary positions into a permutation, keeping relative order.
+/// Used at setup, where starting positions are given for the 6-player board.
+pub fn normalize(state: &mut GameState) {
+    for track in ALL_TRACKS {
+        let houses = order(state, track);
+        for (i, &h) in houses.iter().enumerate() {
+            *position_mut(state, h, track) = (i + 1) as u8;
+        }
+    }
+    sync(state);
+}
+
+/// Star orders a house may place, from its King's Court position.
+pub fn star_limit(state: &GameState, house: HouseName) -> u8 {
+    star_order_limit(state.player_count(), position(state, house, Track::KingsCourt))
+}
+
+/// Check that every track is a permutation of 1..=player_count, that
+/// turn order matches the Iron Throne track, and that no house has gained
+/// or lost units (`GameState::check_unit_pools`), and that a combat in
+/// progress belongs to one march order (`GameState::check_combat`).
+pub fn check_invariants(state: &GameState) -> Result<(), String> {
+    let pc = state.playing_houses.len();
+    for track in ALL_TRACKS {
+        let mut seen = vec![false; pc];
+        for &h in &state.playing_houses {
+            let pos = position(state, h, track) as usize;
+            if pos == 0 || pos > pc || seen[pos - 1] {
+                return Err(format!("{:?} track: {:?} has invalid or duplicate position {}", track, h, pos));
+            }
+            seen[pos - 1] = true;
+        }
+    }
+    if state.turn_order != order(state, Track::IronThrone) {
+        return Err(format!("turn order {:?} does not follow the Iron Throne track", state.turn_order));
+    }
+    state.check_unit_pools()?;
+    state.check_combat()?;
+    Ok(())
+}
+
+/// Re-derive everything that depends on track positions. A new turn order
+/// takes effect at once: the house whose turn it is in the action phase
+/// keeps it, and play goes on from its new seat.
+fn sync(state: &mut GameState) {
+    let acting = state.action_turn().and_then(|t| state.turn_order.get(t.player_index as usize).copied());
+    state.turn_order = order(state, Track::IronThrone);
+    let seat = acting.and_then(|h| state.turn_order.iter().position(|&t| t == h));
+    if let (Some(seat), Some(turn)) = (seat, state.action_turn_mut()) {
+        turn.player_index = seat as u8;
+    }
+    debug_assert_eq!(check_invariants(state), Ok(()));
+}
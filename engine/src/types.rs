@@ -3,11 +3,12 @@
 // ═══════════════════════════════════════════════════════════════════════
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use schemars::JsonSchema;
+use std::collections::{HashMap, VecDeque};
 
 // ── Enums ──────────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum HouseName {
     Stark,
     Lannister,
@@ -41,7 +42,19 @@ impl std::fmt::Display for HouseName {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+impl std::str::FromStr for HouseName {
+    type Err = String;
+
+    /// Parse a house name, case-insensitively.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        HouseName::ALL.iter()
+            .copied()
+            .find(|h| h.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("Unknown house: {}", s))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum UnitType {
     Footman,
     Knight,
@@ -50,6 +63,8 @@ pub enum UnitType {
 }
 
 impl UnitType {
+    pub const ALL: [UnitType; 4] = [UnitType::Footman, UnitType::Knight, UnitType::Ship, UnitType::SiegeEngine];
+
     /// Base combat strength of this unit type.
     /// SiegeEngine is 4 when attacking castle/stronghold, 0 otherwise — handled in combat.
     pub fn combat_strength(self) -> u8 {
@@ -72,7 +87,7 @@ impl UnitType {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum OrderType {
     March,
     Raid,
@@ -81,14 +96,14 @@ pub enum OrderType {
     ConsolidatePower,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum AreaType {
     Land,
     Sea,
     Port,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum Phase {
     Westeros,
     Planning,
@@ -96,7 +111,7 @@ pub enum Phase {
     Combat,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum ActionSubPhase {
     Raid,
     March,
@@ -104,14 +119,14 @@ pub enum ActionSubPhase {
     Done,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum Track {
     IronThrone,
     Fiefdoms,
     KingsCourt,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum CombatPhase {
     Support,
     Cards,
@@ -120,14 +135,14 @@ pub enum CombatPhase {
     PostCombat,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum SupportChoice {
     Attacker,
     Defender,
     None,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum BiddingType {
     IronThrone,
     Fiefdoms,
@@ -138,12 +153,42 @@ pub enum BiddingType {
 // ── Area ID ────────────────────────────────────────────────────────────
 // Compact, copyable area identifier. Index into the static AREAS array.
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, JsonSchema, PartialOrd, Ord)]
 pub struct AreaId(pub u8);
 
+// JSON map keys are strings, so `HashMap<AreaId, _>` fields (garrisons,
+// orders) serialize as {"3": ...}; accept both forms when reading back.
+impl<'de> Deserialize<'de> for AreaId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AreaIdVisitor;
+
+        impl serde::de::Visitor<'_> for AreaIdVisitor {
+            type Value = AreaId;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("an area index")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<AreaId, E> {
+                u8::try_from(v).map(AreaId).map_err(|_| E::custom(format!("area index out of range: {}", v)))
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<AreaId, E> {
+                u8::try_from(v).map(AreaId).map_err(|_| E::custom(format!("area index out of range: {}", v)))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<AreaId, E> {
+                v.parse().map(AreaId).map_err(|_| E::custom(format!("invalid area index: {}", v)))
+            }
+        }
+
+        deserializer.deserialize_any(AreaIdVisitor)
+    }
+}
+
 // ── Unit ───────────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct Unit {
     pub unit_type: UnitType,
     pub house: HouseName,
@@ -153,7 +198,7 @@ pub struct Unit {
 // ── Order Token ────────────────────────────────────────────────────────
 
 /// Definition of the 15 order tokens each house owns (static data).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct OrderTokenDef {
     pub order_type: OrderType,
     pub strength: i8, // can be -1 for March-1
@@ -161,7 +206,7 @@ pub struct OrderTokenDef {
 }
 
 /// An order placed on an area.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct Order {
     pub order_type: OrderType,
     pub strength: i8,
@@ -210,7 +255,7 @@ pub fn star_order_limit(player_count: u8, position: u8) -> u8 {
 
 // ── House Card ─────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum HouseCardId {
     // Stark
     EddardStark, RobbStark, GreatjonUmber, RooseBolton, TheBlackfish, SerRodrikCassel, CatelynStark,
@@ -226,18 +271,128 @@ pub enum HouseCardId {
     TheRedViper, AreoHotah, ObaraSand, Darkstar, NymeriaSand, ArianneMartell, DoranMartell,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct HouseCard {
     pub id: HouseCardId,
     pub house: HouseName,
     pub strength: u8,
     pub swords: u8,
     pub fortifications: u8,
+    pub ability: CardAbility,
+}
+
+/// Which side of a combat a house is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CombatSide {
+    Attacker,
+    Defender,
+}
+
+/// What a house card does besides its strength and icons. The combat
+/// resolver works from this, and agents and UIs can read what an
+/// opponent's remaining cards threaten without matching on card ids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CardAbility {
+    /// Strength and icons only.
+    None,
+
+    // Strength modifiers
+    /// +`bonus` when its house is on `side`.
+    FlatBonus { bonus: u8, side: CombatSide },
+    /// +1 per card in its house's discard pile.
+    BonusPerDiscard,
+    /// +1 per own ship adjacent to the embattled area, in sea areas (and
+    /// ports when `ports`).
+    BonusPerAdjacentShip { ports: bool },
+    /// +1 per supporting area its house controls.
+    BonusPerOwnSupport,
+    /// +1 per adjacent land area its house controls.
+    BonusPerAdjacentLand,
+    /// The opponent's support counts for nothing.
+    CancelEnemySupport,
+
+    // Before strengths are compared
+    /// The opponent takes back the card they played and chooses another.
+    CancelOpponentCard,
+    /// For `power_cost` power, swap this card for another from hand.
+    SwapCard { power_cost: u8 },
+    /// One of the opponent's orders next to the embattled area is
+    /// removed, not the march that started the battle.
+    RemoveAdjacentOrder,
+
+    // Casualties and conquest
+    /// No casualties when its house wins.
+    NoCasualtiesOnWin,
+    /// No casualties and no conquest, whoever wins; attackers go back.
+    NoCasualtiesNoConquest,
+
+    // After a loss
+    /// Goes back to hand instead of the discard pile.
+    ReturnToHand,
+    /// Takes power equal to the winner's card strength from the winner.
+    StealPowerByOpponentStrength,
+    /// Moves the winner to the bottom of a track of its house's choice.
+    MoveOpponentToTrackBottom,
+    /// Survivors retreat to the home area (land combats only).
+    RetreatHome,
+
+    // After a win
+    /// Takes `amount` power from the loser when winning by `min_margin`.
+    StealPower { amount: u8, min_margin: u8 },
+    /// One winning footman becomes a knight.
+    UpgradeFootman,
+    /// The loser discards the strongest card in hand.
+    DiscardStrongestFromOpponentHand,
+    /// One of the loser's orders is removed, chosen by its house when
+    /// `chosen`, otherwise the first one on the map.
+    RemoveEnemyOrder { chosen: bool },
+    /// Looks at the loser's hand and discards a card from it.
+    DiscardFromOpponentHand,
+    /// Its house chooses where the losing defender retreats.
+    ChooseOpponentRetreat,
+    /// A winning attacker marches again from the conquered area.
+    MarchAgain,
+}
+
+/// When a `CardAbility` takes effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AbilityTiming {
+    /// Never: the card has no ability.
+    Never,
+    /// While strengths are computed.
+    Strength,
+    /// Before strengths are computed.
+    PreCombat,
+    /// When casualties and conquest are settled.
+    Outcome,
+    /// After combat, if its house lost.
+    OnLoss,
+    /// After combat, if its house won.
+    OnWin,
+}
+
+impl CardAbility {
+    pub fn timing(self) -> AbilityTiming {
+        use CardAbility::*;
+        match self {
+            None => AbilityTiming::Never,
+            FlatBonus { .. } | BonusPerDiscard | BonusPerAdjacentShip { .. } | BonusPerOwnSupport
+            | BonusPerAdjacentLand | CancelEnemySupport => AbilityTiming::Strength,
+            CancelOpponentCard | SwapCard { .. } | MoveOpponentToTrackBottom | RemoveAdjacentOrder => AbilityTiming::PreCombat,
+            NoCasualtiesOnWin | NoCasualtiesNoConquest => AbilityTiming::Outcome,
+            ReturnToHand | StealPowerByOpponentStrength | RetreatHome => AbilityTiming::OnLoss,
+            StealPower { .. } | UpgradeFootman | DiscardStrongestFromOpponentHand | RemoveEnemyOrder { .. }
+            | DiscardFromOpponentHand | ChooseOpponentRetreat | MarchAgain => AbilityTiming::OnWin,
+        }
+    }
 }
 
 // ── Westeros Card ──────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum WesterosCardType {
     // Deck 1
     Supply,
@@ -260,7 +415,7 @@ pub enum WesterosCardType {
     LastDaysOfSummer,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct WesterosCard {
     pub deck: u8, // 1, 2, or 3
     pub card_type: WesterosCardType,
@@ -269,7 +424,7 @@ pub struct WesterosCard {
 
 // ── Wildling Card ──────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum WildlingCardType {
     AKingBeyondTheWall,
     CrowKillers,
@@ -282,7 +437,7 @@ pub enum WildlingCardType {
     TheHordeDescends,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct WildlingCard {
     pub card_type: WildlingCardType,
 }
@@ -306,7 +461,7 @@ pub fn supply_limits(supply: u8) -> &'static [u8] {
 
 // ── Garrison ───────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct Garrison {
     pub house: Option<HouseName>, // None = neutral (King's Landing, The Eyrie)
     pub strength: u8,
@@ -314,19 +469,48 @@ pub struct Garrison {
 
 // ── Bidding State ──────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A sealed-bid auction: every house still to bid is asked at once, the
+/// bids collect in `sealed_bids` without anyone seeing them (players only
+/// learn who has bid, `PlayerView::bids_in`) and all are revealed together
+/// by a `BidsRevealed` event when the last one is in.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BiddingState {
     pub bidding_type: BiddingType,
-    pub bids: HashMap<HouseName, u8>,
+    #[serde(alias = "bids")]
+    pub sealed_bids: HashMap<HouseName, u8>,
     pub current_track: Option<Track>,
     pub remaining_tracks: Vec<Track>,
     pub bid_order: Vec<HouseName>,
-    pub next_bidder_idx: usize,
+}
+
+impl BiddingState {
+    /// An auction nobody has bid in yet.
+    pub fn new(bidding_type: BiddingType, current_track: Option<Track>, remaining_tracks: Vec<Track>, bid_order: Vec<HouseName>) -> Self {
+        BiddingState { bidding_type, sealed_bids: HashMap::new(), current_track, remaining_tracks, bid_order }
+    }
+
+    pub fn has_bid(&self, house: HouseName) -> bool {
+        self.sealed_bids.contains_key(&house)
+    }
+
+    /// Houses whose bid is in, in bid order.
+    pub fn bids_in(&self) -> Vec<HouseName> {
+        self.bid_order.iter().copied().filter(|&h| self.has_bid(h)).collect()
+    }
+
+    pub fn all_in(&self) -> bool {
+        self.bid_order.iter().all(|&h| self.has_bid(h))
+    }
+
+    /// `house`'s bid, 0 if it has none.
+    pub fn bid_of(&self, house: HouseName) -> u8 {
+        self.sealed_bids.get(&house).copied().unwrap_or(0)
+    }
 }
 
 // ── Combat State ───────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CombatState {
     pub attacker: HouseName,
     pub defender: HouseName,
@@ -344,12 +528,76 @@ pub struct CombatState {
     pub phase: CombatPhase,
     pub aeron_resolved: bool,
     pub tyrion_resolved: bool,
+    #[serde(default)]
+    pub queen_of_thorns_resolved: bool,
+    #[serde(default)]
+    pub doran_resolved: bool,
     pub pending_support_houses: Vec<(AreaId, HouseName)>,
+    /// Result of the strength comparison, set on entering PostCombat.
+    #[serde(default)]
+    pub outcome: Option<CombatOutcome>,
+    /// Remaining post-combat steps, resolved front to back.
+    #[serde(default)]
+    pub post_combat_steps: VecDeque<PostCombatStep>,
+}
+
+impl CombatState {
+    pub fn winner(&self) -> Option<HouseName> {
+        self.outcome.map(|o| if o.attacker_wins { self.attacker } else { self.defender })
+    }
+
+    pub fn loser(&self) -> Option<HouseName> {
+        self.outcome.map(|o| if o.attacker_wins { self.defender } else { self.attacker })
+    }
+
+    pub fn winner_card(&self) -> Option<HouseCardId> {
+        self.outcome.and_then(|o| if o.attacker_wins { self.attacker_card } else { self.defender_card })
+    }
+
+    pub fn loser_card(&self) -> Option<HouseCardId> {
+        self.outcome.and_then(|o| if o.attacker_wins { self.defender_card } else { self.attacker_card })
+    }
+}
+
+/// Who won a combat and what it costs the loser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CombatOutcome {
+    pub attacker_wins: bool,
+    /// Units the loser must remove (swords − fortifications, after modifiers).
+    pub casualties: u8,
+    /// Arianne Martell was played: nobody conquers, attackers go home.
+    pub no_conquest: bool,
+    /// Support strength counted for each side (after Balon Greyjoy).
+    #[serde(default)]
+    pub attacker_support: i16,
+    #[serde(default)]
+    pub defender_support: i16,
+}
+
+/// Ordered steps of combat finalization. Each step may pause on a
+/// decision; the pipeline resumes with the next step once it is answered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum PostCombatStep {
+    Casualties,
+    LoserAbilities,
+    WinnerAbilities,
+    Retreat,
+    Cleanup,
+}
+
+impl PostCombatStep {
+    pub const ORDER: [PostCombatStep; 5] = [
+        PostCombatStep::Casualties,
+        PostCombatStep::LoserAbilities,
+        PostCombatStep::WinnerAbilities,
+        PostCombatStep::Retreat,
+        PostCombatStep::Cleanup,
+    ];
 }
 
 // ── House Profile (per-player state) ───────────────────────────────────
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct HouseProfile {
     pub name: HouseName,
     pub iron_throne: u8,    // position 1–6 (1 = top)
@@ -361,9 +609,25 @@ pub struct HouseProfile {
     pub hand: Vec<HouseCardId>,       // cards in hand (PRIVATE)
     pub discards: Vec<HouseCardId>,   // played cards (PUBLIC)
     pub used_order_tokens: Vec<u8>,   // indices into ORDER_TOKENS used this round
+    #[serde(default)]
+    pub card_stats: CardStats,        // house cards played so far (PUBLIC)
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+/// A house's house-card play over the game. Played cards are face up, so
+/// all of it is public.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CardStats {
+    /// Cards played in resolved combats.
+    pub cards_played: u16,
+    /// How often each card was played.
+    pub plays: HashMap<HouseCardId, u16>,
+    /// Swords on the cards it won combats with.
+    pub swords_dealt: u16,
+    /// Times one of its cards' abilities took effect.
+    pub abilities_triggered: u16,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
 pub struct UnitPool {
     pub footmen: u8,
     pub knights: u8,
@@ -372,6 +636,9 @@ pub struct UnitPool {
 }
 
 impl UnitPool {
+    /// Every unit a house owns, on the board or off it.
+    pub const CAPACITY: UnitPool = UnitPool { footmen: 10, knights: 5, ships: 6, siege_engines: 2 };
+
     pub fn get(&self, ut: UnitType) -> u8 {
         match ut {
             UnitType::Footman => self.footmen,
@@ -390,10 +657,83 @@ impl UnitPool {
     }
 }
 
+impl HouseProfile {
+    /// Take a unit of type `ut` out of the pool to put it on the board.
+    pub fn take_unit(&mut self, ut: UnitType) -> Result<(), GameError> {
+        let count = self.available_units.get_mut(ut);
+        if *count == 0 {
+            return Err(GameError::PoolExhausted { house: self.name, unit_type: ut });
+        }
+        *count -= 1;
+        Ok(())
+    }
+
+    /// Put a unit of type `ut` that left the board back in the pool. A pool
+    /// already at capacity refuses it rather than minting a unit.
+    pub fn return_unit(&mut self, ut: UnitType) -> Result<(), GameError> {
+        let count = self.available_units.get_mut(ut);
+        if *count >= UnitPool::CAPACITY.get(ut) {
+            return Err(GameError::PoolOverflow { house: self.name, unit_type: ut });
+        }
+        *count += 1;
+        Ok(())
+    }
+
+    /// Turn one of this house's units of type `from` into a `to`: the `to`
+    /// comes out of the pool and the `from` goes back in.
+    pub fn swap_unit(&mut self, from: UnitType, to: UnitType) -> Result<(), GameError> {
+        self.take_unit(to)?;
+        if let Err(e) = self.return_unit(from) {
+            *self.available_units.get_mut(to) += 1;
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+// ── Errors ─────────────────────────────────────────────────────────────
+
+/// A rules-engine bookkeeping error: the state no longer adds up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameError {
+    /// A unit was taken from a pool that had none left.
+    PoolExhausted { house: HouseName, unit_type: UnitType },
+    /// A unit was returned to a pool that was already full.
+    PoolOverflow { house: HouseName, unit_type: UnitType },
+    /// Units on the board (or marching in combat) and in the pool do not
+    /// add up to the house's capacity.
+    UnitsNotConserved { house: HouseName, unit_type: UnitType, in_play: u8, in_pool: u8 },
+    /// A combat that is not the resolution of exactly one march order.
+    CombatMismatch(String),
+}
+
+impl std::fmt::Display for GameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameError::PoolExhausted { house, unit_type } =>
+                write!(f, "{} has no {:?} left in its pool", house, unit_type),
+            GameError::PoolOverflow { house, unit_type } =>
+                write!(f, "{}'s {:?} pool is already full", house, unit_type),
+            GameError::UnitsNotConserved { house, unit_type, in_play, in_pool } =>
+                write!(f, "{} has {} {:?} in play and {} in the pool, capacity {}",
+                    house, in_play, unit_type, in_pool, UnitPool::CAPACITY.get(*unit_type)),
+            GameError::CombatMismatch(reason) => write!(f, "combat mismatch: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+impl From<GameError> for String {
+    fn from(e: GameError) -> String {
+        e.to_string()
+    }
+}
+
 // ── Area (board tile) ──────────────────────────────────────────────────
 
 /// Dynamic per-area state during a game.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[derive(Default)]
 pub struct AreaState {
     pub units: Vec<Unit>,
@@ -405,7 +745,8 @@ pub struct AreaState {
 
 // ── Pending Decision Types ─────────────────────────────────────────────
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum PendingDecision {
     /// Westeros card choice (e.g. Throne of Blades: Supply or Mustering)
     WesterosChoice {
@@ -420,22 +761,38 @@ pub enum PendingDecision {
         attacker: HouseName,
         defender: HouseName,
     },
-    /// Tyrion cancelled opponent's card — must choose replacement
+    /// Tyrion cancelled `decider`'s card: `decider` plays one of
+    /// `available_cards` (its hand but the cancelled card) against Tyrion's
+    /// owner, `opponent`. Not asked when nothing else is in hand; the house
+    /// then fights without a card.
     TyrionReplace {
+        decider: HouseName,
         opponent: HouseName,
+        #[serde(default)]
+        available_cards: Vec<HouseCardId>,
     },
-    /// Aeron Damphair: pay 2 power to swap card?
+    /// Aeron Damphair: `house` may pay `power_cost` to take Aeron back
+    /// and play one of `available_cards` (its hand) instead. Only asked
+    /// when the house can pay and has a card to play.
     AeronSwap {
         house: HouseName,
+        #[serde(default)]
+        available_cards: Vec<HouseCardId>,
+        #[serde(default)]
+        power_cost: u8,
     },
-    /// Patchface: choose card to discard from opponent's hand
+    /// Patchface: `decider` discards one of the cards in `opponent`'s hand
     PatchfaceDiscard {
+        decider: HouseName,
         opponent: HouseName,
         visible_cards: Vec<HouseCardId>,
     },
-    /// Robb Stark: winner chooses defender retreat area
+    /// Robb Stark: winner chooses where `defender`'s surviving `units` retreat
     RobbRetreat {
         house: HouseName,
+        defender: HouseName,
+        units: Vec<Unit>,
+        from_area: AreaId,
         possible_areas: Vec<AreaId>,
     },
     /// Generic retreat: loser picks retreat destination
@@ -476,6 +833,12 @@ pub enum PendingDecision {
     PlaceOrders {
         house: HouseName,
     },
+    /// Choose which eligible order to resolve this turn (Raid, March or Consolidate Power)
+    ChooseOrderToResolve {
+        house: HouseName,
+        order_type: OrderType,
+        candidates: Vec<AreaId>,
+    },
     /// Choose raid target
     ChooseRaid {
         house: HouseName,
@@ -487,6 +850,10 @@ pub enum PendingDecision {
         house: HouseName,
         from_area: AreaId,
         valid_destinations: Vec<AreaId>,
+        /// Indices into the area's units that may march: the house's own,
+        /// not routed. Omitted when empty, keeping the older shape.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        selectable_units: Vec<usize>,
     },
     /// Select house card for combat
     SelectHouseCard {
@@ -502,34 +869,524 @@ pub enum PendingDecision {
         house: HouseName,
         options: Vec<String>,
     },
-    /// Cersei Lannister: choose opponent order to remove
+    /// Cersei Lannister: `decider` removes one of `opponent`'s orders
     CerseiRemoveOrder {
+        decider: HouseName,
         opponent: HouseName,
+        /// Areas holding one of `opponent`'s orders.
+        #[serde(default)]
+        valid_targets: Vec<AreaId>,
     },
-    /// Doran Martell: choose influence track to move opponent to bottom
+    /// Doran Martell: `decider` picks the influence track `opponent`
+    /// drops to the bottom of
     DoranChooseTrack {
+        decider: HouseName,
         opponent: HouseName,
     },
-    /// Queen of Thorns: remove adjacent opponent order
+    /// Queen of Thorns: `decider` removes one of `opponent`'s orders next
+    /// to the battle
     QueenOfThornsRemoveOrder {
+        decider: HouseName,
         opponent: HouseName,
+        /// Areas adjacent to the embattled one holding one of `opponent`'s
+        /// orders, bar the march that started the battle.
+        #[serde(default)]
+        valid_targets: Vec<AreaId>,
     },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl PendingDecision {
+    /// The areas an order-removal decision (Cersei, Queen of Thorns)
+    /// offers, empty for any other decision.
+    pub fn removal_targets(&self) -> &[AreaId] {
+        match self {
+            Self::CerseiRemoveOrder { valid_targets, .. }
+            | Self::QueenOfThornsRemoveOrder { valid_targets, .. } => valid_targets,
+            _ => &[],
+        }
+    }
+
+    /// House that has to answer this decision. For the card abilities
+    /// that act on the other side of a battle this is the `decider`, not
+    /// the `opponent` the decision is about (which decisions upgraded from
+    /// wire version 2 don't know, see `wire`).
+    pub fn house(&self) -> HouseName {
+        match self {
+            Self::PlaceOrders { house } => *house,
+            Self::ChooseOrderToResolve { house, .. } => *house,
+            Self::ChooseRaid { house, .. } => *house,
+            Self::ChooseMarch { house, .. } => *house,
+            Self::SupportDeclaration { house, .. } => *house,
+            Self::SelectHouseCard { house, .. } => *house,
+            Self::TyrionReplace { decider, .. } => *decider,
+            Self::AeronSwap { house, .. } => *house,
+            Self::PatchfaceDiscard { decider, .. } => *decider,
+            Self::Retreat { house, .. } => *house,
+            Self::Reconcile { house, .. } => *house,
+            Self::Muster { house, .. } => *house,
+            Self::MessengerRaven { house } => *house,
+            Self::WildlingPenaltyChoice { house, .. } => *house,
+            Self::CerseiRemoveOrder { decider, .. } => *decider,
+            Self::DoranChooseTrack { decider, .. } => *decider,
+            Self::QueenOfThornsRemoveOrder { decider, .. } => *decider,
+            Self::LeavePowerToken { house, .. } => *house,
+            Self::UseValyrianBlade { house } => *house,
+            Self::Bidding { house, .. } => *house,
+            Self::WesterosChoice { chooser, .. } => *chooser,
+            Self::RobbRetreat { house, .. } => *house,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct MusterArea {
     pub area_id: AreaId,
     pub points: u8, // 2 for stronghold, 1 for castle
 }
 
+// ── Event Log ──────────────────────────────────────────────────────────
+
+/// Something that happened during the game, stamped with a monotonically
+/// increasing sequence number so the order of events is unambiguous.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct GameEvent {
+    pub seq: u32,
+    pub round: u8,
+    pub phase: Phase,
+    pub kind: GameEventKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameEventKind {
+    /// Control of an area changed hands. `castles_after` is the new
+    /// controller's castle/stronghold count immediately after the change.
+    ControlChanged {
+        area_id: AreaId,
+        from: Option<HouseName>,
+        to: Option<HouseName>,
+        castles_after: u8,
+    },
+    /// `to` took the castle of `port`, where `from` had ships: `captured`
+    /// of them became `to`'s ships, `destroyed` went back to `from`'s pool.
+    PortSeized {
+        port: AreaId,
+        from: HouseName,
+        to: HouseName,
+        captured: u8,
+        destroyed: u8,
+    },
+    /// A house added power tokens to its pool.
+    PowerGained {
+        house: HouseName,
+        amount: u8,
+    },
+    /// A house lost power tokens from its pool (bids, penalties, steals).
+    PowerSpent {
+        house: HouseName,
+        amount: u8,
+    },
+    /// A house's contribution to a wildling bid.
+    WildlingBid {
+        house: HouseName,
+        amount: u8,
+    },
+    /// Every order on the board, flipped together once all houses have
+    /// placed theirs.
+    OrdersRevealed {
+        orders: Vec<(AreaId, Order)>,
+    },
+    /// Every bid of a Clash of Kings track or wildling attack, in bid
+    /// order, shown once all are in.
+    BidsRevealed {
+        bidding_type: BiddingType,
+        bids: Vec<(HouseName, u8)>,
+    },
+    /// A wildling attack was resolved. `target` is the highest bidder when
+    /// the Night's Watch wins, the lowest bidder otherwise.
+    WildlingAttack {
+        threat: u8,
+        total_bid: u8,
+        bids: Vec<(HouseName, u8)>,
+        card: Option<WildlingCardType>,
+        nights_watch_won: bool,
+        target: HouseName,
+    },
+    /// A battle was decided.
+    CombatResolved {
+        area_id: AreaId,
+        attacker: HouseName,
+        defender: HouseName,
+        winner: HouseName,
+        attacker_card: Option<HouseCardId>,
+        defender_card: Option<HouseCardId>,
+        attacker_strength: i16,
+        defender_strength: i16,
+        attacker_support: i16,
+        defender_support: i16,
+        /// Units the loser had to remove as sword casualties.
+        casualties: u8,
+    },
+    /// The game ended with a winner.
+    GameWon {
+        house: HouseName,
+        castles: u8,
+    },
+    /// A house left the game; its units left the board with it.
+    Resigned {
+        house: HouseName,
+    },
+    /// A house offered a draw; the offer stands until the end of the round.
+    DrawOffered {
+        house: HouseName,
+    },
+    /// Every house still playing offered a draw: the game ends, led by the
+    /// house ahead on the tiebreaker.
+    DrawAgreed {
+        houses: Vec<HouseName>,
+    },
+    /// The engine answered a decision with its only legal action instead
+    /// of asking (`GameOptions.auto_forced`, and zero bids under
+    /// `auto_zero_bids`).
+    DecisionForced {
+        decision: PendingDecision,
+    },
+}
+
+// ── Game Options ───────────────────────────────────────────────────────
+
+/// Castles and strongholds needed to win under the standard rules.
+pub const CASTLES_TO_WIN: u8 = 7;
+
+/// Per-house adjustment to the standard setup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Handicap {
+    pub house: HouseName,
+    /// Added to the starting 5 power tokens (may be negative).
+    #[serde(default)]
+    pub power_bonus: i8,
+    /// Added to the castles this house needs to win.
+    #[serde(default)]
+    pub castle_adjust: i8,
+    /// Added to the barrels this house counts at setup and at every
+    /// supply update (the track still runs 0–6).
+    #[serde(default)]
+    pub supply_adjust: i8,
+    /// A starting unit of this type left off the board and in the pool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub removed_unit: Option<UnitType>,
+}
+
+impl Handicap {
+    /// No adjustment for `house`.
+    pub fn none(house: HouseName) -> Self {
+        Handicap { house, power_bonus: 0, castle_adjust: 0, supply_adjust: 0, removed_unit: None }
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.power_bonus == 0 && self.castle_adjust == 0 && self.supply_adjust == 0 && self.removed_unit.is_none()
+    }
+}
+
+/// A neutral force token's strength in one area at one player count,
+/// replacing the setup chart's entry. Strength 0 leaves the area empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct NeutralForce {
+    pub player_count: u8,
+    pub area: AreaId,
+    pub strength: u8,
+}
+
+/// Chess-style clock: every house starts with `budget_ms` of thinking
+/// time and gains `increment_ms` after each decision. The engine ignores
+/// it; the game runner keeps the clocks and deals with a house that runs
+/// out by its action policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TimeControl {
+    pub budget_ms: u64,
+    #[serde(default)]
+    pub increment_ms: u64,
+}
+
+impl std::fmt::Display for TimeControl {
+    /// Seconds, as `budget+increment` (e.g. "60+0.5").
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}+{}", self.budget_ms as f64 / 1000.0, self.increment_ms as f64 / 1000.0)
+    }
+}
+
+impl std::str::FromStr for TimeControl {
+    type Err = String;
+
+    /// `budget+increment` in seconds (e.g. "60+1", "0.5+0.01"); the
+    /// increment may be left out.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (budget, increment) = s.split_once('+').unwrap_or((s, "0"));
+        let millis = |part: &str| -> Result<u64, String> {
+            match part.trim().parse::<f64>() {
+                Ok(secs) if secs >= 0.0 && secs.is_finite() => Ok((secs * 1000.0).round() as u64),
+                _ => Err(format!("Invalid time control: {} (expected seconds as budget+increment, e.g. 60+1)", s)),
+            }
+        };
+        let control = TimeControl { budget_ms: millis(budget)?, increment_ms: millis(increment)? };
+        if control.budget_ms == 0 {
+            return Err(format!("Invalid time control: {} (the budget must be positive)", s));
+        }
+        Ok(control)
+    }
+}
+
+/// What happens to the units of a house that resigns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ResignPolicy {
+    /// Units, garrisons and power tokens are taken off the board.
+    #[default]
+    Remove,
+    /// Land areas it held with units or a garrison get a neutral force
+    /// token of their combined strength instead.
+    Neutral,
+}
+
+impl ResignPolicy {
+    fn is_remove(&self) -> bool {
+        *self == ResignPolicy::Remove
+    }
+}
+
+/// What wins the game. Research variants replace the castle race
+/// outright: under them, reaching the castle count wins nothing, and a
+/// game that runs out of rounds goes to the tiebreaker as usual.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum VictoryCondition {
+    /// The standard game: first to its castle count, or the tiebreaker
+    /// after round 10.
+    #[default]
+    Castles,
+    /// No early win; the tiebreaker (most castles first) decides after
+    /// `rounds` rounds.
+    MostCastlesAfter { rounds: u8 },
+    /// Control `area` at the end of `rounds` rounds in a row.
+    KingOfTheHill { area: AreaId, rounds: u8 },
+    /// Reach `supply` on the supply track; if several houses get there
+    /// at once, the best by the tiebreaker.
+    SupplyDomination { supply: u8 },
+}
+
+impl VictoryCondition {
+    fn is_castles(&self) -> bool {
+        *self == VictoryCondition::Castles
+    }
+
+    /// The round after which the tiebreaker decides the game.
+    pub fn last_round(&self) -> u8 {
+        match self {
+            VictoryCondition::MostCastlesAfter { rounds } => *rounds,
+            _ => 10,
+        }
+    }
+}
+
+/// Rule variations a game is played with. The default is the standard game.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct GameOptions {
+    #[serde(default)]
+    pub handicaps: Vec<Handicap>,
+    /// Thinking time per house; none means unlimited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_control: Option<TimeControl>,
+    #[serde(default, skip_serializing_if = "ResignPolicy::is_remove")]
+    pub resign_policy: ResignPolicy,
+    /// Bid 0 for houses without power instead of asking them.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub auto_zero_bids: bool,
+    /// Answer decisions with a single legal action (a lone retreat, the
+    /// last card in hand, a bid without power) instead of asking.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub auto_forced: bool,
+    /// Changes to the neutral force chart; entries for other player
+    /// counts are ignored.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub neutral_forces: Vec<NeutralForce>,
+    #[serde(default, skip_serializing_if = "VictoryCondition::is_castles")]
+    pub victory: VictoryCondition,
+}
+
+impl GameOptions {
+    pub fn is_standard(&self) -> bool {
+        self.handicaps.iter().all(Handicap::is_none) && self.time_control.is_none()
+            && self.resign_policy.is_remove() && !self.auto_zero_bids && !self.auto_forced
+            && self.neutral_forces.is_empty()
+            && self.victory.is_castles()
+    }
+
+    pub fn handicap(&self, house: HouseName) -> Option<&Handicap> {
+        self.handicaps.iter().find(|h| h.house == house)
+    }
+
+    /// Neutral force strength set for an area at a player count, if any.
+    pub fn neutral_force(&self, player_count: u8, area: AreaId) -> Option<u8> {
+        self.neutral_forces.iter()
+            .find(|n| n.player_count == player_count && n.area == area)
+            .map(|n| n.strength)
+    }
+
+    /// Starting power tokens for a house.
+    pub fn starting_power(&self, house: HouseName) -> u8 {
+        let bonus = self.handicap(house).map_or(0, |h| h.power_bonus as i16);
+        (5 + bonus).clamp(0, 20) as u8
+    }
+
+    /// Castles and strongholds a house needs to win (at least 1).
+    pub fn castles_to_win(&self, house: HouseName) -> u8 {
+        let adjust = self.handicap(house).map_or(0, |h| h.castle_adjust as i16);
+        (CASTLES_TO_WIN as i16 + adjust).max(1) as u8
+    }
+
+    /// Supply level of a house counting `barrels` on the board.
+    pub fn supply_level(&self, house: HouseName, barrels: u8) -> u8 {
+        let adjust = self.handicap(house).map_or(0, |h| h.supply_adjust as i16);
+        (barrels as i16 + adjust).clamp(0, 6) as u8
+    }
+
+    /// Same options with handicaps in HouseName::ALL order and no-op
+    /// entries dropped, and neutral forces sorted by player count and
+    /// area, so equal options always serialize identically.
+    pub fn canonical(&self) -> Self {
+        let handicaps = HouseName::ALL.iter()
+            .filter_map(|&h| self.handicap(h).copied())
+            .filter(|h| !h.is_none())
+            .collect();
+        let mut neutral_forces = self.neutral_forces.clone();
+        neutral_forces.sort_by_key(|n| (n.player_count, n.area));
+        GameOptions { handicaps, neutral_forces, ..self.clone() }
+    }
+}
+
+// ── Engine phase ───────────────────────────────────────────────────────
+
+/// Where the engine stands in the round, together with the bookkeeping
+/// that only means something there: card progress and any bidding or
+/// mustering during Westeros, the turn during the action phase, the battle
+/// during combat. A combat outside the combat phase, or a mustering index
+/// in the action phase, can't be expressed.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub enum EnginePhase {
+    Westeros(WesterosProgress),
+    /// Orders go on the board face down as each house submits them and are
+    /// flipped together once all are in, before the Messenger Raven.
+    Planning {
+        #[serde(default)]
+        orders_revealed: bool,
+    },
+    Action(ActionTurn),
+    /// A march's battle; the action phase resumes at `turn` once it ends.
+    Combat { turn: ActionTurn, combat: Box<CombatState> },
+}
+
+impl EnginePhase {
+    /// A Westeros phase about to draw its cards.
+    pub fn westeros() -> Self {
+        EnginePhase::Westeros(WesterosProgress { cards: Vec::new(), step: 0, substate: WesterosSubstate::Cards })
+    }
+
+    /// A planning phase with no orders placed.
+    pub fn planning() -> Self {
+        EnginePhase::Planning { orders_revealed: false }
+    }
+
+    /// The first turn of the action phase: raids, Iron Throne holder first.
+    pub fn action() -> Self {
+        EnginePhase::Action(ActionTurn { sub_phase: ActionSubPhase::Raid, player_index: 0 })
+    }
+
+    pub fn kind(&self) -> Phase {
+        match self {
+            EnginePhase::Westeros(_) => Phase::Westeros,
+            EnginePhase::Planning { .. } => Phase::Planning,
+            EnginePhase::Action(_) => Phase::Action,
+            EnginePhase::Combat { .. } => Phase::Combat,
+        }
+    }
+}
+
+/// Whose turn it is in the action phase, and for which kind of order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ActionTurn {
+    pub sub_phase: ActionSubPhase,
+    /// Index into the turn order of the house to act next.
+    pub player_index: u8,
+}
+
+/// Progress through the Westeros phase.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WesterosProgress {
+    /// The cards drawn this phase, one per deck.
+    pub cards: Vec<WesterosCard>,
+    /// 0 before the draw; then 1–3 for the next card to resolve, 4 once
+    /// all are resolved.
+    pub step: u8,
+    pub substate: WesterosSubstate,
+}
+
+/// What the Westeros phase is busy with between cards.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub enum WesterosSubstate {
+    /// Resolving cards.
+    Cards,
+    /// Clash of Kings or a wildling attack.
+    Bidding(BiddingState),
+    /// Houses muster in seating order; `house_idx` indexes `playing_houses`.
+    Mustering { house_idx: u8 },
+}
+
 // ── Game State ─────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What the engine draws randomness for once the game is set up. Each
+/// purpose is its own stream of the game seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RngPurpose {
+    /// Winter Is Coming shuffling a Westeros deck.
+    WesterosReshuffle,
+    /// A fresh wildling deck once the last card is drawn.
+    WildlingReshuffle,
+}
+
+impl RngPurpose {
+    /// ChaCha stream number; fixed forever, like a wire name.
+    pub fn stream(self) -> u64 {
+        match self {
+            RngPurpose::WesterosReshuffle => 1,
+            RngPurpose::WildlingReshuffle => 2,
+        }
+    }
+}
+
+/// Draws taken so far from each `RngPurpose` stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RngCounters {
+    #[serde(default)]
+    pub westeros_reshuffle: u64,
+    #[serde(default)]
+    pub wildling_reshuffle: u64,
+}
+
+impl RngCounters {
+    pub fn get_mut(&mut self, purpose: RngPurpose) -> &mut u64 {
+        match purpose {
+            RngPurpose::WesterosReshuffle => &mut self.westeros_reshuffle,
+            RngPurpose::WildlingReshuffle => &mut self.wildling_reshuffle,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GameState {
     pub round: u8, // 1–10
-    pub phase: Phase,
-    pub action_sub_phase: ActionSubPhase,
-    pub action_player_index: u8,
+    /// The phase, with its own progress.
+    pub engine_phase: EnginePhase,
 
     /// Per-house profiles. Order matches HouseName::ALL.
     pub houses: HashMap<HouseName, HouseProfile>,
@@ -556,29 +1413,43 @@ pub struct GameState {
     pub order_restrictions: Vec<OrderType>,
     pub star_order_restrictions: Vec<OrderType>,
 
-    // Active combat (if any)
-    pub combat: Option<CombatState>,
-
-    // Bidding state (Clash of Kings / Wildling Attack)
-    pub bidding: Option<BiddingState>,
-
-    // Westeros phase tracking
-    pub westeros_cards_drawn: Vec<WesterosCard>,
-    pub westeros_step: u8,
-    pub muster_house_idx: u8,
-
-    // Deterministic RNG
+    // Deterministic RNG: draws per purpose (see `engine::next_rng`)
     pub seed: u64,
-    pub rng_counter: u64,
+    #[serde(default)]
+    pub rng_counters: RngCounters,
 
-    // Current pending decision the game is waiting on
-    pub pending: Option<PendingDecision>,
+    // Decisions the game is waiting on, answered front to back
+    pub pending: VecDeque<PendingDecision>,
 
     // Winner (if game over)
     pub winner: Option<HouseName>,
 
     // Which houses are playing (subset of HouseName::ALL based on player count)
     pub playing_houses: Vec<HouseName>,
+
+    // Ordered log of game events (captures, victory, ...)
+    #[serde(default)]
+    pub events: Vec<GameEvent>,
+
+    // Handicaps and other rule variations
+    #[serde(default)]
+    pub options: GameOptions,
+
+    // Houses that resigned, in the order they did. The engine answers
+    // their decisions for them.
+    #[serde(default)]
+    pub resigned: Vec<HouseName>,
+    // Houses offering a draw this round
+    #[serde(default)]
+    pub draw_offers: Vec<HouseName>,
+    // The game ended in an agreed draw; `winner` then holds the leader by
+    // the tiebreaker
+    #[serde(default)]
+    pub drawn: bool,
+    // Under `VictoryCondition::KingOfTheHill`: who has held the hill at
+    // the end of how many rounds in a row
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hill_holder: Option<(HouseName, u8)>,
 }
 
 impl GameState {
@@ -587,6 +1458,69 @@ impl GameState {
         self.playing_houses.len() as u8
     }
 
+    /// Playing houses that have not resigned.
+    pub fn remaining_houses(&self) -> Vec<HouseName> {
+        self.playing_houses.iter().copied().filter(|h| !self.resigned.contains(h)).collect()
+    }
+
+    /// The decision that must be answered next (head of the queue).
+    pub fn peek_pending(&self) -> Option<&PendingDecision> {
+        self.pending.front()
+    }
+
+    /// Is the game waiting on any decision?
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Queue a decision behind any that are already outstanding.
+    pub fn enqueue_pending(&mut self, decision: PendingDecision) {
+        self.pending.push_back(decision);
+    }
+
+    /// Remove and return the head of the queue.
+    pub fn pop_pending(&mut self) -> Option<PendingDecision> {
+        self.pending.pop_front()
+    }
+
+    /// Move the first queued decision `pick` matches to the head of the
+    /// queue; false if none does.
+    pub fn promote_pending(&mut self, pick: impl Fn(&PendingDecision) -> bool) -> bool {
+        let Some(i) = self.pending.iter().position(pick) else { return false };
+        let decision = self.pending.remove(i).expect("position is in the queue");
+        self.pending.push_front(decision);
+        true
+    }
+
+    /// Put a decision back at the head of the queue, still unanswered.
+    pub fn restore_pending(&mut self, decision: PendingDecision) {
+        self.pending.push_front(decision);
+    }
+
+    /// Drop every outstanding decision (e.g. when the game ends).
+    pub fn clear_pending(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Append an event to the log, stamped with the next sequence number.
+    pub fn log_event(&mut self, kind: GameEventKind) {
+        let seq = self.events.len() as u32;
+        tracing::debug!(seq, round = self.round, phase = ?self.phase(), event = ?kind, "game event");
+        self.events.push(GameEvent {
+            seq,
+            round: self.round,
+            phase: self.phase(),
+            kind,
+        });
+    }
+
+    /// Number of castle/stronghold areas a house currently controls.
+    pub fn castle_count(&self, h: HouseName) -> u8 {
+        self.areas.iter().enumerate()
+            .filter(|(i, a)| a.house == Some(h) && crate::map::AREAS[*i].has_castle_or_stronghold())
+            .count() as u8
+    }
+
     /// Get the house profile for a house.
     pub fn house(&self, h: HouseName) -> &HouseProfile {
         &self.houses[&h]
@@ -607,8 +1541,126 @@ impl GameState {
         &mut self.areas[id.0 as usize]
     }
 
+    /// Check that each house's units in play and in its pool add up to
+    /// `UnitPool::CAPACITY`. Units marching in a combat count as in play.
+    pub fn check_unit_pools(&self) -> Result<(), GameError> {
+        let marching = self.combat().into_iter().flat_map(|c| &c.attacking_units);
+        let mut in_play: HashMap<(HouseName, UnitType), u8> = HashMap::new();
+        for unit in self.areas.iter().flat_map(|a| &a.units).chain(marching) {
+            *in_play.entry((unit.house, unit.unit_type)).or_default() += 1;
+        }
+        for &house in &self.playing_houses {
+            let pool = &self.house(house).available_units;
+            for ut in UnitType::ALL {
+                let on = in_play.get(&(house, ut)).copied().unwrap_or(0);
+                if on as u16 + pool.get(ut) as u16 != UnitPool::CAPACITY.get(ut) as u16 {
+                    return Err(GameError::UnitsNotConserved { house, unit_type: ut, in_play: on, in_pool: pool.get(ut) });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the combat in progress, if any, is one march order's
+    /// battle: the attacker's units came from a single area, which holds
+    /// its march order until the battle is decided, and each side's stack
+    /// is its own house's.
+    pub fn check_combat(&self) -> Result<(), GameError> {
+        let mismatch = |reason: String| Err(GameError::CombatMismatch(reason));
+        let Some(combat) = self.combat() else { return Ok(()) };
+        if combat.attacker == combat.defender {
+            return mismatch(format!("{} fights itself", combat.attacker));
+        }
+        if let Some(u) = combat.attacking_units.iter().find(|u| u.house != combat.attacker) {
+            return mismatch(format!("{}'s {:?} marches for {}", u.house, u.unit_type, combat.attacker));
+        }
+        if let Some(u) = combat.defending_units.iter().find(|u| u.house != combat.defender) {
+            return mismatch(format!("{}'s {:?} defends for {}", u.house, u.unit_type, combat.defender));
+        }
+        let Some(from) = combat.march_from_area else { return Ok(()) };
+        if from == combat.area_id {
+            return mismatch(format!("the march into area {} starts there", from.0));
+        }
+        // Once the battle is decided the spent order may already be gone
+        // (Cersei, Nymeria)
+        let order = self.area(from).order;
+        if combat.outcome.is_none() && !order.is_some_and(|o| o.order_type == OrderType::March && o.house == combat.attacker) {
+            return mismatch(format!("area {} holds no march order of {}", from.0, combat.attacker));
+        }
+        Ok(())
+    }
+
     /// Current player whose turn it is in the action phase.
     pub fn current_action_player(&self) -> HouseName {
-        self.turn_order[self.action_player_index as usize]
+        let index = self.action_turn().map_or(0, |t| t.player_index);
+        self.turn_order[index as usize]
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.engine_phase.kind()
+    }
+
+    /// The battle being fought, during the combat phase.
+    pub fn combat(&self) -> Option<&CombatState> {
+        match &self.engine_phase {
+            EnginePhase::Combat { combat, .. } => Some(combat),
+            _ => None,
+        }
+    }
+
+    pub fn combat_mut(&mut self) -> Option<&mut CombatState> {
+        match &mut self.engine_phase {
+            EnginePhase::Combat { combat, .. } => Some(combat),
+            _ => None,
+        }
+    }
+
+    /// The action-phase turn, also kept while a march's combat is fought.
+    pub fn action_turn(&self) -> Option<ActionTurn> {
+        match &self.engine_phase {
+            EnginePhase::Action(turn) | EnginePhase::Combat { turn, .. } => Some(*turn),
+            _ => None,
+        }
+    }
+
+    pub fn action_turn_mut(&mut self) -> Option<&mut ActionTurn> {
+        match &mut self.engine_phase {
+            EnginePhase::Action(turn) | EnginePhase::Combat { turn, .. } => Some(turn),
+            _ => None,
+        }
+    }
+
+    /// The action sub-phase; `Raid` outside the action and combat phases.
+    pub fn action_sub_phase(&self) -> ActionSubPhase {
+        self.action_turn().map_or(ActionSubPhase::Raid, |t| t.sub_phase)
+    }
+
+    pub fn westeros(&self) -> Option<&WesterosProgress> {
+        match &self.engine_phase {
+            EnginePhase::Westeros(progress) => Some(progress),
+            _ => None,
+        }
+    }
+
+    pub fn westeros_mut(&mut self) -> Option<&mut WesterosProgress> {
+        match &mut self.engine_phase {
+            EnginePhase::Westeros(progress) => Some(progress),
+            _ => None,
+        }
+    }
+
+    /// The bidding under way, during the Westeros phase.
+    pub fn bidding(&self) -> Option<&BiddingState> {
+        match self.westeros().map(|w| &w.substate) {
+            Some(WesterosSubstate::Bidding(bidding)) => Some(bidding),
+            _ => None,
+        }
+    }
+
+    pub fn bidding_mut(&mut self) -> Option<&mut BiddingState> {
+        match self.westeros_mut().map(|w| &mut w.substate) {
+            Some(WesterosSubstate::Bidding(bidding)) => Some(bidding),
+            _ => None,
+        }
     }
 }
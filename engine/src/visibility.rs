@@ -12,9 +12,13 @@
 // ═══════════════════════════════════════════════════════════════════════
 
 use crate::types::*;
+use crate::engine::{self, MusterAction2};
 use crate::map::NUM_AREAS;
 use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
+use crate::threats::ThreatMap;
 use std::collections::HashMap;
+use std::sync::OnceLock;
 
 // ── What is PUBLIC ─────────────────────────────────────────────────────
 //
@@ -27,6 +31,7 @@ use std::collections::HashMap;
 // • Current round and phase
 // • Westeros cards AFTER drawn (and which deck they came from)
 // • House cards in DISCARD piles (played cards are face-up)
+// • Card play statistics (cards played, swords dealt, abilities triggered)
 // • Orders AFTER reveal (orders are placed face-down, then all flipped)
 // • Combat results (attacker, defender, cards played, strengths)
 // • Number of cards remaining in each house's hand (but NOT which cards)
@@ -48,7 +53,7 @@ use std::collections::HashMap;
 
 /// The view of the game state that a specific player is allowed to see.
 /// This is what gets passed to an Agent's decision functions.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PlayerView {
     // ── Public info ────────────────────────────────────────
     pub viewer: HouseName,
@@ -85,6 +90,18 @@ pub struct PlayerView {
     /// Winner (if game is over).
     pub winner: Option<HouseName>,
 
+    /// Houses that resigned, in order; the engine plays out their turns.
+    #[serde(default)]
+    pub resigned: Vec<HouseName>,
+    /// Houses offering a draw this round. The game ends drawn once every
+    /// house still playing is on the list.
+    #[serde(default)]
+    pub draw_offers: Vec<HouseName>,
+    /// Houses whose sealed bid is in, during bidding. The amounts stay
+    /// hidden until every bid is in and all are revealed together.
+    #[serde(default)]
+    pub bids_in: Vec<HouseName>,
+
     // ── Private info (only for the viewer) ─────────────────
     /// Your own hand of house cards.
     pub my_hand: Vec<HouseCardId>,
@@ -92,10 +109,70 @@ pub struct PlayerView {
     /// Your own unrevealed orders (during Planning phase, before reveal).
     /// Maps area_id → order. Empty if orders have been revealed.
     pub my_orders: HashMap<AreaId, Order>,
+
+    /// Order tokens (indices into ORDER_TOKENS) you could still place:
+    /// not on the board, not restricted, stars only within your budget.
+    #[serde(default)]
+    pub my_available_tokens: Vec<u8>,
+
+    /// Star orders you may still place: your King's Court allowance less
+    /// the stars you have on the board.
+    #[serde(default)]
+    pub my_star_budget: u8,
+
+    /// Your thinking time, when the game has a time control. Filled in
+    /// by the game runner; `player_view` leaves it empty.
+    #[serde(default)]
+    pub clock: Option<ClockView>,
+
+    // ── Derived ────────────────────────────────────────────
+    /// Built by `threats()` on first use; never serialized.
+    #[serde(skip)]
+    threat_map: OnceLock<ThreatMap>,
+}
+
+impl PlayerView {
+    /// What the viewer's enemies could bring to each area next move,
+    /// computed once per view.
+    pub fn threats(&self) -> &ThreatMap {
+        self.threat_map.get_or_init(|| ThreatMap::of(self))
+    }
+
+    /// Orders the pending Cersei or Queen of Thorns decision may remove.
+    pub fn removable_orders(&self) -> &[AreaId] {
+        self.pending.as_ref().map_or(&[], PendingDecision::removal_targets)
+    }
+
+    /// Tokens still free for the viewer's next order once `placed` are
+    /// down: from `my_available_tokens`, stars while the budget lasts.
+    pub fn placeable_tokens(&self, placed: &[u8]) -> Vec<u8> {
+        let stars = placed.iter().filter(|&&t| ORDER_TOKENS[t as usize].star).count() as u8;
+        self.my_available_tokens.iter().copied()
+            .filter(|t| !placed.contains(t))
+            .filter(|&t| !ORDER_TOKENS[t as usize].star || stars < self.my_star_budget)
+            .collect()
+    }
+
+    /// What the viewer can still muster in `area_id` with `points` left
+    /// after `chosen`, and the cost of each: the options `validate_action`
+    /// accepts, within the pool and supply.
+    pub fn muster_options(&self, area_id: AreaId, points: u8, chosen: &[(AreaId, MusterAction2)]) -> Vec<(MusterAction2, u8)> {
+        let me = &self.house_info[&self.viewer];
+        engine::board_muster_options(self, self.viewer, me.available_units, me.supply, area_id, points, chosen)
+    }
+}
+
+/// The viewer's clock under `GameOptions::time_control`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ClockView {
+    /// Time left before this decision; taking longer forfeits the game.
+    pub remaining_ms: u64,
+    /// Added back after every answer.
+    pub increment_ms: u64,
 }
 
 /// Public information about a house (visible to all players).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PublicHouseInfo {
     pub name: HouseName,
     pub iron_throne: u8,
@@ -109,10 +186,62 @@ pub struct PublicHouseInfo {
     pub discards: Vec<HouseCardId>,
     /// Available units in the pool (public — you can see the plastic pieces).
     pub available_units: UnitPool,
+    /// Castles and strongholds this house needs to win (7 unless handicapped).
+    pub castles_to_win: u8,
+    /// What the cards this house may still hold could do in its next
+    /// combat (from `possible_hand`).
+    #[serde(default)]
+    pub remaining_abilities: AbilitySummary,
+    /// House cards played so far, swords dealt and abilities triggered.
+    #[serde(default)]
+    pub card_stats: CardStats,
+}
+
+/// The dangerous abilities among a set of house cards, for pricing in
+/// what an opponent could still play.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AbilitySummary {
+    /// Highest card strength.
+    pub max_strength: u8,
+    /// Most swords on one card: casualties the house can inflict.
+    pub max_swords: u8,
+    /// Most fortifications on one card: casualties it can block.
+    pub max_fortifications: u8,
+    /// Can make its opponent take back a card and play another (Tyrion).
+    pub cancels_card: bool,
+    /// Can make its opponent's support count for nothing (Balon).
+    pub cancels_support: bool,
+    /// Can move its opponent to the bottom of a track (Doran).
+    pub drops_track: bool,
+    /// Can take power from its opponent.
+    pub steals_power: bool,
+    /// Can remove one of its opponent's orders (Cersei, Nymeria, the
+    /// Queen of Thorns).
+    pub removes_order: bool,
+}
+
+impl AbilitySummary {
+    pub fn of(cards: &[HouseCardId]) -> Self {
+        let mut summary = AbilitySummary::default();
+        for card in cards.iter().map(|&c| crate::cards::get_house_card(c)) {
+            summary.max_strength = summary.max_strength.max(card.strength);
+            summary.max_swords = summary.max_swords.max(card.swords);
+            summary.max_fortifications = summary.max_fortifications.max(card.fortifications);
+            match card.ability {
+                CardAbility::CancelOpponentCard => summary.cancels_card = true,
+                CardAbility::CancelEnemySupport => summary.cancels_support = true,
+                CardAbility::MoveOpponentToTrackBottom => summary.drops_track = true,
+                CardAbility::StealPower { .. } | CardAbility::StealPowerByOpponentStrength => summary.steals_power = true,
+                CardAbility::RemoveEnemyOrder { .. } | CardAbility::RemoveAdjacentOrder => summary.removes_order = true,
+                _ => {}
+            }
+        }
+        summary
+    }
 }
 
 /// View of a single area on the board.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AreaView {
     pub id: AreaId,
     pub units: Vec<Unit>,
@@ -133,12 +262,23 @@ pub struct AreaView {
 /// Orders are revealed after all players have placed them in the Planning phase.
 /// During the Action phase and Combat, all orders are visible.
 fn orders_are_revealed(state: &GameState) -> bool {
-    matches!(state.phase, Phase::Action | Phase::Combat)
-        || (state.phase == Phase::Westeros)
+    !matches!(state.engine_phase, EnginePhase::Planning { orders_revealed: false })
 }
 
 /// Build the PlayerView for a specific house.
 pub fn player_view(state: &GameState, viewer: HouseName) -> PlayerView {
+    build_view(state, viewer, true)
+}
+
+/// A cheaper view for agents that only need the board, their own
+/// decision, hand and tokens (see `Agent::needs_view`): skips every
+/// house's discards, card stats and remaining abilities, the garrisons
+/// and the combat.
+pub fn minimal_view(state: &GameState, viewer: HouseName) -> PlayerView {
+    build_view(state, viewer, false)
+}
+
+fn build_view(state: &GameState, viewer: HouseName, full: bool) -> PlayerView {
     let revealed = orders_are_revealed(state);
 
     // Build area views
@@ -179,8 +319,15 @@ pub fn player_view(state: &GameState, viewer: HouseName) -> PlayerView {
             supply: profile.supply,
             power: profile.power,
             cards_in_hand: profile.hand.len() as u8,
-            discards: profile.discards.clone(),
+            discards: if full { profile.discards.clone() } else { Vec::new() },
             available_units: profile.available_units,
+            castles_to_win: state.options.castles_to_win(h),
+            remaining_abilities: if full {
+                AbilitySummary::of(&possible_hand(state, h))
+            } else {
+                AbilitySummary::default()
+            },
+            card_stats: if full { profile.card_stats.clone() } else { CardStats::default() },
         });
     }
 
@@ -199,63 +346,80 @@ pub fn player_view(state: &GameState, viewer: HouseName) -> PlayerView {
         }
     }
 
-    // Pending decision: only pass it if it involves the viewer
-    let pending = state.pending.as_ref().and_then(|p| {
-        if pending_involves(p, viewer) { Some(p.clone()) } else { None }
-    });
+    // Pending decision: only pass it if it involves the viewer. Sealed
+    // bids and orders are all asked at once, so a house still to answer
+    // sees its own decision whichever is at the head of the queue.
+    let open = engine::open_decisions(state);
+    let pending = match state.peek_pending() {
+        Some(_) if !open.is_empty() => open.into_iter().find(|p| p.house() == viewer),
+        Some(p) if pending_involves(p, viewer) => Some(p.clone()),
+        _ => None,
+    };
 
     PlayerView {
         viewer,
         round: state.round,
-        phase: state.phase,
-        action_sub_phase: state.action_sub_phase,
+        phase: state.phase(),
+        action_sub_phase: state.action_sub_phase(),
         wildling_threat: state.wildling_threat,
         turn_order: state.turn_order.clone(),
         playing_houses: state.playing_houses.clone(),
         house_info,
         areas: area_views,
-        garrisons: state.garrisons.clone(),
-        combat: state.combat.clone(),
+        garrisons: if full { state.garrisons.clone() } else { HashMap::new() },
+        combat: if full { state.combat().cloned() } else { None },
         pending,
         valyrian_steel_blade_used: state.valyrian_steel_blade_used,
         messenger_raven_used: state.messenger_raven_used,
         order_restrictions: state.order_restrictions.clone(),
         star_order_restrictions: state.star_order_restrictions.clone(),
         winner: state.winner,
+        resigned: state.resigned.clone(),
+        draw_offers: state.draw_offers.clone(),
+        bids_in: state.bidding().map(|b| b.bids_in()).unwrap_or_default(),
         my_hand,
         my_orders,
+        my_available_tokens: engine::available_tokens(state, viewer),
+        my_star_budget: engine::star_budget(state, viewer),
+        clock: None,
+        threat_map: OnceLock::new(),
     }
 }
 
+/// The view of the house answering `pending` when it is asked ahead of
+/// its turn, as with `engine::open_bids`.
+pub fn view_for(state: &GameState, pending: &PendingDecision) -> PlayerView {
+    let mut view = player_view(state, pending.house());
+    view.pending = Some(pending.clone());
+    view
+}
+
 /// Check if a pending decision involves a specific house.
 fn pending_involves(pending: &PendingDecision, house: HouseName) -> bool {
     match pending {
         PendingDecision::WesterosChoice { chooser, .. } => *chooser == house,
         PendingDecision::SupportDeclaration { house: h, .. } => *h == house,
-        PendingDecision::TyrionReplace { opponent } => *opponent == house,
-        PendingDecision::AeronSwap { house: h } => *h == house,
-        PendingDecision::PatchfaceDiscard { .. } => {
-            // Patchface is decided by the Baratheon player (winner)
-            // But the cards shown are the opponent's — still the decision-maker sees them
-            // The actual decider is the Patchface player
-            true // Simplified: let the agent framework handle filtering
-        }
+        PendingDecision::TyrionReplace { decider, .. } => *decider == house,
+        PendingDecision::AeronSwap { house: h, .. } => *h == house,
+        // Patchface's decision lists the opponent's hand: its decider only
+        PendingDecision::PatchfaceDiscard { decider, .. } => *decider == house,
         PendingDecision::RobbRetreat { .. } => true,
         PendingDecision::Retreat { house: h, .. } => *h == house,
         PendingDecision::Reconcile { house: h, .. } => *h == house,
         PendingDecision::Muster { house: h, .. } => *h == house,
-        PendingDecision::Bidding { .. } => true, // All houses bid simultaneously
+        PendingDecision::Bidding { house: h, .. } => *h == house,
         PendingDecision::LeavePowerToken { .. } => true,
         PendingDecision::UseValyrianBlade { .. } => true,
         PendingDecision::PlaceOrders { house: h } => *h == house,
+        PendingDecision::ChooseOrderToResolve { house: h, .. } => *h == house,
         PendingDecision::ChooseRaid { house: h, .. } => *h == house,
         PendingDecision::ChooseMarch { house: h, .. } => *h == house,
         PendingDecision::SelectHouseCard { house: h, .. } => *h == house,
         PendingDecision::MessengerRaven { house: h } => *h == house,
         PendingDecision::WildlingPenaltyChoice { house: h, .. } => *h == house,
-        PendingDecision::CerseiRemoveOrder { .. } => true,
-        PendingDecision::DoranChooseTrack { .. } => true,
-        PendingDecision::QueenOfThornsRemoveOrder { .. } => true,
+        PendingDecision::CerseiRemoveOrder { decider, .. } => *decider == house,
+        PendingDecision::DoranChooseTrack { decider, .. } => *decider == house,
+        PendingDecision::QueenOfThornsRemoveOrder { decider, .. } => *decider == house,
     }
 }
 
@@ -0,0 +1,176 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Wire format — the JSON contract for actions, decisions and events
+//
+// Replays, API logs and bot clients outlive any one build, so the JSON
+// shape of the engine types is a policy rather than whatever the derives
+// happen to produce:
+//
+//   • enums carrying data are tagged with a snake_case "type" field —
+//     struct variants inline their fields (PendingDecision, GameEventKind),
+//     the rest put their payload under "value" (Action, MusterAction2)
+//   • fieldless enums (houses, units, cards, phases) are their variant
+//     name, e.g. "Stark", "Footman"
+//   • struct fields keep their Rust names
+//
+// The frozen wire-format tests pin every name, so renaming a variant
+// without `#[serde(rename = "...")]` fails the build. Logs carry the
+// `WIRE_VERSION` they were written with; older versions are upgraded
+// here before deserializing.
+//
+// Version 1: externally tagged enums with PascalCase variant names,
+//            e.g. {"Bid": 2}, "MarchSkip", {"PlaceOrders": {"house": ...}}.
+// Version 2: the tagged policy above. Part-way through it the Queen of
+//            Thorns decision gained `house`, the house asked, without a
+//            version bump; before, it held only `opponent` and asked that
+//            house. Both shapes are version 2 and are told apart by the
+//            field.
+// Version 3: the card-ability decisions (Tyrion, Patchface, Cersei, Doran,
+//            Queen of Thorns) name the house answering them `decider`;
+//            Robb's retreat names the `defender`, its `units` and the
+//            battle's `from_area`. Older Robb retreats recorded none of
+//            them and can't be upgraded.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::engine::Action;
+use crate::types::{GameEvent, PendingDecision};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+/// Wire format version written by this build.
+pub const WIRE_VERSION: u32 = 3;
+
+/// Reads an `Action` written with wire format `version`.
+pub fn read_action(value: Value, version: u32) -> Result<Action, String> {
+    read(value, version, |v| Ok(upgrade_action(v)))
+}
+
+/// Reads a `PendingDecision` written with wire format `version`.
+pub fn read_pending(value: Value, version: u32) -> Result<PendingDecision, String> {
+    read(value, version, upgrade_pending)
+}
+
+/// Reads a `GameEvent` written with wire format `version`.
+pub fn read_event(value: Value, version: u32) -> Result<GameEvent, String> {
+    read(value, version, upgrade_event)
+}
+
+fn read<T: DeserializeOwned>(value: Value, version: u32, upgrade: fn(Value) -> Result<Value, String>) -> Result<T, String> {
+    let value = match version {
+        0 => return Err("wire format version 0 does not exist".into()),
+        WIRE_VERSION => value,
+        v if v < WIRE_VERSION => upgrade(value)?,
+        v => return Err(format!("wire format version {} is newer than this build ({})", v, WIRE_VERSION)),
+    };
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+// ── Upgrades ───────────────────────────────────────────────────────────
+//
+// Each takes a value written with any earlier version to the current
+// shape; a step that finds its change already made leaves it alone. A
+// value missing what the current shape needs, with nothing to recover it
+// from, is an error rather than a guess.
+
+/// Rewrites a version 1 or 2 `Action` into the current shape.
+pub fn upgrade_action(value: Value) -> Value {
+    let mut action = retag(value, Some("value"));
+    if action["type"] == "muster" {
+        if let Some(Value::Array(builds)) = action.get_mut("value") {
+            for build in builds {
+                if let Some(pair) = build.as_array_mut().filter(|p| p.len() == 2) {
+                    pair[1] = retag(pair[1].take(), Some("value"));
+                }
+            }
+        }
+    }
+    action
+}
+
+/// Rewrites a version 1 or 2 `PendingDecision` into the current shape.
+pub fn upgrade_pending(value: Value) -> Result<Value, String> {
+    refuse_robb_retreat(name_decider(retag(value, None)))
+}
+
+/// Rewrites a version 1 or 2 `GameEvent` into the current shape,
+/// including the decision a `DecisionForced` event carries.
+pub fn upgrade_event(mut value: Value) -> Result<Value, String> {
+    if let Some(kind) = value.get_mut("kind") {
+        *kind = retag(kind.take(), None);
+        if let Some(decision) = kind.get_mut("decision") {
+            *decision = upgrade_pending(decision.take())?;
+        }
+    }
+    Ok(value)
+}
+
+/// Version 2 asked Tyrion's, Patchface's, Cersei's and Doran's decisions
+/// of `opponent`, and Queen of Thorns' of `house` or, before it had one,
+/// of `opponent`: version 3 calls the house asked `decider`. Where the
+/// house asked was `opponent`, the other side of the battle was never
+/// recorded and the upgraded `opponent` stays the decider.
+fn name_decider(mut value: Value) -> Value {
+    if let Some(fields) = value.as_object_mut().filter(|f| !f.contains_key("decider")) {
+        let asked = match fields.get("type").and_then(Value::as_str) {
+            Some("tyrion_replace" | "patchface_discard" | "cersei_remove_order" | "doran_choose_track") => fields.get("opponent").cloned(),
+            Some("queen_of_thorns_remove_order") => fields.remove("house").or_else(|| fields.get("opponent").cloned()),
+            _ => None,
+        };
+        if let Some(house) = asked {
+            fields.insert("decider".into(), house);
+        }
+    }
+    value
+}
+
+/// Robb retreats before version 3 held only Robb's owner and the areas on
+/// offer. The retreating house, its units and the battle's area aren't
+/// recorded anywhere in the decision, so it can't be upgraded.
+fn refuse_robb_retreat(value: Value) -> Result<Value, String> {
+    if value["type"] == "robb_retreat" && value.get("defender").is_none() {
+        return Err("cannot upgrade a robb_retreat from before version 3: it doesn't record the defender, its units or the battle's area".into());
+    }
+    Ok(value)
+}
+
+/// Turns an externally tagged enum value (`"Unit"` or `{"Variant": x}`)
+/// into a "type"-tagged one. With `content` the payload goes under that
+/// key; without it the payload's fields are inlined. Anything else is
+/// returned unchanged, so already-upgraded values pass through.
+fn retag(value: Value, content: Option<&str>) -> Value {
+    let (name, payload) = match value {
+        Value::String(name) => (name, None),
+        Value::Object(map) if map.len() == 1 && !map.contains_key("type") => {
+            let (name, payload) = map.into_iter().next().unwrap();
+            (name, Some(payload))
+        }
+        other => return other,
+    };
+    let mut tagged = Map::new();
+    tagged.insert("type".into(), Value::String(snake_case(&name)));
+    match (payload, content) {
+        (None, _) => {}
+        (Some(payload), Some(key)) => {
+            tagged.insert(key.into(), payload);
+        }
+        (Some(Value::Object(fields)), None) => tagged.extend(fields),
+        (Some(other), None) => {
+            tagged.insert("value".into(), other);
+        }
+    }
+    Value::Object(tagged)
+}
+
+fn snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
@@ -0,0 +1,83 @@
+// ═══════════════════════════════════════════════════════════════════════
+// protocol-tests — check an external bot against the wire protocol
+//
+//   protocol-tests "python3 bots/python/starter_bot.py"
+//
+// Asks the bot real positions of every decision type over stdin/stdout
+// (see `got_server::conformance`) and reports, per type, how many answers
+// were legal. Exits 1 if any answer fails, so bot authors can run it in
+// their own CI before entering tournaments.
+// ═══════════════════════════════════════════════════════════════════════
+
+use clap::Parser;
+use got_server::conformance;
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(name = "protocol-tests", about = "Check an external bot's answers for every decision type")]
+struct Cli {
+    /// Command starting the bot, run through `sh -c`
+    command: String,
+    /// Positions asked per decision type
+    #[arg(long, default_value_t = 3)]
+    per_kind: usize,
+    /// Random games played at most to find positions
+    #[arg(long, default_value_t = 40)]
+    games: usize,
+    #[arg(long, default_value_t = 1)]
+    seed: u64,
+    /// Time allowed per answer
+    #[arg(long, default_value_t = 5000)]
+    timeout_ms: u64,
+    /// Fail when some decision type could not be sampled
+    #[arg(long)]
+    strict: bool,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let samples = match conformance::samples(cli.seed, cli.games, cli.per_kind) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Cannot build positions: {}", e);
+            std::process::exit(2);
+        }
+    };
+    println!("Asking '{}' {} positions...\n", cli.command, samples.len());
+    let results = match conformance::run(&cli.command, &samples, Duration::from_millis(cli.timeout_ms)) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    println!("{:<26} {:>6} {:>6} {:>9}  First failure", "Decision", "Asked", "Legal", "Slowest");
+    println!("{}", "-".repeat(80));
+    let mut failed = 0;
+    let mut kinds: Vec<_> = results.iter().map(|r| r.kind).collect();
+    kinds.dedup();
+    for kind in kinds {
+        let of_kind: Vec<_> = results.iter().filter(|r| r.kind == kind).collect();
+        let legal = of_kind.iter().filter(|r| r.outcome.is_ok()).count();
+        let slowest = of_kind.iter().map(|r| r.elapsed).max().unwrap_or_default();
+        let first = of_kind.iter().find_map(|r| r.outcome.as_ref().err().map(|e| format!("{} round {}: {}", r.house, r.round, e)));
+        failed += of_kind.len() - legal;
+        println!("{:<26} {:>6} {:>6} {:>7}ms  {}", kind.key(), of_kind.len(), legal, slowest.as_millis(), first.unwrap_or_default());
+    }
+    let uncovered = conformance::uncovered(&samples);
+    if !uncovered.is_empty() {
+        let names: Vec<String> = uncovered.iter().map(|k| k.key()).collect();
+        println!("\nNot sampled in {} games: {}", cli.games, names.join(", "));
+    }
+
+    if failed > 0 {
+        println!("\nFAIL: {} of {} answers not legal", failed, results.len());
+        std::process::exit(1);
+    }
+    if cli.strict && !uncovered.is_empty() {
+        println!("\nFAIL: {} decision types not tested (--strict)", uncovered.len());
+        std::process::exit(1);
+    }
+    println!("\nOK: all {} answers legal", results.len());
+}
@@ -2,18 +2,74 @@
 // Runner — CLI entry point for running games and tournaments
 // ═══════════════════════════════════════════════════════════════════════
 
-use got_engine::types::HouseName;
-use got_agents::{RandomAgent, HeuristicAgent};
-use got_agents::Agent;
-use got_tournament::{run_game, database::Database};
+use got_engine::types::{GameEvent, GameEventKind, HouseName};
+use got_engine::map::{analysis as map_analysis, area_name, AREAS};
+use got_engine::setup::SetupConfig;
+use got_agents::registry::parse_lineup;
+use got_tournament::{assign_seats, biggest_battle, castle_trajectory, run_games_parallel, run_seated_game, run_seated_game_observed, ActionPolicy, FaultKind, SeatAgent};
+use got_tournament::database::{Database, DbWriter, LeaderboardFilter};
+use got_tournament::dataset::{self, DatasetFormat, ExportOptions};
+use got_tournament::ratings::RatingSystem;
+use got_tournament::training;
+use got_tournament::league::{self, LeagueOptions};
+use got_tournament::probes::{self, ProbeOptions, Swap};
+use got_tournament::balance::{self, BalanceFilter};
+use got_tournament::experiments::{self, ExperimentOptions, Perturbation};
+use got_tournament::map_balance::{self, MapBalanceOptions, Zone};
+use got_tournament::analysis::{self, AnalyzeOptions};
+use got_tournament::calibration::{self, SampleOptions};
+use got_tournament::rollouts::{self, RolloutBenchOptions};
+use got_tournament::daemon::{self, DaemonExit, DaemonOptions};
+use got_tournament::notify::{Hook, Milestone, Notifier};
+use got_tournament::matches::{run_matches_parallel, MatchOptions};
+use got_tournament::contexts::{self, ContextWriter};
+use got_tournament::positions;
+use got_tournament::differential;
+use got_tournament::metrics::Metrics;
+use signal_hook::consts::{SIGINT, SIGTERM};
+use got_server::{api, schema};
+use got_server::lobby::{self, LobbyOptions, ServerMessage};
+use got_engine::types::{GameOptions, TimeControl, CASTLES_TO_WIN};
+use got_engine::encoding::DecisionKind;
+use got_engine::eval;
+use got_engine::threats::ThreatMap;
+use got_engine::rollout::RolloutLimit;
+use got_engine::replay::{self, Annotation, AnnotationTarget, Replay, ReplayPlayer};
+use got_engine::snapshot;
+use got_agents::AgentSpec;
+use got_agents::cloned::FitOptions;
 use std::collections::HashMap;
-use clap::{Parser, Subcommand};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use clap::{Args, Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(name = "got-runner", about = "Game of Thrones Strategy Lab")]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Log format on stderr; what gets logged is set by RUST_LOG (default "warn"), e.g. RUST_LOG=got_engine=debug
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum LogFormat {
+    Text,
+    /// One JSON object per line, with the fields of every enclosing span
+    Json,
+}
+
+fn init_logging(format: LogFormat) {
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("warn"));
+    let logs = tracing_subscriber::fmt().with_env_filter(filter).with_writer(std::io::stderr);
+    match format {
+        LogFormat::Text => logs.init(),
+        LogFormat::Json => logs.json().init(),
+    }
 }
 
 #[derive(Subcommand)]
@@ -24,139 +80,2053 @@ enum Commands {
         seed: u64,
         #[arg(short, long, default_value_t = 6)]
         players: u8,
-        /// Agent type: "random" or "heuristic"
+        /// Agent spec for every seat, or one per seat separated by '/' (e.g. "heuristic/random")
         #[arg(short, long, default_value = "random")]
         agent: String,
+        /// Comma-separated houses to play (overrides --players), e.g. "stark,lannister,baratheon,tyrell"
+        #[arg(long)]
+        houses: Option<String>,
+        /// Shuffle which seat plays which house
+        #[arg(long)]
+        random_seats: bool,
+        /// Game options JSON file (e.g. handicaps written by `balance`)
+        #[arg(long)]
+        options: Option<String>,
+        /// Comma-separated houses played at this terminal (hot seat), e.g. "stark,lannister"
+        #[arg(long)]
+        humans: Option<String>,
+        /// Save the game as a replay (JSON Lines) to this file
+        #[arg(long)]
+        record: Option<String>,
+        /// Save every decision's view and answer to <DIR>/game-<seed>/view_NNNN.json (re-run one with `decide`)
+        #[arg(long, value_name = "DIR")]
+        dump_decisions: Option<String>,
+    },
+    /// Re-run one decision saved by --dump-decisions (or a bare player view) with any agent
+    Decide {
+        /// Decision context or player view JSON file
+        #[arg(long)]
+        view: String,
+        #[arg(short, long, default_value = "heuristic")]
+        agent: String,
+        /// Agent seed (the first one with --n)
+        #[arg(short, long, default_value_t = 0)]
+        seed: u64,
+        /// Ask N fresh agents, seeded seed..seed+N, and print how often each answer comes up
+        #[arg(short, long, default_value_t = 1)]
+        n: usize,
+    },
+    /// Score an agent on the curated positions (share of answers meeting each position's expectations)
+    VerifyAgent {
+        /// Agent spec
+        spec: String,
+        /// Directory of position files
+        #[arg(long, default_value = "positions")]
+        positions: String,
+        /// Fresh agents asked per position, seeded seed..seed+N
+        #[arg(short, long, default_value_t = 10)]
+        n: usize,
+        #[arg(short, long, default_value_t = 0)]
+        seed: u64,
+        /// Exit with status 1 when the suite score is below this
+        #[arg(long)]
+        min_score: Option<f64>,
+    },
+    /// Play the canonical games and compare them with the stored engine snapshots
+    Snapshots {
+        #[arg(long, default_value = "engine/snapshots/canonical.json")]
+        file: String,
+        /// Rewrite the stored snapshots instead (after a deliberate rule change)
+        #[arg(long)]
+        bless: bool,
     },
     /// Run a tournament of N games
-    Tournament {
-        #[arg(short, long, default_value_t = 100)]
+    Tournament(TournamentArgs),
+    /// Play best-of-N matches: the same seats for N games, houses rotating each game
+    Match(MatchArgs),
+    /// Measure how many games per second the database can store
+    BenchDb {
+        #[arg(short, long, default_value_t = 2000)]
         games: u32,
+        /// Games committed per transaction by the writer thread
+        #[arg(long, default_value_t = 64)]
+        batch: usize,
+    },
+    /// Show leaderboard from database
+    Leaderboard {
+        #[arg(short, long, default_value = "results.db")]
+        db: String,
+        /// Show a recomputed rating system ("elo" or "trueskill") instead of the stored ELO column
+        #[arg(short, long, conflicts_with_all = ["by", "house", "pivot"])]
+        system: Option<String>,
+        /// Order rows by "elo", "winrate" or "games"
+        #[arg(long, default_value = "elo")]
+        by: String,
+        /// Count only the games played as this house
+        #[arg(long)]
+        house: Option<String>,
+        /// Leave out agents with fewer games
+        #[arg(long, default_value_t = 0)]
+        min_games: u32,
+        /// Only agents whose name starts with this, e.g. "mcts"
+        #[arg(long)]
+        agent_prefix: Option<String>,
+        /// Show each agent's wins/games per house
+        #[arg(long, conflicts_with = "house")]
+        pivot: bool,
+    },
+    /// Wipe and recompute ratings from the stored game history
+    RecomputeRatings {
+        #[arg(short, long, default_value = "results.db")]
+        db: String,
+        /// Rating system: "elo" or "trueskill"
+        #[arg(short, long, default_value = "elo")]
+        system: String,
+    },
+    /// Re-run a stored game from its recorded seeds and check the winner matches
+    Repro {
+        #[arg(short, long, default_value = "results.db")]
+        db: String,
+        /// Game id in the database
+        #[arg(short, long)]
+        game: i64,
+        /// Save every decision's view and answer to <DIR>/game-<seed>/view_NNNN.json (re-run one with `decide`)
+        #[arg(long, value_name = "DIR")]
+        dump_decisions: Option<String>,
+    },
+    /// Play back a recorded replay file and list its annotations
+    Replay {
+        /// Replay written by `play --record`
+        file: String,
+        /// List every action, not only the annotated ones
+        #[arg(long)]
+        all: bool,
+    },
+    /// Add a note to a recorded replay file
+    Annotate {
+        /// Replay written by `play --record`
+        file: String,
+        /// Action index the note is about (the whole game when neither --action nor --event is given)
+        #[arg(long)]
+        action: Option<usize>,
+        /// Event sequence number the note is about
+        #[arg(long, conflicts_with = "action")]
+        event: Option<u32>,
+        #[arg(long, default_value = "user")]
+        author: String,
+        #[arg(short, long)]
+        text: Option<String>,
+        /// Structured value as key=value (JSON values are parsed, e.g. win_probability=0.72); repeatable
+        #[arg(short, long)]
+        data: Vec<String>,
+    },
+    /// Flag recorded decisions that a reference agent values far below its best alternative
+    Analyze {
+        /// Replay written by `play --record`
+        #[arg(long)]
+        replay: String,
+        /// Agent that proposes alternatives and plays the playouts
+        #[arg(long, default_value = "heuristic")]
+        reference: String,
+        /// Playouts per candidate action (0 = value candidates with the static evaluator)
+        #[arg(long, default_value_t = 16)]
+        playouts: usize,
+        /// Where playouts stop: full, decisions=N and/or rounds=N (cut short = valued by win probability)
+        #[arg(long, default_value = "full")]
+        rollout: String,
+        /// Best-scored alternatives of the reference to value besides its own choice
+        #[arg(long, default_value_t = 3)]
+        alternatives: usize,
+        /// Placing lost (0–1) at which a decision is flagged as a blunder
+        #[arg(long, default_value_t = 0.25)]
+        threshold: f64,
+        /// Only analyze this house's decisions
+        #[arg(long)]
+        house: Option<String>,
+        /// Write the replay with one annotation per contested decision to this file
+        #[arg(short, long)]
+        out: Option<String>,
+        /// Worker threads (0 = one per core)
+        #[arg(short, long, default_value_t = 0)]
+        threads: usize,
+    },
+    /// Play random games on this engine and a reference engine (the TypeScript original) and report where they first differ
+    DiffEngine {
+        /// Shell command of the reference engine, speaking the JSON Lines protocol of `differential`
+        #[arg(long)]
+        reference: String,
+        #[arg(short, long, default_value_t = 10)]
+        games: u64,
+        #[arg(short, long, default_value_t = 6)]
+        players: u8,
+        /// Seed of the first game (games use seed..seed+N for setup and actions)
+        #[arg(short, long, default_value_t = 0)]
+        seed: u64,
+        /// Actions compared per game at most
+        #[arg(long, default_value_t = 5000)]
+        max_steps: usize,
+    },
+    /// Threat maps: build time per view over the positions of sampled self-play games
+    BenchThreats {
+        /// Agent in every seat
+        #[arg(short, long, default_value = "heuristic")]
+        agent: String,
+        #[arg(short, long, default_value_t = 4)]
+        games: u64,
+        #[arg(short, long, default_value_t = 6)]
+        players: u8,
+        /// Sample every n-th decision
+        #[arg(long, default_value_t = 10)]
+        every: usize,
+        /// Times every sampled view is mapped
+        #[arg(long, default_value_t = 20)]
+        repeat: usize,
+    },
+    /// Measure playouts/sec of truncated rollouts and how far their values drift from full ones
+    BenchRollouts {
+        /// Agent in every seat, for the sampled games and the rollouts
+        #[arg(short, long, default_value = "heuristic")]
+        agent: String,
+        #[arg(short, long, default_value_t = 4)]
+        games: u64,
+        #[arg(short, long, default_value_t = 6)]
+        players: u8,
+        /// Sample every n-th decision
+        #[arg(long, default_value_t = 100)]
+        every: usize,
+        /// Rollouts per position and limit
+        #[arg(long, default_value_t = 8)]
+        playouts: usize,
+        /// Limits to compare with full rollouts, separated by '/'
+        #[arg(long, default_value = "rounds=1/rounds=2/rounds=4/decisions=100")]
+        limits: String,
+        /// Worker threads (0 = one per core)
+        #[arg(short, long, default_value_t = 0)]
+        threads: usize,
+    },
+    /// Check (and with --fit, refit) the static evaluator's win probabilities on self-play positions
+    EvalCalibrate {
+        /// Agent in every seat
+        #[arg(short, long, default_value = "heuristic")]
+        agent: String,
+        #[arg(short, long, default_value_t = 200)]
+        games: u64,
+        #[arg(short, long, default_value_t = 6)]
+        players: u8,
+        /// Sample every n-th decision
+        #[arg(long, default_value_t = 10)]
+        every: usize,
+        /// Fit new weights on part of the games and score them on the rest
+        #[arg(long)]
+        fit: bool,
+        /// Share of games held out for scoring the fitted weights
+        #[arg(long, default_value_t = 0.3)]
+        val_fraction: f64,
+        #[arg(long, default_value_t = 2000)]
+        epochs: usize,
+        #[arg(long, default_value_t = 2.0)]
+        lr: f64,
+        /// Worker threads (0 = one per core)
+        #[arg(short, long, default_value_t = 0)]
+        threads: usize,
+    },
+    /// Export (observation, action, outcome) samples from stored games for training
+    Dataset {
+        #[arg(short, long, default_value = "results.db")]
+        db: String,
+        /// Output directory
+        #[arg(short, long, default_value = "data")]
+        out: String,
+        /// "npz" or "parquet"
+        #[arg(short, long, default_value = "npz")]
+        format: String,
+        /// Share of games put in the validation split
+        #[arg(long, default_value_t = 0.1)]
+        val_fraction: f64,
+        /// Only export decisions made by this agent spec
+        #[arg(short, long)]
+        agent: Option<String>,
+        /// Export at most this many games
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Fit a behaviour-cloning model on an exported dataset
+    TrainClone {
+        /// Directory written by `dataset`
+        #[arg(long, default_value = "data")]
+        data: String,
+        /// Where to save the model
+        #[arg(short, long, default_value = "clone.bin")]
+        out: String,
+        #[arg(short, long, default_value_t = 5)]
+        epochs: usize,
+        #[arg(long, default_value_t = 0.05)]
+        lr: f32,
+        /// Games to play with the model against heuristic agents afterwards
+        #[arg(long, default_value_t = 20)]
+        eval_games: u32,
+    },
+    /// Run generations of league self-play training against frozen checkpoints
+    League(LeagueArgs),
+    /// Measure the value of each decision type by swapping it from a donor agent into a base agent
+    Probe {
+        /// Agent being probed
+        #[arg(long, default_value = "heuristic")]
+        base: String,
+        /// Agent whose decisions are swapped in
+        #[arg(long, default_value = "random")]
+        donor: String,
+        /// Agent in every other seat
+        #[arg(long, default_value = "heuristic")]
+        opponents: String,
+        /// Comma-separated decision types (e.g. "select_house_card,bidding"); all by default
+        #[arg(long)]
+        decisions: Option<String>,
+        /// Paired seeds to play (each seed plays one game per decision type plus a baseline)
+        #[arg(short, long, default_value_t = 100)]
+        seeds: u64,
         #[arg(short, long, default_value_t = 6)]
         players: u8,
+        /// Worker threads for running games (0 = one per core)
+        #[arg(short, long, default_value_t = 0)]
+        threads: usize,
+    },
+    /// Estimate per-house win rates from mirror games and suggest handicaps
+    Balance {
         #[arg(short, long, default_value = "results.db")]
         db: String,
-        /// Agent type: "random", "heuristic", or "mixed" (3 random + 3 heuristic)
+        /// Only games with this many players
+        #[arg(short, long)]
+        players: Option<u8>,
+        /// Only mirror games of this agent spec
+        #[arg(short, long)]
+        agent: Option<String>,
+        /// Write the suggested options to this JSON file (use with --options)
+        #[arg(short, long)]
+        out: Option<String>,
+    },
+    /// Show how many legal answers each kind of decision had in the stored games
+    Branching {
+        #[arg(short, long, default_value = "results.db")]
+        db: String,
+    },
+    /// Measure each house's win-rate sensitivity to changes in its starting resources
+    Experiments {
+        /// Agent in every seat
+        #[arg(short, long, default_value = "heuristic")]
+        agent: String,
+        /// Comma-separated perturbations (e.g. "power+2,supply-1,remove-knight"); a standard set by default
+        #[arg(long)]
+        perturbations: Option<String>,
+        /// Paired seeds to play (each seed plays a baseline plus one game per house and perturbation)
+        #[arg(short, long, default_value_t = 50)]
+        seeds: u64,
+        #[arg(short, long, default_value_t = 6)]
+        players: u8,
+        /// Worker threads for running games (0 = one per core)
+        #[arg(short, long, default_value_t = 0)]
+        threads: usize,
+    },
+    /// Play mirror games and measure how often each area is taken, fought over and held
+    MapBalance {
+        /// Agent in every seat
         #[arg(short, long, default_value = "random")]
         agent: String,
+        #[arg(short, long, default_value_t = 1000)]
+        games: u64,
+        #[arg(short, long, default_value_t = 6)]
+        players: u8,
+        /// Worker threads for running games (0 = one per core)
+        #[arg(short, long, default_value_t = 0)]
+        threads: usize,
+        /// Write the per-area heatmap (MapHeatmap JSON) to this file
+        #[arg(short, long)]
+        out: Option<String>,
     },
-    /// Show leaderboard from database
-    Leaderboard {
+    /// Show a league's members and league Elo
+    LeagueTable {
         #[arg(short, long, default_value = "results.db")]
         db: String,
+        /// League name (lists all leagues when omitted)
+        #[arg(short, long)]
+        name: Option<String>,
     },
+    /// Host a game: remote players join over TCP/WebSocket, AI fills the rest
+    Lobby(LobbyArgs),
+    /// Join a lobby and play the seat at this terminal (or with a local agent)
+    Connect {
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        addr: String,
+        /// House to claim (any open seat when omitted)
+        #[arg(long)]
+        house: Option<String>,
+        /// Name shown to the other players
+        #[arg(long)]
+        name: Option<String>,
+        /// Local agent answering the server's decisions
+        #[arg(short, long, default_value = "human")]
+        agent: String,
+    },
+    /// Print the JSON Schemas of the wire types (or write them to a directory)
+    Schema {
+        /// Write <Type>.schema.json files and openapi.json here instead of printing
+        #[arg(long)]
+        out: Option<String>,
+        /// Print the OpenAPI description of the HTTP API instead
+        #[arg(long)]
+        openapi: bool,
+    },
+    /// Serve the HTTP API for creating and playing games
+    Serve {
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: String,
+        /// Worker threads handling requests
+        #[arg(short, long, default_value_t = 4)]
+        threads: usize,
+    },
+}
+
+#[derive(Args)]
+struct LobbyArgs {
+    #[arg(long, default_value = "0.0.0.0:7878")]
+    addr: String,
+    #[arg(short, long, default_value_t = 42)]
+    seed: u64,
+    #[arg(short, long, default_value_t = 6)]
+    players: u8,
+    /// Comma-separated houses to play (overrides --players)
+    #[arg(long)]
+    houses: Option<String>,
+    /// Shuffle which seat plays which house
+    #[arg(long)]
+    random_seats: bool,
+    /// Game options JSON file (e.g. handicaps written by `balance`)
+    #[arg(long)]
+    options: Option<String>,
+    /// Seats held for remote players
+    #[arg(short, long, default_value_t = 1)]
+    remote: usize,
+    /// Agents for the seats nobody joins, '/'-separated in seat order
+    #[arg(short, long, default_value = "heuristic")]
+    agent: String,
+    /// Start after this many seconds with whoever has joined (default: wait for all)
+    #[arg(long)]
+    wait: Option<u64>,
+}
+
+#[derive(Args)]
+struct LeagueArgs {
+    #[arg(short, long, default_value = "main")]
+    name: String,
+    #[arg(short, long, default_value = "results.db")]
+    db: String,
+    /// Directory for learner checkpoints
+    #[arg(long, default_value = "league")]
+    dir: String,
+    /// Generations to run
+    #[arg(long, default_value_t = 5)]
+    generations: u32,
+    /// Games per generation
+    #[arg(short, long, default_value_t = 40)]
+    games: u32,
+    #[arg(short, long, default_value_t = 6)]
+    players: u8,
+    /// Freeze the best recent learner into the pool every N generations
+    #[arg(long, default_value_t = 2)]
+    freeze_every: u32,
+    /// Most frozen checkpoints kept as opponents
+    #[arg(long, default_value_t = 8)]
+    pool_size: usize,
+    /// Baseline opponents for a new league, '/'-separated specs
+    #[arg(long, default_value = "heuristic/random")]
+    baselines: String,
+    #[arg(long, default_value_t = 2)]
+    epochs: usize,
+    #[arg(long, default_value_t = 0.02)]
+    lr: f32,
+    /// Worker threads for running games (0 = one per core)
+    #[arg(short, long, default_value_t = 0)]
+    threads: usize,
+}
+
+#[derive(Args)]
+struct MatchArgs {
+    /// Matches to play
+    #[arg(short, long, default_value_t = 10)]
+    matches: u32,
+    /// Games per match (0 = one per house, so every seat plays every house)
+    #[arg(short, long, default_value_t = 0)]
+    games: usize,
+    #[arg(short, long, default_value_t = 6)]
+    players: u8,
+    #[arg(short, long, default_value = "results.db")]
+    db: String,
+    /// Agent lineup: one spec, '/'-separated specs per seat, or "mixed" (heuristic/random alternating)
+    #[arg(short, long, default_value = "mixed")]
+    agent: String,
+    /// Comma-separated houses to play (overrides --players)
+    #[arg(long)]
+    houses: Option<String>,
+    /// Game options JSON file (e.g. handicaps written by `balance`)
+    #[arg(long)]
+    options: Option<String>,
+    /// Worker threads for running matches (0 = one per core)
+    #[arg(short, long, default_value_t = 1)]
+    threads: usize,
+}
+
+#[derive(Args)]
+struct TournamentArgs {
+    #[arg(short, long, default_value_t = 100)]
+    games: u32,
+    #[arg(short, long, default_value_t = 6)]
+    players: u8,
+    #[arg(short, long, default_value = "results.db")]
+    db: String,
+    /// Agent lineup: one spec, '/'-separated specs per seat, or "mixed" (heuristic/random alternating)
+    #[arg(short, long, default_value = "random")]
+    agent: String,
+    /// Comma-separated houses to play (overrides --players)
+    #[arg(long)]
+    houses: Option<String>,
+    /// Shuffle which seat plays which house each game
+    #[arg(long)]
+    random_seats: bool,
+    /// Game options JSON file (e.g. handicaps written by `balance`)
+    #[arg(long)]
+    options: Option<String>,
+    /// Thinking time per house in seconds, as budget+increment (e.g. "60+1"); see --policy for running out
+    #[arg(long)]
+    time_control: Option<TimeControl>,
+    /// Bid 0 for houses without power instead of asking their agents
+    #[arg(long)]
+    auto_zero_bids: bool,
+    /// Apply decisions with a single legal answer without asking the agents
+    #[arg(long)]
+    auto_forced: bool,
+    /// Re-run and store games already in the database (same seed, lineup and options)
+    #[arg(long)]
+    allow_duplicates: bool,
+    /// Store the candidates and scores agents report for their decisions (decision_info table)
+    #[arg(long)]
+    decision_info: bool,
+    /// Illegal actions and timeouts: "lenient" plays random legal answers instead, "strict" forfeits the game for that house
+    #[arg(long, default_value = "lenient")]
+    policy: ActionPolicy,
+    /// Worker threads for running games (0 = one per core)
+    #[arg(short, long, default_value_t = 1)]
+    threads: usize,
+    /// Keep playing until SIGTERM/SIGINT instead of stopping after --games
+    #[arg(long)]
+    daemon: bool,
+    /// Daemon: stop scheduling a pairing (agents on houses, options) once it has this many stored games (0 = no limit)
+    #[arg(long, default_value_t = 0, requires = "daemon")]
+    per_pairing: u32,
+    /// Daemon: games per batch; a stop request takes effect after the batch under way
+    #[arg(long, default_value_t = 32, requires = "daemon")]
+    batch: usize,
+    /// Daemon: rebuild ratings and write a summary snapshot every N games
+    #[arg(long, default_value_t = 500, requires = "daemon")]
+    snapshot_every: u32,
+    /// Daemon: summary snapshot file (default: <db>.summary.json)
+    #[arg(long, requires = "daemon")]
+    snapshot: Option<String>,
+    /// Daemon: serve Prometheus metrics at http://ADDR/metrics (e.g. 127.0.0.1:9100)
+    #[arg(long, requires = "daemon")]
+    metrics_addr: Option<String>,
+    /// Shell command run on milestones (finished, elo_crossed, engine_error) with the event as JSON on stdin; repeatable
+    #[arg(long)]
+    notify_cmd: Vec<String>,
+    /// URL the milestone JSON is POSTed to; repeatable
+    #[arg(long)]
+    notify_url: Vec<String>,
+    /// Comma-separated Elo thresholds; crossing one in either direction is a milestone
+    #[arg(long, value_delimiter = ',')]
+    notify_elo: Vec<f64>,
+}
+
+impl TournamentArgs {
+    fn notifier(&self) -> Notifier {
+        let hooks = self.notify_cmd.iter().cloned().map(Hook::Command)
+            .chain(self.notify_url.iter().cloned().map(Hook::Webhook))
+            .collect();
+        Notifier::new(hooks, self.notify_elo.clone())
+    }
 }
 
 fn main() {
     let cli = Cli::parse();
+    init_logging(cli.log_format);
 
     match cli.command {
-        Commands::Play { seed, players, agent } => cmd_play(seed, players, &agent),
-        Commands::Tournament { games, players, db, agent } => cmd_tournament(games, players, &db, &agent),
-        Commands::Leaderboard { db } => cmd_leaderboard(&db),
+        Commands::Play { seed, players, agent, houses, random_seats, options, humans, record, dump_decisions } => {
+            cmd_play(seed, players, &agent, houses.as_deref(), random_seats, options.as_deref(), humans.as_deref(), record.as_deref(), dump_decisions.as_deref())
+        }
+        Commands::Decide { view, agent, seed, n } => cmd_decide(&view, &agent, seed, n),
+        Commands::VerifyAgent { spec, positions, n, seed, min_score } => cmd_verify_agent(&spec, &positions, n, seed, min_score),
+        Commands::Snapshots { file, bless } => cmd_snapshots(&file, bless),
+        Commands::Tournament(args) => cmd_tournament(&args),
+        Commands::Match(args) => cmd_match(&args),
+        Commands::BenchDb { games, batch } => cmd_bench_db(games, batch),
+        Commands::Leaderboard { db, system, by, house, min_games, agent_prefix, pivot } => {
+            cmd_leaderboard(&db, system.as_deref(), &by, house.as_deref(), min_games, agent_prefix, pivot)
+        }
+        Commands::RecomputeRatings { db, system } => cmd_recompute_ratings(&db, &system),
+        Commands::Repro { db, game, dump_decisions } => cmd_repro(&db, game, dump_decisions.as_deref()),
+        Commands::Replay { file, all } => cmd_replay(&file, all),
+        Commands::Annotate { file, action, event, author, text, data } => {
+            cmd_annotate(&file, action, event, &author, text, &data)
+        }
+        Commands::Analyze { replay, reference, playouts, rollout, alternatives, threshold, house, out, threads } => {
+            cmd_analyze(&replay, &reference, playouts, &rollout, alternatives, threshold, house.as_deref(), out.as_deref(), threads)
+        }
+        Commands::DiffEngine { reference, games, players, seed, max_steps } => cmd_diff_engine(&reference, games, players, seed, max_steps),
+        Commands::BenchThreats { agent, games, players, every, repeat } => cmd_bench_threats(&agent, games, players, every, repeat),
+        Commands::BenchRollouts { agent, games, players, every, playouts, limits, threads } => {
+            cmd_bench_rollouts(&agent, games, players, every, playouts, &limits, threads)
+        }
+        Commands::EvalCalibrate { agent, games, players, every, fit, val_fraction, epochs, lr, threads } => {
+            cmd_eval_calibrate(&agent, games, players, every, fit, val_fraction, epochs, lr, threads)
+        }
+        Commands::Dataset { db, out, format, val_fraction, agent, limit } => {
+            cmd_dataset(&db, &out, &format, val_fraction, agent, limit)
+        }
+        Commands::TrainClone { data, out, epochs, lr, eval_games } => {
+            cmd_train_clone(&data, &out, epochs, lr, eval_games)
+        }
+        Commands::League(args) => cmd_league(&args),
+        Commands::Balance { db, players, agent, out } => cmd_balance(&db, players, agent, out.as_deref()),
+        Commands::Branching { db } => cmd_branching(&db),
+        Commands::Experiments { agent, perturbations, seeds, players, threads } => {
+            cmd_experiments(&agent, perturbations.as_deref(), seeds, players, threads)
+        }
+        Commands::MapBalance { agent, games, players, threads, out } => cmd_map_balance(&agent, games, players, threads, out.as_deref()),
+        Commands::Probe { base, donor, opponents, decisions, seeds, players, threads } => {
+            cmd_probe(&base, &donor, &opponents, decisions.as_deref(), seeds, players, threads)
+        }
+        Commands::LeagueTable { db, name } => cmd_league_table(&db, name.as_deref()),
+        Commands::Lobby(args) => cmd_lobby(&args),
+        Commands::Connect { addr, house, name, agent } => cmd_connect(&addr, house.as_deref(), name.as_deref(), &agent),
+        Commands::Schema { out, openapi } => cmd_schema(out.as_deref(), openapi),
+        Commands::Serve { addr, threads } => {
+            println!("=== HTTP API on http://{} ===", addr);
+            if let Err(e) = api::serve(&addr, threads) {
+                eprintln!("Server error: {}", e);
+            }
+        }
     }
 }
 
-fn cmd_play(seed: u64, player_count: u8, agent_type: &str) {
+#[allow(clippy::too_many_arguments)]
+fn cmd_play(
+    seed: u64,
+    player_count: u8,
+    agent_type: &str,
+    houses: Option<&str>,
+    random_seats: bool,
+    options: Option<&str>,
+    humans: Option<&str>,
+    record: Option<&str>,
+    dump_decisions: Option<&str>,
+) {
     println!("=== Game of Thrones Strategy Lab ===\n");
-    println!("Running single game: seed={}, players={}, agent={}\n", seed, player_count, agent_type);
+    let config = match load_options(options).and_then(|o| setup_config(seed, player_count, houses, random_seats, &o)) {
+        Ok(c) => c,
+        Err(e) => return eprintln!("Setup error: {}", e),
+    };
+    println!("Running single game: seed={}, players={}, agent={}\n", seed, config.player_count(), agent_type);
 
-    let mut agents = make_agents(seed, player_count, agent_type);
-    match run_game(&mut agents, seed, player_count, 50_000) {
+    let lineup = match parse_lineup(agent_type, config.houses.len()) {
+        Ok(l) => l,
+        Err(e) => return eprintln!("Agent error: {}", e),
+    };
+    let mut seats = assign_seats(&config, &lineup);
+    if let Some(list) = humans {
+        for house in list.split(',') {
+            let house: HouseName = match house.parse() {
+                Ok(h) => h,
+                Err(e) => return eprintln!("Agent error: {}", e),
+            };
+            match seats.iter_mut().find(|s| s.house == house) {
+                Some(seat) => seat.spec = AgentSpec::new("human"),
+                None => return eprintln!("Agent error: {} is not playing in this game", house),
+            }
+        }
+    }
+    if seats.iter().any(|s| s.spec.kind == "human") {
+        for s in &seats {
+            println!("  Seat {}: {:10} {}", s.seat + 1, s.house.to_string(), s.spec);
+        }
+        println!();
+    }
+    let players = seats.iter().map(|s| ReplayPlayer { house: s.house, agent: s.spec.to_string() }).collect();
+    let mut replay = Replay::new(config.clone(), players);
+    let mut dumps = match open_dumps(dump_decisions, &seats, config.seed) {
+        Ok(d) => d,
+        Err(e) => return eprintln!("Dump error: {}", e),
+    };
+    let outcome = run_seated_game_observed(&config, &seats, 50_000, |house, view, action| {
+        if record.is_some() {
+            replay.record(view.round, house, action.clone());
+        }
+        if let Some(dumps) = dumps.as_mut() {
+            dumps.record(house, view, action);
+        }
+    });
+    if let Some(dumps) = dumps {
+        dumps.report();
+    }
+    match outcome {
         Ok(result) => {
+            if let Some(path) = record {
+                replay.finish(&result.events, result.winner, result.final_ranking.clone());
+                for r in &result.decision_info {
+                    let agent = seats.iter().find(|s| s.house == r.house).map_or(String::new(), |s| s.spec.to_string());
+                    replay.annotate(r.info.annotation(AnnotationTarget::Action { index: r.decision }, &agent));
+                }
+                match replay.save(Path::new(path)) {
+                    Ok(()) => println!("Replay saved to {} ({} actions)\n", path, replay.actions.len()),
+                    Err(e) => eprintln!("Replay error: {}", e),
+                }
+            }
             println!("Game finished!");
-            println!("  Winner: {}", result.winner);
+            if result.drawn {
+                println!("  Drawn by agreement; leader on points: {}", result.winner);
+            } else {
+                println!("  Winner: {}", result.winner);
+            }
+            if !result.resigned.is_empty() {
+                let resigned: Vec<String> = result.resigned.iter().map(|h| h.to_string()).collect();
+                println!("  Resigned: {}", resigned.join(", "));
+            }
             println!("  Rounds played: {}", result.rounds_played);
             println!();
             println!("  Final standings:");
+            // A game decided on points ends with the round counter past round 10
+            let rounds = result.rounds_played.min(10);
             for pr in &result.player_results {
-                println!("    {:10} -- castles: {}, supply: {}, power: {}, IT:{} F:{} KC:{}",
-                    pr.house.to_string(),
+                println!("    {:10} [{}] -- castles: {}, supply: {}, power: {}, IT:{} F:{} KC:{}",
+                    pr.house.to_string(), pr.agent_name,
                     pr.final_castles, pr.final_supply, pr.final_power,
                     pr.final_iron_throne, pr.final_fiefdoms, pr.final_kings_court,
                 );
+                let st = &pr.stats;
+                println!("    {:10}    battles: {}W/{}L, power: +{}/-{}, captured: {}, wildling bids: {}, cards played: {}",
+                    "", st.battles_won, st.battles_lost, st.power_gained, st.power_spent,
+                    st.areas_captured, st.wildling_bid, st.cards_played,
+                );
+                let castles = castle_trajectory(&result.events, pr.house, pr.final_castles, rounds);
+                let castles: Vec<String> = castles.iter().map(|c| c.to_string()).collect();
+                println!("    {:10}    castles by round: {}", "", castles.join(" "));
+                if let Some(battle) = biggest_battle(&result.events, pr.house) {
+                    println!("    {:10}    biggest battle: {}", "", describe_battle(battle, pr.house));
+                }
             }
         }
         Err(e) => eprintln!("Game error: {}", e),
     }
 }
 
-fn cmd_tournament(num_games: u32, player_count: u8, db_path: &str, agent_type: &str) {
+/// One line on a battle, from the side of `house`.
+fn describe_battle(event: &GameEvent, house: HouseName) -> String {
+    let GameEventKind::CombatResolved { area_id, attacker, defender, winner, attacker_strength, defender_strength, .. } = event.kind else {
+        return String::new();
+    };
+    let (opponent, ours, theirs, role) = if attacker == house {
+        (defender, attacker_strength, defender_strength, "attacking")
+    } else {
+        (attacker, defender_strength, attacker_strength, "defending")
+    };
+    format!("round {}, {} {} against {}, {} to {}, {}",
+        event.round, role, area_name(area_id), opponent, ours, theirs,
+        if winner == house { "won" } else { "lost" })
+}
+
+fn cmd_tournament(args: &TournamentArgs) {
+    let (num_games, db_path, agent_type) = (args.games, args.db.as_str(), args.agent.as_str());
+    let (houses, random_seats) = (args.houses.as_deref(), args.random_seats);
+
+    // Validate the house selection and options once up front
+    let mut options = match load_options(args.options.as_deref()) {
+        Ok(o) => o,
+        Err(e) => return eprintln!("Setup error: {}", e),
+    };
+    if args.time_control.is_some() {
+        options.time_control = args.time_control;
+    }
+    options.auto_zero_bids |= args.auto_zero_bids;
+    options.auto_forced |= args.auto_forced;
+    let player_count = match setup_config(0, args.players, houses, random_seats, &options) {
+        Ok(c) => c.player_count(),
+        Err(e) => return eprintln!("Setup error: {}", e),
+    };
+    let lineup = match parse_lineup(agent_type, player_count as usize) {
+        Ok(l) => l,
+        Err(e) => return eprintln!("Agent error: {}", e),
+    };
+    if let Some(e) = lineup.iter().find_map(|spec| spec.build(HouseName::Stark, 0).err()) {
+        return eprintln!("Agent error: {}", e);
+    }
+    if args.daemon {
+        let setup = setup_config(0, player_count, houses, random_seats, &options).expect("validated above");
+        return cmd_tournament_daemon(args, setup, lineup);
+    }
     println!("=== Tournament: {} games, {} players, agent={} ===\n", num_games, player_count, agent_type);
 
     let db = Database::new(db_path);
+    let notifier = args.notifier();
 
-    // Register one agent per house (all random for now)
-    let agent_name = "RandomAgent";
-    let agent_id = db.register_agent(agent_name);
-
-    let mut wins: HashMap<HouseName, u32> = HashMap::new();
-    let mut errors = 0u32;
+    // Register every distinct agent in the lineup under its canonical spec
+    let mut agent_ids: Vec<(String, i64)> = Vec::new();
+    for spec in &lineup {
+        let name = spec.to_string();
+        if !agent_ids.iter().any(|(n, _)| *n == name) {
+            let id = db.register_agent(&name);
+            agent_ids.push((name, id));
+        }
+    }
+    if notifier.has_thresholds() {
+        // Where everyone stands before the first game
+        db.recompute_ratings(RatingSystem::Elo);
+        notifier.check_elo(&db.filtered_leaderboard(&LeaderboardFilter::default()));
+    }
 
-    for g in 0..num_games {
-        let seed = 42u64 + g as u64 * 1000;
-        let mut agents = make_agents(seed, player_count, agent_type);
-        match run_game(&mut agents, seed, player_count, 50_000) {
-            Ok(result) => {
-                *wins.entry(result.winner).or_insert(0) += 1;
+    // Build the schedule, dropping games already stored
+    let schedule = (0..num_games)
+        .map(|g| {
+            let seed = 42u64 + g as u64 * 1000;
+            let config = setup_config(seed, player_count, houses, random_seats, &options).expect("validated above");
+            let seats = assign_seats(&config, &lineup);
+            (config, seats)
+        })
+        .collect();
+    let (games, skipped) = db.skip_stored_games(schedule, args.allow_duplicates);
 
-                // Store result
-                let agent_ids: Vec<(String, i64)> = result.player_results.iter()
-                    .map(|pr| (pr.house.to_string(), agent_id))
-                    .collect();
-                db.store_game(&result, &agent_ids);
+    let wins: Mutex<HashMap<HouseName, u32>> = Mutex::new(HashMap::new());
+    let errors = AtomicU32::new(0);
+    let faults = AtomicU32::new(0);
+    let forfeits = AtomicU32::new(0);
+    let done = AtomicU32::new(0);
+    let writer = Mutex::new(DbWriter::spawn(db_path, agent_ids, 64));
+    let scheduled = games.len() as u32;
 
-                if (g + 1) % 10 == 0 || g + 1 == num_games {
-                    print!("\rGame {}/{}...", g + 1, num_games);
+    run_games_parallel(&games, args.threads, 50_000, args.policy, |i, result| {
+        match result {
+            Ok(mut result) => {
+                *wins.lock().unwrap().entry(result.winner).or_insert(0) += 1;
+                for fault in &result.agent_faults {
+                    faults.fetch_add(1, Ordering::Relaxed);
+                    let outcome = match (fault.kind, result.forfeit) {
+                        (FaultKind::Panic, _) => "panicked; a random agent takes over",
+                        (FaultKind::IllegalAction, Some(_)) => "played an illegal action and forfeits",
+                        (FaultKind::IllegalAction, None) => "played an illegal action; a random one is played instead",
+                        (FaultKind::Timeout, Some(_)) => "ran out of time and forfeits",
+                        (FaultKind::Timeout, None) => "ran out of time; random legal moves from here on",
+                    };
+                    tracing::warn!(game = i + 1, agent = %fault.agent, house = %fault.house, decision = fault.decision,
+                        "{} -- {}", outcome, fault.message);
                 }
+                if result.forfeit.is_some() {
+                    forfeits.fetch_add(1, Ordering::Relaxed);
+                }
+                if !args.decision_info {
+                    result.decision_info.clear();
+                }
+                writer.lock().unwrap().send(result);
             }
             Err(e) => {
-                errors += 1;
-                eprintln!("Game {}: ERROR -- {}", g + 1, e);
+                errors.fetch_add(1, Ordering::Relaxed);
+                tracing::error!(game = i + 1, seed = games[i].0.seed, "{}", e);
+                notifier.fire(&Milestone::EngineError { seed: games[i].0.seed, message: e });
             }
         }
-    }
+        let n = done.fetch_add(1, Ordering::Relaxed) + 1;
+        if n.is_multiple_of(10) || n == scheduled {
+            print!("\rGame {}/{}...", n, scheduled);
+        }
+    });
+    writer.into_inner().unwrap().finish();
+    let wins = wins.into_inner().unwrap();
+    let errors = errors.into_inner();
 
     println!("\n\n--- Summary ({} games, {} errors) ---", num_games, errors);
+    if skipped > 0 {
+        println!("  Skipped {} games already in the database (use --allow-duplicates to re-run them)", skipped);
+    }
+    let (faults, forfeits) = (faults.into_inner(), forfeits.into_inner());
+    if faults > 0 {
+        println!("  {} agent panics and illegal actions, {} games forfeited (agent_faults table)", faults, forfeits);
+    }
     for &house in &HouseName::ALL {
         let w = wins.get(&house).copied().unwrap_or(0);
-        let pct = if num_games > 0 { w as f64 / num_games as f64 * 100.0 } else { 0.0 };
+        let played = num_games - skipped;
+        let pct = if played > 0 { w as f64 / played as f64 * 100.0 } else { 0.0 };
         println!("  {:10}: {:>4} wins ({:.1}%)", house.to_string(), w, pct);
     }
     println!("\nResults saved to: {}", db_path);
     println!("Total games in DB: {}", db.game_count());
+    if notifier.has_thresholds() {
+        db.recompute_ratings(RatingSystem::Elo);
+        notifier.check_elo(&db.filtered_leaderboard(&LeaderboardFilter::default()));
+    }
+    notifier.fire(&Milestone::Finished { games: num_games - skipped, errors, db: db_path.to_string() });
+}
+
+fn cmd_match(args: &MatchArgs) {
+    let options = match load_options(args.options.as_deref()) {
+        Ok(o) => o,
+        Err(e) => return eprintln!("Setup error: {}", e),
+    };
+    let setup = match setup_config(0, args.players, args.houses.as_deref(), false, &options) {
+        Ok(c) => c,
+        Err(e) => return eprintln!("Setup error: {}", e),
+    };
+    let lineup = match parse_lineup(&args.agent, setup.houses.len()) {
+        Ok(l) => l,
+        Err(e) => return eprintln!("Agent error: {}", e),
+    };
+    if let Some(e) = lineup.iter().find_map(|spec| spec.build(HouseName::Stark, 0).err()) {
+        return eprintln!("Agent error: {}", e);
+    }
+    let games = if args.games == 0 { setup.houses.len() } else { args.games };
+    println!("=== {} matches of {} games, {} players, agent={} ===\n", args.matches, games, setup.houses.len(), args.agent);
+
+    let db = Database::new(&args.db);
+    let mut agent_ids: Vec<(String, i64)> = Vec::new();
+    for spec in &lineup {
+        let name = spec.to_string();
+        if !agent_ids.iter().any(|(n, _)| *n == name) {
+            let id = db.register_agent(&name);
+            agent_ids.push((name, id));
+        }
+    }
+    let matches: Vec<MatchOptions> = (0..args.matches)
+        .map(|m| MatchOptions {
+            lineup: lineup.clone(),
+            setup: SetupConfig { seed: 42 + m as u64 * 1000, ..setup.clone() },
+            games,
+        })
+        .collect();
+
+    let finished = Mutex::new(Vec::new());
+    let errors = AtomicU32::new(0);
+    run_matches_parallel(&matches, args.threads, 50_000, |i, result| match result {
+        Ok(mut result) => {
+            for game in &mut result.games {
+                game.decision_info.clear();
+            }
+            let line: Vec<String> = result.standings.iter()
+                .map(|s| format!("seat {} {} {}w/{}p", s.seat + 1, s.spec, s.wins, s.points))
+                .collect();
+            let winners: Vec<String> = result.winners().iter().map(|s| format!("seat {}", s + 1)).collect();
+            println!("Match {:>3} (seed {}): {} -- won by {}", i + 1, result.seed, line.join(", "), winners.join(" and "));
+            finished.lock().unwrap().push((i, result));
+        }
+        Err(e) => {
+            errors.fetch_add(1, Ordering::Relaxed);
+            tracing::error!(r#match = i + 1, "{}", e);
+        }
+    });
+    // Stored in schedule order, so match ratings don't depend on threading
+    let mut finished = finished.into_inner().unwrap();
+    finished.sort_by_key(|(i, _)| *i);
+    for (_, result) in &finished {
+        db.store_match(result, &agent_ids);
+    }
+
+    println!("\n--- Match leaderboard ({} errors) ---", errors.into_inner());
+    println!("  {:30} {:>7} {:>8} {:>6}", "Agent", "Elo", "Matches", "Won");
+    for (name, rating, played, wins) in db.match_leaderboard(RatingSystem::Elo) {
+        println!("  {:30} {:>7.0} {:>8} {:>6}", name, rating.mu, played, wins);
+    }
+    println!("\nResults saved to: {}", args.db);
+}
+
+/// Play tournament batches until a stop signal (or every pairing's target),
+/// snapshotting ratings and a summary along the way.
+fn cmd_tournament_daemon(args: &TournamentArgs, setup: SetupConfig, lineup: Vec<AgentSpec>) {
+    let stop = Arc::new(AtomicBool::new(false));
+    for signal in [SIGTERM, SIGINT] {
+        // A second signal while winding down exits at once
+        let registered = signal_hook::flag::register_conditional_shutdown(signal, 1, stop.clone())
+            .and_then(|_| signal_hook::flag::register(signal, stop.clone()));
+        if let Err(e) = registered {
+            return eprintln!("Cannot install signal handler: {}", e);
+        }
+    }
+    let snapshot = args.snapshot.clone().unwrap_or_else(|| format!("{}.summary.json", args.db));
+    let metrics = match &args.metrics_addr {
+        Some(addr) => {
+            let metrics = Arc::new(Metrics::new());
+            if let Err(e) = got_server::metrics::spawn(addr, metrics.clone()) {
+                return eprintln!("Cannot serve metrics: {}", e);
+            }
+            println!("Metrics at http://{}/metrics", addr);
+            Some(metrics)
+        }
+        None => None,
+    };
+    let opts = DaemonOptions {
+        lineup,
+        setup,
+        per_pairing: args.per_pairing,
+        batch: args.batch,
+        snapshot_every: args.snapshot_every,
+        snapshot_path: snapshot.clone().into(),
+        threads: args.threads,
+        decision_info: args.decision_info,
+        policy: args.policy,
+        metrics,
+    };
+    let target = match args.per_pairing {
+        0 => "no target".to_string(),
+        n => format!("{} games per pairing", n),
+    };
+    println!("=== Tournament daemon: agent={}, {}, snapshots to {} ===", args.agent, target, snapshot);
+    println!("Stop with SIGTERM or Ctrl-C; the batch under way is finished first.\n");
+
+    let start = Instant::now();
+    let notifier = args.notifier();
+    let outcome = daemon::run(&args.db, &opts, &stop, &notifier, |stats| {
+        let rate = stats.games as f64 / start.elapsed().as_secs_f64().max(1e-9);
+        println!("{} games ({} errors, {} agent faults, {} forfeits), {:.2} games/s, {} snapshots",
+            stats.games, stats.errors, stats.faults, stats.forfeits, rate, stats.snapshots);
+    });
+    match outcome {
+        Ok((exit, stats)) => {
+            match exit {
+                DaemonExit::Stopped => println!("\nStopped after {} games.", stats.games),
+                DaemonExit::TargetReached => println!("\nEvery pairing has {} games; nothing left to play.", args.per_pairing),
+            }
+            println!("Results saved to: {}; summary in {}", args.db, snapshot);
+            notifier.fire(&Milestone::Finished { games: stats.games, errors: stats.errors, db: args.db.clone() });
+        }
+        Err(e) => eprintln!("Daemon error: {}", e),
+    }
 }
 
-fn cmd_leaderboard(db_path: &str) {
+/// Store one real game result many times, per-game and through the batching
+/// writer, and report games stored per second for each.
+fn cmd_bench_db(num_games: u32, batch: usize) {
+    let config = SetupConfig::new(6, 42);
+    let lineup = parse_lineup("random", 6).expect("builtin spec");
+    let result = match run_seated_game(&config, &assign_seats(&config, &lineup), 50_000) {
+        Ok(r) => r,
+        Err(e) => return eprintln!("Game error: {}", e),
+    };
+    println!("=== DB benchmark: {} games, {} events per game ===\n", num_games, result.events.len());
+
+    let dir = std::env::temp_dir();
+    let per_game_path = dir.join(format!("got-bench-{}-single.db", std::process::id()));
+    let batched_path = dir.join(format!("got-bench-{}-batched.db", std::process::id()));
+
+    let db = Database::new(per_game_path.to_str().unwrap());
+    let agent_ids = vec![("random".to_string(), db.register_agent("random"))];
+    let start = Instant::now();
+    for _ in 0..num_games {
+        db.store_game(&result, &agent_ids);
+    }
+    let single = num_games as f64 / start.elapsed().as_secs_f64();
+    println!("  one transaction per game: {:>10.0} games/s", single);
+
+    let db = Database::new(batched_path.to_str().unwrap());
+    let agent_ids = vec![("random".to_string(), db.register_agent("random"))];
+    let start = Instant::now();
+    let writer = DbWriter::spawn(batched_path.to_str().unwrap(), agent_ids, batch);
+    for _ in 0..num_games {
+        writer.send(result.clone());
+    }
+    writer.finish();
+    let batched = num_games as f64 / start.elapsed().as_secs_f64();
+    println!("  writer thread, batch {:>4}: {:>10.0} games/s", batch, batched);
+
+    for path in [per_game_path, batched_path] {
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+}
+
+fn cmd_repro(db_path: &str, game_id: i64, dump_decisions: Option<&str>) {
     let db = Database::new(db_path);
-    let board = db.leaderboard();
-    if board.is_empty() {
-        println!("No agents found. Run some tournaments first.");
+    let (config, seats, stored_winner) = match db.load_game_setup(game_id) {
+        Ok(s) => s,
+        Err(e) => return eprintln!("Repro error: {}", e),
+    };
+    println!("=== Repro game {}: seed={}, houses={:?}, random seats={} ===", game_id, config.seed, config.houses, config.randomize_seats);
+    for s in &seats {
+        println!("  seat {}: {:10} {} (seed {})", s.seat, s.house.to_string(), s.spec, s.seed);
+    }
+    let mut dumps = match open_dumps(dump_decisions, &seats, config.seed) {
+        Ok(d) => d,
+        Err(e) => return eprintln!("Dump error: {}", e),
+    };
+    let outcome = run_seated_game_observed(&config, &seats, 50_000, |house, view, action| {
+        if let Some(dumps) = dumps.as_mut() {
+            dumps.record(house, view, action);
+        }
+    });
+    if let Some(dumps) = dumps {
+        dumps.report();
+    }
+    match outcome {
+        Ok(result) if result.winner.to_string() == stored_winner => {
+            println!("\nOK: winner {} in round {} matches the database", result.winner, result.rounds_played);
+        }
+        Ok(result) => {
+            eprintln!("\nMISMATCH: replay winner {} (round {}), database has {}", result.winner, result.rounds_played, stored_winner);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("\nReplay error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Decision contexts being saved for `play --dump-decisions` and
+/// `repro --dump-decisions`. The first write error stops the saving.
+struct Dumps {
+    writer: ContextWriter,
+    agents: HashMap<HouseName, String>,
+    error: Option<String>,
+}
+
+impl Dumps {
+    fn record(&mut self, house: HouseName, view: &got_engine::visibility::PlayerView, action: &got_engine::engine::Action) {
+        if self.error.is_none() {
+            let agent = self.agents.get(&house).map_or("", |a| a.as_str());
+            if let Err(e) = self.writer.record(house, agent, view, action) {
+                self.error = Some(e);
+            }
+        }
+    }
+
+    fn report(self) {
+        match self.error {
+            Some(e) => eprintln!("Dump error after {} decisions: {}", self.writer.len(), e),
+            None => println!("Saved {} decision contexts to {}\n", self.writer.len(), self.writer.dir().display()),
+        }
+    }
+}
+
+fn open_dumps(dir: Option<&str>, seats: &[SeatAgent], seed: u64) -> Result<Option<Dumps>, String> {
+    let Some(dir) = dir else { return Ok(None) };
+    Ok(Some(Dumps {
+        writer: ContextWriter::new(Path::new(dir), seed)?,
+        agents: seats.iter().map(|s| (s.house, s.spec.to_string())).collect(),
+        error: None,
+    }))
+}
+
+fn cmd_decide(path: &str, agent: &str, seed: u64, n: usize) {
+    let (view, context) = match contexts::load(Path::new(path)) {
+        Ok(v) => v,
+        Err(e) => return eprintln!("Decide error: {}", e),
+    };
+    let spec: AgentSpec = match agent.parse() {
+        Ok(s) => s,
+        Err(e) => return eprintln!("Agent error: {}", e),
+    };
+    match &context {
+        Some(c) => println!("=== Decision {} of game {}: {} ({}), round {} ===", c.decision, c.seed, c.house, c.agent, view.round),
+        None => println!("=== {}, round {} ===", view.viewer, view.round),
+    }
+    if let Some(pending) = &view.pending {
+        println!("  pending: {}", DecisionKind::of(pending).key());
+    }
+    let json = |a: &got_engine::engine::Action| serde_json::to_string(a).unwrap();
+    let recorded = context.as_ref().map(|c| json(&c.action));
+
+    if n > 1 {
+        let counts = match contexts::answers(&view, &spec, seed, n) {
+            Ok(c) => c,
+            Err(e) => return eprintln!("Decide error: {}", e),
+        };
+        println!("  {} over {} seeds ({}..{}): {} distinct answers", spec, n, seed, seed + n as u64 - 1, counts.len());
+        for c in counts.iter().take(20) {
+            let answer = json(&c.action);
+            let mark = if recorded.as_ref() == Some(&answer) { "  (recorded)" } else { "" };
+            println!("    {:>5} {:>5.1}%  {}{}", c.count, 100.0 * c.count as f64 / n as f64, answer, mark);
+        }
+        if counts.len() > 20 {
+            println!("    ... {} more answers", counts.len() - 20);
+        }
+        if let Some(r) = recorded.filter(|r| !counts.iter().any(|c| json(&c.action) == *r)) {
+            println!("  recorded answer, never given: {}", r);
+        }
         return;
     }
-    println!("=== Leaderboard ===\n");
-    println!("{:<20} {:>8} {:>8} {:>8}", "Agent", "ELO", "Games", "Wins");
-    println!("{}", "-".repeat(48));
-    for (name, elo, games, wins_count) in &board {
-        println!("{:<20} {:>8.1} {:>8} {:>8}", name, elo, games, wins_count);
+
+    if let Err(e) = contexts::check_decision(&view) {
+        return eprintln!("Decide error: {}", e);
+    }
+    let mut agent = match spec.build(view.viewer, seed) {
+        Ok(a) => a,
+        Err(e) => return eprintln!("Agent error: {}", e),
+    };
+    let action = agent.decide(&view);
+    println!("  {} answers: {}", spec, json(&action));
+    if let Some(info) = agent.last_decision_info() {
+        for c in info.candidates.iter().take(5) {
+            println!("    {:>8.3}  {}", c.score, json(&c.action));
+        }
+    }
+    if let Some(r) = recorded {
+        let same = if r == json(&action) { "same" } else { "different" };
+        println!("  recorded answer: {} ({})", r, same);
+    }
+}
+
+fn cmd_verify_agent(spec: &str, dir: &str, n: usize, seed: u64, min_score: Option<f64>) {
+    let spec: AgentSpec = match spec.parse() {
+        Ok(s) => s,
+        Err(e) => return eprintln!("Agent error: {}", e),
+    };
+    let suite = match positions::load_suite(Path::new(dir)) {
+        Ok(s) => s,
+        Err(e) => return eprintln!("Positions error: {}", e),
+    };
+    println!("=== {} on {} positions from {}, {} seeds each ===", spec, suite.len(), dir, n);
+    let scores = match positions::verify(&spec, &suite, seed, n.max(1)) {
+        Ok(s) => s,
+        Err(e) => return eprintln!("Verify error: {}", e),
+    };
+    for s in &scores {
+        let mark = if s.passed == s.asked { "ok  " } else { "FAIL" };
+        println!("  {} {:<32} {:>3}/{}", mark, s.name, s.passed, s.asked);
+        for (action, count, why) in s.failures.iter().take(3) {
+            println!("         {:>3}x {}: {}", count, serde_json::to_string(action).unwrap(), why);
+        }
+    }
+    let score = positions::suite_score(&scores);
+    println!("\nScore: {:.3} ({} of {} positions always passed)", score, scores.iter().filter(|s| s.passed == s.asked).count(), scores.len());
+    if let Some(min) = min_score.filter(|&m| score < m) {
+        eprintln!("Score {:.3} is below the required {:.3}", score, min);
+        std::process::exit(1);
+    }
+}
+
+fn cmd_snapshots(path: &str, bless: bool) {
+    let actual = snapshot::canonical_snapshots();
+    if bless {
+        let json = serde_json::to_string_pretty(&actual).unwrap() + "\n";
+        if let Err(e) = std::fs::write(path, json) {
+            return eprintln!("Write error: {}", e);
+        }
+        return println!("Wrote {} snapshots to {}", actual.len(), path);
+    }
+    let stored: Vec<snapshot::Snapshot> = match std::fs::read_to_string(path).map_err(|e| e.to_string())
+        .and_then(|s| serde_json::from_str(&s).map_err(|e| e.to_string()))
+    {
+        Ok(s) => s,
+        Err(e) => return eprintln!("Snapshot error: {}: {}", path, e),
+    };
+    let diffs = snapshot::diff(&stored, &actual);
+    if diffs.is_empty() {
+        return println!("All {} canonical games match {}", actual.len(), path);
+    }
+    for line in &diffs {
+        println!("  {}", line);
+    }
+    eprintln!("{} of {} canonical games changed; rerun with --bless if the change is intended", diffs.len(), actual.len());
+    std::process::exit(1);
+}
+
+fn cmd_replay(path: &str, all: bool) {
+    let replay = match Replay::load(Path::new(path)) {
+        Ok(r) => r,
+        Err(e) => return eprintln!("Replay error: {}", e),
+    };
+    println!("=== Replay {}: seed={}, {} actions, {} annotations ===", path, replay.setup.seed, replay.actions.len(), replay.annotations.len());
+    for p in &replay.players {
+        println!("  {:10} {}", p.house.to_string(), p.agent);
+    }
+    println!();
+    for note in replay.annotations_on(&AnnotationTarget::Game) {
+        println!("  game: {}", note.summary());
+    }
+    for recorded in &replay.actions {
+        let target = AnnotationTarget::Action { index: recorded.index };
+        let notes: Vec<&Annotation> = replay.annotations_on(&target).collect();
+        if all || !notes.is_empty() {
+            println!("  #{:<5} round {:2} {:10} {:?}", recorded.index, recorded.round, recorded.house.to_string(), recorded.action);
+        }
+        for note in notes {
+            println!("         {}", note.summary());
+        }
+    }
+    for note in &replay.annotations {
+        if let AnnotationTarget::Event { seq } = note.target {
+            let event = replay.events.iter().find(|e| e.seq == seq);
+            println!("  event {}: {} {}", seq, event.map_or(String::from("(not recorded)"), |e| format!("{:?}", e.kind)), note.summary());
+        }
+    }
+
+    match replay.play() {
+        Ok(state) if state.winner == replay.winner => match state.winner {
+            Some(winner) => println!("\nOK: replay reproduces the recorded game, winner {} in round {}", winner, state.round),
+            None => println!("\nOK: replay plays back to round {} (game not finished)", state.round),
+        },
+        Ok(state) => {
+            eprintln!("\nMISMATCH: replay winner {:?}, recorded {:?}", state.winner, replay.winner);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("\nReplay error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn cmd_annotate(path: &str, action: Option<usize>, event: Option<u32>, author: &str, text: Option<String>, data: &[String]) {
+    let target = match (action, event) {
+        (Some(index), _) => AnnotationTarget::Action { index },
+        (None, Some(seq)) => AnnotationTarget::Event { seq },
+        (None, None) => AnnotationTarget::Game,
+    };
+    let mut note = Annotation::new(target, author);
+    note.text = text;
+    for pair in data {
+        let Some((key, value)) = pair.split_once('=') else {
+            return eprintln!("Annotate error: expected key=value, got '{}'", pair);
+        };
+        // Numbers, booleans and quoted JSON stay typed; anything else is a string
+        let value = serde_json::from_str(value).unwrap_or_else(|_| serde_json::Value::from(value));
+        note = note.with(key, value);
+    }
+    if note.text.is_none() && note.data.is_empty() {
+        return eprintln!("Annotate error: give --text or at least one --data key=value");
+    }
+    let checked = Replay::load(Path::new(path)).and_then(|r| match note.target {
+        AnnotationTarget::Action { index } if index >= r.actions.len() => {
+            Err(format!("the replay has {} actions, no action {}", r.actions.len(), index))
+        }
+        _ => Ok(()),
+    });
+    match checked.and_then(|()| replay::append_annotation(Path::new(path), &note)) {
+        Ok(()) => println!("Added to {}: {}", path, note.summary()),
+        Err(e) => eprintln!("Annotate error: {}", e),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_analyze(
+    path: &str,
+    reference: &str,
+    playouts: usize,
+    rollout: &str,
+    alternatives: usize,
+    threshold: f64,
+    house: Option<&str>,
+    out: Option<&str>,
+    threads: usize,
+) {
+    let mut replay = match Replay::load(Path::new(path)) {
+        Ok(r) => r,
+        Err(e) => return eprintln!("Analyze error: {}", e),
+    };
+    let reference: AgentSpec = match reference.parse() {
+        Ok(s) => s,
+        Err(e) => return eprintln!("Agent error: {}", e),
+    };
+    let house = match house.map(str::parse::<HouseName>).transpose() {
+        Ok(h) => h,
+        Err(e) => return eprintln!("Analyze error: {}", e),
+    };
+    let rollout: RolloutLimit = match rollout.parse() {
+        Ok(l) => l,
+        Err(e) => return eprintln!("Analyze error: {}", e),
+    };
+    let valued_by = match playouts {
+        0 => "static evaluation".to_string(),
+        n if rollout.is_full() => format!("{} playouts per candidate", n),
+        n => format!("{} playouts per candidate, cut at {}", n, rollout),
+    };
+    println!("=== Analyzing {}: {} decisions against {} ({}) ===\n",
+        path, replay.actions.len(), reference, valued_by);
+    let opts = AnalyzeOptions { reference: reference.clone(), playouts, rollout, alternatives, threshold, house, seed: replay.setup.seed, threads };
+    let start = Instant::now();
+    let analysis = match analysis::analyze(&replay, &opts, 50_000) {
+        Ok(a) => a,
+        Err(e) => return eprintln!("Analyze error: {}", e),
+    };
+
+    println!("{:>6} {:>5} {:<10} {:>7} {:>7} {:>6}  Played / better", "Action", "Round", "House", "Played", "Best", "Loss");
+    println!("{}", "-".repeat(72));
+    for r in analysis.blunders() {
+        println!("{:>6} {:>5} {:<10} {:>7.3} {:>7.3} {:>6.3}  {:?}", r.index, r.round, r.house.to_string(), r.played_value, r.best_value, r.loss(), r.played);
+        println!("{:>47}  {:?}", "", r.best);
+    }
+    println!("\n{:<10} {:>9} {:>9} {:>8} {:>9}", "House", "Examined", "Contested", "Blunders", "Mean loss");
+    for p in &replay.players {
+        let examined = replay.actions.iter().filter(|a| a.house == p.house && house.is_none_or(|h| h == p.house)).count();
+        let contested: Vec<_> = analysis.contested.iter().filter(|r| r.house == p.house).collect();
+        let blunders = contested.iter().filter(|r| r.loss() >= threshold).count();
+        let mean_loss = contested.iter().map(|r| r.loss()).sum::<f64>() / contested.len().max(1) as f64;
+        println!("{:<10} {:>9} {:>9} {:>8} {:>9.3}", p.house.to_string(), examined, contested.len(), blunders, mean_loss);
+    }
+    println!("\n{} of {} decisions flagged (loss >= {}), done in {:.1}s",
+        analysis.blunders().count(), analysis.examined, threshold, start.elapsed().as_secs_f64());
+
+    if let Some(out) = out {
+        analysis.annotate(&mut replay, &format!("analyze:{}", reference));
+        match replay.save(Path::new(out)) {
+            Ok(()) => println!("Annotated replay written to {}", out),
+            Err(e) => eprintln!("Analyze error: {}", e),
+        }
     }
 }
 
-fn make_agents(seed: u64, player_count: u8, agent_type: &str) -> HashMap<HouseName, Box<dyn Agent>> {
-    let mut agents: HashMap<HouseName, Box<dyn Agent>> = HashMap::new();
-    for (i, &house) in HouseName::ALL.iter().take(player_count as usize).enumerate() {
-        let agent: Box<dyn Agent> = match agent_type {
-            "heuristic" => Box::new(HeuristicAgent::new(house, seed + i as u64)),
-            "mixed" => {
-                if i % 2 == 0 {
-                    Box::new(HeuristicAgent::new(house, seed + i as u64))
-                } else {
-                    Box::new(RandomAgent::new(house, seed + i as u64))
+fn cmd_diff_engine(command: &str, games: u64, players: u8, seed: u64, max_steps: usize) {
+    println!("=== {} games, {} players, against `{}` ===", games, players, command);
+    let mut by_phase: HashMap<String, u32> = HashMap::new();
+    for game_seed in seed..seed + games {
+        // A fresh process per game, so one bad game can't poison the next
+        let mut reference = match differential::ReferenceEngine::spawn(command) {
+            Ok(r) => r,
+            Err(e) => return eprintln!("Reference error: {}", e),
+        };
+        let setup = SetupConfig::new(players, game_seed);
+        match differential::diff_game(&mut reference, &setup, game_seed, max_steps) {
+            Ok(None) => println!("  seed {:>5}: agree", game_seed),
+            Ok(Some(d)) => {
+                let action = d.action.as_ref()
+                    .map_or("setup".to_string(), |(house, a)| format!("{} {}", house, serde_json::to_string(a).unwrap()));
+                println!("  seed {:>5}: differ at step {} (round {}, {:?}) after {}", game_seed, d.step, d.round, d.phase, action);
+                for line in &d.differences {
+                    println!("               {}", line);
                 }
+                *by_phase.entry(format!("{:?}", d.phase)).or_default() += 1;
+            }
+            Err(e) => println!("  seed {:>5}: error: {}", game_seed, e),
+        }
+    }
+    let mut phases: Vec<_> = by_phase.into_iter().collect();
+    phases.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    println!("\nFirst differences by phase:");
+    for (phase, n) in phases {
+        println!("  {:<10} {}", phase, n);
+    }
+}
+
+fn cmd_bench_threats(agent: &str, games: u64, players: u8, every: usize, repeat: usize) {
+    let agent: AgentSpec = match agent.parse() {
+        Ok(s) => s,
+        Err(e) => return eprintln!("Agent error: {}", e),
+    };
+    let mut views = Vec::new();
+    for g in 0..games {
+        let config = SetupConfig::new(players, 1 + g);
+        let seats = assign_seats(&config, &vec![agent.clone(); players as usize]);
+        let mut decision = 0;
+        let outcome = run_seated_game_observed(&config, &seats, 50_000, |_, view, _| {
+            if decision % every.max(1) == 0 {
+                views.push(view.clone());
             }
-            _ => Box::new(RandomAgent::new(house, seed + i as u64)),
+            decision += 1;
+        });
+        if let Err(e) = outcome {
+            return eprintln!("Bench error: game {}: {}", 1 + g, e);
+        }
+    }
+    println!("=== Threat maps of {} views (every {}th decision of {} {}-player {} games), {} times each ===\n", views.len(), every, games, players, agent, repeat);
+    let start = Instant::now();
+    let mut threatened = 0;
+    for _ in 0..repeat.max(1) {
+        for view in &views {
+            let map = ThreatMap::of(view);
+            threatened += map.areas.iter().filter(|a| !a.reachable_by.is_empty()).count();
+        }
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+    let maps = (views.len() * repeat.max(1)).max(1);
+    println!("{:.2} µs per map, {:.0} maps/s", 1e6 * elapsed / maps as f64, maps as f64 / elapsed);
+    println!("{:.1} of {} areas within an enemy's reach on average", threatened as f64 / maps as f64, got_engine::map::NUM_AREAS);
+}
+
+fn cmd_bench_rollouts(agent: &str, games: u64, players: u8, every: usize, playouts: usize, limits: &str, threads: usize) {
+    let agent: AgentSpec = match agent.parse() {
+        Ok(s) => s,
+        Err(e) => return eprintln!("Agent error: {}", e),
+    };
+    let limits = match limits.split('/').map(str::parse).collect::<Result<Vec<RolloutLimit>, String>>() {
+        Ok(l) => l,
+        Err(e) => return eprintln!("Bench error: {}", e),
+    };
+    println!("=== {} {} rollouts per position, every {}th decision of {} {}-player games ===\n", playouts, agent, every, games, players);
+    let opts = RolloutBenchOptions { agent, games, first_seed: 1, players, every, playouts, limits, threads };
+    let start = Instant::now();
+    let bench = match rollouts::bench(&opts, 50_000) {
+        Ok(b) => b,
+        Err(e) => return eprintln!("Bench error: {}", e),
+    };
+    println!("{} positions ({:.1}s)\n", bench.positions, start.elapsed().as_secs_f64());
+    println!("{:<22} {:>12} {:>10} {:>10} {:>10} {:>8}", "Limit", "Playouts/s", "Decisions", "Truncated", "Mean error", "Leader");
+    println!("{}", "-".repeat(77));
+    for r in &bench.limits {
+        println!("{:<22} {:>12.1} {:>10.1} {:>9.0}% {:>10.3} {:>7.0}%",
+            r.limit.to_string(), r.playouts_per_sec(), r.mean_decisions(), 100.0 * r.truncated_share(), r.mean_error, 100.0 * r.leader_agreement);
+    }
+    println!("\nPlayouts/s per worker thread; mean error and leader agreement against the full rollouts' win probabilities");
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_eval_calibrate(agent: &str, games: u64, players: u8, every: usize, fit: bool, val_fraction: f64, epochs: usize, lr: f64, threads: usize) {
+    let agent: AgentSpec = match agent.parse() {
+        Ok(s) => s,
+        Err(e) => return eprintln!("Agent error: {}", e),
+    };
+    println!("=== Sampling every {}th decision of {} {}-player {} mirror games ===\n", every, games, players, agent);
+    let start = Instant::now();
+    let opts = SampleOptions { agent, games, first_seed: 1, players, every, threads };
+    let samples = match calibration::sample_positions(&opts, 50_000) {
+        Ok(s) => s,
+        Err(e) => return eprintln!("Calibration error: {}", e),
+    };
+    let (weights, scored) = if fit {
+        let (val, train): (Vec<_>, Vec<_>) = samples.into_iter().partition(|s| dataset::is_validation(s.seed as i64, val_fraction));
+        println!("Fitting on {} positions, scoring on {} held-out positions", train.len(), val.len());
+        (calibration::fit_weights(&train, &eval::WEIGHTS, epochs, lr), val)
+    } else {
+        (eval::WEIGHTS, samples)
+    };
+    let report = calibration::calibrate(&scored, &weights, 10);
+    println!("{} positions, {} predictions ({:.1}s)\n", report.positions, report.predictions, start.elapsed().as_secs_f64());
+    println!("{:<12} {:>8} {:>10} {:>9}", "Predicted", "Count", "Mean pred", "Observed");
+    println!("{}", "-".repeat(42));
+    for b in &report.bins {
+        println!("{:>4.0}%-{:>4.0}%  {:>8} {:>9.1}% {:>8.1}%", 100.0 * b.low, 100.0 * b.high, b.count, 100.0 * b.predicted, 100.0 * b.observed);
+    }
+    println!("\nBrier    {:.4} (1/n guess {:.4})", report.brier, report.baseline_brier);
+    println!("Log loss {:.4} (1/n guess {:.4})", report.log_loss, report.baseline_log_loss);
+    if fit {
+        println!("\nFitted weights (engine::eval::WEIGHTS):");
+        for (name, w) in eval::FEATURE_NAMES.iter().zip(weights) {
+            println!("  {:<14} {:>8.3}", name, w);
+        }
+        let list: Vec<String> = weights.iter().map(|w| format!("{:.2}", w)).collect();
+        println!("  [{}]", list.join(", "));
+    }
+}
+
+fn cmd_dataset(db_path: &str, out: &str, format: &str, val_fraction: f64, agent: Option<String>, limit: Option<usize>) {
+    let format = match format.parse::<DatasetFormat>() {
+        Ok(f) => f,
+        Err(e) => return eprintln!("Dataset error: {}", e),
+    };
+    if !(0.0..=1.0).contains(&val_fraction) {
+        return eprintln!("Dataset error: --val-fraction must be between 0 and 1");
+    }
+    let db = Database::new(db_path);
+    println!("=== Exporting {} games from {} to {} ({}) ===", db.game_count(), db_path, out, format.extension());
+    let start = Instant::now();
+    let opts = ExportOptions { format, val_fraction, agent, limit };
+    let summary = match dataset::export(&db, Path::new(out), &opts) {
+        Ok(s) => s,
+        Err(e) => return eprintln!("Dataset error: {}", e),
+    };
+    println!("  train: {} games, {} samples", summary.train_games, summary.train_samples);
+    println!("  val:   {} games, {} samples", summary.val_games, summary.val_samples);
+    if !summary.skipped.is_empty() {
+        println!("  skipped {} games:", summary.skipped.len());
+        for (id, reason) in summary.skipped.iter().take(10) {
+            println!("    game {}: {}", id, reason);
+        }
+    }
+    println!("  done in {:.1}s", start.elapsed().as_secs_f64());
+}
+
+fn cmd_train_clone(data: &str, out: &str, epochs: usize, lr: f32, eval_games: u32) {
+    println!("=== Behaviour cloning from {} ===", data);
+    let start = Instant::now();
+    let opts = FitOptions { epochs, learning_rate: lr, ..FitOptions::default() };
+    let (model, report) = match training::train_clone(Path::new(data), &opts) {
+        Ok(r) => r,
+        Err(e) => return eprintln!("Training error: {}", e),
+    };
+    println!("  {} training / {} validation samples, fitted in {:.1}s\n",
+        report.train_samples, report.val_samples, start.elapsed().as_secs_f64());
+    println!("  {:<26} {:>8} {:>8} {:>8}", "Decision", "Samples", "Train", "Val");
+    for &(kind, correct, total) in &report.train_accuracy {
+        let val = report.val_accuracy.iter().find(|v| v.0 == kind)
+            .map_or("-".to_string(), |&(_, c, t)| format!("{:.1}%", 100.0 * c as f64 / t as f64));
+        println!("  {:<26} {:>8} {:>7.1}% {:>8}", kind.name(), total, 100.0 * correct as f64 / total as f64, val);
+    }
+    println!("  {:<26} {:>8} {:>7.1}% {:>7.1}%", "overall", report.train_samples,
+        100.0 * training::overall(&report.train_accuracy), 100.0 * training::overall(&report.val_accuracy));
+
+    if let Err(e) = model.save(out) {
+        return eprintln!("Training error: {}", e);
+    }
+    println!("\n  Model saved to {} (agent spec: cloned{{model={}}})", out, out);
+    if eval_games == 0 {
+        return;
+    }
+
+    // Cloned agents on even seats, heuristic on odd
+    let lineup = match parse_lineup(&format!("cloned{{model={}}}/heuristic", out), 6) {
+        Ok(l) => l,
+        Err(e) => return eprintln!("Agent error: {}", e),
+    };
+    let games: Vec<_> = (0..eval_games as u64)
+        .map(|seed| {
+            let config = SetupConfig { randomize_seats: true, ..SetupConfig::new(6, seed) };
+            let seats = assign_seats(&config, &lineup);
+            (config, seats)
+        })
+        .collect();
+    let wins: Mutex<HashMap<String, u32>> = Mutex::new(HashMap::new());
+    let failed = AtomicU32::new(0);
+    run_games_parallel(&games, 0, 50_000, ActionPolicy::Lenient, |i, result| match result {
+        Ok(r) => {
+            let spec = games[i].1.iter().find(|s| s.house == r.winner).map_or(String::new(), |s| s.spec.to_string());
+            *wins.lock().unwrap().entry(spec).or_default() += 1;
+        }
+        Err(_) => { failed.fetch_add(1, Ordering::Relaxed); }
+    });
+    println!("\n  Evaluation: {} games, 3 cloned vs 3 heuristic seats", eval_games);
+    for (spec, n) in wins.into_inner().unwrap() {
+        println!("    {:30} {} wins", spec, n);
+    }
+    let failed = failed.into_inner();
+    if failed > 0 {
+        println!("    {} games failed", failed);
+    }
+}
+
+fn cmd_league(args: &LeagueArgs) {
+    let baselines = match args.baselines.split('/').map(str::parse).collect::<Result<Vec<AgentSpec>, String>>() {
+        Ok(b) => b,
+        Err(e) => return eprintln!("Agent error: {}", e),
+    };
+    let opts = LeagueOptions {
+        name: args.name.clone(),
+        dir: args.dir.clone().into(),
+        players: args.players,
+        games_per_generation: args.games,
+        freeze_every: args.freeze_every,
+        pool_size: args.pool_size,
+        baselines,
+        threads: args.threads,
+        fit: FitOptions { epochs: args.epochs, learning_rate: args.lr, ..FitOptions::default() },
+    };
+    println!("=== League {}: {} generations of {} games ===\n", args.name, args.generations, args.games);
+    for _ in 0..args.generations {
+        let start = Instant::now();
+        let report = match league::run_generation(&args.db, &opts) {
+            Ok(r) => r,
+            Err(e) => return eprintln!("League error: {}", e),
+        };
+        println!("Generation {}: {} games ({} errors), {} winner decisions, {:.1}s",
+            report.generation, report.games, report.errors, report.samples, start.elapsed().as_secs_f64());
+        if let Some((spec, elo)) = &report.learner {
+            println!("  learner   {} (league Elo {:.0})", spec, elo);
+        }
+        if let Some(spec) = &report.frozen {
+            println!("  frozen    {}", spec);
+        }
+        for spec in &report.retired {
+            println!("  retired   {}", spec);
+        }
+        println!("  next      {}", report.next_learner);
+    }
+    println!();
+    cmd_league_table(&args.db, Some(&args.name));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_probe(base: &str, donor: &str, opponents: &str, decisions: Option<&str>, seeds: u64, players: u8, threads: usize) {
+    let parse = |s: &str| s.parse::<AgentSpec>();
+    let (base, donor, opponents) = match (parse(base), parse(donor), parse(opponents)) {
+        (Ok(b), Ok(d), Ok(o)) => (b, d, o),
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => return eprintln!("Agent error: {}", e),
+    };
+    let kinds = match decisions {
+        Some(list) => match list.split(',').map(|k| k.trim().parse()).collect::<Result<Vec<DecisionKind>, String>>() {
+            Ok(k) => k,
+            Err(e) => return eprintln!("Probe error: {}", e),
+        },
+        None => DecisionKind::ALL.to_vec(),
+    };
+    println!("=== Probing {} with decisions from {} vs {}: {} paired seeds, {} decision types ===\n",
+        base, donor, opponents, seeds, kinds.len());
+    let opts = ProbeOptions { base, donor, opponents, kinds, seeds, first_seed: 1, players, threads };
+    let start = Instant::now();
+    let mut results = match probes::run_probes(&opts, 50_000) {
+        Ok(r) => r,
+        Err(e) => return eprintln!("Probe error: {}", e),
+    };
+    results.sort_by(|a, b| a.mean_diff.total_cmp(&b.mean_diff));
+    println!("{:<28} {:>6} {:>8} {:>8} {:>9} {:>7} {:>11}", "Swapped", "Pairs", "Base", "Probe", "Diff", "±SE", "Wins B/P");
+    println!("{}", "-".repeat(84));
+    for r in &results {
+        let label = match r.swap {
+            Swap::Decision(k) => k.key(),
+            Swap::Everything => "(whole agent)".to_string(),
+        };
+        println!("{:<28} {:>6} {:>8.3} {:>8.3} {:>+9.3} {:>7.3} {:>5}/{:<5}",
+            label, r.pairs, r.base_score, r.probe_score, r.mean_diff, r.std_err, r.base_wins, r.probe_wins);
+    }
+    println!("\nScores are the subject seat's placing (1 = first, 0 = last); done in {:.1}s", start.elapsed().as_secs_f64());
+}
+
+fn cmd_experiments(agent: &str, perturbations: Option<&str>, seeds: u64, players: u8, threads: usize) {
+    let agent = match agent.parse::<AgentSpec>() {
+        Ok(a) => a,
+        Err(e) => return eprintln!("Agent error: {}", e),
+    };
+    let perturbations = match perturbations {
+        Some(list) => match list.split(',').map(|p| p.trim().parse()).collect::<Result<Vec<Perturbation>, String>>() {
+            Ok(p) => p,
+            Err(e) => return eprintln!("Experiment error: {}", e),
+        },
+        None => Perturbation::DEFAULTS.to_vec(),
+    };
+    println!("=== Handicap experiments with {} in every seat: {} paired seeds, {} perturbations ===\n",
+        agent, seeds, perturbations.len());
+    let opts = ExperimentOptions { agent, perturbations, seeds, first_seed: 1, players, threads };
+    let start = Instant::now();
+    let results = match experiments::run_experiments(&opts, 50_000) {
+        Ok(r) => r,
+        Err(e) => return eprintln!("Experiment error: {}", e),
+    };
+    println!("{:<10} {:<16} {:>6} {:>8} {:>8} {:>9} {:>7} {:>11}", "House", "Perturbation", "Pairs", "Base", "Changed", "Delta", "±SE", "Wins B/C");
+    println!("{}", "-".repeat(82));
+    for r in &results {
+        println!("{:<10} {:<16} {:>6} {:>8.3} {:>8.3} {:>+9.3} {:>7.3} {:>5}/{:<5}",
+            r.house.to_string(), r.perturbation.to_string(), r.pairs, r.base_rate, r.perturbed_rate, r.delta, r.std_err,
+            r.base_wins, r.perturbed_wins);
+    }
+    println!("\nRates are the house's win rate without and with the perturbation; done in {:.1}s", start.elapsed().as_secs_f64());
+}
+
+fn cmd_map_balance(agent: &str, games: u64, players: u8, threads: usize, out: Option<&str>) {
+    let agent = match agent.parse::<AgentSpec>() {
+        Ok(a) => a,
+        Err(e) => return eprintln!("Agent error: {}", e),
+    };
+    println!("=== Map balance: {} {}-player games with {} in every seat ===\n", games, players, agent);
+    let opts = MapBalanceOptions { agent, games, first_seed: 1, players, threads };
+    let start = Instant::now();
+    let heatmap = match map_balance::run_map_balance(&opts, 50_000) {
+        Ok(h) => h,
+        Err(e) => return eprintln!("Map balance error: {}", e),
+    };
+    let mut areas: Vec<_> = heatmap.areas.iter().collect();
+    areas.sort_by(|a, b| b.heat.total_cmp(&a.heat));
+    println!("{:<24} {:>8} {:>8} {:>6} {:<18} {:>6} {:>5}", "Area", "Captures", "Battles", "Held", "Top holder", "Lift", "Zone");
+    println!("{}", "-".repeat(81));
+    for a in &areas {
+        let top = a.controllers.first()
+            .map_or_else(|| "-".to_string(), |(h, share)| format!("{} {:.0}%", h, 100.0 * share));
+        let lift = a.win_lift.map_or_else(|| "-".to_string(), |l| format!("{:.2}", l));
+        let zone = match a.zone {
+            Zone::Normal => "",
+            Zone::Dead => "dead",
+            Zone::Hot => "HOT",
         };
-        agents.insert(house, agent);
+        println!("{:<24} {:>8.2} {:>8.2} {:>5.0}% {:<18} {:>6} {:>5}",
+            a.name, a.capture_rate, a.battle_rate, 100.0 * a.held_share, top, lift, zone);
+    }
+    println!("\nCaptures and battles per game; Held: share of round ends controlled; Lift: final holder's wins / fair share.");
+    println!("{} games in {:.1}s", heatmap.games, start.elapsed().as_secs_f64());
+    if let Some(path) = out {
+        match std::fs::write(path, serde_json::to_string_pretty(&heatmap).unwrap() + "\n") {
+            Ok(()) => println!("Heatmap written to {}", path),
+            Err(e) => eprintln!("Cannot write {}: {}", path, e),
+        }
+    }
+}
+
+fn cmd_balance(db_path: &str, players: Option<u8>, agent: Option<String>, out: Option<&str>) {
+    let db = Database::new(db_path);
+    let filter = BalanceFilter { players, agent };
+    let houses = balance::house_balance(&db, &filter);
+    if houses.is_empty() {
+        return println!("No standard-rules mirror games match. Run a tournament with one agent in every seat first.");
+    }
+    println!("=== House balance (mirror games, standard rules) ===\n");
+    println!("{:<10} {:>6} {:>6} {:>8} {:>8} {:>9} {:>8} {:>7} {:>8}", "House", "Games", "Wins", "Rate", "Fair", "Estimate", "Power", "Castles", "Region");
+    println!("{}", "-".repeat(79));
+    // Home region: land nearer the house's home than any other house's,
+    // and the castles and strongholds in it
+    let playing: Vec<HouseName> = houses.iter().map(|h| h.house).collect();
+    for h in &houses {
+        let handicap = balance::handicap_for(h);
+        let region = map_analysis::home_region(&playing, h.house);
+        let castles = region.iter().filter(|a| AREAS[a.0 as usize].has_castle_or_stronghold()).count();
+        println!("{:<10} {:>6} {:>6} {:>7.1}% {:>7.1}% {:>8.1}% {:>+8} {:>7} {:>8}",
+            h.house.to_string(), h.games, h.wins, 100.0 * h.win_rate, 100.0 * h.fair, 100.0 * h.estimate,
+            handicap.power_bonus, CASTLES_TO_WIN as i8 + handicap.castle_adjust, format!("{}/{}", region.len(), castles));
     }
-    agents
+    println!("\nRegion: land areas nearest the house's home / castles among them.");
+    let options = balance::suggest_options(&houses);
+    let json = serde_json::to_string_pretty(&options).unwrap();
+    match out {
+        Some(path) => match std::fs::write(path, &json) {
+            Ok(()) => println!("\nSuggested options written to {} (pass with --options {})", path, path),
+            Err(e) => eprintln!("Cannot write {}: {}", path, e),
+        },
+        None => println!("\nSuggested options:\n{}", json),
+    }
+}
+
+fn cmd_branching(db_path: &str) {
+    let db = Database::new(db_path);
+    let (games, totals) = db.branching_totals();
+    if games == 0 {
+        return println!("No games with branching statistics. Run a tournament first.");
+    }
+    let per_game = |n: usize| n as f64 / games as f64;
+    println!("=== Branching over {} games ===\n", games);
+    println!("{:<26} {:>10} {:>8} {:>6} {:>8}", "Decision", "Per game", "Mean", "Max", "Forced");
+    println!("{}", "-".repeat(62));
+    for k in &totals.per_kind {
+        println!("{:<26} {:>10.1} {:>8.2} {:>6} {:>7.1}%",
+            k.kind, per_game(k.decisions), k.mean(), k.max, 100.0 * k.forced as f64 / k.decisions.max(1) as f64);
+    }
+    println!("{}", "-".repeat(62));
+    println!("{:<26} {:>10.1} {:>8.2} {:>6} {:>7.1}%",
+        "all", per_game(totals.decisions), totals.mean(), totals.max, 100.0 * totals.forced as f64 / totals.decisions.max(1) as f64);
+    println!("\nGame tree size ~10^{:.0} (mean over games of the summed log10 branching).", totals.log10_tree_size / games as f64);
+    println!("Orders and musters count single-area answers only, so their figures are lower bounds.");
+}
+
+fn cmd_league_table(db_path: &str, name: Option<&str>) {
+    let db = Database::new(db_path);
+    let Some(name) = name else {
+        let leagues = db.leagues();
+        if leagues.is_empty() {
+            println!("No leagues found. Start one with `league`.");
+        }
+        for (league, members) in leagues {
+            println!("  {:20} {} members", league, members);
+        }
+        return;
+    };
+    let mut members = db.league_members(name);
+    if members.is_empty() {
+        return println!("No league named {}.", name);
+    }
+    members.sort_by(|a, b| b.elo.total_cmp(&a.elo));
+    println!("=== League {} ===\n", name);
+    println!("{:<5} {:<45} {:>4} {:<9} {:>8} {:>6}", "Rank", "Agent", "Gen", "Role", "Elo", "Seats");
+    println!("{}", "-".repeat(82));
+    for (i, m) in members.iter().enumerate() {
+        println!("{:<5} {:<45} {:>4} {:<9} {:>8.1} {:>6}",
+            i + 1, m.spec.to_string(), m.generation, m.role.name(), m.elo, m.games);
+    }
+}
+
+fn cmd_lobby(args: &LobbyArgs) {
+    let config = match load_options(args.options.as_deref())
+        .and_then(|o| setup_config(args.seed, args.players, args.houses.as_deref(), args.random_seats, &o))
+    {
+        Ok(c) => c,
+        Err(e) => return eprintln!("Setup error: {}", e),
+    };
+    let lineup = match parse_lineup(&args.agent, config.houses.len()) {
+        Ok(l) => l,
+        Err(e) => return eprintln!("Agent error: {}", e),
+    };
+    let opts = LobbyOptions {
+        addr: args.addr.clone(),
+        config,
+        remote_seats: args.remote,
+        lineup,
+        join_window: args.wait.map(Duration::from_secs),
+        max_decisions: 50_000,
+    };
+    println!("=== Lobby: {} players, {} remote seat(s), AI: {} ===\n", opts.config.player_count(), args.remote, args.agent);
+    match lobby::run_lobby(&opts, |line| println!("  {}", line)) {
+        Ok((result, players)) => {
+            println!("\nGame finished after {} rounds. Final ranking:", result.rounds_played);
+            for (i, house) in result.final_ranking.iter().enumerate() {
+                let who = match players.iter().find(|p| p.house == *house) {
+                    Some(p) => format!("{} (remote)", p.name),
+                    None => result.player_results.iter()
+                        .find(|p| p.house == *house)
+                        .map_or(String::new(), |p| p.agent_name.clone()),
+                };
+                println!("  {}. {:10} {}", i + 1, house.to_string(), who);
+            }
+        }
+        Err(e) => eprintln!("Lobby error: {}", e),
+    }
+}
+
+fn cmd_connect(addr: &str, house: Option<&str>, name: Option<&str>, agent: &str) {
+    let spec: AgentSpec = match agent.parse() {
+        Ok(s) => s,
+        Err(e) => return eprintln!("Agent error: {}", e),
+    };
+    let outcome = lobby::connect(addr, house, name, |h| spec.build(h, 0), |msg| match msg {
+        ServerMessage::Welcome { house, seat, .. } => println!("Seated as House {} (seat {})", house, seat + 1),
+        ServerMessage::Lobby { seats } => {
+            let open = seats.iter().filter(|s| s.open).count();
+            println!("Lobby: {} of {} seats waiting for players", open, seats.len());
+        }
+        ServerMessage::Rejected { reason } => println!("Server rejected the action: {}", reason),
+        _ => {}
+    });
+    match outcome {
+        Ok(ranking) => {
+            println!("\nGame over. Final ranking:");
+            for (i, house) in ranking.iter().enumerate() {
+                println!("  {}. {}", i + 1, house);
+            }
+        }
+        Err(e) => eprintln!("Connection error: {}", e),
+    }
+}
+
+fn cmd_schema(out: Option<&str>, openapi: bool) {
+    let Some(dir) = out else {
+        let doc = if openapi {
+            schema::openapi()
+        } else {
+            serde_json::Value::Object(schema::schemas().into_iter()
+                .map(|(name, s)| (name.to_string(), serde_json::to_value(s).unwrap()))
+                .collect())
+        };
+        return println!("{}", serde_json::to_string_pretty(&doc).unwrap());
+    };
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        return eprintln!("Cannot create {}: {}", dir, e);
+    }
+    let mut files: Vec<(String, serde_json::Value)> = schema::schemas().into_iter()
+        .map(|(name, s)| (format!("{}.schema.json", name), serde_json::to_value(s).unwrap()))
+        .collect();
+    files.push(("openapi.json".to_string(), schema::openapi()));
+    for (file, doc) in &files {
+        let path = Path::new(dir).join(file);
+        if let Err(e) = std::fs::write(&path, serde_json::to_string_pretty(doc).unwrap() + "\n") {
+            return eprintln!("Cannot write {}: {}", path.display(), e);
+        }
+    }
+    println!("Wrote {} schema files to {}", files.len(), dir);
+}
+
+fn cmd_leaderboard(
+    db_path: &str,
+    system: Option<&str>,
+    by: &str,
+    house: Option<&str>,
+    min_games: u32,
+    agent_prefix: Option<String>,
+    pivot: bool,
+) {
+    let filter = match (by.parse(), house.map(str::parse).transpose()) {
+        (Ok(sort), Ok(house)) => LeaderboardFilter { sort, house, min_games, agent_prefix },
+        (Err(e), _) | (_, Err(e)) => return eprintln!("{}", e),
+    };
+    let db = Database::new(db_path);
+    if let Some(system) = system {
+        return print_rating_leaderboard(&db, system, &filter);
+    }
+    if pivot {
+        return print_house_pivot(&db, &filter);
+    }
+    let board = db.filtered_leaderboard(&filter);
+    if board.is_empty() {
+        println!("No agents found. Run some tournaments first.");
+        return;
+    }
+    match filter.house {
+        Some(house) => println!("=== Leaderboard (as {}) ===\n", house),
+        None => println!("=== Leaderboard ===\n"),
+    }
+    println!("{:<20} {:>8} {:>8} {:>8} {:>8}", "Agent", "ELO", "Games", "Wins", "Win%");
+    println!("{}", "-".repeat(57));
+    for row in &board {
+        println!("{:<20} {:>8.1} {:>8} {:>8} {:>7.1}%", row.name, row.elo, row.games, row.wins, row.win_rate() * 100.0);
+    }
+}
+
+fn print_house_pivot(db: &Database, filter: &LeaderboardFilter) {
+    let board = db.house_pivot(filter);
+    if board.is_empty() {
+        println!("No agents found. Run some tournaments first.");
+        return;
+    }
+    let houses: Vec<HouseName> = HouseName::ALL.iter().copied()
+        .filter(|&h| board.iter().any(|r| r.by_house.iter().any(|&(played, _, _)| played == h)))
+        .collect();
+    println!("=== Leaderboard by house (wins/games) ===\n");
+    print!("{:<20} {:>8}", "Agent", "ELO");
+    for house in &houses {
+        print!(" {:>10}", house.to_string());
+    }
+    println!(" {:>10}", "Total");
+    println!("{}", "-".repeat(29 + 11 * (houses.len() + 1)));
+    for row in &board {
+        print!("{:<20} {:>8.1}", row.name, row.elo);
+        for &house in &houses {
+            let cell = row.by_house.iter()
+                .find(|&&(played, _, _)| played == house)
+                .map_or("-".to_string(), |&(_, games, wins)| format!("{}/{}", wins, games));
+            print!(" {:>10}", cell);
+        }
+        println!(" {:>10}", format!("{}/{}", row.wins(), row.games()));
+    }
+}
+
+fn print_rating_leaderboard(db: &Database, system: &str, filter: &LeaderboardFilter) {
+    let system: RatingSystem = match system.parse() {
+        Ok(s) => s,
+        Err(e) => return eprintln!("{}", e),
+    };
+    let mut board = db.rating_leaderboard(system);
+    board.retain(|(name, _, games, _)| {
+        *games >= filter.min_games && filter.agent_prefix.as_ref().is_none_or(|p| name.starts_with(p.as_str()))
+    });
+    if board.is_empty() {
+        println!("No {} ratings found. Run recompute-ratings --system {} first.", system.name(), system.name());
+        return;
+    }
+    println!("=== Leaderboard ({}) ===\n", system.name());
+    println!("{:<20} {:>8} {:>8} {:>8} {:>8} {:>8}", "Agent", "Score", "Mu", "Sigma", "Games", "Wins");
+    println!("{}", "-".repeat(66));
+    for (name, r, games, wins_count) in &board {
+        println!("{:<20} {:>8.1} {:>8.1} {:>8.2} {:>8} {:>8}", name, r.score(), r.mu, r.sigma, games, wins_count);
+    }
+}
+
+fn cmd_recompute_ratings(db_path: &str, system: &str) {
+    let system: RatingSystem = match system.parse() {
+        Ok(s) => s,
+        Err(e) => return eprintln!("{}", e),
+    };
+    let db = Database::new(db_path);
+    let rated = db.recompute_ratings(system);
+    println!("Recomputed {} ratings from {} games\n", system.name(), rated);
+    print_rating_leaderboard(&db, system.name(), &LeaderboardFilter::default());
+}
+
+/// Build the setup from either an explicit house list or a player count.
+/// Read game options from a JSON file; standard rules when no file is given.
+fn load_options(path: Option<&str>) -> Result<GameOptions, String> {
+    let Some(path) = path else { return Ok(GameOptions::default()) };
+    let json = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Invalid options in {}: {}", path, e))
+}
+
+/// Build and validate a setup. Handicaps for houses that are not playing
+/// are dropped, so one options file serves every player count.
+fn setup_config(seed: u64, player_count: u8, houses: Option<&str>, random_seats: bool, options: &GameOptions) -> Result<SetupConfig, String> {
+    let mut config = match houses {
+        Some(list) => {
+            let houses = list.split(',')
+                .map(|s| s.parse::<HouseName>())
+                .collect::<Result<Vec<_>, _>>()?;
+            SetupConfig::with_houses(houses, seed)
+        }
+        None => SetupConfig::new(player_count, seed),
+    };
+    config.randomize_seats = random_seats;
+    config.options = options.clone();
+    config.options.handicaps.retain(|h| config.houses.contains(&h.house));
+    config.validate()?;
+    Ok(config)
 }
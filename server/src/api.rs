@@ -0,0 +1,497 @@
+// ═══════════════════════════════════════════════════════════════════════
+// HTTP API — create and play games over REST
+//
+//   POST /games                        create a game (body: CreateGame)
+//   GET  /games[?all=1]                active games (all games with ?all=1)
+//   GET  /games/{id}                   one game's summary
+//   GET  /games/{id}/view/{house}      that house's PlayerView
+//   POST /games/{id}/actions/{house}   answer the house's pending decision
+//   POST /games/{id}/annotations       attach a note to the game, an action or an event
+//   GET  /games/{id}/log               setup, actions, events and notes of a finished game
+//   GET  /map                          the board: areas, adjacency, icons, ports (StaticMap)
+//   GET  /metrics                      games finished, decisions, engine errors (Prometheus text)
+//
+// Houses listed as `external` when the game is created are played through
+// the API; the agents in `agents` play the rest, immediately, whenever it is
+// their turn. Creating a game returns one token per external house; views
+// and actions for that house need it as `Authorization: Bearer <token>` (or
+// `?token=`), so a client only ever sees its own hidden information.
+// Bodies and responses are JSON; errors are {"error": "..."}.
+// ═══════════════════════════════════════════════════════════════════════
+
+use got_agents::registry::parse_lineup;
+use got_agents::Agent;
+use got_engine::engine::{self, Action, ActionError};
+use got_engine::map::static_map;
+use got_engine::setup::{create_game, SetupConfig};
+use got_engine::types::*;
+use got_engine::visibility::{player_view, PlayerView};
+use got_engine::replay::{Annotation, AnnotationTarget, Replay, ReplayPlayer};
+use got_engine::wire;
+use got_tournament::metrics::Metrics;
+use got_tournament::runner::pending_house;
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+/// Most decisions one game may take before it is abandoned as stuck.
+const MAX_DECISIONS: usize = 50_000;
+
+/// An error with the HTTP status it is reported under.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiError {
+    pub status: u16,
+    pub message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        ApiError { status, message: message.into() }
+    }
+}
+
+// ── Wire types ─────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct CreateGame {
+    /// Random when absent.
+    pub seed: Option<u64>,
+    /// Official house selection for this many players (default 6).
+    pub players: Option<u8>,
+    /// Explicit houses; overrides `players`.
+    pub houses: Option<Vec<String>>,
+    #[serde(default)]
+    pub random_seats: bool,
+    /// Agent lineup for the seats not played over the API (default "heuristic").
+    pub agents: Option<String>,
+    /// Houses played through the API.
+    #[serde(default)]
+    pub external: Vec<String>,
+    #[serde(default)]
+    pub options: GameOptions,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Created {
+    pub game: GameSummary,
+    /// Access token for each external house.
+    pub tokens: HashMap<HouseName, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SeatSummary {
+    pub house: HouseName,
+    /// Agent spec, or "external" for houses played through the API.
+    pub agent: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GameSummary {
+    pub id: u64,
+    pub seed: u64,
+    pub round: u8,
+    pub phase: Phase,
+    pub finished: bool,
+    pub winner: Option<HouseName>,
+    /// External house whose decision the game is waiting for.
+    pub waiting_for: Option<HouseName>,
+    pub decisions: usize,
+    pub seats: Vec<SeatSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LoggedAction {
+    pub round: u8,
+    pub house: HouseName,
+    pub action: Action,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GameLog {
+    /// Wire format the log was written with (`wire::WIRE_VERSION`).
+    pub version: u32,
+    pub id: u64,
+    pub setup: SetupConfig,
+    pub seats: Vec<SeatSummary>,
+    pub actions: Vec<LoggedAction>,
+    pub events: Vec<GameEvent>,
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+    pub winner: HouseName,
+    pub ranking: Vec<HouseName>,
+}
+
+impl GameLog {
+    /// Parses a log written by this or any earlier build. Logs from before
+    /// versioning have no `version` field and are wire format 1.
+    pub fn from_json(text: &str) -> Result<GameLog, String> {
+        let mut log: serde_json::Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        let version = match log.get("version") {
+            None => 1,
+            Some(v) => v.as_u64().ok_or("version must be a number")? as u32,
+        };
+        if version > wire::WIRE_VERSION {
+            return Err(format!("log has wire format {}, this build reads up to {}", version, wire::WIRE_VERSION));
+        }
+        if version < wire::WIRE_VERSION {
+            if let Some(serde_json::Value::Array(actions)) = log.get_mut("actions") {
+                for logged in actions {
+                    if let Some(action) = logged.get_mut("action") {
+                        *action = wire::upgrade_action(action.take());
+                    }
+                }
+            }
+            if let Some(serde_json::Value::Array(events)) = log.get_mut("events") {
+                for event in events {
+                    *event = wire::upgrade_event(event.take())?;
+                }
+            }
+            log["version"] = wire::WIRE_VERSION.into();
+        }
+        serde_json::from_value(log).map_err(|e| e.to_string())
+    }
+
+    /// The log as a replay file (see `got-runner replay`).
+    pub fn to_replay(&self) -> Replay {
+        let players = self.seats.iter().map(|s| ReplayPlayer { house: s.house, agent: s.agent.clone() }).collect();
+        let mut replay = Replay::new(self.setup.clone(), players);
+        for logged in &self.actions {
+            replay.record(logged.round, logged.house, logged.action.clone());
+        }
+        replay.finish(&self.events, self.winner, self.ranking.clone());
+        replay.annotations = self.annotations.clone();
+        replay
+    }
+}
+
+// ── Games ──────────────────────────────────────────────────────────────
+
+struct Seat {
+    house: HouseName,
+    agent: String,
+    /// None for external houses.
+    ai: Option<Box<dyn Agent>>,
+    token: Option<String>,
+}
+
+struct ManagedGame {
+    id: u64,
+    config: SetupConfig,
+    state: GameState,
+    seats: Vec<Seat>,
+    actions: Vec<LoggedAction>,
+    annotations: Vec<Annotation>,
+}
+
+impl ManagedGame {
+    fn waiting_for(&self) -> Option<HouseName> {
+        if self.state.winner.is_some() {
+            return None;
+        }
+        self.state.peek_pending().map(pending_house)
+    }
+
+    fn seat(&self, house: HouseName) -> Result<&Seat, ApiError> {
+        self.seats.iter().find(|s| s.house == house)
+            .ok_or_else(|| ApiError::new(404, format!("{} is not playing in game {}", house, self.id)))
+    }
+
+    fn authorize(&self, house: HouseName, token: Option<&str>) -> Result<(), ApiError> {
+        match &self.seat(house)?.token {
+            Some(t) if Some(t.as_str()) == token => Ok(()),
+            Some(_) => Err(ApiError::new(401, format!("missing or wrong token for {}", house))),
+            None => Err(ApiError::new(403, format!("{} is played by an agent", house))),
+        }
+    }
+
+    /// Play `house`'s answer and log it; a refused answer is neither.
+    fn apply(&mut self, house: HouseName, action: Action) -> Result<(), ActionError> {
+        let round = self.state.round;
+        engine::apply_action_as(&mut self.state, house, action.clone())?;
+        self.actions.push(LoggedAction { round, house, action });
+        Ok(())
+    }
+
+    /// `run_agents`, counting the game into `metrics` once it ends or fails.
+    fn run_agents_counted(&mut self, metrics: &Metrics) -> Result<(), ApiError> {
+        let outcome = self.run_agents();
+        match &outcome {
+            Ok(()) if self.state.winner.is_some() => metrics.record_finished(self.actions.len()),
+            Err(e) if e.status == 500 => metrics.record_engine_error(),
+            _ => {}
+        }
+        outcome
+    }
+
+    /// Advance the engine, letting agents answer their own decisions, until
+    /// an external house has to decide or the game is over.
+    fn run_agents(&mut self) -> Result<(), ApiError> {
+        loop {
+            engine::advance(&mut self.state);
+            if self.state.winner.is_some() {
+                return Ok(());
+            }
+            if self.actions.len() >= MAX_DECISIONS {
+                return Err(ApiError::new(500, format!("game {} exceeded {} decisions", self.id, MAX_DECISIONS)));
+            }
+            let house = match self.state.peek_pending() {
+                Some(p) => pending_house(p),
+                None => return Err(ApiError::new(500, format!("game {} is stuck in {:?}", self.id, self.state.phase()))),
+            };
+            let view = player_view(&self.state, house);
+            let seat = self.seats.iter_mut().find(|s| s.house == house)
+                .ok_or_else(|| ApiError::new(500, format!("no seat for {}", house)))?;
+            match seat.ai.as_mut() {
+                Some(agent) => {
+                    let action = agent.decide(&view);
+                    if let Some(info) = agent.last_decision_info() {
+                        let target = AnnotationTarget::Action { index: self.actions.len() };
+                        self.annotations.push(info.annotation(target, &seat.agent));
+                    }
+                    self.apply(house, action)
+                        .map_err(|e| ApiError::new(500, format!("{}'s agent in game {}: {}", house, self.id, e)))?;
+                }
+                None => return Ok(()),
+            }
+        }
+    }
+
+    fn summary(&self) -> GameSummary {
+        GameSummary {
+            id: self.id,
+            seed: self.config.seed,
+            round: self.state.round,
+            phase: self.state.phase(),
+            finished: self.state.winner.is_some(),
+            winner: self.state.winner,
+            waiting_for: self.waiting_for(),
+            decisions: self.actions.len(),
+            seats: self.seat_summaries(),
+        }
+    }
+
+    fn seat_summaries(&self) -> Vec<SeatSummary> {
+        self.seats.iter().map(|s| SeatSummary { house: s.house, agent: s.agent.clone() }).collect()
+    }
+}
+
+/// Every game the API is serving, in memory.
+#[derive(Default)]
+pub struct GameStore {
+    next_id: u64,
+    games: BTreeMap<u64, ManagedGame>,
+    metrics: Arc<Metrics>,
+}
+
+impl GameStore {
+    pub fn new() -> Self {
+        GameStore::default()
+    }
+
+    pub fn metrics(&self) -> Arc<Metrics> {
+        self.metrics.clone()
+    }
+
+    fn game(&self, id: u64) -> Result<&ManagedGame, ApiError> {
+        self.games.get(&id).ok_or_else(|| ApiError::new(404, format!("no game {}", id)))
+    }
+
+    fn game_mut(&mut self, id: u64) -> Result<&mut ManagedGame, ApiError> {
+        self.games.get_mut(&id).ok_or_else(|| ApiError::new(404, format!("no game {}", id)))
+    }
+
+    pub fn create(&mut self, req: CreateGame) -> Result<Created, ApiError> {
+        let bad = |e: String| ApiError::new(400, e);
+        let seed = req.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let mut config = match &req.houses {
+            Some(list) => {
+                let houses = list.iter().map(|h| h.parse()).collect::<Result<Vec<HouseName>, _>>().map_err(bad)?;
+                SetupConfig::with_houses(houses, seed)
+            }
+            None => SetupConfig::new(req.players.unwrap_or(6), seed),
+        };
+        config.randomize_seats = req.random_seats;
+        config.options = req.options;
+        config.validate().map_err(bad)?;
+        let external = req.external.iter().map(|h| h.parse()).collect::<Result<Vec<HouseName>, _>>().map_err(bad)?;
+        if let Some(h) = external.iter().find(|h| !config.houses.contains(h)) {
+            return Err(bad(format!("{} is not playing in this game", h)));
+        }
+
+        let seat_houses = config.seats();
+        let lineup = parse_lineup(req.agents.as_deref().unwrap_or("heuristic"), seat_houses.len()).map_err(bad)?;
+        let mut seats = Vec::new();
+        let mut tokens = HashMap::new();
+        for (i, (&house, spec)) in seat_houses.iter().zip(&lineup).enumerate() {
+            if external.contains(&house) {
+                let token = format!("{:032x}", rand::thread_rng().gen::<u128>());
+                tokens.insert(house, token.clone());
+                seats.push(Seat { house, agent: "external".to_string(), ai: None, token: Some(token) });
+            } else {
+                let agent = spec.build(house, seed + i as u64).map_err(bad)?;
+                seats.push(Seat { house, agent: spec.to_string(), ai: Some(agent), token: None });
+            }
+        }
+
+        self.next_id += 1;
+        let state = create_game(&config).map_err(bad)?;
+        let mut game = ManagedGame { id: self.next_id, config, state, seats, actions: Vec::new(), annotations: Vec::new() };
+        game.run_agents_counted(&self.metrics)?;
+        let summary = game.summary();
+        self.games.insert(game.id, game);
+        Ok(Created { game: summary, tokens })
+    }
+
+    /// Unfinished games, or every game with `all`.
+    pub fn list(&self, all: bool) -> Vec<GameSummary> {
+        self.games.values()
+            .filter(|g| all || g.state.winner.is_none())
+            .map(ManagedGame::summary)
+            .collect()
+    }
+
+    pub fn summary(&self, id: u64) -> Result<GameSummary, ApiError> {
+        Ok(self.game(id)?.summary())
+    }
+
+    pub fn view(&self, id: u64, house: HouseName, token: Option<&str>) -> Result<PlayerView, ApiError> {
+        let game = self.game(id)?;
+        game.authorize(house, token)?;
+        Ok(player_view(&game.state, house))
+    }
+
+    /// Answer `house`'s pending decision, then let the agents play on.
+    pub fn act(&mut self, id: u64, house: HouseName, token: Option<&str>, action: Action) -> Result<GameSummary, ApiError> {
+        let metrics = self.metrics.clone();
+        let game = self.game_mut(id)?;
+        game.authorize(house, token)?;
+        if game.waiting_for() != Some(house) {
+            return Err(ApiError::new(409, format!("game {} is not waiting for {}", id, house)));
+        }
+        game.apply(house, action).map_err(|e| ApiError::new(422, format!("action refused: {}", e)))?;
+        game.run_agents_counted(&metrics)?;
+        Ok(game.summary())
+    }
+
+    /// Attach a note to the game, one of its actions or one of its events.
+    /// Returns all of the game's annotations.
+    pub fn annotate(&mut self, id: u64, annotation: Annotation) -> Result<Vec<Annotation>, ApiError> {
+        let game = self.game_mut(id)?;
+        match annotation.target {
+            AnnotationTarget::Action { index } if index >= game.actions.len() => {
+                return Err(ApiError::new(422, format!("game {} has {} actions, no action {}", id, game.actions.len(), index)));
+            }
+            AnnotationTarget::Event { seq } if !game.state.events.iter().any(|e| e.seq == seq) => {
+                return Err(ApiError::new(422, format!("game {} has no event {}", id, seq)));
+            }
+            _ => {}
+        }
+        game.annotations.push(annotation);
+        Ok(game.annotations.clone())
+    }
+
+    pub fn log(&self, id: u64) -> Result<GameLog, ApiError> {
+        let game = self.game(id)?;
+        let winner = game.state.winner.ok_or_else(|| ApiError::new(409, format!("game {} is still running", id)))?;
+        Ok(GameLog {
+            version: wire::WIRE_VERSION,
+            id,
+            setup: game.config.clone(),
+            seats: game.seat_summaries(),
+            actions: game.actions.clone(),
+            events: game.state.events.clone(),
+            annotations: game.annotations.clone(),
+            winner,
+            ranking: engine::final_ranking(&game.state),
+        })
+    }
+}
+
+// ── HTTP ───────────────────────────────────────────────────────────────
+
+fn json<T: Serialize>(value: Result<T, ApiError>) -> (u16, String) {
+    match value {
+        Ok(v) => (200, serde_json::to_string(&v).unwrap()),
+        Err(e) => (e.status, serde_json::json!({ "error": e.message }).to_string()),
+    }
+}
+
+fn parse_body<T: for<'de> Deserialize<'de>>(body: &str) -> Result<T, ApiError> {
+    serde_json::from_str(body).map_err(|e| ApiError::new(400, format!("invalid JSON body: {}", e)))
+}
+
+fn parse_id(s: &str) -> Result<u64, ApiError> {
+    s.parse().map_err(|_| ApiError::new(404, format!("no game {}", s)))
+}
+
+fn parse_house(s: &str) -> Result<HouseName, ApiError> {
+    s.parse().map_err(|e: String| ApiError::new(404, e))
+}
+
+/// Route one request. `url` may carry a query string; `token` comes from
+/// the Authorization header. Returns the status and JSON body.
+pub fn handle(store: &Mutex<GameStore>, method: &str, url: &str, token: Option<&str>, body: &str) -> (u16, String) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let param = |key: &str| {
+        query.split('&').find_map(|kv| kv.split_once('=').filter(|(k, _)| *k == key).map(|(_, v)| v))
+    };
+    let token = token.or(param("token"));
+    let parts: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+    let mut store = store.lock().unwrap();
+    match (method, parts.as_slice()) {
+        ("POST", ["games"]) => json(parse_body(body).and_then(|req| store.create(req))),
+        ("GET", ["games"]) => json(Ok(store.list(matches!(param("all"), Some("1" | "true"))))),
+        ("GET", ["games", id]) => json(parse_id(id).and_then(|id| store.summary(id))),
+        ("GET", ["games", id, "view", house]) => json((|| {
+            store.view(parse_id(id)?, parse_house(house)?, token)
+        })()),
+        ("POST", ["games", id, "actions", house]) => json((|| {
+            let action: Action = parse_body(body)?;
+            store.act(parse_id(id)?, parse_house(house)?, token, action)
+        })()),
+        ("POST", ["games", id, "annotations"]) => json((|| {
+            let annotation: Annotation = parse_body(body)?;
+            store.annotate(parse_id(id)?, annotation)
+        })()),
+        ("GET", ["games", id, "log"]) => json(parse_id(id).and_then(|id| store.log(id))),
+        ("GET", ["map"]) => json(Ok(static_map())),
+        _ => json::<()>(Err(ApiError::new(404, format!("no route for {} {}", method, path)))),
+    }
+}
+
+/// Serve the API on `addr` with `threads` worker threads. Blocks forever.
+pub fn serve(addr: &str, threads: usize) -> Result<(), String> {
+    let server = Arc::new(tiny_http::Server::http(addr).map_err(|e| format!("Cannot listen on {}: {}", addr, e))?);
+    let store = Arc::new(Mutex::new(GameStore::new()));
+    let metrics = store.lock().unwrap().metrics();
+    let workers: Vec<_> = (0..threads.max(1))
+        .map(|_| {
+            let (server, store, metrics) = (server.clone(), store.clone(), metrics.clone());
+            std::thread::spawn(move || {
+                for mut request in server.incoming_requests() {
+                    if request.method() == &tiny_http::Method::Get && request.url() == "/metrics" {
+                        let _ = request.respond(crate::metrics::response(&metrics));
+                        continue;
+                    }
+                    let token = request.headers().iter()
+                        .find(|h| h.field.equiv("Authorization"))
+                        .and_then(|h| h.value.as_str().strip_prefix("Bearer ").map(|t| t.trim().to_string()));
+                    let mut body = String::new();
+                    let (status, out) = match request.as_reader().read_to_string(&mut body) {
+                        Ok(_) => handle(&store, request.method().as_str(), request.url(), token.as_deref(), &body),
+                        Err(e) => json::<()>(Err(ApiError::new(400, format!("unreadable body: {}", e)))),
+                    };
+                    let header = tiny_http::Header::from_bytes("Content-Type", "application/json").unwrap();
+                    let response = tiny_http::Response::from_string(out).with_status_code(status).with_header(header);
+                    let _ = request.respond(response);
+                }
+            })
+        })
+        .collect();
+    for w in workers {
+        let _ = w.join();
+    }
+    Ok(())
+}
@@ -0,0 +1,227 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Protocol conformance — certify an external bot before it plays
+//
+// A bot process speaks the lobby protocol (see `lobby`) on its standard
+// input and output, one JSON message per line:
+//
+//   bot ← {"type":"welcome","house":"Stark","seat":0,"map":{...}}
+//   bot ← {"type":"decide","view":{...}}
+//   bot → {"type":"action","action":{...}}
+//   bot ← {"type":"rejected","reason":"..."}          illegal answer
+//   bot ← {"type":"game_over","winner":"Stark","ranking":[...]}
+//
+// The same bot plays in a lobby over TCP by sending a `join` first.
+//
+// `samples` plays random games and keeps real positions for every decision
+// type it meets (some — Patchface, Aeron, Doran — only come up when the
+// card is played, so a few games may miss them). `run` starts the bot once
+// per house, welcomes it, asks it that house's positions and checks every
+// answer parses, arrives in time and is legal in the position's state.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::lobby::{ClientMessage, ServerMessage};
+use got_agents::{Agent, RandomAgent};
+use got_engine::encoding::DecisionKind;
+use got_engine::engine;
+use got_engine::map::static_map;
+use got_engine::setup::{create_game, SetupConfig};
+use got_engine::types::{GameState, HouseName};
+use got_engine::visibility::{player_view, PlayerView};
+use got_tournament::runner::pending_house;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// A position waiting on one house's decision.
+#[derive(Clone)]
+pub struct Sample {
+    pub kind: DecisionKind,
+    pub state: GameState,
+    pub view: PlayerView,
+}
+
+/// Up to `per_kind` positions of every decision type, from random 6-player
+/// games with seeds `seed`.. (at most `games` of them; fewer once every
+/// type is covered).
+pub fn samples(seed: u64, games: usize, per_kind: usize) -> Result<Vec<Sample>, String> {
+    let mut found: Vec<Sample> = Vec::new();
+    let count = |found: &[Sample], kind| found.iter().filter(|s| s.kind == kind).count();
+    for game in 0..games as u64 {
+        if DecisionKind::ALL.iter().all(|&k| count(&found, k) >= per_kind) {
+            break;
+        }
+        let config = SetupConfig::new(6, seed + game);
+        let mut state = create_game(&config)?;
+        let mut agents: Vec<RandomAgent> = config.houses.iter().map(|&h| RandomAgent::new(h, seed + game)).collect();
+        for _ in 0..20_000 {
+            engine::advance(&mut state);
+            if state.winner.is_some() {
+                break;
+            }
+            let Some(pending) = state.peek_pending() else { break };
+            let kind = DecisionKind::of(pending);
+            let house = pending_house(pending);
+            let view = player_view(&state, house);
+            if count(&found, kind) < per_kind {
+                found.push(Sample { kind, state: state.clone(), view: view.clone() });
+            }
+            let agent = agents.iter_mut().find(|a| a.house() == house).ok_or("no agent for the pending house")?;
+            let action = agent.decide(&view);
+            engine::apply_action_as(&mut state, house, action)?;
+        }
+    }
+    found.sort_by_key(|s| s.kind.index());
+    Ok(found)
+}
+
+/// How the bot answered one position.
+#[derive(Debug, Clone)]
+pub struct CaseResult {
+    pub kind: DecisionKind,
+    pub house: HouseName,
+    pub round: u8,
+    /// Why the answer fails, or `Ok`.
+    pub outcome: Result<(), String>,
+    pub elapsed: Duration,
+}
+
+/// A running bot: its input, and its output lines as they arrive.
+struct Bot {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+impl Bot {
+    fn start(command: &str) -> Result<Bot, String> {
+        let mut child = Command::new("sh").arg("-c").arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| format!("Cannot start '{}': {}", command, e))?;
+        let stdin = child.stdin.take().ok_or("no stdin")?;
+        let stdout = child.stdout.take().ok_or("no stdout")?;
+        let (tx, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Bot { child, stdin, lines })
+    }
+
+    fn send(&mut self, msg: &ServerMessage) -> Result<(), String> {
+        let json = serde_json::to_string(msg).map_err(|e| e.to_string())?;
+        writeln!(self.stdin, "{}", json).and_then(|_| self.stdin.flush()).map_err(|e| format!("bot stopped reading: {}", e))
+    }
+
+    /// The next non-empty line within `timeout`.
+    fn recv(&self, timeout: Duration) -> Result<String, String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            match self.lines.recv_timeout(left) {
+                Ok(line) if line.trim().is_empty() => continue,
+                Ok(line) => return Ok(line),
+                Err(RecvTimeoutError::Timeout) => return Err(format!("no answer within {} ms", timeout.as_millis())),
+                Err(RecvTimeoutError::Disconnected) => return Err("bot exited".to_string()),
+            }
+        }
+    }
+
+    fn stop(mut self) {
+        drop(self.stdin);
+        // Give the bot a moment to leave on its own after game_over
+        let deadline = Instant::now() + Duration::from_millis(500);
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Ask `command` every sample, one bot process per house, allowing
+/// `timeout` per answer. A bot that goes silent or exits is started
+/// again for the house's remaining samples.
+pub fn run(command: &str, samples: &[Sample], timeout: Duration) -> Result<Vec<CaseResult>, String> {
+    let mut houses: Vec<HouseName> = samples.iter().map(|s| s.view.viewer).collect();
+    houses.sort_by_key(|&h| h as u8);
+    houses.dedup();
+    let mut results = Vec::new();
+    for house in houses {
+        let seat = samples.iter().find(|s| s.view.viewer == house)
+            .and_then(|s| s.view.playing_houses.iter().position(|&h| h == house))
+            .unwrap_or(0);
+        let welcome = ServerMessage::Welcome { house, seat, map: Box::new(static_map()) };
+        let mut bot: Option<Bot> = None;
+        for sample in samples.iter().filter(|s| s.view.viewer == house) {
+            let started = Instant::now();
+            let mut running = match bot.take() {
+                Some(b) => b,
+                None => {
+                    let mut b = Bot::start(command)?;
+                    // A bot that can't even read its welcome fails below
+                    let _ = b.send(&welcome);
+                    b
+                }
+            };
+            let answer = ask(&mut running, sample, timeout);
+            let elapsed = started.elapsed();
+            let outcome = match answer {
+                Ok(Ok(())) => {
+                    bot = Some(running);
+                    Ok(())
+                }
+                Ok(Err(reason)) => {
+                    // As the lobby would; the bot reads on
+                    let _ = running.send(&ServerMessage::Rejected { reason: reason.clone() });
+                    bot = Some(running);
+                    Err(reason)
+                }
+                Err(reason) => {
+                    running.stop();
+                    Err(reason)
+                }
+            };
+            results.push(CaseResult { kind: sample.kind, house, round: sample.view.round, outcome, elapsed });
+        }
+        if let Some(mut b) = bot {
+            let _ = b.send(&ServerMessage::GameOver { winner: house, ranking: vec![house] });
+            b.stop();
+        }
+    }
+    results.sort_by_key(|r| r.kind.index());
+    Ok(results)
+}
+
+/// The bot's answer to one sample: `Err` when the bot itself failed (no
+/// answer in time, exited), `Ok(Err)` when its answer is wrong.
+fn ask(bot: &mut Bot, sample: &Sample, timeout: Duration) -> Result<Result<(), String>, String> {
+    bot.send(&ServerMessage::Decide { view: Box::new(sample.view.clone()) })?;
+    let line = bot.recv(timeout)?;
+    Ok(match serde_json::from_str::<ClientMessage>(&line) {
+        Ok(ClientMessage::Action { action }) => engine::validate_action(&sample.state, &action).map_err(String::from),
+        Ok(ClientMessage::Join { .. }) => Err("sent join, expected an action".to_string()),
+        Err(e) => Err(format!("unreadable answer ({}): {}", e, truncate(&line))),
+    })
+}
+
+fn truncate(line: &str) -> String {
+    match line.char_indices().nth(120) {
+        Some((i, _)) => format!("{}...", &line[..i]),
+        None => line.to_string(),
+    }
+}
+
+/// Decision types none of `samples` covers.
+pub fn uncovered(samples: &[Sample]) -> Vec<DecisionKind> {
+    DecisionKind::ALL.iter().copied().filter(|&k| !samples.iter().any(|s| s.kind == k)).collect()
+}
@@ -0,0 +1,8 @@
+pub mod lobby;
+pub mod api;
+pub mod schema;
+pub mod conformance;
+pub mod metrics;
+
+#[cfg(test)]
+mod tests;
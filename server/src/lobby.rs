@@ -0,0 +1,478 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Network Lobby — remote players claim houses, the game runs here
+//
+// Clients connect over plain TCP (one JSON message per line) or WebSocket
+// (one JSON message per text frame) on the same port; a connection that
+// opens with an HTTP `GET` is upgraded to WebSocket.
+//
+//   client → {"type":"join","house":"stark","name":"alice"}   house optional
+//   server → {"type":"welcome","house":"Stark","seat":0,"map":{...}}  map: StaticMap
+//   server → {"type":"lobby","seats":[...]}                   after every join
+//   server → {"type":"decide","view":{...}}                   your PlayerView
+//   client → {"type":"action","action":{"type":"bid","value":2}}  engine Action
+//   server → {"type":"rejected","reason":"..."}               then decide again
+//   server → {"type":"game_over","winner":"Stark","ranking":[...]}
+//
+// The game starts once every remote seat is claimed (or the join window
+// closes) and the configured agents fill the rest. Bids are asked of all
+// houses at once, so remote players bid in parallel. A client only ever
+// receives its own PlayerView. If a client disconnects mid-game the
+// heuristic agent plays its house to the end.
+// ═══════════════════════════════════════════════════════════════════════
+
+use got_agents::{Agent, AgentSpec, HeuristicAgent};
+use got_engine::engine::{check_answer, Action, MusterAction2};
+use got_engine::map::{static_map, StaticMap};
+use got_engine::setup::SetupConfig;
+use got_engine::types::*;
+use got_engine::visibility::PlayerView;
+use got_tournament::runner::{run_game_with_bids, ActionPolicy, BidCollection, GameResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tungstenite::{Message, WebSocket};
+
+// ── Protocol ───────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Claim a seat; any open house when `house` is absent.
+    Join { house: Option<String>, name: Option<String> },
+    /// Answer to the last `decide`.
+    Action { action: Action },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// The seat claimed, and the map once so clients need no copy of their own.
+    Welcome { house: HouseName, seat: usize, map: Box<StaticMap> },
+    Lobby { seats: Vec<SeatInfo> },
+    Decide { view: Box<PlayerView> },
+    Rejected { reason: String },
+    GameOver { winner: HouseName, ranking: Vec<HouseName> },
+    Error { message: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SeatInfo {
+    pub house: HouseName,
+    /// Remote player holding the seat; None for open or AI seats.
+    pub player: Option<String>,
+    /// Whether the seat is waiting for a remote player.
+    pub open: bool,
+}
+
+// ── Transport ──────────────────────────────────────────────────────────
+
+/// A message stream to one client: lines over TCP or WebSocket text frames.
+pub trait Connection: Send {
+    fn send_text(&mut self, text: &str) -> io::Result<()>;
+    /// Next message; None once the peer has closed the connection.
+    fn recv_text(&mut self) -> io::Result<Option<String>>;
+}
+
+pub struct LineConnection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl LineConnection {
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        Ok(LineConnection { reader: BufReader::new(stream.try_clone()?), writer: stream })
+    }
+}
+
+impl Connection for LineConnection {
+    fn send_text(&mut self, text: &str) -> io::Result<()> {
+        writeln!(self.writer, "{}", text)?;
+        self.writer.flush()
+    }
+
+    fn recv_text(&mut self) -> io::Result<Option<String>> {
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            if !line.trim().is_empty() {
+                return Ok(Some(line.trim().to_string()));
+            }
+        }
+    }
+}
+
+pub struct WsConnection(WebSocket<TcpStream>);
+
+impl Connection for WsConnection {
+    fn send_text(&mut self, text: &str) -> io::Result<()> {
+        self.0.send(Message::Text(text.to_string())).map_err(io::Error::other)
+    }
+
+    fn recv_text(&mut self) -> io::Result<Option<String>> {
+        loop {
+            match self.0.read() {
+                Ok(Message::Text(t)) => return Ok(Some(t)),
+                Ok(Message::Close(_)) => return Ok(None),
+                Ok(_) => continue,
+                Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => return Ok(None),
+                Err(e) => return Err(io::Error::other(e)),
+            }
+        }
+    }
+}
+
+/// Wrap an accepted stream, upgrading to WebSocket if it opens with `GET `.
+pub fn accept_connection(stream: TcpStream) -> io::Result<Box<dyn Connection>> {
+    let mut head = [0u8; 4];
+    let n = stream.peek(&mut head)?;
+    if n == 4 && &head == b"GET " {
+        let ws = tungstenite::accept(stream).map_err(|e| io::Error::other(e.to_string()))?;
+        Ok(Box::new(WsConnection(ws)))
+    } else {
+        Ok(Box::new(LineConnection::new(stream)?))
+    }
+}
+
+fn send(conn: &mut dyn Connection, msg: &ServerMessage) -> io::Result<()> {
+    conn.send_text(&serde_json::to_string(msg).map_err(io::Error::other)?)
+}
+
+// ── Remote seats ───────────────────────────────────────────────────────
+
+type SharedConnection = Arc<Mutex<Box<dyn Connection>>>;
+
+/// A house played by a remote client. Falls back to the heuristic agent
+/// for the rest of the game once the client is gone.
+pub struct RemoteAgent {
+    house: HouseName,
+    conn: SharedConnection,
+    connected: bool,
+    fallback: HeuristicAgent,
+}
+
+impl RemoteAgent {
+    fn new(house: HouseName, conn: SharedConnection, seed: u64) -> Self {
+        RemoteAgent { house, conn, connected: true, fallback: HeuristicAgent::new(house, seed) }
+    }
+
+    /// Send the view and wait for an action that fits the pending decision.
+    fn ask(&mut self, view: &PlayerView) -> Option<Action> {
+        let pending = view.pending.as_ref()?;
+        let mut conn = self.conn.lock().unwrap();
+        loop {
+            send(conn.as_mut(), &ServerMessage::Decide { view: Box::new(view.clone()) }).ok()?;
+            let text = conn.recv_text().ok()??;
+            let reason = match serde_json::from_str::<ClientMessage>(&text) {
+                Ok(ClientMessage::Action { action }) => match check_answer(pending, &action) {
+                    Ok(()) => return Some(action),
+                    Err(e) => e.to_string(),
+                },
+                Ok(other) => format!("expected an action, got {:?}", other),
+                Err(e) => format!("invalid message: {}", e),
+            };
+            send(conn.as_mut(), &ServerMessage::Rejected { reason }).ok()?;
+        }
+    }
+}
+
+impl Agent for RemoteAgent {
+    fn name(&self) -> &str { "remote" }
+    fn house(&self) -> HouseName { self.house }
+
+    fn decide(&mut self, view: &PlayerView) -> Action {
+        if self.connected {
+            match self.ask(view) {
+                Some(action) => return action,
+                None => self.connected = false,
+            }
+        }
+        self.fallback.decide(view)
+    }
+
+    // Remote answers arrive as whole actions through `decide`; the
+    // per-decision methods are only the disconnected fallback.
+
+    fn place_orders(&mut self, view: &PlayerView) -> Vec<(AreaId, u8)> {
+        self.fallback.place_orders(view)
+    }
+
+    fn choose_order_to_resolve(&mut self, view: &PlayerView, order_type: OrderType, candidates: &[AreaId]) -> AreaId {
+        self.fallback.choose_order_to_resolve(view, order_type, candidates)
+    }
+
+    fn choose_raid(&mut self, view: &PlayerView, from: AreaId, targets: &[AreaId]) -> Option<AreaId> {
+        self.fallback.choose_raid(view, from, targets)
+    }
+
+    fn choose_march(&mut self, view: &PlayerView, from: AreaId, destinations: &[AreaId], selectable: &[usize]) -> (AreaId, Vec<usize>) {
+        self.fallback.choose_march(view, from, destinations, selectable)
+    }
+
+    fn leave_power_token(&mut self, view: &PlayerView, area: AreaId) -> bool {
+        self.fallback.leave_power_token(view, area)
+    }
+
+    fn declare_support(&mut self, view: &PlayerView, attacker: HouseName, defender: HouseName) -> SupportChoice {
+        self.fallback.declare_support(view, attacker, defender)
+    }
+
+    fn select_house_card(&mut self, view: &PlayerView, available: &[HouseCardId]) -> HouseCardId {
+        self.fallback.select_house_card(view, available)
+    }
+
+    fn use_valyrian_blade(&mut self, view: &PlayerView) -> bool {
+        self.fallback.use_valyrian_blade(view)
+    }
+
+    fn submit_bid(&mut self, view: &PlayerView, bid_type: BiddingType, track: Option<Track>) -> u8 {
+        self.fallback.submit_bid(view, bid_type, track)
+    }
+
+    fn westeros_choice(&mut self, view: &PlayerView, options: &[String]) -> usize {
+        self.fallback.westeros_choice(view, options)
+    }
+
+    fn choose_muster(&mut self, view: &PlayerView, areas: &[MusterArea]) -> Vec<(AreaId, MusterAction2)> {
+        self.fallback.choose_muster(view, areas)
+    }
+
+    fn choose_retreat(&mut self, view: &PlayerView, options: &[AreaId]) -> AreaId {
+        self.fallback.choose_retreat(view, options)
+    }
+
+    fn choose_reconcile(&mut self, view: &PlayerView, area: AreaId) -> (AreaId, usize) {
+        self.fallback.choose_reconcile(view, area)
+    }
+
+    fn use_messenger_raven(&mut self, view: &PlayerView) -> Option<(AreaId, u8)> {
+        self.fallback.use_messenger_raven(view)
+    }
+
+    fn use_aeron(&mut self, view: &PlayerView, options: &[HouseCardId]) -> Option<HouseCardId> {
+        self.fallback.use_aeron(view, options)
+    }
+
+    fn tyrion_replacement(&mut self, view: &PlayerView, options: &[HouseCardId]) -> HouseCardId {
+        self.fallback.tyrion_replacement(view, options)
+    }
+
+    fn patchface_discard(&mut self, view: &PlayerView, visible: &[HouseCardId]) -> HouseCardId {
+        self.fallback.patchface_discard(view, visible)
+    }
+
+    fn robb_retreat(&mut self, view: &PlayerView, options: &[AreaId]) -> AreaId {
+        self.fallback.robb_retreat(view, options)
+    }
+
+    fn wildling_penalty(&mut self, view: &PlayerView, options: &[String]) -> usize {
+        self.fallback.wildling_penalty(view, options)
+    }
+
+    fn cersei_remove_order(&mut self, view: &PlayerView) -> AreaId {
+        self.fallback.cersei_remove_order(view)
+    }
+
+    fn doran_choose_track(&mut self, view: &PlayerView) -> Track {
+        self.fallback.doran_choose_track(view)
+    }
+
+    fn queen_of_thorns(&mut self, view: &PlayerView) -> AreaId {
+        self.fallback.queen_of_thorns(view)
+    }
+}
+
+// ── Lobby ──────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+pub struct LobbyOptions {
+    pub addr: String,
+    pub config: SetupConfig,
+    /// Seats held open for remote players.
+    pub remote_seats: usize,
+    /// Agent for each seat (in seat order) not taken by a remote player.
+    pub lineup: Vec<AgentSpec>,
+    /// Start with whoever has joined after this long; None waits for all.
+    pub join_window: Option<Duration>,
+    pub max_decisions: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct RemotePlayer {
+    pub house: HouseName,
+    pub name: String,
+}
+
+struct Claim {
+    player: RemotePlayer,
+    conn: SharedConnection,
+}
+
+/// Accept players until the remote seats are claimed, play the game and
+/// tell every client the result. `on_event` receives progress lines.
+pub fn run_lobby(opts: &LobbyOptions, mut on_event: impl FnMut(&str)) -> Result<(GameResult, Vec<RemotePlayer>), String> {
+    let seats = opts.config.seats();
+    if opts.remote_seats > seats.len() {
+        return Err(format!("{} remote seats requested for a {}-player game", opts.remote_seats, seats.len()));
+    }
+    let listener = TcpListener::bind(&opts.addr).map_err(|e| format!("Cannot listen on {}: {}", opts.addr, e))?;
+    let local = listener.local_addr().map_err(|e| e.to_string())?;
+    on_event(&format!("Lobby open on {} — waiting for {} player(s)", local, opts.remote_seats));
+
+    let deadline = opts.join_window.map(|w| Instant::now() + w);
+    listener.set_nonblocking(deadline.is_some()).map_err(|e| e.to_string())?;
+    let mut claims: Vec<Claim> = Vec::new();
+    while claims.len() < opts.remote_seats {
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                if deadline.is_some_and(|d| Instant::now() >= d) {
+                    on_event(&format!("Join window closed with {} of {} player(s)", claims.len(), opts.remote_seats));
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(50));
+                continue;
+            }
+            Err(e) => return Err(format!("Accept failed: {}", e)),
+        };
+        let _ = stream.set_nonblocking(false);
+        match join(stream, &seats, &claims) {
+            Ok(claim) => {
+                on_event(&format!("{} joined as {}", claim.player.name, claim.player.house));
+                claims.push(claim);
+                broadcast(&claims, &ServerMessage::Lobby { seats: seat_infos(&seats, &claims, opts.remote_seats) });
+            }
+            Err(e) => on_event(&format!("Join refused: {}", e)),
+        }
+    }
+
+    // ── Seat the game ──
+    let mut agents: HashMap<HouseName, Box<dyn Agent>> = HashMap::new();
+    let mut fill = opts.lineup.iter().cycle();
+    for (seat, &house) in seats.iter().enumerate() {
+        let seed = opts.config.seed + seat as u64;
+        let agent: Box<dyn Agent> = match claims.iter().find(|c| c.player.house == house) {
+            Some(c) => Box::new(RemoteAgent::new(house, c.conn.clone(), seed)),
+            None => fill.next().ok_or("Empty lineup for the AI seats")?.build(house, seed)?,
+        };
+        agents.insert(house, agent);
+    }
+    on_event("Game started");
+    // Remote players bid at once rather than waiting on each other
+    let result = run_game_with_bids(&mut agents, &opts.config, opts.max_decisions, ActionPolicy::Lenient, BidCollection::Simultaneous);
+    match &result {
+        Ok(r) => broadcast(&claims, &ServerMessage::GameOver { winner: r.winner, ranking: r.final_ranking.clone() }),
+        Err(e) => broadcast(&claims, &ServerMessage::Error { message: e.clone() }),
+    }
+    Ok((result?, claims.into_iter().map(|c| c.player).collect()))
+}
+
+/// Read the client's join request and give it a seat.
+fn join(stream: TcpStream, seats: &[HouseName], claims: &[Claim]) -> Result<Claim, String> {
+    let peer = stream.peer_addr().map_or("?".to_string(), |a| a.to_string());
+    stream.set_read_timeout(Some(Duration::from_secs(30))).map_err(|e| e.to_string())?;
+    let mut conn = accept_connection(stream.try_clone().map_err(|e| e.to_string())?).map_err(|e| format!("{}: {}", peer, e))?;
+    let outcome = (|| {
+        let text = conn.recv_text().map_err(|e| e.to_string())?.ok_or("closed before joining")?;
+        let (house, name) = match serde_json::from_str::<ClientMessage>(&text) {
+            Ok(ClientMessage::Join { house, name }) => (house, name),
+            Ok(_) => return Err("expected a join message".to_string()),
+            Err(e) => return Err(format!("invalid message: {}", e)),
+        };
+        let taken = |h: HouseName| claims.iter().any(|c| c.player.house == h);
+        let house = match house {
+            Some(h) => {
+                let h: HouseName = h.parse()?;
+                if !seats.contains(&h) {
+                    return Err(format!("{} is not playing in this game", h));
+                }
+                if taken(h) {
+                    return Err(format!("{} is already taken", h));
+                }
+                h
+            }
+            None => *seats.iter().find(|&&h| !taken(h)).ok_or("no open seats")?,
+        };
+        Ok((house, name.unwrap_or_else(|| peer.clone())))
+    })();
+    match outcome {
+        Ok((house, name)) => {
+            let seat = seats.iter().position(|&h| h == house).unwrap();
+            send(conn.as_mut(), &ServerMessage::Welcome { house, seat, map: Box::new(static_map()) }).map_err(|e| e.to_string())?;
+            // Decisions can take as long as the player needs
+            stream.set_read_timeout(None).map_err(|e| e.to_string())?;
+            Ok(Claim { player: RemotePlayer { house, name }, conn: Arc::new(Mutex::new(conn)) })
+        }
+        Err(message) => {
+            let _ = send(conn.as_mut(), &ServerMessage::Error { message: message.clone() });
+            Err(format!("{}: {}", peer, message))
+        }
+    }
+}
+
+fn seat_infos(seats: &[HouseName], claims: &[Claim], remote_seats: usize) -> Vec<SeatInfo> {
+    let open = remote_seats.saturating_sub(claims.len());
+    let mut unclaimed = 0;
+    seats.iter()
+        .map(|&house| {
+            let player = claims.iter().find(|c| c.player.house == house).map(|c| c.player.name.clone());
+            let open = player.is_none() && {
+                unclaimed += 1;
+                unclaimed <= open
+            };
+            SeatInfo { house, player, open }
+        })
+        .collect()
+}
+
+fn broadcast(claims: &[Claim], msg: &ServerMessage) {
+    for c in claims {
+        let _ = send(c.conn.lock().unwrap().as_mut(), msg);
+    }
+}
+
+// ── Client ─────────────────────────────────────────────────────────────
+
+/// Join a lobby over TCP and play the assigned house with a local agent,
+/// built by `make_agent` once the seat is known. `on_message` sees every
+/// server message. Returns the final ranking.
+pub fn connect(
+    addr: &str,
+    house: Option<&str>,
+    name: Option<&str>,
+    make_agent: impl FnOnce(HouseName) -> Result<Box<dyn Agent>, String>,
+    mut on_message: impl FnMut(&ServerMessage),
+) -> Result<Vec<HouseName>, String> {
+    let stream = TcpStream::connect(addr).map_err(|e| format!("Cannot connect to {}: {}", addr, e))?;
+    let mut conn = LineConnection::new(stream).map_err(|e| e.to_string())?;
+    let join = ClientMessage::Join { house: house.map(String::from), name: name.map(String::from) };
+    conn.send_text(&serde_json::to_string(&join).unwrap()).map_err(|e| e.to_string())?;
+
+    let mut make_agent = Some(make_agent);
+    let mut agent: Option<Box<dyn Agent>> = None;
+    loop {
+        let text = conn.recv_text().map_err(|e| e.to_string())?.ok_or("Server closed the connection")?;
+        let msg: ServerMessage = serde_json::from_str(&text).map_err(|e| format!("Bad server message: {}", e))?;
+        on_message(&msg);
+        match msg {
+            ServerMessage::Welcome { house, .. } => {
+                if let Some(make) = make_agent.take() {
+                    agent = Some(make(house)?);
+                }
+            }
+            ServerMessage::Decide { view } => {
+                let agent = agent.as_mut().ok_or("Asked to decide before being seated")?;
+                let reply = ClientMessage::Action { action: agent.decide(&view) };
+                conn.send_text(&serde_json::to_string(&reply).unwrap()).map_err(|e| e.to_string())?;
+            }
+            ServerMessage::GameOver { ranking, .. } => return Ok(ranking),
+            ServerMessage::Error { message } => return Err(message),
+            ServerMessage::Lobby { .. } | ServerMessage::Rejected { .. } => {}
+        }
+    }
+}
@@ -0,0 +1,32 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Metrics over HTTP — `GET /metrics` in the Prometheus text format
+//
+// The HTTP API answers it on its own port; a tournament daemon, which has
+// no server of its own, starts `spawn` on the address it is given.
+// ═══════════════════════════════════════════════════════════════════════
+
+use got_tournament::metrics::Metrics;
+use std::io::Cursor;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// The response to a scrape.
+pub fn response(metrics: &Metrics) -> tiny_http::Response<Cursor<Vec<u8>>> {
+    let header = tiny_http::Header::from_bytes("Content-Type", "text/plain; version=0.0.4").unwrap();
+    tiny_http::Response::from_string(metrics.render()).with_header(header)
+}
+
+/// Serve `metrics` at `addr` on a background thread: `GET /metrics`, 404
+/// for anything else.
+pub fn spawn(addr: &str, metrics: Arc<Metrics>) -> Result<JoinHandle<()>, String> {
+    let server = tiny_http::Server::http(addr).map_err(|e| format!("Cannot listen on {}: {}", addr, e))?;
+    Ok(std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let _ = if request.method() == &tiny_http::Method::Get && request.url() == "/metrics" {
+                request.respond(response(&metrics))
+            } else {
+                request.respond(tiny_http::Response::from_string("not found").with_status_code(404))
+            };
+        }
+    }))
+}
@@ -0,0 +1,164 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Wire schemas — JSON Schema for every type crossing the process boundary
+//
+// Bot authors and UI developers get a machine-checkable contract for the
+// views they receive and the actions they send, generated from the Rust
+// types themselves (schemars) so it cannot drift from the serde derives.
+// `openapi()` describes the HTTP API with the same definitions. The copies
+// in `schemas/` are regenerated with `got-runner schema --out schemas`.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::api::{CreateGame, Created, GameLog, GameSummary};
+use crate::lobby::{ClientMessage, ServerMessage};
+use got_engine::engine::Action;
+use got_engine::map::StaticMap;
+use got_engine::replay::{Annotation, Replay};
+use got_engine::types::{GameEvent, PendingDecision};
+use got_engine::visibility::PlayerView;
+use got_tournament::map_balance::MapHeatmap;
+use got_tournament::runner::GameResult;
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+use serde_json::{json, Value};
+
+/// Standalone JSON Schema for each wire type, by type name.
+pub fn schemas() -> Vec<(&'static str, RootSchema)> {
+    vec![
+        ("PlayerView", schema_for!(PlayerView)),
+        ("PendingDecision", schema_for!(PendingDecision)),
+        ("Action", schema_for!(Action)),
+        ("GameEvent", schema_for!(GameEvent)),
+        ("Replay", schema_for!(Replay)),
+        ("Annotation", schema_for!(Annotation)),
+        ("GameResult", schema_for!(GameResult)),
+        ("CreateGame", schema_for!(CreateGame)),
+        ("Created", schema_for!(Created)),
+        ("GameSummary", schema_for!(GameSummary)),
+        ("GameLog", schema_for!(GameLog)),
+        ("ClientMessage", schema_for!(ClientMessage)),
+        ("ServerMessage", schema_for!(ServerMessage)),
+        ("StaticMap", schema_for!(StaticMap)),
+        ("MapHeatmap", schema_for!(MapHeatmap)),
+    ]
+}
+
+/// OpenAPI 3.0 description of the HTTP API (see `api`).
+pub fn openapi() -> Value {
+    let mut generator = SchemaGenerator::new(SchemaSettings::openapi3());
+    // Registers each type (and everything it contains) as a definition
+    generator.subschema_for::<CreateGame>();
+    generator.subschema_for::<Created>();
+    generator.subschema_for::<GameSummary>();
+    generator.subschema_for::<PlayerView>();
+    generator.subschema_for::<Action>();
+    generator.subschema_for::<GameLog>();
+    generator.subschema_for::<Annotation>();
+    generator.subschema_for::<StaticMap>();
+    let mut components = serde_json::to_value(generator.take_definitions()).unwrap();
+    components["Error"] = json!({
+        "type": "object",
+        "required": ["error"],
+        "properties": { "error": { "type": "string" } },
+    });
+
+    let schema = |name: &str| json!({ "$ref": format!("#/components/schemas/{}", name) });
+    let ok = |description: &str, body: Value| json!({
+        "description": description,
+        "content": { "application/json": { "schema": body } },
+    });
+    let error = |description: &str| ok(description, schema("Error"));
+    let id = json!({ "name": "id", "in": "path", "required": true, "schema": { "type": "integer", "format": "uint64" } });
+    let house = json!({ "name": "house", "in": "path", "required": true, "schema": { "type": "string" },
+        "description": "House name, case-insensitive" });
+    let body = |name: &str| json!({ "required": true, "content": { "application/json": { "schema": schema(name) } } });
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Game of Thrones Strategy Lab",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Create games, play houses through their PlayerView and download finished game logs.",
+        },
+        "components": {
+            "schemas": components,
+            "securitySchemes": { "seatToken": { "type": "http", "scheme": "bearer" } },
+        },
+        "paths": {
+            "/games": {
+                "get": {
+                    "summary": "List active games (all games with ?all=1)",
+                    "parameters": [{ "name": "all", "in": "query", "schema": { "type": "string" } }],
+                    "responses": { "200": ok("Game summaries", json!({ "type": "array", "items": schema("GameSummary") })) },
+                },
+                "post": {
+                    "summary": "Create a game",
+                    "requestBody": body("CreateGame"),
+                    "responses": {
+                        "200": ok("The game and one token per external house", schema("Created")),
+                        "400": error("Invalid setup"),
+                    },
+                },
+            },
+            "/games/{id}": {
+                "get": {
+                    "summary": "One game's summary",
+                    "parameters": [id],
+                    "responses": { "200": ok("Summary", schema("GameSummary")), "404": error("No such game") },
+                },
+            },
+            "/games/{id}/view/{house}": {
+                "get": {
+                    "summary": "A house's PlayerView",
+                    "parameters": [id, house],
+                    "security": [{ "seatToken": [] }],
+                    "responses": {
+                        "200": ok("What the house may see", schema("PlayerView")),
+                        "401": error("Missing or wrong token"),
+                        "403": error("House is played by an agent"),
+                        "404": error("No such game or house"),
+                    },
+                },
+            },
+            "/games/{id}/actions/{house}": {
+                "post": {
+                    "summary": "Answer the house's pending decision",
+                    "parameters": [id, house],
+                    "security": [{ "seatToken": [] }],
+                    "requestBody": body("Action"),
+                    "responses": {
+                        "200": ok("Summary after the agents have played on", schema("GameSummary")),
+                        "401": error("Missing or wrong token"),
+                        "409": error("Not this house's turn"),
+                        "422": error("Action does not answer the pending decision"),
+                    },
+                },
+            },
+            "/map": {
+                "get": {
+                    "summary": "The board: every area with its adjacency, icons and ports",
+                    "responses": { "200": ok("Areas by id, as indexed in PlayerView", schema("StaticMap")) },
+                },
+            },
+            "/games/{id}/annotations": {
+                "post": {
+                    "summary": "Attach a note to the game, one of its actions or one of its events",
+                    "parameters": [id],
+                    "requestBody": body("Annotation"),
+                    "responses": {
+                        "200": ok("All of the game's annotations", json!({ "type": "array", "items": schema("Annotation") })),
+                        "404": error("No such game"),
+                        "422": error("No such action or event"),
+                    },
+                },
+            },
+            "/games/{id}/log": {
+                "get": {
+                    "summary": "Setup, actions, events and annotations of a finished game",
+                    "parameters": [id],
+                    "responses": { "200": ok("Game log", schema("GameLog")), "409": error("Game still running") },
+                },
+            },
+        },
+    })
+}
@@ -0,0 +1,111 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Tests for the HTTP API, through its request handler
+// ═══════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use crate::api::{handle, Created, GameStore, GameSummary};
+    use got_agents::random::RandomAgent;
+    use got_agents::Agent;
+    use got_engine::encoding::DecisionKind;
+    use got_engine::engine::{Action, ActionError};
+    use got_engine::types::*;
+    use got_engine::visibility::PlayerView;
+    use std::sync::Mutex;
+
+    /// A three-player game with Stark played over the API.
+    fn created(store: &Mutex<GameStore>) -> Created {
+        let body = r#"{"seed": 7, "players": 3, "agents": "random", "external": ["Stark"]}"#;
+        let (status, out) = handle(store, "POST", "/games", None, body);
+        assert_eq!(status, 200, "{}", out);
+        serde_json::from_str(&out).unwrap()
+    }
+
+    fn error(out: &str) -> String {
+        let body: serde_json::Value = serde_json::from_str(out).unwrap();
+        body["error"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_views_and_actions_need_the_house_token() {
+        let store = Mutex::new(GameStore::new());
+        let created = created(&store);
+        let id = created.game.id;
+        let token = created.tokens[&HouseName::Stark].as_str();
+        let view_url = format!("/games/{}/view/Stark", id);
+
+        let (status, out) = handle(&store, "GET", &view_url, None, "");
+        assert_eq!(status, 401);
+        assert_eq!(error(&out), "missing or wrong token for Stark");
+        assert_eq!(handle(&store, "GET", &view_url, Some("not-the-token"), "").0, 401);
+        let body = serde_json::to_string(&Action::MarchSkip).unwrap();
+        let (status, _) = handle(&store, "POST", &format!("/games/{}/actions/Stark", id), None, &body);
+        assert_eq!(status, 401);
+        // Agent-played houses have no token to present
+        let (status, out) = handle(&store, "GET", &format!("/games/{}/view/Lannister", id), Some(token), "");
+        assert_eq!(status, 403);
+        assert_eq!(error(&out), "Lannister is played by an agent");
+
+        // The token works from the header or the query string
+        assert_eq!(handle(&store, "GET", &view_url, Some(token), "").0, 200);
+        assert_eq!(handle(&store, "GET", &format!("{}?token={}", view_url, token), None, "").0, 200);
+    }
+
+    #[test]
+    fn test_create_view_and_act_round_trip() {
+        let store = Mutex::new(GameStore::new());
+        let created = created(&store);
+        let id = created.game.id;
+        let token = created.tokens[&HouseName::Stark].as_str();
+        assert_eq!(created.game.waiting_for, Some(HouseName::Stark));
+
+        let mut agent = RandomAgent::new(HouseName::Stark, 7);
+        let mut decisions = created.game.decisions;
+        for _ in 0..5 {
+            let (status, out) = handle(&store, "GET", &format!("/games/{}/view/Stark", id), Some(token), "");
+            assert_eq!(status, 200, "{}", out);
+            let view: PlayerView = serde_json::from_str(&out).unwrap();
+            assert!(view.pending.is_some());
+
+            let body = serde_json::to_string(&agent.decide(&view)).unwrap();
+            let (status, out) = handle(&store, "POST", &format!("/games/{}/actions/Stark", id), Some(token), &body);
+            assert_eq!(status, 200, "{}", out);
+            let summary: GameSummary = serde_json::from_str(&out).unwrap();
+            assert!(summary.decisions > decisions);
+            decisions = summary.decisions;
+            if summary.finished {
+                break;
+            }
+            assert_eq!(summary.waiting_for, Some(HouseName::Stark));
+        }
+
+        let (status, out) = handle(&store, "GET", &format!("/games/{}", id), None, "");
+        assert_eq!(status, 200);
+        let summary: GameSummary = serde_json::from_str(&out).unwrap();
+        assert_eq!(summary.decisions, decisions);
+    }
+
+    #[test]
+    fn test_illegal_action_is_refused_with_the_engine_error() {
+        let store = Mutex::new(GameStore::new());
+        let created = created(&store);
+        let id = created.game.id;
+        let token = created.tokens[&HouseName::Stark].as_str();
+        let url = format!("/games/{}/actions/Stark", id);
+
+        // Round 1 opens with orders, not a bid
+        let body = serde_json::to_string(&Action::Bid(3)).unwrap();
+        let (status, out) = handle(&store, "POST", &url, Some(token), &body);
+        assert_eq!(status, 422);
+        let refused = ActionError::WrongDecisionType { pending: DecisionKind::PlaceOrders, action: DecisionKind::Bidding };
+        assert_eq!(error(&out), format!("action refused: {}", refused));
+
+        // Nothing was played, and bodies that are not actions are a 400
+        let (_, out) = handle(&store, "GET", &format!("/games/{}", id), None, "");
+        let summary: GameSummary = serde_json::from_str(&out).unwrap();
+        assert_eq!(summary.decisions, created.game.decisions);
+        assert_eq!(summary.waiting_for, Some(HouseName::Stark));
+        assert_eq!(handle(&store, "POST", &url, Some(token), "{").0, 400);
+    }
+}
@@ -0,0 +1,238 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Decision analysis — blunder detection against a reference agent
+//
+// Every recorded decision of a replay is revisited in its position. The
+// reference agent says what it would play there; the played action, the
+// reference's choice and its best-scored alternatives are then valued by
+// playouts: the game continues from the position after that action with
+// the reference agent in every seat, and the deciding house's placing
+// (1.0 for first down to 0.0 for last) is averaged. All candidates of a
+// decision share their playout seeds, so their values are paired.
+//
+// Analysis happens after the game and starts from the true position,
+// hands included. The undrawn Westeros and wildling decks are reshuffled
+// for each playout so values don't rest on card order nobody could know.
+// A decision is a blunder when the best candidate beats the played
+// action by at least the threshold.
+//
+// With zero playouts the candidates are valued by the static evaluator
+// instead (`engine::eval`): the deciding house's win probability in the
+// position right after the action. Much faster, and blind to anything the
+// evaluator's features do not see. In between, a rollout limit cuts the
+// playouts short (`engine::rollout`) and values them by the evaluator's
+// win probability where they stop.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::runner::play_out;
+use got_agents::{Agent, AgentSpec};
+use got_engine::engine::{self, action_fits, Action};
+use got_engine::eval;
+use got_engine::rollout::{reshuffle_hidden, RolloutLimit};
+use got_engine::replay::{Annotation, AnnotationTarget, Replay};
+use got_engine::setup::create_game;
+use got_engine::types::*;
+use got_engine::visibility::player_view;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct AnalyzeOptions {
+    /// Agent that proposes alternatives and plays the playouts.
+    pub reference: AgentSpec,
+    /// Playouts per candidate action; 0 values candidates with the
+    /// static evaluator.
+    pub playouts: usize,
+    /// Where playouts stop; short of the end they are valued by win
+    /// probability instead of placing.
+    pub rollout: RolloutLimit,
+    /// Best-scored alternatives from the reference's decision info to value
+    /// besides its own choice.
+    pub alternatives: usize,
+    /// Value loss at which a decision is flagged.
+    pub threshold: f64,
+    /// Only analyze this house's decisions.
+    pub house: Option<HouseName>,
+    pub seed: u64,
+    pub threads: usize,
+}
+
+/// A decision where the reference saw at least one alternative.
+#[derive(Debug, Clone)]
+pub struct DecisionReport {
+    pub index: usize,
+    pub round: u8,
+    pub house: HouseName,
+    pub played: Action,
+    pub played_value: f64,
+    pub best: Action,
+    pub best_value: f64,
+    /// Candidates valued, the played action included.
+    pub candidates: usize,
+}
+
+impl DecisionReport {
+    /// How much placing the played action gave away against the best candidate.
+    pub fn loss(&self) -> f64 {
+        (self.best_value - self.played_value).max(0.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    /// Decisions looked at (after the house filter).
+    pub examined: usize,
+    /// Decisions where the reference offered something other than the
+    /// played action, in game order.
+    pub contested: Vec<DecisionReport>,
+    pub threshold: f64,
+}
+
+impl Analysis {
+    pub fn blunders(&self) -> impl Iterator<Item = &DecisionReport> {
+        self.contested.iter().filter(|r| r.loss() >= self.threshold)
+    }
+
+    /// Add one annotation per contested decision to `replay`, marking the
+    /// blunders in the text.
+    pub fn annotate(&self, replay: &mut Replay, author: &str) {
+        for r in &self.contested {
+            let mut note = Annotation::new(AnnotationTarget::Action { index: r.index }, author)
+                .with("played_value", r.played_value)
+                .with("best_value", r.best_value)
+                .with("loss", r.loss())
+                .with("best_action", serde_json::to_value(&r.best).unwrap());
+            if r.loss() >= self.threshold {
+                note = note.with_text("blunder");
+            }
+            replay.annotate(note);
+        }
+    }
+}
+
+/// Value the decisions of `replay` against `opts.reference`.
+pub fn analyze(replay: &Replay, opts: &AnalyzeOptions, max_decisions: usize) -> Result<Analysis, String> {
+    use rayon::prelude::*;
+    opts.reference.build(HouseName::Stark, 0)?;
+
+    // The position before every decision, checked against the recording
+    let mut positions = Vec::new();
+    let mut state = create_game(&replay.setup)?;
+    for recorded in &replay.actions {
+        engine::advance(&mut state);
+        let pending = state.peek_pending()
+            .ok_or_else(|| format!("action {}: the game is not waiting for a decision", recorded.index))?;
+        if pending.house() != recorded.house || !action_fits(pending, &recorded.action) {
+            return Err(format!("action {} does not match the replayed game", recorded.index));
+        }
+        if opts.house.is_none_or(|h| h == recorded.house) {
+            positions.push((recorded, state.clone()));
+        }
+        engine::apply_action(&mut state, recorded.action.clone())
+            .map_err(|e| format!("action {}: {}", recorded.index, e))?;
+    }
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(opts.threads)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let reports: Vec<Option<DecisionReport>> = pool.install(|| {
+        positions.par_iter()
+            .map(|(recorded, state)| {
+                let house = recorded.house;
+                let candidates = candidates(state, house, &recorded.action, opts, recorded.index as u64);
+                if candidates.len() < 2 {
+                    return None;
+                }
+                let values: Vec<f64> = candidates.iter()
+                    .map(|action| value(state, house, action, opts, recorded.index as u64, max_decisions))
+                    .collect();
+                let best = (0..values.len()).max_by(|&a, &b| values[a].total_cmp(&values[b])).unwrap();
+                Some(DecisionReport {
+                    index: recorded.index,
+                    round: recorded.round,
+                    house,
+                    played: recorded.action.clone(),
+                    played_value: values[0],
+                    best: candidates[best].clone(),
+                    best_value: values[best],
+                    candidates: candidates.len(),
+                })
+            })
+            .collect()
+    });
+    Ok(Analysis { examined: positions.len(), contested: reports.into_iter().flatten().collect(), threshold: opts.threshold })
+}
+
+/// The played action first, then the reference's choice and alternatives,
+/// without duplicates.
+fn candidates(state: &GameState, house: HouseName, played: &Action, opts: &AnalyzeOptions, salt: u64) -> Vec<Action> {
+    let view = player_view(state, house);
+    let Ok(mut agent) = opts.reference.build(house, opts.seed ^ salt) else {
+        return vec![played.clone()];
+    };
+    let choice = agent.decide(&view);
+    let alternatives: Vec<Action> = agent.last_decision_info()
+        .map(|info| info.candidates.into_iter().take(opts.alternatives).map(|c| c.action).collect())
+        .unwrap_or_default();
+
+    let pending = state.peek_pending().expect("position waits for a decision");
+    let mut keys = Vec::new();
+    let mut out = Vec::new();
+    for action in std::iter::once(played.clone()).chain(std::iter::once(choice)).chain(alternatives) {
+        let key = serde_json::to_value(&action).unwrap();
+        if !keys.contains(&key) && action_fits(pending, &action) {
+            keys.push(key);
+            out.push(action);
+        }
+    }
+    out
+}
+
+/// Mean placing of `house` over the playouts after `action`. Playouts that
+/// fail to finish count as a middle placing. With a rollout limit, the mean
+/// static evaluation where the playouts stop; without playouts, the static
+/// evaluation of the position after `action`. An action the engine refuses
+/// is worth nothing.
+fn value(state: &GameState, house: HouseName, action: &Action, opts: &AnalyzeOptions, salt: u64, max_decisions: usize) -> f64 {
+    if opts.playouts == 0 {
+        let mut s = state.clone();
+        reshuffle_hidden(&mut s, opts.seed ^ salt);
+        if engine::apply_action(&mut s, action.clone()).is_err() {
+            return 0.0;
+        }
+        engine::advance(&mut s);
+        return eval::static_eval(&s, house) as f64;
+    }
+    let total: f64 = (0..opts.playouts as u64)
+        .map(|p| {
+            let seed = opts.seed ^ salt.wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ p.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            let mut s = state.clone();
+            reshuffle_hidden(&mut s, seed);
+            if engine::apply_action(&mut s, action.clone()).is_err() {
+                return 0.0;
+            }
+            let mut agents: HashMap<HouseName, Box<dyn Agent>> = HashMap::new();
+            for (i, &h) in s.playing_houses.iter().enumerate() {
+                match opts.reference.build(h, seed.wrapping_add(i as u64)) {
+                    Ok(agent) => { agents.insert(h, agent); }
+                    Err(_) => return 0.5,
+                }
+            }
+            match play_out(&mut s, &mut agents, opts.rollout, max_decisions) {
+                Ok(_) if !opts.rollout.is_full() => eval::static_eval(&s, house) as f64,
+                Ok(_) => placing(&s, house),
+                Err(_) => 0.5,
+            }
+        })
+        .sum();
+    total / opts.playouts.max(1) as f64
+}
+
+/// 1.0 for first place down to 0.0 for last, by the official tiebreaker.
+fn placing(state: &GameState, house: HouseName) -> f64 {
+    let ranking = engine::final_ranking(state);
+    let n = ranking.len();
+    match ranking.iter().position(|&h| h == house) {
+        Some(rank) if n > 1 => 1.0 - rank as f64 / (n - 1) as f64,
+        _ => 1.0,
+    }
+}
@@ -0,0 +1,73 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Async Runner — many games at once for agents that wait on the network
+//
+// `run_games_parallel` gives every game a worker thread, so with remote
+// or process agents most threads sit idle waiting for answers and the
+// tournament runs at the pace of the slowest connection. Here each game
+// runs the ordinary game loop on a tokio blocking thread, its agents
+// wrapped in `BlockingAgent`; their futures are driven by the runtime's
+// I/O driver, so the number of games in flight is bounded by
+// `concurrency` rather than by cores. Bids are collected from all houses
+// at once, as in the lobby.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::runner::{run_game_with_bids, ActionPolicy, BidCollection, GameResult};
+use got_agents::async_agent::{AsyncAgent, BlockingAgent};
+use got_agents::Agent;
+use got_engine::setup::SetupConfig;
+use got_engine::types::HouseName;
+use std::collections::HashMap;
+use tokio::runtime::Handle;
+use tokio::task::JoinSet;
+
+/// The agents of one async game, by house.
+pub type AsyncAgents = HashMap<HouseName, Box<dyn AsyncAgent>>;
+
+/// Play `games` with up to `concurrency` in flight (0 = all at once),
+/// calling `on_result` with each game's index as it finishes. Must be
+/// awaited on a multi-threaded tokio runtime.
+pub async fn run_games_async<F>(
+    games: Vec<(SetupConfig, AsyncAgents)>,
+    concurrency: usize,
+    max_decisions: usize,
+    policy: ActionPolicy,
+    mut on_result: F,
+) where
+    F: FnMut(usize, Result<GameResult, String>),
+{
+    let handle = Handle::current();
+    let mut running = JoinSet::new();
+    for (i, (config, agents)) in games.into_iter().enumerate() {
+        while concurrency > 0 && running.len() >= concurrency {
+            report(running.join_next().await, &mut on_result);
+        }
+        let handle = handle.clone();
+        running.spawn_blocking(move || {
+            let mut agents: HashMap<HouseName, Box<dyn Agent>> = agents.into_iter()
+                .map(|(house, agent)| {
+                    let agent: Box<dyn Agent> = Box::new(BlockingAgent::new(agent, handle.clone(), config.seed));
+                    (house, agent)
+                })
+                .collect();
+            (i, run_game_with_bids(&mut agents, &config, max_decisions, policy, BidCollection::Simultaneous))
+        });
+    }
+    while let Some(joined) = running.join_next().await {
+        report(Some(joined), &mut on_result);
+    }
+}
+
+type Finished = (usize, Result<GameResult, String>);
+
+fn report<F>(joined: Option<Result<Finished, tokio::task::JoinError>>, on_result: &mut F)
+where
+    F: FnMut(usize, Result<GameResult, String>),
+{
+    match joined {
+        Some(Ok((i, result))) => on_result(i, result),
+        // Agent panics are caught in the game loop; anything else is an
+        // engine bug and goes up, as it does from the thread pool
+        Some(Err(e)) => std::panic::resume_unwind(e.into_panic()),
+        None => {}
+    }
+}
@@ -0,0 +1,122 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Balance — per-house win rates and handicaps that even them out
+//
+// Baseline win probabilities come from mirror games (the same agent in
+// every seat) played under the standard rules, so differences between
+// houses reflect the board and not the agents. Each house's rate is
+// shrunk towards the fair share 1/n with a prior worth PRIOR_GAMES games
+// before handicaps are derived from it, so a few lucky games do not
+// produce large adjustments.
+//
+// Handicaps (see `GameOptions`):
+//   power bonus    about one power token per 25% the house is above or
+//                  below its fair share (negative for strong houses),
+//                  at most MAX_POWER_BONUS either way
+//   castle tweak   +1 castle to win for houses winning 50% more often
+//                  than their fair share, −1 for those winning 50% less
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::database::Database;
+use got_engine::types::{GameOptions, Handicap, HouseName};
+
+/// Pseudo-games of fair-share results mixed into every house's rate.
+const PRIOR_GAMES: f64 = 20.0;
+/// Power tokens per 100% deviation from the fair share.
+const POWER_PER_DEVIATION: f64 = 4.0;
+const MAX_POWER_BONUS: i8 = 3;
+/// Relative deviation beyond which the castle threshold moves.
+const CASTLE_DEVIATION: f64 = 0.5;
+
+#[derive(Debug, Clone, Default)]
+pub struct BalanceFilter {
+    /// Only games with this many players.
+    pub players: Option<u8>,
+    /// Only mirror games of this agent (any mirror game when None).
+    pub agent: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HouseBalance {
+    pub house: HouseName,
+    pub games: u32,
+    pub wins: u32,
+    /// Fair share: 1 / players.
+    pub fair: f64,
+    /// Raw win rate.
+    pub win_rate: f64,
+    /// Win rate shrunk towards the fair share.
+    pub estimate: f64,
+}
+
+impl HouseBalance {
+    /// How far the estimate is from the fair share, relative to it
+    /// (+0.5 = wins 50% more often than it should).
+    pub fn deviation(&self) -> f64 {
+        self.estimate / self.fair - 1.0
+    }
+}
+
+/// Per-house win statistics from standard-rules mirror games, in
+/// HouseName::ALL order. Houses that never played are left out.
+pub fn house_balance(db: &Database, filter: &BalanceFilter) -> Vec<HouseBalance> {
+    let mut games = [0u32; 6];
+    let mut wins = [0u32; 6];
+    let mut fair = [0f64; 6];
+    for (winner, seats, options) in db.house_results() {
+        let players = seats.len();
+        let mirror = seats.iter().all(|(_, agent)| *agent == seats[0].1);
+        if options.is_some()
+            || !mirror
+            || filter.players.is_some_and(|p| p as usize != players)
+            || filter.agent.as_ref().is_some_and(|a| seats.first().map(|s| &s.1) != Some(a))
+        {
+            continue;
+        }
+        for (house, _) in &seats {
+            let Ok(h) = house.parse::<HouseName>() else { continue };
+            let i = HouseName::ALL.iter().position(|&x| x == h).unwrap();
+            games[i] += 1;
+            fair[i] += 1.0 / players as f64;
+            if *house == winner {
+                wins[i] += 1;
+            }
+        }
+    }
+
+    HouseName::ALL.iter().enumerate()
+        .filter(|&(i, _)| games[i] > 0)
+        .map(|(i, &house)| {
+            let n = games[i] as f64;
+            // Mean fair share over the games played (player counts may differ)
+            let fair = fair[i] / n;
+            HouseBalance {
+                house,
+                games: games[i],
+                wins: wins[i],
+                fair,
+                win_rate: wins[i] as f64 / n,
+                estimate: (wins[i] as f64 + PRIOR_GAMES * fair) / (n + PRIOR_GAMES),
+            }
+        })
+        .collect()
+}
+
+/// Handicap for one house from its balance estimate.
+pub fn handicap_for(balance: &HouseBalance) -> Handicap {
+    let deviation = balance.deviation();
+    let power_bonus = (-deviation * POWER_PER_DEVIATION).round()
+        .clamp(-MAX_POWER_BONUS as f64, MAX_POWER_BONUS as f64) as i8;
+    let castle_adjust = if deviation > CASTLE_DEVIATION {
+        1
+    } else if deviation < -CASTLE_DEVIATION {
+        -1
+    } else {
+        0
+    };
+    Handicap { power_bonus, castle_adjust, ..Handicap::none(balance.house) }
+}
+
+/// Options that handicap every house by its estimated strength.
+pub fn suggest_options(balance: &[HouseBalance]) -> GameOptions {
+    GameOptions { handicaps: balance.iter().map(handicap_for).collect(), ..GameOptions::default() }.canonical()
+}
@@ -0,0 +1,188 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Evaluation calibration — fitting and checking `engine::eval`
+//
+// Self-play games are sampled every few decisions; each sample keeps the
+// evaluation features of every playing house and who went on to win.
+// `fit_weights` finds the conditional-logit weights that make the
+// softmax of the house scores most likely to pick the eventual winner,
+// and `calibrate` checks any weights against held samples: Brier score
+// and log loss next to the uninformed 1/n guess, and a reliability table
+// of predicted against observed win rates.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::runner::{assign_seats, run_seated_game_observed};
+use got_agents::AgentSpec;
+use got_engine::eval::{self, NUM_FEATURES};
+use got_engine::setup::SetupConfig;
+
+#[derive(Debug, Clone)]
+pub struct SampleOptions {
+    /// Agent in every seat.
+    pub agent: AgentSpec,
+    pub games: u64,
+    pub first_seed: u64,
+    pub players: u8,
+    /// Keep every n-th decision's position.
+    pub every: usize,
+    pub threads: usize,
+}
+
+/// One position: every playing house's features and the eventual winner.
+#[derive(Debug, Clone)]
+pub struct PositionSample {
+    pub seed: u64,
+    pub round: u8,
+    pub features: Vec<[f32; NUM_FEATURES]>,
+    /// Index into `features` of the house that won the game.
+    pub winner: usize,
+}
+
+/// Play `opts.games` mirror games and sample their positions.
+pub fn sample_positions(opts: &SampleOptions, max_decisions: usize) -> Result<Vec<PositionSample>, String> {
+    use rayon::prelude::*;
+    opts.agent.build(got_engine::types::HouseName::Stark, 0)?;
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(opts.threads)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let games: Vec<Vec<PositionSample>> = pool.install(|| {
+        (0..opts.games).into_par_iter()
+            .map(|g| {
+                let config = SetupConfig::new(opts.players, opts.first_seed + g);
+                let seats = assign_seats(&config, &vec![opts.agent.clone(); opts.players as usize]);
+                let mut positions = Vec::new();
+                let mut decision = 0;
+                let result = run_seated_game_observed(&config, &seats, max_decisions, |_, view, _| {
+                    if decision % opts.every.max(1) == 0 {
+                        let features = view.playing_houses.iter()
+                            .map(|&h| eval::features(view, h).unwrap_or_default().to_array())
+                            .collect();
+                        positions.push((view.round, view.playing_houses.clone(), features));
+                    }
+                    decision += 1;
+                });
+                let Ok(result) = result else { return Vec::new() };
+                positions.into_iter()
+                    .filter_map(|(round, houses, features)| {
+                        let winner = houses.iter().position(|&h| h == result.winner)?;
+                        Some(PositionSample { seed: config.seed, round, features, winner })
+                    })
+                    .collect()
+            })
+            .collect()
+    });
+    Ok(games.into_iter().flatten().collect())
+}
+
+fn probabilities(sample: &PositionSample, weights: &[f64; NUM_FEATURES]) -> Vec<f64> {
+    let scores: Vec<f64> = sample.features.iter()
+        .map(|f| f.iter().zip(weights).map(|(&x, w)| x as f64 * w).sum())
+        .collect();
+    let max = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let exp: Vec<f64> = scores.iter().map(|s| (s - max).exp()).collect();
+    let total: f64 = exp.iter().sum();
+    exp.into_iter().map(|e| e / total).collect()
+}
+
+// ── Fitting ────────────────────────────────────────────────────────────
+
+/// Conditional-logit weights by full-batch gradient ascent on the mean
+/// log-likelihood, with a small L2 penalty, starting from `start`.
+pub fn fit_weights(samples: &[PositionSample], start: &[f32; NUM_FEATURES], epochs: usize, learning_rate: f64) -> [f32; NUM_FEATURES] {
+    const L2: f64 = 1e-3;
+    let mut w: [f64; NUM_FEATURES] = start.map(|x| x as f64);
+    if samples.is_empty() {
+        return *start;
+    }
+    for _ in 0..epochs {
+        let mut grad = [0.0f64; NUM_FEATURES];
+        for s in samples {
+            let p = probabilities(s, &w);
+            for (h, f) in s.features.iter().enumerate() {
+                let target = if h == s.winner { 1.0 } else { 0.0 };
+                for (g, &x) in grad.iter_mut().zip(f) {
+                    *g += (target - p[h]) * x as f64;
+                }
+            }
+        }
+        for (wi, g) in w.iter_mut().zip(grad) {
+            *wi += learning_rate * (g / samples.len() as f64 - L2 * *wi);
+        }
+    }
+    w.map(|x| x as f32)
+}
+
+// ── Calibration ────────────────────────────────────────────────────────
+
+/// Predictions that fell into one probability bucket.
+#[derive(Debug, Clone)]
+pub struct ReliabilityBin {
+    pub low: f64,
+    pub high: f64,
+    pub count: usize,
+    /// Mean predicted win probability.
+    pub predicted: f64,
+    /// Share of these predictions whose house won.
+    pub observed: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CalibrationReport {
+    pub positions: usize,
+    /// One per house per position.
+    pub predictions: usize,
+    pub brier: f64,
+    pub log_loss: f64,
+    /// The same scores for predicting 1/n everywhere.
+    pub baseline_brier: f64,
+    pub baseline_log_loss: f64,
+    pub bins: Vec<ReliabilityBin>,
+}
+
+/// Score `weights` on `samples`, with `bins` equal-width probability buckets.
+pub fn calibrate(samples: &[PositionSample], weights: &[f32; NUM_FEATURES], bins: usize) -> CalibrationReport {
+    let w = weights.map(|x| x as f64);
+    let bins = bins.max(1);
+    let mut buckets = vec![(0usize, 0.0f64, 0usize); bins];
+    let (mut brier, mut log_loss, mut base_brier, mut base_log_loss) = (0.0, 0.0, 0.0, 0.0);
+    let mut predictions = 0;
+    for s in samples {
+        let p = probabilities(s, &w);
+        let fair = 1.0 / p.len() as f64;
+        for (h, &ph) in p.iter().enumerate() {
+            let won = h == s.winner;
+            let y = won as u8 as f64;
+            brier += (ph - y).powi(2);
+            base_brier += (fair - y).powi(2);
+            if won {
+                log_loss -= ph.max(1e-9).ln();
+                base_log_loss -= fair.ln();
+            }
+            let b = ((ph * bins as f64) as usize).min(bins - 1);
+            buckets[b].0 += 1;
+            buckets[b].1 += ph;
+            buckets[b].2 += won as usize;
+            predictions += 1;
+        }
+    }
+    let per_prediction = predictions.max(1) as f64;
+    let per_position = samples.len().max(1) as f64;
+    CalibrationReport {
+        positions: samples.len(),
+        predictions,
+        brier: brier / per_prediction,
+        log_loss: log_loss / per_position,
+        baseline_brier: base_brier / per_prediction,
+        baseline_log_loss: base_log_loss / per_position,
+        bins: buckets.into_iter().enumerate()
+            .filter(|(_, (count, _, _))| *count > 0)
+            .map(|(i, (count, predicted, won))| ReliabilityBin {
+                low: i as f64 / bins as f64,
+                high: (i + 1) as f64 / bins as f64,
+                count,
+                predicted: predicted / count as f64,
+                observed: won as f64 / count as f64,
+            })
+            .collect(),
+    }
+}
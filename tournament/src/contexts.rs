@@ -0,0 +1,142 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Decision contexts — every decision of a game, saved to re-run alone
+//
+// A context is what one agent was shown for one decision and what it
+// answered: the `PlayerView` and the `Action`, with the house, the agent
+// spec and the decision's position in the game. A game's contexts go to
+// their own directory, one JSON file per decision:
+//
+//   <dir>/game-<seed>/view_0000.json, view_0001.json, ...
+//
+// Since agents only ever see a view, any agent can be asked the same
+// decision again from the file (`got-runner decide --view ...`). Agents
+// with internal state (search trees kept between decisions, random
+// streams) start fresh, so a re-run answer can differ from the recorded
+// one without anything being wrong. `answers` asks a decision of many
+// fresh agents, one seed each, and counts what they answer — the
+// agent's action distribution on that position.
+// ═══════════════════════════════════════════════════════════════════════
+
+use got_agents::AgentSpec;
+use got_engine::engine::Action;
+use got_engine::types::HouseName;
+use got_engine::visibility::PlayerView;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// One decision as the agent met it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionContext {
+    /// Position of the decision in the game (0 = first), the same as the
+    /// action index in a replay of the game.
+    pub decision: usize,
+    pub seed: u64,
+    pub house: HouseName,
+    /// Spec of the agent that decided.
+    pub agent: String,
+    pub view: PlayerView,
+    pub action: Action,
+}
+
+/// Writes one game's decision contexts into its directory.
+pub struct ContextWriter {
+    dir: PathBuf,
+    seed: u64,
+    decisions: usize,
+}
+
+impl ContextWriter {
+    /// Create `<root>/game-<seed>`, emptying it of earlier contexts.
+    pub fn new(root: &Path, seed: u64) -> Result<Self, String> {
+        let dir = root.join(format!("game-{}", seed));
+        if dir.exists() {
+            for entry in std::fs::read_dir(&dir).map_err(|e| format!("Cannot read {}: {}", dir.display(), e))? {
+                let path = entry.map_err(|e| e.to_string())?.path();
+                if path.file_name().is_some_and(|n| n.to_string_lossy().starts_with("view_")) {
+                    std::fs::remove_file(&path).map_err(|e| format!("Cannot remove {}: {}", path.display(), e))?;
+                }
+            }
+        }
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+        Ok(ContextWriter { dir, seed, decisions: 0 })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Contexts written so far.
+    pub fn len(&self) -> usize {
+        self.decisions
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.decisions == 0
+    }
+
+    /// Save the next decision of the game.
+    pub fn record(&mut self, house: HouseName, agent: &str, view: &PlayerView, action: &Action) -> Result<PathBuf, String> {
+        let context = DecisionContext {
+            decision: self.decisions,
+            seed: self.seed,
+            house,
+            agent: agent.to_string(),
+            view: view.clone(),
+            action: action.clone(),
+        };
+        let path = self.dir.join(format!("view_{:04}.json", self.decisions));
+        let json = serde_json::to_string_pretty(&context).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+        self.decisions += 1;
+        Ok(path)
+    }
+}
+
+/// Read a decision to re-run: a saved context, or a bare `PlayerView`
+/// (as served by the HTTP API), which has no recorded answer.
+pub fn load(path: &Path) -> Result<(PlayerView, Option<DecisionContext>), String> {
+    let json = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    if let Ok(context) = serde_json::from_str::<DecisionContext>(&json) {
+        return Ok((context.view.clone(), Some(context)));
+    }
+    let view: PlayerView = serde_json::from_str(&json)
+        .map_err(|e| format!("{} is neither a decision context nor a player view: {}", path.display(), e))?;
+    Ok((view, None))
+}
+
+/// Whether `view` asks its viewer for a decision.
+pub fn check_decision(view: &PlayerView) -> Result<(), String> {
+    let pending = view.pending.as_ref().ok_or("the view has no pending decision")?;
+    if pending.house() != view.viewer {
+        return Err(format!("the pending decision belongs to {}, not the viewer {}", pending.house(), view.viewer));
+    }
+    Ok(())
+}
+
+/// One distinct answer to a decision and how many seeds gave it.
+#[derive(Debug, Clone)]
+pub struct AnswerCount {
+    pub action: Action,
+    pub count: usize,
+}
+
+/// Ask the decision in `view` of `n` agents built from `spec` with seeds
+/// `seed`, `seed + 1`, ... Distinct answers come most frequent first,
+/// ties in the order they were first given.
+pub fn answers(view: &PlayerView, spec: &AgentSpec, seed: u64, n: usize) -> Result<Vec<AnswerCount>, String> {
+    check_decision(view)?;
+    // Actions compare by their JSON, which is also how they are saved
+    let mut counts: Vec<(String, AnswerCount)> = Vec::new();
+    for i in 0..n as u64 {
+        let mut agent = spec.build(view.viewer, seed + i)?;
+        let action = agent.decide(view);
+        let key = serde_json::to_string(&action).map_err(|e| e.to_string())?;
+        match counts.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, c)) => c.count += 1,
+            None => counts.push((key, AnswerCount { action, count: 1 })),
+        }
+    }
+    let mut counts: Vec<AnswerCount> = counts.into_iter().map(|(_, c)| c).collect();
+    counts.sort_by_key(|c| std::cmp::Reverse(c.count));
+    Ok(counts)
+}
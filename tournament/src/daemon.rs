@@ -0,0 +1,257 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Daemon — a tournament that keeps playing until it is told to stop
+//
+// Games are played in batches. Seeds follow the tournament schedule
+// (42, 1042, 2042, …) from the start, and games already in the database
+// are skipped, so a restarted daemon picks up where the last one left off.
+//
+// A pairing is one assignment of agents to houses under one set of
+// options — what `GameKey` hashes apart from the seed. With a target per
+// pairing, games whose pairing already has that many stored games are not
+// scheduled; once no seed in a long stretch yields a game, every pairing
+// the lineup can produce is full and the daemon ends on its own.
+//
+// Every `snapshot_every` games the Elo and TrueSkill ratings are rebuilt
+// from the whole history and a summary is written to a JSON file
+// (replaced atomically, so readers never see half of one); Elo threshold
+// hooks are checked against each rebuild. The stop flag
+// is checked between batches: the batch under way is finished and stored
+// and a last snapshot is written before `run` returns.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::database::{Database, DbWriter, LeaderboardFilter, LeaderboardRow};
+use crate::metrics::Metrics;
+use crate::notify::{Milestone, Notifier};
+use crate::ratings::RatingSystem;
+use crate::runner::{assign_seats, run_games_parallel, ActionPolicy, GameKey, SeatAgent};
+use got_agents::AgentSpec;
+use got_engine::setup::SetupConfig;
+use got_engine::types::HouseName;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seeds in a row whose pairing is full before every pairing counts as
+/// full. Seeds already stored don't count: there are only so many.
+const FULL_AFTER: u64 = 1000;
+
+#[derive(Debug, Clone)]
+pub struct DaemonOptions {
+    /// Agent per seat.
+    pub lineup: Vec<AgentSpec>,
+    /// Houses, seating and options of every game; the seed is replaced.
+    pub setup: SetupConfig,
+    /// Stored games wanted per pairing; 0 plays on without a limit.
+    pub per_pairing: u32,
+    /// Games played between checks of the stop flag.
+    pub batch: usize,
+    /// Games between snapshots.
+    pub snapshot_every: u32,
+    pub snapshot_path: PathBuf,
+    pub threads: usize,
+    /// Keep the candidates and scores agents report.
+    pub decision_info: bool,
+    pub policy: ActionPolicy,
+    /// Counters for a metrics endpoint, updated as games finish and are stored.
+    pub metrics: Option<Arc<Metrics>>,
+}
+
+/// What the daemon has done since it started.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DaemonStats {
+    pub games: u32,
+    pub errors: u32,
+    /// Agent panics and illegal actions.
+    pub faults: u32,
+    /// Games ended by a forfeit under the strict action policy.
+    pub forfeits: u32,
+    pub wins: HashMap<HouseName, u32>,
+    pub snapshots: u32,
+}
+
+/// Summary written to the snapshot file.
+#[derive(Debug, Clone, Serialize)]
+pub struct Snapshot {
+    /// Seconds since the Unix epoch.
+    pub written_at: u64,
+    pub games_in_db: u32,
+    pub session: DaemonStats,
+    pub leaderboard: Vec<SnapshotRow>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotRow {
+    pub agent: String,
+    pub elo: f64,
+    /// Conservative TrueSkill score (mu − 3σ).
+    pub trueskill: Option<f64>,
+    pub games: u32,
+    pub wins: u32,
+}
+
+/// How a `run` ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DaemonExit {
+    /// The stop flag was raised.
+    Stopped,
+    /// Every pairing reached its target.
+    TargetReached,
+}
+
+/// Play batches until `stop` is raised or every pairing is full.
+/// `on_batch` sees the running totals after each batch is stored;
+/// `notifier` hears about engine errors and Elo crossings.
+pub fn run(
+    db_path: &str,
+    opts: &DaemonOptions,
+    stop: &AtomicBool,
+    notifier: &Notifier,
+    mut on_batch: impl FnMut(&DaemonStats),
+) -> Result<(DaemonExit, DaemonStats), String> {
+    if opts.lineup.is_empty() {
+        return Err("The daemon needs at least one agent".to_string());
+    }
+    let db = Database::new(db_path);
+    let mut agent_ids: Vec<(String, i64)> = Vec::new();
+    for spec in &opts.lineup {
+        let name = spec.to_string();
+        if !agent_ids.iter().any(|(n, _)| *n == name) {
+            agent_ids.push((name.clone(), db.register_agent(&name)));
+        }
+    }
+
+    if notifier.has_thresholds() {
+        // Where everyone stands before the first game
+        db.recompute_ratings(RatingSystem::Elo);
+        notifier.check_elo(&db.filtered_leaderboard(&LeaderboardFilter::default()));
+    }
+
+    let mut stats = DaemonStats::default();
+    let mut pairings: HashMap<String, u32> = HashMap::new();
+    let mut next_game = 0u64;
+    let mut since_snapshot = 0u32;
+    let exit = loop {
+        if stop.load(Ordering::Relaxed) {
+            break DaemonExit::Stopped;
+        }
+        let games = schedule(&db, opts, &mut pairings, &mut next_game);
+        if games.is_empty() {
+            break DaemonExit::TargetReached;
+        }
+
+        let writer = Mutex::new(DbWriter::spawn_with_metrics(db_path, agent_ids.clone(), 64, opts.metrics.clone()));
+        let batch = Mutex::new(DaemonStats::default());
+        run_games_parallel(&games, opts.threads, 50_000, opts.policy, |i, result| {
+            let mut batch = batch.lock().unwrap();
+            match result {
+                Ok(mut result) => {
+                    if let Some(metrics) = &opts.metrics {
+                        metrics.record_game(&result);
+                    }
+                    batch.games += 1;
+                    batch.faults += result.agent_faults.len() as u32;
+                    batch.forfeits += result.forfeit.is_some() as u32;
+                    *batch.wins.entry(result.winner).or_insert(0) += 1;
+                    if !opts.decision_info {
+                        result.decision_info.clear();
+                    }
+                    writer.lock().unwrap().send(result);
+                }
+                Err(message) => {
+                    if let Some(metrics) = &opts.metrics {
+                        metrics.record_engine_error();
+                    }
+                    batch.errors += 1;
+                    notifier.fire(&Milestone::EngineError { seed: games[i].0.seed, message });
+                }
+            }
+        });
+        writer.into_inner().unwrap().finish();
+
+        let batch = batch.into_inner().unwrap();
+        stats.games += batch.games;
+        stats.errors += batch.errors;
+        stats.faults += batch.faults;
+        stats.forfeits += batch.forfeits;
+        for (house, w) in batch.wins {
+            *stats.wins.entry(house).or_insert(0) += w;
+        }
+        since_snapshot += batch.games;
+        if since_snapshot >= opts.snapshot_every.max(1) {
+            write_snapshot(&db, opts, &mut stats)?;
+            notifier.check_elo(&db.filtered_leaderboard(&LeaderboardFilter::default()));
+            since_snapshot = 0;
+        }
+        on_batch(&stats);
+    };
+    if since_snapshot > 0 || stats.snapshots == 0 {
+        write_snapshot(&db, opts, &mut stats)?;
+        notifier.check_elo(&db.filtered_leaderboard(&LeaderboardFilter::default()));
+    }
+    Ok((exit, stats))
+}
+
+/// The next batch: games not yet stored whose pairing is below target.
+fn schedule(
+    db: &Database,
+    opts: &DaemonOptions,
+    pairings: &mut HashMap<String, u32>,
+    next_game: &mut u64,
+) -> Vec<(SetupConfig, Vec<SeatAgent>)> {
+    let mut games = Vec::new();
+    let mut misses = 0;
+    while games.len() < opts.batch.max(1) && misses < FULL_AFTER {
+        let seed = 42 + *next_game * 1000;
+        *next_game += 1;
+        let config = SetupConfig { seed, ..opts.setup.clone() };
+        let seats = assign_seats(&config, &opts.lineup);
+        let key = GameKey::from_seats(&config, &seats);
+        let pairing = format!("{}/{}", key.lineup_hash, key.options_hash);
+        if db.contains_game(&key) {
+            continue;
+        }
+        let stored = *pairings.entry(pairing.clone()).or_insert_with(|| db.pairing_games(&key));
+        if opts.per_pairing > 0 && stored >= opts.per_pairing {
+            misses += 1;
+            continue;
+        }
+        misses = 0;
+        // Counted when scheduled, so one batch cannot overshoot the target
+        *pairings.get_mut(&pairing).unwrap() += 1;
+        games.push((config, seats));
+    }
+    games
+}
+
+/// Rebuild the ratings and replace the snapshot file.
+fn write_snapshot(db: &Database, opts: &DaemonOptions, stats: &mut DaemonStats) -> Result<(), String> {
+    db.recompute_ratings(RatingSystem::Elo);
+    db.recompute_ratings(RatingSystem::TrueSkill);
+    let trueskill: HashMap<String, f64> = db.rating_leaderboard(RatingSystem::TrueSkill).into_iter()
+        .map(|(name, rating, _, _)| (name, rating.score()))
+        .collect();
+    stats.snapshots += 1;
+    let leaderboard = db.filtered_leaderboard(&LeaderboardFilter::default()).into_iter()
+        .map(|LeaderboardRow { name, elo, games, wins }| SnapshotRow {
+            trueskill: trueskill.get(&name).copied(),
+            agent: name,
+            elo,
+            games,
+            wins,
+        })
+        .collect();
+    let snapshot = Snapshot {
+        written_at: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+        games_in_db: db.game_count(),
+        session: stats.clone(),
+        leaderboard,
+    };
+    let json = serde_json::to_string_pretty(&snapshot).map_err(|e| e.to_string())?;
+    let tmp = opts.snapshot_path.with_extension("tmp");
+    std::fs::write(&tmp, json)
+        .and_then(|()| std::fs::rename(&tmp, &opts.snapshot_path))
+        .map_err(|e| format!("Cannot write {}: {}", opts.snapshot_path.display(), e))
+}
@@ -3,16 +3,118 @@
 // ═══════════════════════════════════════════════════════════════════════
 
 use rusqlite::{Connection, params};
-use crate::runner::GameResult;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use crate::league::{LeagueMember, MemberRole};
+use crate::matches::MatchResult;
+use crate::metrics::Metrics;
+use crate::ratings::{self, Rating, RatingSystem};
+use crate::runner::{AgentFault, BranchingStats, DecisionRecord, GameKey, GameResult, KindBranching, SeatAgent};
+use got_engine::map::AREAS;
+use got_engine::setup::SetupConfig;
+use got_engine::types::{GameEvent, GameEventKind, GameOptions, HouseName};
 
 pub struct Database {
     conn: Connection,
 }
 
+/// One game for balance statistics: winner, (house, agent name) per seat,
+/// options JSON (None for the standard game).
+pub type HouseResult = (String, Vec<(String, String)>, Option<String>);
+
+/// Order of leaderboard rows, best first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LeaderboardSort {
+    #[default]
+    Elo,
+    WinRate,
+    Games,
+}
+
+impl FromStr for LeaderboardSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "elo" => Ok(LeaderboardSort::Elo),
+            "winrate" => Ok(LeaderboardSort::WinRate),
+            "games" => Ok(LeaderboardSort::Games),
+            other => Err(format!("Unknown leaderboard order: {} (known: elo, winrate, games)", other)),
+        }
+    }
+}
+
+/// Which agents a leaderboard shows, counting which games.
+#[derive(Debug, Clone, Default)]
+pub struct LeaderboardFilter {
+    pub sort: LeaderboardSort,
+    /// Count only the games played as this house.
+    pub house: Option<HouseName>,
+    /// Leave out agents with fewer games (after the house filter).
+    pub min_games: u32,
+    /// Only agents whose name starts with this.
+    pub agent_prefix: Option<String>,
+}
+
+/// One agent on a leaderboard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeaderboardRow {
+    pub name: String,
+    pub elo: f64,
+    pub games: u32,
+    pub wins: u32,
+}
+
+impl LeaderboardRow {
+    pub fn win_rate(&self) -> f64 {
+        self.wins as f64 / self.games.max(1) as f64
+    }
+}
+
+/// One agent's record per house it played: (house, games, wins).
+#[derive(Debug, Clone, PartialEq)]
+pub struct HousePivotRow {
+    pub name: String,
+    pub elo: f64,
+    pub by_house: Vec<(HouseName, u32, u32)>,
+}
+
+impl HousePivotRow {
+    pub fn games(&self) -> u32 {
+        self.by_house.iter().map(|&(_, games, _)| games).sum()
+    }
+
+    pub fn wins(&self) -> u32 {
+        self.by_house.iter().map(|&(_, _, wins)| wins).sum()
+    }
+}
+
+impl LeaderboardSort {
+    /// SQL ordering over `elo`, `games` and `wins` columns.
+    fn order_by(self) -> &'static str {
+        match self {
+            LeaderboardSort::Elo => "elo DESC, name",
+            LeaderboardSort::WinRate => "CAST(wins AS REAL) / MAX(games, 1) DESC, games DESC, name",
+            LeaderboardSort::Games => "games DESC, elo DESC, name",
+        }
+    }
+}
+
 impl Database {
     /// Open (or create) a database at the given path.
     pub fn new(path: &str) -> Self {
         let conn = Connection::open(path).expect("Failed to open database");
+        // WAL lets readers (duplicate checks, leaderboards) run while the
+        // writer thread commits; NORMAL sync is safe under WAL.
+        conn.execute_batch("
+            PRAGMA journal_mode = WAL;
+            PRAGMA synchronous = NORMAL;
+        ").expect("Failed to configure database");
+        conn.busy_timeout(Duration::from_secs(30)).expect("Failed to set busy timeout");
         let db = Database { conn };
         db.create_schema();
         db
@@ -41,7 +143,15 @@ impl Database {
                 seed        INTEGER NOT NULL,
                 rounds      INTEGER NOT NULL,
                 winner      TEXT NOT NULL,
-                played_at   TEXT NOT NULL DEFAULT (datetime('now'))
+                played_at   TEXT NOT NULL DEFAULT (datetime('now')),
+                lineup_hash  TEXT,
+                options_hash TEXT,
+                houses       TEXT,
+                random_seats INTEGER,
+                options      TEXT,
+                forfeit      TEXT,
+                decisions    INTEGER,
+                log10_tree_size REAL
             );
 
             CREATE TABLE IF NOT EXISTS game_players (
@@ -54,9 +164,159 @@ impl Database {
                 power       INTEGER NOT NULL,
                 iron_throne INTEGER NOT NULL,
                 fiefdoms    INTEGER NOT NULL,
-                kings_court INTEGER NOT NULL
+                kings_court INTEGER NOT NULL,
+                final_rank  INTEGER,
+                seat        INTEGER,
+                agent_spec  TEXT,
+                agent_seed  INTEGER
+            );
+
+            CREATE TABLE IF NOT EXISTS wildling_attacks (
+                id              INTEGER PRIMARY KEY,
+                game_id         INTEGER NOT NULL REFERENCES games(id),
+                round           INTEGER NOT NULL,
+                threat          INTEGER NOT NULL,
+                total_bid       INTEGER NOT NULL,
+                card            TEXT,
+                nights_watch_won INTEGER NOT NULL,
+                target_house    TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS ratings (
+                agent_id    INTEGER NOT NULL REFERENCES agents(id),
+                system      TEXT NOT NULL,
+                mu          REAL NOT NULL,
+                sigma       REAL NOT NULL,
+                PRIMARY KEY (agent_id, system)
+            );
+
+            CREATE TABLE IF NOT EXISTS combats (
+                id                INTEGER PRIMARY KEY,
+                game_id           INTEGER NOT NULL REFERENCES games(id),
+                round             INTEGER NOT NULL,
+                area              TEXT NOT NULL,
+                attacker          TEXT NOT NULL,
+                defender          TEXT NOT NULL,
+                winner            TEXT NOT NULL,
+                attacker_card     TEXT,
+                defender_card     TEXT,
+                attacker_strength INTEGER NOT NULL,
+                defender_strength INTEGER NOT NULL,
+                attacker_support  INTEGER NOT NULL,
+                defender_support  INTEGER NOT NULL,
+                casualties        INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS league_members (
+                league      TEXT NOT NULL,
+                agent_id    INTEGER NOT NULL REFERENCES agents(id),
+                generation  INTEGER NOT NULL,
+                role        TEXT NOT NULL,
+                elo         REAL NOT NULL DEFAULT 1500.0,
+                games       INTEGER NOT NULL DEFAULT 0,
+                added_at    TEXT NOT NULL DEFAULT (datetime('now')),
+                PRIMARY KEY (league, agent_id)
+            );
+
+            CREATE TABLE IF NOT EXISTS league_games (
+                league      TEXT NOT NULL,
+                game_id     INTEGER NOT NULL REFERENCES games(id),
+                generation  INTEGER NOT NULL,
+                PRIMARY KEY (league, game_id)
+            );
+
+            CREATE TABLE IF NOT EXISTS wildling_bids (
+                id          INTEGER PRIMARY KEY,
+                attack_id   INTEGER NOT NULL REFERENCES wildling_attacks(id),
+                house       TEXT NOT NULL,
+                bid         INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS decision_info (
+                id          INTEGER PRIMARY KEY,
+                game_id     INTEGER NOT NULL REFERENCES games(id),
+                decision    INTEGER NOT NULL,
+                round       INTEGER NOT NULL,
+                house       TEXT NOT NULL,
+                info        TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS agent_faults (
+                id          INTEGER PRIMARY KEY,
+                game_id     INTEGER NOT NULL REFERENCES games(id),
+                kind        TEXT NOT NULL DEFAULT 'panic',
+                decision    INTEGER NOT NULL,
+                round       INTEGER NOT NULL,
+                house       TEXT NOT NULL,
+                agent       TEXT NOT NULL,
+                message     TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS branching (
+                game_id     INTEGER NOT NULL REFERENCES games(id),
+                kind        TEXT NOT NULL,
+                decisions   INTEGER NOT NULL,
+                forced      INTEGER NOT NULL,
+                options     INTEGER NOT NULL,
+                max_options INTEGER NOT NULL,
+                PRIMARY KEY (game_id, kind)
+            );
+
+            CREATE TABLE IF NOT EXISTS matches (
+                id          INTEGER PRIMARY KEY,
+                seed        INTEGER NOT NULL,
+                games       INTEGER NOT NULL,
+                played_at   TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE TABLE IF NOT EXISTS match_players (
+                match_id    INTEGER NOT NULL REFERENCES matches(id),
+                seat        INTEGER NOT NULL,
+                agent_id    INTEGER NOT NULL REFERENCES agents(id),
+                wins        INTEGER NOT NULL,
+                points      INTEGER NOT NULL,
+                match_rank  INTEGER NOT NULL,
+                PRIMARY KEY (match_id, seat)
+            );
+
+            CREATE TABLE IF NOT EXISTS match_games (
+                match_id    INTEGER NOT NULL REFERENCES matches(id),
+                game_id     INTEGER NOT NULL REFERENCES games(id),
+                game_index  INTEGER NOT NULL,
+                PRIMARY KEY (match_id, game_index)
             );
         ").expect("Failed to create schema");
+
+        // Databases created before these columns existed
+        self.ensure_column("game_players", "final_rank", "INTEGER");
+        self.ensure_column("games", "lineup_hash", "TEXT");
+        self.ensure_column("games", "options_hash", "TEXT");
+        self.ensure_column("games", "houses", "TEXT");
+        self.ensure_column("games", "random_seats", "INTEGER");
+        self.ensure_column("games", "options", "TEXT");
+        self.ensure_column("game_players", "seat", "INTEGER");
+        self.ensure_column("game_players", "agent_spec", "TEXT");
+        self.ensure_column("game_players", "agent_seed", "INTEGER");
+        self.ensure_column("games", "forfeit", "TEXT");
+        self.ensure_column("agent_faults", "kind", "TEXT NOT NULL DEFAULT 'panic'");
+        self.ensure_column("games", "decisions", "INTEGER");
+        self.ensure_column("games", "log10_tree_size", "REAL");
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_games_key ON games (seed, lineup_hash, options_hash)",
+            [],
+        ).expect("Failed to create game key index");
+    }
+
+    fn ensure_column(&self, table: &str, column: &str, decl: &str) {
+        let exists: bool = self.conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+            params![table, column],
+            |row| row.get::<_, i64>(0),
+        ).map(|n| n > 0).unwrap_or(false);
+        if !exists {
+            self.conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, decl), [])
+                .unwrap_or_else(|e| panic!("Failed to add {}.{}: {}", table, column, e));
+        }
     }
 
     /// Register an agent (or return existing ID).
@@ -72,11 +332,43 @@ impl Database {
         ).expect("Failed to get agent id")
     }
 
-    /// Store a completed game result.
+    /// Store a completed game result in its own transaction.
     pub fn store_game(&self, result: &GameResult, agent_ids: &[(String, i64)]) -> i64 {
+        let tx = self.conn.unchecked_transaction().expect("Failed to begin transaction");
+        let game_id = self.insert_game(result, agent_ids);
+        tx.commit().expect("Failed to commit game");
+        game_id
+    }
+
+    /// Store several games in a single transaction.
+    pub fn store_games(&self, results: &[GameResult], agent_ids: &[(String, i64)]) -> Vec<i64> {
+        let tx = self.conn.unchecked_transaction().expect("Failed to begin transaction");
+        let ids = results.iter().map(|r| self.insert_game(r, agent_ids)).collect();
+        tx.commit().expect("Failed to commit games");
+        ids
+    }
+
+    fn insert_game(&self, result: &GameResult, agent_ids: &[(String, i64)]) -> i64 {
         self.conn.execute(
-            "INSERT INTO games (seed, rounds, winner) VALUES (?1, ?2, ?3)",
-            params![result.seed as i64, result.rounds_played as i64, result.winner.to_string()],
+            "INSERT INTO games (seed, rounds, winner, lineup_hash, options_hash, houses, random_seats, options, forfeit,
+                                decisions, log10_tree_size)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                result.seed as i64,
+                result.rounds_played as i64,
+                result.winner.to_string(),
+                result.key.lineup_hash,
+                result.key.options_hash,
+                // Selection order matters: it is what seat shuffling permutes
+                result.setup.houses.iter().map(|h| h.to_string()).collect::<Vec<_>>().join(","),
+                result.setup.randomize_seats,
+                // NULL for the standard game
+                (!result.setup.options.is_standard())
+                    .then(|| serde_json::to_string(&result.setup.options.canonical()).unwrap()),
+                result.forfeit.map(|h| h.to_string()),
+                result.decisions as i64,
+                result.branching.log10_tree_size,
+            ],
         ).expect("Failed to store game");
         let game_id = self.conn.last_insert_rowid();
 
@@ -85,10 +377,16 @@ impl Database {
                 .find(|(name, _)| *name == pr.agent_name || name == &pr.house.to_string())
                 .map(|(_, id)| *id)
                 .unwrap_or(0);
+            // 1 = winner
+            let final_rank = result.final_ranking.iter()
+                .position(|&h| h == pr.house)
+                .map(|i| i as i64 + 1);
+            let seat = result.seats.iter().find(|s| s.house == pr.house);
 
             self.conn.execute(
-                "INSERT INTO game_players (game_id, agent_id, house, castles, supply, power, iron_throne, fiefdoms, kings_court)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                "INSERT INTO game_players (game_id, agent_id, house, castles, supply, power, iron_throne, fiefdoms, kings_court,
+                                           final_rank, seat, agent_spec, agent_seed)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
                 params![
                     game_id,
                     agent_id,
@@ -99,10 +397,20 @@ impl Database {
                     pr.final_iron_throne as i64,
                     pr.final_fiefdoms as i64,
                     pr.final_kings_court as i64,
+                    final_rank,
+                    seat.map(|s| s.seat as i64),
+                    seat.map(|s| s.spec.to_string()),
+                    seat.map(|s| s.seed as i64),
                 ],
             ).expect("Failed to store game player");
         }
 
+        self.store_wildling_attacks(game_id, &result.events);
+        self.store_combats(game_id, &result.events);
+        self.store_decision_info(game_id, &result.decision_info);
+        self.store_agent_faults(game_id, &result.agent_faults);
+        self.store_branching(game_id, &result.branching);
+
         // Update agent stats
         for (name, agent_id) in agent_ids {
             let won = result.winner.to_string() == *name
@@ -116,6 +424,255 @@ impl Database {
         game_id
     }
 
+    /// One row per wildling attack, plus each house's bid.
+    fn store_wildling_attacks(&self, game_id: i64, events: &[GameEvent]) {
+        for event in events {
+            if let GameEventKind::WildlingAttack { threat, total_bid, bids, card, nights_watch_won, target } = &event.kind {
+                self.conn.execute(
+                    "INSERT INTO wildling_attacks (game_id, round, threat, total_bid, card, nights_watch_won, target_house)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        game_id,
+                        event.round as i64,
+                        *threat as i64,
+                        *total_bid as i64,
+                        card.map(|c| format!("{:?}", c)),
+                        nights_watch_won,
+                        target.to_string(),
+                    ],
+                ).expect("Failed to store wildling attack");
+                let attack_id = self.conn.last_insert_rowid();
+                for (house, bid) in bids {
+                    self.conn.execute(
+                        "INSERT INTO wildling_bids (attack_id, house, bid) VALUES (?1, ?2, ?3)",
+                        params![attack_id, house.to_string(), *bid as i64],
+                    ).expect("Failed to store wildling bid");
+                }
+            }
+        }
+    }
+
+    /// One row per battle with strengths, support, cards and casualties.
+    fn store_combats(&self, game_id: i64, events: &[GameEvent]) {
+        for event in events {
+            if let GameEventKind::CombatResolved {
+                area_id, attacker, defender, winner, attacker_card, defender_card,
+                attacker_strength, defender_strength, attacker_support, defender_support, casualties,
+            } = &event.kind {
+                self.conn.execute(
+                    "INSERT INTO combats (game_id, round, area, attacker, defender, winner, attacker_card, defender_card,
+                                          attacker_strength, defender_strength, attacker_support, defender_support, casualties)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                    params![
+                        game_id,
+                        event.round as i64,
+                        AREAS[area_id.0 as usize].name,
+                        attacker.to_string(),
+                        defender.to_string(),
+                        winner.to_string(),
+                        attacker_card.map(|c| format!("{:?}", c)),
+                        defender_card.map(|c| format!("{:?}", c)),
+                        *attacker_strength as i64,
+                        *defender_strength as i64,
+                        *attacker_support as i64,
+                        *defender_support as i64,
+                        *casualties as i64,
+                    ],
+                ).expect("Failed to store combat");
+            }
+        }
+    }
+
+    /// One row per reported decision, the `DecisionInfo` as JSON.
+    fn store_decision_info(&self, game_id: i64, records: &[DecisionRecord]) {
+        for r in records {
+            self.conn.execute(
+                "INSERT INTO decision_info (game_id, decision, round, house, info) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![game_id, r.decision as i64, r.round as i64, r.house.to_string(), serde_json::to_string(&r.info).unwrap()],
+            ).expect("Failed to store decision info");
+        }
+    }
+
+    fn store_agent_faults(&self, game_id: i64, faults: &[AgentFault]) {
+        for f in faults {
+            self.conn.execute(
+                "INSERT INTO agent_faults (game_id, kind, decision, round, house, agent, message) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![game_id, f.kind.name(), f.decision as i64, f.round as i64, f.house.to_string(), f.agent, f.message],
+            ).expect("Failed to store agent fault");
+        }
+    }
+
+    /// One row per decision kind the game asked for.
+    fn store_branching(&self, game_id: i64, branching: &BranchingStats) {
+        for k in &branching.per_kind {
+            self.conn.execute(
+                "INSERT INTO branching (game_id, kind, decisions, forced, options, max_options) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![game_id, k.kind, k.decisions as i64, k.forced as i64, k.options as i64, k.max as i64],
+            ).expect("Failed to store branching");
+        }
+    }
+
+    /// Branching summed over every game that recorded it, and the number
+    /// of those games; divide by it for per-game figures.
+    pub fn branching_totals(&self) -> (u32, BranchingStats) {
+        let (games, log10_tree_size): (u32, f64) = self.conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(log10_tree_size), 0) FROM games WHERE id IN (SELECT game_id FROM branching)",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).unwrap_or((0, 0.0));
+        let mut stmt = self.conn.prepare(
+            "SELECT kind, SUM(decisions), SUM(forced), SUM(options), MAX(max_options) FROM branching GROUP BY kind ORDER BY kind"
+        ).expect("Failed to prepare branching query");
+        let per_kind: Vec<KindBranching> = stmt.query_map([], |row| {
+            Ok(KindBranching {
+                kind: row.get(0)?,
+                decisions: row.get::<_, i64>(1)? as usize,
+                forced: row.get::<_, i64>(2)? as usize,
+                options: row.get::<_, i64>(3)? as usize,
+                max: row.get::<_, i64>(4)? as usize,
+            })
+        })
+        .expect("Failed to query branching")
+        .filter_map(|r| r.ok())
+        .collect();
+        let totals = BranchingStats {
+            decisions: per_kind.iter().map(|k| k.decisions).sum(),
+            forced: per_kind.iter().map(|k| k.forced).sum(),
+            options: per_kind.iter().map(|k| k.options).sum(),
+            max: per_kind.iter().map(|k| k.max).max().unwrap_or(0),
+            log10_tree_size,
+            per_kind,
+        };
+        (games, totals)
+    }
+
+    /// Every recorded agent panic and illegal action with the game it
+    /// happened in, oldest first.
+    pub fn agent_faults(&self) -> Vec<(i64, AgentFault)> {
+        let mut stmt = self.conn.prepare(
+            "SELECT game_id, kind, decision, round, house, agent, message FROM agent_faults ORDER BY id"
+        ).expect("Failed to prepare agent fault query");
+        stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, u8>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
+            ))
+        })
+        .expect("Failed to query agent faults")
+        .filter_map(|r| r.ok())
+        .filter_map(|(game_id, kind, decision, round, house, agent, message)| {
+            let kind = kind.parse().ok()?;
+            Some((game_id, AgentFault { kind, decision: decision as usize, round, house: house.parse().ok()?, agent, message }))
+        })
+        .collect()
+    }
+
+    /// Decision info stored with a game, in decision order.
+    pub fn decision_info(&self, game_id: i64) -> Result<Vec<DecisionRecord>, String> {
+        let mut stmt = self.conn.prepare(
+            "SELECT decision, round, house, info FROM decision_info WHERE game_id = ?1 ORDER BY decision",
+        ).map_err(|e| e.to_string())?;
+        let rows = stmt.query_map(params![game_id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(2)?, row.get::<_, String>(3)?))
+        }).map_err(|e| e.to_string())?;
+        let mut records = Vec::new();
+        for row in rows {
+            let (decision, round, house, info) = row.map_err(|e| e.to_string())?;
+            records.push(DecisionRecord {
+                decision: decision as usize,
+                round: round as u8,
+                house: house.parse()?,
+                info: serde_json::from_str(&info).map_err(|e| format!("decision {}: {}", decision, e))?,
+            });
+        }
+        Ok(records)
+    }
+
+    /// Load what is needed to replay a stored game: its setup, seated
+    /// agents and recorded winner.
+    pub fn load_game_setup(&self, game_id: i64) -> Result<(SetupConfig, Vec<SeatAgent>, String), String> {
+        let (seed, winner, houses, random_seats, options): (i64, String, Option<String>, Option<bool>, Option<String>) = self.conn.query_row(
+            "SELECT seed, winner, houses, random_seats, options FROM games WHERE id = ?1",
+            params![game_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+        ).map_err(|_| format!("No game {} in database", game_id))?;
+        let houses = houses.ok_or_else(|| format!("Game {} was stored without its setup and cannot be replayed", game_id))?;
+        let houses = houses.split(',')
+            .map(|h| h.parse::<HouseName>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let config = SetupConfig {
+            houses,
+            seed: seed as u64,
+            randomize_seats: random_seats.unwrap_or(false),
+            options: match options {
+                Some(json) => serde_json::from_str(&json)
+                    .map_err(|e| format!("Game {} has unreadable options: {}", game_id, e))?,
+                None => GameOptions::default(),
+            },
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT seat, house, agent_spec, agent_seed FROM game_players WHERE game_id = ?1 ORDER BY seat"
+        ).map_err(|e| e.to_string())?;
+        let rows = stmt.query_map(params![game_id], |row| {
+            Ok((
+                row.get::<_, Option<i64>>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<i64>>(3)?,
+            ))
+        }).map_err(|e| e.to_string())?;
+        let mut seats = Vec::new();
+        for row in rows {
+            let (seat, house, spec, agent_seed) = row.map_err(|e| e.to_string())?;
+            let (Some(seat), Some(spec), Some(agent_seed)) = (seat, spec, agent_seed) else {
+                return Err(format!("Game {} has no agent seeds recorded and cannot be replayed", game_id));
+            };
+            seats.push(SeatAgent {
+                seat: seat as usize,
+                house: house.parse()?,
+                spec: spec.parse()?,
+                seed: agent_seed as u64,
+            });
+        }
+        Ok((config, seats, winner))
+    }
+
+    /// Whether a game with the same seed, lineup and options is already stored.
+    pub fn contains_game(&self, key: &GameKey) -> bool {
+        self.conn.query_row(
+            "SELECT COUNT(*) FROM games WHERE seed = ?1 AND lineup_hash = ?2 AND options_hash = ?3",
+            params![key.seed as i64, key.lineup_hash, key.options_hash],
+            |row| row.get::<_, i64>(0),
+        ).map(|n| n > 0).unwrap_or(false)
+    }
+
+    /// `games` less those already stored (`contains_game`), and how many
+    /// were dropped. With `allow_duplicates` every game is kept.
+    pub fn skip_stored_games(&self, games: Vec<(SetupConfig, Vec<SeatAgent>)>, allow_duplicates: bool) -> (Vec<(SetupConfig, Vec<SeatAgent>)>, u32) {
+        let total = games.len() as u32;
+        let kept: Vec<_> = games.into_iter()
+            .filter(|(config, seats)| allow_duplicates || !self.contains_game(&GameKey::from_seats(config, seats)))
+            .collect();
+        let skipped = total - kept.len() as u32;
+        (kept, skipped)
+    }
+
+    /// Games stored with the same agents on the same houses and the same
+    /// options as `key`, whatever their seed.
+    pub fn pairing_games(&self, key: &GameKey) -> u32 {
+        self.conn.query_row(
+            "SELECT COUNT(*) FROM games WHERE lineup_hash = ?1 AND options_hash = ?2",
+            params![key.lineup_hash, key.options_hash],
+            |row| row.get(0),
+        ).unwrap_or(0)
+    }
+
     /// Update ELO ratings for a set of agents after a game.
     /// Simple multiplayer ELO: winner gains K points from each loser.
     pub fn update_elo(&self, winner_id: i64, loser_ids: &[i64], k: f64) {
@@ -168,9 +725,451 @@ impl Database {
         .collect()
     }
 
+    /// Leaderboard narrowed and ordered by `filter`. Without a house the
+    /// agents' running totals are used; with one, each game the agent
+    /// played as that house counts once.
+    pub fn filtered_leaderboard(&self, filter: &LeaderboardFilter) -> Vec<LeaderboardRow> {
+        let source = if filter.house.is_some() {
+            "SELECT a.name AS name, a.elo AS elo, COUNT(*) AS games, SUM(gp.house = g.winner) AS wins
+             FROM agents a
+             JOIN game_players gp ON gp.agent_id = a.id
+             JOIN games g ON g.id = gp.game_id
+             WHERE gp.house = ?1
+             GROUP BY a.id"
+        } else {
+            "SELECT name, elo, games, wins FROM agents WHERE ?1 IS NULL"
+        };
+        let sql = format!(
+            "SELECT name, elo, games, wins FROM ({})
+             WHERE games >= ?2 AND (?3 IS NULL OR substr(name, 1, length(?3)) = ?3)
+             ORDER BY {}",
+            source, filter.sort.order_by(),
+        );
+        let mut stmt = self.conn.prepare(&sql).expect("Failed to prepare leaderboard query");
+        let house = filter.house.map(|h| h.to_string());
+        stmt.query_map(params![house, filter.min_games, filter.agent_prefix], |row| {
+            Ok(LeaderboardRow {
+                name: row.get(0)?,
+                elo: row.get(1)?,
+                games: row.get(2)?,
+                wins: row.get(3)?,
+            })
+        })
+        .expect("Failed to query leaderboard")
+        .filter_map(|r| r.ok())
+        .collect()
+    }
+
+    /// Every agent's games and wins split by the house it played, ordered
+    /// by `filter.sort` over its totals. `filter.house` is ignored: the
+    /// pivot shows all houses.
+    pub fn house_pivot(&self, filter: &LeaderboardFilter) -> Vec<HousePivotRow> {
+        let sql = format!(
+            "WITH per_house AS (
+                 SELECT gp.agent_id AS agent_id, gp.house AS house,
+                        COUNT(*) AS house_games, SUM(gp.house = g.winner) AS house_wins
+                 FROM game_players gp JOIN games g ON g.id = gp.game_id
+                 GROUP BY gp.agent_id, gp.house
+             ),
+             totals AS (
+                 SELECT a.id AS id, a.name AS name, a.elo AS elo, SUM(p.house_games) AS games, SUM(p.house_wins) AS wins
+                 FROM agents a JOIN per_house p ON p.agent_id = a.id
+                 WHERE ?2 IS NULL OR substr(a.name, 1, length(?2)) = ?2
+                 GROUP BY a.id HAVING SUM(p.house_games) >= ?1
+             )
+             SELECT name, elo, house, house_games, house_wins
+             FROM totals JOIN per_house ON per_house.agent_id = totals.id
+             ORDER BY {}, house",
+            filter.sort.order_by(),
+        );
+        let mut stmt = self.conn.prepare(&sql).expect("Failed to prepare house pivot query");
+        let rows = stmt.query_map(params![filter.min_games, filter.agent_prefix], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, f64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, u32>(3)?,
+                row.get::<_, u32>(4)?,
+            ))
+        }).expect("Failed to query house pivot");
+
+        let mut board: Vec<HousePivotRow> = Vec::new();
+        for (name, elo, house, games, wins) in rows.filter_map(|r| r.ok()) {
+            let Ok(house) = house.parse() else { continue };
+            if board.last().is_none_or(|r| r.name != name) {
+                board.push(HousePivotRow { name, elo, by_house: Vec::new() });
+            }
+            board.last_mut().unwrap().by_house.push((house, games, wins));
+        }
+        board
+    }
+
+    /// Every stored game in the order it was played, as (agent id, rank)
+    /// seats. Games stored before final ranks existed rank the winner 1st
+    /// and everyone else tied 2nd.
+    pub fn rated_games(&self) -> Vec<Vec<(i64, u8)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT gp.game_id, gp.agent_id, gp.final_rank, gp.house = g.winner
+             FROM game_players gp JOIN games g ON g.id = gp.game_id
+             ORDER BY gp.game_id, gp.id"
+        ).expect("Failed to prepare rating history query");
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, Option<i64>>(2)?,
+                row.get::<_, bool>(3)?,
+            ))
+        }).expect("Failed to query rating history");
+
+        let mut games: Vec<Vec<(i64, u8)>> = Vec::new();
+        let mut current = None;
+        for (game_id, agent_id, rank, won) in rows.filter_map(|r| r.ok()) {
+            if current != Some(game_id) {
+                current = Some(game_id);
+                games.push(Vec::new());
+            }
+            let rank = rank.map_or(if won { 1 } else { 2 }, |r| r as u8);
+            games.last_mut().unwrap().push((agent_id, rank));
+        }
+        games
+    }
+
+    /// Wipe and rebuild a rating system from the stored game history, in
+    /// the order games were played. Returns the number of games rated.
+    pub fn recompute_ratings(&self, system: RatingSystem) -> usize {
+        let games = self.rated_games();
+        let mut ratings = HashMap::new();
+        for seats in &games {
+            ratings::update(system, &mut ratings, seats);
+        }
+        self.replace_ratings(system, &ratings);
+        games.len()
+    }
+
+    /// Replace all ratings for a system. Elo is also written to `agents.elo`.
+    pub fn replace_ratings(&self, system: RatingSystem, ratings: &HashMap<i64, Rating>) {
+        let tx = self.conn.unchecked_transaction().expect("Failed to begin transaction");
+        self.conn.execute("DELETE FROM ratings WHERE system = ?1", params![system.name()])
+            .expect("Failed to clear ratings");
+        if system == RatingSystem::Elo {
+            self.conn.execute("UPDATE agents SET elo = 1500.0", []).expect("Failed to reset ELO");
+        }
+        for (agent_id, r) in ratings {
+            self.conn.execute(
+                "INSERT INTO ratings (agent_id, system, mu, sigma) VALUES (?1, ?2, ?3, ?4)",
+                params![agent_id, system.name(), r.mu, r.sigma],
+            ).expect("Failed to store rating");
+            if system == RatingSystem::Elo {
+                self.conn.execute("UPDATE agents SET elo = ?1 WHERE id = ?2", params![r.mu, agent_id])
+                    .expect("Failed to store ELO");
+            }
+        }
+        tx.commit().expect("Failed to commit ratings");
+    }
+
+    /// Leaderboard for a rating system: (name, rating, games, wins), best first.
+    pub fn rating_leaderboard(&self, system: RatingSystem) -> Vec<(String, Rating, u32, u32)> {
+        let mut stmt = self.conn.prepare(
+            "SELECT a.name, r.mu, r.sigma, a.games, a.wins
+             FROM ratings r JOIN agents a ON a.id = r.agent_id
+             WHERE r.system = ?1"
+        ).expect("Failed to prepare rating leaderboard query");
+        let mut board: Vec<(String, Rating, u32, u32)> = stmt.query_map(params![system.name()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                Rating { mu: row.get(1)?, sigma: row.get(2)? },
+                row.get::<_, u32>(3)?,
+                row.get::<_, u32>(4)?,
+            ))
+        })
+        .expect("Failed to query rating leaderboard")
+        .filter_map(|r| r.ok())
+        .collect();
+        board.sort_by(|a, b| b.1.score().total_cmp(&a.1.score()));
+        board
+    }
+
+    /// Finishing order of a stored game (house names, winner first).
+    pub fn final_ranking(&self, game_id: i64) -> Vec<String> {
+        let mut stmt = self.conn.prepare(
+            "SELECT house FROM game_players WHERE game_id = ?1 AND final_rank IS NOT NULL ORDER BY final_rank"
+        ).expect("Failed to prepare ranking query");
+
+        stmt.query_map(params![game_id], |row| row.get::<_, String>(0))
+            .expect("Failed to query ranking")
+            .filter_map(|r| r.ok())
+            .collect()
+    }
+
+    // ── League ─────────────────────────────────────────────────────────
+
+    /// Add an agent to a league, or change the role of one already in it.
+    pub fn upsert_league_member(&self, league: &str, agent_id: i64, generation: u32, role: MemberRole) {
+        self.conn.execute(
+            "INSERT INTO league_members (league, agent_id, generation, role) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (league, agent_id) DO UPDATE SET role = excluded.role",
+            params![league, agent_id, generation, role.name()],
+        ).expect("Failed to store league member");
+    }
+
+    /// Every member of a league, oldest first.
+    pub fn league_members(&self, league: &str) -> Vec<LeagueMember> {
+        let mut stmt = self.conn.prepare(
+            "SELECT lm.agent_id, a.name, lm.generation, lm.role, lm.elo, lm.games
+             FROM league_members lm JOIN agents a ON a.id = lm.agent_id
+             WHERE lm.league = ?1 ORDER BY lm.generation, lm.rowid"
+        ).expect("Failed to prepare league query");
+        stmt.query_map(params![league], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, f64>(4)?,
+                row.get::<_, u32>(5)?,
+            ))
+        })
+        .expect("Failed to query league")
+        .filter_map(|r| r.ok())
+        .filter_map(|(agent_id, name, generation, role, elo, games)| Some(LeagueMember {
+            agent_id,
+            spec: name.parse().ok()?,
+            generation,
+            role: role.parse().ok()?,
+            elo,
+            games,
+        }))
+        .collect()
+    }
+
+    /// Record one generation's games and the members' updated league Elo.
+    pub fn record_league_generation(&self, league: &str, generation: u32, game_ids: &[i64], members: &[LeagueMember]) {
+        let tx = self.conn.unchecked_transaction().expect("Failed to begin transaction");
+        for id in game_ids {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO league_games (league, game_id, generation) VALUES (?1, ?2, ?3)",
+                params![league, id, generation],
+            ).expect("Failed to store league game");
+        }
+        for m in members {
+            self.conn.execute(
+                "UPDATE league_members SET elo = ?1, games = ?2 WHERE league = ?3 AND agent_id = ?4",
+                params![m.elo, m.games, league, m.agent_id],
+            ).expect("Failed to store league rating");
+        }
+        tx.commit().expect("Failed to commit league generation");
+    }
+
+    /// Names of all leagues with their member counts.
+    pub fn leagues(&self) -> Vec<(String, u32)> {
+        let mut stmt = self.conn.prepare(
+            "SELECT league, COUNT(*) FROM league_members GROUP BY league ORDER BY league"
+        ).expect("Failed to prepare league list query");
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .expect("Failed to query leagues")
+            .filter_map(|r| r.ok())
+            .collect()
+    }
+
+    // ── Matches ────────────────────────────────────────────────────────
+
+    /// Store a match with all its games in one transaction. Returns the
+    /// match id.
+    pub fn store_match(&self, result: &MatchResult, agent_ids: &[(String, i64)]) -> i64 {
+        let tx = self.conn.unchecked_transaction().expect("Failed to begin transaction");
+        self.conn.execute(
+            "INSERT INTO matches (seed, games) VALUES (?1, ?2)",
+            params![result.seed as i64, result.games.len() as i64],
+        ).expect("Failed to store match");
+        let match_id = self.conn.last_insert_rowid();
+        for (i, game) in result.games.iter().enumerate() {
+            let game_id = self.insert_game(game, agent_ids);
+            self.conn.execute(
+                "INSERT INTO match_games (match_id, game_id, game_index) VALUES (?1, ?2, ?3)",
+                params![match_id, game_id, i as i64],
+            ).expect("Failed to store match game");
+        }
+        for s in &result.standings {
+            let name = s.spec.to_string();
+            let agent_id = agent_ids.iter().find(|(n, _)| *n == name).map_or(0, |(_, id)| *id);
+            self.conn.execute(
+                "INSERT INTO match_players (match_id, seat, agent_id, wins, points, match_rank)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![match_id, s.seat as i64, agent_id, s.wins, s.points, s.place],
+            ).expect("Failed to store match player");
+        }
+        tx.commit().expect("Failed to commit match");
+        match_id
+    }
+
+    /// Every stored match in the order played, as (agent id, match rank)
+    /// seats.
+    pub fn rated_matches(&self) -> Vec<Vec<(i64, u8)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT match_id, agent_id, match_rank FROM match_players ORDER BY match_id, seat"
+        ).expect("Failed to prepare match history query");
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, u8>(2)?))
+        }).expect("Failed to query match history");
+
+        let mut matches: Vec<Vec<(i64, u8)>> = Vec::new();
+        let mut current = None;
+        for (match_id, agent_id, rank) in rows.filter_map(|r| r.ok()) {
+            if current != Some(match_id) {
+                current = Some(match_id);
+                matches.push(Vec::new());
+            }
+            matches.last_mut().unwrap().push((agent_id, rank));
+        }
+        matches
+    }
+
+    /// Leaderboard over whole matches, rated from scratch with `system`:
+    /// (name, rating, matches, match wins), best first. Shared first
+    /// places count as wins.
+    pub fn match_leaderboard(&self, system: RatingSystem) -> Vec<(String, Rating, u32, u32)> {
+        let matches = self.rated_matches();
+        let mut ratings = HashMap::new();
+        let mut totals: HashMap<i64, (u32, u32)> = HashMap::new();
+        for seats in &matches {
+            ratings::update(system, &mut ratings, seats);
+            for &(id, rank) in seats {
+                let t = totals.entry(id).or_default();
+                t.0 += 1;
+                t.1 += (rank == 1) as u32;
+            }
+        }
+        let mut stmt = self.conn.prepare("SELECT id, name FROM agents").expect("Failed to prepare agent query");
+        let names: HashMap<i64, String> = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .expect("Failed to query agents")
+            .filter_map(|r| r.ok())
+            .collect();
+        let mut board: Vec<(String, Rating, u32, u32)> = ratings.into_iter()
+            .filter_map(|(id, rating)| {
+                let (played, wins) = totals[&id];
+                Some((names.get(&id)?.clone(), rating, played, wins))
+            })
+            .collect();
+        board.sort_by(|a, b| b.1.score().total_cmp(&a.1.score()).then_with(|| a.0.cmp(&b.0)));
+        board
+    }
+
+    /// Every stored game as (winner, [(house, agent name)], options JSON),
+    /// for balance statistics. Options are None for standard games.
+    pub fn house_results(&self) -> Vec<HouseResult> {
+        let mut stmt = self.conn.prepare(
+            "SELECT g.id, g.winner, g.options, gp.house, a.name
+             FROM games g
+             JOIN game_players gp ON gp.game_id = g.id
+             JOIN agents a ON a.id = gp.agent_id
+             ORDER BY g.id, gp.id"
+        ).expect("Failed to prepare house results query");
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        }).expect("Failed to query house results");
+
+        let mut games: Vec<HouseResult> = Vec::new();
+        let mut current = None;
+        for (game_id, winner, options, house, agent) in rows.filter_map(|r| r.ok()) {
+            if current != Some(game_id) {
+                current = Some(game_id);
+                games.push((winner, Vec::new(), options));
+            }
+            games.last_mut().unwrap().1.push((house, agent));
+        }
+        games
+    }
+
+    /// Ids of all stored games, oldest first.
+    pub fn game_ids(&self) -> Vec<i64> {
+        let mut stmt = self.conn.prepare("SELECT id FROM games ORDER BY id")
+            .expect("Failed to prepare game id query");
+
+        stmt.query_map([], |row| row.get::<_, i64>(0))
+            .expect("Failed to query game ids")
+            .filter_map(|r| r.ok())
+            .collect()
+    }
+
     /// Get total number of games stored.
     pub fn game_count(&self) -> u32 {
         self.conn.query_row("SELECT COUNT(*) FROM games", [], |row| row.get(0))
             .unwrap_or(0)
     }
 }
+
+// ── Background writer ──────────────────────────────────────────────────
+
+/// Stores results on a dedicated thread so game workers never wait on
+/// SQLite. Results that arrive together are committed in one transaction.
+pub struct DbWriter {
+    tx: Option<Sender<GameResult>>,
+    handle: Option<JoinHandle<usize>>,
+}
+
+impl DbWriter {
+    /// Open `path` on a writer thread. At most `batch_size` games are
+    /// committed per transaction.
+    pub fn spawn(path: &str, agent_ids: Vec<(String, i64)>, batch_size: usize) -> Self {
+        DbWriter::spawn_with_metrics(path, agent_ids, batch_size, None)
+    }
+
+    /// `spawn`, timing every commit into `metrics`.
+    pub fn spawn_with_metrics(path: &str, agent_ids: Vec<(String, i64)>, batch_size: usize, metrics: Option<Arc<Metrics>>) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let path = path.to_string();
+        let handle = std::thread::spawn(move || {
+            let db = Database::new(&path);
+            write_batches(&db, &rx, &agent_ids, batch_size.max(1), metrics.as_deref())
+        });
+        DbWriter { tx: Some(tx), handle: Some(handle) }
+    }
+
+    /// Queue a result for storage.
+    pub fn send(&self, result: GameResult) {
+        self.tx.as_ref().unwrap().send(result).expect("Database writer thread stopped");
+    }
+
+    /// Flush outstanding results and return the number of games stored.
+    pub fn finish(mut self) -> usize {
+        self.tx.take();
+        self.handle.take().unwrap().join().expect("Database writer thread panicked")
+    }
+}
+
+impl Drop for DbWriter {
+    fn drop(&mut self) {
+        self.tx.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn write_batches(db: &Database, rx: &Receiver<GameResult>, agent_ids: &[(String, i64)], batch_size: usize, metrics: Option<&Metrics>) -> usize {
+    let mut stored = 0;
+    // Block for the first result, then take whatever else is already queued
+    while let Ok(first) = rx.recv() {
+        let mut batch = vec![first];
+        while batch.len() < batch_size {
+            match rx.try_recv() {
+                Ok(r) => batch.push(r),
+                Err(_) => break,
+            }
+        }
+        let start = Instant::now();
+        let written = db.store_games(&batch, agent_ids).len();
+        if let Some(metrics) = metrics {
+            metrics.record_db_write(written, start.elapsed());
+        }
+        stored += written;
+    }
+    stored
+}
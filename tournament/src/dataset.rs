@@ -0,0 +1,465 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Dataset export — stored games as supervised training samples
+//
+// Games are not stored move by move; every stored game records its engine
+// and agent seeds, so it is replayed deterministically and each decision
+// is captured on the way. A sample is (observation, decision kind, action
+// index, outcome), encoded by `got_engine::encoding`. The outcome is the
+// deciding house's final placing scaled to 1.0 (winner) .. 0.0 (last).
+//
+// Splits are by game: every sample of a game lands in the same split, so
+// validation never sees positions from a training game.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::database::Database;
+use crate::runner::run_seated_game_observed;
+use got_engine::encoding::{decision_targets, encode_observation, DecisionKind, OBS_LEN};
+use got_engine::types::HouseName;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// One decision target with the observation it was made from.
+#[derive(Debug, Clone)]
+pub struct Sample {
+    pub game_id: i64,
+    pub house: HouseName,
+    pub kind: DecisionKind,
+    /// Area a per-area target is about, if any.
+    pub focus: Option<u8>,
+    pub action: u32,
+    pub outcome: f32,
+    pub obs: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatasetFormat {
+    /// NumPy archive: one `.npz` per split with one array per column.
+    Npz,
+    /// Apache Parquet: one file per split, observations as a fixed-size list column.
+    Parquet,
+}
+
+impl DatasetFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            DatasetFormat::Npz => "npz",
+            DatasetFormat::Parquet => "parquet",
+        }
+    }
+}
+
+impl FromStr for DatasetFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "npz" => Ok(DatasetFormat::Npz),
+            "parquet" => Ok(DatasetFormat::Parquet),
+            other => Err(format!("Unknown dataset format: {} (known: npz, parquet)", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub format: DatasetFormat,
+    /// Share of games (0–1) put in the validation split.
+    pub val_fraction: f64,
+    /// Only keep decisions made by seats with this agent spec.
+    pub agent: Option<String>,
+    /// Stop after this many games.
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ExportSummary {
+    pub train_games: usize,
+    pub val_games: usize,
+    pub train_samples: usize,
+    pub val_samples: usize,
+    /// Games that could not be replayed (no seeds stored, or the replay
+    /// did not reproduce the stored winner).
+    pub skipped: Vec<(i64, String)>,
+}
+
+/// Whether a game belongs to the validation split. Fixed per game id, so
+/// re-exporting the same database gives the same split.
+pub fn is_validation(game_id: i64, val_fraction: f64) -> bool {
+    let h = (game_id as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 11;
+    (h as f64 / (1u64 << 53) as f64) < val_fraction
+}
+
+/// Replay a stored game and collect its samples.
+pub fn game_samples(db: &Database, game_id: i64, agent: Option<&str>) -> Result<Vec<Sample>, String> {
+    let (config, seats, stored_winner) = db.load_game_setup(game_id)?;
+    let mut samples = Vec::new();
+    let result = run_seated_game_observed(&config, &seats, 50_000, |house, view, action| {
+        let spec = seats.iter().find(|s| s.house == house).map(|s| s.spec.to_string());
+        if agent.is_some_and(|a| spec.as_deref() != Some(a)) {
+            return;
+        }
+        let Some(pending) = view.pending.as_ref() else { return };
+        for target in decision_targets(pending, action) {
+            samples.push(Sample {
+                game_id,
+                house,
+                kind: target.kind,
+                focus: target.focus.map(|a| a.0),
+                action: target.action,
+                outcome: 0.0,
+                obs: encode_observation(view, target.focus),
+            });
+        }
+    })?;
+    if result.winner.to_string() != stored_winner {
+        return Err(format!("replay winner {} does not match stored winner {}", result.winner, stored_winner));
+    }
+
+    let n = result.final_ranking.len().max(2);
+    for s in &mut samples {
+        let rank = result.final_ranking.iter().position(|&h| h == s.house).unwrap_or(n - 1);
+        s.outcome = 1.0 - rank as f32 / (n - 1) as f32;
+    }
+    Ok(samples)
+}
+
+/// Replay every stored game and write `train.<ext>`, `val.<ext>` and
+/// `meta.json` into `out_dir`.
+pub fn export(db: &Database, out_dir: &Path, opts: &ExportOptions) -> Result<ExportSummary, String> {
+    fs::create_dir_all(out_dir).map_err(|e| format!("Cannot create {}: {}", out_dir.display(), e))?;
+    let mut train = new_writer(opts.format, out_dir, "train")?;
+    let mut val = new_writer(opts.format, out_dir, "val")?;
+    let mut summary = ExportSummary::default();
+
+    let ids = db.game_ids();
+    for &game_id in ids.iter().take(opts.limit.unwrap_or(usize::MAX)) {
+        let samples = match game_samples(db, game_id, opts.agent.as_deref()) {
+            Ok(s) => s,
+            Err(e) => {
+                summary.skipped.push((game_id, e));
+                continue;
+            }
+        };
+        if is_validation(game_id, opts.val_fraction) {
+            val.write(&samples)?;
+            summary.val_games += 1;
+            summary.val_samples += samples.len();
+        } else {
+            train.write(&samples)?;
+            summary.train_games += 1;
+            summary.train_samples += samples.len();
+        }
+    }
+    train.finish()?;
+    val.finish()?;
+    write_meta(out_dir, opts, &summary)?;
+    Ok(summary)
+}
+
+fn write_meta(out_dir: &Path, opts: &ExportOptions, summary: &ExportSummary) -> Result<(), String> {
+    let decisions: Vec<serde_json::Value> = DecisionKind::ALL.iter()
+        .map(|k| serde_json::json!({ "id": k.index(), "name": k.name(), "action_space": k.action_space() }))
+        .collect();
+    let meta = serde_json::json!({
+        "format": opts.format.extension(),
+        "obs_len": OBS_LEN,
+        "houses": HouseName::ALL.iter().map(|h| h.to_string()).collect::<Vec<_>>(),
+        "decisions": decisions,
+        "agent": opts.agent,
+        "val_fraction": opts.val_fraction,
+        "train": { "games": summary.train_games, "samples": summary.train_samples },
+        "val": { "games": summary.val_games, "samples": summary.val_samples },
+    });
+    let path = out_dir.join("meta.json");
+    fs::write(&path, serde_json::to_string_pretty(&meta).unwrap())
+        .map_err(|e| format!("Cannot write {}: {}", path.display(), e))
+}
+
+// ── Writers ────────────────────────────────────────────────────────────
+
+trait SampleWriter {
+    fn write(&mut self, samples: &[Sample]) -> Result<(), String>;
+    fn finish(self: Box<Self>) -> Result<(), String>;
+}
+
+fn new_writer(format: DatasetFormat, dir: &Path, split: &str) -> Result<Box<dyn SampleWriter>, String> {
+    let path = dir.join(format!("{}.{}", split, format.extension()));
+    match format {
+        DatasetFormat::Npz => Ok(Box::new(NpzWriter::new(path)?)),
+        DatasetFormat::Parquet => Ok(Box::new(ParquetWriter::new(path)?)),
+    }
+}
+
+fn house_index(h: HouseName) -> u8 {
+    HouseName::ALL.iter().position(|&x| x == h).unwrap() as u8
+}
+
+/// A column of an `.npz`: little-endian values spooled to a temp file
+/// until the row count (needed by the `.npy` header) is known.
+struct NpyColumn {
+    name: &'static str,
+    descr: &'static str,
+    width: usize,
+    path: PathBuf,
+    out: BufWriter<File>,
+}
+
+struct NpzWriter {
+    path: PathBuf,
+    rows: usize,
+    columns: Vec<NpyColumn>,
+}
+
+impl NpzWriter {
+    const COLUMNS: [(&'static str, &'static str, usize); 7] = [
+        ("game_id", "<i8", 1),
+        ("house", "|u1", 1),
+        ("decision", "|u1", 1),
+        ("focus", "<i2", 1),
+        ("action", "<i4", 1),
+        ("outcome", "<f4", 1),
+        ("obs", "<f4", OBS_LEN),
+    ];
+
+    fn new(path: PathBuf) -> Result<Self, String> {
+        let mut columns = Vec::new();
+        for (name, descr, width) in Self::COLUMNS {
+            let tmp = path.with_extension(format!("{}.tmp", name));
+            let file = File::create(&tmp).map_err(|e| format!("Cannot create {}: {}", tmp.display(), e))?;
+            columns.push(NpyColumn { name, descr, width, path: tmp, out: BufWriter::new(file) });
+        }
+        Ok(NpzWriter { path, rows: 0, columns })
+    }
+
+    fn write_rows(&mut self, samples: &[Sample]) -> io::Result<()> {
+        let [game_id, house, decision, focus, action, outcome, obs] = &mut self.columns[..] else { unreachable!() };
+        for s in samples {
+            game_id.out.write_all(&s.game_id.to_le_bytes())?;
+            house.out.write_all(&[house_index(s.house)])?;
+            decision.out.write_all(&[s.kind.index() as u8])?;
+            focus.out.write_all(&s.focus.map_or(-1i16, |a| a as i16).to_le_bytes())?;
+            action.out.write_all(&(s.action as i32).to_le_bytes())?;
+            outcome.out.write_all(&s.outcome.to_le_bytes())?;
+            for v in &s.obs {
+                obs.out.write_all(&v.to_le_bytes())?;
+            }
+        }
+        self.rows += samples.len();
+        Ok(())
+    }
+
+    fn assemble(self) -> Result<(), String> {
+        use zip::write::SimpleFileOptions;
+        let file = File::create(&self.path).map_err(|e| format!("Cannot create {}: {}", self.path.display(), e))?;
+        let mut zip = zip::ZipWriter::new(BufWriter::new(file));
+        let options = SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored)
+            .large_file(true);
+        for col in self.columns {
+            let file = col.out.into_inner().map_err(|e| e.to_string())?;
+            drop(file);
+            let shape = if col.width == 1 {
+                format!("({},)", self.rows)
+            } else {
+                format!("({}, {})", self.rows, col.width)
+            };
+            zip.start_file(format!("{}.npy", col.name), options).map_err(|e| e.to_string())?;
+            zip.write_all(&npy_header(col.descr, &shape)).map_err(|e| e.to_string())?;
+            let mut data = File::open(&col.path).map_err(|e| e.to_string())?;
+            io::copy(&mut data, &mut zip).map_err(|e| e.to_string())?;
+            let _ = fs::remove_file(&col.path);
+        }
+        zip.finish().map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+impl SampleWriter for NpzWriter {
+    fn write(&mut self, samples: &[Sample]) -> Result<(), String> {
+        self.write_rows(samples).map_err(|e| format!("Cannot write {}: {}", self.path.display(), e))
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), String> {
+        (*self).assemble()
+    }
+}
+
+/// `.npy` v1.0 header, padded so the data starts on a 64-byte boundary.
+fn npy_header(descr: &str, shape: &str) -> Vec<u8> {
+    let mut dict = format!("{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}", descr, shape);
+    let unpadded = 10 + dict.len() + 1;
+    dict.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+    dict.push('\n');
+    let mut header = b"\x93NUMPY\x01\x00".to_vec();
+    header.extend_from_slice(&(dict.len() as u16).to_le_bytes());
+    header.extend_from_slice(dict.as_bytes());
+    header
+}
+
+struct ParquetWriter {
+    path: PathBuf,
+    schema: arrow_schema::SchemaRef,
+    writer: parquet::arrow::ArrowWriter<File>,
+}
+
+impl ParquetWriter {
+    fn obs_item() -> arrow_schema::FieldRef {
+        Arc::new(arrow_schema::Field::new("item", arrow_schema::DataType::Float32, false))
+    }
+
+    fn new(path: PathBuf) -> Result<Self, String> {
+        use arrow_schema::{DataType, Field, Schema};
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("game_id", DataType::Int64, false),
+            Field::new("house", DataType::Utf8, false),
+            Field::new("decision", DataType::UInt8, false),
+            Field::new("focus", DataType::Int16, true),
+            Field::new("action", DataType::Int32, false),
+            Field::new("outcome", DataType::Float32, false),
+            Field::new("obs", DataType::FixedSizeList(Self::obs_item(), OBS_LEN as i32), false),
+        ]));
+        let file = File::create(&path).map_err(|e| format!("Cannot create {}: {}", path.display(), e))?;
+        let writer = parquet::arrow::ArrowWriter::try_new(file, schema.clone(), None).map_err(|e| e.to_string())?;
+        Ok(ParquetWriter { path, schema, writer })
+    }
+}
+
+impl SampleWriter for ParquetWriter {
+    fn write(&mut self, samples: &[Sample]) -> Result<(), String> {
+        use arrow_array::*;
+        if samples.is_empty() {
+            return Ok(());
+        }
+        let obs: Vec<f32> = samples.iter().flat_map(|s| s.obs.iter().copied()).collect();
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from_iter_values(samples.iter().map(|s| s.game_id))),
+            Arc::new(StringArray::from_iter_values(samples.iter().map(|s| s.house.to_string()))),
+            Arc::new(UInt8Array::from_iter_values(samples.iter().map(|s| s.kind.index() as u8))),
+            Arc::new(Int16Array::from_iter(samples.iter().map(|s| s.focus.map(|a| a as i16)))),
+            Arc::new(Int32Array::from_iter_values(samples.iter().map(|s| s.action as i32))),
+            Arc::new(Float32Array::from_iter_values(samples.iter().map(|s| s.outcome))),
+            Arc::new(FixedSizeListArray::try_new(Self::obs_item(), OBS_LEN as i32, Arc::new(Float32Array::from(obs)), None)
+                .map_err(|e| e.to_string())?),
+        ];
+        let batch = RecordBatch::try_new(self.schema.clone(), columns).map_err(|e| e.to_string())?;
+        self.writer.write(&batch).map_err(|e| format!("Cannot write {}: {}", self.path.display(), e))
+    }
+
+    fn finish(self: Box<Self>) -> Result<(), String> {
+        self.writer.close().map(|_| ()).map_err(|e| format!("Cannot write {}: {}", self.path.display(), e))
+    }
+}
+
+// ── Reading ────────────────────────────────────────────────────────────
+
+/// An exported split loaded back into memory, one entry per sample.
+#[derive(Debug, Clone, Default)]
+pub struct LoadedSplit {
+    pub game_id: Vec<i64>,
+    pub decision: Vec<u8>,
+    pub focus: Vec<i16>,
+    pub action: Vec<i32>,
+    pub outcome: Vec<f32>,
+    /// Row-major, OBS_LEN values per sample.
+    pub obs: Vec<f32>,
+}
+
+impl LoadedSplit {
+    pub fn len(&self) -> usize {
+        self.action.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.action.is_empty()
+    }
+
+    pub fn obs_row(&self, i: usize) -> &[f32] {
+        &self.obs[i * OBS_LEN..(i + 1) * OBS_LEN]
+    }
+
+    pub fn kind(&self, i: usize) -> DecisionKind {
+        DecisionKind::ALL[self.decision[i] as usize]
+    }
+}
+
+/// Load `<split>.npz` or `<split>.parquet` from an export directory.
+pub fn load_split(dir: &Path, split: &str) -> Result<LoadedSplit, String> {
+    let npz = dir.join(format!("{}.npz", split));
+    let parquet = dir.join(format!("{}.parquet", split));
+    let data = if npz.exists() {
+        read_npz(&npz)?
+    } else if parquet.exists() {
+        read_parquet(&parquet)?
+    } else {
+        return Err(format!("No {} split in {}", split, dir.display()));
+    };
+    if data.obs.len() != data.len() * OBS_LEN {
+        return Err(format!("{} split in {} has {}-wide observations, this build encodes {}",
+            split, dir.display(), data.obs.len() / data.len().max(1), OBS_LEN));
+    }
+    Ok(data)
+}
+
+fn read_npz(path: &Path) -> Result<LoadedSplit, String> {
+    let err = |e: String| format!("Cannot read {}: {}", path.display(), e);
+    let file = File::open(path).map_err(|e| err(e.to_string()))?;
+    let mut zip = zip::ZipArchive::new(io::BufReader::new(file)).map_err(|e| err(e.to_string()))?;
+    let mut column = |name: &str| -> Result<Vec<u8>, String> {
+        let mut entry = zip.by_name(&format!("{}.npy", name)).map_err(|e| err(format!("{}: {}", name, e)))?;
+        let mut bytes = Vec::new();
+        io::Read::read_to_end(&mut entry, &mut bytes).map_err(|e| err(e.to_string()))?;
+        npy_data(bytes).ok_or_else(|| err(format!("{}.npy is not a .npy array", name)))
+    };
+    fn le<const N: usize, T>(bytes: &[u8], f: fn([u8; N]) -> T) -> Vec<T> {
+        bytes.chunks_exact(N).map(|b| f(b.try_into().unwrap())).collect()
+    }
+    Ok(LoadedSplit {
+        game_id: le(&column("game_id")?, i64::from_le_bytes),
+        decision: column("decision")?,
+        focus: le(&column("focus")?, i16::from_le_bytes),
+        action: le(&column("action")?, i32::from_le_bytes),
+        outcome: le(&column("outcome")?, f32::from_le_bytes),
+        obs: le(&column("obs")?, f32::from_le_bytes),
+    })
+}
+
+/// Strip a `.npy` header, leaving the raw array data.
+fn npy_data(mut bytes: Vec<u8>) -> Option<Vec<u8>> {
+    if !bytes.starts_with(b"\x93NUMPY") || bytes.len() < 10 {
+        return None;
+    }
+    let header_len = match bytes[6] {
+        1 => 10 + u16::from_le_bytes([bytes[8], bytes[9]]) as usize,
+        _ => 12 + u32::from_le_bytes(bytes.get(8..12)?.try_into().ok()?) as usize,
+    };
+    if header_len > bytes.len() {
+        return None;
+    }
+    bytes.drain(..header_len);
+    Some(bytes)
+}
+
+fn read_parquet(path: &Path) -> Result<LoadedSplit, String> {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Float32Type, Int16Type, Int32Type, Int64Type, UInt8Type};
+    let err = |e: String| format!("Cannot read {}: {}", path.display(), e);
+    let file = File::open(path).map_err(|e| err(e.to_string()))?;
+    let reader = parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(file)
+        .and_then(|b| b.build())
+        .map_err(|e| err(e.to_string()))?;
+    let mut data = LoadedSplit::default();
+    for batch in reader {
+        let batch = batch.map_err(|e| err(e.to_string()))?;
+        let col = |name: &str| batch.column_by_name(name).cloned().ok_or_else(|| err(format!("missing column {}", name)));
+        data.game_id.extend(col("game_id")?.as_primitive::<Int64Type>().values());
+        data.decision.extend(col("decision")?.as_primitive::<UInt8Type>().values());
+        data.focus.extend(col("focus")?.as_primitive::<Int16Type>().iter().map(|f| f.unwrap_or(-1)));
+        data.action.extend(col("action")?.as_primitive::<Int32Type>().values());
+        data.outcome.extend(col("outcome")?.as_primitive::<Float32Type>().values());
+        data.obs.extend(col("obs")?.as_fixed_size_list().values().as_primitive::<Float32Type>().values());
+    }
+    Ok(data)
+}
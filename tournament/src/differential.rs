@@ -0,0 +1,201 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Differential testing — this engine against a reference engine
+//
+// The engine is a port of a TypeScript original. To find where the two
+// disagree, a game is played here with random legal actions and every
+// action is fed to the reference engine as well; after each step the
+// public states are compared. The first step where they differ is
+// reported with the phase it was played in and the fields that differ,
+// so discrepancies in combat, supply or Westeros resolution show up as
+// such. Play stops there: after one difference every later step would
+// differ too.
+//
+// The reference engine is any command (run through the shell) speaking
+// JSON Lines on stdin/stdout, one response line per request line, in the
+// wire format of `got_engine::wire`:
+//
+//   → {"type":"setup","setup":{...SetupConfig}}
+//   ← {"state":{...public state}}
+//   → {"type":"action","house":"Stark","action":{"type":"bid","value":2}}
+//   ← {"state":{...}}            or   {"error":"..."}
+//
+// The public state is a `PlayerView` without the viewer's private fields
+// (viewer, pending, my_hand, my_orders, clock), orders hidden during
+// Planning; see `public_state`.
+// ═══════════════════════════════════════════════════════════════════════
+
+use got_engine::engine::{self, Action};
+use got_engine::setup::{create_game, SetupConfig};
+use got_engine::types::*;
+use got_engine::visibility::player_view;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+/// Fields of a `PlayerView` that only its viewer sees.
+const PRIVATE_FIELDS: &[&str] = &["viewer", "pending", "my_hand", "my_orders", "clock"];
+
+/// Differences listed per divergence at most.
+const MAX_DIFFERENCES: usize = 20;
+
+/// A running reference engine.
+pub struct ReferenceEngine {
+    command: String,
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl ReferenceEngine {
+    /// Start `command` through the shell.
+    pub fn spawn(command: &str) -> Result<Self, String> {
+        let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+        let mut child = Command::new(shell)
+            .args([flag, command])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("cannot run `{}`: {}", command, e))?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        Ok(ReferenceEngine { command: command.to_string(), child, stdin, stdout })
+    }
+
+    /// Start a game; the public state it begins in.
+    pub fn setup(&mut self, setup: &SetupConfig) -> Result<Value, String> {
+        self.request(&json!({ "type": "setup", "setup": setup }))
+    }
+
+    /// Apply `house`'s answer; the public state after it.
+    pub fn apply(&mut self, house: HouseName, action: &Action) -> Result<Value, String> {
+        self.request(&json!({ "type": "action", "house": house, "action": action }))
+    }
+
+    fn request(&mut self, message: &Value) -> Result<Value, String> {
+        writeln!(self.stdin, "{}", message)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| format!("`{}`: {}", self.command, e))?;
+        let mut line = String::new();
+        match self.stdout.read_line(&mut line) {
+            Ok(0) => return Err(format!("`{}` closed its output", self.command)),
+            Ok(_) => {}
+            Err(e) => return Err(format!("`{}`: {}", self.command, e)),
+        }
+        let mut response: Value = serde_json::from_str(&line)
+            .map_err(|e| format!("`{}` answered with invalid JSON: {}", self.command, e))?;
+        if let Some(error) = response.get("error") {
+            return Err(format!("reference engine: {}", error.as_str().map_or_else(|| error.to_string(), str::to_string)));
+        }
+        response.get_mut("state").map(Value::take)
+            .ok_or_else(|| format!("`{}` answered without a state", self.command))
+    }
+}
+
+impl Drop for ReferenceEngine {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// What every player can see of `state`, as compared with the reference.
+pub fn public_state(state: &GameState) -> Value {
+    let mut view = serde_json::to_value(player_view(state, state.playing_houses[0])).expect("player view serializes");
+    let fields = view.as_object_mut().expect("player view is an object");
+    for field in PRIVATE_FIELDS {
+        fields.remove(*field);
+    }
+    if state.phase() == Phase::Planning {
+        if let Some(Value::Array(areas)) = fields.get_mut("areas") {
+            for (area, area_state) in areas.iter_mut().zip(&state.areas) {
+                area["order"] = Value::Null;
+                area["has_hidden_order"] = Value::Bool(area_state.order.is_some());
+            }
+        }
+    }
+    view
+}
+
+/// Where two JSON values differ, as `path: ours != theirs` lines (object
+/// keys and array indices joined by dots).
+pub fn json_diff(ours: &Value, theirs: &Value) -> Vec<String> {
+    let mut lines = Vec::new();
+    diff_into(ours, theirs, String::new(), &mut lines);
+    lines
+}
+
+fn diff_into(ours: &Value, theirs: &Value, path: String, lines: &mut Vec<String>) {
+    let child = |key: &str| if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) };
+    match (ours, theirs) {
+        (Value::Object(a), Value::Object(b)) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys().filter(|k| !a.contains_key(*k))).collect();
+            keys.sort();
+            for key in keys {
+                let null = Value::Null;
+                diff_into(a.get(key).unwrap_or(&null), b.get(key).unwrap_or(&null), child(key), lines);
+            }
+        }
+        (Value::Array(a), Value::Array(b)) if a.len() == b.len() => {
+            for (i, (x, y)) in a.iter().zip(b).enumerate() {
+                diff_into(x, y, child(&i.to_string()), lines);
+            }
+        }
+        (a, b) if a != b => lines.push(format!("{}: {} != {}", if path.is_empty() { "." } else { &path }, a, b)),
+        _ => {}
+    }
+}
+
+/// The first step where the engines disagree.
+#[derive(Debug, Clone)]
+pub struct Divergence {
+    /// Actions applied before the states differed (0: right after setup).
+    pub step: usize,
+    /// Round and phase the differing action was played in.
+    pub round: u8,
+    pub phase: Phase,
+    /// The action, None when the setups already differ.
+    pub action: Option<(HouseName, Action)>,
+    /// `json_diff` of the two public states, at most `MAX_DIFFERENCES`.
+    pub differences: Vec<String>,
+}
+
+/// Play `setup` with random legal actions (seeded by `action_seed`) on
+/// both engines for at most `max_steps` actions. None if they agree
+/// throughout; an error if the reference engine fails.
+pub fn diff_game(
+    reference: &mut ReferenceEngine,
+    setup: &SetupConfig,
+    action_seed: u64,
+    max_steps: usize,
+) -> Result<Option<Divergence>, String> {
+    let mut state = create_game(setup)?;
+    engine::advance(&mut state);
+    let compare = |state: &GameState, theirs: &Value| {
+        let mut differences = json_diff(&public_state(state), theirs);
+        differences.truncate(MAX_DIFFERENCES);
+        differences
+    };
+
+    let differences = compare(&state, &reference.setup(setup)?);
+    if !differences.is_empty() {
+        return Ok(Some(Divergence { step: 0, round: state.round, phase: state.phase(), action: None, differences }));
+    }
+    let mut rng = ChaCha8Rng::seed_from_u64(action_seed);
+    for step in 1..=max_steps {
+        let Some(house) = state.peek_pending().map(|p| p.house()) else { break };
+        let Some(action) = engine::random_legal_action(&state, &mut rng) else { break };
+        let (round, phase) = (state.round, state.phase());
+        engine::apply_action(&mut state, action.clone())?;
+        engine::advance(&mut state);
+        let differences = compare(&state, &reference.apply(house, &action)?);
+        if !differences.is_empty() {
+            return Ok(Some(Divergence { step, round, phase, action: Some((house, action)), differences }));
+        }
+        if state.winner.is_some() {
+            break;
+        }
+    }
+    Ok(None)
+}
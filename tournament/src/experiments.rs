@@ -0,0 +1,190 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Handicap experiments — how much each starting resource is worth
+//
+// A perturbation changes one house's start through a `Handicap`: more or
+// fewer power tokens, a shifted supply level, or one starting unit left
+// off the board. For every seed a baseline mirror game (the same agent in
+// every seat, standard setup) is played, then one game per house and
+// perturbation with the same seed, seats and agent seeds, so the two
+// games of a pair differ only in the perturbed house's start.
+//
+// A row of the report is one (house, perturbation): the house's win rate
+// in the baseline games and in the perturbed ones, and the mean paired
+// difference with its standard error. Perturbations the setup rejects
+// (removing a unit type a house does not start with) have no row.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::runner::{assign_seats, run_games_parallel, ActionPolicy, SeatAgent};
+use got_agents::AgentSpec;
+use got_engine::setup::SetupConfig;
+use got_engine::types::{GameOptions, Handicap, HouseName, UnitType};
+use std::fmt;
+use std::sync::Mutex;
+
+/// One change to a house's starting resources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Perturbation {
+    /// Power tokens added to the starting 5 (may be negative).
+    Power(i8),
+    /// Barrels added to the supply level (may be negative).
+    Supply(i8),
+    /// One starting unit of this type removed.
+    RemoveUnit(UnitType),
+}
+
+impl Perturbation {
+    /// The experiments run when none are named.
+    pub const DEFAULTS: [Perturbation; 6] = [
+        Perturbation::Power(2),
+        Perturbation::Power(-2),
+        Perturbation::Supply(1),
+        Perturbation::Supply(-1),
+        Perturbation::RemoveUnit(UnitType::Footman),
+        Perturbation::RemoveUnit(UnitType::Knight),
+    ];
+
+    pub fn handicap(self, house: HouseName) -> Handicap {
+        let none = Handicap::none(house);
+        match self {
+            Perturbation::Power(n) => Handicap { power_bonus: n, ..none },
+            Perturbation::Supply(n) => Handicap { supply_adjust: n, ..none },
+            Perturbation::RemoveUnit(ut) => Handicap { removed_unit: Some(ut), ..none },
+        }
+    }
+}
+
+/// `power+2`, `supply-1`, `remove-footman`.
+impl fmt::Display for Perturbation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Perturbation::Power(n) => write!(f, "power{:+}", n),
+            Perturbation::Supply(n) => write!(f, "supply{:+}", n),
+            Perturbation::RemoveUnit(ut) => write!(f, "remove-{}", format!("{:?}", ut).to_lowercase()),
+        }
+    }
+}
+
+impl std::str::FromStr for Perturbation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let amount = |rest: &str| rest.parse::<i8>().ok().filter(|&n| n != 0)
+            .ok_or_else(|| format!("Bad perturbation '{}': expected a signed non-zero amount, e.g. power+2", s));
+        if let Some(rest) = s.strip_prefix("power") {
+            return amount(rest).map(Perturbation::Power);
+        }
+        if let Some(rest) = s.strip_prefix("supply") {
+            return amount(rest).map(Perturbation::Supply);
+        }
+        if let Some(rest) = s.strip_prefix("remove-") {
+            return UnitType::ALL.iter()
+                .find(|ut| format!("{:?}", ut).eq_ignore_ascii_case(rest))
+                .map(|&ut| Perturbation::RemoveUnit(ut))
+                .ok_or_else(|| format!("Unknown unit type '{}'", rest));
+        }
+        Err(format!("Unknown perturbation '{}' (expected power±N, supply±N or remove-<unit>)", s))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExperimentOptions {
+    /// Agent in every seat.
+    pub agent: AgentSpec,
+    pub perturbations: Vec<Perturbation>,
+    pub seeds: u64,
+    pub first_seed: u64,
+    pub players: u8,
+    pub threads: usize,
+}
+
+/// Win-rate sensitivity of one house to one perturbation.
+#[derive(Debug, Clone)]
+pub struct Sensitivity {
+    pub house: HouseName,
+    pub perturbation: Perturbation,
+    /// Seeds where both games of the pair finished.
+    pub pairs: usize,
+    pub base_wins: u32,
+    pub perturbed_wins: u32,
+    pub base_rate: f64,
+    pub perturbed_rate: f64,
+    /// Mean of the paired differences (perturbed − base) and its standard error.
+    pub delta: f64,
+    pub std_err: f64,
+}
+
+/// The same seed and seats with `perturbation` applied to `house`.
+pub fn perturbed_setup(config: &SetupConfig, house: HouseName, perturbation: Perturbation) -> Option<SetupConfig> {
+    let options = GameOptions { handicaps: vec![perturbation.handicap(house)], ..config.options.clone() };
+    let perturbed = SetupConfig { options, ..config.clone() };
+    perturbed.validate().is_ok().then_some(perturbed)
+}
+
+/// Play every pair and summarise each (house, perturbation), houses in
+/// seating order and perturbations in the order given.
+pub fn run_experiments(opts: &ExperimentOptions, max_decisions: usize) -> Result<Vec<Sensitivity>, String> {
+    opts.agent.build(HouseName::Stark, 0)?;
+    let houses = SetupConfig::new(opts.players, opts.first_seed).houses;
+    let cells: Vec<(HouseName, Perturbation)> = houses.iter()
+        .flat_map(|&h| opts.perturbations.iter().map(move |&p| (h, p)))
+        .collect();
+
+    // Per seed: the baseline game, then one game per cell the setup
+    // accepts; `slots` maps each seed's cells to their games
+    let mut games: Vec<(SetupConfig, Vec<SeatAgent>)> = Vec::new();
+    let mut slots: Vec<Vec<Option<usize>>> = Vec::new();
+    for s in 0..opts.seeds {
+        let config = SetupConfig::new(opts.players, opts.first_seed + s);
+        let seats = assign_seats(&config, &vec![opts.agent.clone(); config.houses.len()]);
+        let mut seed_slots = vec![Some(games.len())];
+        games.push((config.clone(), seats.clone()));
+        for &(house, perturbation) in &cells {
+            seed_slots.push(perturbed_setup(&config, house, perturbation).map(|perturbed| {
+                games.push((perturbed, seats.clone()));
+                games.len() - 1
+            }));
+        }
+        slots.push(seed_slots);
+    }
+
+    let winners: Mutex<Vec<Option<HouseName>>> = Mutex::new(vec![None; games.len()]);
+    run_games_parallel(&games, opts.threads, max_decisions, ActionPolicy::Lenient, |i, result| {
+        if let Ok(r) = result {
+            winners.lock().unwrap()[i] = r.final_ranking.first().copied();
+        }
+    });
+    let winners = winners.into_inner().unwrap();
+
+    Ok(cells.into_iter().enumerate()
+        .map(|(c, (house, perturbation))| {
+            let won = |slot: Option<usize>| winners[slot?].map(|w| if w == house { 1.0 } else { 0.0 });
+            let pairs: Vec<(f64, f64)> = slots.iter()
+                .filter_map(|seed_slots| Some((won(seed_slots[0])?, won(seed_slots[c + 1])?)))
+                .collect();
+            summarise(house, perturbation, &pairs)
+        })
+        .filter(|s| s.pairs > 0)
+        .collect())
+}
+
+fn summarise(house: HouseName, perturbation: Perturbation, pairs: &[(f64, f64)]) -> Sensitivity {
+    let n = pairs.len();
+    let mean = |f: &dyn Fn(&(f64, f64)) -> f64| if n == 0 { 0.0 } else { pairs.iter().map(f).sum::<f64>() / n as f64 };
+    let delta = mean(&|p| p.1 - p.0);
+    let variance = if n > 1 {
+        pairs.iter().map(|p| (p.1 - p.0 - delta).powi(2)).sum::<f64>() / (n - 1) as f64
+    } else {
+        0.0
+    };
+    Sensitivity {
+        house,
+        perturbation,
+        pairs: n,
+        base_wins: pairs.iter().filter(|p| p.0 > 0.0).count() as u32,
+        perturbed_wins: pairs.iter().filter(|p| p.1 > 0.0).count() as u32,
+        base_rate: mean(&|p| p.0),
+        perturbed_rate: mean(&|p| p.1),
+        delta,
+        std_err: (variance / n.max(1) as f64).sqrt(),
+    }
+}
@@ -0,0 +1,283 @@
+// ═══════════════════════════════════════════════════════════════════════
+// League — self-play training against a pool of frozen checkpoints
+//
+// A league is a named set of agents in the database:
+//   baseline  fixed opponents (e.g. heuristic), always in the pool
+//   learner   the cloned model currently being trained
+//   frozen    past learner versions kept as opponents
+//   retired   past learner versions no longer played against
+//
+// Each generation the learner takes every other seat and the remaining
+// seats are drawn from the pool (baselines + frozen). The learner is then
+// trained further on the winners' decisions from those games and saved as
+// the next generation's checkpoint. Every `freeze_every` generations the
+// best-rated learner version since the last freeze joins the pool, so the
+// learner keeps meeting the strategies it used to beat instead of
+// collapsing onto whatever beats the latest opponent. League Elo is
+// computed from league games only and stored per member.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::database::Database;
+use crate::dataset::game_samples;
+use crate::ratings::{self, Rating, RatingSystem};
+use crate::runner::{run_games_parallel, ActionPolicy, GameResult, SeatAgent};
+use got_agents::cloned::{Example, FitOptions, PolicyModel};
+use got_agents::AgentSpec;
+use got_engine::setup::SetupConfig;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemberRole {
+    Baseline,
+    Learner,
+    Frozen,
+    Retired,
+}
+
+impl MemberRole {
+    pub fn name(self) -> &'static str {
+        match self {
+            MemberRole::Baseline => "baseline",
+            MemberRole::Learner => "learner",
+            MemberRole::Frozen => "frozen",
+            MemberRole::Retired => "retired",
+        }
+    }
+
+    /// Whether members with this role are drawn as opponents.
+    pub fn in_pool(self) -> bool {
+        matches!(self, MemberRole::Baseline | MemberRole::Frozen)
+    }
+}
+
+impl FromStr for MemberRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "baseline" => Ok(MemberRole::Baseline),
+            "learner" => Ok(MemberRole::Learner),
+            "frozen" => Ok(MemberRole::Frozen),
+            "retired" => Ok(MemberRole::Retired),
+            other => Err(format!("Unknown league role: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for MemberRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LeagueMember {
+    pub agent_id: i64,
+    pub spec: AgentSpec,
+    /// Generation the member joined (learner versions: the generation
+    /// whose checkpoint they are).
+    pub generation: u32,
+    pub role: MemberRole,
+    /// Elo over this league's games only.
+    pub elo: f64,
+    /// Seats played in this league.
+    pub games: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct LeagueOptions {
+    pub name: String,
+    /// Directory checkpoints are written to.
+    pub dir: PathBuf,
+    pub players: u8,
+    pub games_per_generation: u32,
+    /// Freeze a learner version into the pool every this many generations.
+    pub freeze_every: u32,
+    /// Most frozen checkpoints kept in the pool; the oldest retire first.
+    pub pool_size: usize,
+    /// Fixed opponents, added when the league is created.
+    pub baselines: Vec<AgentSpec>,
+    pub threads: usize,
+    pub fit: FitOptions,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GenerationReport {
+    pub generation: u32,
+    pub games: usize,
+    pub errors: usize,
+    /// Winner decisions the learner was trained on.
+    pub samples: usize,
+    /// Learner that played this generation, if any yet.
+    pub learner: Option<(String, f64)>,
+    pub next_learner: String,
+    pub frozen: Option<String>,
+    pub retired: Vec<String>,
+}
+
+fn cloned_spec(path: &str) -> AgentSpec {
+    let mut spec = AgentSpec::new("cloned");
+    spec.params.insert("model".to_string(), path.to_string());
+    spec
+}
+
+/// Play one generation, retrain the learner and update the pool.
+pub fn run_generation(db_path: &str, opts: &LeagueOptions) -> Result<GenerationReport, String> {
+    let dir = opts.dir.to_string_lossy().to_string();
+    if dir.contains([',', '{', '}', '=']) {
+        return Err(format!("League directory {} cannot be used in an agent spec", dir));
+    }
+    std::fs::create_dir_all(&opts.dir).map_err(|e| format!("Cannot create {}: {}", dir, e))?;
+    let db = Database::new(db_path);
+
+    if db.league_members(&opts.name).is_empty() {
+        if opts.baselines.is_empty() {
+            return Err("A new league needs at least one baseline agent".to_string());
+        }
+        for spec in &opts.baselines {
+            spec.build(got_engine::types::HouseName::Stark, 0)?;
+            db.upsert_league_member(&opts.name, db.register_agent(&spec.to_string()), 0, MemberRole::Baseline);
+        }
+    }
+    let mut members = db.league_members(&opts.name);
+    let learner = members.iter()
+        .filter(|m| m.role == MemberRole::Learner)
+        .max_by_key(|m| m.generation)
+        .cloned();
+    let generation = learner.as_ref().map_or(0, |l| l.generation);
+    let pool: Vec<AgentSpec> = members.iter().filter(|m| m.role.in_pool()).map(|m| m.spec.clone()).collect();
+    if pool.is_empty() {
+        return Err(format!("League {} has no opponents in its pool", opts.name));
+    }
+
+    // ── Schedule and play ──
+    let games: Vec<(SetupConfig, Vec<SeatAgent>)> = (0..opts.games_per_generation as u64)
+        .map(|g| {
+            let seed = 7_000_000 + generation as u64 * 100_000 + g;
+            let config = SetupConfig { randomize_seats: true, ..SetupConfig::new(opts.players, seed) };
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            let lineup: Vec<AgentSpec> = (0..config.houses.len())
+                .map(|seat| match &learner {
+                    Some(l) if seat % 2 == 0 => l.spec.clone(),
+                    _ => pool.choose(&mut rng).unwrap().clone(),
+                })
+                .collect();
+            let seats = crate::runner::assign_seats(&config, &lineup);
+            (config, seats)
+        })
+        .collect();
+    let finished: Mutex<Vec<(usize, GameResult)>> = Mutex::new(Vec::new());
+    let errors = Mutex::new(0usize);
+    run_games_parallel(&games, opts.threads, 50_000, ActionPolicy::Lenient, |i, result| match result {
+        Ok(r) => finished.lock().unwrap().push((i, r)),
+        Err(_) => *errors.lock().unwrap() += 1,
+    });
+    let mut finished = finished.into_inner().unwrap();
+    finished.sort_by_key(|(i, _)| *i);
+    // Decision info is only stored when a tournament asks for it
+    let results: Vec<GameResult> = finished.into_iter()
+        .map(|(_, mut r)| { r.decision_info.clear(); r })
+        .collect();
+
+    let agent_ids: Vec<(String, i64)> = members.iter().map(|m| (m.spec.to_string(), m.agent_id)).collect();
+    let game_ids = db.store_games(&results, &agent_ids);
+
+    // ── League Elo ──
+    let mut elo: HashMap<i64, Rating> = members.iter()
+        .map(|m| (m.agent_id, Rating { mu: m.elo, sigma: 0.0 }))
+        .collect();
+    for r in &results {
+        let seats: Vec<(i64, u8)> = r.seats.iter()
+            .filter_map(|s| {
+                let id = agent_ids.iter().find(|(n, _)| *n == s.spec.to_string())?.1;
+                let rank = r.final_ranking.iter().position(|&h| h == s.house)? as u8 + 1;
+                Some((id, rank))
+            })
+            .collect();
+        for (id, _) in &seats {
+            if let Some(m) = members.iter_mut().find(|m| m.agent_id == *id) {
+                m.games += 1;
+            }
+        }
+        ratings::update(RatingSystem::Elo, &mut elo, &seats);
+    }
+    for m in &mut members {
+        m.elo = elo[&m.agent_id].mu;
+    }
+    db.record_league_generation(&opts.name, generation, &game_ids, &members);
+
+    // ── Train the next learner on the winners' decisions ──
+    let mut samples = Vec::new();
+    for &id in &game_ids {
+        samples.extend(game_samples(&db, id, None)?.into_iter().filter(|s| s.outcome >= 1.0));
+    }
+    let examples: Vec<Example> = samples.iter()
+        .map(|s| Example { kind: s.kind, obs: &s.obs, action: s.action })
+        .collect();
+    let mut model = match &learner {
+        Some(l) => PolicyModel::load(l.spec.params.get("model").map_or("", |p| p.as_str()))?,
+        None => PolicyModel::default(),
+    };
+    model.train(&examples, &FitOptions { seed: generation as u64, ..opts.fit.clone() });
+    let next = generation + 1;
+    let path = opts.dir.join(format!("gen-{:03}.bin", next)).to_string_lossy().to_string();
+    model.save(&path)?;
+    let next_spec = cloned_spec(&path);
+
+    let mut report = GenerationReport {
+        generation,
+        games: results.len(),
+        errors: errors.into_inner().unwrap(),
+        samples: examples.len(),
+        learner: learner.as_ref().map(|l| {
+            let m = members.iter().find(|m| m.agent_id == l.agent_id).unwrap();
+            (m.spec.to_string(), m.elo)
+        }),
+        next_learner: next_spec.to_string(),
+        ..GenerationReport::default()
+    };
+
+    // ── Freeze and retire ──
+    if let Some(l) = &learner {
+        db.upsert_league_member(&opts.name, l.agent_id, l.generation, MemberRole::Retired);
+        let last_frozen = members.iter()
+            .filter(|m| m.role == MemberRole::Frozen)
+            .map(|m| m.generation)
+            .max()
+            .unwrap_or(0);
+        if opts.freeze_every > 0 && next % opts.freeze_every == 0 {
+            let best = members.iter()
+                .filter(|m| matches!(m.role, MemberRole::Learner | MemberRole::Retired))
+                .filter(|m| m.generation > last_frozen && m.games > 0)
+                .max_by(|a, b| a.elo.total_cmp(&b.elo));
+            if let Some(best) = best {
+                db.upsert_league_member(&opts.name, best.agent_id, best.generation, MemberRole::Frozen);
+                report.frozen = Some(best.spec.to_string());
+            }
+        }
+        let frozen: Vec<LeagueMember> = db.league_members(&opts.name).into_iter()
+            .filter(|m| m.role == MemberRole::Frozen)
+            .collect();
+        for old in frozen.iter().take(frozen.len().saturating_sub(opts.pool_size)) {
+            db.upsert_league_member(&opts.name, old.agent_id, old.generation, MemberRole::Retired);
+            report.retired.push(old.spec.to_string());
+        }
+    }
+
+    // The new version starts from its parent's rating
+    let next_id = db.register_agent(&next_spec.to_string());
+    db.upsert_league_member(&opts.name, next_id, next, MemberRole::Learner);
+    if let Some(parent) = report.learner.as_ref() {
+        let mut child = db.league_members(&opts.name).into_iter().find(|m| m.agent_id == next_id).unwrap();
+        child.elo = parent.1;
+        db.record_league_generation(&opts.name, next, &[], &[child]);
+    }
+    Ok(report)
+}
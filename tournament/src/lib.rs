@@ -1,4 +1,27 @@
 pub mod runner;
 pub mod database;
+pub mod ratings;
+pub mod dataset;
+pub mod training;
+pub mod league;
+pub mod probes;
+pub mod balance;
+pub mod analysis;
+pub mod calibration;
+pub mod rollouts;
+pub mod daemon;
+pub mod notify;
+pub mod matches;
+pub mod contexts;
+pub mod positions;
+pub mod differential;
+pub mod metrics;
+pub mod experiments;
+pub mod map_balance;
+#[cfg(feature = "async")]
+pub mod async_runner;
 
-pub use runner::run_game;
+pub use runner::{assign_seats, biggest_battle, castle_trajectory, run_game, run_game_with_bids, run_game_with_policy, run_game_with_setup, run_games_parallel, run_seated_game, run_seated_game_observed, run_seated_game_with_policy, ActionPolicy, AgentFault, BidCollection, DecisionRecord, FaultKind, GameKey, SeatAgent};
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,231 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Map balance — Monte Carlo statistics per area
+//
+// Many mirror games (the same agent in every seat) are played and each
+// area's history is rebuilt from the setup and the event log:
+//
+//   capture rate   changes of hands into a house, per game
+//   battle rate    battles fought in the area, per game
+//   held share     share of round ends some house controlled it
+//   controllers    share of round ends each house controlled it
+//   win lift       how often the house holding it at the end won,
+//                  relative to the fair share 1/n (1.0 = no edge)
+//
+// Areas nobody fights over or takes are dead zones; areas whose final
+// holder wins far more than its share are hot spots. `MapHeatmap` is the
+// export a map renderer colours: `heat` is each area's battle and capture
+// activity scaled so the busiest area is 1.0.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::runner::{assign_seats, run_games_parallel, ActionPolicy, GameResult};
+use got_agents::AgentSpec;
+use got_engine::map::{AREAS, NUM_AREAS};
+use got_engine::setup::{create_game, SetupConfig};
+use got_engine::types::{AreaId, GameEventKind, HouseName};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Captures plus battles per game below which an area is a dead zone.
+const DEAD_ACTIVITY: f64 = 0.1;
+/// Win lift from which an area is a hot spot.
+const HOT_LIFT: f64 = 1.5;
+/// Games an area must end held in before its win lift counts.
+const MIN_HELD_GAMES: u32 = 20;
+
+#[derive(Debug, Clone)]
+pub struct MapBalanceOptions {
+    /// Agent in every seat.
+    pub agent: AgentSpec,
+    pub games: u64,
+    pub first_seed: u64,
+    pub players: u8,
+    pub threads: usize,
+}
+
+/// What an area's numbers say about it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Zone {
+    Normal,
+    /// Hardly ever taken or fought over.
+    Dead,
+    /// Its holder at the end wins far more often than its share.
+    Hot,
+}
+
+/// Per-area statistics over every game the area was in play.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AreaBalance {
+    pub area: AreaId,
+    pub name: String,
+    /// Games the area was in play (not blocked at the player count).
+    pub games: u32,
+    pub capture_rate: f64,
+    pub battle_rate: f64,
+    pub held_share: f64,
+    /// Share of round ends each house held it, largest first.
+    pub controllers: Vec<(HouseName, f64)>,
+    /// None until the area ended MIN_HELD_GAMES games held.
+    pub win_lift: Option<f64>,
+    /// Captures plus battles per game, relative to the busiest area (0–1).
+    pub heat: f64,
+    pub zone: Zone,
+}
+
+/// The analyzer's export, for a map renderer to colour.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MapHeatmap {
+    pub agent: String,
+    pub players: u8,
+    pub games: u32,
+    /// Areas in id order; areas never in play are left out.
+    pub areas: Vec<AreaBalance>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct AreaTally {
+    games: u32,
+    captures: u32,
+    battles: u32,
+    rounds: u32,
+    held: HashMap<HouseName, u32>,
+    held_at_end: u32,
+    holder_won: u32,
+    /// Wins the holders at the end would have had with no edge.
+    holder_expected: f64,
+}
+
+/// Running totals over the games recorded so far.
+#[derive(Debug, Clone)]
+pub struct MapBalance {
+    games: u32,
+    areas: Vec<AreaTally>,
+}
+
+impl Default for MapBalance {
+    fn default() -> Self {
+        MapBalance::new()
+    }
+}
+
+impl MapBalance {
+    pub fn new() -> Self {
+        MapBalance { games: 0, areas: vec![AreaTally::default(); NUM_AREAS] }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.games
+    }
+
+    /// Add one finished game: its setup gives the starting controllers,
+    /// its events everything after.
+    pub fn record(&mut self, result: &GameResult) -> Result<(), String> {
+        let start = create_game(&result.setup)?;
+        let mut control: Vec<Option<HouseName>> = start.areas.iter().map(|a| a.house).collect();
+        let fair = 1.0 / result.setup.houses.len() as f64;
+        let mut events = result.events.iter().peekable();
+        for round in 1..=result.rounds_played.max(1) {
+            while let Some(event) = events.next_if(|e| e.round <= round) {
+                match event.kind {
+                    GameEventKind::ControlChanged { area_id, to, .. } => {
+                        control[area_id.0 as usize] = to;
+                        if to.is_some() {
+                            self.areas[area_id.0 as usize].captures += 1;
+                        }
+                    }
+                    GameEventKind::CombatResolved { area_id, .. } => self.areas[area_id.0 as usize].battles += 1,
+                    _ => {}
+                }
+            }
+            for (i, tally) in self.areas.iter_mut().enumerate() {
+                if start.areas[i].blocked {
+                    continue;
+                }
+                tally.rounds += 1;
+                if let Some(house) = control[i] {
+                    *tally.held.entry(house).or_insert(0) += 1;
+                }
+            }
+        }
+        for (i, tally) in self.areas.iter_mut().enumerate() {
+            if start.areas[i].blocked {
+                continue;
+            }
+            tally.games += 1;
+            if let Some(house) = control[i] {
+                tally.held_at_end += 1;
+                tally.holder_expected += fair;
+                if house == result.winner {
+                    tally.holder_won += 1;
+                }
+            }
+        }
+        self.games += 1;
+        Ok(())
+    }
+
+    /// One entry per area that was ever in play, in id order.
+    pub fn report(&self) -> Vec<AreaBalance> {
+        let activity = |t: &AreaTally| (t.captures + t.battles) as f64 / t.games.max(1) as f64;
+        let busiest = self.areas.iter().map(activity).fold(0.0, f64::max);
+        self.areas.iter().enumerate()
+            .filter(|(_, t)| t.games > 0)
+            .map(|(i, t)| {
+                let games = t.games as f64;
+                let rounds = t.rounds.max(1) as f64;
+                let mut controllers: Vec<(HouseName, f64)> = HouseName::ALL.iter()
+                    .filter_map(|h| t.held.get(h).map(|&n| (*h, n as f64 / rounds)))
+                    .collect();
+                controllers.sort_by(|a, b| b.1.total_cmp(&a.1));
+                let win_lift = (t.held_at_end >= MIN_HELD_GAMES).then(|| t.holder_won as f64 / t.holder_expected);
+                let zone = if activity(t) < DEAD_ACTIVITY {
+                    Zone::Dead
+                } else if win_lift.is_some_and(|l| l >= HOT_LIFT) {
+                    Zone::Hot
+                } else {
+                    Zone::Normal
+                };
+                AreaBalance {
+                    area: AreaId(i as u8),
+                    name: AREAS[i].name.to_string(),
+                    games: t.games,
+                    capture_rate: t.captures as f64 / games,
+                    battle_rate: t.battles as f64 / games,
+                    held_share: t.held.values().sum::<u32>() as f64 / rounds,
+                    controllers,
+                    win_lift,
+                    heat: if busiest > 0.0 { activity(t) / busiest } else { 0.0 },
+                    zone,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Play `opts.games` mirror games and tally every area.
+pub fn run_map_balance(opts: &MapBalanceOptions, max_decisions: usize) -> Result<MapHeatmap, String> {
+    opts.agent.build(HouseName::Stark, 0)?;
+    let games: Vec<_> = (0..opts.games)
+        .map(|g| {
+            let config = SetupConfig::new(opts.players, opts.first_seed + g);
+            let seats = assign_seats(&config, &vec![opts.agent.clone(); config.houses.len()]);
+            (config, seats)
+        })
+        .collect();
+    let balance = Mutex::new(MapBalance::new());
+    run_games_parallel(&games, opts.threads, max_decisions, ActionPolicy::Lenient, |_, result| {
+        if let Ok(r) = result {
+            // The setup already created the game once, so it cannot fail here
+            let _ = balance.lock().unwrap().record(&r);
+        }
+    });
+    let balance = balance.into_inner().unwrap();
+    Ok(MapHeatmap {
+        agent: opts.agent.to_string(),
+        players: opts.players,
+        games: balance.games(),
+        areas: balance.report(),
+    })
+}
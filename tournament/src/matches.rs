@@ -0,0 +1,150 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Matches — the same seats playing several games in a row
+//
+// A match puts one agent spec on each seat for N games. The houses
+// rotate: in game g seat s plays the setup's house (s + g) mod n, so a
+// match of n games has every seat play every house once. Game g is
+// seeded with the match seed + g.
+//
+// The match winner is the seat with the most game wins; ties go to the
+// placement points (n − place, summed over the games), and seats equal
+// on both share their place. Match ratings treat every match as one
+// multiplayer game ranked by these places.
+//
+// Agents are built afresh for every game. What carries over is the
+// `MatchContext` handed to `Agent::on_match_start` before a game and
+// `Agent::on_match_end` after it: the seat and every finished game of
+// the match, which is what adaptation experiments work from.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::runner::{run_game_with_setup, GameResult, SeatAgent};
+use got_agents::agent::{MatchContext, MatchGame};
+use got_agents::{Agent, AgentSpec};
+use got_engine::setup::SetupConfig;
+use got_engine::types::HouseName;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct MatchOptions {
+    /// One spec per seat, kept for the whole match.
+    pub lineup: Vec<AgentSpec>,
+    /// Houses and options. The seed is the first game's; seats are never
+    /// shuffled, the rotation decides them.
+    pub setup: SetupConfig,
+    pub games: usize,
+}
+
+/// One seat's totals over a match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchStanding {
+    pub seat: usize,
+    pub spec: AgentSpec,
+    pub wins: u32,
+    /// Placement points: n − place per game.
+    pub points: u32,
+    /// Place in the match, 1 = match winner.
+    pub place: u8,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchResult {
+    pub seed: u64,
+    /// Every game in the order played.
+    pub games: Vec<GameResult>,
+    /// By seat.
+    pub standings: Vec<MatchStanding>,
+}
+
+impl MatchResult {
+    /// Seats sharing first place.
+    pub fn winners(&self) -> Vec<usize> {
+        self.standings.iter().filter(|s| s.place == 1).map(|s| s.seat).collect()
+    }
+}
+
+/// Setup and seats of game `game` of a match.
+pub fn match_game(opts: &MatchOptions, game: usize) -> (SetupConfig, Vec<SeatAgent>) {
+    let seed = opts.setup.seed + game as u64;
+    let config = SetupConfig { seed, randomize_seats: false, ..opts.setup.clone() };
+    let n = config.houses.len();
+    let seats = opts.lineup.iter().enumerate()
+        .map(|(seat, spec)| SeatAgent {
+            seat,
+            house: config.houses[(seat + game) % n],
+            spec: spec.clone(),
+            seed: seed + seat as u64,
+        })
+        .collect();
+    (config, seats)
+}
+
+/// Play every game of a match in order. An engine error in any game
+/// ends the match.
+pub fn play_match(opts: &MatchOptions, max_decisions: usize) -> Result<MatchResult, String> {
+    if opts.lineup.len() != opts.setup.houses.len() {
+        return Err(format!("A match needs one agent per seat: {} agents for {} houses", opts.lineup.len(), opts.setup.houses.len()));
+    }
+    let mut games = Vec::new();
+    let mut history: Vec<MatchGame> = Vec::new();
+    for game in 0..opts.games {
+        let (config, seats) = match_game(opts, game);
+        let mut agents: HashMap<HouseName, Box<dyn Agent>> = HashMap::new();
+        for s in &seats {
+            let mut agent = s.spec.build(s.house, s.seed)?;
+            agent.on_match_start(&MatchContext { games: opts.games, game, seat: s.seat, history: history.clone() });
+            agents.insert(s.house, agent);
+        }
+        let mut result = run_game_with_setup(&mut agents, &config, max_decisions)
+            .map_err(|e| format!("game {} of the match: {}", game + 1, e))?;
+        result.seats = seats.clone();
+        history.push(MatchGame {
+            seed: config.seed,
+            houses: seats.iter().map(|s| s.house).collect(),
+            places: seats.iter().map(|s| place_of(&result, s.house)).collect(),
+        });
+        for s in &seats {
+            let ctx = MatchContext { games: opts.games, game, seat: s.seat, history: history.clone() };
+            agents.get_mut(&s.house).expect("every seat has an agent").on_match_end(&ctx);
+        }
+        games.push(result);
+    }
+    let standings = standings(&opts.lineup, &history);
+    Ok(MatchResult { seed: opts.setup.seed, games, standings })
+}
+
+/// Run matches on `threads` worker threads (0 = one per core), calling
+/// `on_result` from the workers as each match finishes.
+pub fn run_matches_parallel<F>(matches: &[MatchOptions], threads: usize, max_decisions: usize, on_result: F)
+where
+    F: Fn(usize, Result<MatchResult, String>) + Sync,
+{
+    use rayon::prelude::*;
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .expect("Failed to build thread pool");
+    pool.install(|| {
+        matches.par_iter().enumerate().for_each(|(i, opts)| on_result(i, play_match(opts, max_decisions)));
+    });
+}
+
+fn place_of(result: &GameResult, house: HouseName) -> u8 {
+    result.final_ranking.iter().position(|&h| h == house).map_or(result.final_ranking.len() as u8, |i| i as u8 + 1)
+}
+
+/// Wins, placement points and match place of every seat.
+pub fn standings(lineup: &[AgentSpec], history: &[MatchGame]) -> Vec<MatchStanding> {
+    let n = lineup.len() as u32;
+    let mut standings: Vec<MatchStanding> = lineup.iter().enumerate()
+        .map(|(seat, spec)| {
+            let places = history.iter().filter_map(|g| g.places.get(seat).copied());
+            let (wins, points) = places.fold((0, 0), |(w, p), place| (w + (place == 1) as u32, p + n.saturating_sub(place as u32)));
+            MatchStanding { seat, spec: spec.clone(), wins, points, place: 0 }
+        })
+        .collect();
+    let keys: Vec<(u32, u32)> = standings.iter().map(|s| (s.wins, s.points)).collect();
+    for s in &mut standings {
+        s.place = 1 + keys.iter().filter(|&&k| k > (s.wins, s.points)).count() as u8;
+    }
+    standings
+}
@@ -0,0 +1,114 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Metrics — counters of a long-running tournament or server
+//
+// A lab deployment runs `tournament --daemon` or `serve` for days; these
+// counters let a Prometheus scraper (or anyone with curl) see that it is
+// still making progress. Everything is an atomic counter, so games on any
+// thread and the database writer update them without locking. `render`
+// gives the Prometheus text exposition format; `got_server::metrics`
+// serves it over HTTP.
+//
+//   got_games_completed_total            games that finished
+//   got_decisions_total                  decisions answered in them
+//   got_decisions_per_second             decisions_total over the uptime
+//   got_agent_timeouts_total             decisions over the time control
+//   got_agent_faults_total               panics and illegal actions
+//   got_engine_errors_total              games stopped by an engine error
+//   got_db_write_seconds_sum / _count    time spent committing game batches
+//   got_db_games_written_total           games those batches stored
+//   got_uptime_seconds
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::runner::{FaultKind, GameResult};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+pub struct Metrics {
+    started: Instant,
+    games_completed: AtomicU64,
+    decisions: AtomicU64,
+    agent_timeouts: AtomicU64,
+    agent_faults: AtomicU64,
+    engine_errors: AtomicU64,
+    db_writes: AtomicU64,
+    db_write_micros: AtomicU64,
+    db_games_written: AtomicU64,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Metrics::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Metrics {
+            started: Instant::now(),
+            games_completed: AtomicU64::new(0),
+            decisions: AtomicU64::new(0),
+            agent_timeouts: AtomicU64::new(0),
+            agent_faults: AtomicU64::new(0),
+            engine_errors: AtomicU64::new(0),
+            db_writes: AtomicU64::new(0),
+            db_write_micros: AtomicU64::new(0),
+            db_games_written: AtomicU64::new(0),
+        }
+    }
+
+    /// Count a finished game, its decisions and its agents' faults.
+    pub fn record_game(&self, result: &GameResult) {
+        self.record_finished(result.decisions);
+        let timeouts = result.agent_faults.iter().filter(|f| f.kind == FaultKind::Timeout).count() as u64;
+        self.agent_timeouts.fetch_add(timeouts, Ordering::Relaxed);
+        self.agent_faults.fetch_add(result.agent_faults.len() as u64 - timeouts, Ordering::Relaxed);
+    }
+
+    /// Count a finished game that took `decisions` decisions.
+    pub fn record_finished(&self, decisions: usize) {
+        self.games_completed.fetch_add(1, Ordering::Relaxed);
+        self.decisions.fetch_add(decisions as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_engine_error(&self) {
+        self.engine_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count one database commit of `games` games that took `elapsed`.
+    pub fn record_db_write(&self, games: usize, elapsed: Duration) {
+        self.db_writes.fetch_add(1, Ordering::Relaxed);
+        self.db_write_micros.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+        self.db_games_written.fetch_add(games as u64, Ordering::Relaxed);
+    }
+
+    /// Every metric in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let uptime = self.started.elapsed().as_secs_f64();
+        let count = |c: &AtomicU64| c.load(Ordering::Relaxed);
+        let decisions = count(&self.decisions);
+        let per_second = if uptime > 0.0 { decisions as f64 / uptime } else { 0.0 };
+        let mut out = String::new();
+        metric(&mut out, "got_games_completed_total", "counter", "Games that finished.", count(&self.games_completed));
+        metric(&mut out, "got_decisions_total", "counter", "Decisions answered in finished games.", decisions);
+        metric(&mut out, "got_decisions_per_second", "gauge", "Decisions per second since start.", format!("{:.3}", per_second));
+        metric(&mut out, "got_agent_timeouts_total", "counter", "Decisions that ran over the time control.", count(&self.agent_timeouts));
+        metric(&mut out, "got_agent_faults_total", "counter", "Agent panics and illegal actions.", count(&self.agent_faults));
+        metric(&mut out, "got_engine_errors_total", "counter", "Games stopped by an engine error.", count(&self.engine_errors));
+        let _ = writeln!(out, "# HELP got_db_write_seconds Time spent committing batches of games.");
+        let _ = writeln!(out, "# TYPE got_db_write_seconds summary");
+        let _ = writeln!(out, "got_db_write_seconds_sum {:.6}", count(&self.db_write_micros) as f64 / 1e6);
+        let _ = writeln!(out, "got_db_write_seconds_count {}", count(&self.db_writes));
+        metric(&mut out, "got_db_games_written_total", "counter", "Games stored in the database.", count(&self.db_games_written));
+        metric(&mut out, "got_uptime_seconds", "gauge", "Seconds since start.", format!("{:.3}", uptime));
+        out
+    }
+}
+
+/// One metric with its HELP and TYPE lines.
+fn metric(out: &mut String, name: &str, kind: &str, help: &str, value: impl std::fmt::Display) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    let _ = writeln!(out, "{} {}", name, value);
+}
@@ -0,0 +1,132 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Notifications — hooks fired on tournament milestones
+//
+// Long experiments run unattended; hooks tell someone when something
+// worth a look happens:
+//   finished       the tournament (or daemon run) ended
+//   elo_crossed    an agent's Elo went across one of the thresholds
+//   engine_error   a game stopped with an engine error
+//
+// Each milestone is one JSON object (`Milestone`, tagged by "event"). A
+// command hook runs through the shell with the JSON on stdin and the
+// event name in GOT_EVENT; a webhook receives it as the body of a POST.
+// Hooks never stop a tournament: one that fails is reported on stderr
+// and the others still run.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::database::LeaderboardRow;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Milestone {
+    Finished { games: u32, errors: u32, db: String },
+    EloCrossed { agent: String, elo: f64, threshold: f64, above: bool },
+    EngineError { seed: u64, message: String },
+}
+
+impl Milestone {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Milestone::Finished { .. } => "finished",
+            Milestone::EloCrossed { .. } => "elo_crossed",
+            Milestone::EngineError { .. } => "engine_error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Hook {
+    /// Shell command, given the milestone on stdin.
+    Command(String),
+    /// URL the milestone is POSTed to.
+    Webhook(String),
+}
+
+impl Hook {
+    fn run(&self, milestone: &Milestone) -> Result<(), String> {
+        let json = serde_json::to_string(milestone).map_err(|e| e.to_string())?;
+        match self {
+            Hook::Command(cmd) => {
+                let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+                let mut child = Command::new(shell)
+                    .args([flag, cmd])
+                    .env("GOT_EVENT", milestone.name())
+                    .stdin(Stdio::piped())
+                    .spawn()
+                    .map_err(|e| format!("cannot run `{}`: {}", cmd, e))?;
+                if let Some(mut stdin) = child.stdin.take() {
+                    // A command that doesn't read its input is fine
+                    let _ = stdin.write_all(json.as_bytes());
+                }
+                let status = child.wait().map_err(|e| e.to_string())?;
+                if !status.success() {
+                    return Err(format!("`{}` exited with {}", cmd, status));
+                }
+                Ok(())
+            }
+            Hook::Webhook(url) => ureq::post(url)
+                .timeout(Duration::from_secs(10))
+                .set("Content-Type", "application/json")
+                .send_string(&json)
+                .map(|_| ())
+                .map_err(|e| format!("POST {}: {}", url, e)),
+        }
+    }
+}
+
+/// The configured hooks, plus where each agent stood against the Elo
+/// thresholds when last checked.
+#[derive(Debug, Default)]
+pub struct Notifier {
+    hooks: Vec<Hook>,
+    thresholds: Vec<f64>,
+    above: Mutex<HashMap<(String, usize), bool>>,
+}
+
+impl Notifier {
+    pub fn new(hooks: Vec<Hook>, elo_thresholds: Vec<f64>) -> Self {
+        Notifier { hooks, thresholds: elo_thresholds, above: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn has_thresholds(&self) -> bool {
+        !self.thresholds.is_empty()
+    }
+
+    /// Run every hook for `milestone`.
+    pub fn fire(&self, milestone: &Milestone) {
+        for hook in &self.hooks {
+            if let Err(e) = hook.run(milestone) {
+                tracing::warn!(event = milestone.name(), "notification hook failed: {}", e);
+            }
+        }
+    }
+
+    /// Compare the leaderboard's Elo ratings to the thresholds and fire
+    /// for every crossing since the last check, returning the crossings.
+    /// An agent seen for the first time is only recorded.
+    pub fn check_elo(&self, board: &[LeaderboardRow]) -> Vec<Milestone> {
+        let mut crossed = Vec::new();
+        {
+            let mut above = self.above.lock().unwrap();
+            for row in board {
+                for (i, &threshold) in self.thresholds.iter().enumerate() {
+                    let now = row.elo >= threshold;
+                    let before = above.insert((row.name.clone(), i), now);
+                    if before.is_some_and(|b| b != now) {
+                        crossed.push(Milestone::EloCrossed { agent: row.name.clone(), elo: row.elo, threshold, above: now });
+                    }
+                }
+            }
+        }
+        for milestone in &crossed {
+            self.fire(milestone);
+        }
+        crossed
+    }
+}
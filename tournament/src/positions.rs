@@ -0,0 +1,204 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Positions — curated decisions with the answers a sound agent gives
+//
+// A position file (`positions/*.json`) holds one `PlayerView` waiting on
+// its viewer's decision and what is expected of the answer:
+//
+//   {
+//     "name": "defend-kings-landing",
+//     "description": "...",
+//     "expect": [{ "type": "order_in", "area": "King's Landing", "orders": ["Defense", "Support"] }],
+//     "view": { ...PlayerView... }
+//   }
+//
+// Every expectation has to hold for an answer to pass. Views usually come
+// from `--dump-decisions` and are then edited into the situation the
+// position is about; areas are named as on the map.
+//
+// `verify` asks each position of several fresh agents, one seed each, and
+// scores the agent by the share of passing answers, averaged over the
+// positions — a check of agent behaviour that doesn't depend on how whole
+// games happen to go.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::contexts::{answers, check_decision};
+use got_agents::AgentSpec;
+use got_engine::engine::Action;
+use got_engine::map::{area_name, NUM_AREAS};
+use got_engine::types::{AreaId, OrderType, ORDER_TOKENS};
+use got_engine::visibility::PlayerView;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Something the answer to a position must satisfy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Expectation {
+    /// Orders placed: `area` gets one of `orders`.
+    OrderIn { area: String, orders: Vec<OrderType> },
+    /// A march into one of `areas`.
+    MarchTo { areas: Vec<String> },
+    /// A bid of at least `amount`, or everything the viewer has if less.
+    BidAtLeast { amount: u8 },
+    /// A wildling bid of at least the viewer's share of the threat (threat
+    /// over the number of houses, rounded up), or everything if less.
+    BidThreatShare,
+    /// Exactly one of these actions.
+    OneOf { actions: Vec<Action> },
+    /// None of these actions.
+    NoneOf { actions: Vec<Action> },
+}
+
+impl Expectation {
+    /// Why `action` fails the expectation, or `Ok` if it passes.
+    pub fn check(&self, view: &PlayerView, action: &Action) -> Result<(), String> {
+        match self {
+            Expectation::OrderIn { area, orders } => {
+                let id = area_id(area)?;
+                let Action::PlaceOrders(placed) = action else {
+                    return Err("expected orders".to_string());
+                };
+                let order = placed.iter().find(|&&(a, _)| a == id)
+                    .and_then(|&(_, token)| ORDER_TOKENS.get(token as usize))
+                    .map(|t| t.order_type);
+                match order {
+                    Some(o) if orders.contains(&o) => Ok(()),
+                    Some(o) => Err(format!("{:?} order in {}, expected {}", o, area, list(orders))),
+                    None => Err(format!("no order in {}", area)),
+                }
+            }
+            Expectation::MarchTo { areas } => {
+                let ids = areas.iter().map(|a| area_id(a)).collect::<Result<Vec<_>, _>>()?;
+                match action {
+                    Action::March { to, .. } if ids.contains(to) => Ok(()),
+                    Action::March { to, .. } => Err(format!("marched to {}, expected {}", area_name(*to), areas.join(" or "))),
+                    _ => Err(format!("no march, expected one to {}", areas.join(" or "))),
+                }
+            }
+            Expectation::BidAtLeast { amount } => check_bid(view, action, *amount),
+            Expectation::BidThreatShare => {
+                let houses = view.playing_houses.len().max(1) as u8;
+                check_bid(view, action, view.wildling_threat.div_ceil(houses))
+            }
+            Expectation::OneOf { actions } => {
+                let answer = json(action);
+                if actions.iter().any(|a| json(a) == answer) { Ok(()) } else { Err("not one of the expected actions".to_string()) }
+            }
+            Expectation::NoneOf { actions } => {
+                let answer = json(action);
+                if actions.iter().any(|a| json(a) == answer) { Err("one of the excluded actions".to_string()) } else { Ok(()) }
+            }
+        }
+    }
+}
+
+fn check_bid(view: &PlayerView, action: &Action, amount: u8) -> Result<(), String> {
+    let power = view.house_info.get(&view.viewer).map_or(0, |i| i.power);
+    let needed = amount.min(power);
+    match action {
+        Action::Bid(bid) if *bid >= needed => Ok(()),
+        Action::Bid(bid) => Err(format!("bid {}, expected at least {}", bid, needed)),
+        _ => Err("expected a bid".to_string()),
+    }
+}
+
+fn area_id(name: &str) -> Result<AreaId, String> {
+    (0..NUM_AREAS as u8).map(AreaId)
+        .find(|&id| area_name(id).eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("unknown area '{}'", name))
+}
+
+fn list(orders: &[OrderType]) -> String {
+    orders.iter().map(|o| format!("{:?}", o)).collect::<Vec<_>>().join(" or ")
+}
+
+fn json(action: &Action) -> String {
+    serde_json::to_string(action).unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Position {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub expect: Vec<Expectation>,
+    pub view: PlayerView,
+}
+
+impl Position {
+    /// Why `action` fails the position: the first expectation it misses.
+    pub fn check(&self, action: &Action) -> Result<(), String> {
+        self.expect.iter().try_for_each(|e| e.check(&self.view, action))
+    }
+}
+
+/// Every `*.json` position in `dir`, by file name. Fails on a file that
+/// doesn't parse, a view without a decision for its viewer, or an
+/// expectation naming an unknown area.
+pub fn load_suite(dir: &Path) -> Result<Vec<Position>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Cannot read {}: {}", dir.display(), e))?;
+    let mut paths: Vec<_> = entries.filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|x| x == "json"))
+        .collect();
+    paths.sort();
+    let mut suite = Vec::new();
+    for path in paths {
+        let text = std::fs::read_to_string(&path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        let position: Position = serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        check_decision(&position.view).map_err(|e| format!("{}: {}", path.display(), e))?;
+        for e in &position.expect {
+            let areas: Vec<&String> = match e {
+                Expectation::OrderIn { area, .. } => vec![area],
+                Expectation::MarchTo { areas } => areas.iter().collect(),
+                _ => Vec::new(),
+            };
+            for a in areas {
+                area_id(a).map_err(|e| format!("{}: {}", path.display(), e))?;
+            }
+        }
+        suite.push(position);
+    }
+    Ok(suite)
+}
+
+/// How one agent did on one position.
+#[derive(Debug, Clone)]
+pub struct PositionScore {
+    pub name: String,
+    pub asked: usize,
+    pub passed: usize,
+    /// Distinct failing answers: the action, how many seeds gave it, why
+    /// it fails.
+    pub failures: Vec<(Action, usize, String)>,
+}
+
+impl PositionScore {
+    pub fn rate(&self) -> f64 {
+        if self.asked == 0 { 0.0 } else { self.passed as f64 / self.asked as f64 }
+    }
+}
+
+/// Ask every position of `n` agents built from `spec` (seeds `seed`..).
+pub fn verify(spec: &AgentSpec, suite: &[Position], seed: u64, n: usize) -> Result<Vec<PositionScore>, String> {
+    suite.iter()
+        .map(|position| {
+            let counts = answers(&position.view, spec, seed, n).map_err(|e| format!("{}: {}", position.name, e))?;
+            let mut score = PositionScore { name: position.name.clone(), asked: n, passed: 0, failures: Vec::new() };
+            for c in counts {
+                match position.check(&c.action) {
+                    Ok(()) => score.passed += c.count,
+                    Err(why) => score.failures.push((c.action, c.count, why)),
+                }
+            }
+            Ok(score)
+        })
+        .collect()
+}
+
+/// The suite score: pass rates averaged over the positions.
+pub fn suite_score(scores: &[PositionScore]) -> f64 {
+    if scores.is_empty() {
+        return 0.0;
+    }
+    scores.iter().map(PositionScore::rate).sum::<f64>() / scores.len() as f64
+}
@@ -0,0 +1,151 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Strength probes — marginal value of one decision type
+//
+// A probe swaps a single decision type from a donor agent into a base
+// agent (`composite{base=B,<decision>=A}`) and measures how much the
+// subject seat's result changes. Games are paired: for every seed the
+// baseline game (B in the subject seat) and each probe game use the same
+// setup, the same opponents and the same agent seeds, so most of the
+// game-to-game noise cancels in the per-seed difference.
+//
+// The subject seat rotates with the seed so every house is probed. The
+// score is the subject's finishing position, 1.0 for first down to 0.0
+// for last.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::runner::{assign_seats, run_games_parallel, ActionPolicy, GameResult, SeatAgent};
+use got_agents::AgentSpec;
+use got_engine::encoding::DecisionKind;
+use got_engine::setup::SetupConfig;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone)]
+pub struct ProbeOptions {
+    /// Agent being probed.
+    pub base: AgentSpec,
+    /// Agent decisions are borrowed from.
+    pub donor: AgentSpec,
+    /// Everyone else at the table.
+    pub opponents: AgentSpec,
+    /// Decision types to probe one at a time.
+    pub kinds: Vec<DecisionKind>,
+    pub seeds: u64,
+    pub first_seed: u64,
+    pub players: u8,
+    pub threads: usize,
+}
+
+/// What changed in the subject seat: one decision type, or the whole agent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Swap {
+    Decision(DecisionKind),
+    Everything,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProbeResult {
+    pub swap: Swap,
+    pub spec: AgentSpec,
+    /// Seeds where both games of the pair finished.
+    pub pairs: usize,
+    pub base_score: f64,
+    pub probe_score: f64,
+    /// Mean of the paired differences (probe − base) and its standard error.
+    pub mean_diff: f64,
+    pub std_err: f64,
+    pub base_wins: u32,
+    pub probe_wins: u32,
+}
+
+/// `composite{base=<base>,<kind>=<donor>}`.
+pub fn swap_spec(base: &AgentSpec, donor: &AgentSpec, kind: DecisionKind) -> AgentSpec {
+    let mut spec = AgentSpec::new("composite");
+    spec.params.insert("base".to_string(), base.to_string());
+    spec.params.insert(kind.key(), donor.to_string());
+    spec
+}
+
+/// The same seats with a different agent in `seat`, keeping every agent
+/// seed, so the two games differ only in that seat's decisions.
+pub fn paired_seats(seats: &[SeatAgent], seat: usize, spec: &AgentSpec) -> Vec<SeatAgent> {
+    let mut paired = seats.to_vec();
+    paired[seat].spec = spec.clone();
+    paired
+}
+
+/// Placing of the agent in `seat`: 1.0 for first place down to 0.0 for last.
+pub fn placing_score(result: &GameResult, seat: usize) -> Option<f64> {
+    let house = result.seats.get(seat)?.house;
+    let n = result.final_ranking.len();
+    let rank = result.final_ranking.iter().position(|&h| h == house)?;
+    Some(if n > 1 { 1.0 - rank as f64 / (n - 1) as f64 } else { 1.0 })
+}
+
+/// Play every pair and summarise each swap, in the order of `opts.kinds`
+/// followed by the whole-agent swap.
+pub fn run_probes(opts: &ProbeOptions, max_decisions: usize) -> Result<Vec<ProbeResult>, String> {
+    let mut variants: Vec<(Swap, AgentSpec)> = opts.kinds.iter()
+        .map(|&k| (Swap::Decision(k), swap_spec(&opts.base, &opts.donor, k)))
+        .collect();
+    variants.push((Swap::Everything, opts.donor.clone()));
+    for (_, spec) in &variants {
+        spec.build(got_engine::types::HouseName::Stark, 0)?;
+    }
+
+    // Per seed: the baseline game, then one game per variant
+    let per_seed = variants.len() + 1;
+    let mut games = Vec::new();
+    let mut subjects = Vec::new();
+    for s in 0..opts.seeds {
+        let seed = opts.first_seed + s;
+        let config = SetupConfig::new(opts.players, seed);
+        let seat = (s % config.houses.len() as u64) as usize;
+        let mut lineup = vec![opts.opponents.clone(); config.houses.len()];
+        lineup[seat] = opts.base.clone();
+        let seats = assign_seats(&config, &lineup);
+        games.push((config.clone(), seats.clone()));
+        for (_, spec) in &variants {
+            games.push((config.clone(), paired_seats(&seats, seat, spec)));
+        }
+        subjects.push(seat);
+    }
+
+    let scores: Mutex<Vec<Option<f64>>> = Mutex::new(vec![None; games.len()]);
+    run_games_parallel(&games, opts.threads, max_decisions, ActionPolicy::Lenient, |i, result| {
+        if let Ok(r) = result {
+            scores.lock().unwrap()[i] = placing_score(&r, subjects[i / per_seed]);
+        }
+    });
+    let scores = scores.into_inner().unwrap();
+
+    Ok(variants.into_iter().enumerate()
+        .map(|(v, (swap, spec))| {
+            let pairs: Vec<(f64, f64)> = scores.chunks(per_seed)
+                .filter_map(|chunk| Some((chunk[0]?, chunk[v + 1]?)))
+                .collect();
+            summarise(swap, spec, &pairs)
+        })
+        .collect())
+}
+
+fn summarise(swap: Swap, spec: AgentSpec, pairs: &[(f64, f64)]) -> ProbeResult {
+    let n = pairs.len();
+    let mean = |f: &dyn Fn(&(f64, f64)) -> f64| if n == 0 { 0.0 } else { pairs.iter().map(f).sum::<f64>() / n as f64 };
+    let mean_diff = mean(&|p| p.1 - p.0);
+    let variance = if n > 1 {
+        pairs.iter().map(|p| (p.1 - p.0 - mean_diff).powi(2)).sum::<f64>() / (n - 1) as f64
+    } else {
+        0.0
+    };
+    ProbeResult {
+        swap,
+        spec,
+        pairs: n,
+        base_score: mean(&|p| p.0),
+        probe_score: mean(&|p| p.1),
+        mean_diff,
+        std_err: (variance / n.max(1) as f64).sqrt(),
+        base_wins: pairs.iter().filter(|p| p.0 >= 1.0).count() as u32,
+        probe_wins: pairs.iter().filter(|p| p.1 >= 1.0).count() as u32,
+    }
+}
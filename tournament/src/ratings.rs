@@ -0,0 +1,120 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Ratings — multiplayer rating systems over full finishing orders
+//
+// Each game is a list of (agent, rank) seats, rank 1 = winner. An agent
+// that fills several seats in one game is updated once per seat, all
+// from its pre-game rating.
+// ═══════════════════════════════════════════════════════════════════════
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RatingSystem {
+    /// Pairwise Elo: every pair of seats is a game between them.
+    Elo,
+    /// TrueSkill-style Bayesian rating (Weng–Lin Bradley–Terry full-pair
+    /// approximation). Ranked by the conservative estimate μ − 3σ.
+    TrueSkill,
+}
+
+impl RatingSystem {
+    pub fn name(self) -> &'static str {
+        match self {
+            RatingSystem::Elo => "elo",
+            RatingSystem::TrueSkill => "trueskill",
+        }
+    }
+
+    pub fn initial(self) -> Rating {
+        match self {
+            RatingSystem::Elo => Rating { mu: 1500.0, sigma: 0.0 },
+            RatingSystem::TrueSkill => Rating { mu: TS_MU, sigma: TS_MU / 3.0 },
+        }
+    }
+}
+
+impl FromStr for RatingSystem {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "elo" => Ok(RatingSystem::Elo),
+            "trueskill" => Ok(RatingSystem::TrueSkill),
+            other => Err(format!("Unknown rating system: {} (known: elo, trueskill)", other)),
+        }
+    }
+}
+
+/// A rating. Elo uses only `mu`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rating {
+    pub mu: f64,
+    pub sigma: f64,
+}
+
+impl Rating {
+    /// Value to sort leaderboards by.
+    pub fn score(&self) -> f64 {
+        self.mu - 3.0 * self.sigma
+    }
+}
+
+const ELO_K: f64 = 32.0;
+const TS_MU: f64 = 25.0;
+const TS_BETA: f64 = TS_MU / 6.0;
+const TS_KAPPA: f64 = 0.0001;
+
+/// Update `ratings` in place for one game. `seats` are (agent id, rank).
+pub fn update(system: RatingSystem, ratings: &mut HashMap<i64, Rating>, seats: &[(i64, u8)]) {
+    if seats.len() < 2 {
+        return;
+    }
+    let before: Vec<Rating> = seats.iter()
+        .map(|(id, _)| *ratings.entry(*id).or_insert_with(|| system.initial()))
+        .collect();
+    let n = seats.len();
+
+    for i in 0..n {
+        let (id, rank) = seats[i];
+        let r = before[i];
+        let others = (0..n).filter(|&q| q != i);
+        let (delta_mu, sigma) = match system {
+            RatingSystem::Elo => {
+                // K is shared across the n−1 pairings so one game moves a
+                // rating about as much as a single two-player game
+                let k = ELO_K / (n - 1) as f64;
+                let delta: f64 = others
+                    .map(|q| {
+                        let expected = 1.0 / (1.0 + 10f64.powf((before[q].mu - r.mu) / 400.0));
+                        k * (pair_score(rank, seats[q].1) - expected)
+                    })
+                    .sum();
+                (delta, r.sigma)
+            }
+            RatingSystem::TrueSkill => {
+                let (mut omega, mut eta) = (0.0, 0.0);
+                for q in others {
+                    let o = before[q];
+                    let c = (r.sigma.powi(2) + o.sigma.powi(2) + 2.0 * TS_BETA.powi(2)).sqrt();
+                    let p = 1.0 / (1.0 + ((o.mu - r.mu) / c).exp());
+                    omega += r.sigma.powi(2) / c * (pair_score(rank, seats[q].1) - p);
+                    eta += (r.sigma / c) * (r.sigma.powi(2) / c.powi(2)) * p * (1.0 - p);
+                }
+                (omega, r.sigma * (1.0 - eta).max(TS_KAPPA).sqrt())
+            }
+        };
+        let live = ratings.get_mut(&id).unwrap();
+        live.mu += delta_mu;
+        live.sigma = live.sigma.min(sigma);
+    }
+}
+
+/// 1 if `rank` beat `other`, 0.5 for a tie, 0 otherwise (lower rank is better).
+fn pair_score(rank: u8, other: u8) -> f64 {
+    match rank.cmp(&other) {
+        std::cmp::Ordering::Less => 1.0,
+        std::cmp::Ordering::Equal => 0.5,
+        std::cmp::Ordering::Greater => 0.0,
+    }
+}
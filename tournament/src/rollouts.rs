@@ -0,0 +1,177 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Rollout benchmarks — what truncating simulations costs and saves
+//
+// Positions are sampled from mirror games every few decisions. From each
+// one the same agent plays a batch of rollouts per limit, the full-game
+// rollouts always among them, and every house's mean rollout value (win
+// probability, `engine::rollout`) is recorded. All limits share their
+// rollout seeds, so the comparison is paired.
+//
+// Speed is playouts per second of worker time. Strength impact is read
+// off against the full rollouts: the mean absolute difference of the
+// houses' values, and how often both agree on who is ahead — the house a
+// search would steer towards.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::runner::play_out;
+use got_agents::{Agent, AgentSpec};
+use got_engine::eval;
+use got_engine::rollout::{reshuffle_hidden, RolloutLimit};
+use got_engine::setup::{create_game, SetupConfig};
+use got_engine::types::*;
+use std::collections::HashMap;
+use std::time::Instant;
+
+#[derive(Debug, Clone)]
+pub struct RolloutBenchOptions {
+    /// Agent in every seat, in the sampled games and the rollouts.
+    pub agent: AgentSpec,
+    pub games: u64,
+    pub first_seed: u64,
+    pub players: u8,
+    /// Keep every n-th decision's position.
+    pub every: usize,
+    /// Rollouts per position and limit.
+    pub playouts: usize,
+    /// Limits to compare; full rollouts are added when missing.
+    pub limits: Vec<RolloutLimit>,
+    pub threads: usize,
+}
+
+/// One limit's totals over all positions.
+#[derive(Debug, Clone)]
+pub struct LimitReport {
+    pub limit: RolloutLimit,
+    pub playouts: usize,
+    pub truncated: usize,
+    pub decisions: usize,
+    /// Worker time spent in this limit's rollouts.
+    pub seconds: f64,
+    /// Mean absolute difference from the full rollouts' values.
+    pub mean_error: f64,
+    /// Share of positions where the best-valued house matches the full
+    /// rollouts'.
+    pub leader_agreement: f64,
+}
+
+impl LimitReport {
+    pub fn playouts_per_sec(&self) -> f64 {
+        self.playouts as f64 / self.seconds.max(1e-9)
+    }
+
+    pub fn mean_decisions(&self) -> f64 {
+        self.decisions as f64 / self.playouts.max(1) as f64
+    }
+
+    pub fn truncated_share(&self) -> f64 {
+        self.truncated as f64 / self.playouts.max(1) as f64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RolloutBench {
+    pub positions: usize,
+    /// Full rollouts first, then the other limits in the order given.
+    pub limits: Vec<LimitReport>,
+}
+
+/// Per position and limit: mean values by house, truncated rollouts,
+/// decisions played and seconds taken.
+type PositionRun = Vec<(Vec<f64>, usize, usize, f64)>;
+
+/// Sample positions and roll every limit out from each of them.
+pub fn bench(opts: &RolloutBenchOptions, max_decisions: usize) -> Result<RolloutBench, String> {
+    use rayon::prelude::*;
+    opts.agent.build(HouseName::Stark, 0)?;
+    let mut limits = vec![RolloutLimit::FULL];
+    limits.extend(opts.limits.iter().filter(|l| !l.is_full()));
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(opts.threads)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let runs: Vec<PositionRun> = pool.install(|| {
+        let positions: Vec<GameState> = (0..opts.games).into_par_iter()
+            .flat_map(|g| sample_game(opts, opts.first_seed + g, max_decisions))
+            .collect();
+        positions.par_iter()
+            .enumerate()
+            .map(|(p, state)| limits.iter().map(|&limit| run_limit(state, limit, opts, p as u64, max_decisions)).collect())
+            .collect()
+    });
+
+    let reports = limits.iter().enumerate()
+        .map(|(l, &limit)| {
+            let mut report = LimitReport { limit, playouts: 0, truncated: 0, decisions: 0, seconds: 0.0, mean_error: 0.0, leader_agreement: 0.0 };
+            let mut values = 0;
+            for run in &runs {
+                let (full, _, _, _) = &run[0];
+                let (mean, truncated, decisions, seconds) = &run[l];
+                report.playouts += opts.playouts;
+                report.truncated += truncated;
+                report.decisions += decisions;
+                report.seconds += seconds;
+                report.mean_error += mean.iter().zip(full).map(|(a, b)| (a - b).abs()).sum::<f64>();
+                values += mean.len();
+                if leader(mean) == leader(full) {
+                    report.leader_agreement += 1.0;
+                }
+            }
+            report.mean_error /= values.max(1) as f64;
+            report.leader_agreement /= runs.len().max(1) as f64;
+            report
+        })
+        .collect();
+    Ok(RolloutBench { positions: runs.len(), limits: reports })
+}
+
+fn agents_for(state: &GameState, agent: &AgentSpec, seed: u64) -> HashMap<HouseName, Box<dyn Agent>> {
+    state.playing_houses.iter().enumerate()
+        .filter_map(|(i, &h)| Some((h, agent.build(h, seed.wrapping_add(i as u64)).ok()?)))
+        .collect()
+}
+
+/// Every `opts.every`-th position of one mirror game.
+fn sample_game(opts: &RolloutBenchOptions, seed: u64, max_decisions: usize) -> Vec<GameState> {
+    let Ok(mut state) = create_game(&SetupConfig::new(opts.players, seed)) else {
+        return Vec::new();
+    };
+    let mut agents = agents_for(&state, &opts.agent, seed);
+    let mut positions = Vec::new();
+    let step = RolloutLimit::decisions(opts.every.max(1));
+    let mut played = 0;
+    while played < max_decisions {
+        match play_out(&mut state, &mut agents, step, max_decisions - played) {
+            Ok(r) if r.truncated => {
+                played += r.decisions;
+                positions.push(state.clone());
+            }
+            _ => break,
+        }
+    }
+    positions
+}
+
+fn run_limit(state: &GameState, limit: RolloutLimit, opts: &RolloutBenchOptions, position: u64, max_decisions: usize) -> (Vec<f64>, usize, usize, f64) {
+    let start = Instant::now();
+    let mut totals = vec![0.0; state.playing_houses.len()];
+    let (mut truncated, mut decisions) = (0, 0);
+    for p in 0..opts.playouts as u64 {
+        let seed = state.seed ^ position.wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ p.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        let mut s = state.clone();
+        reshuffle_hidden(&mut s, seed);
+        let mut agents = agents_for(&s, &opts.agent, seed);
+        let Ok(r) = play_out(&mut s, &mut agents, limit, max_decisions) else { continue };
+        truncated += r.truncated as usize;
+        decisions += r.decisions;
+        for (total, &h) in totals.iter_mut().zip(&s.playing_houses) {
+            *total += eval::static_eval(&s, h) as f64;
+        }
+    }
+    let mean = totals.into_iter().map(|t| t / opts.playouts.max(1) as f64).collect();
+    (mean, truncated, decisions, start.elapsed().as_secs_f64())
+}
+
+fn leader(values: &[f64]) -> Option<usize> {
+    (0..values.len()).max_by(|&a, &b| values[a].total_cmp(&values[b]))
+}
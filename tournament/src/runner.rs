@@ -3,21 +3,368 @@
 // ═══════════════════════════════════════════════════════════════════════
 
 use got_engine::types::*;
-use got_engine::engine;
-use got_engine::visibility::player_view;
-use got_agents::Agent;
+use got_engine::encoding::DecisionKind;
+use got_engine::engine::{self, Action};
+use got_engine::rollout::{self, Rollout, RolloutLimit};
+use got_engine::setup::SetupConfig;
+use got_engine::visibility::{minimal_view, player_view, view_for, ClockView, PlayerView};
+use got_agents::{Agent, AgentSpec, RandomAgent};
+use got_agents::agent::DecisionInfo;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
 use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 /// Result of a completed game.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GameResult {
     pub seed: u64,
     pub winner: HouseName,
     pub rounds_played: u8,
     pub player_results: Vec<PlayerResult>,
+    /// Finishing order, winner first, by the official tiebreaker.
+    pub final_ranking: Vec<HouseName>,
+    pub key: GameKey,
+    /// Setup the game was created from.
+    pub setup: SetupConfig,
+    /// Agent spec and seed for every seat, when the game was run from a
+    /// lineup. Together with `setup` this reproduces the game exactly.
+    pub seats: Vec<SeatAgent>,
+    /// The engine's event log for the whole game.
+    pub events: Vec<GameEvent>,
+    /// What the agents weighed, for the decisions they reported on.
+    #[serde(default)]
+    pub decision_info: Vec<DecisionRecord>,
+    /// Agent calls that panicked or answered illegally during the game.
+    #[serde(default)]
+    pub agent_faults: Vec<AgentFault>,
+    /// House that forfeited, by an illegal action or by running out of
+    /// time under the strict action policy; the game ended there.
+    #[serde(default)]
+    pub forfeit: Option<HouseName>,
+    /// Houses that resigned, in the order they did; ranked last.
+    #[serde(default)]
+    pub resigned: Vec<HouseName>,
+    /// The houses still playing agreed a draw. `winner` is then the
+    /// leader by the tiebreaker.
+    #[serde(default)]
+    pub drawn: bool,
+    /// Decisions answered during the game, fallbacks included.
+    #[serde(default)]
+    pub decisions: usize,
+    /// How many legal answers those decisions had.
+    #[serde(default)]
+    pub branching: BranchingStats,
 }
 
-#[derive(Debug, Clone)]
+/// An agent's `DecisionInfo` and the decision it belongs to.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DecisionRecord {
+    /// Position of the decision in the game (0 = first), the same as the
+    /// action index in a replay of the game.
+    pub decision: usize,
+    pub round: u8,
+    pub house: HouseName,
+    pub info: DecisionInfo,
+}
+
+/// How many legal answers a game's decisions had, as `engine::legal_actions`
+/// lists them. Orders and musters count only their single-area answers, so
+/// for those kinds the figures are a lower bound.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct BranchingStats {
+    pub decisions: usize,
+    /// Decisions with a single legal answer.
+    pub forced: usize,
+    /// Legal answers summed over the decisions.
+    pub options: usize,
+    pub max: usize,
+    /// Sum of log10 of each decision's answers: the size of a game tree
+    /// branching as this game did.
+    pub log10_tree_size: f64,
+    /// Per decision kind, by key.
+    pub per_kind: Vec<KindBranching>,
+}
+
+/// Branching of the decisions of one kind.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct KindBranching {
+    /// `DecisionKind::key`, e.g. `choose_march`.
+    pub kind: String,
+    pub decisions: usize,
+    pub forced: usize,
+    pub options: usize,
+    pub max: usize,
+}
+
+impl KindBranching {
+    fn new(kind: String) -> Self {
+        KindBranching { kind, decisions: 0, forced: 0, options: 0, max: 0 }
+    }
+
+    /// Legal answers per decision.
+    pub fn mean(&self) -> f64 {
+        self.options as f64 / self.decisions.max(1) as f64
+    }
+
+    fn add(&mut self, decisions: usize, forced: usize, options: usize, max: usize) {
+        self.decisions += decisions;
+        self.forced += forced;
+        self.options += options;
+        self.max = self.max.max(max);
+    }
+}
+
+impl BranchingStats {
+    /// Count a decision of `kind` with `options` legal answers.
+    pub fn record(&mut self, kind: DecisionKind, options: usize) {
+        self.decisions += 1;
+        self.forced += (options == 1) as usize;
+        self.options += options;
+        self.max = self.max.max(options);
+        self.log10_tree_size += (options.max(1) as f64).log10();
+        self.kind_mut(kind.key()).add(1, (options == 1) as usize, options, options);
+    }
+
+    /// Add another game's (or games') figures to these.
+    pub fn merge(&mut self, other: &BranchingStats) {
+        self.decisions += other.decisions;
+        self.forced += other.forced;
+        self.options += other.options;
+        self.max = self.max.max(other.max);
+        self.log10_tree_size += other.log10_tree_size;
+        for k in &other.per_kind {
+            self.kind_mut(k.kind.clone()).add(k.decisions, k.forced, k.options, k.max);
+        }
+    }
+
+    /// Legal answers per decision.
+    pub fn mean(&self) -> f64 {
+        self.options as f64 / self.decisions.max(1) as f64
+    }
+
+    fn kind_mut(&mut self, kind: String) -> &mut KindBranching {
+        let i = match self.per_kind.binary_search_by(|k| k.kind.cmp(&kind)) {
+            Ok(i) => i,
+            Err(i) => {
+                self.per_kind.insert(i, KindBranching::new(kind));
+                i
+            }
+        };
+        &mut self.per_kind[i]
+    }
+}
+
+/// An agent that panicked, answered with an illegal action or ran out of
+/// time. After a panic a random agent plays the house's decisions from
+/// there on, after a lenient timeout random legal answers do; the result
+/// still counts for the agent that failed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AgentFault {
+    #[serde(default)]
+    pub kind: FaultKind,
+    /// Position of the decision in the game (0 = first).
+    pub decision: usize,
+    pub round: u8,
+    pub house: HouseName,
+    pub agent: String,
+    /// The panic message, or why the action was refused.
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultKind {
+    #[default]
+    Panic,
+    IllegalAction,
+    Timeout,
+}
+
+impl FaultKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            FaultKind::Panic => "panic",
+            FaultKind::IllegalAction => "illegal_action",
+            FaultKind::Timeout => "timeout",
+        }
+    }
+}
+
+impl FromStr for FaultKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "panic" => Ok(FaultKind::Panic),
+            "illegal_action" => Ok(FaultKind::IllegalAction),
+            "timeout" => Ok(FaultKind::Timeout),
+            other => Err(format!("Unknown agent fault: {}", other)),
+        }
+    }
+}
+
+/// What the runner does with an action `engine::validate_action` refuses,
+/// or with an agent that runs out of time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ActionPolicy {
+    /// Play a random legal answer instead and record the fault. A house
+    /// out of time gets random legal answers for the rest of the game.
+    #[default]
+    Lenient,
+    /// The house forfeits: the game ends there, with it ranked last.
+    Strict,
+}
+
+impl FromStr for ActionPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "lenient" => Ok(ActionPolicy::Lenient),
+            "strict" => Ok(ActionPolicy::Strict),
+            other => Err(format!("Unknown action policy: {} (known: lenient, strict)", other)),
+        }
+    }
+}
+
+/// How the runner asks for the bids of a Clash of Kings or wildling round.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BidCollection {
+    /// One house after another, in bid order.
+    #[default]
+    Sequential,
+    /// Every house at once on its own thread, so remote players think in
+    /// parallel; the answers are applied in bid order when all are in. Bids
+    /// are secret, so each house sees what it would have seen in turn and
+    /// the game plays out the same.
+    Simultaneous,
+}
+
+/// The agent in one seat, with everything needed to rebuild it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SeatAgent {
+    pub seat: usize,
+    pub house: HouseName,
+    pub spec: AgentSpec,
+    pub seed: u64,
+}
+
+/// Put one spec on each seat of the setup; seat `i` gets agent seed `seed + i`.
+pub fn assign_seats(config: &SetupConfig, lineup: &[AgentSpec]) -> Vec<SeatAgent> {
+    config.seats().into_iter()
+        .zip(lineup)
+        .enumerate()
+        .map(|(seat, (house, spec))| SeatAgent {
+            seat,
+            house,
+            spec: spec.clone(),
+            seed: config.seed + seat as u64,
+        })
+        .collect()
+}
+
+/// Build the seated agents and run the game, recording the seats in the result.
+pub fn run_seated_game(
+    config: &SetupConfig,
+    seats: &[SeatAgent],
+    max_decisions: usize,
+) -> Result<GameResult, String> {
+    run_seated_game_with_policy(config, seats, max_decisions, ActionPolicy::Lenient)
+}
+
+/// `run_seated_game` with the given treatment of illegal actions.
+pub fn run_seated_game_with_policy(
+    config: &SetupConfig,
+    seats: &[SeatAgent],
+    max_decisions: usize,
+    policy: ActionPolicy,
+) -> Result<GameResult, String> {
+    let mut agents = build_seats(seats)?;
+    let mut result = run_game_observed(&mut agents, config, max_decisions, policy, BidCollection::Sequential, |_, _, _| {})?;
+    result.seats = seats.to_vec();
+    Ok(result)
+}
+
+/// `run_seated_game`, calling `on_decision` with the deciding house, the
+/// view it was shown and its answer for every decision in the game.
+pub fn run_seated_game_observed(
+    config: &SetupConfig,
+    seats: &[SeatAgent],
+    max_decisions: usize,
+    on_decision: impl FnMut(HouseName, &PlayerView, &Action),
+) -> Result<GameResult, String> {
+    let mut agents = build_seats(seats)?;
+    let mut result = run_game_observed(&mut agents, config, max_decisions, ActionPolicy::Lenient, BidCollection::Sequential, on_decision)?;
+    result.seats = seats.to_vec();
+    Ok(result)
+}
+
+fn build_seats(seats: &[SeatAgent]) -> Result<HashMap<HouseName, Box<dyn Agent>>, String> {
+    let mut agents: HashMap<HouseName, Box<dyn Agent>> = HashMap::new();
+    for s in seats {
+        agents.insert(s.house, s.spec.build(s.house, s.seed)?);
+    }
+    Ok(agents)
+}
+
+/// Identity of a game for duplicate detection: same seed, same agents on
+/// the same houses, same setup options.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct GameKey {
+    pub seed: u64,
+    pub lineup_hash: String,
+    pub options_hash: String,
+}
+
+impl GameKey {
+    pub fn new(config: &SetupConfig, agents: &HashMap<HouseName, Box<dyn Agent>>) -> Self {
+        Self::with_names(config, |h| agents.get(&h).map_or(String::new(), |a| a.name().to_string()))
+    }
+
+    /// Key for a seated lineup, without building the agents.
+    pub fn from_seats(config: &SetupConfig, seats: &[SeatAgent]) -> Self {
+        Self::with_names(config, |h| seats.iter().find(|s| s.house == h).map_or(String::new(), |s| s.spec.to_string()))
+    }
+
+    fn with_names(config: &SetupConfig, agent_name: impl Fn(HouseName) -> String) -> Self {
+        // Canonical house order, so seat shuffling alone never changes the key
+        let lineup: Vec<String> = HouseName::ALL.iter()
+            .filter(|h| config.houses.contains(h))
+            .map(|&h| format!("{}={}", h, agent_name(h)))
+            .collect();
+        let houses: Vec<String> = HouseName::ALL.iter()
+            .filter(|h| config.houses.contains(h))
+            .map(|h| h.to_string())
+            .collect();
+        let mut options = format!("houses={}", houses.join(","));
+        // Standard games keep the hash they had before options existed
+        if !config.options.is_standard() {
+            options.push_str(&format!(";options={}", serde_json::to_string(&config.options.canonical()).unwrap()));
+        }
+        GameKey {
+            seed: config.seed,
+            lineup_hash: stable_hash(&lineup.join(";")),
+            options_hash: stable_hash(&options),
+        }
+    }
+}
+
+/// FNV-1a, hex encoded. Stable across Rust versions and platforms, unlike
+/// `DefaultHasher`, so hashes stored in the database stay comparable.
+fn stable_hash(s: &str) -> String {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in s.bytes() {
+        h ^= b as u64;
+        h = h.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{:016x}", h)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PlayerResult {
     pub house: HouseName,
     pub agent_name: String,
@@ -27,6 +374,96 @@ pub struct PlayerResult {
     pub final_iron_throne: u8,
     pub final_fiefdoms: u8,
     pub final_kings_court: u8,
+    pub stats: HouseStats,
+    /// House cards played, swords dealt and abilities triggered.
+    #[serde(default)]
+    pub card_stats: CardStats,
+}
+
+/// Per-house totals aggregated from the game's event log.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct HouseStats {
+    pub battles_won: u32,
+    pub battles_lost: u32,
+    pub power_gained: u32,
+    pub power_spent: u32,
+    /// Areas this house took control of during play.
+    pub areas_captured: u32,
+    /// Total power contributed to wildling bids.
+    pub wildling_bid: u32,
+    /// House cards played in battles.
+    pub cards_played: u32,
+}
+
+impl HouseStats {
+    /// Aggregate one house's statistics from the event log.
+    pub fn from_events(events: &[GameEvent], house: HouseName) -> Self {
+        let mut stats = HouseStats::default();
+        for event in events {
+            match &event.kind {
+                GameEventKind::CombatResolved { attacker, defender, winner, attacker_card, defender_card, .. } => {
+                    let card = if *attacker == house {
+                        attacker_card
+                    } else if *defender == house {
+                        defender_card
+                    } else {
+                        continue;
+                    };
+                    if *winner == house { stats.battles_won += 1; } else { stats.battles_lost += 1; }
+                    if card.is_some() { stats.cards_played += 1; }
+                }
+                GameEventKind::PowerGained { house: h, amount } if *h == house => {
+                    stats.power_gained += *amount as u32;
+                }
+                GameEventKind::PowerSpent { house: h, amount } if *h == house => {
+                    stats.power_spent += *amount as u32;
+                }
+                GameEventKind::WildlingBid { house: h, amount } if *h == house => {
+                    stats.wildling_bid += *amount as u32;
+                }
+                GameEventKind::ControlChanged { to: Some(h), .. } if *h == house => {
+                    stats.areas_captured += 1;
+                }
+                _ => {}
+            }
+        }
+        stats
+    }
+}
+
+/// A house's castle and stronghold count at the end of each round,
+/// worked back from its final count through the control changes in the
+/// event log.
+pub fn castle_trajectory(events: &[GameEvent], house: HouseName, final_castles: u8, rounds: u8) -> Vec<u8> {
+    use got_engine::map::AREAS;
+    let mut trajectory = vec![0; rounds as usize];
+    let mut count = final_castles as i16;
+    let mut changes = events.iter().rev().peekable();
+    for round in (1..=rounds).rev() {
+        trajectory[round as usize - 1] = count.max(0) as u8;
+        while let Some(event) = changes.next_if(|e| e.round >= round) {
+            let GameEventKind::ControlChanged { area_id, from, to, .. } = event.kind else { continue };
+            if !AREAS[area_id.0 as usize].has_castle_or_stronghold() {
+                continue;
+            }
+            if to == Some(house) { count -= 1; }
+            if from == Some(house) { count += 1; }
+        }
+    }
+    trajectory
+}
+
+/// The battle `house` fought with the most strength on the field, both
+/// sides together.
+pub fn biggest_battle(events: &[GameEvent], house: HouseName) -> Option<&GameEvent> {
+    events.iter()
+        .filter_map(|e| match e.kind {
+            GameEventKind::CombatResolved { attacker, defender, attacker_strength, defender_strength, .. }
+                if attacker == house || defender == house => Some((e, attacker_strength + defender_strength)),
+            _ => None,
+        })
+        .max_by_key(|&(e, total)| (total, std::cmp::Reverse(e.seq)))
+        .map(|(e, _)| e)
 }
 
 /// Run a complete game with the given agents.
@@ -38,8 +475,88 @@ pub fn run_game(
     player_count: u8,
     max_decisions: usize, // safety limit to prevent infinite loops
 ) -> Result<GameResult, String> {
-    let mut state = got_engine::setup::create_initial_state(player_count, seed);
+    run_game_with_setup(agents, &SetupConfig::new(player_count, seed), max_decisions)
+}
+
+/// Run a complete game from an explicit house selection.
+pub fn run_game_with_setup(
+    agents: &mut HashMap<HouseName, Box<dyn Agent>>,
+    config: &SetupConfig,
+    max_decisions: usize,
+) -> Result<GameResult, String> {
+    run_game_with_policy(agents, config, max_decisions, ActionPolicy::Lenient)
+}
+
+/// `run_game_with_setup` with the given treatment of illegal actions.
+pub fn run_game_with_policy(
+    agents: &mut HashMap<HouseName, Box<dyn Agent>>,
+    config: &SetupConfig,
+    max_decisions: usize,
+    policy: ActionPolicy,
+) -> Result<GameResult, String> {
+    run_game_observed(agents, config, max_decisions, policy, BidCollection::Sequential, |_, _, _| {})
+}
+
+/// `run_game_with_policy`, collecting bids as `bids` says.
+pub fn run_game_with_bids(
+    agents: &mut HashMap<HouseName, Box<dyn Agent>>,
+    config: &SetupConfig,
+    max_decisions: usize,
+    policy: ActionPolicy,
+    bids: BidCollection,
+) -> Result<GameResult, String> {
+    run_game_observed(agents, config, max_decisions, policy, bids, |_, _, _| {})
+}
+
+fn run_game_observed(
+    agents: &mut HashMap<HouseName, Box<dyn Agent>>,
+    config: &SetupConfig,
+    max_decisions: usize,
+    policy: ActionPolicy,
+    bids: BidCollection,
+    mut on_decision: impl FnMut(HouseName, &PlayerView, &Action),
+) -> Result<GameResult, String> {
+    let seed = config.seed;
+    let _game = tracing::info_span!("game", seed, players = config.player_count()).entered();
+    let key = GameKey::new(config, agents);
+    // Results count for the agents seated at the start, fallbacks or not
+    let names: HashMap<HouseName, String> = agents.iter().map(|(&h, a)| (h, a.name().to_string())).collect();
+    let mut state = got_engine::setup::create_game(config)?;
     let mut decision_count = 0;
+    // Answers of houses asked ahead of their turn (simultaneous bids)
+    let mut asked_ahead: HashMap<HouseName, (Answer, Duration)> = HashMap::new();
+    let mut clocks = config.options.time_control.map(|control| Clocks::new(control, &state.playing_houses));
+    // Houses out of time under the lenient policy: the runner plays their
+    // decisions from then on
+    let mut flagged: Vec<HouseName> = Vec::new();
+    let mut decision_info = Vec::new();
+    let mut agent_faults = Vec::new();
+    let mut branching = BranchingStats::default();
+    let finish = |state: &GameState, winner, decisions, decision_info, agent_faults, branching| {
+        tracing::debug!(winner = %winner, rounds = state.round, "game over");
+        let mut result = build_result(state, seed, winner, key.clone(), config.clone());
+        result.decisions = decisions;
+        result.branching = branching;
+        result.decision_info = decision_info;
+        result.agent_faults = agent_faults;
+        for pr in &mut result.player_results {
+            if let Some(name) = names.get(&pr.house) {
+                pr.agent_name = name.clone();
+            }
+        }
+        result
+    };
+    // The game ends at `house`'s fault; everyone else keeps the order
+    // they stand in now
+    let forfeit = |state: &GameState, house, decisions, decision_info, agent_faults, branching| {
+        let mut ranking = engine::final_ranking(state);
+        ranking.retain(|&h| h != house);
+        ranking.push(house);
+        let mut result = finish(state, ranking[0], decisions, decision_info, agent_faults, branching);
+        result.final_ranking = ranking;
+        result.forfeit = Some(house);
+        result
+    };
 
     // Main game loop
     loop {
@@ -48,65 +565,258 @@ pub fn run_game(
 
         // Check game over
         if let Some(winner) = state.winner {
-            return Ok(build_result(&state, seed, winner));
+            return Ok(finish(&state, winner, decision_count, decision_info, agent_faults, branching));
         }
 
         // If there's a pending decision, ask the appropriate agent
-        if let Some(ref pending) = state.pending {
+        if let Some(pending) = state.peek_pending() {
             let house = pending_house(pending);
-            if let Some(agent) = agents.get_mut(&house) {
-                let view = player_view(&state, house);
-                let action = agent.decide(&view);
-                engine::apply_action(&mut state, action);
+            branching.record(DecisionKind::of(pending), engine::legal_actions(&state).len());
+            if flagged.contains(&house) {
+                let action = random_fallback(&state, seed ^ decision_count as u64)?;
+                on_decision(house, &player_view(&state, house), &action);
+                engine::apply_action_as(&mut state, house, action)?;
+                state.check_unit_pools()?;
                 decision_count += 1;
-
-                if decision_count > max_decisions {
-                    return Err(format!(
-                        "Game exceeded {} decisions without finishing (round {})",
-                        max_decisions, state.round
-                    ));
-                }
-            } else {
+                continue;
+            }
+            if bids == BidCollection::Simultaneous && asked_ahead.is_empty() && matches!(pending, PendingDecision::Bidding { .. }) {
+                asked_ahead = ask_bids(agents, &state, clocks.as_ref(), &flagged)?;
+            }
+            let Some(agent) = agents.get_mut(&house) else {
                 return Err(format!("No agent for house {:?}", house));
+            };
+            let mut view = agent_view(agent.as_ref(), &state, house);
+            if let Some(clocks) = &clocks {
+                clocks.show(&mut view);
+            }
+            let fault = |kind, message: String| {
+                tracing::debug!(house = %house, round = state.round, decision = decision_count, ?kind, "agent fault: {}", message);
+                AgentFault {
+                    kind,
+                    decision: decision_count,
+                    round: state.round,
+                    house,
+                    agent: names.get(&house).cloned().unwrap_or_default(),
+                    message,
+                }
+            };
+            let (answer, elapsed) = match asked_ahead.remove(&house) {
+                Some(timed) => timed,
+                None => timed(|| ask_agent(agent.as_mut(), &view)),
+            };
+            if let Some(Err(message)) = clocks.as_mut().map(|c| c.charge(house, elapsed)) {
+                agent_faults.push(fault(FaultKind::Timeout, message));
+                if policy == ActionPolicy::Strict {
+                    return Ok(forfeit(&state, house, decision_count, decision_info, agent_faults, branching));
+                }
+                // The late answer is dropped along with the agent
+                flagged.push(house);
+                asked_ahead.remove(&house);
+                continue;
+            }
+            let mut action = match answer {
+                Ok((action, info)) => {
+                    if let Some(info) = info {
+                        decision_info.push(DecisionRecord { decision: decision_count, round: state.round, house, info });
+                    }
+                    action
+                }
+                Err(message) => {
+                    agent_faults.push(fault(FaultKind::Panic, message));
+                    // Whatever state the failed agent was left in, it
+                    // doesn't decide again in this game
+                    let mut fallback: Box<dyn Agent> = Box::new(RandomAgent::new(house, seed ^ decision_count as u64));
+                    let (action, _) = ask_agent(fallback.as_mut(), &view)
+                        .map_err(|e| format!("Fallback agent for {:?} panicked too: {}", house, e))?;
+                    agents.insert(house, fallback);
+                    action
+                }
+            };
+            if let Err(reason) = engine::validate_action(&state, &action) {
+                agent_faults.push(fault(FaultKind::IllegalAction, reason.to_string()));
+                match policy {
+                    ActionPolicy::Strict => return Ok(forfeit(&state, house, decision_count, decision_info, agent_faults, branching)),
+                    ActionPolicy::Lenient => action = random_fallback(&state, seed ^ decision_count as u64)?,
+                }
+            }
+            tracing::trace!(decision = decision_count, round = state.round, house = %house, action = ?action, "decision");
+            on_decision(house, &view, &action);
+            engine::apply_action_as(&mut state, house, action)?;
+            // The engine answers for a house that resigned from now on
+            asked_ahead.retain(|h, _| !state.resigned.contains(h));
+            state.check_unit_pools()?;
+            state.check_combat()?;
+            decision_count += 1;
+
+            if decision_count > max_decisions {
+                return Err(format!(
+                    "Game exceeded {} decisions without finishing (round {})",
+                    max_decisions, state.round
+                ));
             }
         } else if state.winner.is_none() {
             // No pending and no winner — shouldn't happen
             // Engine should always either set pending or advance
             return Err(format!(
-                "Game stuck: phase={:?}, round={}, step={}",
-                state.phase, state.round, state.westeros_step
+                "Game stuck: phase={:?}, round={}",
+                state.engine_phase, state.round
             ));
         }
     }
 }
 
-fn pending_house(pending: &PendingDecision) -> HouseName {
-    match pending {
-        PendingDecision::PlaceOrders { house } => *house,
-        PendingDecision::ChooseRaid { house, .. } => *house,
-        PendingDecision::ChooseMarch { house, .. } => *house,
-        PendingDecision::SupportDeclaration { house, .. } => *house,
-        PendingDecision::SelectHouseCard { house, .. } => *house,
-        PendingDecision::TyrionReplace { opponent } => *opponent,
-        PendingDecision::AeronSwap { house } => *house,
-        PendingDecision::PatchfaceDiscard { opponent, .. } => *opponent,
-        PendingDecision::Retreat { house, .. } => *house,
-        PendingDecision::Reconcile { house, .. } => *house,
-        PendingDecision::Muster { house, .. } => *house,
-        PendingDecision::MessengerRaven { house } => *house,
-        PendingDecision::WildlingPenaltyChoice { house, .. } => *house,
-        PendingDecision::CerseiRemoveOrder { opponent } => *opponent,
-        PendingDecision::DoranChooseTrack { opponent } => *opponent,
-        PendingDecision::QueenOfThornsRemoveOrder { opponent } => *opponent,
-        PendingDecision::LeavePowerToken { house, .. } => *house,
-        PendingDecision::UseValyrianBlade { house } => *house,
-        PendingDecision::Bidding { house, .. } => *house,
-        PendingDecision::WesterosChoice { chooser, .. } => *chooser,
-        PendingDecision::RobbRetreat { house, .. } => *house,
-    }
-}
-
-fn build_result(state: &GameState, seed: u64, winner: HouseName) -> GameResult {
+/// A random legal answer to the pending decision.
+fn random_fallback(state: &GameState, seed: u64) -> Result<Action, String> {
+    engine::random_legal_action(state, &mut ChaCha8Rng::seed_from_u64(seed))
+        .ok_or_else(|| "No decision to fall back on".to_string())
+}
+
+/// One decision from `agent` and what it weighed for it, or the panic
+/// message if the agent panicked.
+fn ask_agent(agent: &mut dyn Agent, view: &PlayerView) -> Answer {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let action = agent.decide(view);
+        (action, agent.last_decision_info())
+    }))
+    .map_err(|payload| {
+        payload.downcast_ref::<&str>().map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string())
+    })
+}
+
+/// An agent's answer with what it weighed, or its panic message.
+type Answer = Result<(Action, Option<DecisionInfo>), String>;
+
+/// `f`'s result and how long it took.
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let value = f();
+    (value, start.elapsed())
+}
+
+/// Thinking time left per house under a time control.
+struct Clocks {
+    control: TimeControl,
+    remaining: HashMap<HouseName, Duration>,
+}
+
+impl Clocks {
+    fn new(control: TimeControl, houses: &[HouseName]) -> Self {
+        let budget = Duration::from_millis(control.budget_ms);
+        Clocks { control, remaining: houses.iter().map(|&h| (h, budget)).collect() }
+    }
+
+    /// Put the viewer's clock in its view.
+    fn show(&self, view: &mut PlayerView) {
+        view.clock = Some(ClockView {
+            remaining_ms: self.remaining[&view.viewer].as_millis() as u64,
+            increment_ms: self.control.increment_ms,
+        });
+    }
+
+    /// Charge `house` for an answer that took `elapsed`; the fault
+    /// message if that was more than it had left.
+    fn charge(&mut self, house: HouseName, elapsed: Duration) -> Result<(), String> {
+        let left = self.remaining.get_mut(&house).expect("every playing house has a clock");
+        match left.checked_sub(elapsed) {
+            Some(rest) => {
+                *left = rest + Duration::from_millis(self.control.increment_ms);
+                Ok(())
+            }
+            None => Err(format!("took {}ms with {}ms left on the clock ({})", elapsed.as_millis(), left.as_millis(), self.control)),
+        }
+    }
+}
+
+/// Ask every house still to bid at once, one thread each.
+fn ask_bids(
+    agents: &mut HashMap<HouseName, Box<dyn Agent>>,
+    state: &GameState,
+    clocks: Option<&Clocks>,
+    flagged: &[HouseName],
+) -> Result<HashMap<HouseName, (Answer, Duration)>, String> {
+    let mut open = engine::open_bids(state);
+    open.retain(|p| !flagged.contains(&p.house()));
+    if let Some(missing) = open.iter().map(PendingDecision::house).find(|h| !agents.contains_key(h)) {
+        return Err(format!("No agent for house {:?}", missing));
+    }
+    Ok(std::thread::scope(|scope| {
+        let handles: Vec<_> = agents.iter_mut()
+            .filter_map(|(&house, agent)| {
+                let pending = open.iter().find(|p| p.house() == house)?;
+                let mut view = view_for(state, pending);
+                if let Some(clocks) = clocks {
+                    clocks.show(&mut view);
+                }
+                Some((house, scope.spawn(move || timed(|| ask_agent(agent.as_mut(), &view)))))
+            })
+            .collect();
+        handles.into_iter()
+            .map(|(house, handle)| (house, handle.join().unwrap_or_else(|_| (Err("bid thread failed".to_string()), Duration::ZERO))))
+            .collect()
+    }))
+}
+
+/// Continue a game already in progress with `agents` until it ends or
+/// `limit` cuts it short.
+pub fn play_out(
+    state: &mut GameState,
+    agents: &mut HashMap<HouseName, Box<dyn Agent>>,
+    limit: RolloutLimit,
+    max_decisions: usize,
+) -> Result<Rollout, String> {
+    if let Some(house) = state.playing_houses.iter().find(|h| !agents.contains_key(h)) {
+        return Err(format!("No agent for house {:?}", house));
+    }
+    rollout::rollout(state, limit, max_decisions, |state, house| {
+        let agent = agents.get_mut(&house).expect("every playing house has an agent");
+        let view = agent_view(agent.as_ref(), state, house);
+        agent.decide(&view)
+    })
+}
+
+/// The view `agent` is asked with: the full one unless it has said it
+/// can do without (`Agent::needs_view`).
+fn agent_view(agent: &dyn Agent, state: &GameState, house: HouseName) -> PlayerView {
+    if agent.needs_view() {
+        player_view(state, house)
+    } else {
+        minimal_view(state, house)
+    }
+}
+
+/// Run seated games on `threads` worker threads (0 = one per core),
+/// treating illegal actions by `policy` and calling `on_result` from the
+/// workers as each game finishes.
+pub fn run_games_parallel<F>(
+    games: &[(SetupConfig, Vec<SeatAgent>)],
+    threads: usize,
+    max_decisions: usize,
+    policy: ActionPolicy,
+    on_result: F,
+) where
+    F: Fn(usize, Result<GameResult, String>) + Sync,
+{
+    use rayon::prelude::*;
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .expect("Failed to build thread pool");
+    pool.install(|| {
+        games.par_iter().enumerate().for_each(|(i, (config, seats))| {
+            on_result(i, run_seated_game_with_policy(config, seats, max_decisions, policy));
+        });
+    });
+}
+
+/// House that has to answer `pending`.
+pub fn pending_house(pending: &PendingDecision) -> HouseName {
+    pending.house()
+}
+
+fn build_result(state: &GameState, seed: u64, winner: HouseName, key: GameKey, setup: SetupConfig) -> GameResult {
     use got_engine::map::AREAS;
 
     let player_results: Vec<PlayerResult> = state.playing_houses.iter()
@@ -117,13 +827,15 @@ fn build_result(state: &GameState, seed: u64, winner: HouseName) -> GameResult {
                 .count() as u8;
             PlayerResult {
                 house: h,
-                agent_name: String::new(), // Filled by caller
+                agent_name: String::new(), // Filled from the agents by run_game
                 final_castles: castles,
                 final_supply: profile.supply,
                 final_power: profile.power,
                 final_iron_throne: profile.iron_throne,
                 final_fiefdoms: profile.fiefdoms,
                 final_kings_court: profile.kings_court,
+                stats: HouseStats::from_events(&state.events, h),
+                card_stats: profile.card_stats.clone(),
             }
         })
         .collect();
@@ -133,5 +845,17 @@ fn build_result(state: &GameState, seed: u64, winner: HouseName) -> GameResult {
         winner,
         rounds_played: state.round,
         player_results,
+        final_ranking: engine::final_ranking(state),
+        key,
+        setup,
+        seats: Vec::new(),
+        events: state.events.clone(),
+        decision_info: Vec::new(),
+        agent_faults: Vec::new(),
+        forfeit: None,
+        resigned: state.resigned.clone(),
+        drawn: state.drawn,
+        decisions: 0,
+        branching: BranchingStats::default(),
     }
 }
@@ -0,0 +1,1024 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Tests for result storage, the queries built on it, notifications,
+// matches, decision contexts, ratings and search support
+// ═══════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use crate::database::{Database, LeaderboardFilter, LeaderboardRow, LeaderboardSort};
+    use crate::runner::{BranchingStats, GameKey, GameResult, HouseStats, PlayerResult};
+    use got_engine::engine::{Action, MusterAction2};
+    use got_engine::setup::SetupConfig;
+    use got_engine::types::*;
+    use got_engine::visibility::{AbilitySummary, PlayerView};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    const STARK: HouseName = HouseName::Stark;
+    const LANNISTER: HouseName = HouseName::Lannister;
+    const BARATHEON: HouseName = HouseName::Baratheon;
+
+    /// A finished three-player game: who played which house, and who won.
+    fn game(seed: u64, seats: &[(HouseName, &str)], winner: HouseName) -> GameResult {
+        let setup = SetupConfig::with_houses(seats.iter().map(|&(h, _)| h).collect(), seed);
+        let player_results = seats.iter()
+            .map(|&(house, agent)| PlayerResult {
+                house,
+                agent_name: agent.to_string(),
+                final_castles: if house == winner { 7 } else { 2 },
+                final_supply: 2,
+                final_power: 5,
+                final_iron_throne: 1,
+                final_fiefdoms: 1,
+                final_kings_court: 1,
+                stats: HouseStats::default(),
+                card_stats: CardStats::default(),
+            })
+            .collect();
+        let mut final_ranking: Vec<HouseName> = seats.iter().map(|&(h, _)| h).collect();
+        final_ranking.sort_by_key(|&h| h != winner);
+        GameResult {
+            seed,
+            winner,
+            rounds_played: 10,
+            player_results,
+            final_ranking,
+            key: GameKey { seed, lineup_hash: format!("lineup-{}", seed), options_hash: String::new() },
+            setup,
+            seats: Vec::new(),
+            events: Vec::new(),
+            decision_info: Vec::new(),
+            agent_faults: Vec::new(),
+            forfeit: None,
+            resigned: Vec::new(),
+            drawn: false,
+            decisions: 0,
+            branching: BranchingStats::default(),
+        }
+    }
+
+    /// Three games between two MCTS agents and the heuristic, each agent
+    /// playing every house once. mcts-a wins twice, heuristic once, and
+    /// heuristic has taken rating points from mcts-a.
+    fn fixture_db() -> Database {
+        let db = Database::in_memory();
+        let ids: Vec<(String, i64)> = ["mcts-a", "mcts-b", "heuristic"].iter()
+            .map(|&n| (n.to_string(), db.register_agent(n)))
+            .collect();
+        db.store_game(&game(1, &[(STARK, "mcts-a"), (LANNISTER, "mcts-b"), (BARATHEON, "heuristic")], STARK), &ids);
+        db.store_game(&game(2, &[(STARK, "heuristic"), (LANNISTER, "mcts-a"), (BARATHEON, "mcts-b")], STARK), &ids);
+        db.store_game(&game(3, &[(STARK, "mcts-b"), (LANNISTER, "heuristic"), (BARATHEON, "mcts-a")], BARATHEON), &ids);
+        db.update_elo(ids[2].1, &[ids[0].1], 32.0);
+        db
+    }
+
+    fn names(rows: &[LeaderboardRow]) -> Vec<&str> {
+        rows.iter().map(|r| r.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_leaderboard_sorts_by_rating_win_rate_or_games() {
+        let db = fixture_db();
+        let board = |sort| db.filtered_leaderboard(&LeaderboardFilter { sort, ..Default::default() });
+
+        assert_eq!(names(&board(LeaderboardSort::Elo)), ["heuristic", "mcts-b", "mcts-a"]);
+        let by_win_rate = board(LeaderboardSort::WinRate);
+        assert_eq!(names(&by_win_rate), ["mcts-a", "heuristic", "mcts-b"]);
+        // All tied on games: ELO decides
+        assert_eq!(names(&board(LeaderboardSort::Games)), ["heuristic", "mcts-b", "mcts-a"]);
+
+        let top = &by_win_rate[0];
+        assert_eq!((top.games, top.wins), (3, 2));
+        assert!((top.win_rate() - 2.0 / 3.0).abs() < 1e-9);
+        assert!("rating".parse::<LeaderboardSort>().is_err());
+    }
+
+    #[test]
+    fn test_leaderboard_filters_by_house_games_and_prefix() {
+        let db = fixture_db();
+
+        // As Stark every agent has one game; the heuristic and mcts-a won theirs
+        let stark = db.filtered_leaderboard(&LeaderboardFilter {
+            sort: LeaderboardSort::WinRate,
+            house: Some(STARK),
+            ..Default::default()
+        });
+        let rows: Vec<(&str, u32, u32)> = stark.iter().map(|r| (r.name.as_str(), r.games, r.wins)).collect();
+        assert_eq!(rows, [("heuristic", 1, 1), ("mcts-a", 1, 1), ("mcts-b", 1, 0)]);
+
+        let mcts = db.filtered_leaderboard(&LeaderboardFilter { agent_prefix: Some("mcts".into()), ..Default::default() });
+        assert_eq!(names(&mcts), ["mcts-b", "mcts-a"]);
+
+        assert_eq!(db.filtered_leaderboard(&LeaderboardFilter { min_games: 3, ..Default::default() }).len(), 3);
+        assert!(db.filtered_leaderboard(&LeaderboardFilter { min_games: 4, ..Default::default() }).is_empty());
+        assert!(db.filtered_leaderboard(&LeaderboardFilter { house: Some(STARK), min_games: 2, ..Default::default() }).is_empty());
+    }
+
+    #[test]
+    fn test_house_pivot_splits_each_agent_by_house() {
+        let db = fixture_db();
+        let pivot = db.house_pivot(&LeaderboardFilter { sort: LeaderboardSort::WinRate, ..Default::default() });
+        let order: Vec<&str> = pivot.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(order, ["mcts-a", "heuristic", "mcts-b"]);
+
+        let a = &pivot[0];
+        assert_eq!(a.by_house, [(BARATHEON, 1, 1), (LANNISTER, 1, 0), (STARK, 1, 1)]);
+        assert_eq!((a.games(), a.wins()), (3, 2));
+
+        let mcts = db.house_pivot(&LeaderboardFilter { agent_prefix: Some("mcts-b".into()), ..Default::default() });
+        assert_eq!(mcts.len(), 1);
+        assert_eq!(mcts[0].wins(), 0);
+    }
+
+    #[test]
+    fn test_stored_games_are_skipped_unless_duplicates_are_allowed() {
+        use crate::runner::{assign_seats, run_seated_game, GameKey};
+        use got_agents::AgentSpec;
+
+        let db = Database::in_memory();
+        let ids = vec![("random".to_string(), db.register_agent("random"))];
+        let lineup = vec![AgentSpec::new("random"); 3];
+        let schedule = || -> Vec<_> {
+            [42, 1042].iter()
+                .map(|&seed| {
+                    let config = SetupConfig::with_houses(vec![STARK, LANNISTER, BARATHEON], seed);
+                    let seats = assign_seats(&config, &lineup);
+                    (config, seats)
+                })
+                .collect()
+        };
+
+        let (games, skipped) = db.skip_stored_games(schedule(), false);
+        assert_eq!((games.len(), skipped), (2, 0));
+        let (config, seats) = &games[0];
+        let result = run_seated_game(config, seats, 50_000).unwrap();
+        assert_eq!(result.key, GameKey::from_seats(config, seats));
+        db.store_game(&result, &ids);
+        assert!(db.contains_game(&result.key));
+
+        // The played seed is skipped, the other kept
+        let (games, skipped) = db.skip_stored_games(schedule(), false);
+        assert_eq!(skipped, 1);
+        let seeds: Vec<u64> = games.iter().map(|(c, _)| c.seed).collect();
+        assert_eq!(seeds, [1042]);
+        // --allow-duplicates plays it again
+        let (games, skipped) = db.skip_stored_games(schedule(), true);
+        assert_eq!((games.len(), skipped), (2, 0));
+
+        // Another lineup or other options on the same seed is another game
+        let (config, _) = &schedule()[0];
+        let heuristic = assign_seats(config, &vec![AgentSpec::new("heuristic"); 3]);
+        assert!(!db.contains_game(&GameKey::from_seats(config, &heuristic)));
+        let mut options = config.clone();
+        options.options.auto_zero_bids = true;
+        assert!(!db.contains_game(&GameKey::from_seats(&options, &assign_seats(&options, &lineup))));
+    }
+
+    #[test]
+    fn test_db_writer_stores_every_game_from_every_worker() {
+        use crate::database::DbWriter;
+
+        let path = std::env::temp_dir().join(format!("got-writer-{}.db", std::process::id()));
+        let path = path.to_str().unwrap().to_string();
+        let remove = || for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path, suffix));
+        };
+        remove();
+        let db = Database::new(&path);
+        let ids: Vec<(String, i64)> = ["mcts-a", "mcts-b", "heuristic"].iter()
+            .map(|&n| (n.to_string(), db.register_agent(n)))
+            .collect();
+        let seats = [(STARK, "mcts-a"), (LANNISTER, "mcts-b"), (BARATHEON, "heuristic")];
+
+        // Three workers, seven games each, four games to a batch
+        let writer = DbWriter::spawn(&path, ids.clone(), 4);
+        std::thread::scope(|s| {
+            for worker in 0..3u64 {
+                let writer = &writer;
+                s.spawn(move || {
+                    for g in 0..7 {
+                        writer.send(game(worker * 100 + g, &seats, STARK));
+                    }
+                });
+            }
+        });
+        assert_eq!(writer.finish(), 21);
+        assert_eq!(db.game_count(), 21);
+
+        // A writer dropped without `finish` still flushes its last batch
+        let writer = DbWriter::spawn(&path, ids, 4);
+        for g in 0..5 {
+            writer.send(game(1000 + g, &seats, LANNISTER));
+        }
+        drop(writer);
+        assert_eq!(db.game_count(), 26);
+        let board = db.filtered_leaderboard(&LeaderboardFilter::default());
+        assert_eq!(board.iter().map(|r| r.games).sum::<u32>(), 3 * 26);
+        assert_eq!(board.iter().find(|r| r.name == "mcts-a").unwrap().wins, 21);
+
+        // Written through the write-ahead log, which lives beside the file
+        // while a connection is open
+        assert!(std::path::Path::new(&format!("{}-wal", path)).exists());
+        drop(db);
+        remove();
+    }
+
+    #[test]
+    fn test_elo_thresholds_fire_on_crossing_only() {
+        use crate::notify::{Milestone, Notifier};
+        let row = |name: &str, elo: f64| LeaderboardRow { name: name.to_string(), elo, games: 1, wins: 0 };
+        let notifier = Notifier::new(Vec::new(), vec![1550.0]);
+
+        // The first sighting only records where each agent stands
+        assert!(notifier.check_elo(&[row("mcts", 1600.0), row("random", 1500.0)]).is_empty());
+        assert!(notifier.check_elo(&[row("mcts", 1580.0), row("random", 1540.0)]).is_empty());
+
+        let crossed = notifier.check_elo(&[row("mcts", 1540.0), row("random", 1560.0)]);
+        assert_eq!(crossed, [
+            Milestone::EloCrossed { agent: "mcts".into(), elo: 1540.0, threshold: 1550.0, above: false },
+            Milestone::EloCrossed { agent: "random".into(), elo: 1560.0, threshold: 1550.0, above: true },
+        ]);
+        assert!(notifier.check_elo(&[row("mcts", 1545.0), row("random", 1570.0)]).is_empty());
+
+        let json = serde_json::to_value(&crossed[1]).unwrap();
+        assert_eq!(json["event"], "elo_crossed");
+        assert_eq!(crossed[1].name(), "elo_crossed");
+    }
+
+    #[test]
+    fn test_match_standings_break_ties_on_points_then_share() {
+        use crate::matches::standings;
+        use got_agents::agent::MatchGame;
+        let lineup: Vec<got_agents::AgentSpec> = ["a", "b", "c"].iter().map(|n| n.parse().unwrap()).collect();
+        let game = |places: [u8; 3]| MatchGame { seed: 0, houses: vec![STARK, LANNISTER, BARATHEON], places: places.to_vec() };
+
+        // One win each; seat 1 has the most placement points
+        let table = standings(&lineup, &[game([1, 2, 3]), game([2, 3, 1]), game([2, 1, 3])]);
+        let summary: Vec<(u32, u32, u8)> = table.iter().map(|s| (s.wins, s.points, s.place)).collect();
+        assert_eq!(summary, [(1, 4, 1), (1, 3, 2), (1, 2, 3)]);
+
+        // Equal on wins and points: the place is shared
+        let table = standings(&lineup, &[game([1, 2, 3]), game([2, 1, 3])]);
+        let places: Vec<u8> = table.iter().map(|s| s.place).collect();
+        assert_eq!(places, [1, 1, 3]);
+    }
+
+    #[test]
+    fn test_match_rotates_houses_and_rates_whole_matches() {
+        use crate::matches::{play_match, MatchOptions};
+        let lineup: Vec<got_agents::AgentSpec> = ["random", "random", "heuristic"].iter().map(|n| n.parse().unwrap()).collect();
+        let opts = MatchOptions {
+            lineup: lineup.clone(),
+            setup: SetupConfig::with_houses(vec![STARK, LANNISTER, BARATHEON], 42),
+            games: 3,
+        };
+        let result = play_match(&opts, 50_000).unwrap();
+        assert_eq!(result.games.len(), 3);
+        for (g, game) in result.games.iter().enumerate() {
+            assert_eq!(game.seed, 42 + g as u64);
+            let houses: Vec<HouseName> = game.seats.iter().map(|s| s.house).collect();
+            assert_eq!(houses, (0..3).map(|s| opts.setup.houses[(s + g) % 3]).collect::<Vec<_>>());
+        }
+        assert_eq!(result.standings.iter().map(|s| s.wins).sum::<u32>(), 3);
+        assert!(!result.winners().is_empty());
+
+        let db = Database::in_memory();
+        let ids: Vec<(String, i64)> = ["random", "heuristic"].iter()
+            .map(|&n| (n.to_string(), db.register_agent(n)))
+            .collect();
+        db.store_match(&result, &ids);
+        assert_eq!(db.game_count(), 3);
+        assert_eq!(db.rated_matches().len(), 1);
+        let board = db.match_leaderboard(crate::ratings::RatingSystem::Elo);
+        assert_eq!(board.iter().map(|r| r.2).sum::<u32>(), 3);
+    }
+
+    /// Plays at random, then on its `fail_at`-th decision panics or, with
+    /// `illegal` set, answers with a bid it can't pay for. With `stall`
+    /// set it instead takes a quarter of a second over that decision.
+    struct Faulty {
+        inner: got_agents::RandomAgent,
+        decisions: usize,
+        fail_at: usize,
+        illegal: bool,
+        stall: bool,
+        resign: bool,
+        /// What `needs_view` answers.
+        needs_view: bool,
+        /// For each decision, whether the view carried the full-view summaries.
+        shown: Arc<Mutex<Vec<bool>>>,
+    }
+
+    // `decide` is overridden, so the per-decision methods are never called
+    impl got_agents::Agent for Faulty {
+        fn name(&self) -> &str { "faulty" }
+        fn house(&self) -> HouseName { self.inner.house() }
+        fn needs_view(&self) -> bool { self.needs_view }
+
+        fn decide(&mut self, view: &PlayerView) -> Action {
+            self.decisions += 1;
+            let full = !view.garrisons.is_empty()
+                || view.house_info.values().any(|i| !i.discards.is_empty() || i.remaining_abilities != AbilitySummary::default());
+            self.shown.lock().unwrap().push(full);
+            if self.decisions == self.fail_at && self.resign {
+                return Action::Resign;
+            } else if self.decisions == self.fail_at && self.stall {
+                std::thread::sleep(std::time::Duration::from_millis(250));
+            } else if self.decisions == self.fail_at {
+                if self.illegal {
+                    return Action::Bid(u8::MAX);
+                }
+                panic!("faulty agent gave up");
+            }
+            self.inner.decide(view)
+        }
+
+        fn place_orders(&mut self, _view: &PlayerView) -> Vec<(AreaId, u8)> { unreachable!() }
+        fn choose_order_to_resolve(&mut self, _view: &PlayerView, _order_type: OrderType, _candidates: &[AreaId]) -> AreaId { unreachable!() }
+        fn choose_raid(&mut self, _view: &PlayerView, _from: AreaId, _targets: &[AreaId]) -> Option<AreaId> { unreachable!() }
+        fn choose_march(&mut self, _view: &PlayerView, _from: AreaId, _destinations: &[AreaId], _selectable: &[usize]) -> (AreaId, Vec<usize>) { unreachable!() }
+        fn leave_power_token(&mut self, _view: &PlayerView, _area: AreaId) -> bool { unreachable!() }
+        fn declare_support(&mut self, _view: &PlayerView, _attacker: HouseName, _defender: HouseName) -> SupportChoice { unreachable!() }
+        fn select_house_card(&mut self, _view: &PlayerView, _available: &[HouseCardId]) -> HouseCardId { unreachable!() }
+        fn use_valyrian_blade(&mut self, _view: &PlayerView) -> bool { unreachable!() }
+        fn submit_bid(&mut self, _view: &PlayerView, _bid_type: BiddingType, _track: Option<Track>) -> u8 { unreachable!() }
+        fn westeros_choice(&mut self, _view: &PlayerView, _options: &[String]) -> usize { unreachable!() }
+        fn choose_muster(&mut self, _view: &PlayerView, _areas: &[MusterArea]) -> Vec<(AreaId, MusterAction2)> { unreachable!() }
+        fn choose_retreat(&mut self, _view: &PlayerView, _options: &[AreaId]) -> AreaId { unreachable!() }
+        fn choose_reconcile(&mut self, _view: &PlayerView, _area: AreaId) -> (AreaId, usize) { unreachable!() }
+        fn use_messenger_raven(&mut self, _view: &PlayerView) -> Option<(AreaId, u8)> { unreachable!() }
+        fn use_aeron(&mut self, _view: &PlayerView, _options: &[HouseCardId]) -> Option<HouseCardId> { unreachable!() }
+        fn tyrion_replacement(&mut self, _view: &PlayerView, _options: &[HouseCardId]) -> HouseCardId { unreachable!() }
+        fn patchface_discard(&mut self, _view: &PlayerView, _visible: &[HouseCardId]) -> HouseCardId { unreachable!() }
+        fn robb_retreat(&mut self, _view: &PlayerView, _options: &[AreaId]) -> AreaId { unreachable!() }
+        fn wildling_penalty(&mut self, _view: &PlayerView, _options: &[String]) -> usize { unreachable!() }
+        fn cersei_remove_order(&mut self, _view: &PlayerView) -> AreaId { unreachable!() }
+        fn doran_choose_track(&mut self, _view: &PlayerView) -> Track { unreachable!() }
+        fn queen_of_thorns(&mut self, _view: &PlayerView) -> AreaId { unreachable!() }
+    }
+
+    #[test]
+    fn test_agent_panic_is_recorded_and_the_game_goes_on() {
+        use crate::runner::run_game_with_setup;
+        use got_agents::{Agent, RandomAgent};
+        let config = SetupConfig::with_houses(vec![STARK, LANNISTER, BARATHEON], 42);
+        let mut agents: HashMap<HouseName, Box<dyn Agent>> = HashMap::new();
+        agents.insert(STARK, Box::new(Faulty { inner: RandomAgent::new(STARK, 1), decisions: 0, fail_at: 3, illegal: false, stall: false, resign: false, needs_view: true, shown: Default::default() }));
+        agents.insert(LANNISTER, Box::new(RandomAgent::new(LANNISTER, 2)));
+        agents.insert(BARATHEON, Box::new(RandomAgent::new(BARATHEON, 3)));
+
+        let result = run_game_with_setup(&mut agents, &config, 50_000).unwrap();
+        assert_eq!(result.agent_faults.len(), 1);
+        let fault = &result.agent_faults[0];
+        assert_eq!((fault.house, fault.agent.as_str(), fault.message.as_str()), (STARK, "faulty", "faulty agent gave up"));
+        // The result still counts for the agent that failed
+        let stark = result.player_results.iter().find(|p| p.house == STARK).unwrap();
+        assert_eq!(stark.agent_name, "faulty");
+
+        let db = Database::in_memory();
+        let ids: Vec<(String, i64)> = ["faulty", "random"].iter()
+            .map(|&n| (n.to_string(), db.register_agent(n)))
+            .collect();
+        let game_id = db.store_game(&result, &ids);
+        assert_eq!(db.agent_faults(), [(game_id, fault.clone())]);
+    }
+
+    #[test]
+    fn test_illegal_action_is_replaced_or_forfeits_by_policy() {
+        use crate::runner::{run_game_with_policy, ActionPolicy, FaultKind};
+        use got_agents::{Agent, RandomAgent};
+        let config = SetupConfig::with_houses(vec![STARK, LANNISTER, BARATHEON], 42);
+        let play = |policy| {
+            let mut agents: HashMap<HouseName, Box<dyn Agent>> = HashMap::new();
+            agents.insert(STARK, Box::new(Faulty { inner: RandomAgent::new(STARK, 1), decisions: 0, fail_at: 3, illegal: true, stall: false, resign: false, needs_view: true, shown: Default::default() }));
+            agents.insert(LANNISTER, Box::new(RandomAgent::new(LANNISTER, 2)));
+            agents.insert(BARATHEON, Box::new(RandomAgent::new(BARATHEON, 3)));
+            run_game_with_policy(&mut agents, &config, 50_000, policy).unwrap()
+        };
+
+        let lenient = play(ActionPolicy::Lenient);
+        assert_eq!(lenient.agent_faults.len(), 1);
+        assert_eq!(lenient.agent_faults[0].kind, FaultKind::IllegalAction);
+        assert_eq!(lenient.forfeit, None);
+        assert!(lenient.rounds_played > 1, "the game went on");
+
+        let strict = play(ActionPolicy::Strict);
+        assert_eq!(strict.forfeit, Some(STARK));
+        assert_eq!(strict.final_ranking.last(), Some(&STARK));
+        assert_ne!(strict.winner, STARK);
+        assert_eq!(strict.winner, strict.final_ranking[0]);
+
+        // The forfeit costs the agent its rating: it is ranked last
+        let db = Database::in_memory();
+        let ids: Vec<(String, i64)> = ["faulty", "random"].iter()
+            .map(|&n| (n.to_string(), db.register_agent(n)))
+            .collect();
+        db.store_game(&strict, &ids);
+        let seats = &db.rated_games()[0];
+        assert!(seats.contains(&(ids[0].1, 3)));
+        assert_eq!(db.agent_faults()[0].1.kind, FaultKind::IllegalAction);
+    }
+
+    #[test]
+    fn test_time_control_shows_clocks_and_forfeits_on_timeout() {
+        use crate::runner::{assign_seats, run_game_with_policy, run_seated_game_observed, ActionPolicy, FaultKind};
+        use got_agents::{Agent, AgentSpec, RandomAgent};
+        assert_eq!("60+0.5".parse::<TimeControl>(), Ok(TimeControl { budget_ms: 60_000, increment_ms: 500 }));
+        assert_eq!("0.2".parse::<TimeControl>().unwrap().to_string(), "0.2+0");
+        assert!("0+1".parse::<TimeControl>().is_err());
+
+        let mut config = SetupConfig::with_houses(vec![STARK, LANNISTER, BARATHEON], 42);
+        config.options.time_control = Some(TimeControl { budget_ms: 60_000, increment_ms: 1_000 });
+        let seats = assign_seats(&config, &vec![AgentSpec::new("random"); 3]);
+        let mut clocks = 0;
+        let result = run_seated_game_observed(&config, &seats, 50_000, |_, view, _| {
+            let clock = view.clock.expect("every view carries the clock");
+            assert_eq!(clock.increment_ms, 1_000);
+            assert!(clock.remaining_ms > 0);
+            clocks += 1;
+        }).unwrap();
+        assert!(clocks > 0);
+        assert_eq!(result.forfeit, None);
+
+        // A quarter-second answer on a fifth of a second forfeits under the
+        // strict policy, and hands the house to random legal moves under
+        // the lenient one
+        config.options.time_control = Some(TimeControl { budget_ms: 200, increment_ms: 0 });
+        let stalling = || {
+            let mut agents: HashMap<HouseName, Box<dyn Agent>> = HashMap::new();
+            agents.insert(STARK, Box::new(Faulty { inner: RandomAgent::new(STARK, 1), decisions: 0, fail_at: 3, illegal: false, stall: true, resign: false, needs_view: true, shown: Default::default() }));
+            agents.insert(LANNISTER, Box::new(RandomAgent::new(LANNISTER, 2)));
+            agents.insert(BARATHEON, Box::new(RandomAgent::new(BARATHEON, 3)));
+            agents
+        };
+        let lenient = run_game_with_policy(&mut stalling(), &config, 50_000, ActionPolicy::Lenient).unwrap();
+        assert_eq!(lenient.forfeit, None);
+        assert_eq!(lenient.agent_faults.len(), 1, "the house is not asked again");
+        assert_eq!(lenient.agent_faults[0].kind, FaultKind::Timeout);
+        assert!(lenient.rounds_played > 1);
+
+        let result = run_game_with_policy(&mut stalling(), &config, 50_000, ActionPolicy::Strict).unwrap();
+        assert_eq!(result.forfeit, Some(STARK));
+        assert_eq!(result.final_ranking.last(), Some(&STARK));
+        assert_eq!(result.agent_faults.len(), 1);
+        assert_eq!(result.agent_faults[0].kind, FaultKind::Timeout);
+
+        let db = Database::in_memory();
+        let ids: Vec<(String, i64)> = ["faulty", "random"].iter()
+            .map(|&n| (n.to_string(), db.register_agent(n)))
+            .collect();
+        db.store_game(&result, &ids);
+        assert_eq!(db.agent_faults()[0].1.kind, FaultKind::Timeout);
+    }
+
+    #[test]
+    fn test_resignation_is_recorded_and_the_others_play_on() {
+        use crate::runner::{run_game_with_bids, ActionPolicy, BidCollection};
+        use got_agents::{Agent, RandomAgent};
+
+        let config = SetupConfig::with_houses(vec![STARK, LANNISTER, BARATHEON, HouseName::Greyjoy], 5);
+        let mut agents: HashMap<HouseName, Box<dyn Agent>> = HashMap::new();
+        agents.insert(STARK, Box::new(Faulty { inner: RandomAgent::new(STARK, 1), decisions: 0, fail_at: 3, illegal: false, stall: false, resign: true, needs_view: true, shown: Default::default() }));
+        for (i, house) in [LANNISTER, BARATHEON, HouseName::Greyjoy].into_iter().enumerate() {
+            agents.insert(house, Box::new(RandomAgent::new(house, i as u64 + 2)));
+        }
+        let result = run_game_with_bids(&mut agents, &config, 50_000, ActionPolicy::Strict, BidCollection::Simultaneous).unwrap();
+        assert_eq!(result.resigned, vec![STARK]);
+        assert!(!result.drawn);
+        assert_eq!(result.forfeit, None, "resigning is not a fault");
+        assert!(result.agent_faults.is_empty());
+        assert_ne!(result.winner, STARK);
+        assert_eq!(result.final_ranking.last(), Some(&STARK));
+        assert!(result.rounds_played > 1, "the others play on");
+    }
+
+    #[test]
+    fn test_simultaneous_bids_play_the_same_game() {
+        use crate::runner::{run_game_with_bids, ActionPolicy, BidCollection};
+        use got_agents::{Agent, HeuristicAgent};
+        use got_engine::types::GameEventKind;
+        let config = SetupConfig::new(6, 11);
+        let play = |bids| {
+            let mut agents: HashMap<HouseName, Box<dyn Agent>> = config.houses.iter()
+                .map(|&h| (h, Box::new(HeuristicAgent::new(h, h as u64)) as Box<dyn Agent>))
+                .collect();
+            run_game_with_bids(&mut agents, &config, 50_000, ActionPolicy::Lenient, bids).unwrap()
+        };
+
+        let sequential = play(BidCollection::Sequential);
+        let simultaneous = play(BidCollection::Simultaneous);
+        assert!(sequential.events.iter().any(|e| matches!(e.kind, GameEventKind::WildlingBid { .. })), "the game had a bidding round");
+        assert_eq!(simultaneous.final_ranking, sequential.final_ranking);
+        assert_eq!(simultaneous.rounds_played, sequential.rounds_played);
+        assert_eq!(serde_json::to_value(&simultaneous.events).unwrap(), serde_json::to_value(&sequential.events).unwrap());
+        assert!(simultaneous.agent_faults.is_empty());
+    }
+
+    #[test]
+    fn test_minimal_views_play_the_same_random_game() {
+        use crate::runner::{run_game_with_policy, ActionPolicy};
+        use got_agents::{Agent, RandomAgent};
+        let config = SetupConfig::new(6, 13);
+        // The same random agents, asking for the full view or not
+        let play = |needs_view: bool| {
+            let shown: Arc<Mutex<Vec<bool>>> = Default::default();
+            let mut agents: HashMap<HouseName, Box<dyn Agent>> = config.houses.iter()
+                .map(|&h| {
+                    let inner = RandomAgent::new(h, h as u64);
+                    assert!(!inner.needs_view());
+                    let agent: Box<dyn Agent> = Box::new(Faulty {
+                        inner, decisions: 0, fail_at: usize::MAX, illegal: false, stall: false, resign: false,
+                        needs_view, shown: shown.clone(),
+                    });
+                    (h, agent)
+                })
+                .collect();
+            let result = run_game_with_policy(&mut agents, &config, 50_000, ActionPolicy::Strict).unwrap();
+            let shown = shown.lock().unwrap().clone();
+            (result, shown)
+        };
+
+        let (minimal, minimal_shown) = play(false);
+        let (full, full_shown) = play(true);
+        assert!(!minimal_shown.is_empty() && minimal_shown.iter().all(|&f| !f));
+        assert!(full_shown.iter().all(|&f| f));
+        assert_eq!(minimal_shown.len(), full_shown.len());
+        assert_eq!(minimal.final_ranking, full.final_ranking);
+        assert_eq!(serde_json::to_value(&minimal.events).unwrap(), serde_json::to_value(&full.events).unwrap());
+        assert!(minimal.agent_faults.is_empty());
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_async_games_match_sync_games() {
+        use crate::async_runner::{run_games_async, AsyncAgents};
+        use crate::runner::{run_game_with_policy, ActionPolicy};
+        use got_agents::async_agent::{AsyncAgent, DecideFuture};
+        use got_agents::{Agent, HeuristicAgent};
+        use std::time::Duration;
+
+        // Answers like the heuristic agent, after a network round trip
+        struct Delayed(HeuristicAgent);
+        impl AsyncAgent for Delayed {
+            fn name(&self) -> &str { "delayed" }
+            fn house(&self) -> HouseName { self.0.house() }
+            fn decide<'a>(&'a mut self, view: &'a PlayerView) -> DecideFuture<'a> {
+                Box::pin(async move {
+                    tokio::time::sleep(Duration::from_micros(100)).await;
+                    self.0.decide(view)
+                })
+            }
+        }
+
+        let configs: Vec<SetupConfig> = (0..3).map(|seed| SetupConfig::new(6, 40 + seed)).collect();
+        let games = configs.iter()
+            .map(|config| {
+                let agents: AsyncAgents = config.houses.iter()
+                    .map(|&h| (h, Box::new(Delayed(HeuristicAgent::new(h, h as u64))) as Box<dyn AsyncAgent>))
+                    .collect();
+                (config.clone(), agents)
+            })
+            .collect();
+        let mut results = vec![None; configs.len()];
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(run_games_async(games, 2, 50_000, ActionPolicy::Lenient, |i, r| results[i] = Some(r.unwrap())));
+
+        for (config, result) in configs.iter().zip(results) {
+            let result = result.expect("every game reported");
+            let mut agents: HashMap<HouseName, Box<dyn Agent>> = config.houses.iter()
+                .map(|&h| (h, Box::new(HeuristicAgent::new(h, h as u64)) as Box<dyn Agent>))
+                .collect();
+            let sync = run_game_with_policy(&mut agents, config, 50_000, ActionPolicy::Lenient).unwrap();
+            assert_eq!(result.final_ranking, sync.final_ranking);
+            assert_eq!(serde_json::to_value(&result.events).unwrap(), serde_json::to_value(&sync.events).unwrap());
+            assert!(result.player_results.iter().all(|p| p.agent_name == "delayed"));
+            assert!(result.agent_faults.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_decision_contexts_round_trip() {
+        use crate::contexts::{self, ContextWriter};
+        use got_engine::setup::create_game;
+        use got_engine::visibility::player_view;
+
+        let config = SetupConfig::with_houses(vec![STARK, LANNISTER, BARATHEON], 5);
+        let state = create_game(&config).unwrap();
+        let house = STARK;
+        let view = player_view(&state, house);
+        let root = std::env::temp_dir().join(format!("got-contexts-{}", std::process::id()));
+        let action = Action::PlaceOrders(Vec::new());
+
+        let mut writer = ContextWriter::new(&root, config.seed).unwrap();
+        writer.record(house, "random", &view, &action).unwrap();
+        let path = writer.record(house, "random", &view, &action).unwrap();
+        assert_eq!(writer.len(), 2);
+        assert!(path.ends_with("game-5/view_0001.json"));
+
+        let (loaded, context) = contexts::load(&path).unwrap();
+        let context = context.expect("a saved context has its answer");
+        assert_eq!((context.decision, context.seed, context.house), (1, 5, house));
+        assert_eq!(context.agent, "random");
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&view).unwrap());
+
+        // A bare view loads without an answer
+        let bare = root.join("view.json");
+        std::fs::write(&bare, serde_json::to_string(&view).unwrap()).unwrap();
+        let (_, context) = contexts::load(&bare).unwrap();
+        assert!(context.is_none());
+
+        // Writing the game again starts from an empty directory
+        let writer = ContextWriter::new(&root, config.seed).unwrap();
+        assert!(writer.is_empty());
+        assert_eq!(std::fs::read_dir(writer.dir()).unwrap().count(), 0);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_answers_count_every_seed() {
+        use crate::contexts::{answers, check_decision};
+        use got_agents::AgentSpec;
+        use got_engine::engine::advance;
+        use got_engine::setup::create_game;
+        use got_engine::visibility::player_view;
+
+        let config = SetupConfig::with_houses(vec![STARK, LANNISTER, BARATHEON], 9);
+        let mut state = create_game(&config).unwrap();
+        advance(&mut state);
+        let house = state.peek_pending().expect("planning asks for orders").house();
+        let view = player_view(&state, house);
+        check_decision(&view).unwrap();
+
+        let random: AgentSpec = "random".parse().unwrap();
+        let counts = answers(&view, &random, 0, 12).unwrap();
+        assert_eq!(counts.iter().map(|c| c.count).sum::<usize>(), 12);
+        assert!(counts.windows(2).all(|w| w[0].count >= w[1].count));
+        // The same seeds give the same answers
+        let again = answers(&view, &random, 0, 12).unwrap();
+        assert_eq!(serde_json::to_value(&again[0].action).unwrap(), serde_json::to_value(&counts[0].action).unwrap());
+
+        // Every house places orders at once; one that has answered isn't asked
+        let other = *config.houses.iter().find(|&&h| h != house).unwrap();
+        check_decision(&player_view(&state, other)).unwrap();
+        got_engine::engine::apply_action_as(&mut state, house, counts[0].action.clone()).unwrap();
+        assert!(check_decision(&player_view(&state, house)).is_err());
+        assert!(answers(&player_view(&state, house), &random, 0, 3).is_err());
+    }
+
+    #[test]
+    fn test_positions_suite_loads_and_checks_answers() {
+        use crate::positions::{load_suite, suite_score, verify, Expectation};
+        use got_agents::AgentSpec;
+
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../positions");
+        let suite = load_suite(&dir).unwrap();
+        assert!(suite.len() >= 4);
+
+        let kl = suite.iter().find(|p| p.name == "defend-kings-landing").unwrap();
+        let kings_landing = AreaId(20);
+        assert!(kl.check(&Action::PlaceOrders(vec![(kings_landing, 3)])).is_ok());
+        let err = kl.check(&Action::PlaceOrders(vec![(kings_landing, 0)])).unwrap_err();
+        assert!(err.contains("March"), "{}", err);
+        assert!(kl.check(&Action::PlaceOrders(Vec::new())).is_err());
+
+        let bid = suite.iter().find(|p| p.name == "wildling-bid-threat-share").unwrap();
+        assert!(bid.check(&Action::Bid(4)).is_ok());
+        assert!(bid.check(&Action::Bid(3)).is_err());
+        // Capped at the power the viewer has
+        let all_in = Expectation::BidAtLeast { amount: 50 };
+        assert!(all_in.check(&bid.view, &Action::Bid(7)).is_ok());
+
+        let random: AgentSpec = "random".parse().unwrap();
+        let scores = verify(&random, &suite, 0, 4).unwrap();
+        assert_eq!(scores.len(), suite.len());
+        assert!(scores.iter().all(|s| s.asked == 4 && s.passed + s.failures.iter().map(|f| f.1).sum::<usize>() == 4));
+        let score = suite_score(&scores);
+        assert!((0.0..=1.0).contains(&score));
+    }
+
+    #[test]
+    fn test_differential_public_state_and_json_diff() {
+        use crate::differential::{json_diff, public_state};
+        use got_engine::setup::create_game;
+        use serde_json::json;
+
+        let mut state = create_game(&SetupConfig::new(3, 5)).unwrap();
+        got_engine::engine::advance(&mut state);
+        let winterfell = AreaId(3);
+        state.areas[winterfell.0 as usize].order = Some(Order {
+            order_type: OrderType::March, strength: 0, star: false, house: STARK, token_index: 0,
+        });
+        let public = public_state(&state);
+        assert!(public.get("my_hand").is_none() && public.get("viewer").is_none());
+        let area = &public["areas"][winterfell.0 as usize];
+        assert_eq!(area["order"], serde_json::Value::Null, "orders are face down in Planning");
+        assert_eq!(area["has_hidden_order"], true);
+        assert!(json_diff(&public, &public.clone()).is_empty());
+
+        let ours = json!({ "round": 1, "house_info": { "Stark": { "supply": 1 } }, "areas": [{ "units": [] }] });
+        let theirs = json!({ "round": 1, "house_info": { "Stark": { "supply": 2 } }, "areas": [{ "units": [], "blocked": true }] });
+        assert_eq!(json_diff(&ours, &theirs), vec!["areas.0.blocked: null != true", "house_info.Stark.supply: 1 != 2"]);
+    }
+
+    #[test]
+    fn test_differential_reports_a_broken_reference() {
+        use crate::differential::{diff_game, ReferenceEngine};
+
+        // `cat` echoes the request back, which has no state in it
+        let mut echo = ReferenceEngine::spawn("cat").unwrap();
+        let err = diff_game(&mut echo, &SetupConfig::new(3, 1), 0, 10).unwrap_err();
+        assert!(err.contains("without a state"), "{}", err);
+        let mut silent = ReferenceEngine::spawn("true").unwrap();
+        assert!(diff_game(&mut silent, &SetupConfig::new(3, 1), 0, 10).is_err());
+    }
+
+    #[test]
+    fn test_metrics_render_counts_games_and_db_writes() {
+        use crate::metrics::Metrics;
+        use crate::runner::{AgentFault, FaultKind};
+        use std::time::Duration;
+
+        let metrics = Metrics::new();
+        let mut result = game(1, &[(STARK, "a"), (LANNISTER, "b"), (BARATHEON, "c")], STARK);
+        result.decisions = 120;
+        result.agent_faults = vec![
+            AgentFault { kind: FaultKind::Timeout, decision: 3, round: 1, house: STARK, agent: "a".into(), message: String::new() },
+            AgentFault { kind: FaultKind::Panic, decision: 9, round: 2, house: LANNISTER, agent: "b".into(), message: String::new() },
+        ];
+        metrics.record_game(&result);
+        metrics.record_finished(30);
+        metrics.record_engine_error();
+        metrics.record_db_write(2, Duration::from_millis(250));
+
+        let text = metrics.render();
+        for line in [
+            "got_games_completed_total 2", "got_decisions_total 150", "got_agent_timeouts_total 1",
+            "got_agent_faults_total 1", "got_engine_errors_total 1", "got_db_write_seconds_sum 0.250000",
+            "got_db_write_seconds_count 1", "got_db_games_written_total 2", "# TYPE got_decisions_total counter",
+        ] {
+            assert!(text.lines().any(|l| l == line), "missing `{}` in\n{}", line, text);
+        }
+    }
+
+    #[test]
+    fn test_experiments_parse_and_pair_perturbed_games() {
+        use crate::experiments::{perturbed_setup, run_experiments, ExperimentOptions, Perturbation};
+
+        for text in ["power+2", "supply-1", "remove-knight"] {
+            assert_eq!(text.parse::<Perturbation>().unwrap().to_string(), text);
+        }
+        assert!("power+0".parse::<Perturbation>().is_err());
+        assert!("remove-dragon".parse::<Perturbation>().is_err());
+        assert!("gold+1".parse::<Perturbation>().is_err());
+
+        let config = SetupConfig::new(3, 7);
+        let perturbed = perturbed_setup(&config, STARK, Perturbation::Power(2)).unwrap();
+        assert_eq!((perturbed.seed, &perturbed.houses), (config.seed, &config.houses));
+        assert_eq!(perturbed.options.starting_power(STARK), 7);
+        assert!(perturbed_setup(&config, STARK, Perturbation::RemoveUnit(UnitType::SiegeEngine)).is_none());
+
+        let opts = ExperimentOptions {
+            agent: "random".parse().unwrap(),
+            perturbations: vec![Perturbation::Supply(1), Perturbation::RemoveUnit(UnitType::SiegeEngine)],
+            seeds: 2,
+            first_seed: 1,
+            players: 3,
+            threads: 1,
+        };
+        let rows = run_experiments(&opts, 50_000).unwrap();
+        assert_eq!(rows.len(), 3, "one row per house; nobody starts with a siege engine");
+        for row in &rows {
+            assert_eq!(row.perturbation, Perturbation::Supply(1));
+            assert_eq!(row.pairs, 2);
+            assert!((row.delta - (row.perturbed_rate - row.base_rate)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_map_balance_rebuilds_area_history_from_events() {
+        use crate::map_balance::{MapBalance, Zone};
+        use got_engine::map::{KARHOLD, WINTERFELL};
+
+        let mut result = game(1, &[(STARK, "a"), (LANNISTER, "b"), (BARATHEON, "c")], STARK);
+        result.rounds_played = 2;
+        let event = |seq, round, kind| GameEvent { seq, round, phase: Phase::Action, kind };
+        result.events = vec![
+            event(0, 1, GameEventKind::ControlChanged { area_id: KARHOLD, from: None, to: Some(STARK), castles_after: 3 }),
+            event(1, 2, GameEventKind::CombatResolved {
+                area_id: KARHOLD, attacker: LANNISTER, defender: STARK, winner: LANNISTER,
+                attacker_card: None, defender_card: None, attacker_strength: 3, defender_strength: 1,
+                attacker_support: 0, defender_support: 0, casualties: 0,
+            }),
+            event(2, 2, GameEventKind::ControlChanged { area_id: KARHOLD, from: Some(STARK), to: Some(LANNISTER), castles_after: 3 }),
+        ];
+        let mut balance = MapBalance::new();
+        for _ in 0..20 {
+            balance.record(&result).unwrap();
+        }
+        let report = balance.report();
+        let area = |id: AreaId| report.iter().find(|a| a.area == id).unwrap();
+
+        let karhold = area(KARHOLD);
+        assert_eq!((karhold.games, karhold.capture_rate, karhold.battle_rate), (20, 2.0, 1.0));
+        assert_eq!(karhold.held_share, 1.0);
+        assert_eq!(karhold.controllers, vec![(STARK, 0.5), (LANNISTER, 0.5)]);
+        assert_eq!(karhold.heat, 1.0, "the busiest area");
+        assert_eq!(karhold.win_lift, Some(0.0), "Lannister holds it at the end and never wins");
+        assert_eq!(karhold.zone, Zone::Normal);
+
+        let winterfell = area(WINTERFELL);
+        assert_eq!(winterfell.controllers, vec![(STARK, 1.0)]);
+        assert!((winterfell.win_lift.unwrap() - 3.0).abs() < 1e-9, "Stark wins every game, three times its share");
+        assert_eq!((winterfell.heat, winterfell.zone), (0.0, Zone::Dead));
+
+        let start = got_engine::setup::create_game(&result.setup).unwrap();
+        let blocked = start.areas.iter().position(|a| a.blocked).expect("3-player games block areas");
+        assert!(report.iter().all(|a| a.area.0 as usize != blocked), "blocked areas are not in play");
+    }
+
+    #[test]
+    fn test_branching_counts_every_decision_and_sums_in_the_db() {
+        use crate::runner::run_game_with_setup;
+        use got_agents::{Agent, RandomAgent};
+        let config = SetupConfig::with_houses(vec![STARK, LANNISTER, BARATHEON], 7);
+        let mut agents: HashMap<HouseName, Box<dyn Agent>> = HashMap::new();
+        for (i, &h) in config.houses.iter().enumerate() {
+            agents.insert(h, Box::new(RandomAgent::new(h, i as u64)));
+        }
+        let result = run_game_with_setup(&mut agents, &config, 50_000).unwrap();
+        let b = &result.branching;
+        assert_eq!(b.decisions, result.decisions);
+        assert_eq!(b.per_kind.iter().map(|k| k.decisions).sum::<usize>(), b.decisions);
+        assert!(b.per_kind.windows(2).all(|w| w[0].kind < w[1].kind));
+        assert!(b.forced > 0 && b.forced < b.decisions);
+        assert!(b.mean() > 1.0 && b.max >= b.mean() as usize);
+        assert!(b.log10_tree_size > 0.0);
+        let orders = b.per_kind.iter().find(|k| k.kind == "place_orders").unwrap();
+        assert_eq!(orders.forced, 0);
+
+        let db = Database::in_memory();
+        let ids = vec![("random".to_string(), db.register_agent("random"))];
+        db.store_games(&[result.clone(), result.clone()], &ids);
+        let (games, totals) = db.branching_totals();
+        assert_eq!(games, 2);
+        let mut twice = b.clone();
+        twice.merge(b);
+        assert_eq!((totals.decisions, totals.forced, totals.options, totals.max), (twice.decisions, twice.forced, twice.options, twice.max));
+        assert!((totals.log10_tree_size - 2.0 * b.log10_tree_size).abs() < 1e-6);
+        assert_eq!(totals.per_kind.len(), b.per_kind.len());
+    }
+
+    /// A random game from `seed`, played until `stop` holds.
+    fn play_until(seed: u64, stop: impl Fn(&GameState) -> bool) -> GameState {
+        use got_engine::engine::{advance, apply_action, random_legal_action};
+        use got_engine::setup::create_game;
+        use rand::SeedableRng;
+        let mut state = create_game(&SetupConfig::new(6, seed)).unwrap();
+        let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(seed);
+        advance(&mut state);
+        while !stop(&state) {
+            let action = random_legal_action(&state, &mut rng).expect("the game reaches the position");
+            apply_action(&mut state, action).unwrap();
+            advance(&mut state);
+        }
+        state
+    }
+
+    #[test]
+    fn test_zobrist_hashes_the_whole_position() {
+        use got_agents::search_support::ZobristHash;
+        use got_engine::visibility::player_view;
+
+        // Equal positions hash equal, whatever order their maps come back in
+        let state = play_until(3, |s| s.round == 2);
+        let copy: GameState = serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+        assert_eq!(copy.zobrist(), state.zobrist());
+        assert_eq!(player_view(&copy, STARK).zobrist(), player_view(&state, STARK).zobrist());
+
+        // The battle: the cards chosen and the supports declared
+        let battle = play_until(5, |s| s.combat().is_some());
+        let mut carded = battle.clone();
+        carded.combat_mut().unwrap().attacker_card = Some(HouseCardId::EddardStark);
+        assert_ne!(carded.zobrist(), battle.zobrist());
+        let mut supported = battle.clone();
+        supported.combat_mut().unwrap().support_decisions.insert(AreaId(0), SupportChoice::Defender);
+        assert_ne!(supported.zobrist(), battle.zobrist());
+        let defender = battle.combat().unwrap().defender;
+        assert_ne!(player_view(&carded, defender).zobrist(), player_view(&battle, defender).zobrist());
+
+        // Sealed bids, hidden from views until revealed
+        let auction = play_until(7, |s| s.bidding().is_some());
+        let (mut two, mut three) = (auction.clone(), auction.clone());
+        two.bidding_mut().unwrap().sealed_bids.insert(STARK, 2);
+        three.bidding_mut().unwrap().sealed_bids.insert(STARK, 3);
+        assert_ne!(two.zobrist(), three.zobrist());
+        assert_eq!(player_view(&two, LANNISTER).zobrist(), player_view(&three, LANNISTER).zobrist());
+
+        // Deck order
+        let mut shuffled = state.clone();
+        shuffled.wildling_deck.reverse();
+        assert_ne!(shuffled.zobrist(), state.zobrist());
+        shuffled = state.clone();
+        shuffled.westeros_deck_1.reverse();
+        assert_ne!(shuffled.zobrist(), state.zobrist());
+    }
+
+    #[test]
+    fn test_transposition_table_stores_probes_and_replaces_by_depth() {
+        use got_agents::search_support::TranspositionTable;
+
+        let table: TranspositionTable<&str> = TranspositionTable::new(3);
+        assert_eq!(table.capacity(), 4);
+        assert_eq!(table.probe(1), None);
+        assert!(table.store(1, 2, "one"));
+        assert_eq!(table.probe(1), Some(("one", 2)));
+        // Another position in the same slot: kept out by a deeper entry,
+        // let in at the same depth or deeper
+        assert!(!table.store(5, 1, "five"));
+        assert_eq!(table.probe(1), Some(("one", 2)));
+        assert!(table.store(5, 2, "five"));
+        assert_eq!(table.probe(1), None);
+        assert_eq!(table.probe(5), Some(("five", 2)));
+        // The same position is always updated
+        assert!(table.store(5, 0, "five again"));
+        assert_eq!(table.probe(5), Some(("five again", 0)));
+
+        let stats = table.stats();
+        assert_eq!((stats.probes, stats.hits, stats.stores), (6, 4, 3));
+        assert_eq!((stats.replacements, stats.rejected), (1, 1));
+        assert!((stats.hit_rate() - 4.0 / 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_elo_moves_an_equal_pair_by_half_k() {
+        use crate::ratings::{self, RatingSystem};
+
+        let mut table = HashMap::new();
+        ratings::update(RatingSystem::Elo, &mut table, &[(1, 1), (2, 2)]);
+        assert!((table[&1].mu - 1516.0).abs() < 1e-9);
+        assert!((table[&2].mu - 1484.0).abs() < 1e-9);
+
+        // Three equal seats: K is shared, so the winner still gains K/2,
+        // the middle seat breaks even and the total is conserved
+        let mut table = HashMap::new();
+        ratings::update(RatingSystem::Elo, &mut table, &[(1, 1), (2, 2), (3, 3)]);
+        assert!((table[&1].mu - 1516.0).abs() < 1e-9);
+        assert!((table[&2].mu - 1500.0).abs() < 1e-9);
+        assert!((table[&3].mu - 1484.0).abs() < 1e-9);
+        assert_eq!(table[&1].sigma, 0.0);
+        assert!("ELO".parse::<RatingSystem>().is_ok());
+        assert!("glicko".parse::<RatingSystem>().is_err());
+    }
+
+    #[test]
+    fn test_trueskill_shrinks_sigma_after_a_game() {
+        use crate::ratings::{self, RatingSystem};
+
+        let initial = RatingSystem::TrueSkill.initial();
+        let mut table = HashMap::new();
+        ratings::update(RatingSystem::TrueSkill, &mut table, &[(1, 1), (2, 2), (3, 3)]);
+        assert!(table[&1].mu > initial.mu);
+        assert!(table[&3].mu < initial.mu);
+        assert!((table[&2].mu - initial.mu).abs() < 1e-9);
+        for r in table.values() {
+            assert!(r.sigma < initial.sigma);
+        }
+        assert!(table[&1].score() > table[&2].score() && table[&2].score() > table[&3].score());
+
+        // A single seat is not a game
+        let mut table = HashMap::new();
+        ratings::update(RatingSystem::TrueSkill, &mut table, &[(1, 1)]);
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn test_recompute_ratings_replays_every_game_deterministically() {
+        use crate::ratings::{self, RatingSystem};
+
+        let db = fixture_db();
+        for system in [RatingSystem::Elo, RatingSystem::TrueSkill] {
+            assert_eq!(db.recompute_ratings(system), 3);
+            let first = db.rating_leaderboard(system);
+            assert_eq!(db.recompute_ratings(system), 3);
+            assert_eq!(db.rating_leaderboard(system), first);
+
+            // Same as folding the stored games by hand; the fixture's extra
+            // ELO adjustment is discarded
+            let mut table = HashMap::new();
+            for seats in db.rated_games() {
+                ratings::update(system, &mut table, &seats);
+            }
+            assert_eq!(first.len(), 3);
+            for (name, rating, games, _) in &first {
+                assert_eq!(*rating, table[&db.register_agent(name)]);
+                assert_eq!(*games, 3);
+            }
+        }
+        // Elo is zero-sum
+        let elo = db.rating_leaderboard(RatingSystem::Elo);
+        let total: f64 = elo.iter().map(|(_, r, _, _)| r.mu).sum();
+        assert!((total - 4500.0).abs() < 1e-9);
+        assert_eq!(elo[0].0, "mcts-a");
+    }
+}
@@ -0,0 +1,57 @@
+// ═══════════════════════════════════════════════════════════════════════
+// Training — fit learning agents on exported datasets
+//
+// Behaviour cloning: a `PolicyModel` is fitted to the (observation,
+// action) pairs of an export made with `got-runner dataset`, usually
+// filtered to a single agent (`--agent heuristic`), and scored on the
+// held-out validation games of the same export.
+// ═══════════════════════════════════════════════════════════════════════
+
+use crate::dataset::{load_split, LoadedSplit};
+use got_agents::cloned::{Example, FitOptions, PolicyModel};
+use got_engine::encoding::DecisionKind;
+use std::path::Path;
+
+/// Per-kind (correct, total) predictions.
+pub type Accuracy = Vec<(DecisionKind, usize, usize)>;
+
+#[derive(Debug, Clone)]
+pub struct CloneReport {
+    pub train_samples: usize,
+    pub val_samples: usize,
+    pub train_accuracy: Accuracy,
+    pub val_accuracy: Accuracy,
+}
+
+/// Overall share of correct predictions.
+pub fn overall(accuracy: &Accuracy) -> f64 {
+    let (correct, total) = accuracy.iter().fold((0, 0), |(c, t), &(_, ci, ti)| (c + ci, t + ti));
+    if total == 0 { 0.0 } else { correct as f64 / total as f64 }
+}
+
+fn examples(split: &LoadedSplit) -> Vec<Example<'_>> {
+    (0..split.len())
+        .map(|i| Example { kind: split.kind(i), obs: split.obs_row(i), action: split.action[i] as u32 })
+        .collect()
+}
+
+/// Fit a cloning model on `<data_dir>/train.*` and score it on `val.*`.
+pub fn train_clone(data_dir: &Path, opts: &FitOptions) -> Result<(PolicyModel, CloneReport), String> {
+    let train = load_split(data_dir, "train")?;
+    if train.is_empty() {
+        return Err(format!("Training split in {} is empty", data_dir.display()));
+    }
+    // A missing validation split only loses the held-out score.
+    let val = load_split(data_dir, "val").unwrap_or_default();
+
+    let train_examples = examples(&train);
+    let val_examples = examples(&val);
+    let model = PolicyModel::fit(&train_examples, opts);
+    let report = CloneReport {
+        train_samples: train.len(),
+        val_samples: val.len(),
+        train_accuracy: model.accuracy(&train_examples),
+        val_accuracy: model.accuracy(&val_examples),
+    };
+    Ok((model, report))
+}